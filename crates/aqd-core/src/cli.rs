@@ -1,6 +1,18 @@
 // SPDX-License-Identifier: Apache-2.0
 
-use clap::{Parser, Subcommand};
+use {
+    crate::{deploy::Deploy, doctor::Doctor, history::HistoryAction, run::RunPlaybook},
+    aqd_utils::{
+        parse_burst, parse_proxy, parse_rate_limit, parse_rpc_header, parse_timeout, CassetteMode,
+        ProgressFormat, RpcHeader, TableStyle, Theme,
+    },
+    clap::{ArgAction, Parser, Subcommand},
+    std::{path::PathBuf, time::Duration},
+    url::Url,
+};
+
+#[cfg(any(feature = "polkadot", feature = "solana"))]
+use crate::{convert::ConvertAction, wallet::WalletAction};
 
 #[cfg(feature = "solana")]
 use aqd_solana::SolanaAction;
@@ -8,13 +20,141 @@ use aqd_solana::SolanaAction;
 #[cfg(feature = "polkadot")]
 use aqd_polkadot::PolkadotAction;
 
+#[cfg(feature = "soroban")]
+use aqd_soroban::SorobanAction;
+
+#[cfg(feature = "evm")]
+use aqd_evm::EvmAction;
+
 #[derive(Parser)]
 #[command(  author = env!("CARGO_PKG_AUTHORS"), 
-            about = "Aqd is a versatile CLI tool for interacting with contracts on Solana and Polkadot blockchains.", 
+            about = "Aqd is a versatile CLI tool for interacting with contracts on Solana, Polkadot, Soroban and EVM blockchains.", 
             subcommand_required = true)]
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+    #[clap(
+        short,
+        long,
+        global = true,
+        action = ArgAction::Count,
+        help = "Specifies the verbosity of the logs printed to stderr: -v for the main steps, -vv for the RPC requests and the encoded data, -vvv for everything"
+    )]
+    pub verbose: u8,
+    #[clap(
+        short,
+        long,
+        global = true,
+        env = "AQD_QUIET",
+        help = "Specifies whether to print only the essential result of the command (a signature, a program ID, a contract address, ...), without titles, warnings, spinners and colors"
+    )]
+    pub quiet: bool,
+    #[clap(
+        long,
+        global = true,
+        default_value = "default",
+        env = "AQD_THEME",
+        help = "Specifies the colors of the output: default, or the colors to change from the default theme, e.g. title=blue,key=magenta,warning=red"
+    )]
+    pub theme: Theme,
+    #[clap(
+        long,
+        global = true,
+        default_value = "plain",
+        env = "AQD_TABLE_STYLE",
+        help = "Specifies the style of the tables of --output table: plain, with the columns aligned with spaces, or unicode, with box-drawing borders"
+    )]
+    pub table_style: TableStyle,
+    #[clap(
+        long,
+        global = true,
+        default_value = "human",
+        env = "AQD_PROGRESS",
+        help = "Specifies the format of the progress of the transactions: human, or ndjson to print a line of JSON per stage (started, blockhash-fetched, signed, submitted, confirmed, finalized) to stderr, without spinners"
+    )]
+    pub progress: ProgressFormat,
+    #[clap(
+        long,
+        global = true,
+        env = "AQD_RECORD",
+        value_name = "CASSETTE",
+        conflicts_with = "replay",
+        help = "Specifies a cassette file to record the RPC requests of the command and their responses to, to replay them later with --replay"
+    )]
+    pub record: Option<PathBuf>,
+    #[clap(
+        long,
+        global = true,
+        env = "AQD_REPLAY",
+        value_name = "CASSETTE",
+        help = "Specifies a cassette file recorded with --record to replay the RPC responses from, without any network access"
+    )]
+    pub replay: Option<PathBuf>,
+    #[clap(
+        long,
+        global = true,
+        default_value = "30s",
+        env = "AQD_RPC_TIMEOUT",
+        value_parser = parse_timeout,
+        help = "Specifies the timeout of each RPC request to the Solana cluster or the Polkadot node, in seconds or with a unit (e.g. 500ms, 45s, 2m). On Solana, it includes the connection to the RPC server"
+    )]
+    pub rpc_timeout: Duration,
+    #[clap(
+        long,
+        global = true,
+        default_value = "10s",
+        env = "AQD_CONNECT_TIMEOUT",
+        value_parser = parse_timeout,
+        help = "Specifies the timeout of the connection to the websocket endpoint of the Polkadot node or the Solana cluster, in seconds or with a unit (e.g. 500ms, 45s, 2m)"
+    )]
+    pub connect_timeout: Duration,
+    #[clap(
+        long,
+        global = true,
+        env = "AQD_PROXY",
+        value_parser = parse_proxy,
+        help = "Specifies the HTTP (http://) or SOCKS5 (socks5://, socks5h://) proxy to route the Solana RPC and Polkadot websocket traffic through. Defaults to the proxy of the HTTPS_PROXY, HTTP_PROXY and ALL_PROXY environment variables, except for the hosts of NO_PROXY"
+    )]
+    pub proxy: Option<Url>,
+    #[clap(
+        long,
+        global = true,
+        env = "AQD_RPC_HEADER",
+        hide_env_values = true,
+        value_name = "NAME=VALUE",
+        value_parser = parse_rpc_header,
+        help = "Specifies a header to send with the RPC requests to the Solana cluster or the Polkadot node, such as the API key of a managed RPC provider. Can be repeated. Read the value from a file with NAME=@<path>, or from an environment variable with NAME=env:<VAR>"
+    )]
+    pub rpc_header: Vec<RpcHeader>,
+    #[clap(
+        long,
+        global = true,
+        env = "AQD_RATE_LIMIT",
+        value_name = "REQUESTS_PER_SECOND",
+        value_parser = parse_rate_limit,
+        help = "Specifies the maximum number of RPC requests per second to the Solana cluster or the Polkadot node, shared by all the requests of the command, such as the account scans and the parallel deployments. Defaults to no limit"
+    )]
+    pub rate_limit: Option<f64>,
+    #[clap(
+        long,
+        global = true,
+        env = "AQD_RATE_LIMIT_BURST",
+        requires = "rate_limit",
+        value_parser = parse_burst,
+        help = "Specifies the number of RPC requests that can be sent at once before --rate-limit applies. Defaults to the number of requests per second"
+    )]
+    pub rate_limit_burst: Option<u32>,
+}
+
+impl Cli {
+    /// The recording or replaying of the RPC requests, if any.
+    pub fn cassette(&self) -> Option<CassetteMode> {
+        match (&self.record, &self.replay) {
+            (Some(path), _) => Some(CassetteMode::Record(path.clone())),
+            (None, Some(path)) => Some(CassetteMode::Replay(path.clone())),
+            (None, None) => None,
+        }
+    }
 }
 
 #[allow(clippy::large_enum_variant)]
@@ -32,4 +172,40 @@ pub enum Commands {
         #[clap(subcommand)]
         action: PolkadotAction,
     },
+    #[cfg(feature = "soroban")]
+    #[command(about = "Interact with Soroban contracts on chain")]
+    Soroban {
+        #[clap(subcommand)]
+        action: SorobanAction,
+    },
+    #[cfg(feature = "evm")]
+    #[command(about = "Interact with EVM contracts on chain")]
+    Evm {
+        #[clap(subcommand)]
+        action: EvmAction,
+    },
+    Run(RunPlaybook),
+    Deploy(Deploy),
+    #[command(about = "List the deployments and calls submitted by aqd on all the chains")]
+    History {
+        #[clap(subcommand)]
+        action: HistoryAction,
+    },
+    Doctor(Doctor),
+    #[cfg(any(feature = "polkadot", feature = "solana"))]
+    #[command(
+        about = "Convert addresses between formats: SS58 and hex public keys, Solana public keys and keypairs"
+    )]
+    Convert {
+        #[clap(subcommand)]
+        action: ConvertAction,
+    },
+    #[cfg(any(feature = "polkadot", feature = "solana"))]
+    #[command(
+        about = "List, label and show the balances of the Solana and Polkadot keys aqd signs with"
+    )]
+    Wallet {
+        #[clap(subcommand)]
+        action: WalletAction,
+    },
 }
@@ -0,0 +1,40 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {anyhow::Result, clap::Subcommand};
+
+#[cfg(feature = "polkadot")]
+use aqd_polkadot::{PolkadotPublicKeyCommand, PolkadotSs58Command};
+
+#[cfg(feature = "solana")]
+use aqd_solana::{SolanaKeypairPubkey, SolanaPubkey};
+
+/// Available subcommands for the `convert` subcommand.
+#[derive(Debug, Subcommand)]
+pub enum ConvertAction {
+    #[cfg(feature = "polkadot")]
+    Ss58(PolkadotSs58Command),
+    #[cfg(feature = "polkadot")]
+    Hex(PolkadotPublicKeyCommand),
+    #[cfg(feature = "solana")]
+    Pubkey(SolanaPubkey),
+    #[cfg(feature = "solana")]
+    Keypair(SolanaKeypairPubkey),
+}
+
+impl ConvertAction {
+    /// Handle the conversion of an address, a public key or a keypair.
+    ///
+    /// The conversions are done offline, without connecting to any node.
+    pub fn handle(&self) -> Result<()> {
+        match self {
+            #[cfg(feature = "polkadot")]
+            ConvertAction::Ss58(ss58_args) => ss58_args.handle(),
+            #[cfg(feature = "polkadot")]
+            ConvertAction::Hex(hex_args) => hex_args.handle(),
+            #[cfg(feature = "solana")]
+            ConvertAction::Pubkey(pubkey_args) => pubkey_args.handle(),
+            #[cfg(feature = "solana")]
+            ConvertAction::Keypair(keypair_args) => keypair_args.handle(),
+        }
+    }
+}
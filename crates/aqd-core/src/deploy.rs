@@ -0,0 +1,174 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::run::run_step,
+    anyhow::{anyhow, Result},
+    aqd_utils::{
+        deployed_address, is_quiet, print_key_value, print_text, print_title,
+        prompt_confirm_transaction, OutputFormat, ReleaseManifest,
+    },
+    serde_json::{json, Map, Value},
+    std::{
+        env, fs,
+        path::{Path, PathBuf},
+    },
+};
+
+#[derive(Debug, clap::Args)]
+#[clap(
+    name = "deploy",
+    about = "Deploy the contracts of a release manifest on all their chains, and write their addresses to a file"
+)]
+pub struct Deploy {
+    #[clap(
+        long,
+        help = "Specifies the path of the release manifest TOML file listing the contracts to deploy"
+    )]
+    manifest: PathBuf,
+    #[clap(
+        long,
+        env = "AQD_ENV",
+        help = "Specifies the environment of the manifest to deploy to (e.g. staging)"
+    )]
+    env: Option<String>,
+    #[clap(
+        long,
+        help = "Specifies the path of the JSON file to write the addresses of the deployed contracts to. Defaults to addresses.<env>.json next to the manifest"
+    )]
+    addresses: Option<PathBuf>,
+    #[clap(
+        short('y'),
+        long,
+        env = "AQD_SKIP_CONFIRM",
+        help = "Specifies whether to skip the confirmation prompt."
+    )]
+    skip_confirm: bool,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
+}
+
+impl Deploy {
+    /// Handle the deployment of a release manifest.
+    ///
+    /// The contracts are deployed in the order of the manifest, each by its aqd command (`aqd
+    /// solana deploy` or `aqd polkadot instantiate`) run in the directory of the manifest, as the
+    /// steps of `aqd run` are. The plan is confirmed once for all the contracts. The addresses
+    /// file is written after each deployment, so that it lists the contracts already deployed if
+    /// a later one fails.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the manifest is invalid, if an artifact is missing, or if
+    /// a deployment fails. No contract is deployed after a failure.
+    pub fn handle(&self) -> Result<()> {
+        let manifest = ReleaseManifest::from_file(&self.manifest)?;
+        let steps = manifest.steps(self.env.as_deref())?;
+        let dir = match self.manifest.parent() {
+            Some(dir) if dir != Path::new("") => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        for contract in &manifest.contracts {
+            for path in [Some(&contract.artifact), contract.idl.as_ref()]
+                .into_iter()
+                .flatten()
+            {
+                if !dir.join(path).is_file() {
+                    return Err(anyhow!(
+                        "Artifact {} of contract {} not found",
+                        dir.join(path).display(),
+                        contract.name
+                    ));
+                }
+            }
+        }
+        let addresses_path = self.addresses.clone().unwrap_or_else(|| {
+            dir.join(match &self.env {
+                Some(env) => format!("addresses.{}.json", env),
+                None => "addresses.json".to_string(),
+            })
+        });
+        let aqd = env::current_exe()
+            .map_err(|e| anyhow!("Failed to locate the aqd executable: {}", e))?;
+
+        if !self.skip_confirm {
+            prompt_confirm_transaction(|| {
+                print_title!(format!(
+                    "Deployment of {}{}",
+                    self.manifest.display(),
+                    self.env
+                        .as_ref()
+                        .map_or(String::new(), |env| format!(" to {}", env))
+                ));
+                for step in &steps {
+                    print_key_value!(&step.name, format!("aqd {}", step.args.join(" ")));
+                }
+            })?;
+        }
+
+        let mut contracts = Map::new();
+        for (step, contract) in steps.iter().zip(&manifest.contracts) {
+            if !self.output.is_structured() && !is_quiet() {
+                print_title!(format!("Deploying {} on {}", step.name, step.chain));
+            }
+            let output = run_step(&aqd, &dir, &step.name, &step.args)
+                .map_err(|e| anyhow!("Contract {}: {}", step.name, e))?;
+            let address = deployed_address(&step.chain, &output).ok_or_else(|| {
+                anyhow!(
+                    "Contract {}: no address in the output of aqd {}",
+                    step.name,
+                    step.args.join(" ")
+                )
+            })?;
+            if !self.output.is_structured() && !is_quiet() {
+                print_key_value!("Address", &address);
+            }
+
+            let mut entry = json!({
+                "chain": step.chain,
+                "address": address,
+                "artifact": contract.artifact,
+            });
+            if let Some(idl) = &contract.idl {
+                entry["idl"] = json!(idl);
+            }
+            if let Some(code_hash) = output.get("code_hash").filter(|hash| !hash.is_null()) {
+                entry["code_hash"] = code_hash.clone();
+            }
+            contracts.insert(step.name.clone(), entry);
+            write_addresses(&addresses_path, self.env.as_deref(), &contracts)?;
+        }
+
+        let addresses = addresses_json(self.env.as_deref(), &contracts);
+        if self.output.is_structured() {
+            self.output.print(&addresses)?;
+        } else if is_quiet() {
+            print_text!(addresses_path.display());
+        } else {
+            print_title!(format!(
+                "Deployed the {} contracts, addresses written to {}",
+                contracts.len(),
+                addresses_path.display()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// The content of the addresses file: the environment and the deployed contracts, by name.
+fn addresses_json(env: Option<&str>, contracts: &Map<String, Value>) -> Value {
+    json!({
+        "environment": env,
+        "contracts": contracts,
+    })
+}
+
+/// Write the addresses of the deployed contracts to the addresses file.
+fn write_addresses(path: &Path, env: Option<&str>, contracts: &Map<String, Value>) -> Result<()> {
+    let content = serde_json::to_string_pretty(&addresses_json(env, contracts))?;
+    fs::write(path, content).map_err(|e| anyhow!("Failed to write {}: {}", path.display(), e))
+}
@@ -0,0 +1,62 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    aqd_utils::{general_diagnostics, print_diagnostics, CheckStatus, OutputFormat},
+};
+
+#[cfg(any(feature = "polkadot", feature = "evm"))]
+use tokio::runtime::Runtime;
+
+#[derive(Debug, clap::Args)]
+#[clap(
+    name = "doctor",
+    about = "Check the environment of aqd: the versions, the configuration files, the keypairs and the RPC endpoints of each chain"
+)]
+pub struct Doctor {
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
+}
+
+impl Doctor {
+    /// Handle the diagnostics of the environment.
+    ///
+    /// The checks of each chain resolve their settings as its commands do, from the same
+    /// configuration files and environment variables, so that the output of this command can be
+    /// pasted as is in a support request.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error, after printing all the checks, if any of them failed.
+    pub fn handle(&self) -> Result<()> {
+        #[allow(unused_mut)]
+        let mut diagnostics = general_diagnostics(env!("CARGO_PKG_VERSION"));
+        #[cfg(feature = "solana")]
+        diagnostics.extend(aqd_solana::diagnostics());
+        #[cfg(any(feature = "polkadot", feature = "evm"))]
+        {
+            let runtime = Runtime::new()?;
+            #[cfg(feature = "polkadot")]
+            diagnostics.extend(runtime.block_on(aqd_polkadot::diagnostics()));
+            #[cfg(feature = "evm")]
+            diagnostics.extend(runtime.block_on(aqd_evm::diagnostics()));
+        }
+        #[cfg(feature = "soroban")]
+        diagnostics.extend(aqd_soroban::diagnostics());
+
+        print_diagnostics(&diagnostics, self.output)?;
+        let failed = diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.status == CheckStatus::Error)
+            .count();
+        if failed > 0 {
+            return Err(anyhow!("{} of the checks failed", failed));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,162 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    aqd_utils::{print_text, History, HistoryEntry, HistoryFilter, HistoryOutcome, OutputFormat},
+    chrono::{DateTime, NaiveDate, TimeZone, Utc},
+    clap::Subcommand,
+};
+
+/// Available subcommands for the `history` subcommand.
+#[derive(Debug, Subcommand)]
+pub enum HistoryAction {
+    List(HistoryList),
+    Clear(HistoryClear),
+}
+
+#[derive(Clone, Debug, clap::Args)]
+#[clap(
+    name = "list",
+    about = "List the deployments and calls submitted by aqd, oldest first"
+)]
+pub struct HistoryList {
+    #[clap(
+        long,
+        help = "Specifies the chain of the transactions to list: solana, polkadot, soroban or evm"
+    )]
+    chain: Option<String>,
+    #[clap(
+        long,
+        help = "Specifies the action of the transactions to list: deploy, upload, instantiate or call"
+    )]
+    action: Option<String>,
+    #[clap(
+        long,
+        help = "Specifies the program ID or the contract address of the transactions to list"
+    )]
+    contract: Option<String>,
+    #[clap(
+        long,
+        help = "Specifies the instruction, message or function of the transactions to list"
+    )]
+    function: Option<String>,
+    #[clap(
+        long,
+        value_parser = parse_outcome,
+        help = "Specifies the outcome of the transactions to list: success or failure"
+    )]
+    outcome: Option<HistoryOutcome>,
+    #[clap(
+        long,
+        value_parser = parse_since,
+        help = "Specifies the date (YYYY-MM-DD, UTC) or the RFC 3339 time from which to list the transactions"
+    )]
+    since: Option<DateTime<Utc>>,
+    #[clap(
+        long,
+        help = "Specifies the maximum number of transactions to list, the latest ones"
+    )]
+    limit: Option<usize>,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
+}
+
+impl HistoryList {
+    /// Handle the listing of the history.
+    ///
+    /// This function reads the entries of the history matching the filters, keeps the latest ones
+    /// if `--limit` is set, and prints them in the output format, or a line per entry for humans.
+    pub fn handle(&self) -> Result<()> {
+        let filter = HistoryFilter {
+            chain: self.chain.clone(),
+            action: self.action.clone(),
+            contract: self.contract.clone(),
+            function: self.function.clone(),
+            outcome: self.outcome,
+            since: self.since,
+        };
+        let mut entries = History::open()?.entries(&filter)?;
+        if let Some(limit) = self.limit {
+            entries.drain(..entries.len().saturating_sub(limit));
+        }
+
+        if self.output.is_structured() {
+            return self.output.print(&entries);
+        }
+        if entries.is_empty() {
+            print_text!("No transactions recorded");
+            return Ok(());
+        }
+        for entry in &entries {
+            print_text!(human_line(entry));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, clap::Args)]
+#[clap(
+    name = "clear",
+    about = "Remove all the transactions recorded in the history"
+)]
+pub struct HistoryClear {}
+
+impl HistoryClear {
+    /// Handle the removal of the history file.
+    pub fn handle(&self) -> Result<()> {
+        let history = History::open()?;
+        history.clear()?;
+        print_text!(format!("Removed {}", history.path().display()));
+        Ok(())
+    }
+}
+
+/// Format an entry on a line for humans, e.g.
+/// `2024-01-01 12:00:00 UTC solana call <program_id>.flip success <signature>`, with the error in
+/// place of the hash of the failed transactions.
+fn human_line(entry: &HistoryEntry) -> String {
+    let target = match (&entry.contract, &entry.function) {
+        (Some(contract), Some(function)) => format!("{}.{}", contract, function),
+        (Some(contract), None) => contract.clone(),
+        (None, Some(function)) => function.clone(),
+        (None, None) => "-".to_string(),
+    };
+    let (outcome, detail) = match entry.outcome {
+        HistoryOutcome::Success => ("success", entry.hash.as_deref().unwrap_or("-")),
+        HistoryOutcome::Failure => ("failure", entry.error.as_deref().unwrap_or("-")),
+    };
+    format!(
+        "{} {} {} {} {} {}",
+        entry.timestamp.format("%Y-%m-%d %H:%M:%S UTC"),
+        entry.chain,
+        entry.action,
+        target,
+        outcome,
+        detail
+    )
+}
+
+fn parse_outcome(outcome: &str) -> Result<HistoryOutcome> {
+    match outcome {
+        "success" => Ok(HistoryOutcome::Success),
+        "failure" => Ok(HistoryOutcome::Failure),
+        _ => Err(anyhow!(
+            "Invalid outcome: {}. Expected `success` or `failure`",
+            outcome
+        )),
+    }
+}
+
+fn parse_since(since: &str) -> Result<DateTime<Utc>> {
+    if let Ok(date) = NaiveDate::parse_from_str(since, "%Y-%m-%d") {
+        return Ok(Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).unwrap()));
+    }
+    DateTime::parse_from_rfc3339(since)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|_| anyhow!("Invalid time: {}. Expected YYYY-MM-DD or RFC 3339", since))
+}
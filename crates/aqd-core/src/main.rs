@@ -1,75 +1,182 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod cli;
+#[cfg(any(feature = "polkadot", feature = "solana"))]
+mod convert;
+mod deploy;
+mod doctor;
+mod history;
+mod run;
+#[cfg(any(feature = "polkadot", feature = "solana"))]
+mod wallet;
 use {
-    crate::cli::{Cli, Commands::*},
-    clap::{CommandFactory, FromArgMatches},
-    std::process::exit,
+    crate::{
+        cli::{Cli, Commands::*},
+        history::HistoryAction,
+    },
+    aqd_utils::{HumanReporter, JsonReporter, OutputFormat, QuietReporter, Reporter},
+    clap::{ArgMatches, CommandFactory, FromArgMatches},
+    std::{io::stderr, sync::Arc},
+    tracing::Level,
 };
 
+#[cfg(any(feature = "polkadot", feature = "solana"))]
+use crate::wallet::WalletAction;
+
 #[cfg(feature = "solana")]
 use aqd_solana::SolanaAction;
 
 #[cfg(feature = "polkadot")]
-use {aqd_polkadot::PolkadotAction, tokio::runtime::Runtime};
+use aqd_polkadot::PolkadotAction;
+
+#[cfg(any(feature = "polkadot", feature = "evm"))]
+use tokio::runtime::Runtime;
+
+#[cfg(feature = "soroban")]
+use aqd_soroban::SorobanAction;
+
+#[cfg(feature = "evm")]
+use aqd_evm::EvmAction;
 
 /// The main entry point for `aqd` command-line application.
 fn main() {
     // Parse command-line arguments.
     let matches = Cli::command().get_matches();
     let cli = Cli::from_arg_matches(&matches).unwrap();
+    aqd_utils::set_quiet(cli.quiet);
+    // The reports of the commands printing JSON are written as lines of JSON too
+    let reporter: Arc<dyn Reporter> = match (cli.quiet, output_format(&matches)) {
+        (true, _) => Arc::new(QuietReporter),
+        (false, OutputFormat::Json) => Arc::new(JsonReporter),
+        (false, _) => Arc::new(HumanReporter::new(cli.theme)),
+    };
+    aqd_utils::set_reporter(reporter);
+    aqd_utils::set_progress(cli.progress);
+    aqd_utils::set_table_style(cli.table_style);
+    aqd_utils::set_timeouts(cli.rpc_timeout, cli.connect_timeout);
+    aqd_utils::set_proxy(cli.proxy.clone());
+    aqd_utils::set_rpc_headers(cli.rpc_header.clone());
+    aqd_utils::set_rate_limit(cli.rate_limit, cli.rate_limit_burst);
+    init_logging(cli.verbose, cli.quiet);
+    if let Err(err) = aqd_utils::set_cassette(cli.cassette()) {
+        aqd_utils::exit_with_error(&err, output_format(&matches));
+    }
+    if let Err(err) = aqd_utils::install_interrupt_handler() {
+        tracing::warn!("{}", err);
+    }
 
-    #[cfg(feature = "polkadot")]
+    #[cfg(any(feature = "polkadot", feature = "evm"))]
     let runtime = Runtime::new().expect("Failed to create Tokio runtime");
 
-    match cli.command {
+    let result = match cli.command {
         #[cfg(feature = "solana")]
         Solana { action } => match action {
-            SolanaAction::Deploy(deploy_args) => {
-                if let Err(err) = deploy_args.handle() {
-                    eprintln!("{}", err);
-                    exit(1);
-                }
-            }
-            SolanaAction::Call(call_args) => {
-                if let Err(err) = call_args.handle() {
-                    eprintln!("{}", err);
-                    exit(1);
-                }
+            SolanaAction::Deploy(deploy_args) => deploy_args.handle(),
+            SolanaAction::Call(call_args) => call_args.handle(),
+            SolanaAction::Bench(bench_args) => bench_args.handle(),
+            SolanaAction::Show(show_args) => show_args.handle(),
+            SolanaAction::AccountsList(accounts_list_args) => accounts_list_args.handle(),
+            SolanaAction::Inspect(inspect_args) => inspect_args.handle(),
+            SolanaAction::SquadsApprove(squads_approve_args) => squads_approve_args.handle(),
+            SolanaAction::SquadsExecute(squads_execute_args) => squads_execute_args.handle(),
+            SolanaAction::SignMessage(sign_message_args) => sign_message_args.handle(),
+            SolanaAction::VerifyMessage(verify_message_args) => verify_message_args.handle(),
+            SolanaAction::Keygen(keygen_args) => keygen_args.handle(),
+        },
+        #[cfg(feature = "polkadot")]
+        Polkadot { action } => runtime.block_on(async {
+            match action {
+                PolkadotAction::Upload(upload_args) => upload_args.handle().await,
+                PolkadotAction::Instantiate(instantiate_args) => instantiate_args.handle().await,
+                PolkadotAction::Call(call_args) => call_args.handle().await,
+                PolkadotAction::Bench(bench_args) => bench_args.handle().await,
+                PolkadotAction::Query(query_args) => query_args.handle().await,
+                PolkadotAction::Batch(batch_args) => batch_args.handle().await,
+                PolkadotAction::Submit(submit_args) => submit_args.handle().await,
+                PolkadotAction::MultisigApprove(approve_args) => approve_args.handle().await,
+                PolkadotAction::MultisigExecute(execute_args) => execute_args.handle().await,
+                PolkadotAction::Remove(remove_args) => remove_args.handle().await,
+                PolkadotAction::Storage(storage_args) => storage_args.handle().await,
+                PolkadotAction::Info(info_args) => info_args.handle().await,
+                PolkadotAction::Balance(balance_args) => balance_args.handle().await,
+                PolkadotAction::Show(show_args) => show_args.handle().await,
+                PolkadotAction::Watch(watch_args) => watch_args.handle().await,
+                PolkadotAction::Events(events_args) => events_args.handle().await,
+                PolkadotAction::Networks(networks_args) => networks_args.handle().await,
+                PolkadotAction::Keystore(keystore_args) => keystore_args.handle().await,
             }
-            SolanaAction::Show(show_args) => {
-                if let Err(err) = show_args.handle() {
-                    eprintln!("{}", err);
-                    exit(1);
-                }
+        }),
+        #[cfg(feature = "soroban")]
+        Soroban { action } => match action {
+            SorobanAction::Deploy(deploy_args) => deploy_args.handle(),
+            SorobanAction::Call(call_args) => call_args.handle(),
+            SorobanAction::Show(show_args) => show_args.handle(),
+        },
+        #[cfg(feature = "evm")]
+        Evm { action } => runtime.block_on(async {
+            match action {
+                EvmAction::Deploy(deploy_args) => deploy_args.handle().await,
+                EvmAction::Call(call_args) => call_args.handle().await,
+                EvmAction::Show(show_args) => show_args.handle().await,
             }
+        }),
+        Run(run_args) => run_args.handle(),
+        Deploy(deploy_args) => deploy_args.handle(),
+        History { action } => match action {
+            HistoryAction::List(list_args) => list_args.handle(),
+            HistoryAction::Clear(clear_args) => clear_args.handle(),
         },
-        #[cfg(feature = "polkadot")]
-        Polkadot { action } => match action {
-            PolkadotAction::Upload(upload_args) => runtime.block_on(async {
-                if let Err(err) = upload_args.handle().await {
-                    eprintln!("{}", err);
-                    exit(1);
-                }
-            }),
-            PolkadotAction::Instantiate(instantiate_args) => runtime.block_on(async {
-                if let Err(err) = instantiate_args.handle().await {
-                    eprintln!("{}", err);
-                    exit(1);
-                }
-            }),
-            PolkadotAction::Call(call_args) => runtime.block_on(async {
-                if let Err(err) = call_args.handle().await {
-                    eprintln!("{}", err);
-                    exit(1);
-                }
-            }),
-            PolkadotAction::Remove(remove_args) => runtime.block_on(async {
-                if let Err(err) = remove_args.handle().await {
-                    eprintln!("{}", err);
-                    exit(1);
-                }
-            }),
+        Doctor(doctor_args) => doctor_args.handle(),
+        #[cfg(any(feature = "polkadot", feature = "solana"))]
+        Convert { action } => action.handle(),
+        #[cfg(any(feature = "polkadot", feature = "solana"))]
+        Wallet { action } => match action {
+            WalletAction::List(list_args) => list_args.handle(),
+            WalletAction::Label(label_args) => label_args.handle(),
+            WalletAction::Show(show_args) => show_args.handle(),
         },
+    };
+
+    // The cassette is saved even if the command failed, to replay its failure too
+    let result = result.and(aqd_utils::save_cassette());
+    if let Err(err) = result {
+        // Remove the keypairs generated for a transaction that was not sent, or keep them if it was
+        aqd_utils::cleanup_generated_files();
+        aqd_utils::exit_with_error(&err, output_format(&matches));
+    }
+}
+
+/// The output format selected for the subcommand, to report its errors in the same format.
+///
+/// The format is an option of the innermost subcommand, e.g. `aqd solana deploy` or `aqd run`.
+fn output_format(matches: &ArgMatches) -> OutputFormat {
+    let mut matches = matches;
+    while let Some((_, subcommand_matches)) = matches.subcommand() {
+        matches = subcommand_matches;
     }
+    matches
+        .try_get_one::<OutputFormat>("output")
+        .ok()
+        .flatten()
+        .copied()
+        .unwrap_or_default()
+}
+
+/// Print the logs of all the crates to stderr, at a level given by the number of `-v` flags.
+///
+/// Without any `-v` flag, only the warnings are printed, such as a node failing over to the next
+/// URL, and only the errors in quiet mode.
+fn init_logging(verbose: u8, quiet: bool) {
+    let level = match verbose {
+        0 if quiet => Level::ERROR,
+        0 => Level::WARN,
+        1 => Level::INFO,
+        2 => Level::DEBUG,
+        _ => Level::TRACE,
+    };
+    tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_writer(|| aqd_utils::RedactingWriter(stderr()))
+        .with_target(verbose > 1)
+        .init();
 }
@@ -0,0 +1,240 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    aqd_utils::{
+        cassette_mode, connect_timeout, is_quiet, parse_jobs, print_key_value, print_title,
+        print_value, progress_format, rate_limit, rpc_timeout, OutputFormat, Playbook,
+        PlaybookContext, AQD_RECORD_ENV, AQD_REPLAY_ENV,
+    },
+    serde_json::{Map, Value},
+    std::{
+        env,
+        path::{Path, PathBuf},
+        process::{Command, Stdio},
+        sync::mpsc,
+        thread,
+    },
+};
+
+/// The chains whose transactions from an account take consecutive nonces (or sequence numbers),
+/// so that their steps are run one at a time, even with several jobs. Solana transactions only
+/// depend on a recent blockhash, so Solana steps run concurrently.
+const NONCE_CHAINS: [&str; 3] = ["polkadot", "soroban", "evm"];
+
+#[derive(Clone, Debug, clap::Args)]
+#[clap(
+    name = "run",
+    about = "Run the steps of a playbook: aqd commands on any chain, using the outputs of the previous steps and checking their own"
+)]
+pub struct RunPlaybook {
+    #[clap(help = "Specifies the path of the playbook YAML file")]
+    playbook: PathBuf,
+    #[clap(
+        short,
+        long,
+        default_value = "1",
+        value_parser = parse_jobs,
+        help = "Specifies the maximum number of steps run concurrently. Steps only wait for the steps they use the outputs of, or list in `after`"
+    )]
+    jobs: usize,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
+}
+
+impl RunPlaybook {
+    /// Handle the run of a playbook.
+    ///
+    /// Each step runs its aqd command in the directory of the playbook, with the JSON output and
+    /// without confirmation prompts. The placeholders of its arguments are replaced by the
+    /// variables and the outputs of the steps it depends on, then its output is checked against
+    /// the expected values. The steps run in order with a single job. With several jobs, a step
+    /// starts as soon as its dependencies are done, except that the steps of a chain with nonces
+    /// run one at a time. No step starts after a failure. The outputs of all the steps are printed
+    /// at the end in the structured output formats.
+    ///
+    /// With `--record` or `--replay`, each step records to, or replays from, its own cassette (see
+    /// [`aqd_utils::CassetteMode::for_step`]).
+    pub fn handle(&self) -> Result<()> {
+        let playbook = Playbook::from_file(&self.playbook)?;
+        let names = playbook.step_names();
+        let dependencies = playbook.dependencies()?;
+        let dir = match self.playbook.parent() {
+            Some(dir) if dir != Path::new("") => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        let aqd = env::current_exe()
+            .map_err(|e| anyhow!("Failed to locate the aqd executable: {}", e))?;
+
+        let mut context = PlaybookContext::new(&playbook);
+        let mut outputs: Vec<Option<Value>> = vec![None; playbook.steps.len()];
+        let mut started = vec![false; playbook.steps.len()];
+        let mut busy_chains: Vec<&str> = vec![];
+        let mut running = 0;
+        let mut failure: Option<anyhow::Error> = None;
+        let (sender, receiver) = mpsc::channel();
+
+        thread::scope(|scope| {
+            loop {
+                // Start the steps whose dependencies are done, up to the number of jobs
+                for (i, step) in playbook.steps.iter().enumerate() {
+                    if failure.is_some() || running >= self.jobs {
+                        break;
+                    }
+                    if started[i] || dependencies[i].iter().any(|&j| outputs[j].is_none()) {
+                        continue;
+                    }
+                    let args = match step.run.args().and_then(|args| {
+                        args.iter()
+                            .map(|arg| context.substitute(arg))
+                            .collect::<Result<Vec<_>>>()
+                    }) {
+                        Ok(args) => args,
+                        Err(e) => {
+                            failure = Some(anyhow!("Step {}: {}", names[i], e));
+                            break;
+                        }
+                    };
+                    let chain = nonce_chain(&args);
+                    if chain.is_some_and(|chain| busy_chains.contains(&chain)) {
+                        continue;
+                    }
+
+                    if !self.output.is_structured() {
+                        print_title!(format!("Step {}: aqd {}", names[i], args.join(" ")));
+                    }
+                    started[i] = true;
+                    running += 1;
+                    busy_chains.extend(chain);
+                    let (aqd, dir, name, sender) = (&aqd, &dir, &names[i], sender.clone());
+                    scope.spawn(move || {
+                        let result = run_step(aqd, dir, name, &args);
+                        sender.send((i, chain, result)).unwrap();
+                    });
+                }
+                if running == 0 {
+                    break;
+                }
+
+                // Wait for a step to finish, and check its output
+                let (i, chain, result) = receiver.recv().unwrap();
+                running -= 1;
+                busy_chains.retain(|busy| Some(*busy) != chain);
+                let step = &playbook.steps[i];
+                let result = result.and_then(|output| {
+                    context.check_expectations(&output, &step.expect)?;
+                    Ok(output)
+                });
+                match result {
+                    Ok(output) => {
+                        if !self.output.is_structured() && !is_quiet() {
+                            if self.jobs > 1 {
+                                print_title!(format!("Step {} done", names[i]));
+                            }
+                            if !output.is_null() {
+                                print_value!(output);
+                            }
+                            for path in step.expect.keys() {
+                                print_key_value!("Checked", path);
+                            }
+                        }
+                        context.insert(&names[i], output.clone());
+                        outputs[i] = Some(output);
+                    }
+                    Err(e) => {
+                        failure.get_or_insert(anyhow!("Step {}: {}", names[i], e));
+                    }
+                }
+            }
+        });
+
+        if let Some(failure) = failure {
+            return Err(failure);
+        }
+        if self.output.is_structured() {
+            let outputs: Map<String, Value> = names
+                .into_iter()
+                .zip(outputs)
+                .map(|(name, output)| (name, output.unwrap_or_default()))
+                .collect();
+            self.output.print(&outputs)?;
+        } else if !is_quiet() {
+            print_title!(format!(
+                "Ran the {} steps of the playbook",
+                playbook.steps.len()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// The chain of a step, if its transactions take nonces, from the first argument of its command.
+fn nonce_chain(args: &[String]) -> Option<&'static str> {
+    let chain = args.first()?;
+    NONCE_CHAINS
+        .iter()
+        .find(|nonce_chain| **nonce_chain == chain.as_str())
+        .copied()
+}
+
+/// Run the aqd command of a step and return its output.
+///
+/// The output of commands without a JSON output is kept as a string. The progress events of the
+/// command, if enabled, are printed on the inherited stderr. The command inherits the network
+/// timeouts and the rate limit of the RPC requests.
+pub(crate) fn run_step(aqd: &Path, dir: &Path, name: &str, args: &[String]) -> Result<Value> {
+    let mut command = Command::new(aqd);
+    command
+        .env_remove(AQD_RECORD_ENV)
+        .env_remove(AQD_REPLAY_ENV);
+    if let Some(cassette) = cassette_mode() {
+        let cassette = cassette.for_step(name);
+        command.env(cassette.env_var(), cassette.path());
+    }
+    if let Some((rate, burst)) = rate_limit() {
+        command
+            .env("AQD_RATE_LIMIT", rate.to_string())
+            .env("AQD_RATE_LIMIT_BURST", burst.to_string());
+    }
+    let result = command
+        .args(args)
+        .current_dir(dir)
+        .env("AQD_OUTPUT", "json")
+        .env("AQD_SKIP_CONFIRM", "true")
+        .env("AQD_PROGRESS", progress_format().to_string())
+        .env(
+            "AQD_RPC_TIMEOUT",
+            format!("{}ms", rpc_timeout().as_millis()),
+        )
+        .env(
+            "AQD_CONNECT_TIMEOUT",
+            format!("{}ms", connect_timeout().as_millis()),
+        )
+        .env_remove("AQD_QUIET")
+        .stdin(Stdio::null())
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|e| anyhow!("Failed to run aqd: {}", e))?;
+    if !result.status.success() {
+        return Err(anyhow!(
+            "Failed with exit code {}",
+            result
+                .status
+                .code()
+                .map_or("unknown".to_string(), |code| code.to_string())
+        ));
+    }
+
+    let stdout = String::from_utf8_lossy(&result.stdout);
+    Ok(match stdout.trim() {
+        "" => Value::Null,
+        stdout => {
+            serde_json::from_str(stdout).unwrap_or_else(|_| Value::String(stdout.to_string()))
+        }
+    })
+}
@@ -0,0 +1,324 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    aqd_utils::{
+        is_quiet, print_key_value, print_text, print_title, AqdConfig, OutputFormat, WalletConfig,
+        WalletKey,
+    },
+    clap::Subcommand,
+    std::{fs, path::Path},
+};
+
+#[cfg(feature = "polkadot")]
+use tokio::runtime::Runtime;
+
+/// Available subcommands for the `wallet` subcommand.
+#[derive(Debug, Subcommand)]
+pub enum WalletAction {
+    List(WalletList),
+    Label(WalletLabel),
+    Show(WalletShow),
+}
+
+#[derive(Clone, Debug, clap::Args)]
+#[clap(
+    name = "list",
+    about = "List the wallets labeled in the aqd configuration file and the keys the commands sign with by default"
+)]
+pub struct WalletList {
+    #[clap(
+        long,
+        help = "Specifies the chain of the wallets to list: solana or polkadot"
+    )]
+    chain: Option<String>,
+    #[clap(
+        long,
+        help = "Specifies whether to query the balance of each wallet, on the cluster or node of the commands of its chain"
+    )]
+    balances: bool,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
+}
+
+impl WalletList {
+    /// Handle the listing of the wallets.
+    ///
+    /// The wallets labeled in the aqd configuration file are listed first, followed by the keys
+    /// the commands of each chain sign with by default, unless they are labeled. Querying the
+    /// balances never fails the command: the error of a wallet is reported with it.
+    pub fn handle(&self) -> Result<()> {
+        let mut wallets = wallets()?;
+        if let Some(chain) = &self.chain {
+            wallets.retain(|wallet| &wallet.chain == chain);
+        }
+        if self.balances {
+            wallets.iter_mut().for_each(query_balance);
+        }
+
+        if self.output.is_structured() {
+            return self.output.print(&wallets);
+        }
+        if is_quiet() {
+            for address in wallets.iter().filter_map(|wallet| wallet.address.as_ref()) {
+                print_text!(address);
+            }
+            return Ok(());
+        }
+        if wallets.is_empty() {
+            print_text!("No wallets labeled or configured");
+            return Ok(());
+        }
+        wallets.iter().for_each(print_wallet);
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, clap::Args)]
+#[clap(
+    name = "label",
+    about = "Label a Solana or Polkadot key in the aqd configuration file, to list it and show its balance by name"
+)]
+pub struct WalletLabel {
+    #[clap(value_parser, help = "Specifies the label of the wallet")]
+    name: String,
+    #[clap(long, help = "Specifies the chain of the wallet: solana or polkadot")]
+    chain: String,
+    #[clap(
+        long,
+        help = "Specifies the address of the wallet: a Solana public key, or a Polkadot SS58 address or hex public key. Defaults to the address of --path"
+    )]
+    address: Option<String>,
+    #[clap(
+        long,
+        help = "Specifies the file the secret key of the wallet is read from: a Solana keypair file or keystore, or a Polkadot secret key URI file or keystore. Defaults to the key the commands of the chain sign with"
+    )]
+    path: Option<String>,
+}
+
+impl WalletLabel {
+    /// Handle the labeling of a wallet.
+    ///
+    /// Only the address of the key and the path of its file are written to the aqd configuration
+    /// file, never the secret key. The address is read from the file unless `--address` is given,
+    /// prompting for the password of a Polkadot keystore. Without `--path` nor `--address`, the
+    /// key the commands of the chain sign with by default is labeled.
+    pub fn handle(&self) -> Result<()> {
+        let (address, path) = match (&self.address, &self.path) {
+            (Some(address), path) => (parse_address(&self.chain, address)?, path.clone()),
+            (None, Some(path)) => (wallet_address(&self.chain, path)?, Some(path.clone())),
+            (None, None) => {
+                let wallet = default_wallets()
+                    .into_iter()
+                    .find(|wallet| wallet.chain == self.chain)
+                    .ok_or_else(|| {
+                        anyhow!("No default {} key: use --path or --address", self.chain)
+                    })?;
+                let path = wallet.source.filter(|source| Path::new(source).is_file());
+                match (wallet.address, &path) {
+                    (Some(address), _) => (address, path),
+                    (None, Some(path)) => (wallet_address(&self.chain, path)?, Some(path.clone())),
+                    (None, None) => {
+                        return Err(anyhow!(
+                            "Failed to read the default {} key: {}",
+                            self.chain,
+                            wallet.error.unwrap_or_default()
+                        ))
+                    }
+                }
+            }
+        };
+        // The configuration file is shared by all the directories
+        let path = path.map(|path| match fs::canonicalize(&path) {
+            Ok(canonical) => canonical.display().to_string(),
+            Err(_) => path,
+        });
+
+        let config_path = AqdConfig::save_wallet(&WalletConfig {
+            name: self.name.clone(),
+            chain: self.chain.clone(),
+            address: address.clone(),
+            path,
+        })?;
+        match is_quiet() {
+            true => print_text!(address),
+            false => print_text!(format!(
+                "Labeled {} as {} in {}",
+                address,
+                self.name,
+                config_path.display()
+            )),
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, clap::Args)]
+#[clap(name = "show", about = "Show a labeled wallet and its balance")]
+pub struct WalletShow {
+    #[clap(value_parser, help = "Specifies the label of the wallet to show")]
+    name: String,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
+}
+
+impl WalletShow {
+    /// Handle the showing of a wallet, with its balance on the cluster or node of the commands of
+    /// its chain.
+    pub fn handle(&self) -> Result<()> {
+        let mut wallet = wallets()?
+            .into_iter()
+            .find(|wallet| wallet.name.as_deref() == Some(self.name.as_str()))
+            .ok_or_else(|| {
+                anyhow!(
+                    "Unknown wallet {}. Run `aqd wallet list` to list the wallets",
+                    self.name
+                )
+            })?;
+        query_balance(&mut wallet);
+
+        if self.output.is_structured() {
+            return self.output.print(&wallet);
+        }
+        if is_quiet() {
+            let balance = wallet
+                .balance
+                .ok_or_else(|| anyhow!("{}", wallet.error.unwrap_or_default()))?;
+            print_text!(balance);
+            return Ok(());
+        }
+        print_wallet(&wallet);
+        Ok(())
+    }
+}
+
+/// The wallets labeled in the aqd configuration file, followed by the keys the commands of each
+/// chain sign with by default, unless they are labeled: a labeled wallet with the address or the
+/// file of a default key is marked as the default instead.
+fn wallets() -> Result<Vec<WalletKey>> {
+    let mut wallets: Vec<WalletKey> = AqdConfig::load()?
+        .wallets
+        .into_iter()
+        .map(|wallet| WalletKey {
+            name: Some(wallet.name),
+            chain: wallet.chain,
+            address: Some(wallet.address),
+            source: wallet.path,
+            ..Default::default()
+        })
+        .collect();
+    for default in default_wallets() {
+        let labeled = wallets.iter_mut().find(|wallet| {
+            wallet.chain == default.chain
+                && ((wallet.address.is_some() && wallet.address == default.address)
+                    || same_file(wallet.source.as_deref(), default.source.as_deref()))
+        });
+        match labeled {
+            Some(wallet) => wallet.default = true,
+            None => wallets.push(default),
+        }
+    }
+    Ok(wallets)
+}
+
+/// Check whether two sources of keys are the same file.
+fn same_file(a: Option<&str>, b: Option<&str>) -> bool {
+    match (a.map(fs::canonicalize), b.map(fs::canonicalize)) {
+        (Some(Ok(a)), Some(Ok(b))) => a == b,
+        _ => false,
+    }
+}
+
+/// The keys the commands of each chain sign with by default.
+fn default_wallets() -> Vec<WalletKey> {
+    // Each chain enabled adds its keys to the chain of sources
+    let wallets = std::iter::empty();
+    #[cfg(feature = "solana")]
+    let wallets = wallets.chain([aqd_solana::default_wallet()]);
+    #[cfg(feature = "polkadot")]
+    let wallets = wallets.chain(aqd_polkadot::default_wallet());
+    wallets.collect()
+}
+
+/// Query the balance of a wallet, recording the error in the wallet if it fails.
+fn query_balance(wallet: &mut WalletKey) {
+    let address = match &wallet.address {
+        Some(address) => address.clone(),
+        None => return,
+    };
+    match wallet_balance(&wallet.chain, &address) {
+        Ok(balance) => wallet.balance = Some(balance),
+        Err(e) => wallet.error = Some(format!("{:#}", e)),
+    }
+}
+
+/// Validate the address of a wallet of a chain.
+fn parse_address(chain: &str, address: &str) -> Result<String> {
+    match chain {
+        #[cfg(feature = "solana")]
+        "solana" => aqd_solana::parse_address(address),
+        #[cfg(feature = "polkadot")]
+        "polkadot" => aqd_polkadot::parse_address(address),
+        _ => Err(unsupported_chain(chain)),
+    }
+}
+
+/// Read the address of the key of a file of a chain.
+fn wallet_address(chain: &str, path: &str) -> Result<String> {
+    match chain {
+        #[cfg(feature = "solana")]
+        "solana" => aqd_solana::wallet_address(path),
+        #[cfg(feature = "polkadot")]
+        "polkadot" => aqd_polkadot::wallet_address(path),
+        _ => Err(unsupported_chain(chain)),
+    }
+}
+
+/// Query the balance of an address of a chain.
+fn wallet_balance(chain: &str, address: &str) -> Result<String> {
+    match chain {
+        #[cfg(feature = "solana")]
+        "solana" => aqd_solana::wallet_balance(address),
+        #[cfg(feature = "polkadot")]
+        "polkadot" => Runtime::new()?.block_on(aqd_polkadot::wallet_balance(address)),
+        _ => Err(unsupported_chain(chain)),
+    }
+}
+
+/// The error of a chain without wallets, or not enabled in this build of aqd.
+fn unsupported_chain(chain: &str) -> anyhow::Error {
+    anyhow!(
+        "Unsupported chain for wallets: {}. Expected solana or polkadot, enabled in this build of aqd",
+        chain
+    )
+}
+
+/// Print a wallet for humans.
+fn print_wallet(wallet: &WalletKey) {
+    let title = match (&wallet.name, wallet.default) {
+        (Some(name), true) => format!("{} ({}, default)", name, wallet.chain),
+        (Some(name), false) => format!("{} ({})", name, wallet.chain),
+        (None, _) => format!("Default {} key", wallet.chain),
+    };
+    print_title!(title);
+    print_key_value!("Address", wallet.address.as_deref().unwrap_or("unknown"));
+    if let Some(source) = &wallet.source {
+        print_key_value!("Source", source);
+    }
+    if let Some(balance) = &wallet.balance {
+        print_key_value!("Balance", balance);
+    }
+    if let Some(error) = &wallet.error {
+        print_key_value!("Error", error);
+    }
+}
@@ -0,0 +1,106 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    ethers::{
+        abi::{
+            token::{LenientTokenizer, Tokenizer},
+            Abi, Function, Param, StateMutability, Token,
+        },
+        types::{Bytes, I256},
+    },
+    serde_json::{json, Value},
+    std::{fs, path::Path},
+    tracing::trace,
+};
+
+/// Load the ABI of a contract from its JSON file, as generated by the Solang compiler (`.abi`)
+/// or solc.
+pub fn load_abi<P: AsRef<Path>>(path: P) -> Result<Abi> {
+    let path = path.as_ref();
+    let content = fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read ABI file {}: {}", path.display(), e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| anyhow!("Failed to parse ABI file {}: {}", path.display(), e))
+}
+
+/// Load the bytecode of a contract from its file, as generated by the Solang compiler (`.bin`).
+///
+/// The file can contain the hex encoded bytecode, with or without a `0x` prefix, or the raw
+/// bytecode.
+pub fn load_bytecode<P: AsRef<Path>>(path: P) -> Result<Bytes> {
+    let path = path.as_ref();
+    let content = fs::read(path)
+        .map_err(|e| anyhow!("Failed to read bytecode file {}: {}", path.display(), e))?;
+    match std::str::from_utf8(&content) {
+        Ok(hex_content) => {
+            let hex_content = hex_content.trim();
+            hex::decode(hex_content.trim_start_matches("0x"))
+                .map(Bytes::from)
+                .map_err(|e| anyhow!("Invalid hex bytecode in {}: {}", path.display(), e))
+        }
+        Err(_) => Ok(Bytes::from(content)),
+    }
+}
+
+/// Encode the arguments of a function or a constructor, given as strings, into ABI tokens.
+///
+/// Each argument is parsed according to the type of the corresponding parameter: integers are
+/// decimal or hex numbers, addresses and bytes are hex strings, and arrays and tuples are written
+/// as in Solidity, e.g. `[1,2,3]` or `(1,true)`.
+pub fn encode_args(params: &[Param], args: &[String]) -> Result<Vec<Token>> {
+    if params.len() != args.len() {
+        return Err(anyhow!(
+            "Expected {} arguments, got {}",
+            params.len(),
+            args.len()
+        ));
+    }
+
+    params
+        .iter()
+        .zip(args)
+        .map(|(param, arg)| {
+            trace!(param = %param.name, kind = %param.kind, arg, "Encoding argument");
+            LenientTokenizer::tokenize(&param.kind, arg)
+                .map_err(|e| anyhow!("Invalid argument {} ({}): {}", param.name, param.kind, e))
+        })
+        .collect()
+}
+
+/// Find a function of the ABI by name.
+///
+/// Overloaded functions are not supported: the first function with the name is returned.
+pub fn find_function<'a>(abi: &'a Abi, name: &str) -> Result<&'a Function> {
+    abi.function(name)
+        .map_err(|_| anyhow!("Function {} not found in the ABI", name))
+}
+
+/// Whether a function does not modify the state of the contract, so that it can be called
+/// without a transaction.
+pub fn is_read_only(function: &Function) -> bool {
+    matches!(
+        function.state_mutability,
+        StateMutability::View | StateMutability::Pure
+    )
+}
+
+/// Convert an ABI token into JSON, to print the results of the contract calls.
+///
+/// Integers are converted into decimal strings, as they may not fit in JSON numbers. Addresses
+/// and bytes are converted into `0x` prefixed hex strings.
+pub fn token_to_json(token: &Token) -> Value {
+    match token {
+        Token::Address(address) => json!(format!("{:#x}", address)),
+        Token::FixedBytes(bytes) | Token::Bytes(bytes) => {
+            json!(format!("0x{}", hex::encode(bytes)))
+        }
+        Token::Int(value) => json!(I256::from_raw(*value).to_string()),
+        Token::Uint(value) => json!(value.to_string()),
+        Token::Bool(value) => json!(value),
+        Token::String(value) => json!(value),
+        Token::FixedArray(tokens) | Token::Array(tokens) | Token::Tuple(tokens) => {
+            Value::Array(tokens.iter().map(token_to_json).collect())
+        }
+    }
+}
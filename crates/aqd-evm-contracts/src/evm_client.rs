@@ -0,0 +1,226 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::{
+        abi::{encode_args, find_function, is_read_only},
+        rpc_client::CassetteClient,
+    },
+    anyhow::{anyhow, Result},
+    aqd_utils::{emit_progress, register_secret, ErrorKind, ProgressEvent, ProgressStage},
+    ethers::{
+        abi::{Abi, Token},
+        contract::ContractFactory,
+        middleware::SignerMiddleware,
+        providers::{Middleware, Provider},
+        signers::{LocalWallet, Signer},
+        types::{
+            transaction::eip2718::TypedTransaction, Address, Bytes, TransactionReceipt,
+            TransactionRequest,
+        },
+    },
+    std::{fs, path::Path, sync::Arc, time::Duration},
+    tracing::{debug, info},
+};
+
+/// The interval between the polls of the replayed transactions.
+const REPLAY_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The result of the deployment of an EVM contract.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvmDeployment {
+    /// The address of the deployed contract.
+    pub address: Address,
+    /// The hash of the deployment transaction.
+    pub transaction_hash: String,
+}
+
+/// The result of an EVM contract call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvmCallResult {
+    /// The hash of the transaction, or `None` if the function was called without a transaction.
+    pub transaction_hash: Option<String>,
+    /// The decoded return values of the function.
+    pub return_values: Vec<Token>,
+}
+
+/// Parse the private key of an EVM account.
+///
+/// The key is the hex encoded 32 bytes secret key, with or without a `0x` prefix, or the path of
+/// a file containing it. The key is registered as a secret to be redacted from the output.
+pub fn parse_private_key(source: &str) -> Result<LocalWallet> {
+    let key = if Path::new(source).is_file() {
+        fs::read_to_string(source)
+            .map_err(|e| anyhow!("Failed to read private key file {}: {}", source, e))?
+    } else {
+        source.to_string()
+    };
+    let key = key.trim().trim_start_matches("0x");
+    register_secret(key);
+    key.parse::<LocalWallet>()
+        .map_err(|_| anyhow!("Not a valid hex private key or private key file"))
+}
+
+/// A client of an EVM JSON-RPC node, with an optional signer of the transactions.
+pub struct EvmClient {
+    provider: Provider<CassetteClient>,
+    wallet: Option<LocalWallet>,
+}
+
+impl EvmClient {
+    /// Connect to the JSON-RPC node at `url`.
+    ///
+    /// The chain ID of the node is used to sign the transactions of the wallet, if any. Without a
+    /// wallet, only read-only calls are possible.
+    pub async fn connect(url: &str, wallet: Option<LocalWallet>) -> Result<Self> {
+        let client =
+            CassetteClient::new(url).map_err(|e| anyhow!("Invalid JSON-RPC URL {}: {}", url, e))?;
+        // The replayed transactions need not be polled at the pace of the blocks
+        let provider = match client.is_replaying() {
+            true => Provider::new(client).interval(REPLAY_POLL_INTERVAL),
+            false => Provider::new(client),
+        };
+        let chain_id = provider
+            .get_chainid()
+            .await
+            .map_err(|e| ErrorKind::Rpc.wrap(anyhow!("Failed to connect to {}: {}", url, e)))?;
+        debug!(url, %chain_id, "Connected to the EVM node");
+
+        let wallet = wallet.map(|wallet| wallet.with_chain_id(chain_id.as_u64()));
+        Ok(EvmClient { provider, wallet })
+    }
+
+    /// The signer middleware of the wallet, to submit transactions.
+    fn signer(&self) -> Result<Arc<SignerMiddleware<Provider<CassetteClient>, LocalWallet>>> {
+        let wallet = self
+            .wallet
+            .clone()
+            .ok_or_else(|| anyhow!("A private key is needed to submit transactions"))?;
+        Ok(Arc::new(SignerMiddleware::new(
+            self.provider.clone(),
+            wallet,
+        )))
+    }
+
+    /// Deploy a contract, with the arguments of its constructor given as strings.
+    pub async fn deploy(
+        &self,
+        abi: Abi,
+        bytecode: Bytes,
+        args: &[String],
+    ) -> Result<EvmDeployment> {
+        let args = match abi.constructor() {
+            Some(constructor) => encode_args(&constructor.inputs, args)?,
+            None => encode_args(&[], args)?,
+        };
+
+        let factory = ContractFactory::new(abi, bytecode, self.signer()?);
+        let deployer = factory
+            .deploy_tokens(args)
+            .map_err(|e| anyhow!("Failed to encode the deployment: {}", e))?;
+        info!("Deploying the contract");
+        let receipt = self.send_and_confirm(deployer.tx).await?;
+        let address = receipt.contract_address.ok_or_else(|| {
+            anyhow!(
+                "Transaction {:#x} did not deploy a contract",
+                receipt.transaction_hash
+            )
+        })?;
+
+        Ok(EvmDeployment {
+            address,
+            transaction_hash: format!("{:#x}", receipt.transaction_hash),
+        })
+    }
+
+    /// Call a function of a contract, with its arguments given as strings.
+    ///
+    /// Read-only (`view` and `pure`) functions, and all the functions if `simulate` is set, are
+    /// called with `eth_call`, without a transaction. The other functions are called with a
+    /// transaction signed by the wallet, and their return values are not available.
+    pub async fn call(
+        &self,
+        abi: &Abi,
+        address: Address,
+        function: &str,
+        args: &[String],
+        simulate: bool,
+    ) -> Result<EvmCallResult> {
+        let function = find_function(abi, function)?;
+        let data = function
+            .encode_input(&encode_args(&function.inputs, args)?)
+            .map_err(|e| anyhow!("Failed to encode the call: {}", e))?;
+        debug!(data = %hex::encode(&data), "Encoded the call data");
+
+        let mut request = TransactionRequest::new().to(address).data(data);
+
+        if simulate || is_read_only(function) {
+            if let Some(wallet) = &self.wallet {
+                request = request.from(wallet.address());
+            }
+            let output = self
+                .provider
+                .call(&TypedTransaction::Legacy(request), None)
+                .await
+                .map_err(|e| {
+                    ErrorKind::SimulationRevert.wrap(anyhow!(
+                        "Call of {} failed: {}",
+                        function.name,
+                        e
+                    ))
+                })?;
+            let return_values = function
+                .decode_output(&output)
+                .map_err(|e| anyhow!("Failed to decode the return value: {}", e))?;
+            return Ok(EvmCallResult {
+                transaction_hash: None,
+                return_values,
+            });
+        }
+
+        info!(function = %function.name, %address, "Submitting the transaction");
+        let receipt = self.send_and_confirm(request.into()).await?;
+
+        Ok(EvmCallResult {
+            transaction_hash: Some(format!("{:#x}", receipt.transaction_hash)),
+            return_values: vec![],
+        })
+    }
+
+    /// Sign a transaction with the wallet, submit it, and wait for its receipt, reporting its
+    /// stages as progress events.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the transaction cannot be submitted, if it is dropped
+    /// from the mempool, or if it reverts.
+    async fn send_and_confirm(&self, transaction: TypedTransaction) -> Result<TransactionReceipt> {
+        emit_progress(ProgressEvent::new("evm", ProgressStage::Started));
+        let signer = self.signer()?;
+        let pending = signer
+            .send_transaction(transaction, None)
+            .await
+            .map_err(|e| anyhow!("Failed to submit the transaction: {}", e))?;
+        emit_progress(
+            ProgressEvent::new("evm", ProgressStage::Submitted)
+                .hash(format!("{:#x}", pending.tx_hash())),
+        );
+        let receipt = pending
+            .await
+            .map_err(|e| anyhow!("Failed to confirm the transaction: {}", e))?
+            .ok_or_else(|| anyhow!("The transaction was dropped from the mempool"))?;
+        if receipt.status.map(|status| status.as_u64()) == Some(0) {
+            return Err(anyhow!(
+                "Transaction {:#x} reverted",
+                receipt.transaction_hash
+            ));
+        }
+
+        let mut event = ProgressEvent::new("evm", ProgressStage::Confirmed)
+            .hash(format!("{:#x}", receipt.transaction_hash));
+        if let Some(block) = receipt.block_number {
+            event = event.block(block);
+        }
+        emit_progress(event);
+        Ok(receipt)
+    }
+}
@@ -0,0 +1,13 @@
+// SPDX-License-Identifier: Apache-2.0
+
+mod abi;
+mod evm_client;
+mod printing_utils;
+mod rpc_client;
+
+pub use {
+    abi::{encode_args, find_function, is_read_only, load_abi, load_bytecode, token_to_json},
+    evm_client::{parse_private_key, EvmCallResult, EvmClient, EvmDeployment},
+    printing_utils::{print_abi_functions, print_call_result},
+    rpc_client::{CassetteClient, CassetteClientError},
+};
@@ -0,0 +1,105 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::{abi::token_to_json, evm_client::EvmCallResult},
+    anyhow::{anyhow, Result},
+    aqd_utils::{print_key_value, print_subtitle, print_title, print_value, OutputFormat},
+    ethers::abi::{Abi, Function},
+    serde_json::{json, Value},
+};
+
+/// Prints information about the functions of an EVM contract ABI.
+///
+/// If a function name is provided, only the functions with that name are printed. Otherwise,
+/// all the functions of the ABI are printed. The information includes the function name, state
+/// mutability, inputs and outputs. The output format can be human-readable, JSON, YAML or a table.
+///
+/// # Arguments
+///
+/// * `abi`: The [`Abi`] of the contract.
+/// * `function_name`: An optional name of the function to print details for.
+/// * `output`: The [`OutputFormat`] of the information.
+pub fn print_abi_functions(
+    abi: &Abi,
+    function_name: Option<String>,
+    output: OutputFormat,
+) -> Result<()> {
+    let functions: Vec<&Function> = match function_name {
+        Some(function_name) => abi
+            .functions_by_name(&function_name)
+            .map_err(|_| anyhow!("Function {} not found in the ABI", function_name))?
+            .iter()
+            .collect(),
+        None => abi.functions().collect(),
+    };
+
+    if output.is_structured() {
+        // Print a single document with all the functions
+        let functions: Vec<Value> = functions.into_iter().map(function_to_json).collect();
+        return output.print(&functions);
+    }
+
+    for function in functions {
+        print_title!("Function name");
+        print_value!(function.name);
+
+        print_title!("State mutability");
+        print_value!(format!("{:?}", function.state_mutability).to_lowercase());
+
+        print_title!("Inputs");
+        if function.inputs.is_empty() {
+            print_value!("No inputs");
+        }
+        for (i, input) in function.inputs.iter().enumerate() {
+            print_subtitle!(format!("Input {}", i + 1));
+            print_key_value!("Name: ", input.name);
+            print_key_value!("Type: ", input.kind);
+        }
+
+        print_title!("Outputs");
+        if function.outputs.is_empty() {
+            print_value!("No outputs");
+        }
+        for output in function.outputs.iter() {
+            print_value!(output.kind);
+        }
+    }
+
+    Ok(())
+}
+
+/// Convert a function of the ABI into JSON.
+fn function_to_json(function: &Function) -> Value {
+    json!({
+        "name": function.name,
+        "state_mutability": format!("{:?}", function.state_mutability).to_lowercase(),
+        "inputs": function.inputs.iter().map(|input| json!({
+            "name": input.name,
+            "type": input.kind.to_string(),
+        })).collect::<Vec<_>>(),
+        "outputs": function.outputs.iter().map(|output| output.kind.to_string()).collect::<Vec<_>>(),
+    })
+}
+
+/// Prints the result of a contract function call: the transaction hash, or the return values of
+/// a call made without a transaction.
+pub fn print_call_result(result: &EvmCallResult, output: OutputFormat) -> Result<()> {
+    let return_values: Vec<Value> = result.return_values.iter().map(token_to_json).collect();
+
+    if output.is_structured() {
+        return output.print(&json!({
+            "transaction": result.transaction_hash,
+            "return_values": return_values,
+        }));
+    }
+
+    print_title!("Result");
+    if let Some(transaction_hash) = &result.transaction_hash {
+        print_key_value!("Transaction: ", transaction_hash);
+    }
+    for return_value in return_values {
+        print_key_value!("Return value: ", return_value);
+    }
+
+    Ok(())
+}
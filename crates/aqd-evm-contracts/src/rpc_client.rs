@@ -0,0 +1,142 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    aqd_utils::{cassette_mode, is_replaying, record_interaction, replay_interaction, Interaction},
+    async_trait::async_trait,
+    ethers::providers::{
+        Http, HttpClientError, JsonRpcClient, JsonRpcError, ProviderError, RpcError,
+    },
+    serde::{de::DeserializeOwned, Serialize},
+    serde_json::Value,
+    std::{fmt, str::FromStr},
+};
+
+/// The chain of the interactions of the EVM JSON-RPC requests in a cassette.
+const CHAIN: &str = "evm";
+
+/// A client of an EVM JSON-RPC node over HTTP.
+///
+/// With `--record`, the requests and their responses are recorded to the cassette, and with
+/// `--replay`, the responses are replayed from the cassette without connecting to the node.
+#[derive(Clone, Debug)]
+pub struct CassetteClient {
+    http: Http,
+    replay: bool,
+}
+
+impl CassetteClient {
+    /// Create a client of the JSON-RPC node at `url`.
+    pub fn new(url: &str) -> Result<Self, <Http as FromStr>::Err> {
+        Ok(CassetteClient {
+            http: Http::from_str(url)?,
+            replay: is_replaying(),
+        })
+    }
+
+    /// Whether the responses are replayed from the cassette.
+    pub fn is_replaying(&self) -> bool {
+        self.replay
+    }
+}
+
+/// The errors of the [`CassetteClient`].
+#[derive(Debug)]
+pub enum CassetteClientError {
+    /// The error of the HTTP request, or of the node
+    Http(HttpClientError),
+    /// A replayed error of the node
+    JsonRpc(JsonRpcError),
+    /// The parameters or the result of the request cannot be converted
+    Serde(serde_json::Error),
+    /// A replayed error that is not an error of the node, or a missing recorded response
+    Replay(String),
+}
+
+impl fmt::Display for CassetteClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CassetteClientError::Http(e) => write!(f, "{}", e),
+            CassetteClientError::JsonRpc(e) => write!(f, "{}", e),
+            CassetteClientError::Serde(e) => write!(f, "{}", e),
+            CassetteClientError::Replay(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for CassetteClientError {}
+
+impl RpcError for CassetteClientError {
+    fn as_error_response(&self) -> Option<&JsonRpcError> {
+        match self {
+            CassetteClientError::Http(e) => e.as_error_response(),
+            CassetteClientError::JsonRpc(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    fn as_serde_error(&self) -> Option<&serde_json::Error> {
+        match self {
+            CassetteClientError::Http(e) => e.as_serde_error(),
+            CassetteClientError::Serde(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<CassetteClientError> for ProviderError {
+    fn from(e: CassetteClientError) -> Self {
+        ProviderError::JsonRpcClientError(Box::new(e))
+    }
+}
+
+#[async_trait]
+impl JsonRpcClient for CassetteClient {
+    type Error = CassetteClientError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: fmt::Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let params = serde_json::to_value(params).map_err(CassetteClientError::Serde)?;
+        let result = if self.replay {
+            let interaction = replay_interaction(CHAIN, method, &params)
+                .map_err(|e| CassetteClientError::Replay(e.to_string()))?;
+            match &interaction.error {
+                // The errors of the node are replayed as such, with their revert data
+                Some(error) => Err(match serde_json::from_value(error.clone()) {
+                    Ok(error) => CassetteClientError::JsonRpc(error),
+                    Err(_) => {
+                        CassetteClientError::Replay(interaction.response().unwrap_err().to_string())
+                    }
+                }),
+                None => Ok(interaction.result.unwrap_or(Value::Null)),
+            }
+        } else {
+            let result = self
+                .http
+                .request::<_, Value>(method, &params)
+                .await
+                .map_err(CassetteClientError::Http);
+            if cassette_mode().is_some() {
+                let response = match &result {
+                    Ok(result) => Ok(result.clone()),
+                    Err(e) => Err(e
+                        .as_error_response()
+                        .map(|error| {
+                            // The error of the node is not serializable, so it is recorded field by field
+                            serde_json::json!({
+                                "code": error.code,
+                                "message": error.message,
+                                "data": error.data,
+                            })
+                        })
+                        .unwrap_or_else(|| Value::String(e.to_string()))),
+                };
+                record_interaction(Interaction::new(CHAIN, method, params, response));
+            }
+            result
+        }?;
+        serde_json::from_value(result).map_err(CassetteClientError::Serde)
+    }
+}
@@ -0,0 +1,96 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::Result,
+    aqd_evm_contracts::{
+        encode_args, find_function, is_read_only, load_abi, load_bytecode, parse_private_key,
+        token_to_json,
+    },
+    ethers::{
+        abi::Token,
+        signers::Signer,
+        types::{Address, U256},
+    },
+    serde_json::json,
+    std::fs,
+};
+
+const ABI: &str = r#"[
+    {"type": "constructor", "inputs": [{"name": "initvalue", "type": "bool"}], "stateMutability": "nonpayable"},
+    {"type": "function", "name": "flip", "inputs": [], "outputs": [], "stateMutability": "nonpayable"},
+    {"type": "function", "name": "get", "inputs": [], "outputs": [{"name": "", "type": "bool"}], "stateMutability": "view"},
+    {"type": "function", "name": "set", "inputs": [{"name": "owner", "type": "address"}, {"name": "values", "type": "int64[]"}], "outputs": [], "stateMutability": "nonpayable"}
+]"#;
+
+// The first development account of anvil and hardhat
+const PRIVATE_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+const ADDRESS: &str = "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266";
+
+/// Purpose: This test checks that the ABI and the hex bytecode are loaded from the files generated by the compiler.
+#[test]
+pub fn test_load_abi_and_bytecode() -> Result<()> {
+    let dir = std::env::temp_dir();
+    let abi_path = dir.join("aqd-test-flipper.abi");
+    let bin_path = dir.join("aqd-test-flipper.bin");
+    fs::write(&abi_path, ABI)?;
+    fs::write(&bin_path, "0x6080\n")?;
+
+    let abi = load_abi(&abi_path)?;
+    assert!(abi.constructor().is_some());
+    assert!(is_read_only(find_function(&abi, "get")?));
+    assert!(!is_read_only(find_function(&abi, "flip")?));
+    assert!(find_function(&abi, "missing").is_err());
+
+    assert_eq!(load_bytecode(&bin_path)?.to_vec(), vec![0x60, 0x80]);
+
+    fs::remove_file(&abi_path)?;
+    fs::remove_file(&bin_path)?;
+    Ok(())
+}
+
+/// Purpose: This test checks that the arguments are encoded according to the types of the ABI parameters.
+#[test]
+pub fn test_encode_args() -> Result<()> {
+    let abi = serde_json::from_str::<ethers::abi::Abi>(ABI)?;
+    let set = find_function(&abi, "set")?;
+
+    let tokens = encode_args(&set.inputs, &[ADDRESS.to_string(), "[1,-2,3]".to_string()])?;
+    assert_eq!(tokens[0], Token::Address(ADDRESS.parse::<Address>()?));
+    assert_eq!(token_to_json(&tokens[1]), json!(["1", "-2", "3"]));
+
+    // Wrong number of arguments, and values that do not match the types
+    assert!(encode_args(&set.inputs, &[ADDRESS.to_string()]).is_err());
+    assert!(encode_args(&set.inputs, &["0x12".to_string(), "[]".to_string()]).is_err());
+
+    Ok(())
+}
+
+/// Purpose: This test checks that the return values of the calls are converted into JSON.
+#[test]
+pub fn test_token_to_json() -> Result<()> {
+    assert_eq!(token_to_json(&Token::Bool(true)), json!(true));
+    assert_eq!(token_to_json(&Token::Uint(U256::from(42))), json!("42"));
+    assert_eq!(token_to_json(&Token::Int(U256::MAX)), json!("-1"));
+    assert_eq!(
+        token_to_json(&Token::Bytes(vec![0xca, 0xfe])),
+        json!("0xcafe")
+    );
+    assert_eq!(
+        token_to_json(&Token::Address(ADDRESS.parse::<Address>()?)),
+        json!(ADDRESS)
+    );
+    Ok(())
+}
+
+/// Purpose: This test checks that private keys are parsed with or without a 0x prefix.
+#[test]
+pub fn test_parse_private_key() -> Result<()> {
+    let wallet = parse_private_key(PRIVATE_KEY)?;
+    assert_eq!(format!("{:#x}", wallet.address()), ADDRESS);
+    assert_eq!(
+        parse_private_key(PRIVATE_KEY.trim_start_matches("0x"))?.address(),
+        wallet.address()
+    );
+    assert!(parse_private_key(ADDRESS).is_err());
+    Ok(())
+}
@@ -0,0 +1,102 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::commands::CLIEvmOpts,
+    anyhow::Result,
+    std::{path::PathBuf, process::exit},
+};
+use {
+    aqd_evm_contracts::{find_function, is_read_only, load_abi, print_call_result},
+    aqd_utils::{
+        check_target_match, is_quiet, print_text, record_history, ErrorKind, HistoryEntry,
+        OutputFormat,
+    },
+};
+
+#[derive(Clone, Debug, clap::Args)]
+#[clap(name = "call", about = "Call a function of an EVM contract")]
+pub struct EvmCall {
+    #[clap(long, help = "Specifies the path of the ABI JSON file of the contract")]
+    abi: PathBuf,
+    #[clap(long, help = "Specifies the address of the deployed contract")]
+    contract: String,
+    #[clap(long, help = "Specifies the name of the function to call")]
+    function: String,
+    #[clap(
+        long,
+        help = "Specifies the arguments to pass to the function.
+                For arrays and tuples, use the Solidity syntax. (e.g. [1,2,3] or (1,true))",
+        // The number of arguments is variable (Can be 0 or more)
+        num_args = 0..,
+    )]
+    args: Vec<String>,
+    #[clap(
+        long,
+        help = "Specifies whether to call the function without a transaction, to get its return values.
+                view and pure functions are always called without a transaction"
+    )]
+    simulate: bool,
+    #[clap(flatten)]
+    evm_opts: CLIEvmOpts,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
+}
+
+impl EvmCall {
+    /// Handle the EVM call command.
+    ///
+    /// This function checks that the command is run in the correct directory, loads the ABI,
+    /// encodes the arguments according to the inputs of the function, and calls it. Read-only
+    /// functions, and all functions if `--simulate` is set, are called without a transaction and
+    /// their return values are printed. Otherwise, the hash of the transaction is printed.
+    pub async fn handle(&self) -> Result<()> {
+        // Make sure the command is run in the correct directory
+        // Fails if the command is run in a Solang project directory of another target
+        let target_match = check_target_match("evm", None)
+            .map_err(|e| anyhow::anyhow!("Failed to check current directory: {}", e))?;
+        if !target_match {
+            exit(ErrorKind::Usage.exit_code());
+        }
+
+        // Parse command-line arguments
+        let abi = load_abi(&self.abi)?;
+        let contract = self
+            .contract
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Invalid contract address {}: {}", self.contract, e))?;
+
+        // Call the function
+        let client = self.evm_opts.connect().await?;
+        let result = client
+            .call(&abi, contract, &self.function, &self.args, self.simulate)
+            .await;
+
+        // Record the transactions in the history, but not the calls made without a transaction
+        let read_only = self.simulate || is_read_only(find_function(&abi, &self.function)?);
+        if !read_only {
+            let entry = HistoryEntry::new("evm", "call")
+                .contract(&self.contract)
+                .function(&self.function);
+            record_history(match &result {
+                Ok(result) => match &result.transaction_hash {
+                    Some(transaction_hash) => entry.hash(transaction_hash),
+                    None => entry,
+                },
+                Err(e) => entry.failed(e),
+            });
+        }
+        let result = result?;
+
+        // Print the transaction hash alone in quiet mode, or the result of the call.
+        if let (true, Some(transaction_hash)) = (is_quiet(), &result.transaction_hash) {
+            print_text!(transaction_hash);
+            return Ok(());
+        }
+        print_call_result(&result, self.output)
+    }
+}
@@ -0,0 +1,97 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::commands::CLIEvmOpts,
+    anyhow::Result,
+    serde_json::json,
+    std::{path::PathBuf, process::exit},
+};
+use {
+    aqd_evm_contracts::{load_abi, load_bytecode},
+    aqd_utils::{
+        check_target_match, is_quiet, print_key_value, print_text, record_history, ErrorKind,
+        HistoryEntry, OutputFormat,
+    },
+};
+
+#[derive(Clone, Debug, clap::Args)]
+#[clap(name = "deploy", about = "Deploy a contract to an EVM chain")]
+pub struct EvmDeploy {
+    #[clap(help = "Specifies the path to the bytecode file of the contract to deploy (.bin)")]
+    bytecode: PathBuf,
+    #[clap(
+        long,
+        help = "Specifies the path of the ABI JSON file of the contract (.abi)"
+    )]
+    abi: PathBuf,
+    #[clap(
+        long,
+        help = "Specifies the arguments of the constructor.
+                For arrays and tuples, use the Solidity syntax. (e.g. [1,2,3] or (1,true))",
+        // The number of arguments is variable (Can be 0 or more)
+        num_args = 0..,
+    )]
+    args: Vec<String>,
+    #[clap(flatten)]
+    evm_opts: CLIEvmOpts,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
+}
+
+impl EvmDeploy {
+    /// Handle the deployment of an EVM contract
+    ///
+    /// This function checks that the command is run in the correct directory, loads the bytecode
+    /// and the ABI of the contract, encodes the constructor arguments, deploys the contract with a
+    /// transaction signed by the private key, records it in the history, then prints the address of
+    /// the contract.
+    pub async fn handle(&self) -> Result<()> {
+        // Make sure the command is run in the correct directory
+        // Fails if the command is run in a Solang project directory of another target
+        let target_match = check_target_match("evm", None)
+            .map_err(|e| anyhow::anyhow!("Failed to check current directory: {}", e))?;
+        if !target_match {
+            exit(ErrorKind::Usage.exit_code());
+        }
+
+        // Load the contract
+        let bytecode = load_bytecode(&self.bytecode)?;
+        let abi = load_abi(&self.abi)?;
+        let output = self.output;
+
+        // Deploy the contract
+        let client = self.evm_opts.connect().await?;
+        let deployment = client.deploy(abi, bytecode, &self.args).await;
+        let entry = HistoryEntry::new("evm", "deploy");
+        record_history(match &deployment {
+            Ok(deployment) => entry
+                .hash(&deployment.transaction_hash)
+                .contract(format!("{:#x}", deployment.address)),
+            Err(e) => entry.failed(e),
+        });
+        let deployment = deployment?;
+
+        // If the output is quiet, print the contract address alone
+        // If the output is structured, print the deployment in the output format
+        // Else, print the contract address and the transaction hash as strings
+        let address = format!("{:#x}", deployment.address);
+        if is_quiet() {
+            print_text!(address);
+        } else if output.is_structured() {
+            output.print(&json!({
+                "address": address,
+                "transaction": deployment.transaction_hash,
+            }))?;
+        } else {
+            print_key_value!("Transaction", deployment.transaction_hash);
+            print_key_value!("Contract address", address);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,39 @@
+// SPDX-License-Identifier: Apache-2.0
+
+pub mod call;
+pub mod deploy;
+pub mod show;
+
+use {
+    anyhow::Result,
+    aqd_evm_contracts::{parse_private_key, EvmClient},
+};
+
+/// The options of the commands connecting to an EVM node.
+#[derive(Clone, Debug, clap::Args)]
+pub struct CLIEvmOpts {
+    #[clap(
+        long,
+        env = "AQD_EVM_URL",
+        help = "Specifies the URL of the JSON-RPC node of the EVM chain"
+    )]
+    url: String,
+    #[clap(
+        long,
+        env = "AQD_EVM_PRIVATE_KEY",
+        hide_env_values = true,
+        help = "Specifies the hex encoded private key, or the path of a file containing it, of the account that signs and pays for the transactions"
+    )]
+    private_key: Option<String>,
+}
+
+impl CLIEvmOpts {
+    /// Connect to the node, with the wallet of the private key if provided.
+    pub async fn connect(&self) -> Result<EvmClient> {
+        let wallet = match &self.private_key {
+            Some(private_key) => Some(parse_private_key(private_key)?),
+            None => None,
+        };
+        EvmClient::connect(&self.url, wallet).await
+    }
+}
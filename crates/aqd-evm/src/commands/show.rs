@@ -0,0 +1,55 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::Result,
+    std::{path::PathBuf, process::exit},
+};
+use {
+    aqd_evm_contracts::{load_abi, print_abi_functions},
+    aqd_utils::{check_target_match, ErrorKind, OutputFormat},
+};
+
+#[derive(Clone, Debug, clap::Args)]
+#[clap(
+    name = "show",
+    about = "Show information about an EVM contract's functions given its ABI JSON file"
+)]
+pub struct EvmShow {
+    #[clap(long, help = "Specifies the path of the ABI JSON file")]
+    abi: PathBuf,
+    #[clap(
+        long,
+        help = "Specifies the name of the function to show information about\n
+                If not specified, information about all functions is shown"
+    )]
+    function: Option<String>,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
+}
+
+impl EvmShow {
+    /// Handle the EVM show command.
+    ///
+    /// This function checks that the command is run in the correct directory, loads the ABI from
+    /// the JSON file, and prints information about its functions.
+    pub async fn handle(&self) -> Result<()> {
+        // Make sure the command is run in the correct directory
+        // Fails if the command is run in a Solang project directory of another target
+        let target_match = check_target_match("evm", None)
+            .map_err(|e| anyhow::anyhow!("Failed to check current directory: {}", e))?;
+        if !target_match {
+            exit(ErrorKind::Usage.exit_code());
+        }
+
+        // Get the ABI from the JSON file
+        let abi = load_abi(&self.abi)?;
+
+        // Print information about the functions
+        print_abi_functions(&abi, self.function.clone(), self.output)
+    }
+}
@@ -0,0 +1,48 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    aqd_evm_contracts::{parse_private_key, EvmClient},
+    aqd_utils::{CheckStatus, Diagnostic},
+    std::{env, time::Instant},
+};
+
+/// The section of the EVM checks.
+const SECTION: &str = "evm";
+
+/// The checks of the EVM environment: the JSON-RPC node of `AQD_EVM_URL` can be reached, and the
+/// private key of `AQD_EVM_PRIVATE_KEY` is valid.
+///
+/// The EVM commands have no default node, so nothing is checked without these variables.
+pub async fn diagnostics() -> Vec<Diagnostic> {
+    let rpc = match env::var("AQD_EVM_URL") {
+        Ok(url) => {
+            let start = Instant::now();
+            let client = EvmClient::connect(&url, None).await;
+            Diagnostic::from_result(
+                SECTION,
+                "rpc",
+                client.map(|_| format!("{} ({} ms)", url, start.elapsed().as_millis())),
+            )
+        }
+        Err(_) => Diagnostic::new(
+            SECTION,
+            "rpc",
+            CheckStatus::Info,
+            "AQD_EVM_URL is not set: use --url",
+        ),
+    };
+    let private_key = match env::var("AQD_EVM_PRIVATE_KEY") {
+        Ok(private_key) => Diagnostic::from_result(
+            SECTION,
+            "private key",
+            parse_private_key(&private_key).map(|_| "Valid private key".to_string()),
+        ),
+        Err(_) => Diagnostic::new(
+            SECTION,
+            "private key",
+            CheckStatus::Info,
+            "AQD_EVM_PRIVATE_KEY is not set: only read-only calls are possible",
+        ),
+    };
+    vec![rpc, private_key]
+}
@@ -0,0 +1,14 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::{EvmCall, EvmDeploy, EvmShow},
+    clap::Subcommand,
+};
+
+/// Available subcommands for the `evm` subcommand.
+#[derive(Debug, Subcommand)]
+pub enum EvmAction {
+    Deploy(EvmDeploy),
+    Call(EvmCall),
+    Show(EvmShow),
+}
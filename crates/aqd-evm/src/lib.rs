@@ -0,0 +1,9 @@
+// SPDX-License-Identifier: Apache-2.0
+
+mod commands;
+mod doctor;
+mod evm_action;
+
+pub use commands::{call::EvmCall, deploy::EvmDeploy, show::EvmShow};
+pub use doctor::diagnostics;
+pub use evm_action::EvmAction;
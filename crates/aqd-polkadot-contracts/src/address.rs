@@ -0,0 +1,94 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    serde::Serialize,
+    sp_core::crypto::{AccountId32, Ss58AddressFormat, Ss58AddressFormatRegistry, Ss58Codec},
+};
+
+/// The SS58 prefix of the generic Substrate addresses, used by the development chains.
+pub const SUBSTRATE_SS58_PREFIX: u16 = 42;
+
+/// A 32-byte public key, with its SS58 address for a network prefix.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Ss58Address {
+    /// The SS58 address
+    pub address: String,
+    /// The public key, hex encoded with a `0x` prefix
+    pub public_key: String,
+    /// The network prefix of the address
+    pub prefix: u16,
+    /// The name of the network of the prefix in the SS58 registry, if known
+    pub network: Option<String>,
+}
+
+impl Ss58Address {
+    /// The SS58 address of a public key for a network prefix.
+    pub fn new(public_key: [u8; 32], prefix: u16) -> Self {
+        let format = Ss58AddressFormat::custom(prefix);
+        Ss58Address {
+            address: AccountId32::from(public_key).to_ss58check_with_version(format),
+            public_key: format!("0x{}", hex::encode(public_key)),
+            prefix,
+            network: ss58_network(prefix),
+        }
+    }
+
+    /// Parse an SS58 address, keeping its network prefix.
+    pub fn parse(address: &str) -> Result<Self> {
+        let (public_key, prefix) = decode_ss58(address)?;
+        Ok(Ss58Address::new(public_key, prefix))
+    }
+}
+
+/// Decode an SS58 address into its public key and its network prefix.
+fn decode_ss58(address: &str) -> Result<([u8; 32], u16)> {
+    let (account, format) = AccountId32::from_ss58check_with_version(address.trim())
+        .map_err(|e| anyhow!("Invalid SS58 address {}: {:?}", address, e))?;
+    Ok((account.into(), format.prefix()))
+}
+
+/// Parse a 32-byte public key, either hex encoded (with or without a `0x` prefix) or as an SS58
+/// address of any network, along with the network prefix of the SS58 address.
+pub fn parse_public_key(input: &str) -> Result<([u8; 32], Option<u16>)> {
+    let input = input.trim();
+    let hex = input.strip_prefix("0x").unwrap_or(input);
+    if hex.len() == 64 {
+        let mut public_key = [0u8; 32];
+        hex::decode_to_slice(hex, &mut public_key)
+            .map_err(|e| anyhow!("Invalid public key {}: {}", input, e))?;
+        return Ok((public_key, None));
+    }
+    let (public_key, prefix) = decode_ss58(input).map_err(|_| {
+        anyhow!(
+            "Invalid public key or address {}. Expected 32 hex encoded bytes or an SS58 address",
+            input
+        )
+    })?;
+    Ok((public_key, Some(prefix)))
+}
+
+/// Parse an SS58 network prefix: a number between 0 and 16383, or the name of a network of the
+/// SS58 registry (e.g. `polkadot`, `kusama`, `astar`).
+pub fn parse_ss58_prefix(input: &str) -> Result<u16> {
+    if let Ok(prefix) = input.parse::<u16>() {
+        return match prefix {
+            0..=16383 => Ok(prefix),
+            _ => Err(anyhow!(
+                "Invalid SS58 prefix: {}. Expected a number up to 16383",
+                prefix
+            )),
+        };
+    }
+    Ss58AddressFormatRegistry::try_from(input.to_lowercase().as_str())
+        .map(|registry| Ss58AddressFormat::from(registry).prefix())
+        .map_err(|_| anyhow!("Unknown network {} in the SS58 registry", input))
+}
+
+/// The name of the network of an SS58 prefix in the SS58 registry, if known.
+pub fn ss58_network(prefix: u16) -> Option<String> {
+    let format = Ss58AddressFormat::custom(prefix);
+    Ss58AddressFormatRegistry::try_from(format)
+        .ok()
+        .map(|_| format.to_string())
+}
@@ -0,0 +1,225 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    contract_extrinsics::{BalanceVariant, DefaultConfig, TokenMetadata},
+    pallet_contracts_primitives::StorageDeposit,
+    serde_json::{Map, Value as JsonValue},
+    std::str::FromStr,
+    subxt::{
+        backend::legacy::LegacyRpcMethods,
+        dynamic::{self, At, Value},
+        OnlineClient,
+    },
+};
+
+/// The number of decimals shown when formatting a balance.
+const DISPLAY_DECIMALS: u32 = 4;
+
+/// The decimals and symbol of the network token assumed when the node does not report them, as
+/// for Substrate development chains.
+const DEFAULT_DECIMALS: u8 = 12;
+const DEFAULT_SYMBOL: &str = "UNIT";
+
+/// A balance passed on the command line.
+///
+/// A balance is either an integer amount in the smallest unit of the network token
+/// (e.g. `1000000000000`), or an amount in token units followed by the token symbol or by `unit`
+/// (e.g. `1.5 DOT`, `1.5DOT` or `0.5unit`). Underscores can be used as separators.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Balance {
+    /// An amount in the smallest unit of the token
+    Raw(u128),
+    /// An amount in token units, with the symbol of the token (`None` for `unit`)
+    Units {
+        amount: String,
+        symbol: Option<String>,
+    },
+}
+
+impl FromStr for Balance {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let input = input.trim().replace('_', "");
+        let split = input
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(input.len());
+        let (amount, unit) = input.split_at(split);
+        let unit = unit.trim();
+        if amount.is_empty() || amount.matches('.').count() > 1 || amount.ends_with('.') {
+            return Err(anyhow!("Invalid balance: {}", input));
+        }
+        if unit.is_empty() {
+            if amount.contains('.') {
+                return Err(anyhow!(
+                    "Invalid balance: {}. A balance without unit is an integer amount in the smallest unit of the token; use e.g. \"{} unit\" for token units",
+                    input,
+                    amount
+                ));
+            }
+            return Ok(Balance::Raw(amount.parse()?));
+        }
+        if !unit.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(anyhow!("Invalid balance unit: {}", unit));
+        }
+        let symbol = if unit.eq_ignore_ascii_case("unit") {
+            None
+        } else {
+            Some(unit.to_string())
+        };
+        Ok(Balance::Units {
+            amount: amount.to_string(),
+            symbol,
+        })
+    }
+}
+
+impl Balance {
+    /// Returns the amount in the smallest unit of the network token.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the symbol does not match the symbol of the network
+    /// token, if the amount has more decimals than the token, or if the amount overflows.
+    pub fn denominate(&self, token: &TokenInfo) -> Result<u128> {
+        let (amount, symbol) = match self {
+            Balance::Raw(amount) => return Ok(*amount),
+            Balance::Units { amount, symbol } => (amount, symbol),
+        };
+        if let Some(symbol) = symbol {
+            if !symbol.eq_ignore_ascii_case(&token.symbol) {
+                return Err(anyhow!(
+                    "The symbol {} does not match the symbol of the network token ({})",
+                    symbol,
+                    token.symbol
+                ));
+            }
+        }
+        let (integer, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+        if fraction.len() > token.decimals as usize {
+            return Err(anyhow!(
+                "{} has more decimals than the network token ({})",
+                amount,
+                token.decimals
+            ));
+        }
+        let overflow = || anyhow!("The balance {} is too large", amount);
+        let integer: u128 = if integer.is_empty() {
+            0
+        } else {
+            integer.parse()?
+        };
+        let fraction: u128 = if fraction.is_empty() {
+            0
+        } else {
+            fraction.parse::<u128>()? * 10u128.pow(token.decimals as u32 - fraction.len() as u32)
+        };
+        integer
+            .checked_mul(10u128.pow(token.decimals as u32))
+            .and_then(|integer| integer.checked_add(fraction))
+            .ok_or_else(overflow)
+    }
+
+    /// Returns the balance as a `BalanceVariant`, in the smallest unit of the network token.
+    pub fn to_variant(&self, token: &TokenInfo) -> Result<BalanceVariant> {
+        Ok(BalanceVariant::Default(self.denominate(token)?))
+    }
+}
+
+/// The decimals and symbol of the network token.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TokenInfo {
+    pub decimals: u8,
+    pub symbol: String,
+}
+
+impl TokenInfo {
+    /// Query the decimals and symbol of the network token from the `system_properties` of the
+    /// node.
+    pub async fn query(rpc: &LegacyRpcMethods<DefaultConfig>) -> Result<Self> {
+        let properties = rpc.system_properties().await?;
+        Self::from_properties(&properties)
+    }
+
+    /// Read the decimals and symbol of the network token from the `system_properties` of a node.
+    ///
+    /// Chains with several tokens report a list of decimals and symbols, the first one being the
+    /// native token. The decimals and symbol of Substrate development chains (12 decimals, `UNIT`)
+    /// are assumed when the node does not report them.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the properties are not numbers and strings, or lists of
+    /// them.
+    pub fn from_properties(properties: &Map<String, JsonValue>) -> Result<Self> {
+        let native = |key: &str| match properties.get(key) {
+            Some(JsonValue::Array(values)) => values.first(),
+            value => value,
+        };
+        let decimals = match native("tokenDecimals") {
+            Some(decimals) => decimals
+                .as_u64()
+                .and_then(|decimals| u8::try_from(decimals).ok())
+                .ok_or_else(|| anyhow!("Invalid token decimals of the network: {}", decimals))?,
+            None => DEFAULT_DECIMALS,
+        };
+        let symbol = match native("tokenSymbol") {
+            Some(symbol) => symbol
+                .as_str()
+                .ok_or_else(|| anyhow!("Invalid token symbol of the network: {}", symbol))?
+                .to_string(),
+            None => DEFAULT_SYMBOL.to_string(),
+        };
+        Ok(TokenInfo { decimals, symbol })
+    }
+
+    /// Get the token metadata used by `contract_extrinsics` to display the events.
+    pub fn token_metadata(&self) -> TokenMetadata {
+        TokenMetadata {
+            token_decimals: self.decimals as usize,
+            symbol: self.symbol.clone(),
+        }
+    }
+
+    /// Format an amount in the smallest unit of the token as token units, e.g. `1.5000 AZERO`.
+    pub fn format(&self, amount: u128) -> String {
+        let unit = 10u128.pow(self.decimals as u32);
+        let fraction = amount % unit;
+        let fraction = if self.decimals as u32 >= DISPLAY_DECIMALS {
+            fraction / 10u128.pow(self.decimals as u32 - DISPLAY_DECIMALS)
+        } else {
+            fraction * 10u128.pow(DISPLAY_DECIMALS - self.decimals as u32)
+        };
+        format!(
+            "{}.{:0width$} {}",
+            amount / unit,
+            fraction,
+            self.symbol,
+            width = DISPLAY_DECIMALS as usize
+        )
+    }
+
+    /// Format a storage deposit, e.g. `Charge: 1.5000 AZERO`.
+    pub fn format_storage_deposit(&self, deposit: &StorageDeposit<u128>) -> String {
+        match deposit {
+            StorageDeposit::Charge(amount) => format!("Charge: {}", self.format(*amount)),
+            StorageDeposit::Refund(amount) => format!("Refund: {}", self.format(*amount)),
+        }
+    }
+}
+
+/// Query the free balance of an account, in the smallest unit of the network token, from
+/// `System::Account` at the latest block. An account which does not exist has no balance.
+pub async fn free_balance(client: &OnlineClient<DefaultConfig>, account: [u8; 32]) -> Result<u128> {
+    let query = dynamic::storage("System", "Account", vec![Value::from_bytes(account)]);
+    let account = match client.storage().at_latest().await?.fetch(&query).await? {
+        Some(account) => account.to_value()?,
+        None => return Ok(0),
+    };
+    Ok(account
+        .at("data")
+        .at("free")
+        .and_then(Value::as_u128)
+        .unwrap_or(0))
+}
@@ -0,0 +1,514 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::{
+        balance::{Balance, TokenInfo},
+        debug_output,
+        decode::contract_value_to_json,
+        events::{typed_events, TypedEvent},
+        extrinsic_options::{ExtrinsicOptions, Gas},
+        runtime::{ensure_account_mapped, revive_address, ContractsPallet},
+        serialize_storage_deposit,
+        submit::{
+            contracts_call_payload, decode_events, estimate_fee, export_call, extrinsic_block,
+            pair_signer, proxy_payload, submit_extrinsic, CostEstimate, ExportedCall,
+            ExtrinsicBlock, FeeEstimate, TxParams,
+        },
+        with_debug_output,
+    },
+    anyhow::{anyhow, Context, Result},
+    contract_extrinsics::{
+        CallCommandBuilder, CallExec, DefaultConfig, DisplayEvents, TokenMetadata,
+    },
+    contract_transcode::{ContractMessageTranscoder, Value},
+    pallet_contracts_primitives::{ContractExecResult, StorageDeposit},
+    serde::Serialize,
+    std::time::Instant,
+    subxt::{
+        blocks::ExtrinsicEvents,
+        ext::codec::{Compact, Encode},
+        tx::DynamicPayload,
+        Config, OnlineClient,
+    },
+    subxt_signer::sr25519::Keypair,
+    tracing::debug,
+};
+
+type AccountId = <DefaultConfig as Config>::AccountId;
+
+/// A call of a contract message on a Polkadot node.
+pub struct PolkadotCall {
+    exec: CallExec,
+    token: TokenInfo,
+    contract: AccountId,
+    value: u128,
+    storage_deposit_limit: Option<u128>,
+    signer: Keypair,
+    tx_params: TxParams,
+    proxied: Option<AccountId>,
+    pallet: ContractsPallet,
+}
+
+/// A builder for configuring and constructing a [`PolkadotCall`].
+pub struct PolkadotCallBuilder {
+    options: ExtrinsicOptions,
+    contract: AccountId,
+    message: String,
+    args: Vec<String>,
+    value: Balance,
+    gas_limit: Option<u64>,
+    proof_size: Option<u64>,
+    proxied: Option<AccountId>,
+}
+
+/// The result of a dry run of a call.
+#[derive(Debug, Serialize)]
+pub struct CallDryRunResult {
+    pub reverted: bool,
+    /// The decoded return value of the message
+    pub data: Value,
+    pub gas_consumed: Gas,
+    pub gas_required: Gas,
+    #[serde(serialize_with = "serialize_storage_deposit")]
+    pub storage_deposit: StorageDeposit<u128>,
+    /// The lines of the debug output of the contract
+    pub debug_output: Vec<String>,
+}
+
+/// The result of a submitted call.
+#[derive(Serialize)]
+pub struct CallResult {
+    pub events: Vec<TypedEvent>,
+    /// The block the call was included in
+    pub block: ExtrinsicBlock,
+    /// The events of the call, to display them
+    #[serde(skip)]
+    pub display_events: DisplayEvents,
+}
+
+impl CallDryRunResult {
+    /// Decode the result of a dry run of a call of `message`.
+    pub fn from_exec_result(
+        transcoder: &ContractMessageTranscoder,
+        message: &str,
+        result: ContractExecResult<u128, ()>,
+    ) -> Result<Self> {
+        let debug_output = debug_output(&result.debug_message);
+        let ret_val = result.result.map_err(|err| {
+            with_debug_output(
+                format!("Error calling the contract: {:?}", err),
+                &debug_output,
+            )
+        })?;
+        let data = transcoder
+            .decode_message_return(message, &mut &ret_val.data[..])
+            .context(format!("Failed to decode return value {:?}", &ret_val))?;
+        Ok(CallDryRunResult {
+            reverted: ret_val.did_revert(),
+            data,
+            gas_consumed: result.gas_consumed.into(),
+            gas_required: result.gas_required.into(),
+            storage_deposit: result.storage_deposit,
+            debug_output,
+        })
+    }
+
+    /// The decoded return value of the message, as JSON (see [`contract_value_to_json`]).
+    pub fn data_json(&self) -> serde_json::Value {
+        contract_value_to_json(&self.data)
+    }
+}
+
+impl PolkadotCallBuilder {
+    /// Set the arguments of the message.
+    pub fn args(mut self, args: Vec<String>) -> Self {
+        self.args = args;
+        self
+    }
+
+    /// Set the value to transfer to the contract.
+    pub fn value(mut self, value: Balance) -> Self {
+        self.value = value;
+        self
+    }
+
+    /// Set the maximum computation time of the call, estimated by default.
+    pub fn gas_limit(mut self, gas_limit: Option<u64>) -> Self {
+        self.gas_limit = gas_limit;
+        self
+    }
+
+    /// Set the maximum proof size of the call, estimated by default.
+    pub fn proof_size(mut self, proof_size: Option<u64>) -> Self {
+        self.proof_size = proof_size;
+        self
+    }
+
+    /// Set the account the call is dispatched from through `Proxy::proxy`, the signer being one of
+    /// its proxies.
+    pub fn proxied(mut self, proxied: Option<AccountId>) -> Self {
+        self.proxied = proxied;
+        self
+    }
+
+    /// Build the call, loading the contract metadata and connecting to the node.
+    pub async fn done(self) -> Result<PolkadotCall> {
+        // The dry runs and the submission use the node connected to first
+        let (url, _, pallet, token) = self.options.connect().await?;
+        let value = self.value.denominate(&token)?;
+        let storage_deposit_limit = self.options.denominated_storage_deposit_limit(&token)?;
+        let signer = pair_signer(self.options.suri())?;
+        let tx_params = self.options.tx_params(&token)?;
+        let exec = CallCommandBuilder::default()
+            .contract(self.contract.clone())
+            .message(self.message)
+            .args(self.args)
+            .extrinsic_opts(self.options.extrinsic_opts_builder(&token, &url)?.done())
+            .gas_limit(self.gas_limit)
+            .proof_size(self.proof_size)
+            .value(self.value.to_variant(&token)?)
+            .done()
+            .await?;
+        Ok(PolkadotCall {
+            exec,
+            token,
+            contract: self.contract,
+            value,
+            storage_deposit_limit,
+            signer,
+            tx_params,
+            proxied: self.proxied,
+            pallet,
+        })
+    }
+}
+
+impl PolkadotCall {
+    /// Create a builder for a call of `message` of the contract at `contract`.
+    pub fn builder(
+        options: ExtrinsicOptions,
+        contract: AccountId,
+        message: impl Into<String>,
+    ) -> PolkadotCallBuilder {
+        PolkadotCallBuilder {
+            options,
+            contract,
+            message: message.into(),
+            args: vec![],
+            value: Balance::Raw(0),
+            gas_limit: None,
+            proof_size: None,
+            proxied: None,
+        }
+    }
+
+    /// Get the address of the called contract
+    pub fn contract(&self) -> &AccountId {
+        &self.contract
+    }
+
+    /// Get the name of the called message
+    pub fn message(&self) -> &str {
+        self.exec.message()
+    }
+
+    /// Get the arguments of the called message
+    pub fn args(&self) -> &[String] {
+        self.exec.args()
+    }
+
+    /// Get the value transferred to the contract, in the smallest unit of the network token
+    pub fn value(&self) -> u128 {
+        self.value
+    }
+
+    /// Get the storage deposit limit, in the smallest unit of the network token
+    pub fn storage_deposit_limit(&self) -> Option<u128> {
+        self.storage_deposit_limit
+    }
+
+    /// Get the decimals and symbol of the network token
+    pub fn token(&self) -> &TokenInfo {
+        &self.token
+    }
+
+    /// Get the account the call is dispatched from through a proxy, if any
+    pub fn proxied(&self) -> Option<&AccountId> {
+        self.proxied.as_ref()
+    }
+
+    /// Get the pallet running the contracts of the node
+    pub fn pallet(&self) -> ContractsPallet {
+        self.pallet
+    }
+
+    /// Get the token metadata of the network, used to display the events
+    pub fn token_metadata(&self) -> TokenMetadata {
+        self.token.token_metadata()
+    }
+
+    /// Get the transcoder of the contract
+    pub fn transcoder(&self) -> &ContractMessageTranscoder {
+        self.exec.transcoder()
+    }
+
+    /// Get the client connected to the node
+    pub fn client(&self) -> &OnlineClient<DefaultConfig> {
+        self.exec.client()
+    }
+
+    /// Get the encoded input data of the call: the selector of the message and its arguments
+    pub fn call_data(&self) -> Result<Vec<u8>> {
+        let call_data = self
+            .exec
+            .transcoder()
+            .encode(self.exec.message(), self.exec.args())?;
+        debug!(
+            message = self.exec.message(),
+            args = ?self.exec.args(),
+            call_data = %hex::encode(&call_data),
+            "Encoded the call data"
+        );
+        Ok(call_data)
+    }
+
+    /// Get the caller of the dry runs performed with the runtime API rather than by
+    /// `contract_extrinsics`: the proxied account when the call is dispatched through a proxy, and
+    /// the signer on pallet-revive, which `contract_extrinsics` does not support.
+    fn dry_run_origin(&self) -> Option<AccountId> {
+        match (&self.proxied, self.pallet) {
+            (Some(proxied), _) => Some(proxied.clone()),
+            (None, ContractsPallet::Revive) => Some(AccountId::from(self.signer.public_key().0)),
+            (None, ContractsPallet::Contracts) => None,
+        }
+    }
+
+    /// Dry-run the call, without submitting any extrinsic.
+    ///
+    /// When the call is dispatched through a proxy, the dry run is performed with the proxied
+    /// account as caller.
+    pub async fn dry_run(&self) -> Result<CallDryRunResult> {
+        debug!(
+            contract = %self.pallet.format_address(&self.contract),
+            message = self.exec.message(),
+            "Dry-running the call"
+        );
+        let result = match self.dry_run_origin() {
+            Some(origin) => {
+                dry_run_call(
+                    self.client(),
+                    self.pallet,
+                    &origin,
+                    &self.contract,
+                    self.value,
+                    self.storage_deposit_limit,
+                    self.call_data()?,
+                )
+                .await?
+            }
+            None => self.exec.call_dry_run().await?,
+        };
+        CallDryRunResult::from_exec_result(self.exec.transcoder(), self.exec.message(), result)
+    }
+
+    /// Estimate the gas required by the call, with a dry run.
+    ///
+    /// When the call is dispatched through a proxy, the dry run is performed with the proxied
+    /// account as caller.
+    pub async fn estimate_gas(&self) -> Result<Gas> {
+        let Some(origin) = self.dry_run_origin() else {
+            return Ok(self.exec.estimate_gas().await?.into());
+        };
+        let result = dry_run_call(
+            self.client(),
+            self.pallet,
+            &origin,
+            &self.contract,
+            self.value,
+            self.storage_deposit_limit,
+            self.call_data()?,
+        )
+        .await?;
+        result.result.map_err(|err| {
+            with_debug_output(
+                format!("Pre-submission dry run of the call failed: {:?}", err),
+                &[],
+            )
+        })?;
+        Ok(result.gas_required.into())
+    }
+
+    /// Get the `Contracts::call` or `Revive::call` extrinsic with the given gas limit, wrapped in
+    /// `Proxy::proxy` if the call is dispatched through a proxy.
+    fn payload(&self, gas_limit: Gas) -> Result<DynamicPayload> {
+        let call = contracts_call_payload(
+            self.pallet,
+            &self.contract,
+            self.value,
+            gas_limit,
+            self.storage_deposit_limit,
+            self.call_data()?,
+        );
+        Ok(match &self.proxied {
+            Some(proxied) => proxy_payload(proxied, call.into_value()),
+            None => call,
+        })
+    }
+
+    /// Estimate the fee and the weight of the call with the given gas limit, without submitting
+    /// it.
+    pub async fn estimate_fee(&self, gas_limit: Gas) -> Result<FeeEstimate> {
+        let call = self.payload(gas_limit)?;
+        estimate_fee(self.client(), &call, &self.signer, &self.tx_params).await
+    }
+
+    /// Estimate the cost of the call with a dry run, without submitting it.
+    pub async fn estimate(&self) -> Result<CostEstimate> {
+        let result = self.dry_run().await?;
+        let gas_limit = self.estimate_gas().await?;
+        let fee = self.estimate_fee(gas_limit).await?;
+        Ok(CostEstimate {
+            partial_fee: fee.partial_fee,
+            weight: fee.weight,
+            gas_limit: Some(gas_limit),
+            storage_deposit: result.storage_deposit,
+        })
+    }
+
+    /// Export the call with the given gas limit, to be signed by an external signer with the
+    /// account of the signer of the call.
+    pub async fn export(&self, gas_limit: Gas) -> Result<ExportedCall> {
+        let call = self.payload(gas_limit)?;
+        let signer = AccountId::from(self.signer.public_key().0);
+        export_call(self.client(), &call, &signer, &self.tx_params).await
+    }
+
+    /// Submit the call with the given gas limit, and wait for it to be included in a block or
+    /// finalized.
+    pub async fn submit(&self, gas_limit: Gas) -> Result<CallResult> {
+        if self.pallet == ContractsPallet::Revive {
+            ensure_account_mapped(self.client(), &self.signer, &self.tx_params).await?;
+        }
+        let events = self.submit_events(gas_limit, &self.tx_params).await?;
+        let block = extrinsic_block(self.client(), &events).await?;
+        let display_events = DisplayEvents::from_events(
+            &events,
+            Some(self.exec.transcoder()),
+            &self.client().metadata(),
+        )?;
+        Ok(CallResult {
+            events: typed_events(
+                &events,
+                Some(self.exec.transcoder()),
+                &self.client().metadata(),
+            )?,
+            block,
+            display_events,
+        })
+    }
+
+    /// The next nonce of the signer, including its pending extrinsics.
+    ///
+    /// On pallet-revive, the account of the signer is mapped first if needed, so that the calls
+    /// submitted with the following nonces are not preceded by the mapping extrinsic.
+    pub async fn next_nonce(&self) -> Result<u64> {
+        if self.pallet == ContractsPallet::Revive {
+            ensure_account_mapped(self.client(), &self.signer, &self.tx_params).await?;
+        }
+        let account = AccountId::from(self.signer.public_key().0);
+        Ok(self.client().tx().account_nonce(&account).await?)
+    }
+
+    /// Submit the call with the given gas limit and nonce, and wait for it to be included in a
+    /// block or finalized, without decoding its events.
+    ///
+    /// The calls can be submitted concurrently with consecutive nonces from
+    /// [`PolkadotCall::next_nonce`], e.g. to benchmark them.
+    pub async fn submit_with_nonce(&self, gas_limit: Gas, nonce: u64) -> Result<ExtrinsicBlock> {
+        let tx_params = TxParams {
+            nonce: Some(nonce),
+            ..self.tx_params.clone()
+        };
+        let events = self.submit_events(gas_limit, &tx_params).await?;
+        extrinsic_block(self.client(), &events).await
+    }
+
+    /// Submit the call with the given gas limit and parameters, and get its events, checking the
+    /// result of the proxied call, if any.
+    async fn submit_events(
+        &self,
+        gas_limit: Gas,
+        tx_params: &TxParams,
+    ) -> Result<ExtrinsicEvents<DefaultConfig>> {
+        let call = self.payload(gas_limit)?;
+        let events = submit_extrinsic(self.client(), &call, &self.signer, tx_params)
+            .await
+            .map_err(|err| anyhow!("Error calling the contract: {}", err))?;
+        if self.proxied.is_some() {
+            // The proxy extrinsic succeeds even if the proxied call fails, its result is in the
+            // `ProxyExecuted` event
+            let results = decode_events::<Result<(), ()>>(&events, "Proxy", "ProxyExecuted");
+            if !matches!(results.as_deref(), Ok([Ok(())])) {
+                return Err(anyhow!(
+                    "Error calling the contract: the proxied call failed"
+                ));
+            }
+        }
+        Ok(events)
+    }
+}
+
+/// Dry-run a call of a contract with the `ContractsApi_call` runtime API, or the `ReviveApi_call`
+/// runtime API on pallet-revive.
+///
+/// Any account can be used as the caller, as nothing is signed.
+pub async fn dry_run_call(
+    client: &OnlineClient<DefaultConfig>,
+    pallet: ContractsPallet,
+    origin: &AccountId,
+    contract: &AccountId,
+    value: u128,
+    storage_deposit_limit: Option<u128>,
+    input_data: Vec<u8>,
+) -> Result<ContractExecResult<u128, ()>> {
+    let api = pallet.runtime_api("call");
+    debug!(%api, input_data = %hex::encode(&input_data), "Calling the runtime API");
+    // Without a gas limit (an `Option<Weight>`), the dry run can use the maximum gas
+    let gas_limit: Option<(Compact<u64>, Compact<u64>)> = None;
+    // pallet-revive addresses the contracts with their 20-byte address, and returns its result in
+    // the same layout as pallet-contracts
+    let call_parameters = match pallet {
+        ContractsPallet::Contracts => (
+            origin,
+            contract,
+            value,
+            gas_limit,
+            storage_deposit_limit,
+            input_data,
+        )
+            .encode(),
+        ContractsPallet::Revive => (
+            origin,
+            revive_address(contract),
+            value,
+            gas_limit,
+            storage_deposit_limit,
+            input_data,
+        )
+            .encode(),
+    };
+    let start = Instant::now();
+    let result: ContractExecResult<u128, ()> = client
+        .runtime_api()
+        .at_latest()
+        .await?
+        .call_raw(&api, Some(&call_parameters))
+        .await?;
+    debug!(
+        %api,
+        elapsed = ?start.elapsed(),
+        gas_required = ?result.gas_required,
+        "Runtime API call completed"
+    );
+    Ok(result)
+}
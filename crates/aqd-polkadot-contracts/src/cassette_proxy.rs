@@ -0,0 +1,227 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::network_proxy::connect_websocket,
+    anyhow::{anyhow, Result},
+    aqd_utils::{
+        is_replaying, rate_limit_delay, record_interaction, record_notification,
+        replay_interaction, ErrorKind, Interaction,
+    },
+    futures::{SinkExt, StreamExt},
+    serde_json::{json, Value},
+    std::{collections::HashMap, sync::Mutex},
+    tokio::net::{TcpListener, TcpStream},
+    tokio_tungstenite::{accept_async, tungstenite::Message, MaybeTlsStream, WebSocketStream},
+    tracing::{debug, warn},
+    url::Url,
+};
+
+/// The chain of the interactions of the Polkadot RPC requests in a cassette.
+const CHAIN: &str = "polkadot";
+
+/// The URL of the cassette proxy, once started.
+static PROXY_URL: Mutex<Option<Url>> = Mutex::new(None);
+
+/// The URL of a local proxy of the node that records the RPC requests and their responses to the
+/// cassette with `--record`, or replays them with `--replay` without connecting to the node.
+///
+/// A proxy is needed, rather than a client recording its requests, as cargo-contract connects its
+/// own clients to the URL of the node. The proxy is started once, and connects each of its
+/// connections to the first node of `urls` that can be reached.
+///
+/// Without a cassette, the proxy only forwards the messages, so that the clients connected to it,
+/// including the ones of cargo-contract, reach the nodes through the network proxy of `--proxy`
+/// or of the environment, with the headers of `--rpc-header`, and under the rate limit of
+/// `--rate-limit`, shared by all the clients.
+pub(crate) async fn cassette_proxy(urls: &[Url]) -> Result<Url> {
+    if let Some(url) = PROXY_URL.lock().unwrap().clone() {
+        return Ok(url);
+    }
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .map_err(|e| anyhow!("Failed to start the cassette proxy: {}", e))?;
+    let url = Url::parse(&format!("ws://{}", listener.local_addr()?))?;
+    debug!(%url, "Started the cassette proxy");
+
+    let urls = urls.to_vec();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let urls = urls.clone();
+            tokio::spawn(async move {
+                if let Err(e) = proxy_connection(stream, &urls).await {
+                    warn!(error = %e, "Cassette proxy connection failed");
+                }
+            });
+        }
+    });
+    *PROXY_URL.lock().unwrap() = Some(url.clone());
+    Ok(url)
+}
+
+/// Serve a connection of a client to the proxy.
+async fn proxy_connection(stream: TcpStream, urls: &[Url]) -> Result<()> {
+    let client = accept_async(stream).await?;
+    if is_replaying() {
+        replay(client).await
+    } else {
+        record(client, urls).await
+    }
+}
+
+/// Forward the messages between a client and the node, recording the requests of the client with
+/// their responses, and the notifications of the subscriptions.
+///
+/// The requests are held back until the rate limit allows them.
+async fn record(client: WebSocketStream<TcpStream>, urls: &[Url]) -> Result<()> {
+    let node = connect_node(urls).await?;
+    let (mut client_sink, mut client_stream) = client.split();
+    let (mut node_sink, mut node_stream) = node.split();
+    // The method and parameters of the requests waiting for their response, by id
+    let mut requests: HashMap<String, (String, Value)> = HashMap::new();
+    // The index of the interaction of the subscriptions, by subscription id
+    let mut subscriptions: HashMap<String, usize> = HashMap::new();
+
+    loop {
+        tokio::select! {
+            message = client_stream.next() => {
+                let Some(message) = message.transpose()? else {
+                    break;
+                };
+                if let Message::Text(text) = &message {
+                    if let Ok(request) = serde_json::from_str::<Value>(text) {
+                        if let Some(method) = request["method"].as_str() {
+                            let params = request.get("params").cloned().unwrap_or_default();
+                            requests.insert(request["id"].to_string(), (method.to_string(), params));
+                            let delay = rate_limit_delay();
+                            if !delay.is_zero() {
+                                debug!(?delay, method, "Waiting for the rate limit of the RPC requests");
+                                tokio::time::sleep(delay).await;
+                            }
+                        }
+                    }
+                }
+                let close = message.is_close();
+                node_sink.send(message).await?;
+                if close {
+                    break;
+                }
+            }
+            message = node_stream.next() => {
+                let Some(message) = message.transpose()? else {
+                    break;
+                };
+                if let Message::Text(text) = &message {
+                    record_message(text, &mut requests, &mut subscriptions);
+                }
+                client_sink.send(message).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Connect to the first node of `urls` that can be reached.
+async fn connect_node(urls: &[Url]) -> Result<WebSocketStream<MaybeTlsStream<TcpStream>>> {
+    let mut errors = vec![];
+    for url in urls {
+        match connect_websocket(url).await {
+            Ok(node) => return Ok(node),
+            Err(e) => errors.push(format!("{}: {}", url, e)),
+        }
+    }
+    Err(ErrorKind::Rpc.wrap(anyhow!(
+        "Failed to connect to any of the nodes:\n{}",
+        errors.join("\n")
+    )))
+}
+
+/// Record a message of the node: the response to a request, or a notification of a subscription.
+fn record_message(
+    text: &str,
+    requests: &mut HashMap<String, (String, Value)>,
+    subscriptions: &mut HashMap<String, usize>,
+) {
+    let Ok(message) = serde_json::from_str::<Value>(text) else {
+        return;
+    };
+    match (message.get("id"), message["method"].as_str()) {
+        (Some(id), _) => {
+            let Some((method, params)) = requests.remove(&id.to_string()) else {
+                return;
+            };
+            let response = match message.get("error") {
+                Some(error) => Err(error.clone()),
+                None => Ok(message["result"].clone()),
+            };
+            // Any result may be the id of a subscription
+            let result = response.as_ref().ok().map(Value::to_string);
+            let index = record_interaction(Interaction::new(CHAIN, &method, params, response));
+            if let (Some(index), Some(result)) = (index, result) {
+                subscriptions.insert(result, index);
+            }
+        }
+        (None, Some(notification)) => {
+            let params = &message["params"];
+            if let Some(index) = subscriptions.get(&params["subscription"].to_string()) {
+                record_notification(*index, notification, params["result"].clone());
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Answer the requests of a client with the responses of the cassette.
+async fn replay(client: WebSocketStream<TcpStream>) -> Result<()> {
+    let (mut sink, mut stream) = client.split();
+    while let Some(message) = stream.next().await.transpose()? {
+        match message {
+            Message::Text(text) => {
+                let request: Value = serde_json::from_str(&text)?;
+                for reply in replay_request(&request) {
+                    sink.send(Message::Text(reply.to_string())).await?;
+                }
+            }
+            Message::Close(_) => break,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// The replayed messages answering a request: its response, then the notifications of a
+/// subscription.
+fn replay_request(request: &Value) -> Vec<Value> {
+    let id = &request["id"];
+    let method = request["method"].as_str().unwrap_or_default();
+    let params = request.get("params").cloned().unwrap_or_default();
+    let interaction = match replay_interaction(CHAIN, method, &params) {
+        Ok(interaction) => interaction,
+        Err(e) => {
+            return vec![json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": -32603, "message": e.to_string() },
+            })]
+        }
+    };
+
+    let mut replies = vec![match &interaction.error {
+        Some(error) if error.is_object() => json!({ "jsonrpc": "2.0", "id": id, "error": error }),
+        Some(error) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": -32603, "message": error },
+        }),
+        None => json!({ "jsonrpc": "2.0", "id": id, "result": interaction.result }),
+    }];
+    if let Some(notification) = &interaction.notification {
+        replies.extend(interaction.notifications.iter().map(|result| {
+            json!({
+                "jsonrpc": "2.0",
+                "method": notification,
+                "params": { "subscription": interaction.result, "result": result },
+            })
+        }));
+    }
+    replies
+}
@@ -0,0 +1,193 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::{cassette_proxy::cassette_proxy, runtime::ContractsPallet},
+    anyhow::{anyhow, Result},
+    aqd_utils::{
+        cassette_mode, connect_timeout, proxy_for, rate_limit, register_secret_url, rpc_headers,
+        rpc_timeout, ErrorKind,
+    },
+    contract_extrinsics::DefaultConfig,
+    jsonrpsee::ws_client::WsClientBuilder,
+    std::time::Instant,
+    subxt::{
+        backend::{legacy::LegacyRpcMethods, rpc::RpcClient},
+        OnlineClient,
+    },
+    tracing::{debug, info, warn},
+    url::Url,
+};
+
+/// Connect to the first node of `urls` that can be reached.
+///
+/// The nodes are tried in order, so that a flaky RPC endpoint can be backed by other endpoints of
+/// the same network. The URL of the node connected to is returned along with the client.
+///
+/// With `--record` or `--replay`, the client connects to the local proxy recording or replaying
+/// the requests to the nodes instead, and the URL of the proxy is returned, so that the other
+/// clients of the command connect to it too. So does the client when the nodes are reached
+/// through a network proxy, with the headers of `--rpc-header`, or under the rate limit of
+/// `--rate-limit`, which the websocket clients of cargo-contract do not support.
+///
+/// A node that cannot be reached before `--connect-timeout` is skipped, and the requests to the
+/// node connected to time out after `--rpc-timeout`.
+///
+/// # Errors
+///
+/// This function returns an error listing the failure of each node if none can be reached.
+pub async fn connect(urls: &[Url]) -> Result<(Url, OnlineClient<DefaultConfig>)> {
+    let (url, client, _) = connect_with_rpc(urls).await?;
+    Ok((url, client))
+}
+
+/// Connect to the first node of `urls` that can be reached, like [`connect`], returning the RPC
+/// methods of the node too, for the requests the client does not make, e.g. `system_properties`.
+pub async fn connect_with_rpc(
+    urls: &[Url],
+) -> Result<(
+    Url,
+    OnlineClient<DefaultConfig>,
+    LegacyRpcMethods<DefaultConfig>,
+)> {
+    for url in urls {
+        register_secret_url(url.as_str());
+    }
+    let proxied = urls.iter().any(|url| proxy_for(url).is_some());
+    let relayed =
+        cassette_mode().is_some() || proxied || !rpc_headers().is_empty() || rate_limit().is_some();
+    let urls = match relayed {
+        true => vec![cassette_proxy(urls).await?],
+        false => urls.to_vec(),
+    };
+    let mut errors = vec![];
+    for url in &urls {
+        debug!(%url, "Connecting to node");
+        let start = Instant::now();
+        match online_client(url).await {
+            Ok((client, rpc)) => {
+                info!(%url, elapsed = ?start.elapsed(), "Connected to node");
+                return Ok((url.clone(), client, rpc));
+            }
+            Err(e) => {
+                warn!(%url, error = %e, "Failed to connect to node, trying the next one");
+                errors.push(format!("{}: {}", url, e));
+            }
+        }
+    }
+    let error = match errors.as_slice() {
+        [] => anyhow!("No URL to connect to"),
+        [error] => anyhow!("Failed to connect to {}", error),
+        errors => anyhow!(
+            "Failed to connect to any of the nodes:\n{}",
+            errors.join("\n")
+        ),
+    };
+    Err(ErrorKind::Rpc.wrap(error))
+}
+
+/// Connect a client to the node at `url`, with the network timeouts.
+///
+/// The websocket client is configured as the default one of subxt, apart from its timeouts.
+async fn online_client(
+    url: &Url,
+) -> Result<(OnlineClient<DefaultConfig>, LegacyRpcMethods<DefaultConfig>)> {
+    let ws_client = WsClientBuilder::default()
+        .connection_timeout(connect_timeout())
+        .request_timeout(rpc_timeout())
+        .max_buffer_capacity_per_subscription(4096)
+        .build(url.as_str())
+        .await?;
+    let rpc_client = RpcClient::new(ws_client);
+    let client = OnlineClient::<DefaultConfig>::from_rpc_client(rpc_client.clone()).await?;
+    Ok((client, LegacyRpcMethods::new(rpc_client)))
+}
+
+/// The calls of the Contracts pallet used to upload, instantiate, call and remove contracts.
+const CONTRACTS_CALLS: [&str; 5] = [
+    "upload_code",
+    "instantiate_with_code",
+    "instantiate",
+    "call",
+    "remove_code",
+];
+
+/// The calls of the Revive pallet used to upload, instantiate, call and remove contracts, and to
+/// map the account of the signer to a 20-byte address.
+const REVIVE_CALLS: [&str; 6] = [
+    "upload_code",
+    "instantiate_with_code",
+    "instantiate",
+    "call",
+    "remove_code",
+    "map_account",
+];
+
+/// Check that the runtime of a node can upload, instantiate and call contracts, before anything is
+/// submitted to it, and detect the pallet running its contracts.
+///
+/// # Errors
+///
+/// This function returns an error explaining the incompatibility if the runtime includes neither
+/// the Contracts pallet nor the Revive pallet, if the pallet lacks one of the calls used, or if
+/// the Contracts pallet still uses the weights v1, whose gas limit is a single number.
+pub fn check_node_compatibility(client: &OnlineClient<DefaultConfig>) -> Result<ContractsPallet> {
+    let metadata = client.metadata();
+    let runtime = client.runtime_version();
+    let contracts_pallet = ContractsPallet::detect(&metadata).ok_or_else(|| {
+        anyhow!(
+            "This node includes neither pallet-contracts nor pallet-revive (runtime spec version {}): contracts cannot be uploaded, instantiated or called on this chain",
+            runtime.spec_version
+        )
+    })?;
+    debug!(
+        pallet = %contracts_pallet,
+        spec_version = runtime.spec_version,
+        "Detected the contracts pallet of the node"
+    );
+    let pallet = metadata
+        .pallet_by_name(contracts_pallet.name())
+        .ok_or_else(|| anyhow!("Missing pallet {}", contracts_pallet.name()))?;
+    let calls = match contracts_pallet {
+        ContractsPallet::Contracts => &CONTRACTS_CALLS[..],
+        ContractsPallet::Revive => &REVIVE_CALLS[..],
+    };
+    for call in calls {
+        if pallet.call_variant_by_name(call).is_none() {
+            return Err(anyhow!(
+                "The {} of this node doesn't support {}::{} (runtime spec version {}): the node is likely too old or too new for aqd",
+                contracts_pallet,
+                contracts_pallet.name(),
+                call,
+                runtime.spec_version
+            ));
+        }
+    }
+    // pallet-revive was introduced after the weights v2
+    if contracts_pallet == ContractsPallet::Revive {
+        return Ok(contracts_pallet);
+    }
+
+    // The gas limit is a `Weight` with a computation time and a proof size since the weights v2
+    let weights_v2 = pallet
+        .call_variant_by_name("call")
+        .and_then(|call| {
+            call.fields
+                .iter()
+                .find(|field| field.name.as_deref() == Some("gas_limit"))
+        })
+        .and_then(|field| metadata.types().resolve(field.ty.id))
+        .map(|ty| {
+            ty.path
+                .segments
+                .iter()
+                .any(|segment| segment == "weight_v2")
+        })
+        .unwrap_or(false);
+    if !weights_v2 {
+        return Err(anyhow!(
+            "The pallet-contracts of this node uses the weights v1 (runtime spec version {}), which are not supported: upgrade the node to a runtime with the weights v2",
+            runtime.spec_version
+        ));
+    }
+    Ok(contracts_pallet)
+}
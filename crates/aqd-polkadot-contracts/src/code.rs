@@ -0,0 +1,88 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::runtime::ContractsPallet,
+    anyhow::{anyhow, Result},
+    contract_extrinsics::DefaultConfig,
+    serde_json::Value,
+    std::{fs, path::Path},
+    subxt::{ext::codec::Decode, Config},
+};
+
+type Hash = <DefaultConfig as Config>::Hash;
+
+/// The code of a contract to instantiate.
+#[derive(Clone, Debug)]
+pub(crate) enum Code {
+    /// The wasm code, uploaded along with the instantiation
+    Upload(Vec<u8>),
+    /// The hash of code already uploaded on chain
+    Existing(Hash),
+}
+
+/// Load the code of a contract from a contract file.
+///
+/// A .wasm file and a .contract bundle contain the code of the contract. For a .json metadata
+/// file, the code is read from the .wasm file next to it if there is one, and only the code hash
+/// of the metadata is used otherwise.
+pub(crate) fn load_code(file: &Path) -> Result<Code> {
+    let read_json = |path: &Path| -> Result<Value> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| anyhow!("Failed to parse {}: {}", path.display(), e))
+    };
+    let hex_field = |metadata: &Value, pointer: &str| -> Result<Vec<u8>> {
+        let value = metadata
+            .pointer(pointer)
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow!("Missing {} in {}", pointer, file.display()))?;
+        hex::decode(value.trim_start_matches("0x"))
+            .map_err(|e| anyhow!("Invalid {} in {}: {}", pointer, file.display(), e))
+    };
+
+    match file.extension().and_then(|ext| ext.to_str()) {
+        Some("wasm") => {
+            Ok(Code::Upload(fs::read(file).map_err(|e| {
+                anyhow!("Failed to read {}: {}", file.display(), e)
+            })?))
+        }
+        Some("contract") => Ok(Code::Upload(hex_field(&read_json(file)?, "/source/wasm")?)),
+        Some("json") => {
+            let wasm = file.with_extension("wasm");
+            if wasm.exists() {
+                return load_code(&wasm);
+            }
+            let hash = hex_field(&read_json(file)?, "/source/hash")?;
+            Ok(Code::Existing(Hash::decode(&mut &hash[..]).map_err(
+                |e| anyhow!("Invalid /source/hash in {}: {}", file.display(), e),
+            )?))
+        }
+        _ => Err(anyhow!(
+            "Invalid contract file {}: expected a .wasm, .contract or .json file",
+            file.display()
+        )),
+    }
+}
+
+/// Load the wasm code of a contract from a contract file, to upload it.
+pub(crate) fn load_wasm(file: &Path) -> Result<Vec<u8>> {
+    match load_code(file)? {
+        Code::Upload(code) => Ok(code),
+        Code::Existing(_) => Err(anyhow!(
+            "No code found for {}: use a .contract bundle or a .wasm file",
+            file.display()
+        )),
+    }
+}
+
+/// Get the hash of the code of a contract from a contract file.
+///
+/// The hash of the code is computed for a .wasm file or a .contract bundle, as `pallet` does, and
+/// the code hash of the metadata is used for a .json metadata file.
+pub fn contract_code_hash(file: &Path, pallet: ContractsPallet) -> Result<Hash> {
+    match load_code(file)? {
+        Code::Upload(code) => Ok(pallet.code_hash(&code)),
+        Code::Existing(code_hash) => Ok(code_hash),
+    }
+}
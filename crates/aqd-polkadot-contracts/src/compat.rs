@@ -0,0 +1,158 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Context, Result},
+    contract_transcode::ContractMessageTranscoder,
+    serde_json::Value,
+    sp_core::blake2_256,
+    std::{
+        env, fmt, fs,
+        path::{Path, PathBuf},
+    },
+};
+
+/// The metadata versions supported, with the toolchains producing them.
+pub const SUPPORTED_METADATA_VERSIONS: &str =
+    "v4 (ink! 4, ink! 5.0.0-alpha, Solang 0.3 and later) and v5 (ink! 5)";
+
+/// A version of the format of the contract metadata.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MetadataVersion {
+    /// The format of ink! 4 and Solang 0.3 and later, read by the transcoder
+    V4,
+    /// The format of ink! 5, adapted to the format of ink! 4
+    V5,
+}
+
+impl fmt::Display for MetadataVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MetadataVersion::V4 => write!(f, "v4"),
+            MetadataVersion::V5 => write!(f, "v5"),
+        }
+    }
+}
+
+impl MetadataVersion {
+    /// Detect the version of the metadata of a contract.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error listing the supported versions if the version is not
+    /// supported, such as the v3 format of ink! 3 and Solang 0.2.
+    pub fn detect(metadata: &Value) -> Result<Self> {
+        let version = match metadata.get("version") {
+            Some(Value::String(version)) => version.clone(),
+            Some(Value::Number(version)) => version.to_string(),
+            Some(version) => {
+                return Err(anyhow!(
+                    "Invalid metadata version {}. Supported versions: {}",
+                    version,
+                    SUPPORTED_METADATA_VERSIONS
+                ))
+            }
+            None => {
+                // Before v4, the metadata was nested in an object named after its version
+                let legacy = ["V3", "V2", "V1"]
+                    .into_iter()
+                    .find(|version| metadata.get(version).is_some());
+                return Err(match legacy {
+                    Some(version) => anyhow!(
+                        "Unsupported metadata version {}: rebuild the contract with a newer toolchain. Supported versions: {}",
+                        version.to_lowercase(),
+                        SUPPORTED_METADATA_VERSIONS
+                    ),
+                    None => anyhow!(
+                        "Missing metadata version: not a contract metadata file. Supported versions: {}",
+                        SUPPORTED_METADATA_VERSIONS
+                    ),
+                });
+            }
+        };
+        match version.as_str() {
+            "4" => Ok(MetadataVersion::V4),
+            "5" => Ok(MetadataVersion::V5),
+            _ => Err(anyhow!(
+                "Unsupported metadata version v{}. Supported versions: {}",
+                version,
+                SUPPORTED_METADATA_VERSIONS
+            )),
+        }
+    }
+}
+
+/// Adapt the metadata of a contract to the v4 format read by the transcoder.
+///
+/// The v5 format only adds the signature topic and module path of the events, and numbers the
+/// version, so these are removed.
+pub fn adapt_metadata(mut metadata: Value) -> Result<(MetadataVersion, Value)> {
+    let version = MetadataVersion::detect(&metadata)?;
+    if version == MetadataVersion::V5 {
+        metadata["version"] = Value::String("4".to_string());
+        if let Some(events) = metadata
+            .pointer_mut("/spec/events")
+            .and_then(Value::as_array_mut)
+        {
+            for event in events.iter_mut().filter_map(Value::as_object_mut) {
+                event.remove("signature_topic");
+                event.remove("module_path");
+            }
+        }
+    }
+    Ok((version, metadata))
+}
+
+/// Read the metadata of a contract from a .contract bundle or a .json metadata file, adapted to
+/// the v4 format.
+pub fn read_metadata(file: &Path) -> Result<(MetadataVersion, Value)> {
+    let content = fs::read_to_string(file)
+        .map_err(|e| anyhow!("Failed to read {}: {}", file.display(), e))?;
+    let metadata: Value = serde_json::from_str(&content)
+        .map_err(|e| anyhow!("Failed to parse {}: {}", file.display(), e))?;
+    adapt_metadata(metadata).context(format!("Invalid metadata {}", file.display()))
+}
+
+/// Get the path of a contract file that can be read by the transcoder and `contract_extrinsics`.
+///
+/// Wasm files and v4 metadata are used as is. Other supported versions are adapted to a copy in
+/// the temporary directory, along with the wasm file next to a .json metadata file.
+pub fn compatible_contract_file(file: &Path) -> Result<PathBuf> {
+    match file.extension().and_then(|ext| ext.to_str()) {
+        Some("contract" | "json") => {}
+        _ => return Ok(file.to_path_buf()),
+    }
+    let (version, metadata) = read_metadata(file)?;
+    if version == MetadataVersion::V4 {
+        return Ok(file.to_path_buf());
+    }
+
+    // The copy is named after the hash of the adapted metadata, so that the copies of different
+    // contracts do not collide
+    let content = serde_json::to_string(&metadata)?;
+    let dir = env::temp_dir().join(format!(
+        "aqd-metadata-{}",
+        hex::encode(&blake2_256(content.as_bytes())[..8])
+    ));
+    let file_name = file
+        .file_name()
+        .ok_or_else(|| anyhow!("Invalid contract file {}", file.display()))?;
+    let adapted = dir.join(file_name);
+    let write = || -> std::io::Result<()> {
+        fs::create_dir_all(&dir)?;
+        fs::write(&adapted, &content)?;
+        let wasm = file.with_extension("wasm");
+        if wasm.exists() {
+            fs::copy(&wasm, adapted.with_extension("wasm"))?;
+        }
+        Ok(())
+    };
+    write().map_err(|e| anyhow!("Failed to write {}: {}", adapted.display(), e))?;
+    Ok(adapted)
+}
+
+/// Load the transcoder of a contract from a .contract bundle or a .json metadata file, adapting
+/// its metadata if needed.
+pub fn load_transcoder(file: &Path) -> Result<ContractMessageTranscoder> {
+    ContractMessageTranscoder::load(compatible_contract_file(file)?)
+        .context(format!("Failed to load metadata {}", file.display()))
+}
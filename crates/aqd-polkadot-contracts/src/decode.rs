@@ -0,0 +1,149 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Context, Result},
+    contract_transcode::{ContractMessageTranscoder, Value as ContractValue},
+    serde::Serialize,
+    serde_json::{json, Map, Value as JsonValue},
+    subxt::utils::H256,
+};
+
+/// An event emitted by a contract, decoded with the metadata of the contract.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ContractEvent {
+    /// The label of the event in the metadata
+    pub name: Option<String>,
+    /// The decoded fields, by name, or by position for events with unnamed fields
+    pub fields: Map<String, JsonValue>,
+}
+
+impl ContractEvent {
+    /// Convert an event decoded by the transcoder of a contract.
+    pub fn from_value(value: &ContractValue) -> Self {
+        let (name, fields) = match value {
+            ContractValue::Map(map) => (
+                map.ident(),
+                map.iter()
+                    .map(|(name, value)| (key_to_string(name), contract_value_to_json(value)))
+                    .collect(),
+            ),
+            ContractValue::Tuple(tuple) => (
+                tuple.ident(),
+                tuple
+                    .values()
+                    .enumerate()
+                    .map(|(index, value)| (index.to_string(), contract_value_to_json(value)))
+                    .collect(),
+            ),
+            value => (
+                None,
+                Map::from_iter([("0".to_string(), contract_value_to_json(value))]),
+            ),
+        };
+        ContractEvent { name, fields }
+    }
+}
+
+/// Decode the return value of a call of `message` of a contract, e.g. the data of a dry run, to
+/// JSON (see [`contract_value_to_json`]).
+///
+/// The return value of an ink! message is wrapped in a `Result`, e.g. `{"Ok": 42}`.
+///
+/// # Errors
+///
+/// This function returns an error if `message` is not a message of the contract, or if the data
+/// does not match its return type.
+pub fn decode_message_return(
+    transcoder: &ContractMessageTranscoder,
+    message: &str,
+    data: &[u8],
+) -> Result<JsonValue> {
+    let value = transcoder
+        .decode_message_return(message, &mut &data[..])
+        .context(format!(
+            "Failed to decode the return value of {}: 0x{}",
+            message,
+            hex::encode(data)
+        ))?;
+    Ok(contract_value_to_json(&value))
+}
+
+/// Decode the data of a `ContractEmitted` event with the metadata of the contract that emitted
+/// it. The event is identified by its signature topic, the first of the `topics` of the event.
+///
+/// # Errors
+///
+/// This function returns an error if the event has no topics, or if it does not match any event
+/// of the contract, e.g. if it was emitted by another contract.
+pub fn decode_contract_event(
+    transcoder: &ContractMessageTranscoder,
+    topics: &[H256],
+    data: &[u8],
+) -> Result<ContractEvent> {
+    let signature_topic = topics
+        .first()
+        .ok_or_else(|| anyhow!("The event 0x{} has no signature topic", hex::encode(data)))?;
+    let value = transcoder
+        .decode_contract_event(signature_topic, &mut &data[..])
+        .context(format!(
+            "Failed to decode the event 0x{}",
+            hex::encode(data)
+        ))?;
+    Ok(ContractEvent::from_value(&value))
+}
+
+/// Convert a value decoded with the metadata of a contract to JSON.
+///
+/// Structs are converted to objects, sequences and unnamed tuples to arrays, byte arrays to hex
+/// strings and integers that may not fit in a JSON number to strings, like [`value_to_json`]
+/// does. Enum variants without fields are converted to their name, and other variants to an
+/// object with the variant name as key, e.g. `{"Ok": 42}`.
+///
+/// [`value_to_json`]: crate::value_to_json
+pub fn contract_value_to_json(value: &ContractValue) -> JsonValue {
+    match value {
+        ContractValue::Bool(b) => json!(b),
+        ContractValue::Char(c) => json!(c.to_string()),
+        ContractValue::UInt(n) => match u64::try_from(*n) {
+            Ok(n) => json!(n),
+            Err(_) => json!(n.to_string()),
+        },
+        ContractValue::Int(n) => match i64::try_from(*n) {
+            Ok(n) => json!(n),
+            Err(_) => json!(n.to_string()),
+        },
+        ContractValue::String(s) | ContractValue::Literal(s) => json!(s),
+        ContractValue::Hex(hex) => json!(format!("0x{}", hex::encode(hex.bytes()))),
+        ContractValue::Seq(seq) => {
+            JsonValue::Array(seq.elems().iter().map(contract_value_to_json).collect())
+        }
+        ContractValue::Map(map) => JsonValue::Object(
+            map.iter()
+                .map(|(name, value)| (key_to_string(name), contract_value_to_json(value)))
+                .collect(),
+        ),
+        ContractValue::Tuple(tuple) => {
+            let mut values: Vec<JsonValue> = tuple.values().map(contract_value_to_json).collect();
+            let value = match values.len() {
+                0 => JsonValue::Null,
+                1 => values.remove(0),
+                _ => JsonValue::Array(values),
+            };
+            match tuple.ident() {
+                // A variant without fields, e.g. `None`
+                Some(ident) if tuple.values().next().is_none() => json!(ident),
+                Some(ident) => json!({ ident: value }),
+                None => value,
+            }
+        }
+        ContractValue::Unit => JsonValue::Null,
+    }
+}
+
+/// The name of a field of a decoded struct.
+fn key_to_string(key: &ContractValue) -> String {
+    match key {
+        ContractValue::String(s) | ContractValue::Literal(s) => s.clone(),
+        key => key.to_string(),
+    }
+}
@@ -0,0 +1,212 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::decode::ContractEvent,
+    anyhow::Result,
+    contract_extrinsics::DefaultConfig,
+    contract_transcode::{ContractMessageTranscoder, Value as ContractValue},
+    scale_info::{PortableRegistry, TypeDef, TypeDefPrimitive},
+    serde::Serialize,
+    serde_json::{json, Map, Value as JsonValue},
+    sp_core::H160,
+    subxt::{
+        blocks::ExtrinsicEvents,
+        ext::{
+            codec::Decode,
+            scale_value::{Composite, Primitive, Value, ValueDef},
+        },
+        utils::AccountId32,
+        Metadata,
+    },
+};
+
+/// An event of an extrinsic, with its fields decoded with the metadata of the node.
+#[derive(Clone, Debug, Serialize)]
+pub struct TypedEvent {
+    pub pallet: String,
+    pub name: String,
+    /// The decoded fields, by name, or by position for events with unnamed fields
+    pub fields: Map<String, JsonValue>,
+    /// The event of the contract, for `Contracts::ContractEmitted` and `Revive::ContractEmitted`
+    /// events emitted by the contract of the transcoder
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contract_event: Option<ContractValue>,
+    /// The documentation of the event in the metadata
+    pub docs: String,
+}
+
+impl TypedEvent {
+    /// The event of the contract, with its fields converted to JSON, if any.
+    pub fn decoded_contract_event(&self) -> Option<ContractEvent> {
+        self.contract_event.as_ref().map(ContractEvent::from_value)
+    }
+}
+
+/// Decode the events of an extrinsic into typed events.
+///
+/// Accounts are converted to SS58 addresses, byte arrays to hex strings and large integers to
+/// strings. The events emitted by contracts are decoded with `transcoder`, if given.
+pub fn typed_events(
+    events: &ExtrinsicEvents<DefaultConfig>,
+    transcoder: Option<&ContractMessageTranscoder>,
+    metadata: &Metadata,
+) -> Result<Vec<TypedEvent>> {
+    let mut typed_events = vec![];
+    for event in events.iter() {
+        let event = event?;
+        let event_metadata = event.event_metadata();
+        let values = event.field_values()?;
+
+        let mut fields = Map::new();
+        for (index, (field, value)) in event_metadata
+            .variant
+            .fields
+            .iter()
+            .zip(values.values())
+            .enumerate()
+        {
+            let name = field.name.clone().unwrap_or_else(|| index.to_string());
+            let mut value = value_to_json(value, metadata.types());
+            if let Some(type_name) = &field.type_name {
+                if type_name.contains("AccountId") {
+                    value = account_to_json(value);
+                }
+            }
+            fields.insert(name, value);
+        }
+
+        let contract_event = match (transcoder, event.pallet_name()) {
+            (Some(transcoder), pallet @ ("Contracts" | "Revive"))
+                if event.variant_name() == "ContractEmitted" =>
+            {
+                // pallet-revive addresses the contracts with 20 bytes
+                let data = match pallet {
+                    "Contracts" => <(AccountId32, Vec<u8>)>::decode(&mut event.field_bytes())?.1,
+                    _ => <(H160, Vec<u8>)>::decode(&mut event.field_bytes())?.1,
+                };
+                // Events emitted by other contracts cannot be decoded with the transcoder, and
+                // the events of ink! are identified by their first topic
+                event.topics().first().and_then(|signature_topic| {
+                    transcoder
+                        .decode_contract_event(signature_topic, &mut &data[..])
+                        .ok()
+                })
+            }
+            _ => None,
+        };
+
+        typed_events.push(TypedEvent {
+            pallet: event.pallet_name().to_string(),
+            name: event.variant_name().to_string(),
+            fields,
+            contract_event,
+            docs: event_metadata
+                .variant
+                .docs
+                .iter()
+                .map(|line| line.trim())
+                .collect::<Vec<_>>()
+                .join(" ")
+                .trim()
+                .to_string(),
+        });
+    }
+    Ok(typed_events)
+}
+
+/// Convert a decoded value to JSON, with the registry of the types it was decoded with.
+///
+/// Byte arrays (such as hashes and trie IDs) are converted to hex strings, and enum variants
+/// without fields (such as the determinism) to their name.
+pub fn value_to_json(value: &Value<u32>, types: &PortableRegistry) -> JsonValue {
+    match &value.value {
+        ValueDef::Composite(composite) => composite_to_json(composite, types),
+        ValueDef::Variant(variant) => {
+            if variant.values.is_empty() {
+                json!(variant.name)
+            } else {
+                let mut map = Map::new();
+                map.insert(
+                    variant.name.clone(),
+                    composite_to_json(&variant.values, types),
+                );
+                JsonValue::Object(map)
+            }
+        }
+        ValueDef::Primitive(primitive) => match primitive {
+            Primitive::Bool(b) => json!(b),
+            Primitive::Char(c) => json!(c.to_string()),
+            Primitive::String(s) => json!(s),
+            // Balances may not fit in a JSON number
+            Primitive::U128(n) => match u64::try_from(*n) {
+                Ok(n) => json!(n),
+                Err(_) => json!(n.to_string()),
+            },
+            Primitive::I128(n) => match i64::try_from(*n) {
+                Ok(n) => json!(n),
+                Err(_) => json!(n.to_string()),
+            },
+            Primitive::U256(bytes) | Primitive::I256(bytes) => {
+                json!(format!("0x{}", hex::encode(bytes)))
+            }
+        },
+        ValueDef::BitSequence(bits) => json!(format!("{bits:?}")),
+    }
+}
+
+/// Convert a composite value to JSON: named composites to objects, sequences of `u8` to hex
+/// strings, and other unnamed composites to arrays. A composite with a single unnamed field (such
+/// as a wrapper type) is converted to the JSON value of that field.
+fn composite_to_json(composite: &Composite<u32>, types: &PortableRegistry) -> JsonValue {
+    match composite {
+        Composite::Named(fields) => JsonValue::Object(
+            fields
+                .iter()
+                .map(|(name, value)| (name.clone(), value_to_json(value, types)))
+                .collect(),
+        ),
+        Composite::Unnamed(values) => {
+            // Only the values decoded as `u8` are bytes: a sequence of small integers of other
+            // types (such as a list of indexes) is kept as an array
+            let bytes: Option<Vec<u8>> = values
+                .iter()
+                .map(|value| match &value.value {
+                    ValueDef::Primitive(Primitive::U128(n)) if is_u8(value.context, types) => {
+                        u8::try_from(*n).ok()
+                    }
+                    _ => None,
+                })
+                .collect();
+            match (bytes, values.as_slice()) {
+                (Some(bytes), _) if bytes.len() > 1 => json!(format!("0x{}", hex::encode(bytes))),
+                (_, [value]) => value_to_json(value, types),
+                _ => JsonValue::Array(
+                    values
+                        .iter()
+                        .map(|value| value_to_json(value, types))
+                        .collect(),
+                ),
+            }
+        }
+    }
+}
+
+/// Whether a type of the registry is `u8`.
+fn is_u8(type_id: u32, types: &PortableRegistry) -> bool {
+    matches!(
+        types.resolve(type_id).map(|ty| &ty.type_def),
+        Some(TypeDef::Primitive(TypeDefPrimitive::U8))
+    )
+}
+
+/// Convert the hex string of an account to its SS58 address, leaving other values unchanged.
+fn account_to_json(value: JsonValue) -> JsonValue {
+    let account = value
+        .as_str()
+        .and_then(|hex| hex::decode(hex.trim_start_matches("0x")).ok())
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok());
+    match account {
+        Some(account) => json!(AccountId32::from(account).to_string()),
+        None => value,
+    }
+}
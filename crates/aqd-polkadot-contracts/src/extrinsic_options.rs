@@ -0,0 +1,226 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::{
+        balance::{Balance, TokenInfo},
+        client::{check_node_compatibility, connect_with_rpc},
+        compat::compatible_contract_file,
+        runtime::ContractsPallet,
+        submit::{Era, TxParams, WaitFor},
+    },
+    anyhow::{anyhow, Result},
+    contract_extrinsics::{state, BalanceVariant, DefaultConfig, ExtrinsicOptsBuilder},
+    serde::Serialize,
+    sp_weights::Weight,
+    std::path::PathBuf,
+    subxt::OnlineClient,
+    url::Url,
+};
+
+/// The options shared by all the extrinsics: the contract file, the node to connect to, the
+/// signer, the storage deposit limit, and the parameters of the signed extrinsic.
+#[derive(Clone, Debug)]
+pub struct ExtrinsicOptions {
+    file: PathBuf,
+    url: Url,
+    fallback_urls: Vec<Url>,
+    suri: String,
+    storage_deposit_limit: Option<Balance>,
+    token: Option<TokenInfo>,
+    tip: Option<Balance>,
+    era: Era,
+    nonce: Option<u64>,
+    wait_for: WaitFor,
+    sudo: bool,
+}
+
+impl ExtrinsicOptions {
+    /// Create the options of an extrinsic.
+    ///
+    /// `file` is the path to a contract wasm file, .contract bundle or .json metadata file, `url`
+    /// the websockets URL of the node, and `suri` the secret key URI of the signer
+    /// (e.g. `//Alice`).
+    pub fn new(file: impl Into<PathBuf>, url: Url, suri: impl Into<String>) -> Self {
+        ExtrinsicOptions {
+            file: file.into(),
+            url,
+            fallback_urls: vec![],
+            suri: suri.into(),
+            storage_deposit_limit: None,
+            token: None,
+            tip: None,
+            era: Era::Immortal,
+            nonce: None,
+            wait_for: WaitFor::Finalized,
+            sudo: false,
+        }
+    }
+
+    /// Set the websockets URLs of other nodes of the network, tried in order when the node of the
+    /// options cannot be reached.
+    pub fn fallback_urls(mut self, fallback_urls: Vec<Url>) -> Self {
+        self.fallback_urls = fallback_urls;
+        self
+    }
+
+    /// Set the maximum amount of balance that can be charged from the signer for the storage
+    /// consumed.
+    pub fn storage_deposit_limit(mut self, storage_deposit_limit: Option<Balance>) -> Self {
+        self.storage_deposit_limit = storage_deposit_limit;
+        self
+    }
+
+    /// Set the decimals and symbol of the network token, instead of querying them from the node.
+    pub fn token(mut self, token: TokenInfo) -> Self {
+        self.token = Some(token);
+        self
+    }
+
+    /// Set the tip paid to the block author, to prioritize the extrinsic.
+    pub fn tip(mut self, tip: Option<Balance>) -> Self {
+        self.tip = tip;
+        self
+    }
+
+    /// Set the mortality of the extrinsic, immortal by default.
+    pub fn era(mut self, era: Era) -> Self {
+        self.era = era;
+        self
+    }
+
+    /// Set the nonce of the signer account, instead of querying it from the node.
+    pub fn nonce(mut self, nonce: Option<u64>) -> Self {
+        self.nonce = nonce;
+        self
+    }
+
+    /// Set whether to wait for the extrinsic to be included in a block or finalized, finalized by
+    /// default.
+    pub fn wait_for(mut self, wait_for: WaitFor) -> Self {
+        self.wait_for = wait_for;
+        self
+    }
+
+    /// Set whether to dispatch the extrinsic with the root origin through `Sudo::sudo`, the signer
+    /// being the sudo key of a development chain.
+    pub fn sudo(mut self, sudo: bool) -> Self {
+        self.sudo = sudo;
+        self
+    }
+
+    /// Returns the path to the contract file.
+    pub fn file(&self) -> &PathBuf {
+        &self.file
+    }
+
+    /// Returns the websockets URL of the node.
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Returns the websockets URLs of the nodes, in the order they are tried.
+    pub fn urls(&self) -> Vec<Url> {
+        let mut urls = vec![self.url.clone()];
+        urls.extend(self.fallback_urls.iter().cloned());
+        urls
+    }
+
+    /// Returns the secret key URI of the signer.
+    pub fn suri(&self) -> &str {
+        &self.suri
+    }
+
+    /// Connect to the first node that can be reached, check that it can run contracts and detect
+    /// the pallet running them. The decimals and symbol of the network token are returned too,
+    /// queried from the node if they were not set.
+    ///
+    /// See [`connect_with_rpc`] and [`check_node_compatibility`].
+    pub(crate) async fn connect(
+        &self,
+    ) -> Result<(Url, OnlineClient<DefaultConfig>, ContractsPallet, TokenInfo)> {
+        let (url, client, rpc) = connect_with_rpc(&self.urls()).await?;
+        let pallet = check_node_compatibility(&client).map_err(|e| anyhow!("{}: {}", url, e))?;
+        let token = match &self.token {
+            Some(token) => token.clone(),
+            None => TokenInfo::query(&rpc).await?,
+        };
+        Ok((url, client, pallet, token))
+    }
+
+    /// Returns the storage deposit limit in the smallest unit of the network token.
+    pub(crate) fn denominated_storage_deposit_limit(
+        &self,
+        token: &TokenInfo,
+    ) -> Result<Option<u128>> {
+        match &self.storage_deposit_limit {
+            Some(limit) => Ok(Some(limit.denominate(token)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the parameters of the signed extrinsic, with the tip in the smallest unit of the
+    /// network token.
+    pub fn tx_params(&self, token: &TokenInfo) -> Result<TxParams> {
+        Ok(TxParams {
+            tip: match &self.tip {
+                Some(tip) => tip.denominate(token)?,
+                None => 0,
+            },
+            era: self.era,
+            nonce: self.nonce,
+            wait_for: self.wait_for,
+            sudo: self.sudo,
+        })
+    }
+
+    /// Convert the options to the builder of the options of `contract_extrinsics`, connecting to
+    /// the node at `url`. The options themselves are built by the extrinsic builders, as their
+    /// type is not exported by `contract_extrinsics`.
+    pub(crate) fn extrinsic_opts_builder(
+        &self,
+        token: &TokenInfo,
+        url: &Url,
+    ) -> Result<ExtrinsicOptsBuilder<state::Suri>> {
+        Ok(ExtrinsicOptsBuilder::default()
+            .file(Some(compatible_contract_file(&self.file)?))
+            .url(url.clone())
+            .suri(self.suri.clone())
+            .storage_deposit_limit(
+                self.denominated_storage_deposit_limit(token)?
+                    .map(BalanceVariant::Default),
+            ))
+    }
+}
+
+/// An amount of gas, made of the computation time and the proof size of the Contracts pallet
+/// weights.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct Gas {
+    pub ref_time: u64,
+    pub proof_size: u64,
+}
+
+impl From<Weight> for Gas {
+    fn from(weight: Weight) -> Self {
+        Gas {
+            ref_time: weight.ref_time(),
+            proof_size: weight.proof_size(),
+        }
+    }
+}
+
+impl From<Gas> for Weight {
+    fn from(gas: Gas) -> Self {
+        Weight::from_parts(gas.ref_time, gas.proof_size)
+    }
+}
+
+impl std::fmt::Display for Gas {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "ref_time: {}, proof_size: {}",
+            self.ref_time, self.proof_size
+        )
+    }
+}
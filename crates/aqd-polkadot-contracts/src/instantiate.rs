@@ -0,0 +1,518 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::{
+        balance::{Balance, TokenInfo},
+        code::{load_code, Code},
+        debug_output,
+        events::{typed_events, TypedEvent},
+        extrinsic_options::{ExtrinsicOptions, Gas},
+        runtime::{
+            ensure_account_mapped, revive_account, revive_address, revive_contract_address,
+            ContractsPallet,
+        },
+        serialize_storage_deposit,
+        submit::{
+            decode_events, estimate_fee, extrinsic_block, option_value, pair_signer,
+            submit_extrinsic, weight_value, CostEstimate, ExtrinsicBlock, FeeEstimate, TxParams,
+        },
+        with_debug_output,
+    },
+    anyhow::{anyhow, Result},
+    contract_extrinsics::{
+        DefaultConfig, DisplayEvents, InstantiateArgs, InstantiateCommandBuilder, InstantiateExec,
+        TokenMetadata,
+    },
+    contract_transcode::{ContractMessageTranscoder, Value},
+    pallet_contracts_primitives::{Code as RuntimeCode, ContractInstantiateResult, StorageDeposit},
+    serde::Serialize,
+    sp_core::{blake2_256, Bytes, H160},
+    subxt::{
+        dynamic::Value as DynamicValue,
+        ext::codec::{Compact, Encode},
+        tx::DynamicPayload,
+        Config, OnlineClient,
+    },
+    subxt_signer::sr25519::Keypair,
+};
+
+type AccountId = <DefaultConfig as Config>::AccountId;
+type Hash = <DefaultConfig as Config>::Hash;
+
+/// An instantiation of a contract on a Polkadot node.
+pub struct PolkadotInstantiate {
+    exec: InstantiateExec,
+    token: TokenInfo,
+    value: u128,
+    storage_deposit_limit: Option<u128>,
+    code: Code,
+    salt: Vec<u8>,
+    signer: Keypair,
+    tx_params: TxParams,
+    pallet: ContractsPallet,
+}
+
+/// A builder for configuring and constructing a [`PolkadotInstantiate`].
+pub struct PolkadotInstantiateBuilder {
+    options: ExtrinsicOptions,
+    constructor: String,
+    args: Vec<String>,
+    value: Balance,
+    gas_limit: Option<u64>,
+    proof_size: Option<u64>,
+    salt: Option<Bytes>,
+}
+
+/// The result of a dry run of an instantiation.
+#[derive(Debug, Serialize)]
+pub struct InstantiateDryRunResult {
+    /// The decoded result of the constructor
+    pub result: Value,
+    pub reverted: bool,
+    /// The address of the contract that would be instantiated
+    pub contract: String,
+    /// The address of the contract derived from the signer, code hash, input data and salt
+    pub predicted_address: String,
+    /// The salt of the address derivation, as hex
+    pub salt: String,
+    pub gas_consumed: Gas,
+    pub gas_required: Gas,
+    #[serde(serialize_with = "serialize_storage_deposit")]
+    pub storage_deposit: StorageDeposit<u128>,
+    /// The lines of the debug output of the contract
+    pub debug_output: Vec<String>,
+}
+
+/// The result of a submitted instantiation.
+#[derive(Serialize)]
+pub struct InstantiateResult {
+    pub contract: String,
+    /// The hash of the code, if it was uploaded along with the instantiation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code_hash: Option<String>,
+    /// The salt of the address derivation, as hex
+    pub salt: String,
+    pub events: Vec<TypedEvent>,
+    /// The block the instantiation was included in
+    pub block: ExtrinsicBlock,
+    /// The events of the instantiation, to display them
+    #[serde(skip)]
+    pub display_events: DisplayEvents,
+    /// The token metadata of the network, used to display the events
+    #[serde(skip)]
+    pub token_metadata: TokenMetadata,
+}
+
+impl InstantiateResult {
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+impl PolkadotInstantiateBuilder {
+    /// Set the name of the constructor to call, `new` by default.
+    pub fn constructor(mut self, constructor: impl Into<String>) -> Self {
+        self.constructor = constructor.into();
+        self
+    }
+
+    /// Set the arguments of the constructor.
+    pub fn args(mut self, args: Vec<String>) -> Self {
+        self.args = args;
+        self
+    }
+
+    /// Set the value to transfer to the contract.
+    pub fn value(mut self, value: Balance) -> Self {
+        self.value = value;
+        self
+    }
+
+    /// Set the maximum computation time of the instantiation, estimated by default.
+    pub fn gas_limit(mut self, gas_limit: Option<u64>) -> Self {
+        self.gas_limit = gas_limit;
+        self
+    }
+
+    /// Set the maximum proof size of the instantiation, estimated by default.
+    pub fn proof_size(mut self, proof_size: Option<u64>) -> Self {
+        self.proof_size = proof_size;
+        self
+    }
+
+    /// Set the salt used in the address derivation of the new contract.
+    pub fn salt(mut self, salt: Option<Bytes>) -> Self {
+        self.salt = salt;
+        self
+    }
+
+    /// Build the instantiation, loading the contract file and connecting to the node.
+    pub async fn done(self) -> Result<PolkadotInstantiate> {
+        // The dry runs and the submission use the node connected to first
+        let (url, _, pallet, token) = self.options.connect().await?;
+        let value = self.value.denominate(&token)?;
+        let storage_deposit_limit = self.options.denominated_storage_deposit_limit(&token)?;
+        let code = load_code(self.options.file())?;
+        let salt = self.salt.clone().map(|salt| salt.0).unwrap_or_default();
+        if pallet == ContractsPallet::Revive && !salt.is_empty() && salt.len() != 32 {
+            return Err(anyhow!(
+                "Invalid salt: pallet-revive expects a salt of 32 bytes, got {} bytes",
+                salt.len()
+            ));
+        }
+        let signer = pair_signer(self.options.suri())?;
+        let tx_params = self.options.tx_params(&token)?;
+        let exec = InstantiateCommandBuilder::default()
+            .constructor(self.constructor)
+            .args(self.args)
+            .extrinsic_opts(self.options.extrinsic_opts_builder(&token, &url)?.done())
+            .value(self.value.to_variant(&token)?)
+            .gas_limit(self.gas_limit)
+            .proof_size(self.proof_size)
+            .salt(self.salt)
+            .done()
+            .await?;
+        Ok(PolkadotInstantiate {
+            exec,
+            token,
+            value,
+            storage_deposit_limit,
+            code,
+            salt,
+            signer,
+            tx_params,
+            pallet,
+        })
+    }
+}
+
+impl PolkadotInstantiate {
+    /// Create a builder for the instantiation of the contract file of the options.
+    pub fn builder(options: ExtrinsicOptions) -> PolkadotInstantiateBuilder {
+        PolkadotInstantiateBuilder {
+            options,
+            constructor: "new".to_string(),
+            args: vec![],
+            value: Balance::Raw(0),
+            gas_limit: None,
+            proof_size: None,
+            salt: None,
+        }
+    }
+
+    /// Get the arguments of the instantiation
+    pub fn args(&self) -> &InstantiateArgs {
+        self.exec.args()
+    }
+
+    /// Get the value transferred to the contract, in the smallest unit of the network token
+    pub fn value(&self) -> u128 {
+        self.value
+    }
+
+    /// Get the decimals and symbol of the network token
+    pub fn token(&self) -> &TokenInfo {
+        &self.token
+    }
+
+    /// Get the transcoder of the contract
+    pub fn transcoder(&self) -> &ContractMessageTranscoder {
+        self.exec.transcoder()
+    }
+
+    /// Get the client connected to the node
+    pub fn client(&self) -> &OnlineClient<DefaultConfig> {
+        self.exec.client()
+    }
+
+    /// Get the pallet running the contracts of the node
+    pub fn pallet(&self) -> ContractsPallet {
+        self.pallet
+    }
+
+    /// Dry-run the instantiation, without submitting any extrinsic.
+    pub async fn dry_run(&self) -> Result<InstantiateDryRunResult> {
+        if self.pallet == ContractsPallet::Revive {
+            return self.revive_dry_run().await;
+        }
+        let result = self.exec.instantiate_dry_run().await?;
+        let debug_output = debug_output(&result.debug_message);
+        if let Err(err) = &result.result {
+            return Err(with_debug_output(
+                format!("Error instantiating the contract: {:?}", err),
+                &debug_output,
+            ));
+        }
+        let decoded = self
+            .exec
+            .decode_instantiate_dry_run(&result)
+            .await
+            .map_err(|e| anyhow!("Failed to decode instantiate dry run result: {}", e))?;
+        Ok(InstantiateDryRunResult {
+            result: decoded.result,
+            reverted: decoded.reverted,
+            contract: decoded.contract,
+            predicted_address: self.predicted_address()?.to_string(),
+            salt: self.salt_hex(),
+            gas_consumed: result.gas_consumed.into(),
+            gas_required: result.gas_required.into(),
+            storage_deposit: result.storage_deposit,
+            debug_output,
+        })
+    }
+
+    /// Dry-run the instantiation on pallet-revive, which `contract_extrinsics` does not support,
+    /// with the `ReviveApi_instantiate` runtime API.
+    async fn revive_dry_run(&self) -> Result<InstantiateDryRunResult> {
+        let result = self.revive_instantiate_rpc().await?;
+        let debug_output = debug_output(&result.debug_message);
+        let ret_val = result.result.map_err(|err| {
+            with_debug_output(
+                format!("Error instantiating the contract: {:?}", err),
+                &debug_output,
+            )
+        })?;
+        let decoded = self
+            .transcoder()
+            .decode_constructor_return(self.args().constructor(), &mut &ret_val.result.data[..])
+            .map_err(|e| anyhow!("Failed to decode instantiate dry run result: {}", e))?;
+        let contract = format!("{:?}", ret_val.account_id);
+        // Without a salt, the address depends on the nonce of the signer, as reported by the
+        // dry run
+        let predicted_address = match self.salt.is_empty() {
+            true => contract.clone(),
+            false => self.pallet.format_address(&self.predicted_address()?),
+        };
+        Ok(InstantiateDryRunResult {
+            result: decoded,
+            reverted: ret_val.result.did_revert(),
+            contract,
+            predicted_address,
+            salt: self.salt_hex(),
+            gas_consumed: result.gas_consumed.into(),
+            gas_required: result.gas_required.into(),
+            storage_deposit: result.storage_deposit,
+            debug_output,
+        })
+    }
+
+    /// Call the `ReviveApi_instantiate` runtime API, with the signer as caller and without gas
+    /// limit.
+    async fn revive_instantiate_rpc(&self) -> Result<ContractInstantiateResult<H160, u128, ()>> {
+        let origin = AccountId::from(self.signer.public_key().0);
+        let gas_limit: Option<(Compact<u64>, Compact<u64>)> = None;
+        let code = match &self.code {
+            Code::Upload(code) => RuntimeCode::Upload(code.clone()),
+            Code::Existing(code_hash) => RuntimeCode::Existing(*code_hash),
+        };
+        let parameters = (
+            origin,
+            self.value,
+            gas_limit,
+            self.storage_deposit_limit,
+            code,
+            self.input_data()?,
+            self.revive_salt(),
+        )
+            .encode();
+        let result = self
+            .client()
+            .runtime_api()
+            .at_latest()
+            .await?
+            .call_raw(&self.pallet.runtime_api("instantiate"), Some(&parameters))
+            .await?;
+        Ok(result)
+    }
+
+    /// Estimate the gas required by the instantiation, with a dry run.
+    pub async fn estimate_gas(&self) -> Result<Gas> {
+        if self.pallet == ContractsPallet::Revive {
+            let result = self.revive_instantiate_rpc().await?;
+            result.result.map_err(|err| {
+                anyhow!(
+                    "Pre-submission dry run of the instantiation failed: {:?}",
+                    err
+                )
+            })?;
+            return Ok(result.gas_required.into());
+        }
+        Ok(self.exec.estimate_gas().await?.into())
+    }
+
+    /// Get the encoded input data of the instantiation: the selector of the constructor and its
+    /// arguments
+    pub fn input_data(&self) -> Result<Vec<u8>> {
+        let args = self.exec.args();
+        self.transcoder()
+            .encode(args.constructor(), args.raw_args())
+    }
+
+    /// Get the hash of the code of the contract
+    pub fn code_hash(&self) -> Hash {
+        match &self.code {
+            Code::Upload(code) => self.pallet.code_hash(code),
+            Code::Existing(code_hash) => *code_hash,
+        }
+    }
+
+    /// Get the salt of the address derivation
+    pub fn salt(&self) -> &[u8] {
+        &self.salt
+    }
+
+    /// Get the salt of the address derivation, as hex
+    fn salt_hex(&self) -> String {
+        format!("0x{}", hex::encode(&self.salt))
+    }
+
+    /// Get the salt of the address derivation on pallet-revive, which derives the address from
+    /// the nonce of the signer without a salt
+    fn revive_salt(&self) -> Option<[u8; 32]> {
+        self.salt.as_slice().try_into().ok()
+    }
+
+    /// Get the address the contract will be instantiated at, without any dry run.
+    ///
+    /// On pallet-revive, the address can only be predicted when the code is uploaded along with
+    /// the instantiation and a salt is used, as the address is derived from the nonce of the
+    /// signer otherwise. The account of the contract is returned, see [`ContractsPallet::format_address`].
+    pub fn predicted_address(&self) -> Result<AccountId> {
+        let deployer = AccountId::from(self.signer.public_key().0);
+        if self.pallet == ContractsPallet::Revive {
+            let (Code::Upload(code), Some(salt)) = (&self.code, self.revive_salt()) else {
+                return Err(anyhow!(
+                    "The address of a contract on pallet-revive can only be predicted with a salt and the code of the contract"
+                ));
+            };
+            let address = revive_contract_address(
+                &revive_address(&deployer),
+                code,
+                &self.input_data()?,
+                &salt,
+            );
+            return Ok(revive_account(&address));
+        }
+        Ok(contract_address(
+            &deployer,
+            &self.code_hash(),
+            &self.input_data()?,
+            &self.salt,
+        ))
+    }
+
+    /// Get the `instantiate_with_code` extrinsic of the pallet if the code of the contract is
+    /// uploaded along with the instantiation, and its `instantiate` extrinsic otherwise.
+    fn payload(&self, gas_limit: Gas) -> Result<DynamicPayload> {
+        let data = self.input_data()?;
+        let (call, code) = match &self.code {
+            Code::Upload(code) => (
+                "instantiate_with_code",
+                ("code", DynamicValue::from_bytes(code)),
+            ),
+            Code::Existing(code_hash) => (
+                "instantiate",
+                ("code_hash", DynamicValue::from_bytes(code_hash.0)),
+            ),
+        };
+        // The salt is optional on pallet-revive
+        let salt = match self.pallet {
+            ContractsPallet::Contracts => DynamicValue::from_bytes(&self.salt),
+            ContractsPallet::Revive => {
+                option_value(self.revive_salt().map(DynamicValue::from_bytes))
+            }
+        };
+        Ok(subxt::dynamic::tx(
+            self.pallet.name(),
+            call,
+            vec![
+                ("value", DynamicValue::u128(self.value)),
+                ("gas_limit", weight_value(gas_limit)),
+                (
+                    "storage_deposit_limit",
+                    self.pallet
+                        .storage_deposit_limit_value(self.storage_deposit_limit),
+                ),
+                code,
+                ("data", DynamicValue::from_bytes(data)),
+                ("salt", salt),
+            ],
+        ))
+    }
+
+    /// Estimate the fee and the weight of the instantiation with the given gas limit, without
+    /// submitting it.
+    pub async fn estimate_fee(&self, gas_limit: Gas) -> Result<FeeEstimate> {
+        let call = self.payload(gas_limit)?;
+        estimate_fee(self.client(), &call, &self.signer, &self.tx_params).await
+    }
+
+    /// Estimate the cost of the instantiation with a dry run, without submitting it.
+    pub async fn estimate(&self) -> Result<CostEstimate> {
+        let result = self.dry_run().await?;
+        let gas_limit = self.estimate_gas().await?;
+        let fee = self.estimate_fee(gas_limit).await?;
+        Ok(CostEstimate {
+            partial_fee: fee.partial_fee,
+            weight: fee.weight,
+            gas_limit: Some(gas_limit),
+            storage_deposit: result.storage_deposit,
+        })
+    }
+
+    /// Submit the instantiation with the given gas limit, and wait for it to be included in a block
+    /// or finalized.
+    pub async fn submit(&self, gas_limit: Gas) -> Result<InstantiateResult> {
+        let call = self.payload(gas_limit)?;
+        if self.pallet == ContractsPallet::Revive {
+            ensure_account_mapped(self.client(), &self.signer, &self.tx_params).await?;
+        }
+        let events = submit_extrinsic(self.client(), &call, &self.signer, &self.tx_params)
+            .await
+            .map_err(|err| anyhow!("Error instantiating the contract: {}", err))?;
+
+        // A constructor can instantiate other contracts, the instantiated contract is the last one
+        let pallet = self.pallet.name();
+        let contract = match self.pallet {
+            ContractsPallet::Contracts => {
+                decode_events::<(AccountId, AccountId)>(&events, pallet, "Instantiated")?
+                    .pop()
+                    .map(|(_, contract)| contract)
+            }
+            ContractsPallet::Revive => {
+                decode_events::<(H160, H160)>(&events, pallet, "Instantiated")?
+                    .pop()
+                    .map(|(_, contract)| revive_account(&contract))
+            }
+        }
+        .ok_or_else(|| anyhow!("Failed to find the Instantiated event"))?;
+        let code_hash = decode_events::<Hash>(&events, pallet, "CodeStored")?.pop();
+        let display_events = DisplayEvents::from_events(
+            &events,
+            Some(self.transcoder()),
+            &self.client().metadata(),
+        )?;
+        Ok(InstantiateResult {
+            contract: self.pallet.format_address(&contract),
+            code_hash: code_hash.map(|code_hash| format!("{code_hash:?}")),
+            salt: self.salt_hex(),
+            events: typed_events(&events, Some(self.transcoder()), &self.client().metadata())?,
+            block: extrinsic_block(self.client(), &events).await?,
+            display_events,
+            token_metadata: self.token.token_metadata(),
+        })
+    }
+}
+
+/// Derive the address of a contract the way the Contracts pallet does, from the account deploying
+/// it, the hash of its code, the input data of the constructor and the salt.
+pub fn contract_address(
+    deployer: &AccountId,
+    code_hash: &Hash,
+    input_data: &[u8],
+    salt: &[u8],
+) -> AccountId {
+    let entropy =
+        (b"contract_addr_v1", deployer, code_hash, input_data, salt).using_encoded(blake2_256);
+    AccountId::from(entropy)
+}
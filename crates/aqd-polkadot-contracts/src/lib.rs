@@ -0,0 +1,85 @@
+// SPDX-License-Identifier: Apache-2.0
+
+mod address;
+mod balance;
+mod call;
+mod cassette_proxy;
+mod client;
+mod code;
+mod compat;
+mod decode;
+mod events;
+mod extrinsic_options;
+mod instantiate;
+mod metadata;
+mod network_proxy;
+mod runtime;
+mod submit;
+mod upload;
+
+pub use {
+    address::{
+        parse_public_key, parse_ss58_prefix, ss58_network, Ss58Address, SUBSTRATE_SS58_PREFIX,
+    },
+    balance::{free_balance, Balance, TokenInfo},
+    call::{dry_run_call, CallDryRunResult, CallResult, PolkadotCall, PolkadotCallBuilder},
+    client::{check_node_compatibility, connect, connect_with_rpc},
+    code::contract_code_hash,
+    compat::{
+        adapt_metadata, compatible_contract_file, load_transcoder, read_metadata, MetadataVersion,
+        SUPPORTED_METADATA_VERSIONS,
+    },
+    decode::{contract_value_to_json, decode_contract_event, decode_message_return, ContractEvent},
+    events::{typed_events, value_to_json, TypedEvent},
+    extrinsic_options::{ExtrinsicOptions, Gas},
+    instantiate::{
+        contract_address, InstantiateDryRunResult, InstantiateResult, PolkadotInstantiate,
+        PolkadotInstantiateBuilder,
+    },
+    metadata::{ArgSpec, ContractSpec, MessageSpec, StorageLayout},
+    runtime::{
+        ensure_account_mapped, is_account_mapped, parse_contract_address, revive_account,
+        revive_address, revive_contract_address, ContractsPallet,
+    },
+    submit::{
+        assemble_signed_extrinsic, contracts_call_payload, decode_events, estimate_fee,
+        export_call, extrinsic_block, pair_signer, proxy_payload, submit_extrinsic,
+        submit_signed_extrinsic, sudo_payload, CostEstimate, Era, ExportedCall, ExtrinsicBlock,
+        FeeEstimate, TxParams, WaitFor,
+    },
+    upload::{PolkadotUpload, UploadDryRunResult, UploadResult},
+};
+
+use {pallet_contracts_primitives::StorageDeposit, serde::Serialize};
+
+/// Split the debug message of a dry run into the lines of its debug output.
+///
+/// The debug message gathers the messages printed by the contract, such as the reason of a
+/// `require` failing, along with the traces of the pallet.
+fn debug_output(debug_message: &[u8]) -> Vec<String> {
+    String::from_utf8_lossy(debug_message)
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Append the debug output of a failed dry run to its error, to diagnose it.
+///
+/// The error is tagged as a [`aqd_utils::ErrorKind::SimulationRevert`].
+pub fn with_debug_output(error: String, debug_output: &[String]) -> anyhow::Error {
+    let error = if debug_output.is_empty() {
+        anyhow::anyhow!(error)
+    } else {
+        anyhow::anyhow!("{}\nDebug output:\n{}", error, debug_output.join("\n"))
+    };
+    aqd_utils::ErrorKind::SimulationRevert.wrap(error)
+}
+
+/// Serialize a storage deposit the way `contract_extrinsics` does, e.g. `{"Charge": 1000}`.
+fn serialize_storage_deposit<S: serde::Serializer>(
+    deposit: &StorageDeposit<u128>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    contract_extrinsics::StorageDeposit::from(deposit).serialize(serializer)
+}
@@ -0,0 +1,462 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::compat::adapt_metadata,
+    anyhow::{anyhow, Result},
+    serde::{Deserialize, Serialize},
+    serde_json::Value,
+    std::{collections::HashMap, fs, path::Path},
+};
+
+/// The constructors and messages of a contract, read from its metadata.
+#[derive(Debug, Serialize)]
+pub struct ContractSpec {
+    pub name: String,
+    pub version: String,
+    /// The version of the metadata format
+    pub metadata_version: String,
+    pub constructors: Vec<MessageSpec>,
+    pub messages: Vec<MessageSpec>,
+}
+
+/// A constructor or a message of a contract.
+#[derive(Debug, Serialize)]
+pub struct MessageSpec {
+    pub label: String,
+    /// The selector of the constructor or message, as hex
+    pub selector: String,
+    pub args: Vec<ArgSpec>,
+    /// Whether the message can modify the storage of the contract, always `true` for constructors
+    pub mutates: bool,
+    pub payable: bool,
+    /// The type returned, `None` if nothing is returned
+    pub return_type: Option<String>,
+    pub docs: String,
+}
+
+/// An argument of a constructor or a message.
+#[derive(Debug, Serialize)]
+pub struct ArgSpec {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+}
+
+/// A node of the storage layout of a contract, read from its metadata.
+#[derive(Debug, Serialize)]
+pub struct StorageLayout {
+    /// The name of the field, struct, enum variant or array element
+    pub name: String,
+    /// The kind of layout: `root`, `leaf`, `struct`, `enum`, `array` or `hash`
+    pub kind: String,
+    /// The storage key of a root or a leaf, as hex
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// The type of a leaf
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub ty: Option<String>,
+    /// The path of a leaf whose value can be read from the contract root storage (e.g.
+    /// `balances.total`), `None` for leaves of mappings and enums
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub docs: String,
+    /// The value of a leaf, when it is read from the contract storage
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<Value>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<StorageLayout>,
+}
+
+#[derive(Deserialize)]
+struct RawLayoutMetadata {
+    #[serde(default)]
+    storage: Option<Value>,
+    #[serde(default)]
+    types: Vec<RawType>,
+}
+
+#[derive(Deserialize)]
+struct RawMetadata {
+    contract: RawContract,
+    spec: RawSpec,
+    #[serde(default)]
+    types: Vec<RawType>,
+}
+
+#[derive(Deserialize)]
+struct RawContract {
+    name: String,
+    version: String,
+}
+
+#[derive(Deserialize)]
+struct RawSpec {
+    constructors: Vec<RawMessage>,
+    messages: Vec<RawMessage>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawMessage {
+    label: String,
+    selector: String,
+    #[serde(default)]
+    args: Vec<RawArg>,
+    #[serde(default)]
+    mutates: Option<bool>,
+    #[serde(default)]
+    payable: bool,
+    #[serde(default)]
+    return_type: Option<RawTypeSpec>,
+    #[serde(default)]
+    docs: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct RawArg {
+    label: String,
+    #[serde(rename = "type")]
+    ty: RawTypeSpec,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawTypeSpec {
+    #[serde(default)]
+    display_name: Vec<String>,
+    #[serde(rename = "type")]
+    id: u32,
+}
+
+#[derive(Deserialize)]
+struct RawType {
+    id: u32,
+    #[serde(rename = "type")]
+    ty: RawTypeDef,
+}
+
+#[derive(Deserialize)]
+struct RawTypeDef {
+    def: Value,
+    #[serde(default)]
+    docs: Vec<String>,
+    #[serde(default)]
+    path: Vec<String>,
+    #[serde(default)]
+    params: Vec<RawTypeParam>,
+}
+
+#[derive(Deserialize)]
+struct RawTypeParam {
+    #[serde(rename = "type")]
+    id: Option<u32>,
+}
+
+impl ContractSpec {
+    /// Read the spec of a contract from a .contract bundle or a .json metadata file.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+        Self::from_json(&content).map_err(|e| anyhow!("Invalid metadata {}: {}", path.display(), e))
+    }
+
+    /// Read the spec of a contract from the JSON of its metadata.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let (version, metadata) = adapt_metadata(serde_json::from_str(json)?)?;
+        let metadata: RawMetadata = serde_json::from_value(metadata)?;
+        let types: HashMap<u32, &RawTypeDef> =
+            metadata.types.iter().map(|ty| (ty.id, &ty.ty)).collect();
+        let messages = |raw: &[RawMessage]| -> Vec<MessageSpec> {
+            raw.iter()
+                .map(|message| MessageSpec::new(message, &types))
+                .collect()
+        };
+        Ok(ContractSpec {
+            name: metadata.contract.name,
+            version: metadata.contract.version,
+            metadata_version: version.to_string(),
+            constructors: messages(&metadata.spec.constructors),
+            messages: messages(&metadata.spec.messages),
+        })
+    }
+
+    /// Find a constructor or a message by its label.
+    pub fn find(&self, label: &str) -> Option<&MessageSpec> {
+        self.constructors
+            .iter()
+            .chain(self.messages.iter())
+            .find(|message| message.label == label)
+    }
+}
+
+impl StorageLayout {
+    /// Read the storage layout of a contract from a .contract bundle or a .json metadata file.
+    ///
+    /// Returns `None` if the metadata does not describe the storage of the contract.
+    pub fn from_file(path: &Path) -> Result<Option<Self>> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+        Self::from_json(&content).map_err(|e| anyhow!("Invalid metadata {}: {}", path.display(), e))
+    }
+
+    /// Read the storage layout of a contract from the JSON of its metadata.
+    pub fn from_json(json: &str) -> Result<Option<Self>> {
+        let (_, metadata) = adapt_metadata(serde_json::from_str(json)?)?;
+        let metadata: RawLayoutMetadata = serde_json::from_value(metadata)?;
+        let types: HashMap<u32, &RawTypeDef> =
+            metadata.types.iter().map(|ty| (ty.id, &ty.ty)).collect();
+        match &metadata.storage {
+            Some(layout) => Ok(Some(Self::new("storage", layout, "", true, &types)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Get the leaves of the layout, in the order they are encoded.
+    pub fn leaves_mut(&mut self) -> Vec<&mut StorageLayout> {
+        if self.children.is_empty() {
+            return if self.kind == "leaf" {
+                vec![self]
+            } else {
+                vec![]
+            };
+        }
+        self.children
+            .iter_mut()
+            .flat_map(|child| child.leaves_mut())
+            .collect()
+    }
+
+    /// Build a node of the layout.
+    ///
+    /// The paths of the leaves follow the struct fields and array elements from the root, the way
+    /// the storage of the contract is read. `readable` is `false` under mappings and enums, whose
+    /// values are not stored in the root storage.
+    fn new(
+        name: &str,
+        layout: &Value,
+        path: &str,
+        readable: bool,
+        types: &HashMap<u32, &RawTypeDef>,
+    ) -> Result<Self> {
+        let (kind, inner) = layout
+            .as_object()
+            .and_then(|layout| layout.iter().next())
+            .ok_or_else(|| anyhow!("Invalid storage layout of {}", name))?;
+        let mut node = StorageLayout {
+            name: name.to_string(),
+            kind: kind.clone(),
+            key: None,
+            ty: None,
+            path: None,
+            docs: String::new(),
+            value: None,
+            children: vec![],
+        };
+        let child = |name: &str, layout: &Value, path: &str, readable: bool| {
+            Self::new(name, layout, path, readable, types)
+        };
+        match kind.as_str() {
+            "root" => {
+                node.key = inner["root_key"].as_str().map(str::to_string);
+                node.children
+                    .push(child(name, &inner["layout"], path, readable)?);
+            }
+            "leaf" => {
+                node.key = inner["key"].as_str().map(str::to_string);
+                if let Some(id) = inner["ty"].as_u64() {
+                    node.ty = Some(resolve_type_name(id as u32, types));
+                    node.docs = types
+                        .get(&(id as u32))
+                        .map(|ty| ty.docs.join("\n").trim().to_string())
+                        .unwrap_or_default();
+                }
+                if readable {
+                    node.path = Some(if path.is_empty() {
+                        "value".to_string()
+                    } else {
+                        path.to_string()
+                    });
+                }
+            }
+            "struct" => {
+                if let Some(struct_name) = inner["name"].as_str() {
+                    node.name = format!("{}: {}", name, struct_name);
+                }
+                for field in inner["fields"].as_array().into_iter().flatten() {
+                    let field_name = field["name"].as_str().unwrap_or("");
+                    let field_path = match (path.is_empty(), field_name.is_empty()) {
+                        (true, _) => field_name.to_string(),
+                        (false, true) => path.to_string(),
+                        (false, false) => format!("{}.{}", path, field_name),
+                    };
+                    node.children
+                        .push(child(field_name, &field["layout"], &field_path, readable)?);
+                }
+            }
+            "enum" => {
+                if let Some(enum_name) = inner["name"].as_str() {
+                    node.name = format!("{}: {}", name, enum_name);
+                }
+                node.key = inner["dispatchKey"].as_str().map(str::to_string);
+                for variant in inner["variants"]
+                    .as_object()
+                    .into_iter()
+                    .flat_map(|v| v.values())
+                {
+                    let variant_name = variant["name"].as_str().unwrap_or("");
+                    let mut variant_node = StorageLayout {
+                        name: variant_name.to_string(),
+                        kind: "variant".to_string(),
+                        key: None,
+                        ty: None,
+                        path: None,
+                        docs: String::new(),
+                        value: None,
+                        children: vec![],
+                    };
+                    for field in variant["fields"].as_array().into_iter().flatten() {
+                        let field_name = field["name"].as_str().unwrap_or("");
+                        variant_node
+                            .children
+                            .push(child(field_name, &field["layout"], "", false)?);
+                    }
+                    node.children.push(variant_node);
+                }
+            }
+            "array" => {
+                let len = inner["len"].as_u64().unwrap_or(0);
+                for i in 0..len {
+                    let element = format!("[{}]", i);
+                    node.children.push(child(
+                        &element,
+                        &inner["layout"],
+                        &format!("{}{}", path, element),
+                        readable,
+                    )?);
+                }
+            }
+            "hash" => {
+                node.children
+                    .push(child(name, &inner["layout"], path, false)?);
+            }
+            _ => {}
+        }
+        Ok(node)
+    }
+}
+
+impl MessageSpec {
+    fn new(raw: &RawMessage, types: &HashMap<u32, &RawTypeDef>) -> Self {
+        MessageSpec {
+            label: raw.label.clone(),
+            selector: raw.selector.clone(),
+            args: raw
+                .args
+                .iter()
+                .map(|arg| ArgSpec {
+                    name: arg.label.clone(),
+                    ty: type_name(&arg.ty, types),
+                })
+                .collect(),
+            mutates: raw.mutates.unwrap_or(true),
+            payable: raw.payable,
+            return_type: raw
+                .return_type
+                .as_ref()
+                .map(|ty| return_type_name(ty, types))
+                .filter(|name| name != "()"),
+            docs: raw
+                .docs
+                .iter()
+                .map(|line| line.trim())
+                .collect::<Vec<_>>()
+                .join("\n")
+                .trim()
+                .to_string(),
+        }
+    }
+}
+
+/// Get the name of a type, resolved with the type registry of the metadata, or from its display
+/// name if it is not in the registry.
+fn type_name(ty: &RawTypeSpec, types: &HashMap<u32, &RawTypeDef>) -> String {
+    if types.contains_key(&ty.id) {
+        resolve_type_name(ty.id, types)
+    } else if !ty.display_name.is_empty() {
+        ty.display_name.join("::")
+    } else {
+        format!("type {}", ty.id)
+    }
+}
+
+/// Get the name of the type returned by a constructor or a message.
+///
+/// The `ConstructorResult` and `MessageResult` wrappers of the ink! metadata, whose error is only
+/// used by the language, are unwrapped.
+fn return_type_name(ty: &RawTypeSpec, types: &HashMap<u32, &RawTypeDef>) -> String {
+    let wrapped = matches!(
+        ty.display_name.last().map(String::as_str),
+        Some("ConstructorResult" | "MessageResult")
+    );
+    if wrapped {
+        let ok_type = types
+            .get(&ty.id)
+            .and_then(|def| def.def.pointer("/variant/variants"))
+            .and_then(Value::as_array)
+            .and_then(|variants| {
+                variants
+                    .iter()
+                    .find(|variant| variant["name"] == "Ok")?
+                    .pointer("/fields/0/type")?
+                    .as_u64()
+            });
+        if let Some(ok_type) = ok_type {
+            return resolve_type_name(ok_type as u32, types);
+        }
+    }
+    type_name(ty, types)
+}
+
+/// Get the name of a type of the type registry, with its generic parameters.
+fn resolve_type_name(id: u32, types: &HashMap<u32, &RawTypeDef>) -> String {
+    let Some(ty) = types.get(&id) else {
+        return format!("type {}", id);
+    };
+    let type_id = |value: &Value| value.as_u64().map(|id| id as u32);
+    let name_of = |value: &Value| match type_id(value) {
+        Some(id) => resolve_type_name(id, types),
+        None => "?".to_string(),
+    };
+
+    if let Some(primitive) = ty.def.get("primitive").and_then(Value::as_str) {
+        return primitive.to_string();
+    }
+    if let Some(name) = ty.path.last() {
+        let params: Vec<String> = ty
+            .params
+            .iter()
+            .filter_map(|param| param.id)
+            .map(|id| resolve_type_name(id, types))
+            .collect();
+        return if params.is_empty() {
+            name.clone()
+        } else {
+            format!("{}<{}>", name, params.join(", "))
+        };
+    }
+    if let Some(sequence) = ty.def.get("sequence") {
+        return format!("Vec<{}>", name_of(&sequence["type"]));
+    }
+    if let Some(array) = ty.def.get("array") {
+        return format!("[{}; {}]", name_of(&array["type"]), array["len"]);
+    }
+    if let Some(tuple) = ty.def.get("tuple").and_then(Value::as_array) {
+        let fields: Vec<String> = tuple.iter().map(name_of).collect();
+        return format!("({})", fields.join(", "));
+    }
+    if let Some(compact) = ty.def.get("compact") {
+        return format!("Compact<{}>", name_of(&compact["type"]));
+    }
+    format!("type {}", id)
+}
@@ -0,0 +1,243 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    aqd_utils::{connect_timeout, proxy_for, rpc_headers},
+    base64::{engine::general_purpose::STANDARD, Engine},
+    std::net::IpAddr,
+    tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{lookup_host, TcpStream},
+        time::timeout,
+    },
+    tokio_tungstenite::{
+        client_async_tls, connect_async,
+        tungstenite::{
+            client::IntoClientRequest,
+            http::{HeaderName, HeaderValue},
+        },
+        MaybeTlsStream, WebSocketStream,
+    },
+    tracing::debug,
+    url::{Host, Url},
+};
+
+/// The maximum length of the response of an HTTP proxy to a `CONNECT` request.
+const MAX_CONNECT_RESPONSE_LENGTH: usize = 8192;
+
+/// Connect to the websocket endpoint at `url`, through the proxy of `--proxy` or of the
+/// environment, if any (see [`aqd_utils::proxy_for`]), before the connection timeout. The
+/// handshake request is sent with the headers of `--rpc-header`.
+///
+/// The proxy is asked to open a tunnel to the endpoint, with a `CONNECT` request for an HTTP
+/// proxy, or a SOCKS5 `CONNECT` command, then the TLS and websocket handshakes go through the
+/// tunnel.
+pub(crate) async fn connect_websocket(
+    url: &Url,
+) -> Result<WebSocketStream<MaybeTlsStream<TcpStream>>> {
+    let mut request = url.as_str().into_client_request()?;
+    for header in rpc_headers() {
+        request.headers_mut().append(
+            HeaderName::from_bytes(header.name.as_bytes())?,
+            HeaderValue::from_str(&header.value)?,
+        );
+    }
+    let connection = async {
+        match proxy_for(url) {
+            None => Ok::<_, anyhow::Error>(connect_async(request).await?.0),
+            Some(proxy) => {
+                debug!(%url, proxy = %proxy_display(&proxy), "Connecting through the proxy");
+                let stream = tunnel(&proxy, url).await?;
+                Ok(client_async_tls(request, stream).await?.0)
+            }
+        }
+    };
+    timeout(connect_timeout(), connection)
+        .await
+        .map_err(|_| anyhow!("timed out after {} ms", connect_timeout().as_millis()))?
+}
+
+/// The URL of a proxy without its credentials, to be logged.
+fn proxy_display(proxy: &Url) -> String {
+    format!(
+        "{}://{}:{}",
+        proxy.scheme(),
+        proxy.host_str().unwrap_or_default(),
+        proxy_port(proxy)
+    )
+}
+
+/// The port of a proxy: the port of its URL, or else the default port of HTTP or SOCKS.
+fn proxy_port(proxy: &Url) -> u16 {
+    proxy.port_or_known_default().unwrap_or(1080)
+}
+
+/// Open a tunnel to the host of `url` through a proxy.
+async fn tunnel(proxy: &Url, url: &Url) -> Result<TcpStream> {
+    let host = url
+        .host()
+        .ok_or_else(|| anyhow!("Missing host in {}", url))?;
+    let port = url
+        .port_or_known_default()
+        .ok_or_else(|| anyhow!("Missing port in {}", url))?;
+    let proxy_host = proxy
+        .host_str()
+        .ok_or_else(|| anyhow!("Missing host in the proxy URL"))?;
+    let mut stream = TcpStream::connect((proxy_host, proxy_port(proxy)))
+        .await
+        .map_err(|e| {
+            anyhow!(
+                "Failed to connect to the proxy {}: {}",
+                proxy_display(proxy),
+                e
+            )
+        })?;
+    match proxy.scheme() {
+        "http" => http_connect(&mut stream, proxy, &host.to_string(), port).await?,
+        _ => socks5_connect(&mut stream, proxy, host, port).await?,
+    }
+    Ok(stream)
+}
+
+/// Ask an HTTP proxy to open a tunnel to `host:port`, with a `CONNECT` request.
+async fn http_connect(stream: &mut TcpStream, proxy: &Url, host: &str, port: u16) -> Result<()> {
+    let target = format!("{}:{}", host, port);
+    let mut request = format!("CONNECT {} HTTP/1.1\r\nHost: {}\r\n", target, target);
+    if !proxy.username().is_empty() {
+        let credentials = format!("{}:{}", proxy.username(), proxy.password().unwrap_or(""));
+        request.push_str(&format!(
+            "Proxy-Authorization: Basic {}\r\n",
+            STANDARD.encode(credentials)
+        ));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).await?;
+
+    // The response is read byte by byte, so that nothing of the tunnel is consumed
+    let mut response = vec![];
+    while !response.ends_with(b"\r\n\r\n") {
+        if response.len() >= MAX_CONNECT_RESPONSE_LENGTH {
+            return Err(anyhow!(
+                "Invalid response of the proxy to the CONNECT request"
+            ));
+        }
+        response.push(stream.read_u8().await?);
+    }
+    let response = String::from_utf8_lossy(&response);
+    let status_line = response.lines().next().unwrap_or_default();
+    match status_line.split_whitespace().nth(1) {
+        Some(status) if status.starts_with('2') => Ok(()),
+        _ => Err(anyhow!(
+            "The proxy refused the connection to {}: {}",
+            target,
+            status_line
+        )),
+    }
+}
+
+/// Ask a SOCKS5 proxy to open a tunnel to `host:port`, authenticating with the credentials of the
+/// proxy URL, if any.
+///
+/// With `socks5://`, the host name is resolved locally, and with `socks5h://`, by the proxy.
+async fn socks5_connect(
+    stream: &mut TcpStream,
+    proxy: &Url,
+    host: Host<&str>,
+    port: u16,
+) -> Result<()> {
+    let (username, password) = (proxy.username(), proxy.password().unwrap_or(""));
+    let authenticate = !username.is_empty();
+    stream
+        .write_all(&[5, 1, if authenticate { 2 } else { 0 }])
+        .await?;
+    let mut reply = [0u8; 2];
+    stream.read_exact(&mut reply).await?;
+    match reply {
+        [5, 0] => {}
+        [5, 2] if authenticate => {
+            // The username and password authentication of RFC 1929
+            if username.len() > 255 || password.len() > 255 {
+                return Err(anyhow!("The SOCKS5 proxy credentials are too long"));
+            }
+            let mut request = vec![1, username.len() as u8];
+            request.extend(username.as_bytes());
+            request.push(password.len() as u8);
+            request.extend(password.as_bytes());
+            stream.write_all(&request).await?;
+            stream.read_exact(&mut reply).await?;
+            if reply[1] != 0 {
+                return Err(anyhow!("The SOCKS5 proxy rejected the credentials"));
+            }
+        }
+        _ => {
+            return Err(anyhow!(
+                "The SOCKS5 proxy requires an unsupported authentication method"
+            ))
+        }
+    }
+
+    let address = match host {
+        Host::Domain(domain) if proxy.scheme() == "socks5" => lookup_host((domain, port))
+            .await?
+            .next()
+            .map(|address| match address.ip() {
+                IpAddr::V4(ip) => Host::Ipv4(ip),
+                IpAddr::V6(ip) => Host::Ipv6(ip),
+            })
+            .ok_or_else(|| anyhow!("Failed to resolve {}", domain))?,
+        host => host.to_owned(),
+    };
+    let mut request = vec![5, 1, 0];
+    match address {
+        Host::Domain(domain) if domain.len() <= 255 => {
+            request.push(3);
+            request.push(domain.len() as u8);
+            request.extend(domain.as_bytes());
+        }
+        Host::Domain(domain) => return Err(anyhow!("The host name {} is too long", domain)),
+        Host::Ipv4(ip) => {
+            request.push(1);
+            request.extend(ip.octets());
+        }
+        Host::Ipv6(ip) => {
+            request.push(4);
+            request.extend(ip.octets());
+        }
+    }
+    request.extend(port.to_be_bytes());
+    stream.write_all(&request).await?;
+
+    let mut reply = [0u8; 4];
+    stream.read_exact(&mut reply).await?;
+    if reply[1] != 0 {
+        return Err(anyhow!(
+            "The SOCKS5 proxy refused the connection: {}",
+            socks5_error(reply[1])
+        ));
+    }
+    // The address bound by the proxy is not needed
+    let bound_length = match reply[3] {
+        1 => 4,
+        4 => 16,
+        3 => stream.read_u8().await? as usize,
+        _ => return Err(anyhow!("Invalid reply of the SOCKS5 proxy")),
+    };
+    let mut bound = vec![0u8; bound_length + 2];
+    stream.read_exact(&mut bound).await?;
+    Ok(())
+}
+
+/// The message of an error code of a SOCKS5 reply.
+fn socks5_error(code: u8) -> &'static str {
+    match code {
+        1 => "general failure",
+        2 => "connection not allowed by the ruleset",
+        3 => "network unreachable",
+        4 => "host unreachable",
+        5 => "connection refused",
+        6 => "TTL expired",
+        7 => "command not supported",
+        8 => "address type not supported",
+        _ => "unknown error",
+    }
+}
@@ -0,0 +1,214 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::submit::{submit_extrinsic, TxParams},
+    anyhow::{anyhow, Result},
+    contract_extrinsics::DefaultConfig,
+    serde::Serialize,
+    sp_core::{blake2_256, keccak_256, H160},
+    std::fmt,
+    subxt::{dynamic::Value, tx::DynamicPayload, Config, Metadata, OnlineClient},
+    subxt_signer::sr25519::Keypair,
+};
+
+type AccountId = <DefaultConfig as Config>::AccountId;
+type Hash = <DefaultConfig as Config>::Hash;
+
+/// The suffix of the accounts derived from a 20-byte address by pallet-revive, such as the
+/// accounts of contracts and Ethereum accounts.
+const REVIVE_ACCOUNT_SUFFIX: [u8; 12] = [0xee; 12];
+
+/// The pallet running the contracts of a chain.
+///
+/// pallet-revive replaces pallet-contracts on newer chains: it runs PolkaVM code, addresses
+/// contracts with 20-byte Ethereum addresses and requires the accounts of the signers to be mapped
+/// to such addresses first. The extrinsics and runtime APIs of both pallets are otherwise similar.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub enum ContractsPallet {
+    /// pallet-contracts, with 32-byte contract addresses
+    #[default]
+    Contracts,
+    /// pallet-revive, with 20-byte contract addresses
+    Revive,
+}
+
+impl fmt::Display for ContractsPallet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContractsPallet::Contracts => write!(f, "pallet-contracts"),
+            ContractsPallet::Revive => write!(f, "pallet-revive"),
+        }
+    }
+}
+
+impl ContractsPallet {
+    /// Detect the pallet running the contracts of a chain from its metadata, pallet-contracts
+    /// being preferred on a chain running both during a migration.
+    pub fn detect(metadata: &Metadata) -> Option<Self> {
+        [ContractsPallet::Contracts, ContractsPallet::Revive]
+            .into_iter()
+            .find(|pallet| metadata.pallet_by_name(pallet.name()).is_some())
+    }
+
+    /// Get the name of the pallet in the runtime metadata, used to build its extrinsics and to
+    /// decode its events
+    pub fn name(&self) -> &'static str {
+        match self {
+            ContractsPallet::Contracts => "Contracts",
+            ContractsPallet::Revive => "Revive",
+        }
+    }
+
+    /// Get the name of a method of the runtime API of the pallet, e.g. `ContractsApi_call`
+    pub(crate) fn runtime_api(&self, method: &str) -> String {
+        format!("{}Api_{}", self.name(), method)
+    }
+
+    /// Get the hash of the code of a contract: its Blake2 hash for pallet-contracts, and its
+    /// Keccak hash for pallet-revive.
+    pub fn code_hash(&self, code: &[u8]) -> Hash {
+        match self {
+            ContractsPallet::Contracts => Hash::from(blake2_256(code)),
+            ContractsPallet::Revive => Hash::from(keccak_256(code)),
+        }
+    }
+
+    /// Format the address of a contract the way the pallet displays it: as SS58 for
+    /// pallet-contracts, and as a 20-byte hex address for pallet-revive.
+    pub fn format_address(&self, account: &AccountId) -> String {
+        match self {
+            ContractsPallet::Contracts => account.to_string(),
+            ContractsPallet::Revive => format!("{:?}", revive_address(account)),
+        }
+    }
+
+    /// Convert the address of a contract to the `dest` argument of the `call` extrinsic of the
+    /// pallet.
+    pub(crate) fn address_value(&self, account: &AccountId) -> Value {
+        match self {
+            ContractsPallet::Contracts => {
+                Value::unnamed_variant("Id", [Value::from_bytes(account.0)])
+            }
+            ContractsPallet::Revive => Value::from_bytes(revive_address(account).0),
+        }
+    }
+
+    /// Convert a storage deposit limit to a `storage_deposit_limit` argument of the extrinsics of
+    /// the pallet: optional for pallet-contracts, and the maximum balance when unlimited for
+    /// pallet-revive.
+    pub(crate) fn storage_deposit_limit_value(&self, limit: Option<u128>) -> Value {
+        match self {
+            ContractsPallet::Contracts => match limit {
+                Some(limit) => Value::unnamed_variant("Some", [Value::u128(limit)]),
+                None => Value::unnamed_variant("None", []),
+            },
+            ContractsPallet::Revive => Value::u128(limit.unwrap_or(u128::MAX)),
+        }
+    }
+}
+
+/// Get the 20-byte address of an account in pallet-revive.
+///
+/// The accounts derived from a 20-byte address keep it, and the address of other accounts is the
+/// last 20 bytes of the Keccak hash of the account.
+pub fn revive_address(account: &AccountId) -> H160 {
+    if account.0[20..] == REVIVE_ACCOUNT_SUFFIX {
+        H160::from_slice(&account.0[..20])
+    } else {
+        H160::from_slice(&keccak_256(&account.0)[12..])
+    }
+}
+
+/// Get the account derived from a 20-byte address by pallet-revive, such as the account of a
+/// contract.
+pub fn revive_account(address: &H160) -> AccountId {
+    let mut account = [0; 32];
+    account[..20].copy_from_slice(address.as_bytes());
+    account[20..].copy_from_slice(&REVIVE_ACCOUNT_SUFFIX);
+    AccountId::from(account)
+}
+
+/// Parse the address of a contract: an SS58 address, or a 20-byte hex address of a
+/// pallet-revive contract.
+pub fn parse_contract_address(input: &str) -> Result<AccountId> {
+    let hex = input.strip_prefix("0x").unwrap_or(input);
+    if hex.len() == 40 {
+        let address = hex::decode(hex).map_err(|e| anyhow!("Invalid address {}: {}", input, e))?;
+        return Ok(revive_account(&H160::from_slice(&address)));
+    }
+    input
+        .parse()
+        .map_err(|e| anyhow!("Invalid address {}: {:?}", input, e))
+}
+
+/// Derive the address of a contract instantiated with a salt the way pallet-revive does (as the
+/// `CREATE2` opcode of Ethereum), from the address deploying it, its code, the input data of the
+/// constructor and the salt.
+pub fn revive_contract_address(
+    deployer: &H160,
+    code: &[u8],
+    input_data: &[u8],
+    salt: &[u8; 32],
+) -> H160 {
+    let init_code_hash = keccak_256(&[code, input_data].concat());
+    let preimage = [&[0xff], deployer.as_bytes(), salt, &init_code_hash].concat();
+    H160::from_slice(&keccak_256(&preimage)[12..])
+}
+
+/// Check whether an account is mapped to its 20-byte address in pallet-revive, which is required
+/// to upload, instantiate or call contracts.
+///
+/// The accounts derived from a 20-byte address are always mapped.
+pub async fn is_account_mapped(
+    client: &OnlineClient<DefaultConfig>,
+    account: &AccountId,
+) -> Result<bool> {
+    if account.0[20..] == REVIVE_ACCOUNT_SUFFIX {
+        return Ok(true);
+    }
+    let query = subxt::dynamic::storage(
+        "Revive",
+        "OriginalAccount",
+        vec![Value::from_bytes(revive_address(account).0)],
+    );
+    Ok(client
+        .storage()
+        .at_latest()
+        .await?
+        .fetch(&query)
+        .await?
+        .is_some())
+}
+
+/// Map the account of the signer to its 20-byte address in pallet-revive with
+/// `Revive::map_account`, if it is not mapped yet.
+///
+/// # Errors
+///
+/// This function returns an error if the account cannot be mapped, or if it is not mapped and an
+/// explicit nonce is used, as the mapping would use the nonce.
+pub async fn ensure_account_mapped(
+    client: &OnlineClient<DefaultConfig>,
+    signer: &Keypair,
+    params: &TxParams,
+) -> Result<()> {
+    let account = AccountId::from(signer.public_key().0);
+    if is_account_mapped(client, &account).await? {
+        return Ok(());
+    }
+    if params.nonce.is_some() {
+        return Err(anyhow!(
+            "The account {} must be mapped with Revive::map_account before submitting an extrinsic with an explicit nonce",
+            account
+        ));
+    }
+    let params = TxParams {
+        sudo: false,
+        ..params.clone()
+    };
+    let call: DynamicPayload = subxt::dynamic::tx("Revive", "map_account", Vec::<Value>::new());
+    submit_extrinsic(client, &call, signer, &params)
+        .await
+        .map_err(|e| anyhow!("Error mapping the account {}: {}", account, e))?;
+    Ok(())
+}
@@ -0,0 +1,572 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::{extrinsic_options::Gas, runtime::ContractsPallet, serialize_storage_deposit},
+    anyhow::{anyhow, Result},
+    aqd_utils::{emit_progress, register_secret_uri, ProgressEvent, ProgressStage},
+    contract_extrinsics::DefaultConfig,
+    pallet_contracts_primitives::StorageDeposit,
+    serde::Serialize,
+    sp_core::blake2_256,
+    std::{fmt, str::FromStr, time::Instant},
+    subxt::{
+        blocks::ExtrinsicEvents,
+        config::{DefaultExtrinsicParamsBuilder, ExtrinsicParams, ExtrinsicParamsEncoder},
+        dynamic::Value,
+        ext::codec::{Compact, Decode, DecodeAll, Encode},
+        tx::{DynamicPayload, SubmittableExtrinsic, TxProgress, TxStatus},
+        utils::MultiSignature,
+        Config, OnlineClient,
+    },
+    subxt_signer::{sr25519::Keypair, SecretUri},
+    tracing::{debug, info},
+};
+
+/// The mortality of an extrinsic.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Era {
+    /// The extrinsic stays valid forever
+    #[default]
+    Immortal,
+    /// The extrinsic stays valid for the given number of blocks after the latest block. The
+    /// number of blocks is rounded up to a power of two, between 4 and 65536.
+    Mortal(u64),
+}
+
+impl FromStr for Era {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        if input.eq_ignore_ascii_case("immortal") {
+            return Ok(Era::Immortal);
+        }
+        match input.parse::<u64>() {
+            Ok(blocks) if blocks > 0 => Ok(Era::Mortal(blocks)),
+            _ => Err(anyhow!(
+                "Invalid era: {}. Expected a number of blocks or `immortal`",
+                input
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Era {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Era::Immortal => write!(f, "immortal"),
+            Era::Mortal(blocks) => write!(f, "{} blocks", blocks),
+        }
+    }
+}
+
+/// The status of a submitted extrinsic to wait for.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WaitFor {
+    /// Wait for the extrinsic to be included in a block, which may still be reverted
+    InBlock,
+    /// Wait for the block of the extrinsic to be finalized
+    #[default]
+    Finalized,
+}
+
+impl FromStr for WaitFor {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        match input {
+            "in-block" => Ok(WaitFor::InBlock),
+            "finalized" => Ok(WaitFor::Finalized),
+            _ => Err(anyhow!(
+                "Invalid status to wait for: {}. Expected `in-block` or `finalized`",
+                input
+            )),
+        }
+    }
+}
+
+/// The block an extrinsic was included in.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ExtrinsicBlock {
+    pub hash: <DefaultConfig as Config>::Hash,
+    pub number: u64,
+    /// The hash of the extrinsic itself
+    pub extrinsic_hash: <DefaultConfig as Config>::Hash,
+    /// The URL of the block explorer page of the extrinsic, if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explorer_url: Option<String>,
+}
+
+impl fmt::Display for ExtrinsicBlock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{} ({:?})", self.number, self.hash)
+    }
+}
+
+/// The parameters of the signature and the submission of an extrinsic.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TxParams {
+    /// The tip paid to the block author, in the smallest unit of the network token
+    pub tip: u128,
+    pub era: Era,
+    /// The nonce of the signer account, queried from the node if `None`
+    pub nonce: Option<u64>,
+    pub wait_for: WaitFor,
+    /// Whether to dispatch the extrinsic with the root origin through `Sudo::sudo`, the signer
+    /// being the sudo key
+    pub sudo: bool,
+}
+
+/// Create the signer of an extrinsic from a secret key URI, registered as a secret to be redacted
+/// from the output.
+pub fn pair_signer(suri: &str) -> Result<Keypair> {
+    register_secret_uri(suri);
+    let uri = SecretUri::from_str(suri).map_err(|e| anyhow!("Invalid secret key URI: {}", e))?;
+    Keypair::from_uri(&uri).map_err(|e| anyhow!("Invalid secret key URI: {}", e))
+}
+
+/// Sign and submit an extrinsic with the given parameters, and wait for it to be included in a
+/// block or finalized.
+///
+/// With `sudo`, the extrinsic is wrapped in `Sudo::sudo` first.
+///
+/// Without an explicit nonce, the nonce of the signer is queried from the node, including its
+/// pending extrinsics.
+///
+/// Each stage of the extrinsic is reported as a progress event.
+///
+/// # Errors
+///
+/// This function returns an error if the extrinsic cannot be submitted, or if it fails.
+pub async fn submit_extrinsic(
+    client: &OnlineClient<DefaultConfig>,
+    call: &DynamicPayload,
+    signer: &Keypair,
+    params: &TxParams,
+) -> Result<ExtrinsicEvents<DefaultConfig>> {
+    info!(
+        pallet = call.pallet_name(),
+        call = call.call_name(),
+        sudo = params.sudo,
+        nonce = ?params.nonce,
+        tip = params.tip,
+        wait_for = ?params.wait_for,
+        "Submitting extrinsic"
+    );
+    let start = Instant::now();
+    emit_progress(ProgressEvent::new("polkadot", ProgressStage::Started));
+    let extrinsic = signed_extrinsic(client, call, signer, params).await?;
+    emit_progress(
+        ProgressEvent::new("polkadot", ProgressStage::Signed)
+            .hash(format!("{:?}", extrinsic.hash())),
+    );
+    let progress = extrinsic.submit_and_watch().await?;
+    debug!(hash = ?progress.extrinsic_hash(), "Extrinsic submitted, waiting for its events");
+    let events = wait_for_events(progress, params.wait_for).await?;
+    info!(
+        block = ?events.block_hash(),
+        elapsed = ?start.elapsed(),
+        "Extrinsic succeeded"
+    );
+    if params.sudo {
+        // The sudo extrinsic succeeds even if the call fails, its result is in the `Sudid` event
+        let results = decode_events::<Result<(), ()>>(&events, "Sudo", "Sudid");
+        if !matches!(results.as_deref(), Ok([Ok(())])) {
+            return Err(anyhow!("The call dispatched with sudo failed"));
+        }
+    }
+    Ok(events)
+}
+
+/// Submit an extrinsic already signed, for example by an external signer, and wait for it to be
+/// included in a block or finalized.
+///
+/// # Errors
+///
+/// This function returns an error if the extrinsic cannot be submitted, or if it fails.
+pub async fn submit_signed_extrinsic(
+    client: &OnlineClient<DefaultConfig>,
+    extrinsic: Vec<u8>,
+    wait_for: WaitFor,
+) -> Result<ExtrinsicEvents<DefaultConfig>> {
+    emit_progress(ProgressEvent::new("polkadot", ProgressStage::Started));
+    let progress = SubmittableExtrinsic::from_bytes(client.clone(), extrinsic)
+        .submit_and_watch()
+        .await?;
+    wait_for_events(progress, wait_for).await
+}
+
+/// Wait for a submitted extrinsic to be included in a block or finalized, and get its events.
+///
+/// The submission, the inclusion in a block and the finalization are reported as progress events.
+async fn wait_for_events(
+    mut progress: TxProgress<DefaultConfig, OnlineClient<DefaultConfig>>,
+    wait_for: WaitFor,
+) -> Result<ExtrinsicEvents<DefaultConfig>> {
+    let hash = format!("{:?}", progress.extrinsic_hash());
+    emit_progress(ProgressEvent::new("polkadot", ProgressStage::Submitted).hash(&hash));
+    while let Some(status) = progress.next().await {
+        match status? {
+            TxStatus::InBestBlock(in_block) => {
+                emit_progress(
+                    ProgressEvent::new("polkadot", ProgressStage::Confirmed)
+                        .hash(&hash)
+                        .block(format!("{:?}", in_block.block_hash())),
+                );
+                if wait_for == WaitFor::InBlock {
+                    return Ok(in_block.wait_for_success().await?);
+                }
+            }
+            TxStatus::InFinalizedBlock(in_block) => {
+                emit_progress(
+                    ProgressEvent::new("polkadot", ProgressStage::Finalized)
+                        .hash(&hash)
+                        .block(format!("{:?}", in_block.block_hash())),
+                );
+                return Ok(in_block.wait_for_success().await?);
+            }
+            TxStatus::Error { message } => {
+                return Err(anyhow!("The extrinsic {} failed: {}", hash, message))
+            }
+            TxStatus::Invalid { message } => {
+                return Err(anyhow!("The extrinsic {} is invalid: {}", hash, message))
+            }
+            TxStatus::Dropped { message } => {
+                return Err(anyhow!(
+                    "The extrinsic {} was dropped by the node: {}",
+                    hash,
+                    message
+                ))
+            }
+            _ => continue,
+        }
+    }
+    Err(anyhow!(
+        "The subscription to the status of the extrinsic {} was closed",
+        hash
+    ))
+}
+
+/// Sign an extrinsic with the given parameters, without submitting it.
+///
+/// Without an explicit nonce, the nonce of the signer is queried from the node, including its
+/// pending extrinsics.
+async fn signed_extrinsic(
+    client: &OnlineClient<DefaultConfig>,
+    call: &DynamicPayload,
+    signer: &Keypair,
+    params: &TxParams,
+) -> Result<SubmittableExtrinsic<DefaultConfig, OnlineClient<DefaultConfig>>> {
+    let builder = params_builder(client, params).await?;
+    let sudo;
+    let call = if params.sudo {
+        sudo = sudo_payload(call.clone().into_value());
+        &sudo
+    } else {
+        call
+    };
+    let extrinsic = match params.nonce {
+        Some(nonce) => {
+            client
+                .tx()
+                .create_signed_with_nonce(call, signer, nonce, builder.build())?
+        }
+        None => {
+            client
+                .tx()
+                .create_signed(call, signer, builder.build())
+                .await?
+        }
+    };
+    Ok(extrinsic)
+}
+
+/// Get the builder of the signed extension parameters of an extrinsic: its tip and mortality.
+async fn params_builder(
+    client: &OnlineClient<DefaultConfig>,
+    params: &TxParams,
+) -> Result<DefaultExtrinsicParamsBuilder<DefaultConfig>> {
+    let mut builder = DefaultExtrinsicParamsBuilder::<DefaultConfig>::new().tip(params.tip);
+    if let Era::Mortal(blocks) = params.era {
+        let latest_block = client.blocks().at_latest().await?;
+        emit_progress(
+            ProgressEvent::new("polkadot", ProgressStage::BlockhashFetched)
+                .block(format!("{:?}", latest_block.hash())),
+        );
+        builder = builder.mortal(latest_block.header(), blocks);
+    }
+    Ok(builder)
+}
+
+/// An extrinsic exported to be signed by an external signer, such as an air-gapped signer or
+/// Polkadot Vault.
+///
+/// Once the signer payload is signed, the extrinsic is assembled from the signer, the signature, the
+/// signed extra parameters and the call data with [`assemble_signed_extrinsic`].
+#[derive(Clone, Debug, Serialize)]
+pub struct ExportedCall {
+    /// The account expected to sign the extrinsic
+    pub signer: String,
+    /// The SCALE-encoded call, as hex
+    pub call_data: String,
+    /// The nonce of the signer account the extrinsic is signed with
+    pub nonce: u64,
+    /// The mortality of the extrinsic
+    pub era: String,
+    /// The tip paid to the block author, in the smallest unit of the network token
+    pub tip: u128,
+    /// The SCALE-encoded signed extra parameters (mortality, nonce and tip), as hex. They are
+    /// included in the extrinsic along with the signature
+    pub extra: String,
+    /// The SCALE-encoded additional parameters (versions of the runtime, genesis hash and
+    /// mortality checkpoint), as hex. They are only part of the signer payload
+    pub additional: String,
+    /// The payload to sign, as hex: the call and the signed extensions, hashed if longer than 256
+    /// bytes
+    pub signer_payload: String,
+}
+
+/// Export an extrinsic with the given parameters to be signed by `signer` with an external signer.
+///
+/// With `sudo`, the extrinsic is wrapped in `Sudo::sudo` first. Without an explicit nonce, the
+/// nonce of the signer is queried from the node.
+pub async fn export_call(
+    client: &OnlineClient<DefaultConfig>,
+    call: &DynamicPayload,
+    signer: &<DefaultConfig as Config>::AccountId,
+    params: &TxParams,
+) -> Result<ExportedCall> {
+    let builder = params_builder(client, params).await?;
+    let sudo;
+    let call = if params.sudo {
+        sudo = sudo_payload(call.clone().into_value());
+        &sudo
+    } else {
+        call
+    };
+    let call_data = client.tx().call_data(call)?;
+    let nonce = match params.nonce {
+        Some(nonce) => nonce,
+        None => client.tx().account_nonce(signer).await?,
+    };
+    let extrinsic_params = <<DefaultConfig as Config>::ExtrinsicParams as ExtrinsicParams<
+        DefaultConfig,
+    >>::new(nonce, client.clone(), builder.build())?;
+    let mut extra = vec![];
+    extrinsic_params.encode_extra_to(&mut extra);
+    let mut additional = vec![];
+    extrinsic_params.encode_additional_to(&mut additional);
+
+    // The signer payload is built like subxt does when signing an extrinsic
+    let mut signer_payload = [call_data.as_slice(), &extra, &additional].concat();
+    if signer_payload.len() > 256 {
+        signer_payload = blake2_256(&signer_payload).to_vec();
+    }
+    Ok(ExportedCall {
+        signer: signer.to_string(),
+        call_data: format!("0x{}", hex::encode(call_data)),
+        nonce,
+        era: params.era.to_string(),
+        tip: params.tip,
+        extra: format!("0x{}", hex::encode(extra)),
+        additional: format!("0x{}", hex::encode(additional)),
+        signer_payload: format!("0x{}", hex::encode(signer_payload)),
+    })
+}
+
+/// Assemble a signed extrinsic from a call exported with [`export_call`] and the signature of its
+/// signer payload, ready to be submitted with [`submit_signed_extrinsic`].
+///
+/// The signature is either a raw sr25519 signature of 64 bytes, or a SCALE-encoded
+/// `MultiSignature` starting with its type, as returned by Polkadot Vault.
+///
+/// # Errors
+///
+/// This function returns an error if the signature is neither of them.
+pub fn assemble_signed_extrinsic(
+    signer: &<DefaultConfig as Config>::AccountId,
+    signature: &[u8],
+    extra: &[u8],
+    call_data: &[u8],
+) -> Result<Vec<u8>> {
+    let signature = match <[u8; 64]>::try_from(signature) {
+        Ok(signature) => MultiSignature::Sr25519(signature),
+        Err(_) => MultiSignature::decode_all(&mut &signature[..]).map_err(|_| {
+            anyhow!(
+                "Invalid signature of {} bytes: expected an sr25519 signature or a SCALE-encoded MultiSignature",
+                signature.len()
+            )
+        })?,
+    };
+    let address: <DefaultConfig as Config>::Address = signer.clone().into();
+
+    // The signed extrinsic of version 4: the signer address, the signature, the signed extra
+    // parameters and the call, prefixed by their length
+    let mut inner = vec![0b1000_0000 + 4];
+    address.encode_to(&mut inner);
+    signature.encode_to(&mut inner);
+    inner.extend_from_slice(extra);
+    inner.extend_from_slice(call_data);
+    let mut extrinsic = Compact(inner.len() as u32).encode();
+    extrinsic.extend(inner);
+    Ok(extrinsic)
+}
+
+/// The estimated cost of an extrinsic, reported by the `TransactionPaymentApi_query_info`
+/// runtime API.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct FeeEstimate {
+    /// The weight of the extrinsic, including the gas limit of a contract call
+    pub weight: Gas,
+    /// The fee charged for the inclusion of the extrinsic, without the tip, in the smallest unit
+    /// of the network token
+    pub partial_fee: u128,
+}
+
+/// The estimated cost of an upload, instantiation or call, without submitting it.
+#[derive(Debug, Serialize)]
+pub struct CostEstimate {
+    /// The fee charged for the inclusion of the extrinsic, without the tip, in the smallest unit
+    /// of the network token
+    pub partial_fee: u128,
+    /// The weight of the extrinsic, including the gas limit of a contract call
+    pub weight: Gas,
+    /// The gas limit of an instantiation or a call
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_limit: Option<Gas>,
+    #[serde(serialize_with = "serialize_storage_deposit")]
+    pub storage_deposit: StorageDeposit<u128>,
+}
+
+/// Estimate the fee and the weight of an extrinsic signed with the given parameters, without
+/// submitting it.
+///
+/// # Errors
+///
+/// This function returns an error if the extrinsic cannot be signed, or if the runtime does not
+/// provide the `TransactionPaymentApi_query_info` runtime API.
+pub async fn estimate_fee(
+    client: &OnlineClient<DefaultConfig>,
+    call: &DynamicPayload,
+    signer: &Keypair,
+    params: &TxParams,
+) -> Result<FeeEstimate> {
+    let extrinsic = signed_extrinsic(client, call, signer, params).await?;
+    let encoded = extrinsic.encoded();
+    let mut parameters = encoded.to_vec();
+    (encoded.len() as u32).encode_to(&mut parameters);
+    // The `RuntimeDispatchInfo` of the extrinsic: its weight, its dispatch class and its fee
+    let (ref_time, proof_size, _class, partial_fee): (Compact<u64>, Compact<u64>, u8, u128) =
+        client
+            .runtime_api()
+            .at_latest()
+            .await?
+            .call_raw("TransactionPaymentApi_query_info", Some(&parameters))
+            .await
+            .map_err(|err| anyhow!("Error estimating the fee: {}", err))?;
+    Ok(FeeEstimate {
+        weight: Gas {
+            ref_time: ref_time.0,
+            proof_size: proof_size.0,
+        },
+        partial_fee,
+    })
+}
+
+/// Get the block a submitted extrinsic was included in, along with the hash of the extrinsic.
+pub async fn extrinsic_block(
+    client: &OnlineClient<DefaultConfig>,
+    events: &ExtrinsicEvents<DefaultConfig>,
+) -> Result<ExtrinsicBlock> {
+    let hash = events.block_hash();
+    let block = client.blocks().at(hash).await?;
+    Ok(ExtrinsicBlock {
+        hash,
+        number: block.number().into(),
+        extrinsic_hash: events.extrinsic_hash(),
+        explorer_url: None,
+    })
+}
+
+/// Build a `Contracts::call` extrinsic, or a `Revive::call` extrinsic on pallet-revive.
+///
+/// The extrinsic can also be nested in another extrinsic, such as a batch or a multisig call.
+pub fn contracts_call_payload(
+    pallet: ContractsPallet,
+    contract: &<DefaultConfig as Config>::AccountId,
+    value: u128,
+    gas_limit: Gas,
+    storage_deposit_limit: Option<u128>,
+    data: Vec<u8>,
+) -> DynamicPayload {
+    subxt::dynamic::tx(
+        pallet.name(),
+        "call",
+        vec![
+            ("dest", pallet.address_value(contract)),
+            ("value", Value::u128(value)),
+            ("gas_limit", weight_value(gas_limit)),
+            (
+                "storage_deposit_limit",
+                pallet.storage_deposit_limit_value(storage_deposit_limit),
+            ),
+            ("data", Value::from_bytes(data)),
+        ],
+    )
+}
+
+/// Build a `Proxy::proxy` extrinsic, dispatching a call from the `real` account, the signer being
+/// one of its proxies.
+pub fn proxy_payload(real: &<DefaultConfig as Config>::AccountId, call: Value) -> DynamicPayload {
+    subxt::dynamic::tx(
+        "Proxy",
+        "proxy",
+        vec![
+            (
+                "real",
+                Value::unnamed_variant("Id", [Value::from_bytes(real.0)]),
+            ),
+            ("force_proxy_type", option_value(None)),
+            ("call", call),
+        ],
+    )
+}
+
+/// Build a `Sudo::sudo` extrinsic, dispatching a call with the root origin, the signer being the
+/// sudo key.
+pub fn sudo_payload(call: Value) -> DynamicPayload {
+    subxt::dynamic::tx("Sudo", "sudo", vec![("call", call)])
+}
+
+/// Convert an amount of gas to a `Weight` argument.
+pub(crate) fn weight_value(gas: Gas) -> Value {
+    Value::named_composite([
+        ("ref_time", Value::u128(gas.ref_time as u128)),
+        ("proof_size", Value::u128(gas.proof_size as u128)),
+    ])
+}
+
+/// Convert an optional value to an `Option` argument.
+pub(crate) fn option_value(value: Option<Value>) -> Value {
+    match value {
+        Some(value) => Value::unnamed_variant("Some", [value]),
+        None => Value::unnamed_variant("None", []),
+    }
+}
+
+/// Decode the leading fields of the events of an extrinsic with the given pallet and name.
+///
+/// For example, the code hash of the `Contracts::CodeStored` events can be decoded as a hash.
+pub fn decode_events<T: Decode>(
+    events: &ExtrinsicEvents<DefaultConfig>,
+    pallet: &str,
+    name: &str,
+) -> Result<Vec<T>> {
+    let mut decoded = vec![];
+    for event in events.iter() {
+        let event = event?;
+        if event.pallet_name() == pallet && event.variant_name() == name {
+            decoded.push(T::decode(&mut event.field_bytes())?);
+        }
+    }
+    Ok(decoded)
+}
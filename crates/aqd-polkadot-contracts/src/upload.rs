@@ -0,0 +1,219 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::{
+        balance::TokenInfo,
+        code::load_wasm,
+        events::{typed_events, TypedEvent},
+        extrinsic_options::ExtrinsicOptions,
+        runtime::{ensure_account_mapped, ContractsPallet},
+        submit::{
+            decode_events, estimate_fee, extrinsic_block, option_value, pair_signer,
+            submit_extrinsic, CostEstimate, ExtrinsicBlock, FeeEstimate, TxParams,
+        },
+    },
+    anyhow::{anyhow, Result},
+    contract_extrinsics::{
+        DefaultConfig, DisplayEvents, TokenMetadata, UploadCommandBuilder, UploadExec,
+    },
+    pallet_contracts_primitives::{CodeUploadResult, StorageDeposit},
+    serde::Serialize,
+    subxt::{dynamic::Value, ext::codec::Encode, tx::DynamicPayload, Config, OnlineClient},
+    subxt_signer::sr25519::Keypair,
+};
+
+type AccountId = <DefaultConfig as Config>::AccountId;
+type Hash = <DefaultConfig as Config>::Hash;
+
+/// An upload of the code of a contract on a Polkadot node.
+pub struct PolkadotUpload {
+    exec: UploadExec,
+    client: OnlineClient<DefaultConfig>,
+    token: TokenInfo,
+    code: Vec<u8>,
+    storage_deposit_limit: Option<u128>,
+    signer: Keypair,
+    tx_params: TxParams,
+    pallet: ContractsPallet,
+}
+
+/// The result of a dry run of an upload.
+#[derive(Debug, Serialize)]
+pub struct UploadDryRunResult {
+    pub code_hash: Hash,
+    /// The deposit reserved for the code, in the smallest unit of the network token
+    pub deposit: u128,
+}
+
+/// The result of a submitted upload.
+#[derive(Serialize)]
+pub struct UploadResult {
+    /// The hash of the stored code, `None` if the code was already uploaded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code_hash: Option<Hash>,
+    pub events: Vec<TypedEvent>,
+    /// The block the upload was included in
+    pub block: ExtrinsicBlock,
+    /// The events of the upload, to display them
+    #[serde(skip)]
+    pub display_events: DisplayEvents,
+}
+
+impl PolkadotUpload {
+    /// Prepare the upload of the code of the contract file of the options.
+    pub async fn new(options: &ExtrinsicOptions) -> Result<Self> {
+        // The dry run and the submission use the node connected to first
+        let (url, client, pallet, token) = options.connect().await?;
+        let exec = UploadCommandBuilder::default()
+            .extrinsic_opts(options.extrinsic_opts_builder(&token, &url)?.done())
+            .done()
+            .await?;
+        Ok(PolkadotUpload {
+            exec,
+            client,
+            code: load_wasm(options.file())?,
+            storage_deposit_limit: options.denominated_storage_deposit_limit(&token)?,
+            signer: pair_signer(options.suri())?,
+            tx_params: options.tx_params(&token)?,
+            token,
+            pallet,
+        })
+    }
+
+    /// Get the hash of the code to upload
+    pub fn code_hash(&self) -> [u8; 32] {
+        self.pallet.code_hash(&self.code).0
+    }
+
+    /// Get the pallet running the contracts of the node
+    pub fn pallet(&self) -> ContractsPallet {
+        self.pallet
+    }
+
+    /// Get the decimals and symbol of the network token
+    pub fn token(&self) -> &TokenInfo {
+        &self.token
+    }
+
+    /// Get the token metadata of the network, used to display the events
+    pub fn token_metadata(&self) -> TokenMetadata {
+        self.token.token_metadata()
+    }
+
+    /// Check whether the code is already stored on chain, in the `CodeInfoOf` storage of the
+    /// pallet (`OwnerInfoOf` before the v12 storage migration of the Contracts pallet).
+    pub async fn is_uploaded(&self) -> Result<bool> {
+        let metadata = self.client.metadata();
+        let Some(entry) = ["CodeInfoOf", "OwnerInfoOf"].into_iter().find(|entry| {
+            metadata
+                .pallet_by_name(self.pallet.name())
+                .and_then(|pallet| pallet.storage())
+                .and_then(|storage| storage.entry_by_name(entry))
+                .is_some()
+        }) else {
+            return Err(anyhow!(
+                "The {} of this node stores no code information",
+                self.pallet
+            ));
+        };
+        let query = subxt::dynamic::storage(
+            self.pallet.name(),
+            entry,
+            vec![Value::from_bytes(self.code_hash())],
+        );
+        Ok(self
+            .client
+            .storage()
+            .at_latest()
+            .await?
+            .fetch(&query)
+            .await?
+            .is_some())
+    }
+
+    /// Dry-run the upload, without submitting any extrinsic.
+    pub async fn dry_run(&self) -> Result<UploadDryRunResult> {
+        let result = match self.pallet {
+            ContractsPallet::Contracts => self.exec.upload_code_rpc().await?,
+            ContractsPallet::Revive => self.revive_upload_code_rpc().await?,
+        }
+        .map_err(|err| anyhow!("Error uploading the code: {:?}", err))?;
+        Ok(UploadDryRunResult {
+            code_hash: result.code_hash,
+            deposit: result.deposit,
+        })
+    }
+
+    /// Dry-run the upload on pallet-revive, which `contract_extrinsics` does not support, with the
+    /// `ReviveApi_upload_code` runtime API.
+    async fn revive_upload_code_rpc(&self) -> Result<CodeUploadResult<Hash, u128>> {
+        let origin = AccountId::from(self.signer.public_key().0);
+        let parameters = (origin, &self.code, self.storage_deposit_limit).encode();
+        let result = self
+            .client
+            .runtime_api()
+            .at_latest()
+            .await?
+            .call_raw(&self.pallet.runtime_api("upload_code"), Some(&parameters))
+            .await?;
+        Ok(result)
+    }
+
+    /// Get the `Contracts::upload_code` or `Revive::upload_code` extrinsic.
+    fn payload(&self) -> DynamicPayload {
+        let args = match self.pallet {
+            ContractsPallet::Contracts => vec![
+                ("code", Value::from_bytes(&self.code)),
+                (
+                    "storage_deposit_limit",
+                    option_value(self.storage_deposit_limit.map(Value::u128)),
+                ),
+                ("determinism", Value::unnamed_variant("Enforced", [])),
+            ],
+            // PolkaVM code is always deterministic
+            ContractsPallet::Revive => vec![
+                ("code", Value::from_bytes(&self.code)),
+                (
+                    "storage_deposit_limit",
+                    self.pallet
+                        .storage_deposit_limit_value(self.storage_deposit_limit),
+                ),
+            ],
+        };
+        subxt::dynamic::tx(self.pallet.name(), "upload_code", args)
+    }
+
+    /// Estimate the fee and the weight of the upload, without submitting it.
+    pub async fn estimate_fee(&self) -> Result<FeeEstimate> {
+        estimate_fee(&self.client, &self.payload(), &self.signer, &self.tx_params).await
+    }
+
+    /// Estimate the cost of the upload with a dry run, without submitting it.
+    pub async fn estimate(&self) -> Result<CostEstimate> {
+        let result = self.dry_run().await?;
+        let fee = self.estimate_fee().await?;
+        Ok(CostEstimate {
+            partial_fee: fee.partial_fee,
+            weight: fee.weight,
+            gas_limit: None,
+            storage_deposit: StorageDeposit::Charge(result.deposit),
+        })
+    }
+
+    /// Submit the upload, and wait for it to be included in a block or finalized.
+    pub async fn submit(&self) -> Result<UploadResult> {
+        let call = self.payload();
+        if self.pallet == ContractsPallet::Revive {
+            ensure_account_mapped(&self.client, &self.signer, &self.tx_params).await?;
+        }
+        let events = submit_extrinsic(&self.client, &call, &self.signer, &self.tx_params)
+            .await
+            .map_err(|err| anyhow!("Error uploading the code: {}", err))?;
+        Ok(UploadResult {
+            code_hash: decode_events::<Hash>(&events, self.pallet.name(), "CodeStored")?.pop(),
+            block: extrinsic_block(&self.client, &events).await?,
+            display_events: DisplayEvents::from_events(&events, None, &self.client.metadata())?,
+            events: typed_events(&events, None, &self.client.metadata())?,
+        })
+    }
+}
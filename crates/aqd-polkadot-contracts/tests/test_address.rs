@@ -0,0 +1,53 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::Result,
+    aqd_polkadot_contracts::{parse_public_key, parse_ss58_prefix, ss58_network, Ss58Address},
+};
+
+/// The public key of the `//Alice` development account.
+const ALICE: &str = "0xd43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d";
+
+/// Purpose: This test checks that a public key is converted to the SS58 addresses of several networks, and back.
+#[test]
+pub fn test_ss58_address() -> Result<()> {
+    let (public_key, prefix) = parse_public_key(ALICE)?;
+    assert_eq!(prefix, None);
+    assert_eq!(
+        Ss58Address::new(public_key, 42).address,
+        "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+    );
+    let polkadot = Ss58Address::new(public_key, 0);
+    assert_eq!(
+        polkadot.address,
+        "15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5"
+    );
+    assert_eq!(polkadot.network.as_deref(), Some("polkadot"));
+    assert_eq!(polkadot.public_key, ALICE);
+
+    // An SS58 address is decoded with its prefix, whatever its network
+    let (decoded, prefix) = parse_public_key("HNZata7iMYWmk5RvZRTiAsSDhV8366zq2YGb3tLH5Upf74F")?;
+    assert_eq!(decoded, public_key);
+    assert_eq!(prefix, Some(2));
+    assert_eq!(Ss58Address::parse(&polkadot.address)?, polkadot);
+
+    // The hex prefix is optional, and the checksum of the addresses is checked
+    assert_eq!(parse_public_key(&ALICE[2..])?.0, public_key);
+    assert!(parse_public_key("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQZ").is_err());
+    assert!(parse_public_key("0xd435").is_err());
+    Ok(())
+}
+
+/// Purpose: This test checks that the SS58 prefixes are parsed as numbers or as network names of the SS58 registry.
+#[test]
+pub fn test_parse_ss58_prefix() -> Result<()> {
+    assert_eq!(parse_ss58_prefix("0")?, 0);
+    assert_eq!(parse_ss58_prefix("polkadot")?, 0);
+    assert_eq!(parse_ss58_prefix("Kusama")?, 2);
+    assert_eq!(parse_ss58_prefix("substrate")?, 42);
+    assert!(parse_ss58_prefix("16384").is_err());
+    assert!(parse_ss58_prefix("not-a-network").is_err());
+    assert_eq!(ss58_network(2).as_deref(), Some("kusama"));
+    assert_eq!(ss58_network(16000), None);
+    Ok(())
+}
@@ -0,0 +1,143 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::Result,
+    aqd_polkadot_contracts::{Balance, Gas, TokenInfo},
+    pallet_contracts_primitives::StorageDeposit,
+    serde_json::json,
+    sp_weights::Weight,
+    std::str::FromStr,
+};
+
+fn dot() -> TokenInfo {
+    TokenInfo {
+        decimals: 10,
+        symbol: "DOT".to_string(),
+    }
+}
+
+/// Purpose: This test checks that balances are parsed as raw amounts or as amounts in token units.
+#[test]
+pub fn test_parse_balance() -> Result<()> {
+    assert_eq!(Balance::from_str("1000")?, Balance::Raw(1000));
+    assert_eq!(Balance::from_str("1_000_000")?, Balance::Raw(1_000_000));
+    assert_eq!(
+        Balance::from_str("1.5 DOT")?,
+        Balance::Units {
+            amount: "1.5".to_string(),
+            symbol: Some("DOT".to_string()),
+        }
+    );
+    assert_eq!(Balance::from_str("1.5DOT")?, Balance::from_str("1.5 DOT")?);
+    assert_eq!(
+        Balance::from_str("0.5unit")?,
+        Balance::Units {
+            amount: "0.5".to_string(),
+            symbol: None,
+        }
+    );
+
+    // A decimal amount needs a unit
+    assert!(Balance::from_str("1.5").is_err());
+    assert!(Balance::from_str("1..5 DOT").is_err());
+    assert!(Balance::from_str("1. DOT").is_err());
+    assert!(Balance::from_str("DOT").is_err());
+    assert!(Balance::from_str("1 D-T").is_err());
+
+    Ok(())
+}
+
+/// Purpose: This test checks that balances are converted to the smallest unit of the network token.
+#[test]
+pub fn test_denominate_balance() -> Result<()> {
+    let token = dot();
+    assert_eq!(Balance::Raw(42).denominate(&token)?, 42);
+    assert_eq!(
+        Balance::from_str("1.5 DOT")?.denominate(&token)?,
+        15_000_000_000
+    );
+    assert_eq!(
+        Balance::from_str("1.5 dot")?.denominate(&token)?,
+        15_000_000_000
+    );
+    assert_eq!(
+        Balance::from_str("0.0000000001 unit")?.denominate(&token)?,
+        1
+    );
+
+    // The symbol must match the network token
+    assert!(Balance::from_str("1 KSM")?.denominate(&token).is_err());
+    // The amount cannot have more decimals than the token
+    assert!(Balance::from_str("0.00000000001 DOT")?
+        .denominate(&token)
+        .is_err());
+    // The amount must fit in a u128
+    assert!(Balance::from_str("100000000000000000000000000000000 DOT")?
+        .denominate(&token)
+        .is_err());
+
+    Ok(())
+}
+
+/// Purpose: This test checks that amounts and storage deposits are formatted in token units.
+#[test]
+pub fn test_format_balance() {
+    let token = dot();
+    assert_eq!(token.format(15_000_000_000), "1.5000 DOT");
+    assert_eq!(token.format(1), "0.0000 DOT");
+    assert_eq!(
+        token.format_storage_deposit(&StorageDeposit::Charge(20_000_000_000)),
+        "Charge: 2.0000 DOT"
+    );
+    assert_eq!(
+        token.format_storage_deposit(&StorageDeposit::Refund(5_000_000_000)),
+        "Refund: 0.5000 DOT"
+    );
+
+    let token = TokenInfo {
+        decimals: 2,
+        symbol: "UNIT".to_string(),
+    };
+    assert_eq!(token.format(150), "1.5000 UNIT");
+}
+
+/// Purpose: This test checks that the decimals and symbol of the network token are read from the
+/// system properties of a node, including chains with several tokens and chains without
+/// properties.
+#[test]
+pub fn test_token_from_properties() -> Result<()> {
+    let properties = |properties: serde_json::Value| properties.as_object().unwrap().clone();
+
+    let token = TokenInfo::from_properties(&properties(
+        json!({ "ss58Format": 0, "tokenDecimals": 10, "tokenSymbol": "DOT" }),
+    ))?;
+    assert_eq!(token, dot());
+
+    // The native token is the first one of chains with several tokens
+    let token = TokenInfo::from_properties(&properties(
+        json!({ "tokenDecimals": [12, 12], "tokenSymbol": ["ACA", "AUSD"] }),
+    ))?;
+    assert_eq!(token.decimals, 12);
+    assert_eq!(token.symbol, "ACA");
+
+    let token = TokenInfo::from_properties(&properties(json!({})))?;
+    assert_eq!(token.decimals, 12);
+    assert_eq!(token.symbol, "UNIT");
+
+    assert!(TokenInfo::from_properties(&properties(json!({ "tokenDecimals": 300 }))).is_err());
+    assert!(TokenInfo::from_properties(&properties(json!({ "tokenSymbol": 1 }))).is_err());
+    Ok(())
+}
+
+/// Purpose: This test checks that gas amounts are converted to and from weights.
+#[test]
+pub fn test_gas_weight() {
+    let gas = Gas {
+        ref_time: 1_000,
+        proof_size: 2_000,
+    };
+    let weight: Weight = gas.into();
+    assert_eq!(weight, Weight::from_parts(1_000, 2_000));
+    assert_eq!(Gas::from(weight), gas);
+    assert_eq!(gas.to_string(), "ref_time: 1000, proof_size: 2000");
+}
@@ -0,0 +1,25 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::Result,
+    aqd_polkadot_contracts::contract_address,
+    subxt::utils::{AccountId32, H256},
+};
+
+/// Purpose: This test checks that contract addresses are derived from the deployer, the code hash,
+/// the input data and the salt, the way the Contracts pallet does.
+#[test]
+pub fn test_contract_address() -> Result<()> {
+    let deployer = AccountId32::from([1; 32]);
+    let code_hash = H256::from([2; 32]);
+    let address = contract_address(&deployer, &code_hash, &[0xde, 0xad], &[0x42]);
+    assert_eq!(
+        hex::encode(address.0),
+        "ec3f122ec256ffcf05c30ee121c0c907bb2cb649fd3cf0573ffc3ba5df503048"
+    );
+
+    // A different salt gives a different address
+    let other = contract_address(&deployer, &code_hash, &[0xde, 0xad], &[0x43]);
+    assert_ne!(address, other);
+    Ok(())
+}
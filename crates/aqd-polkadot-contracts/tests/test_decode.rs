@@ -0,0 +1,51 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::Result,
+    aqd_polkadot_contracts::{decode_contract_event, decode_message_return, load_transcoder},
+    serde_json::json,
+    std::path::Path,
+    subxt::utils::H256,
+};
+
+/// Purpose: This test checks that the return value of a message is decoded to JSON with the
+/// metadata of the contract, wrapped in the `Result` of the ink! messages.
+/// The metadata is defined in tests/contracts/incrementer.json.
+#[test]
+pub fn test_decode_message_return() -> Result<()> {
+    let transcoder = load_transcoder(Path::new("tests/contracts/incrementer.json"))?;
+
+    // Ok(-5i32)
+    let mut data = vec![0];
+    data.extend_from_slice(&(-5i32).to_le_bytes());
+    assert_eq!(
+        decode_message_return(&transcoder, "get", &data)?,
+        json!({ "Ok": -5 })
+    );
+
+    // Ok(())
+    assert_eq!(
+        decode_message_return(&transcoder, "inc", &[0])?,
+        json!({ "Ok": null })
+    );
+
+    // Err(LangError::CouldNotReadInput)
+    assert_eq!(
+        decode_message_return(&transcoder, "get", &[1, 1])?,
+        json!({ "Err": "CouldNotReadInput" })
+    );
+
+    assert!(decode_message_return(&transcoder, "dec", &[0]).is_err());
+    Ok(())
+}
+
+/// Purpose: This test checks that the data of an event that the contract does not define, or
+/// without a signature topic, is reported as an error rather than decoded.
+#[test]
+pub fn test_decode_unknown_contract_event() -> Result<()> {
+    let transcoder = load_transcoder(Path::new("tests/contracts/incrementer.json"))?;
+    let topics = [H256::repeat_byte(7)];
+    assert!(decode_contract_event(&transcoder, &topics, &[7, 1, 2, 3]).is_err());
+    assert!(decode_contract_event(&transcoder, &[], &[7, 1, 2, 3]).is_err());
+    Ok(())
+}
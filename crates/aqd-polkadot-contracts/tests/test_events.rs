@@ -0,0 +1,67 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::Result,
+    aqd_polkadot_contracts::value_to_json,
+    scale_info::{meta_type, PortableRegistry, Registry},
+    serde_json::json,
+    subxt::ext::scale_value::{Composite, Value, ValueDef, Variant},
+};
+
+/// Purpose: This test checks that decoded values are converted to JSON with named fields, hex
+/// byte arrays and large integers as strings.
+#[test]
+pub fn test_value_to_json() -> Result<()> {
+    let mut registry = Registry::new();
+    let u8_id = registry.register_type(&meta_type::<u8>()).id;
+    let u32_id = registry.register_type(&meta_type::<u32>()).id;
+    let types = PortableRegistry::from(registry);
+
+    // The values decoded with the registry: the elements of byte arrays are decoded as u8
+    let bytes = |bytes: &[u8]| Value::from_bytes(bytes).map_context(|_| u8_id);
+    let value = |value: Value<()>| value.map_context(|_| u32_id);
+    let composite = |composite| Value {
+        value: ValueDef::Composite(composite),
+        context: u32_id,
+    };
+    let variant = |name: &str, values| Value {
+        value: ValueDef::Variant(Variant {
+            name: name.to_string(),
+            values: Composite::Unnamed(values),
+        }),
+        context: u32_id,
+    };
+
+    let info = composite(Composite::Named(vec![
+        ("code_hash".to_string(), bytes(&[0xab; 32])),
+        ("deposit".to_string(), value(Value::u128(u128::MAX))),
+        ("refcount".to_string(), value(Value::u128(1))),
+        ("determinism".to_string(), variant("Enforced", vec![])),
+        ("owner".to_string(), variant("Some", vec![bytes(&[1, 2])])),
+    ]));
+    assert_eq!(
+        value_to_json(&info, &types),
+        json!({
+            "code_hash": format!("0x{}", "ab".repeat(32)),
+            "deposit": u128::MAX.to_string(),
+            "refcount": 1,
+            "determinism": "Enforced",
+            "owner": { "Some": "0x0102" },
+        })
+    );
+
+    // A wrapper type is converted to the value it wraps
+    let wrapper = value(Value::unnamed_composite([Value::bool(true)]));
+    assert_eq!(value_to_json(&wrapper, &types), json!(true));
+
+    let tuple = value(Value::unnamed_composite([
+        Value::string("a"),
+        Value::i128(-1),
+    ]));
+    assert_eq!(value_to_json(&tuple, &types), json!(["a", -1]));
+
+    // Small integers of other types than u8 are not bytes
+    let indexes = value(Value::unnamed_composite([Value::u128(1), Value::u128(2)]));
+    assert_eq!(value_to_json(&indexes, &types), json!([1, 2]));
+    Ok(())
+}
@@ -0,0 +1,106 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::Result,
+    aqd_polkadot_contracts::{adapt_metadata, ContractSpec, MetadataVersion, StorageLayout},
+    serde_json::json,
+    std::path::Path,
+};
+
+/// Purpose: This test checks that the constructors and messages of a contract are read from its
+/// metadata, with their selectors, argument types, mutability and return types.
+/// The metadata is defined in tests/contracts/incrementer.json.
+#[test]
+pub fn test_contract_spec() -> Result<()> {
+    let spec = ContractSpec::from_file(Path::new("tests/contracts/incrementer.json"))?;
+    assert_eq!(spec.name, "incrementer");
+    assert_eq!(spec.version, "0.1.0");
+    assert_eq!(spec.metadata_version, "v4");
+
+    let labels: Vec<&str> = spec.constructors.iter().map(|c| c.label.as_str()).collect();
+    assert_eq!(labels, vec!["new", "new_default"]);
+    let new = &spec.constructors[0];
+    assert_eq!(new.selector, "0x9bae9d5e");
+    assert_eq!(new.args.len(), 1);
+    assert_eq!(new.args[0].name, "init_value");
+    assert_eq!(new.args[0].ty, "i32");
+    assert!(new.mutates);
+    assert!(!new.payable);
+    assert_eq!(new.return_type, None);
+
+    let inc = spec.find("inc").expect("inc message");
+    assert_eq!(inc.selector, "0x1d32619f");
+    assert!(inc.mutates);
+    assert_eq!(inc.return_type, None);
+
+    let get = spec.find("get").expect("get message");
+    assert_eq!(get.selector, "0x2f865bd9");
+    assert!(get.args.is_empty());
+    assert!(!get.mutates);
+    assert_eq!(get.return_type.as_deref(), Some("i32"));
+
+    assert!(spec.find("dec").is_none());
+    Ok(())
+}
+
+/// Purpose: This test checks that the version of the metadata is detected, that v5 metadata is
+/// adapted to the v4 format, and that unsupported versions are rejected with the supported ones.
+#[test]
+pub fn test_metadata_versions() -> Result<()> {
+    let (version, _) = adapt_metadata(json!({ "version": "4", "spec": {} }))?;
+    assert_eq!(version, MetadataVersion::V4);
+
+    let v5 = json!({
+        "version": 5,
+        "spec": {
+            "events": [{
+                "label": "Flipped",
+                "module_path": "flipper::flipper",
+                "signature_topic": "0x1234",
+                "args": [],
+            }],
+        },
+    });
+    let (version, adapted) = adapt_metadata(v5)?;
+    assert_eq!(version, MetadataVersion::V5);
+    assert_eq!(adapted["version"], "4");
+    assert_eq!(
+        adapted["spec"]["events"][0],
+        json!({ "label": "Flipped", "args": [] })
+    );
+
+    for metadata in [
+        json!({ "metadataVersion": "0.1.0", "V3": {} }),
+        json!({ "version": "6" }),
+        json!({ "name": "flipper" }),
+    ] {
+        let err = adapt_metadata(metadata).unwrap_err().to_string();
+        assert!(err.contains("Supported versions"), "{}", err);
+    }
+    Ok(())
+}
+
+/// Purpose: This test checks that the storage layout of a contract is read from its metadata as a
+/// tree, with the keys, types and paths of its leaves.
+/// The metadata is defined in tests/contracts/incrementer.json.
+#[test]
+pub fn test_storage_layout() -> Result<()> {
+    let mut layout = StorageLayout::from_file(Path::new("tests/contracts/incrementer.json"))?
+        .expect("storage layout");
+    assert_eq!(layout.kind, "root");
+    assert_eq!(layout.key.as_deref(), Some("0x00000000"));
+
+    let root = &layout.children[0];
+    assert_eq!(root.kind, "struct");
+    assert_eq!(root.name, "storage: Incrementer");
+
+    let leaves = layout.leaves_mut();
+    assert_eq!(leaves.len(), 1);
+    assert_eq!(leaves[0].name, "value");
+    assert_eq!(leaves[0].ty.as_deref(), Some("i32"));
+    assert_eq!(leaves[0].path.as_deref(), Some("value"));
+
+    // Metadata without a storage layout has no layout
+    assert!(StorageLayout::from_json(r#"{ "version": "4", "types": [] }"#)?.is_none());
+    Ok(())
+}
@@ -0,0 +1,47 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::Result,
+    aqd_polkadot_contracts::{
+        parse_contract_address, revive_account, revive_address, revive_contract_address,
+    },
+    sp_core::H160,
+    subxt::utils::AccountId32,
+};
+
+/// Purpose: This test checks that the 20-byte addresses of pallet-revive are converted to and from
+/// accounts, and that they can be parsed as contract addresses along with SS58 addresses.
+#[test]
+pub fn test_revive_address() -> Result<()> {
+    let address = H160::from([0xab; 20]);
+    let account = revive_account(&address);
+    assert_eq!(&account.0[20..], &[0xee; 12]);
+    assert_eq!(revive_address(&account), address);
+
+    // Other accounts are mapped to the last 20 bytes of their Keccak hash
+    let alice = AccountId32::from([1; 32]);
+    assert_ne!(revive_address(&alice).as_bytes(), &alice.0[..20]);
+
+    let parsed = parse_contract_address("0xabababababababababababababababababababab")?;
+    assert_eq!(parsed, account);
+    let parsed = parse_contract_address("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY")?;
+    assert_eq!(
+        hex::encode(parsed.0),
+        "d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d"
+    );
+    assert!(parse_contract_address("0xabab").is_err());
+    Ok(())
+}
+
+/// Purpose: This test checks that the addresses of contracts instantiated with a salt on
+/// pallet-revive are derived as the `CREATE2` opcode of Ethereum does.
+#[test]
+pub fn test_revive_contract_address() -> Result<()> {
+    // The first example of EIP-1014, with the code and the input data as init code
+    let address = revive_contract_address(&H160::zero(), &[0x00], &[], &[0; 32]);
+    assert_eq!(
+        hex::encode(address.as_bytes()),
+        "4d1a2e2bb4f88f0250f26ffff098b0b30b26bf38"
+    );
+    Ok(())
+}
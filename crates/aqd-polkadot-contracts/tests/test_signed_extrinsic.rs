@@ -0,0 +1,45 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::Result,
+    aqd_polkadot_contracts::assemble_signed_extrinsic,
+    subxt::{
+        ext::codec::{Compact, Encode},
+        utils::AccountId32,
+    },
+};
+
+/// Purpose: This test checks that a signed extrinsic is assembled from the signer, the signature, the signed extra parameters and the call data of an exported call.
+#[test]
+pub fn test_assemble_signed_extrinsic() -> Result<()> {
+    let signer = AccountId32([1; 32]);
+    let extra = [3, 4];
+    let call_data = [5, 6, 7];
+
+    // The signed extrinsic of version 4, with the `Id` address and the `Sr25519` signature
+    let mut inner = vec![0x84, 0x00];
+    inner.extend([1; 32]);
+    inner.push(0x01);
+    inner.extend([2; 64]);
+    inner.extend(extra);
+    inner.extend(call_data);
+    let mut expected = Compact(inner.len() as u32).encode();
+    expected.extend(inner);
+
+    // A raw sr25519 signature
+    let extrinsic = assemble_signed_extrinsic(&signer, &[2; 64], &extra, &call_data)?;
+    assert_eq!(extrinsic, expected);
+
+    // A SCALE-encoded MultiSignature, as returned by Polkadot Vault
+    let mut signature = vec![0x01];
+    signature.extend([2; 64]);
+    let extrinsic = assemble_signed_extrinsic(&signer, &signature, &extra, &call_data)?;
+    assert_eq!(extrinsic, expected);
+
+    // Neither of them
+    assert!(assemble_signed_extrinsic(&signer, &[2; 10], &extra, &call_data).is_err());
+    signature.push(0);
+    assert!(assemble_signed_extrinsic(&signer, &signature, &extra, &call_data).is_err());
+
+    Ok(())
+}
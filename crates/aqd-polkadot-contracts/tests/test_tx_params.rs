@@ -0,0 +1,91 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::Result,
+    aqd_polkadot_contracts::{Balance, Era, ExtrinsicOptions, TokenInfo, TxParams, WaitFor},
+    std::str::FromStr,
+    url::Url,
+};
+
+/// Purpose: This test checks that eras are parsed as a number of blocks or as immortal.
+#[test]
+pub fn test_parse_era() -> Result<()> {
+    assert_eq!(Era::from_str("immortal")?, Era::Immortal);
+    assert_eq!(Era::from_str("Immortal")?, Era::Immortal);
+    assert_eq!(Era::from_str("64")?, Era::Mortal(64));
+
+    assert!(Era::from_str("0").is_err());
+    assert!(Era::from_str("-1").is_err());
+    assert!(Era::from_str("mortal").is_err());
+
+    Ok(())
+}
+
+/// Purpose: This test checks that the statuses to wait for are parsed.
+#[test]
+pub fn test_parse_wait_for() -> Result<()> {
+    assert_eq!(WaitFor::from_str("in-block")?, WaitFor::InBlock);
+    assert_eq!(WaitFor::from_str("finalized")?, WaitFor::Finalized);
+    assert_eq!(WaitFor::default(), WaitFor::Finalized);
+
+    assert!(WaitFor::from_str("ready").is_err());
+
+    Ok(())
+}
+
+/// Purpose: This test checks that the tip, era, nonce and waiting strategy of the options are converted to the parameters of the extrinsic.
+#[test]
+pub fn test_tx_params() -> Result<()> {
+    let token = TokenInfo {
+        decimals: 12,
+        symbol: "UNIT".to_string(),
+    };
+    let options = ExtrinsicOptions::new(
+        "flipper.contract",
+        Url::parse("ws://localhost:9944")?,
+        "//Alice",
+    );
+    assert_eq!(options.tx_params(&token)?, TxParams::default());
+
+    let options = options
+        .tip(Some(Balance::from_str("0.5 UNIT")?))
+        .era(Era::Mortal(32))
+        .nonce(Some(7))
+        .wait_for(WaitFor::InBlock)
+        .sudo(true);
+    assert_eq!(
+        options.tx_params(&token)?,
+        TxParams {
+            tip: 500_000_000_000,
+            era: Era::Mortal(32),
+            nonce: Some(7),
+            wait_for: WaitFor::InBlock,
+            sudo: true,
+        }
+    );
+
+    // The tip must be in the network token
+    let options = options.tip(Some(Balance::from_str("1 DOT")?));
+    assert!(options.tx_params(&token).is_err());
+
+    Ok(())
+}
+
+/// Purpose: This test checks that the URL of the options is tried before its fallback URLs.
+#[test]
+pub fn test_fallback_urls() -> Result<()> {
+    let url = Url::parse("wss://rpc1.example.com")?;
+    let options = ExtrinsicOptions::new("flipper.contract", url.clone(), "//Alice");
+    assert_eq!(options.urls(), vec![url.clone()]);
+
+    let fallback_urls = vec![
+        Url::parse("wss://rpc2.example.com")?,
+        Url::parse("wss://rpc3.example.com")?,
+    ];
+    let options = options.fallback_urls(fallback_urls.clone());
+    assert_eq!(
+        options.urls(),
+        vec![url, fallback_urls[0].clone(), fallback_urls[1].clone()]
+    );
+    Ok(())
+}
@@ -0,0 +1,159 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    serde_json::json,
+    std::{fmt::Debug, process::exit},
+};
+
+use {
+    super::{
+        info::{fetch_code_info, fetch_storage},
+        CLINetworkOpts,
+    },
+    aqd_polkadot_contracts::value_to_json,
+    aqd_utils::{check_target_match, print_key_value, print_title, ErrorKind, OutputFormat},
+    contract_extrinsics::DefaultConfig,
+    subxt::{
+        dynamic::{At, Value},
+        Config,
+    },
+};
+
+#[derive(Debug, clap::Args)]
+#[clap(
+    name = "balance",
+    about = "Show the balance and the deposits held for a contract on Polkadot"
+)]
+pub struct PolkadotBalanceCommand {
+    #[clap(
+        name = "contract",
+        long,
+        help = "Specifies the address of the contract to show the balance of."
+    )]
+    contract: <DefaultConfig as Config>::AccountId,
+    #[clap(flatten)]
+    network_opts: CLINetworkOpts,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
+}
+
+/// The balance of a contract account and the deposits held for the contract, in the smallest unit
+/// of the network token.
+#[derive(Debug)]
+struct ContractBalance {
+    contract: String,
+    /// The balance of the contract account which can be transferred or used by the contract
+    free: u128,
+    /// The balance of the contract account which is reserved or held
+    reserved: u128,
+    /// The deposit held for the storage of the contract: its base deposit, and the deposits of
+    /// its storage bytes and items, paid by the callers which added storage
+    storage_deposit: u128,
+    code_hash: String,
+    /// The account which uploaded the code of the contract
+    code_owner: Option<String>,
+    /// The deposit held from the code owner for the code of the contract
+    code_deposit: Option<u128>,
+}
+
+impl PolkadotBalanceCommand {
+    /// Handles the querying of the balance of a contract on the Polkadot network.
+    ///
+    /// The balance of the contract account is read from `System::Account`, the storage deposit
+    /// from the `ContractInfo` of the contract, and the deposit of its code from its `CodeInfo`.
+    pub async fn handle(&self) -> Result<()> {
+        // Make sure the command is run in the correct directory
+        // Fails if the command is run in a Solang Solana project directory
+        let target_match = check_target_match("polkadot", None)
+            .map_err(|e| anyhow!("Failed to check current directory: {}", e))?;
+        if !target_match {
+            exit(ErrorKind::Usage.exit_code());
+        }
+
+        let client = self.network_opts.connect().await?;
+        let metadata = client.metadata();
+        let contract_key = || vec![Value::from_bytes(self.contract.0)];
+
+        let contract_info = fetch_storage(&client, "Contracts", "ContractInfoOf", contract_key())
+            .await?
+            .ok_or_else(|| anyhow!("No contract found at address {}", self.contract))?;
+        let account = fetch_storage(&client, "System", "Account", contract_key()).await?;
+        let balance_of = |field: &str| {
+            account
+                .as_ref()
+                .at("data")
+                .at(field)
+                .and_then(Value::as_u128)
+                .unwrap_or(0)
+        };
+
+        // The total deposit of the contract storage, as computed by the Contracts pallet
+        let storage_deposit = [
+            "storage_base_deposit",
+            "storage_byte_deposit",
+            "storage_item_deposit",
+        ]
+        .iter()
+        .filter_map(|field| contract_info.at(*field).and_then(Value::as_u128))
+        .sum();
+
+        let code_hash = contract_info
+            .at("code_hash")
+            .map(|code_hash| value_to_json(code_hash, metadata.types()))
+            .and_then(|code_hash| code_hash.as_str().map(|c| c.to_string()))
+            .ok_or_else(|| anyhow!("Missing code hash in the contract info"))?;
+        let code_info =
+            fetch_code_info(&client, &hex::decode(code_hash.trim_start_matches("0x"))?).await?;
+        let code_owner = code_info
+            .as_ref()
+            .at("owner")
+            .map(|owner| value_to_json(owner, metadata.types()))
+            .and_then(|owner| owner.as_str().map(|o| o.to_string()));
+        let code_deposit = code_info.as_ref().at("deposit").and_then(Value::as_u128);
+
+        let balance = ContractBalance {
+            contract: self.contract.to_string(),
+            free: balance_of("free"),
+            reserved: balance_of("reserved"),
+            storage_deposit,
+            code_hash,
+            code_owner,
+            code_deposit,
+        };
+
+        if self.output.is_structured() {
+            let output = json!({
+                "contract": balance.contract,
+                "free": balance.free,
+                "reserved": balance.reserved,
+                "storage_deposit": balance.storage_deposit,
+                "code_hash": balance.code_hash,
+                "code_owner": balance.code_owner,
+                "code_deposit": balance.code_deposit,
+            });
+            self.output.print(&output)?;
+        } else {
+            let token = self.network_opts.token().await?;
+            print_title!("Contract balance");
+            print_key_value!("Contract", balance.contract);
+            print_key_value!("Free", token.format(balance.free));
+            print_key_value!("Reserved", token.format(balance.reserved));
+            print_key_value!("Storage deposit", token.format(balance.storage_deposit));
+            print_title!("Code");
+            print_key_value!("Code hash", balance.code_hash);
+            if let Some(owner) = &balance.code_owner {
+                print_key_value!("Owner", owner);
+            }
+            if let Some(deposit) = balance.code_deposit {
+                print_key_value!("Deposit", token.format(deposit));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,293 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Context, Result},
+    serde_json::{json, Value as JsonValue},
+    std::{
+        fmt::Debug,
+        fs,
+        path::{Path, PathBuf},
+        process::exit,
+        str::FromStr,
+    },
+};
+
+use {
+    super::{split_urls, CLIExtrinsicOpts},
+    aqd_polkadot_contracts::{
+        contracts_call_payload, ensure_account_mapped, pair_signer, parse_contract_address,
+        submit_extrinsic, typed_events, with_debug_output, Balance, ContractsPallet,
+        ExtrinsicOptions, Gas, PolkadotCall,
+    },
+    aqd_utils::{
+        check_target_match, print_key_value, print_subtitle, print_text, print_title,
+        print_warning, prompt_confirm_transaction, ErrorKind, OutputFormat,
+    },
+    contract_build::Verbosity,
+    contract_extrinsics::{DefaultConfig, DisplayEvents},
+    subxt::{dynamic::Value, Config},
+};
+
+#[derive(Debug, clap::Args)]
+#[clap(
+    name = "batch",
+    about = "Submit several contract calls as a single extrinsic on Polkadot"
+)]
+pub struct PolkadotBatchCommand {
+    #[clap(
+        long,
+        value_parser,
+        help = "Specifies the path to a JSON file listing the calls to submit. For example:\n
+    [{\"contract\": \"5F...\", \"message\": \"transfer\", \"args\": [\"5G...\", \"100\"], \"value\": \"0\"}]\n
+    Each call can also specify its own \"metadata\" file, and a \"gas_limit\" and \"proof_size\"."
+    )]
+    calls: PathBuf,
+    #[clap(flatten)]
+    extrinsic_cli_opts: CLIExtrinsicOpts,
+    #[clap(
+        short('y'),
+        long,
+        env = "AQD_SKIP_CONFIRM",
+        help = "Specifies whether to skip the confirmation prompt."
+    )]
+    skip_confirm: bool,
+}
+
+/// A contract call of the batch, as listed in the calls file.
+#[derive(Debug)]
+struct CallSpec {
+    contract: <DefaultConfig as Config>::AccountId,
+    message: String,
+    args: Vec<String>,
+    value: Balance,
+    metadata: Option<PathBuf>,
+    gas_limit: Option<u64>,
+    proof_size: Option<u64>,
+}
+
+impl PolkadotBatchCommand {
+    /// Returns the format of the output.
+    pub fn output(&self) -> OutputFormat {
+        self.extrinsic_cli_opts.output
+    }
+
+    /// Handles the submission of a batch of contract calls on the Polkadot network.
+    ///
+    /// Each call of the batch is dry-run first, and the batch is rejected if any of them fails or
+    /// reverts. If the `execute` flag is set, the calls are then submitted as a single
+    /// `Utility::batch_all` extrinsic, signed once, which reverts all the calls if one of them fails.
+    pub async fn handle(&self) -> Result<()> {
+        // Make sure the command is run in the correct directory
+        // Fails if the command is run in a Solang Solana project directory
+        let target_match = check_target_match("polkadot", None)
+            .map_err(|e| anyhow!("Failed to check current directory: {}", e))?;
+        if !target_match {
+            exit(ErrorKind::Usage.exit_code());
+        }
+
+        let specs = read_call_specs(&self.calls)?;
+        if specs.is_empty() {
+            return Err(anyhow!("No calls found in {}", self.calls.display()));
+        }
+
+        // The secret key URI is read once, as reading it may prompt for a password
+        let (url, fallback_urls) = split_urls(self.extrinsic_cli_opts.endpoints().await?)?;
+        let suri = self.extrinsic_cli_opts.suri()?;
+        let token = self.extrinsic_cli_opts.token().await?;
+
+        // Dry-run each call, and build the calls of the batch
+        let mut calls = vec![];
+        let mut batch_calls = vec![];
+        let mut dry_runs = vec![];
+        for (i, spec) in specs.iter().enumerate() {
+            let file = spec
+                .metadata
+                .clone()
+                .unwrap_or_else(|| self.extrinsic_cli_opts.file.clone());
+            let options = ExtrinsicOptions::new(file, url.clone(), suri.clone())
+                .fallback_urls(fallback_urls.clone())
+                .storage_deposit_limit(self.extrinsic_cli_opts.storage_deposit_limit.clone())
+                .token(token.clone());
+            let call = PolkadotCall::builder(options, spec.contract.clone(), spec.message.clone())
+                .args(spec.args.clone())
+                .value(spec.value.clone())
+                .gas_limit(spec.gas_limit)
+                .proof_size(spec.proof_size)
+                .done()
+                .await
+                .context(format!(
+                    "Failed to prepare call {} ({})",
+                    i + 1,
+                    spec.message
+                ))?;
+
+            let result = call.dry_run().await.context(format!(
+                "Dry run of call {} ({}) failed",
+                i + 1,
+                spec.message
+            ))?;
+            if result.reverted {
+                return Err(with_debug_output(
+                    format!("Dry run of call {} ({}) reverted", i + 1, spec.message),
+                    &result.debug_output,
+                ));
+            }
+            let gas_limit = Gas {
+                ref_time: spec.gas_limit.unwrap_or(result.gas_required.ref_time),
+                proof_size: spec.proof_size.unwrap_or(result.gas_required.proof_size),
+            };
+
+            let payload = contracts_call_payload(
+                call.pallet(),
+                &spec.contract,
+                call.value(),
+                gas_limit,
+                call.storage_deposit_limit(),
+                call.call_data()?,
+            );
+            batch_calls.push(payload.into_value());
+
+            dry_runs.push(json!({
+                "contract": spec.contract.to_string(),
+                "message": spec.message,
+                "args": spec.args,
+                "gas_required": result.gas_required,
+            }));
+            calls.push(call);
+        }
+
+        if !self.extrinsic_cli_opts.execute {
+            if self.output().is_structured() {
+                self.output().print(&dry_runs)?;
+            } else {
+                print_title!("Batch Dry Run Result");
+                for (i, spec) in specs.iter().enumerate() {
+                    print_subtitle!(format!("Call {}", i + 1));
+                    print_key_value!("Contract", spec.contract);
+                    print_key_value!("Message", spec.message);
+                    print_key_value!("Args", spec.args.join(" "));
+                    print_key_value!(
+                        "Gas required",
+                        format!(
+                            "ref_time: {}, proof_size: {}",
+                            dry_runs[i]["gas_required"]["ref_time"],
+                            dry_runs[i]["gas_required"]["proof_size"]
+                        )
+                    );
+                }
+                print_warning!("Execution of your batch has NOT been completed. To submit the transaction and execute the calls on chain, please include -x/--execute flag.");
+            }
+            return Ok(());
+        }
+
+        if !self.skip_confirm {
+            prompt_confirm_transaction(|| {
+                print_title!("Batch Summary");
+                for (i, spec) in specs.iter().enumerate() {
+                    print_key_value!(
+                        format!("Call {}", i + 1),
+                        format!("{} {} {}", spec.contract, spec.message, spec.args.join(" "))
+                    );
+                }
+            })?;
+        }
+
+        let first_call = &calls[0];
+        let client = first_call.client();
+        let signer = pair_signer(&suri)?;
+        let batch = subxt::dynamic::tx(
+            "Utility",
+            "batch_all",
+            vec![("calls", Value::unnamed_composite(batch_calls))],
+        );
+        let tx_params = self.extrinsic_cli_opts.tx_params(&token)?;
+        if first_call.pallet() == ContractsPallet::Revive {
+            ensure_account_mapped(client, &signer, &tx_params).await?;
+        }
+        let events = submit_extrinsic(client, &batch, &signer, &tx_params)
+            .await
+            .map_err(|err| anyhow!("Error submitting the batch: {}", err))?;
+
+        // The events can only be decoded with the metadata of the contracts if all the calls
+        // share the same metadata
+        let transcoder = specs
+            .iter()
+            .all(|spec| spec.metadata.is_none())
+            .then(|| first_call.transcoder());
+        let output = if self.output().is_structured() {
+            self.output()
+                .format(&typed_events(&events, transcoder, &client.metadata())?)?
+        } else {
+            DisplayEvents::from_events(&events, transcoder, &client.metadata())?
+                .display_events(Verbosity::Default, &first_call.token_metadata())?
+        };
+        print_text!(output);
+        Ok(())
+    }
+}
+
+/// Read the calls of a batch from a JSON file.
+fn read_call_specs(path: &Path) -> Result<Vec<CallSpec>> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+    let calls: JsonValue = serde_json::from_str(&content)
+        .map_err(|e| anyhow!("Failed to parse {}: {}", path.display(), e))?;
+    calls
+        .as_array()
+        .ok_or_else(|| anyhow!("{} must contain an array of calls", path.display()))?
+        .iter()
+        .enumerate()
+        .map(|(i, call)| {
+            let string_field = |name: &str| -> Result<Option<String>> {
+                match call.get(name) {
+                    None | Some(JsonValue::Null) => Ok(None),
+                    Some(JsonValue::String(s)) => Ok(Some(s.clone())),
+                    // Allow numbers for the value, e.g. "value": 100
+                    Some(JsonValue::Number(n)) => Ok(Some(n.to_string())),
+                    Some(_) => Err(anyhow!("Invalid {} for call {}", name, i + 1)),
+                }
+            };
+            let u64_field = |name: &str| -> Result<Option<u64>> {
+                match call.get(name) {
+                    None | Some(JsonValue::Null) => Ok(None),
+                    Some(value) => value
+                        .as_u64()
+                        .map(Some)
+                        .ok_or_else(|| anyhow!("Invalid {} for call {}", name, i + 1)),
+                }
+            };
+
+            let contract = string_field("contract")?
+                .ok_or_else(|| anyhow!("Missing contract for call {}", i + 1))?;
+            let contract = parse_contract_address(&contract)
+                .map_err(|e| anyhow!("Invalid contract for call {}: {}", i + 1, e))?;
+            let message = string_field("message")?
+                .ok_or_else(|| anyhow!("Missing message for call {}", i + 1))?;
+            let args = match call.get("args") {
+                None | Some(JsonValue::Null) => vec![],
+                Some(JsonValue::Array(args)) => args
+                    .iter()
+                    .map(|arg| match arg {
+                        JsonValue::String(s) => s.clone(),
+                        arg => arg.to_string(),
+                    })
+                    .collect(),
+                Some(_) => return Err(anyhow!("Invalid args for call {}", i + 1)),
+            };
+            let value = match string_field("value")? {
+                Some(value) => Balance::from_str(&value)
+                    .map_err(|e| anyhow!("Invalid value for call {}: {}", i + 1, e))?,
+                None => Balance::Raw(0),
+            };
+            Ok(CallSpec {
+                contract,
+                message,
+                args,
+                value,
+                metadata: string_field("metadata")?.map(PathBuf::from),
+                gas_limit: u64_field("gas_limit")?,
+                proof_size: u64_field("proof_size")?,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,168 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    futures::{stream, StreamExt},
+    std::{fmt::Debug, process::exit, time::Instant},
+};
+
+use {
+    super::CLIExtrinsicOpts,
+    aqd_polkadot_contracts::{parse_contract_address, Balance, PolkadotCall},
+    aqd_utils::{
+        check_target_match, is_quiet, parse_concurrency, parse_iterations, print_key_value,
+        print_title, prompt_confirm_transaction, BenchReport, BenchSample, ErrorKind,
+    },
+    contract_extrinsics::DefaultConfig,
+    subxt::Config,
+};
+
+#[derive(Debug, clap::Args)]
+#[clap(
+    name = "bench",
+    about = "Call a contract message repeatedly and report the latency percentiles, the failures and the throughput"
+)]
+pub struct PolkadotBenchCommand {
+    #[clap(
+        name = "contract",
+        long,
+        value_parser = parse_contract_address,
+        help = "Specifies the address of the contract to call, as SS58 or as a 20-byte hex address on pallet-revive chains."
+    )]
+    contract: <DefaultConfig as Config>::AccountId,
+    #[clap(
+        long,
+        short,
+        help = "Specifies the name of the contract message to call."
+    )]
+    message: String,
+    #[clap(long, num_args = 0.., help = "Specifies the arguments of the contract message to call.")]
+    args: Vec<String>,
+    #[clap(flatten)]
+    extrinsic_cli_opts: CLIExtrinsicOpts,
+    #[clap(
+        name = "value",
+        long,
+        default_value = "0",
+        help = "Specifies the value to be transferred as part of each call.
+    Either an amount in the smallest unit of the token, or in token units, e.g. 1.5 DOT or 0.5unit."
+    )]
+    value: Balance,
+    #[clap(
+        name = "gas",
+        long,
+        help = "Specifies the maximum amount of gas to be used for each call. Estimated once with a dry run by default."
+    )]
+    gas_limit: Option<u64>,
+    #[clap(long, help = "Specifies the maximum proof size for each call.")]
+    proof_size: Option<u64>,
+    #[clap(
+        long,
+        default_value = "10",
+        value_parser = parse_iterations,
+        help = "Specifies the number of calls to submit, or to dry-run without -x/--execute."
+    )]
+    iterations: usize,
+    #[clap(
+        long,
+        default_value = "1",
+        value_parser = parse_concurrency,
+        help = "Specifies the number of calls submitted concurrently."
+    )]
+    concurrency: usize,
+    #[clap(
+        short('y'),
+        long,
+        env = "AQD_SKIP_CONFIRM",
+        help = "Specifies whether to skip the confirmation prompt."
+    )]
+    skip_confirm: bool,
+}
+
+impl PolkadotBenchCommand {
+    /// Handles the benchmark of a contract message.
+    ///
+    /// Without the `execute` flag, the call is dry-run repeatedly, which measures the RPC node
+    /// and the contract execution without submitting anything. With it, the gas limit is
+    /// estimated once, then the extrinsics are submitted with consecutive nonces, so that they
+    /// can be in the transaction pool at the same time, and each is waited for until it is
+    /// included in a block or finalized. An extrinsic rejected by the transaction pool leaves a
+    /// gap in the nonces, which delays the following extrinsics until they become invalid.
+    ///
+    /// The benchmarked calls are not recorded in the history.
+    pub async fn handle(&self) -> Result<()> {
+        // Make sure the command is run in the correct directory
+        // Fails if the command is run in a Solang Solana project directory
+        let target_match = check_target_match("polkadot", None)
+            .map_err(|e| anyhow!("Failed to check current directory: {}", e))?;
+        if !target_match {
+            exit(ErrorKind::Usage.exit_code());
+        }
+
+        let output = self.extrinsic_cli_opts.output;
+        let token = self.extrinsic_cli_opts.token().await?;
+        let options = self.extrinsic_cli_opts.options(&token).await?;
+        let call = PolkadotCall::builder(options, self.contract.clone(), self.message.clone())
+            .args(self.args.clone())
+            .value(self.value.clone())
+            .gas_limit(self.gas_limit)
+            .proof_size(self.proof_size)
+            .done()
+            .await?;
+
+        let execute = self.extrinsic_cli_opts.execute;
+        let (gas_limit, first_nonce) = if execute {
+            let gas_limit = call.estimate_gas().await?;
+            if !self.skip_confirm {
+                prompt_confirm_transaction(|| {
+                    print_title!("Benchmark Summary");
+                    print_key_value!("Message", call.message());
+                    print_key_value!("Args", call.args().join(" "));
+                    print_key_value!("Value", token.format(call.value()));
+                    print_key_value!("Gas limit", gas_limit.to_string());
+                    print_key_value!("Extrinsics", self.iterations);
+                    print_key_value!("Concurrency", self.concurrency);
+                })?;
+            }
+            (Some(gas_limit), call.next_nonce().await?)
+        } else {
+            (None, 0)
+        };
+        if !output.is_structured() && !is_quiet() {
+            print_title!(format!(
+                "{} {} {} times, {} at a time",
+                if execute { "Calling" } else { "Dry-running" },
+                call.message(),
+                self.iterations,
+                self.concurrency
+            ));
+        }
+
+        let start = Instant::now();
+        let samples: Vec<BenchSample> = stream::iter(0..self.iterations)
+            .map(|index| {
+                let call = &call;
+                async move {
+                    let start = Instant::now();
+                    let result = match gas_limit {
+                        Some(gas_limit) => call
+                            .submit_with_nonce(gas_limit, first_nonce + index as u64)
+                            .await
+                            .map(|_| ()),
+                        None => call.dry_run().await.and_then(|result| {
+                            if result.reverted {
+                                Err(anyhow!("The call reverted: {}", result.data))
+                            } else {
+                                Ok(())
+                            }
+                        }),
+                    };
+                    BenchSample::new(start.elapsed(), result)
+                }
+            })
+            .buffer_unordered(self.concurrency)
+            .collect()
+            .await;
+        BenchReport::new(&samples, self.concurrency, start.elapsed()).print(output)
+    }
+}
@@ -1,31 +1,40 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use {
-    anyhow::{anyhow, Context, Result},
-    colored::Colorize,
-    serde_json::{json, to_string_pretty},
+    anyhow::{anyhow, Result},
     std::{fmt::Debug, process::exit},
 };
 
 use {
-    super::CLIExtrinsicOpts,
+    super::{
+        multisig::{parse_multisig, print_multisig_result, submit_as_multi, Multisig},
+        print_cost_estimate, print_debug_output, CLIExtrinsicOpts,
+    },
+    aqd_polkadot_contracts::{
+        check_node_compatibility, contracts_call_payload, dry_run_call, export_call,
+        load_transcoder, pair_signer, parse_contract_address, Balance, CallDryRunResult,
+        ExportedCall, PolkadotCall, TokenInfo,
+    },
     aqd_utils::{
-        check_target_match, print_key_value, print_title, print_warning, prompt_confirm_transaction,
+        check_target_match, is_quiet, print_key_value, print_text, print_title, print_warning,
+        prompt_confirm_transaction, record_history, ErrorKind, HistoryEntry, OutputFormat,
     },
     contract_build::Verbosity,
-    contract_extrinsics::{
-        BalanceVariant, CallCommandBuilder, DefaultConfig, ExtrinsicOptsBuilder, StorageDeposit,
-    },
+    contract_extrinsics::DefaultConfig,
+    sp_core::blake2_256,
     subxt::Config,
 };
 
+type AccountId = <DefaultConfig as Config>::AccountId;
+
 #[derive(Debug, clap::Args)]
 #[clap(name = "call", about = "Call a contract on Polkadot")]
 pub struct PolkadotCallCommand {
     #[clap(
         name = "contract",
         long,
-        help = "Specifies the address of the contract to call."
+        value_parser = parse_contract_address,
+        help = "Specifies the address of the contract to call, as SS58 or as a 20-byte hex address on pallet-revive chains."
     )]
     contract: <DefaultConfig as Config>::AccountId,
     #[clap(
@@ -42,9 +51,10 @@ pub struct PolkadotCallCommand {
         name = "value",
         long,
         default_value = "0",
-        help = "Specifies the value to be transferred as part of the call."
+        help = "Specifies the value to be transferred as part of the call.
+    Either an amount in the smallest unit of the token, or in token units, e.g. 1.5 DOT or 0.5unit."
     )]
-    value: BalanceVariant,
+    value: Balance,
     #[clap(
         name = "gas",
         long,
@@ -56,94 +66,276 @@ pub struct PolkadotCallCommand {
     #[clap(
         short('y'),
         long,
+        env = "AQD_SKIP_CONFIRM",
         help = "Specifies whether to skip the confirmation prompt."
     )]
     skip_confirm: bool,
+    #[clap(
+        long,
+        value_parser = parse_multisig,
+        help = "Specifies a multisig to submit the call through, as its threshold followed by its members, e.g. 2,5F...,5G...,5H...
+    The signer must be one of the members. Run `aqd polkadot multisig-approve` and `aqd polkadot multisig-execute` to complete the call."
+    )]
+    multisig: Option<Multisig>,
+    #[clap(
+        long,
+        conflicts_with_all = ["execute", "multisig", "suri", "suri_env", "suri_file", "keystore"],
+        help = "Specifies the account to dry-run the call from, instead of the account of the secret key URI. No secret key URI is needed."
+    )]
+    origin: Option<AccountId>,
+    #[clap(
+        long,
+        conflicts_with_all = ["multisig", "origin"],
+        help = "Specifies the account to dispatch the call from through `Proxy::proxy`. The signer must be one of its proxies."
+    )]
+    proxied: Option<AccountId>,
+    #[clap(
+        long,
+        conflicts_with_all = ["execute", "multisig", "estimate_only"],
+        help = "Specifies whether to print the SCALE-encoded call and the payload to sign as hex, instead of submitting the extrinsic, so that it can be signed by an external signer such as Polkadot Vault.
+    With --origin, the extrinsic is exported for that account and no secret key URI is needed."
+    )]
+    export_call_data: bool,
+    #[clap(
+        long,
+        conflicts_with_all = ["execute", "origin"],
+        help = "Specifies whether to only estimate the fee, weight and storage deposit of the extrinsic with a dry run, without submitting it."
+    )]
+    estimate_only: bool,
 }
 
 impl PolkadotCallCommand {
-    /// Returns whether to export the call output in JSON format.
-    pub fn output_json(&self) -> bool {
-        self.extrinsic_cli_opts.output_json
+    /// Returns the format of the output.
+    pub fn output(&self) -> OutputFormat {
+        self.extrinsic_cli_opts.output
     }
 
     /// Handles the calling of a contract on the Polkadot network.
     ///
     /// If the `execute` flag is set to `false`, it performs a dry run of the call and displays
-    /// the results. If the `output_json` flag is set to `true`, the output is in JSON format.
+    /// the results in the format of the `output` option.
     /// Otherwise, it prompts for a transaction confirmation and then submits the transaction for execution.
+    ///
+    /// If a multisig is specified, the call is wrapped in a `Multisig::as_multi` extrinsic, which
+    /// records the approval of the signer. The call data, call hash and timepoint needed by the other
+    /// members to approve and execute the call are printed. The gas limit of the call is then
+    /// estimated with the multisig account as caller.
+    ///
+    /// If a proxied account is specified, the call is wrapped in a `Proxy::proxy` extrinsic, and
+    /// dispatched from that account. The dry run is performed with that account as caller.
+    ///
+    /// If an origin is specified, the dry run is performed with the `ContractsApi_call` runtime API
+    /// (`ReviveApi_call` on pallet-revive)
+    /// with that account as caller, without a secret key URI.
     pub async fn handle(&self) -> Result<()> {
         // Make sure the command is run in the correct directory
         // Fails if the command is run in a Solang Solana project directory
         let target_match = check_target_match("polkadot", None)
             .map_err(|e| anyhow!("Failed to check current directory: {}", e))?;
         if !target_match {
-            exit(1);
+            exit(ErrorKind::Usage.exit_code());
+        }
+
+        if let Some(origin) = &self.origin {
+            let transcoder = load_transcoder(&self.extrinsic_cli_opts.file)?;
+            let client = self.extrinsic_cli_opts.connect().await?;
+            let pallet = check_node_compatibility(&client)?;
+            let token = self.extrinsic_cli_opts.token().await?;
+            let (value, storage_deposit_limit) = self.balances(&token)?;
+            let input_data = transcoder.encode(&self.message, &self.args)?;
+            let result = dry_run_call(
+                &client,
+                pallet,
+                origin,
+                &self.contract,
+                value,
+                storage_deposit_limit,
+                input_data.clone(),
+            )
+            .await?;
+            if self.export_call_data {
+                let gas_limit = result.gas_required.into();
+                result
+                    .result
+                    .map_err(|err| anyhow!("Pre-export dry run of the call failed: {:?}", err))?;
+                let payload = contracts_call_payload(
+                    pallet,
+                    &self.contract,
+                    value,
+                    gas_limit,
+                    storage_deposit_limit,
+                    input_data,
+                );
+                let tx_params = self.extrinsic_cli_opts.tx_params(&token)?;
+                let exported = export_call(&client, &payload, origin, &tx_params).await?;
+                return self.print_exported_call(&exported);
+            }
+            let result = CallDryRunResult::from_exec_result(&transcoder, &self.message, result)?;
+            return self.print_dry_run(&token, &result);
         }
 
         // Initialize the extrinsic options
-        let cli_options = ExtrinsicOptsBuilder::default()
-            .file(Some(self.extrinsic_cli_opts.file.clone()))
-            .url(self.extrinsic_cli_opts.url().clone())
-            .suri(self.extrinsic_cli_opts.suri.clone())
-            .storage_deposit_limit(self.extrinsic_cli_opts.storage_deposit_limit.clone())
-            .done();
-        let exec = CallCommandBuilder::default()
-            .contract(self.contract.clone())
-            .message(self.message.clone())
+        let token = self.extrinsic_cli_opts.token().await?;
+        let options = self.extrinsic_cli_opts.options(&token).await?;
+        let suri = options.suri().to_string();
+        let call = PolkadotCall::builder(options, self.contract.clone(), self.message.clone())
             .args(self.args.clone())
-            .extrinsic_opts(cli_options)
+            .value(self.value.clone())
             .gas_limit(self.gas_limit)
             .proof_size(self.proof_size)
-            .value(self.value.clone())
+            .proxied(self.proxied.clone())
             .done()
             .await?;
 
-        if !self.extrinsic_cli_opts.execute {
-            let result = exec.call_dry_run().await?;
-            let ret_val = &result
-                .result
-                .map_err(|err| anyhow!("Error calling the contract: {:?}", err))?;
-            let value = exec
-                .transcoder()
-                .decode_message_return(exec.message(), &mut &ret_val.data[..])
-                .context(format!("Failed to decode return value {:?}", &ret_val))?;
-            if self.output_json() {
-                let json_object = json!({
-                    "reverted": ret_val.did_revert(),
-                    "data": value,
-                    "gas_consumed": result.gas_consumed,
-                    "gas_required": result.gas_required,
-                    "storage_deposit": StorageDeposit::from(&result.storage_deposit),
-                });
-                println!("{}", to_string_pretty(&json_object)?);
-            } else {
-                print_title!("Call Dry Run Result");
-                print_key_value!("Status", format!("{}", value));
-                print_key_value!("Reverted", format!("{:?}", ret_val.did_revert()));
-                print_warning!("Execution of your call has NOT been completed. To submit the transaction and execute the call on chain, please include -x/--execute flag.");
-            };
+        if self.export_call_data {
+            let gas_limit = call.estimate_gas().await?;
+            let exported = call.export(gas_limit).await?;
+            self.print_exported_call(&exported)?;
+        } else if self.estimate_only {
+            let estimate = call.estimate().await?;
+            print_cost_estimate(&token, &estimate, self.output())?;
+        } else if !self.extrinsic_cli_opts.execute {
+            let result = call.dry_run().await?;
+            self.print_dry_run(&token, &result)?;
         } else {
-            let gas_limit = exec.estimate_gas().await?;
+            let gas_limit = match &self.multisig {
+                // The call is dispatched with the multisig account as caller
+                Some(multisig) => {
+                    let result = dry_run_call(
+                        call.client(),
+                        call.pallet(),
+                        &multisig.account_id(),
+                        &self.contract,
+                        call.value(),
+                        call.storage_deposit_limit(),
+                        call.call_data()?,
+                    )
+                    .await?;
+                    result.result.map_err(|err| {
+                        anyhow!(
+                            "Pre-submission dry run of the multisig call failed: {:?}",
+                            err
+                        )
+                    })?;
+                    result.gas_required.into()
+                }
+                None => call.estimate_gas().await?,
+            };
             if !self.skip_confirm {
                 prompt_confirm_transaction(|| {
-                    println!("Call Summary:");
-                    print_key_value!("Message", exec.message());
-                    print_key_value!("Args", exec.args().join(" "));
+                    print_title!("Call Summary");
+                    print_key_value!("Message", call.message());
+                    print_key_value!("Args", call.args().join(" "));
+                    print_key_value!("Value", token.format(call.value()));
                     print_key_value!("Gas limit", gas_limit.to_string());
+                    if let Some(multisig) = &self.multisig {
+                        print_key_value!("Multisig", multisig.account_id());
+                    }
+                    if let Some(proxied) = call.proxied() {
+                        print_key_value!("Proxied", proxied);
+                    }
                 })?;
             }
-            let token_metadata = exec.token_metadata();
-            let display_events = exec
-                .call(Some(gas_limit))
-                .await
-                .map_err(|err| anyhow!("Error calling the contract: {:?}", err))?;
-            let output = if self.output_json() {
-                display_events.to_json()?
+            if let Some(multisig) = &self.multisig {
+                let payload = contracts_call_payload(
+                    call.pallet(),
+                    &self.contract,
+                    call.value(),
+                    gas_limit,
+                    call.storage_deposit_limit(),
+                    call.call_data()?,
+                );
+                let call_data = call.client().tx().call_data(&payload)?;
+                let events = submit_as_multi(
+                    call.client(),
+                    &pair_signer(&suri)?,
+                    &self.extrinsic_cli_opts.tx_params(&token)?,
+                    multisig,
+                    None,
+                    payload.into_value(),
+                    (gas_limit.ref_time, gas_limit.proof_size),
+                )
+                .await?;
+                return print_multisig_result(
+                    call.client(),
+                    &token,
+                    &events,
+                    multisig,
+                    blake2_256(&call_data),
+                    Some(&call_data),
+                    self.output(),
+                )
+                .await;
+            }
+            let result = call.submit(gas_limit).await;
+            let entry = HistoryEntry::new("polkadot", "call")
+                .contract(&self.contract)
+                .function(&self.message);
+            record_history(match &result {
+                Ok(result) => entry.hash(format!("{:?}", result.block.extrinsic_hash)),
+                Err(e) => entry.failed(e),
+            });
+            let mut result = result?;
+            let explorer_url = self.extrinsic_cli_opts.explorer_url(&result.block);
+            result.block.explorer_url = Some(explorer_url.clone());
+            if is_quiet() {
+                print_text!(format!("{:?}", result.block.hash));
+            } else if self.output().is_structured() {
+                self.output().print(&result)?;
             } else {
-                display_events.display_events(Verbosity::Default, token_metadata)?
-            };
-            println!("{output}");
+                print_text!(result
+                    .display_events
+                    .display_events(Verbosity::Default, &call.token_metadata())?);
+                print_key_value!("Block", result.block);
+                print_key_value!("Explorer", explorer_url);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the value and the storage deposit limit of the call, in the smallest unit of the
+    /// network token.
+    fn balances(&self, token: &TokenInfo) -> Result<(u128, Option<u128>)> {
+        Ok((
+            self.value.denominate(token)?,
+            self.extrinsic_cli_opts.storage_deposit_limit(token)?,
+        ))
+    }
+
+    /// Prints a call exported to be signed by an external signer.
+    fn print_exported_call(&self, exported: &ExportedCall) -> Result<()> {
+        if self.output().is_structured() {
+            self.output().print(exported)?;
+        } else {
+            print_title!("Exported Call");
+            print_key_value!("Signer", exported.signer);
+            print_key_value!("Call data", exported.call_data);
+            print_key_value!("Nonce", exported.nonce);
+            print_key_value!("Era", exported.era);
+            print_key_value!("Tip", exported.tip);
+            print_key_value!("Extra", exported.extra);
+            print_key_value!("Additional", exported.additional);
+            print_key_value!("Signer payload", exported.signer_payload);
+            print_warning!("The extrinsic has NOT been submitted. Sign the payload with the signer account, then run `aqd polkadot submit` with --signer, --signature, --extra and --call-data.");
+        }
+        Ok(())
+    }
+
+    /// Prints the result of a dry run of the call.
+    fn print_dry_run(&self, token: &TokenInfo, result: &CallDryRunResult) -> Result<()> {
+        if self.output().is_structured() {
+            self.output().print(result)?;
+        } else {
+            print_title!("Call Dry Run Result");
+            print_key_value!("Status", format!("{}", result.data));
+            print_key_value!("Reverted", format!("{:?}", result.reverted));
+            print_key_value!("Gas required", result.gas_required.to_string());
+            print_key_value!(
+                "Storage deposit",
+                token.format_storage_deposit(&result.storage_deposit)
+            );
+            print_debug_output(&result.debug_output);
+            print_warning!("Execution of your call has NOT been completed. To submit the transaction and execute the call on chain, please include -x/--execute flag.");
         }
         Ok(())
     }
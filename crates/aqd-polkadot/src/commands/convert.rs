@@ -0,0 +1,105 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::Result,
+    aqd_polkadot_contracts::{
+        parse_public_key, parse_ss58_prefix, Ss58Address, SUBSTRATE_SS58_PREFIX,
+    },
+    aqd_utils::{is_quiet, print_key_value, print_text, print_title, OutputFormat},
+};
+
+#[derive(Debug, clap::Args)]
+#[clap(
+    name = "ss58",
+    about = "Convert a hex public key or an SS58 address to the SS58 address of a network"
+)]
+pub struct PolkadotSs58Command {
+    #[clap(
+        value_parser,
+        help = "Specifies the public key (32 hex encoded bytes) or the SS58 address of any network to convert."
+    )]
+    key: String,
+    #[clap(
+        long,
+        default_value = "42",
+        value_parser = parse_ss58_prefix,
+        help = "Specifies the network prefix of the SS58 address: a number, or the name of a network of the SS58 registry (e.g. polkadot, kusama, astar). Defaults to 42, the generic Substrate prefix of the development chains."
+    )]
+    prefix: u16,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
+}
+
+impl PolkadotSs58Command {
+    /// Handles the conversion of a public key or an SS58 address to the SS58 address of the
+    /// network of `--prefix`.
+    pub fn handle(&self) -> Result<()> {
+        let (public_key, _) = parse_public_key(&self.key)?;
+        let address = Ss58Address::new(public_key, self.prefix);
+        match is_quiet() {
+            true => print_text!(address.address),
+            false => print_ss58_address(&address, self.output)?,
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, clap::Args)]
+#[clap(
+    name = "hex",
+    about = "Convert an SS58 address to its hex public key, and detect its network"
+)]
+pub struct PolkadotPublicKeyCommand {
+    #[clap(
+        value_parser,
+        help = "Specifies the SS58 address of any network, or the public key (32 hex encoded bytes) to convert."
+    )]
+    address: String,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
+}
+
+impl PolkadotPublicKeyCommand {
+    /// Handles the conversion of an SS58 address to its hex public key.
+    ///
+    /// The network of the address is detected from its prefix. A public key given in hex is
+    /// printed with its generic Substrate address.
+    pub fn handle(&self) -> Result<()> {
+        let (public_key, prefix) = parse_public_key(&self.address)?;
+        let address = Ss58Address::new(public_key, prefix.unwrap_or(SUBSTRATE_SS58_PREFIX));
+        match is_quiet() {
+            true => print_text!(address.public_key),
+            false => print_ss58_address(&address, self.output)?,
+        }
+        Ok(())
+    }
+}
+
+/// Prints an SS58 address with its public key and its network.
+fn print_ss58_address(address: &Ss58Address, output: OutputFormat) -> Result<()> {
+    if output.is_structured() {
+        output.print(address)?;
+    } else {
+        print_title!("SS58 address");
+        print_key_value!("Address", address.address);
+        print_key_value!("Public key", address.public_key);
+        print_key_value!(
+            "Prefix",
+            match &address.network {
+                Some(network) => format!("{} ({})", address.prefix, network),
+                None => address.prefix.to_string(),
+            }
+        );
+    }
+    Ok(())
+}
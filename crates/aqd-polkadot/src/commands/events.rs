@@ -0,0 +1,107 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    std::{fmt::Debug, path::PathBuf, process::exit},
+};
+
+use {
+    super::{watch::print_contract_events, CLINetworkOpts},
+    aqd_polkadot_contracts::{connect_with_rpc, load_transcoder},
+    aqd_utils::{check_target_match, ErrorKind, OutputFormat},
+    contract_extrinsics::DefaultConfig,
+    subxt::Config,
+};
+
+#[derive(Debug, clap::Args)]
+#[clap(
+    name = "events",
+    about = "Scan the events emitted by a contract over a range of blocks on Polkadot"
+)]
+pub struct PolkadotEventsCommand {
+    #[clap(
+        value_parser,
+        help = "Specifies the path to a contract .contract bundle or .json metadata file, used to decode the events."
+    )]
+    file: PathBuf,
+    #[clap(
+        name = "contract",
+        long,
+        help = "Specifies the address of the contract whose events are scanned."
+    )]
+    contract: <DefaultConfig as Config>::AccountId,
+    #[clap(long, help = "Specifies the first block to scan.")]
+    from_block: u32,
+    #[clap(
+        long,
+        help = "Specifies the last block to scan, included. Defaults to the latest finalized block.
+    The events of old blocks may only be available on archive nodes."
+    )]
+    to_block: Option<u32>,
+    #[clap(flatten)]
+    network_opts: CLINetworkOpts,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
+}
+
+impl PolkadotEventsCommand {
+    /// Handles the scan of the events of a contract on the Polkadot network.
+    ///
+    /// The blocks of the range are fetched in order, and the `Contracts::ContractEmitted` events of
+    /// the contract are decoded with the contract metadata and printed.
+    pub async fn handle(&self) -> Result<()> {
+        // Make sure the command is run in the correct directory
+        // Fails if the command is run in a Solang Solana project directory
+        let target_match = check_target_match("polkadot", None)
+            .map_err(|e| anyhow!("Failed to check current directory: {}", e))?;
+        if !target_match {
+            exit(ErrorKind::Usage.exit_code());
+        }
+
+        let transcoder = load_transcoder(&self.file)?;
+
+        let (_, client, rpc) = connect_with_rpc(&self.network_opts.urls()?).await?;
+
+        let to_block = match self.to_block {
+            Some(to_block) => to_block,
+            // The latest block of the client is the last finalized one
+            None => client.blocks().at_latest().await?.number(),
+        };
+        if self.from_block > to_block {
+            return Err(anyhow!(
+                "Invalid block range: {} is after {}",
+                self.from_block,
+                to_block
+            ));
+        }
+
+        let mut stream = self.output.stream();
+        for number in self.from_block..=to_block {
+            let block_hash = rpc
+                .chain_get_block_hash(Some(number.into()))
+                .await?
+                .ok_or_else(|| anyhow!("Block {} not found", number))?;
+            let events = client
+                .blocks()
+                .at(block_hash)
+                .await?
+                .events()
+                .await
+                .map_err(|e| anyhow!("Failed to fetch the events of block {}: {}", number, e))?;
+            print_contract_events(
+                number.into(),
+                block_hash,
+                &events,
+                &self.contract,
+                &transcoder,
+                &mut stream,
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,172 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    serde_json::{json, Map, Value as JsonValue},
+    std::{fmt::Debug, process::exit},
+};
+
+use {
+    super::CLINetworkOpts,
+    aqd_polkadot_contracts::{value_to_json, TokenInfo},
+    aqd_utils::{check_target_match, print_key_value, print_title, ErrorKind, OutputFormat},
+    contract_extrinsics::{parse_code_hash, DefaultConfig},
+    subxt::{
+        dynamic::{self, At, Value},
+        Config, OnlineClient,
+    },
+};
+
+#[derive(Debug, clap::Args)]
+#[clap(
+    name = "info",
+    about = "Show the on-chain information of a contract or a code hash on Polkadot"
+)]
+#[clap(group(clap::ArgGroup::new("target").required(true).args(["contract", "code_hash"])))]
+pub struct PolkadotInfoCommand {
+    #[clap(
+        name = "contract",
+        long,
+        help = "Specifies the address of the contract to show the information of."
+    )]
+    contract: Option<<DefaultConfig as Config>::AccountId>,
+    #[clap(long, value_parser = parse_code_hash, help = "Specifies the code hash to show the information of.")]
+    code_hash: Option<<DefaultConfig as Config>::Hash>,
+    #[clap(flatten)]
+    network_opts: CLINetworkOpts,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
+}
+
+impl PolkadotInfoCommand {
+    /// Handles the querying of the on-chain information of a contract or a code hash.
+    ///
+    /// For a contract, the `ContractInfo` (code hash, trie ID, storage items, bytes and deposits)
+    /// is read from the `Contracts` pallet storage, followed by the information of its code. For a
+    /// code hash, only the `CodeInfo` (owner, deposit, reference count and determinism) is read.
+    pub async fn handle(&self) -> Result<()> {
+        // Make sure the command is run in the correct directory
+        // Fails if the command is run in a Solang Solana project directory
+        let target_match = check_target_match("polkadot", None)
+            .map_err(|e| anyhow!("Failed to check current directory: {}", e))?;
+        if !target_match {
+            exit(ErrorKind::Usage.exit_code());
+        }
+
+        let client = self.network_opts.connect().await?;
+        let metadata = client.metadata();
+        let to_json = |value: &Value<u32>| value_to_json(value, metadata.types());
+
+        let mut output = Map::new();
+
+        let code_hash = match (&self.contract, &self.code_hash) {
+            (Some(contract), _) => {
+                let contract_info = fetch_storage(
+                    &client,
+                    "Contracts",
+                    "ContractInfoOf",
+                    vec![Value::from_bytes(contract.0)],
+                )
+                .await?
+                .ok_or_else(|| anyhow!("No contract found at address {}", contract))?;
+                let code_hash = contract_info
+                    .at("code_hash")
+                    .map(to_json)
+                    .and_then(|code_hash| code_hash.as_str().map(|c| c.to_string()))
+                    .ok_or_else(|| anyhow!("Missing code hash in the contract info"))?;
+                output.insert("contract".to_string(), json!(contract.to_string()));
+                output.insert("contract_info".to_string(), to_json(&contract_info));
+                code_hash
+            }
+            (None, Some(code_hash)) => format!("{code_hash:?}"),
+            (None, None) => unreachable!("clap requires a contract or a code hash"),
+        };
+
+        let code_hash_bytes = hex::decode(code_hash.trim_start_matches("0x"))?;
+        let code_info = fetch_code_info(&client, &code_hash_bytes)
+            .await?
+            .ok_or_else(|| anyhow!("No code found with code hash {}", code_hash))?;
+        output.insert("code_hash".to_string(), json!(code_hash));
+        output.insert("code_info".to_string(), to_json(&code_info));
+
+        if self.output.is_structured() {
+            self.output.print(&JsonValue::Object(output))?;
+        } else {
+            let token = self.network_opts.token().await?;
+            if let Some(contract) = output.get("contract").and_then(|c| c.as_str()) {
+                print_title!("Contract");
+                print_key_value!("Address", contract);
+                if let Some(JsonValue::Object(contract_info)) = output.get("contract_info") {
+                    for (key, value) in contract_info {
+                        print_key_value!(key, display_field(key, value, &token));
+                    }
+                }
+            }
+            print_title!("Code");
+            print_key_value!("Code hash", code_hash);
+            if let Some(JsonValue::Object(code_info)) = output.get("code_info") {
+                for (key, value) in code_info {
+                    print_key_value!(key, display_field(key, value, &token));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Fetch an entry of the storage of a pallet at the latest block.
+pub(crate) async fn fetch_storage(
+    client: &OnlineClient<DefaultConfig>,
+    pallet: &str,
+    entry: &str,
+    keys: Vec<Value>,
+) -> Result<Option<Value<u32>>> {
+    let query = dynamic::storage(pallet, entry, keys);
+    let value = client.storage().at_latest().await?.fetch(&query).await?;
+    match value {
+        Some(value) => Ok(Some(value.to_value()?)),
+        None => Ok(None),
+    }
+}
+
+/// Fetch the information of a code: its owner, deposit, reference count and determinism.
+///
+/// The code information is stored in `CodeInfoOf` since the v12 storage migration of the
+/// Contracts pallet, and in `OwnerInfoOf` before. The error of `OwnerInfoOf` is returned if
+/// neither entry can be fetched.
+pub(crate) async fn fetch_code_info(
+    client: &OnlineClient<DefaultConfig>,
+    code_hash: &[u8],
+) -> Result<Option<Value<u32>>> {
+    let key = || vec![Value::from_bytes(code_hash)];
+    match fetch_storage(client, "Contracts", "CodeInfoOf", key()).await {
+        Ok(info) => Ok(info),
+        Err(_) => fetch_storage(client, "Contracts", "OwnerInfoOf", key()).await,
+    }
+}
+
+/// Display a field of the contract or code information, formatting the deposits in token units.
+fn display_field(key: &str, value: &JsonValue, token: &TokenInfo) -> String {
+    let amount = match value {
+        JsonValue::Number(n) => n.as_u64().map(u128::from),
+        JsonValue::String(s) => s.parse::<u128>().ok(),
+        _ => None,
+    };
+    match amount {
+        Some(amount) if key.contains("deposit") => token.format(amount),
+        _ => display_json(value),
+    }
+}
+
+/// Display a JSON value without the quotes of strings.
+fn display_json(value: &JsonValue) -> String {
+    match value {
+        JsonValue::String(s) => s.clone(),
+        value => value.to_string(),
+    }
+}
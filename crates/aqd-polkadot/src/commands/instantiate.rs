@@ -2,20 +2,21 @@
 
 use {
     anyhow::{anyhow, Result},
-    colored::Colorize,
+    serde_json::json,
     std::fmt::Debug,
     std::process::exit,
 };
 
 use {
-    super::CLIExtrinsicOpts,
+    super::{print_cost_estimate, print_debug_output, CLIExtrinsicOpts},
+    aqd_polkadot_contracts::{Balance, PolkadotInstantiate},
     aqd_utils::{
-        check_target_match, print_key_value, print_title, print_warning, prompt_confirm_transaction,
+        check_target_match, is_quiet, print_key_value, print_text, print_title, print_warning,
+        prompt_confirm_transaction, record_history, solang_compile, ErrorKind, HistoryEntry,
+        OutputFormat,
     },
+    chacha20poly1305::aead::{rand_core::RngCore, OsRng},
     contract_build::{util::decode_hex, Verbosity},
-    contract_extrinsics::{
-        BalanceVariant, DisplayEvents, ExtrinsicOptsBuilder, InstantiateCommandBuilder,
-    },
     sp_core::Bytes,
 };
 
@@ -37,9 +38,10 @@ pub struct PolkadotInstantiateCommand {
         name = "value",
         long,
         default_value = "0",
-        help = "Specifies the value to be transferred as part of the call."
+        help = "Specifies the value to be transferred as part of the call.
+    Either an amount in the smallest unit of the token, or in token units, e.g. 1.5 DOT or 0.5unit."
     )]
-    value: BalanceVariant,
+    value: Balance,
     #[clap(
         name = "gas",
         long,
@@ -51,53 +53,105 @@ pub struct PolkadotInstantiateCommand {
         help = "Specifies the maximum proof size for this instantiation."
     )]
     proof_size: Option<u64>,
-    #[clap(long, value_parser = parse_hex_bytes, help = "Specifies a salt used in the address derivation of the new contract.")]
+    #[clap(long, value_parser = parse_salt, help = "Specifies a salt used in the address derivation of the new contract, as hex.
+    Use `random` to generate a random salt, so that repeated instantiations of the same code and constructor do not collide.")]
     salt: Option<Bytes>,
+    #[clap(
+        long,
+        help = "Specifies whether to upload the contract code and instantiate the contract in a single extrinsic (instantiate_with_code).
+                Requires a .contract bundle or a .wasm file."
+    )]
+    upload: bool,
+    #[clap(
+        long,
+        conflicts_with = "execute",
+        help = "Specifies whether to only print the address the contract will be instantiated at, derived from the signer, code hash, constructor input and salt, without any dry run."
+    )]
+    show_address_only: bool,
     #[clap(
         short('y'),
         long,
+        env = "AQD_SKIP_CONFIRM",
         help = "Specifies whether to skip the confirmation prompt."
     )]
     skip_confirm: bool,
+    #[clap(
+        long,
+        conflicts_with_all = ["execute", "show_address_only"],
+        help = "Specifies whether to only estimate the fee, weight and storage deposit of the extrinsic with a dry run, without submitting it."
+    )]
+    estimate_only: bool,
+    #[clap(
+        long,
+        help = "Specifies whether to compile the Solang project with `solang compile` before instantiating, so that the latest artifact is used."
+    )]
+    build: bool,
 }
 
-/// Parse hex encoded bytes.
-fn parse_hex_bytes(input: &str) -> Result<Bytes> {
+/// The length of the random salts.
+const RANDOM_SALT_LENGTH: usize = 32;
+
+/// Parse a hex encoded salt, or generate a random salt for `random`.
+fn parse_salt(input: &str) -> Result<Bytes> {
+    if input == "random" {
+        let mut salt = vec![0u8; RANDOM_SALT_LENGTH];
+        OsRng.fill_bytes(&mut salt);
+        return Ok(salt.into());
+    }
     let bytes = decode_hex(input)?;
     Ok(bytes.into())
 }
 
 impl PolkadotInstantiateCommand {
-    /// Returns whether to export the call output in JSON format.
-    pub fn output_json(&self) -> bool {
-        self.extrinsic_cli_opts.output_json
+    /// Returns the format of the output.
+    pub fn output(&self) -> OutputFormat {
+        self.extrinsic_cli_opts.output
     }
 
     /// Handles the instantiation of a contract on the Polkadot network.
     ///
     /// If the `execute` flag is set to `false`, it performs a dry run of the instantiation and displays
-    /// the results. If the `output_json` flag is set to `true`, the output is in JSON format.
+    /// the results in the format of the `output` option.
     /// Otherwise, it prompts for a transaction confirmation and then submits the transaction for execution.
+    ///
+    /// When the file contains the code of the contract (a .contract bundle or a .wasm file), the code
+    /// is uploaded along with the instantiation using `instantiate_with_code`. The `upload` flag makes
+    /// this explicit, and fails early if the file does not contain the code. If `--build` is set, the
+    /// Solang project is compiled first.
     pub async fn handle(&self) -> Result<()> {
         // Make sure the command is run in the correct directory
         // Fails if the command is run in a Solang Solana project directory
         let target_match = check_target_match("polkadot", None)
             .map_err(|e| anyhow!("Failed to check current directory: {}", e))?;
         if !target_match {
-            exit(1);
+            exit(ErrorKind::Usage.exit_code());
+        }
+
+        // Compile the project first, if requested
+        if self.build {
+            solang_compile()?;
+        }
+
+        // Uploading the code along with the instantiation requires the code of the contract,
+        // which .json metadata files do not contain
+        let file = &self.extrinsic_cli_opts.file;
+        let has_code = matches!(
+            file.extension().and_then(|ext| ext.to_str()),
+            Some("contract") | Some("wasm")
+        );
+        if self.upload && !has_code {
+            return Err(anyhow!(
+                "--upload requires a .contract bundle or a .wasm file, got {}",
+                file.display()
+            ));
         }
 
         // Initialize the extrinsic options
-        let cli_options = ExtrinsicOptsBuilder::default()
-            .file(Some(self.extrinsic_cli_opts.file.clone()))
-            .url(self.extrinsic_cli_opts.url().clone())
-            .suri(self.extrinsic_cli_opts.suri.clone())
-            .storage_deposit_limit(self.extrinsic_cli_opts.storage_deposit_limit.clone())
-            .done();
-        let exec = InstantiateCommandBuilder::default()
+        let token = self.extrinsic_cli_opts.token().await?;
+        let options = self.extrinsic_cli_opts.options(&token).await?;
+        let instantiate = PolkadotInstantiate::builder(options)
             .constructor(self.constructor.clone())
             .args(self.args.clone())
-            .extrinsic_opts(cli_options)
             .value(self.value.clone())
             .gas_limit(self.gas_limit)
             .proof_size(self.proof_size)
@@ -105,75 +159,92 @@ impl PolkadotInstantiateCommand {
             .done()
             .await?;
 
-        if !self.extrinsic_cli_opts.execute {
-            let result = exec.instantiate_dry_run().await?;
-            let dry_run_result = exec
-                .decode_instantiate_dry_run(&result)
-                .await
-                .map_err(|e| anyhow!("Failed to decode instantiate dry run result: {}", e))?;
-            if self.output_json() {
-                println!("{}", dry_run_result.to_json()?);
+        if self.show_address_only {
+            let address = instantiate
+                .pallet()
+                .format_address(&instantiate.predicted_address()?);
+            if is_quiet() {
+                print_text!(address);
+            } else if self.output().is_structured() {
+                let json_object = json!({
+                    "predicted_address": address,
+                    "salt": format!("0x{}", hex::encode(instantiate.salt())),
+                });
+                self.output().print(&json_object)?;
+            } else {
+                print_key_value!("Predicted address", address);
+                print_key_value!("Salt", format!("0x{}", hex::encode(instantiate.salt())));
+            }
+            return Ok(());
+        }
+
+        if self.estimate_only {
+            let estimate = instantiate.estimate().await?;
+            print_cost_estimate(&token, &estimate, self.output())?;
+        } else if !self.extrinsic_cli_opts.execute {
+            let result = instantiate.dry_run().await?;
+            if self.output().is_structured() {
+                self.output().print(&result)?;
             } else {
                 print_title!("Instantiate dry run result");
-                print_key_value!("Status", format!("{}", &dry_run_result.result));
-                print_key_value!("Reverted", format!("{:?}", &dry_run_result.reverted));
-                print_key_value!("Contract", &dry_run_result.contract);
-                print_key_value!("Gas consumed", &dry_run_result.gas_consumed.to_string());
+                print_key_value!("Status", format!("{}", &result.result));
+                print_key_value!("Reverted", format!("{:?}", &result.reverted));
+                print_key_value!("Contract", &result.contract);
+                print_key_value!("Predicted address", &result.predicted_address);
+                print_key_value!("Salt", &result.salt);
+                print_key_value!("Gas consumed", result.gas_consumed.to_string());
+                print_key_value!("Gas required", result.gas_required.to_string());
+                print_key_value!(
+                    "Storage deposit",
+                    token.format_storage_deposit(&result.storage_deposit)
+                );
+                print_debug_output(&result.debug_output);
                 print_warning!("Execution of your instantiate call has NOT been completed. To submit the transaction and execute the call on chain, please include -x/--execute flag.");
             }
         } else {
-            let gas_limit = exec.estimate_gas().await?;
+            let gas_limit = instantiate.estimate_gas().await?;
             if !self.skip_confirm {
                 prompt_confirm_transaction(|| {
-                    println!("Instantiation Summary:");
-                    print_key_value!("Constructor", exec.args().constructor());
-                    print_key_value!("Args", exec.args().raw_args().join(" "));
+                    print_title!("Instantiation Summary");
+                    print_key_value!("Constructor", instantiate.args().constructor());
+                    print_key_value!("Args", instantiate.args().raw_args().join(" "));
+                    print_key_value!("Value", token.format(instantiate.value()));
                     print_key_value!("Gas limit", gas_limit.to_string());
                 })?;
             }
-            let instantiate_result = exec
-                .instantiate(Some(gas_limit))
-                .await
-                .map_err(|err| anyhow!("Error instantiating the contract: {:?}", err))?;
-            let events = DisplayEvents::from_events(
-                &instantiate_result.result,
-                Some(exec.transcoder()),
-                &exec.client().metadata(),
-            )?;
-            let contract_address = instantiate_result.contract_address.to_string();
-            if self.output_json() {
-                let display_instantiate_result = InstantiateResult {
-                    code_hash: instantiate_result.code_hash.map(|ch| format!("{ch:?}")),
-                    contract: contract_address,
-                    events,
-                };
-                println!("{}", display_instantiate_result.to_json()?)
+            let result = instantiate.submit(gas_limit).await;
+            let entry = HistoryEntry::new("polkadot", "instantiate")
+                .function(instantiate.args().constructor());
+            record_history(match &result {
+                Ok(result) => entry
+                    .hash(format!("{:?}", result.block.extrinsic_hash))
+                    .contract(&result.contract),
+                Err(e) => entry.failed(e),
+            });
+            let mut result = result?;
+            let explorer_url = self.extrinsic_cli_opts.explorer_url(&result.block);
+            result.block.explorer_url = Some(explorer_url.clone());
+            if is_quiet() {
+                print_text!(result.contract);
+            } else if self.output().is_structured() {
+                self.output().print(&result)?;
             } else {
-                println!(
-                    "{}",
-                    events
-                        .display_events(Verbosity::Default, &instantiate_result.token_metadata)?
-                );
-                if let Some(code_hash) = instantiate_result.code_hash {
-                    print_key_value!("Code hash", format!("{code_hash:?}"));
+                print_text!(result
+                    .display_events
+                    .display_events(Verbosity::Default, &result.token_metadata)?);
+                match &result.code_hash {
+                    Some(code_hash) => print_key_value!("Code hash", code_hash),
+                    None if self.upload => {
+                        print_warning!("The code of the contract was already uploaded, only the instantiation took place.")
+                    }
+                    None => {}
                 }
-                print_key_value!("Contract", contract_address);
+                print_key_value!("Contract", result.contract);
+                print_key_value!("Salt", result.salt);
+                print_key_value!("Block", result.block);
+                print_key_value!("Explorer", explorer_url);
             };
         }
         Ok(())
     }
 }
-
-#[derive(serde::Serialize)]
-pub struct InstantiateResult {
-    pub contract: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub code_hash: Option<String>,
-    pub events: DisplayEvents,
-}
-
-impl InstantiateResult {
-    pub fn to_json(&self) -> Result<String> {
-        Ok(serde_json::to_string_pretty(self)?)
-    }
-}
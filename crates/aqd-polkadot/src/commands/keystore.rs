@@ -0,0 +1,119 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    serde_json::{to_string_pretty, Value},
+    std::{fmt::Debug, fs, path::Path, path::PathBuf},
+};
+
+use {
+    aqd_utils::{
+        decrypt_secret, encrypt_secret, print_key_value, print_title, prompt_keystore_password,
+        prompt_new_password, register_secret_uri,
+    },
+    sp_core::{
+        crypto::{Pair, Ss58Codec},
+        sr25519,
+    },
+};
+
+#[derive(Debug, clap::Args)]
+#[clap(
+    name = "keystore",
+    about = "Create an encrypted keystore holding a secret key URI, to be used with --keystore"
+)]
+pub struct PolkadotKeystoreCommand {
+    #[clap(
+        value_parser,
+        help = "Specifies the path of the keystore file to create."
+    )]
+    path: PathBuf,
+    #[clap(
+        long,
+        help = "Specifies whether to overwrite an existing keystore file."
+    )]
+    force: bool,
+}
+
+impl PolkadotKeystoreCommand {
+    /// Handles the creation of an encrypted keystore.
+    ///
+    /// The secret key URI and the password are read interactively, so that neither ends up in the
+    /// shell history. The secret key URI is checked to be a valid sr25519 key before it is
+    /// encrypted and written to the keystore file.
+    pub async fn handle(&self) -> Result<()> {
+        if self.path.exists() && !self.force {
+            return Err(anyhow!(
+                "{} already exists, use --force to overwrite it",
+                self.path.display()
+            ));
+        }
+
+        let suri = rpassword::prompt_password("Secret key URI: ")?;
+        register_secret_uri(&suri);
+        let pair = sr25519::Pair::from_string(&suri, None)
+            .map_err(|e| anyhow!("Invalid secret key URI: {:?}", e))?;
+
+        let password = prompt_new_password()?;
+        let keystore = encrypt_suri(&suri, &password)?;
+        fs::write(&self.path, to_string_pretty(&keystore)?)
+            .map_err(|e| anyhow!("Failed to write {}: {}", self.path.display(), e))?;
+
+        print_title!("Keystore created");
+        print_key_value!("Path", self.path.display());
+        print_key_value!("Account", pair.public().to_ss58check());
+        Ok(())
+    }
+}
+
+/// Encrypt a secret key URI with a password, into a keystore in the format of
+/// [`encrypt_secret`].
+pub(crate) fn encrypt_suri(suri: &str, password: &str) -> Result<Value> {
+    encrypt_secret(suri.as_bytes(), password)
+}
+
+/// Decrypt the secret key URI of a keystore with a password.
+pub(crate) fn decrypt_suri(keystore: &Value, password: &str) -> Result<String> {
+    String::from_utf8(decrypt_secret(keystore, password)?)
+        .map_err(|_| anyhow!("The keystore does not hold a valid secret key URI"))
+}
+
+/// Read the secret key URI of a keystore, prompting the user for its password.
+pub(crate) fn read_keystore(path: &Path) -> Result<String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read keystore {}: {}", path.display(), e))?;
+    let keystore: Value = serde_json::from_str(&content)
+        .map_err(|e| anyhow!("Failed to parse keystore {}: {}", path.display(), e))?;
+    decrypt_suri(&keystore, &prompt_keystore_password(path)?)
+}
+
+/// A test for the `decrypt_suri` function, with keystores encrypted with 1,000 iterations by
+/// another implementation of PBKDF2-SHA256 and ChaCha20-Poly1305
+#[test]
+fn test_decrypt_suri() {
+    let keystore = |ciphertext: &str| {
+        serde_json::json!({
+            "version": 1,
+            "kdf": {
+                "name": "pbkdf2-sha256",
+                "iterations": 1000,
+                "salt": "000102030405060708090a0b0c0d0e0f",
+            },
+            "cipher": { "name": "chacha20poly1305", "nonce": "000102030405060708090a0b" },
+            "ciphertext": ciphertext,
+        })
+    };
+
+    let alice = keystore("b9a9aa6222852fe674e6103ae2e70e775e03549f0de2f5");
+    assert_eq!(decrypt_suri(&alice, "correct horse").unwrap(), "//Alice");
+    assert!(decrypt_suri(&alice, "wrong horse").is_err());
+
+    // A keystore of other bytes is not a secret key URI
+    let bytes = keystore("69782acda023c1f490c57e29fdf4aa4c3f98");
+    assert_eq!(
+        decrypt_suri(&bytes, "correct horse")
+            .unwrap_err()
+            .to_string(),
+        "The keystore does not hold a valid secret key URI"
+    );
+}
@@ -1,56 +1,195 @@
 // SPDX-License-Identifier: Apache-2.0
 
+mod balance;
+mod batch;
+mod bench;
 mod call;
+mod convert;
+mod events;
+mod info;
 mod instantiate;
+mod keystore;
+mod multisig;
+mod networks;
+mod query;
 mod remove;
+mod show;
+mod storage;
+mod submit;
 mod upload;
+mod watch;
 
 pub use self::{
-    call::PolkadotCallCommand, instantiate::PolkadotInstantiateCommand,
-    remove::PolkadotRemoveCommand, upload::PolkadotUploadCommand,
+    balance::PolkadotBalanceCommand,
+    batch::PolkadotBatchCommand,
+    bench::PolkadotBenchCommand,
+    call::PolkadotCallCommand,
+    convert::{PolkadotPublicKeyCommand, PolkadotSs58Command},
+    events::PolkadotEventsCommand,
+    info::PolkadotInfoCommand,
+    instantiate::PolkadotInstantiateCommand,
+    keystore::PolkadotKeystoreCommand,
+    multisig::{PolkadotMultisigApproveCommand, PolkadotMultisigExecuteCommand},
+    networks::PolkadotNetworksCommand,
+    query::PolkadotQueryCommand,
+    remove::PolkadotRemoveCommand,
+    show::PolkadotShowCommand,
+    storage::PolkadotStorageCommand,
+    submit::PolkadotSubmitCommand,
+    upload::PolkadotUploadCommand,
+    watch::PolkadotWatchCommand,
 };
 
-use {std::path::PathBuf, url::Url};
+pub(crate) use self::keystore::read_keystore;
 
-pub use contract_extrinsics::BalanceVariant;
+use {
+    anyhow::{anyhow, Result},
+    aqd_polkadot_contracts::{
+        connect, connect_with_rpc, Balance, CostEstimate, Era, ExtrinsicBlock, ExtrinsicOptions,
+        TokenInfo, TxParams, WaitFor,
+    },
+    aqd_utils::{
+        configured_explorer, explorer_url, print_key_value, print_subtitle, print_title,
+        print_value, register_secret_uri, AqdConfig, NetworkConfig, OutputFormat,
+        POLKADOT_APPS_EXPLORER,
+    },
+    clap::ValueEnum,
+    contract_extrinsics::DefaultConfig,
+    std::{env, fs, path::PathBuf},
+    subxt::OnlineClient,
+    url::Url,
+};
 
-/// Common CLI options for executing extrinsics on a Polkadot node.
+/// Common CLI options for connecting to a Polkadot node.
 ///
-/// These options allow you to specify the contract or metadata file, the node's URL,
-/// network, secret key URI, whether to execute the extrinsic, the storage deposit limit,
-/// and whether to export the output in JSON format.
+/// These options allow you to specify either the node's URLs or the name of a known network.
 #[derive(Clone, Debug, clap::Args)]
-pub struct CLIExtrinsicOpts {
-    #[clap(
-        value_parser,
-        help = "Specifies the path to a contract wasm file, .contract bundle, or .json metadata file."
-    )]
-    file: PathBuf,
+pub struct CLINetworkOpts {
     #[clap(
         name = "url",
         long,
+        env = "AQD_POLKADOT_URL",
         value_parser,
+        value_delimiter = ',',
         default_value = "ws://localhost:9944",
-        help = "Specifies the websockets URL for the substrate node directly."
+        help = "Specifies the websockets URL for the substrate node directly.
+    Several URLs can be given, separated by commas or with several --url options: the next node is tried when a node cannot be reached."
     )]
-    url: Url,
+    url: Vec<Url>,
     #[clap(
-        value_enum,
         name = "network",
         long,
+        env = "AQD_POLKADOT_NETWORK",
         conflicts_with = "url",
-        help = "Specifies the network name."
+        help = "Specifies the network name: one of the known networks, or a network defined in the aqd configuration file.
+    Run `aqd polkadot networks` to list the available networks."
     )]
-    network: Option<Network>,
+    network: Option<String>,
+    #[clap(
+        long,
+        env = "AQD_POLKADOT_SAME_NODE",
+        help = "Specifies whether all the dry runs and submissions of the command must use the same node, instead of failing over to the next URL."
+    )]
+    same_node: bool,
+}
+
+/// Common CLI options for signing extrinsics.
+///
+/// The secret key URI can be passed directly, or read from an environment variable, a file or an
+/// encrypted keystore, so that it does not leak into the shell history and the process list.
+#[derive(Clone, Debug, clap::Args)]
+#[clap(group(clap::ArgGroup::new("signer").args(["suri", "suri_env", "suri_file", "keystore"])))]
+pub struct CLISignerOpts {
     #[clap(
         name = "suri",
         long,
         short,
+        env = "AQD_POLKADOT_SURI",
+        hide_env_values = true,
         help = "Specifies the secret key URI used for deploying the contract. For example:\n
     For a development account: //Alice\n
     With a password: //Alice///SECRET_PASSWORD"
     )]
-    suri: String,
+    suri: Option<String>,
+    #[clap(
+        long,
+        value_name = "VAR",
+        help = "Specifies the environment variable to read the secret key URI from."
+    )]
+    suri_env: Option<String>,
+    #[clap(
+        long,
+        value_name = "PATH",
+        env = "AQD_POLKADOT_SURI_FILE",
+        help = "Specifies the file to read the secret key URI from."
+    )]
+    suri_file: Option<PathBuf>,
+    #[clap(
+        long,
+        value_name = "PATH",
+        env = "AQD_POLKADOT_KEYSTORE",
+        help = "Specifies the encrypted keystore to read the secret key URI from. The password is prompted interactively.
+    Run `aqd polkadot keystore` to create a keystore."
+    )]
+    keystore: Option<PathBuf>,
+}
+
+/// Common CLI options for the parameters of signed extrinsics.
+#[derive(Clone, Debug, clap::Args)]
+pub struct CLITxOpts {
+    #[clap(
+        long,
+        env = "AQD_POLKADOT_TIP",
+        help = "Specifies a tip paid to the block author, to prioritize the extrinsic.
+    Either an amount in the smallest unit of the token, or in token units, e.g. 1.5 DOT or 0.5unit."
+    )]
+    tip: Option<Balance>,
+    #[clap(
+        long,
+        env = "AQD_POLKADOT_ERA",
+        default_value = "immortal",
+        help = "Specifies the number of blocks the extrinsic stays valid for, or `immortal`.
+    The number of blocks is rounded up to a power of two, between 4 and 65536."
+    )]
+    era: Era,
+    #[clap(
+        long,
+        help = "Specifies the nonce of the signer account, instead of the next nonce known to the node including its pending extrinsics."
+    )]
+    nonce: Option<u64>,
+    #[clap(
+        long,
+        env = "AQD_POLKADOT_WAIT_FOR",
+        default_value = "finalized",
+        help = "Specifies whether to wait for the extrinsic to be included in a block (`in-block`), or for the block to be finalized (`finalized`)."
+    )]
+    wait_for: WaitFor,
+    #[clap(
+        long,
+        env = "AQD_POLKADOT_SUDO",
+        help = "Specifies whether to dispatch the extrinsic with the root origin through `Sudo::sudo`. The signer must be the sudo key, as on development chains such as substrate-contracts-node."
+    )]
+    sudo: bool,
+}
+
+/// Common CLI options for executing extrinsics on a Polkadot node.
+///
+/// These options allow you to specify the contract or metadata file, the node's URL,
+/// network, secret key URI, the parameters of the extrinsic, whether to execute it, the storage
+/// deposit limit, and the format of the output.
+#[derive(Clone, Debug, clap::Args)]
+pub struct CLIExtrinsicOpts {
+    #[clap(
+        value_parser,
+        help = "Specifies the path to a contract wasm file, .contract bundle, or .json metadata file."
+    )]
+    file: PathBuf,
+    #[clap(flatten)]
+    network_opts: CLINetworkOpts,
+    #[clap(flatten)]
+    signer_opts: CLISignerOpts,
+    #[clap(flatten)]
+    tx_opts: CLITxOpts,
     #[clap(
         short('x'),
         long,
@@ -59,16 +198,23 @@ pub struct CLIExtrinsicOpts {
     execute: bool,
     #[clap(
         long,
-        help = "Specifies the maximum amount of balance that can be charged from the caller to pay for the storage consumed."
+        env = "AQD_POLKADOT_STORAGE_DEPOSIT_LIMIT",
+        help = "Specifies the maximum amount of balance that can be charged from the caller to pay for the storage consumed.
+    Either an amount in the smallest unit of the token, or in token units, e.g. 1.5 DOT or 0.5unit."
+    )]
+    storage_deposit_limit: Option<Balance>,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
     )]
-    storage_deposit_limit: Option<BalanceVariant>,
-    #[clap(long, help = "Specifies whether to export the call output in JSON.")]
-    output_json: bool,
+    output: OutputFormat,
 }
 
-/// Available networks.
+/// Known networks.
 #[derive(clap::ValueEnum, Clone, Debug)]
-enum Network {
+pub(crate) enum Network {
     Rococo,
     PhalaPoC5,
     AstarShiden,
@@ -80,26 +226,309 @@ enum Network {
     PendulumTestnet,
 }
 
-impl CLIExtrinsicOpts {
-    /// Returns the URL for the Polkadot node based on the specified network or user input.
-    ///
-    /// If a specific network is chosen, the function returns the URL associated with that network.
-    /// Otherwise, it returns the URL provided by the user in the CLI options.
-    pub fn url(&self) -> Url {
-        if let Some(net) = &self.network {
-            return Url::parse(match net {
-                Network::Rococo => "wss://rococo-contracts-rpc.polkadot.io",
-                Network::PhalaPoC5 => "wss://poc5.phala.network/ws",
-                Network::AstarShiden => "wss://rpc.shiden.astar.network",
-                Network::AstarShibuya => "wss://rpc.shibuya.astar.network",
-                Network::Astar => "wss://rpc.astar.network",
-                Network::AlephZeroTestnet => "wss://ws.test.azero.dev",
-                Network::AlephZero => "wss://ws.azero.dev",
-                Network::T3RNT0RN => "wss://ws.t0rn.io",
-                Network::PendulumTestnet => "wss://rpc-foucoco.pendulumchain.tech",
+impl Network {
+    /// Returns the websockets URL of the network.
+    pub(crate) fn url(&self) -> &'static str {
+        match self {
+            Network::Rococo => "wss://rococo-contracts-rpc.polkadot.io",
+            Network::PhalaPoC5 => "wss://poc5.phala.network/ws",
+            Network::AstarShiden => "wss://rpc.shiden.astar.network",
+            Network::AstarShibuya => "wss://rpc.shibuya.astar.network",
+            Network::Astar => "wss://rpc.astar.network",
+            Network::AlephZeroTestnet => "wss://ws.test.azero.dev",
+            Network::AlephZero => "wss://ws.azero.dev",
+            Network::T3RNT0RN => "wss://ws.t0rn.io",
+            Network::PendulumTestnet => "wss://rpc-foucoco.pendulumchain.tech",
+        }
+    }
+
+    /// Returns the URL template of the Subscan explorer of the network, if any.
+    pub(crate) fn explorer(&self) -> Option<&'static str> {
+        match self {
+            Network::AstarShiden => Some("https://shiden.subscan.io/extrinsic/{tx}"),
+            Network::AstarShibuya => Some("https://shibuya.subscan.io/extrinsic/{tx}"),
+            Network::Astar => Some("https://astar.subscan.io/extrinsic/{tx}"),
+            Network::AlephZeroTestnet => {
+                Some("https://alephzero-testnet.subscan.io/extrinsic/{tx}")
+            }
+            Network::AlephZero => Some("https://alephzero.subscan.io/extrinsic/{tx}"),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the network, as accepted by `--network`.
+    pub(crate) fn name(&self) -> String {
+        self.to_possible_value()
+            .map(|value| value.get_name().to_string())
+            .unwrap_or_default()
+    }
+}
+
+impl CLINetworkOpts {
+    /// Returns the URLs for the Polkadot node based on the specified network or user input, in the
+    /// order they are tried.
+    ///
+    /// If a network is chosen, the function returns the URL associated with that network: either a
+    /// network defined in the aqd configuration file, or a known network. Otherwise, it returns the
+    /// URLs provided by the user in the CLI options.
+    ///
+    /// The networks of the configuration file take precedence over the known networks of the same
+    /// name, so that their URL can be changed.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the network is unknown, or if the configuration file
+    /// cannot be loaded.
+    pub fn urls(&self) -> Result<Vec<Url>> {
+        let name = match &self.network {
+            Some(name) => name,
+            None => return Ok(self.url.clone()),
+        };
+        let config = AqdConfig::load()?;
+        if let Some(network) = config.network(name) {
+            let url = Url::parse(&network.url)
+                .map_err(|e| anyhow!("Invalid URL {} for network {}: {}", network.url, name, e))?;
+            return Ok(vec![url]);
+        }
+        let network = Network::from_str(name, true).map_err(|_| {
+            anyhow!(
+                "Unknown network: {}. Run `aqd polkadot networks` to list the available networks",
+                name
+            )
+        })?;
+        Ok(vec![Url::parse(network.url())?])
+    }
+
+    /// Connect to the first node that can be reached.
+    ///
+    /// See [`connect`].
+    pub async fn connect(&self) -> Result<OnlineClient<DefaultConfig>> {
+        let (_, client) = connect(&self.urls()?).await?;
+        Ok(client)
+    }
+
+    /// Returns the URLs of the nodes the dry runs and submissions of a command can use.
+    ///
+    /// With `--same-node`, the first node that can be reached is the only one used. Otherwise,
+    /// each connection fails over to the next URL.
+    pub async fn endpoints(&self) -> Result<Vec<Url>> {
+        let urls = self.urls()?;
+        if !self.same_node {
+            return Ok(urls);
+        }
+        let (url, _) = connect(&urls).await?;
+        Ok(vec![url])
+    }
+
+    /// Returns the URL of the block explorer page of a submitted extrinsic.
+    ///
+    /// The explorer is the one of the network in the aqd configuration file, by name or by URL, or
+    /// else the Subscan explorer of a known network, or else Polkadot.js Apps connected to the
+    /// node.
+    pub fn explorer_url(&self, block: &ExtrinsicBlock) -> String {
+        let rpc_url = self
+            .urls()
+            .ok()
+            .and_then(|urls| urls.first().map(|url| url.to_string()))
+            .unwrap_or_default();
+        let template = configured_explorer(self.network.as_deref(), &rpc_url)
+            .or_else(|| {
+                self.network
+                    .as_deref()
+                    .and_then(|name| Network::from_str(name, true).ok())
+                    .and_then(|network| network.explorer())
+                    .map(|explorer| explorer.to_string())
             })
-            .unwrap();
+            .unwrap_or_else(|| POLKADOT_APPS_EXPLORER.to_string());
+        explorer_url(
+            &template,
+            &format!("{:?}", block.extrinsic_hash),
+            Some(&format!("{:?}", block.hash)),
+            &rpc_url,
+        )
+    }
+
+    /// Returns the decimals and symbol of the network token.
+    ///
+    /// They are read from the aqd configuration file for the networks that define both, and
+    /// queried from the `system_properties` of the node otherwise, for known networks and URLs
+    /// alike.
+    pub async fn token(&self) -> Result<TokenInfo> {
+        if let Some(name) = &self.network {
+            let config = AqdConfig::load()?;
+            if let Some(NetworkConfig {
+                token_decimals: Some(decimals),
+                token_symbol: Some(symbol),
+                ..
+            }) = config.network(name)
+            {
+                return Ok(TokenInfo {
+                    decimals: *decimals,
+                    symbol: symbol.clone(),
+                });
+            }
         }
-        self.url.clone()
+        let (_, _, rpc) = connect_with_rpc(&self.urls()?).await?;
+        TokenInfo::query(&rpc).await
+    }
+}
+
+impl CLIExtrinsicOpts {
+    /// Returns the URLs of the nodes the dry runs and submissions of the command can use.
+    ///
+    /// See [`CLINetworkOpts::endpoints`].
+    pub async fn endpoints(&self) -> Result<Vec<Url>> {
+        self.network_opts.endpoints().await
+    }
+
+    /// Connect to the first node that can be reached.
+    ///
+    /// See [`CLINetworkOpts::connect`].
+    pub async fn connect(&self) -> Result<OnlineClient<DefaultConfig>> {
+        self.network_opts.connect().await
+    }
+
+    /// Returns the URL of the block explorer page of a submitted extrinsic.
+    ///
+    /// See [`CLINetworkOpts::explorer_url`].
+    pub fn explorer_url(&self, block: &ExtrinsicBlock) -> String {
+        self.network_opts.explorer_url(block)
+    }
+
+    /// Returns the secret key URI used to sign the extrinsic.
+    ///
+    /// See [`CLISignerOpts::suri`].
+    pub fn suri(&self) -> Result<String> {
+        self.signer_opts.suri()
+    }
+
+    /// Returns the decimals and symbol of the network token.
+    ///
+    /// See [`CLINetworkOpts::token`].
+    pub async fn token(&self) -> Result<TokenInfo> {
+        self.network_opts.token().await
+    }
+
+    /// Returns the options of the extrinsic for `aqd_polkadot_contracts`, reading the secret key
+    /// URI.
+    pub async fn options(&self, token: &TokenInfo) -> Result<ExtrinsicOptions> {
+        let (url, fallback_urls) = split_urls(self.endpoints().await?)?;
+        Ok(ExtrinsicOptions::new(self.file.clone(), url, self.suri()?)
+            .fallback_urls(fallback_urls)
+            .storage_deposit_limit(self.storage_deposit_limit.clone())
+            .token(token.clone())
+            .tip(self.tx_opts.tip.clone())
+            .era(self.tx_opts.era)
+            .nonce(self.tx_opts.nonce)
+            .wait_for(self.tx_opts.wait_for)
+            .sudo(self.tx_opts.sudo))
+    }
+
+    /// Returns the parameters of the signed extrinsic.
+    ///
+    /// See [`CLITxOpts::tx_params`].
+    pub fn tx_params(&self, token: &TokenInfo) -> Result<TxParams> {
+        self.tx_opts.tx_params(token)
+    }
+
+    /// Returns the storage deposit limit in the smallest unit of the network token.
+    pub fn storage_deposit_limit(&self, token: &TokenInfo) -> Result<Option<u128>> {
+        match &self.storage_deposit_limit {
+            Some(limit) => Ok(Some(limit.denominate(token)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Print the estimated cost of an extrinsic in the given output format.
+pub(crate) fn print_cost_estimate(
+    token: &TokenInfo,
+    estimate: &CostEstimate,
+    output: OutputFormat,
+) -> Result<()> {
+    if output.is_structured() {
+        output.print(estimate)?;
+    } else {
+        print_title!("Cost Estimate");
+        print_key_value!("Estimated fee", token.format(estimate.partial_fee));
+        print_key_value!("Weight", estimate.weight.to_string());
+        if let Some(gas_limit) = &estimate.gas_limit {
+            print_key_value!("Gas limit", gas_limit.to_string());
+        }
+        print_key_value!(
+            "Storage deposit",
+            token.format_storage_deposit(&estimate.storage_deposit)
+        );
+    }
+    Ok(())
+}
+
+/// Print the debug output of a dry run in its own section, if the contract printed anything.
+pub(crate) fn print_debug_output(debug_output: &[String]) {
+    if debug_output.is_empty() {
+        return;
+    }
+    print_subtitle!("Debug output");
+    for line in debug_output {
+        print_value!(line);
+    }
+}
+
+/// Split URLs into the URL of the node to try first and the fallback URLs.
+pub(crate) fn split_urls(mut urls: Vec<Url>) -> Result<(Url, Vec<Url>)> {
+    if urls.is_empty() {
+        return Err(anyhow!("Missing URL: use --url or --network"));
+    }
+    let url = urls.remove(0);
+    Ok((url, urls))
+}
+
+impl CLISignerOpts {
+    /// Returns the secret key URI used to sign the extrinsic.
+    ///
+    /// The secret key URI is taken from `--suri`, the environment variable of `--suri-env`, the
+    /// file of `--suri-file` (surrounding whitespace is ignored), or the encrypted keystore of
+    /// `--keystore` after prompting for its password. It is registered as a secret, to be
+    /// redacted from the output.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the secret key URI cannot be read.
+    pub fn suri(&self) -> Result<String> {
+        let suri = if let Some(suri) = &self.suri {
+            suri.clone()
+        } else if let Some(var) = &self.suri_env {
+            env::var(var)
+                .map_err(|e| anyhow!("Failed to read environment variable {}: {}", var, e))?
+        } else if let Some(path) = &self.suri_file {
+            let suri = fs::read_to_string(path)
+                .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+            suri.trim().to_string()
+        } else if let Some(path) = &self.keystore {
+            keystore::read_keystore(path)?
+        } else {
+            return Err(anyhow!(
+                "Missing secret key URI: use --suri, --suri-env, --suri-file or --keystore"
+            ));
+        };
+        register_secret_uri(&suri);
+        Ok(suri)
+    }
+}
+
+impl CLITxOpts {
+    /// Returns the parameters of the signed extrinsic, with the tip in the smallest unit of the
+    /// network token.
+    pub fn tx_params(&self, token: &TokenInfo) -> Result<TxParams> {
+        Ok(TxParams {
+            tip: match &self.tip {
+                Some(tip) => tip.denominate(token)?,
+                None => 0,
+            },
+            era: self.era,
+            nonce: self.nonce,
+            wait_for: self.wait_for,
+            sudo: self.sudo,
+        })
     }
 }
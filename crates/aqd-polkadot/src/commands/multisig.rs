@@ -0,0 +1,501 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    serde_json::json,
+    std::{fmt::Debug, process::exit, str::FromStr},
+};
+
+use {
+    super::{CLINetworkOpts, CLISignerOpts, CLITxOpts},
+    aqd_polkadot_contracts::{pair_signer, submit_extrinsic, typed_events, TokenInfo, TxParams},
+    aqd_utils::{
+        check_target_match, print_key_value, print_text, print_title, prompt_confirm_transaction,
+        ErrorKind, OutputFormat,
+    },
+    contract_build::{util::decode_hex, Verbosity},
+    contract_extrinsics::{parse_code_hash, DefaultConfig, DisplayEvents},
+    sp_core::{blake2_256, Bytes},
+    subxt::{
+        blocks::ExtrinsicEvents,
+        dynamic::Value,
+        ext::{
+            codec::{Compact, Encode},
+            scale_value,
+        },
+        tx::Signer,
+        Config, OnlineClient,
+    },
+    subxt_signer::sr25519::Keypair,
+};
+
+type AccountId = <DefaultConfig as Config>::AccountId;
+
+/// A multisig account, as passed with `--multisig threshold,member1,member2,...`.
+#[derive(Clone, Debug)]
+pub struct Multisig {
+    /// The number of approvals required to execute a call
+    threshold: u16,
+    /// The members of the multisig, sorted
+    members: Vec<AccountId>,
+}
+
+/// Parse a multisig account from its threshold and members.
+pub(crate) fn parse_multisig(input: &str) -> Result<Multisig> {
+    let mut parts = input.split(',').map(|part| part.trim());
+    let threshold = parts
+        .next()
+        .and_then(|threshold| u16::from_str(threshold).ok())
+        .ok_or_else(|| {
+            anyhow!("Invalid multisig threshold, expected threshold,member1,member2,...")
+        })?;
+    let mut members = parts
+        .map(|member| {
+            AccountId::from_str(member)
+                .map_err(|e| anyhow!("Invalid multisig member {}: {:?}", member, e))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    members.sort();
+    members.dedup();
+    if members.len() < 2 {
+        return Err(anyhow!("A multisig requires at least two distinct members"));
+    }
+    if threshold < 2 || threshold as usize > members.len() {
+        return Err(anyhow!(
+            "The multisig threshold must be between 2 and the number of members ({})",
+            members.len()
+        ));
+    }
+    Ok(Multisig { threshold, members })
+}
+
+impl Multisig {
+    /// Returns the account ID of the multisig, derived from its members and threshold in the same
+    /// way as the `Multisig` pallet.
+    pub(crate) fn account_id(&self) -> AccountId {
+        let entropy = (b"modlpy/utilisuba", &self.members, self.threshold).encode();
+        AccountId::from(blake2_256(&entropy))
+    }
+
+    /// Returns the members of the multisig other than the signer, sorted.
+    fn other_signatories(&self, signer: &AccountId) -> Result<Value> {
+        if !self.members.contains(signer) {
+            return Err(anyhow!(
+                "The signer {} is not a member of the multisig",
+                signer
+            ));
+        }
+        Ok(Value::unnamed_composite(
+            self.members
+                .iter()
+                .filter(|member| *member != signer)
+                .map(|member| Value::from_bytes(member.0)),
+        ))
+    }
+}
+
+/// The timepoint of a multisig operation: the block number and the extrinsic index of the first
+/// approval, as passed with `--timepoint height,index`.
+#[derive(Clone, Copy, Debug)]
+pub struct Timepoint {
+    height: u32,
+    index: u32,
+}
+
+/// Parse a timepoint from its block number and extrinsic index.
+pub(crate) fn parse_timepoint(input: &str) -> Result<Timepoint> {
+    let (height, index) = input
+        .split_once(',')
+        .ok_or_else(|| anyhow!("Invalid timepoint, expected height,index"))?;
+    Ok(Timepoint {
+        height: u32::from_str(height.trim())?,
+        index: u32::from_str(index.trim())?,
+    })
+}
+
+impl Timepoint {
+    fn to_value(self) -> Value {
+        Value::named_composite([
+            ("height", Value::u128(self.height as u128)),
+            ("index", Value::u128(self.index as u128)),
+        ])
+    }
+}
+
+/// Convert an optional timepoint to a `maybe_timepoint` argument.
+fn maybe_timepoint_value(timepoint: Option<Timepoint>) -> Value {
+    match timepoint {
+        Some(timepoint) => Value::unnamed_variant("Some", [timepoint.to_value()]),
+        None => Value::unnamed_variant("None", []),
+    }
+}
+
+/// Convert a weight to a `max_weight` argument.
+fn weight_value((ref_time, proof_size): (u64, u64)) -> Value {
+    Value::named_composite([
+        ("ref_time", Value::u128(ref_time as u128)),
+        ("proof_size", Value::u128(proof_size as u128)),
+    ])
+}
+
+/// Submit a call through a multisig with `Multisig::as_multi`.
+///
+/// The first approval of a call must not have a timepoint. The call is executed when the last
+/// approval is submitted, with at most `max_weight`.
+pub(crate) async fn submit_as_multi(
+    client: &OnlineClient<DefaultConfig>,
+    signer: &Keypair,
+    tx_params: &TxParams,
+    multisig: &Multisig,
+    timepoint: Option<Timepoint>,
+    call: Value,
+    max_weight: (u64, u64),
+) -> Result<ExtrinsicEvents<DefaultConfig>> {
+    let tx = subxt::dynamic::tx(
+        "Multisig",
+        "as_multi",
+        vec![
+            ("threshold", Value::u128(multisig.threshold as u128)),
+            (
+                "other_signatories",
+                multisig.other_signatories(&Signer::<DefaultConfig>::account_id(signer))?,
+            ),
+            ("maybe_timepoint", maybe_timepoint_value(timepoint)),
+            ("call", call),
+            ("max_weight", weight_value(max_weight)),
+        ],
+    );
+    let events = submit_extrinsic(client, &tx, signer, tx_params)
+        .await
+        .map_err(|err| anyhow!("Error submitting the multisig call: {}", err))?;
+    Ok(events)
+}
+
+/// Print the result of a multisig extrinsic.
+///
+/// When the extrinsic created a new multisig operation, its timepoint is printed, as the other
+/// members need it to approve or execute the call.
+pub(crate) async fn print_multisig_result(
+    client: &OnlineClient<DefaultConfig>,
+    token: &TokenInfo,
+    events: &ExtrinsicEvents<DefaultConfig>,
+    multisig: &Multisig,
+    call_hash: [u8; 32],
+    call_data: Option<&[u8]>,
+    output: OutputFormat,
+) -> Result<()> {
+    let mut status = "Unknown";
+    for event in events.iter() {
+        let event = event?;
+        if event.pallet_name() != "Multisig" {
+            continue;
+        }
+        status = match event.variant_name() {
+            "NewMultisig" => "Created",
+            "MultisigApproval" => "Approved",
+            "MultisigExecuted" => "Executed",
+            _ => status,
+        };
+    }
+    let timepoint = if status == "Created" {
+        let block = client.blocks().at(events.block_hash()).await?;
+        Some(format!("{},{}", block.number(), events.extrinsic_index()))
+    } else {
+        None
+    };
+
+    let multisig_account = multisig.account_id().to_string();
+    let call_hash = format!("0x{}", hex::encode(call_hash));
+    let call_data = call_data.map(|call_data| format!("0x{}", hex::encode(call_data)));
+    if output.is_structured() {
+        let json_object = json!({
+            "multisig": multisig_account,
+            "status": status,
+            "call_hash": call_hash,
+            "call_data": call_data,
+            "timepoint": timepoint,
+            "events": typed_events(events, None, &client.metadata())?,
+        });
+        output.print(&json_object)?;
+    } else {
+        let display_events = DisplayEvents::from_events(events, None, &client.metadata())?;
+        print_text!(display_events.display_events(Verbosity::Default, &token.token_metadata())?);
+        print_title!("Multisig");
+        print_key_value!("Account", multisig_account);
+        print_key_value!("Status", status);
+        print_key_value!("Call hash", call_hash);
+        if let Some(call_data) = call_data {
+            print_key_value!("Call data", call_data);
+        }
+        if let Some(timepoint) = timepoint {
+            print_key_value!("Timepoint", timepoint);
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, clap::Args)]
+#[clap(
+    name = "multisig-approve",
+    about = "Approve a pending multisig call on Polkadot"
+)]
+pub struct PolkadotMultisigApproveCommand {
+    #[clap(
+        long,
+        value_parser = parse_multisig,
+        help = "Specifies the multisig as its threshold followed by its members, e.g. 2,5F...,5G...,5H..."
+    )]
+    multisig: Multisig,
+    #[clap(long, value_parser = parse_code_hash, help = "Specifies the hash of the call to approve.")]
+    call_hash: <DefaultConfig as Config>::Hash,
+    #[clap(
+        long,
+        value_parser = parse_timepoint,
+        help = "Specifies the timepoint of the multisig operation, as printed when it was created: height,index."
+    )]
+    timepoint: Timepoint,
+    #[clap(flatten)]
+    network_opts: CLINetworkOpts,
+    #[clap(flatten)]
+    signer_opts: CLISignerOpts,
+    #[clap(flatten)]
+    tx_opts: CLITxOpts,
+    #[clap(
+        short('y'),
+        long,
+        env = "AQD_SKIP_CONFIRM",
+        help = "Specifies whether to skip the confirmation prompt."
+    )]
+    skip_confirm: bool,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
+}
+
+impl PolkadotMultisigApproveCommand {
+    /// Handles the approval of a pending multisig call with `Multisig::approve_as_multi`.
+    ///
+    /// Only the hash of the call is needed to approve it. The call is not executed, even if the
+    /// threshold is reached: it must be executed with `multisig-execute`, which requires the call
+    /// data.
+    pub async fn handle(&self) -> Result<()> {
+        // Make sure the command is run in the correct directory
+        // Fails if the command is run in a Solang Solana project directory
+        let target_match = check_target_match("polkadot", None)
+            .map_err(|e| anyhow!("Failed to check current directory: {}", e))?;
+        if !target_match {
+            exit(ErrorKind::Usage.exit_code());
+        }
+
+        let signer = pair_signer(&self.signer_opts.suri()?)?;
+        let client = self.network_opts.connect().await?;
+        let token = self.network_opts.token().await?;
+        let tx_params = self.tx_opts.tx_params(&token)?;
+
+        if !self.skip_confirm {
+            prompt_confirm_transaction(|| {
+                print_title!("Multisig Approval Summary");
+                print_key_value!("Multisig", self.multisig.account_id());
+                print_key_value!("Call hash", format!("{:?}", self.call_hash));
+                print_key_value!(
+                    "Timepoint",
+                    format!("{},{}", self.timepoint.height, self.timepoint.index)
+                );
+            })?;
+        }
+
+        let tx = subxt::dynamic::tx(
+            "Multisig",
+            "approve_as_multi",
+            vec![
+                ("threshold", Value::u128(self.multisig.threshold as u128)),
+                (
+                    "other_signatories",
+                    self.multisig
+                        .other_signatories(&Signer::<DefaultConfig>::account_id(&signer))?,
+                ),
+                (
+                    "maybe_timepoint",
+                    maybe_timepoint_value(Some(self.timepoint)),
+                ),
+                ("call_hash", Value::from_bytes(self.call_hash.0)),
+                ("max_weight", weight_value((0, 0))),
+            ],
+        );
+        let events = submit_extrinsic(&client, &tx, &signer, &tx_params)
+            .await
+            .map_err(|err| anyhow!("Error approving the multisig call: {}", err))?;
+
+        print_multisig_result(
+            &client,
+            &token,
+            &events,
+            &self.multisig,
+            self.call_hash.0,
+            None,
+            self.output,
+        )
+        .await
+    }
+}
+
+#[derive(Debug, clap::Args)]
+#[clap(
+    name = "multisig-execute",
+    about = "Execute a pending multisig call on Polkadot"
+)]
+pub struct PolkadotMultisigExecuteCommand {
+    #[clap(
+        long,
+        value_parser = parse_multisig,
+        help = "Specifies the multisig as its threshold followed by its members, e.g. 2,5F...,5G...,5H..."
+    )]
+    multisig: Multisig,
+    #[clap(
+        long,
+        value_parser = parse_call_data,
+        help = "Specifies the encoded call to execute, as hex, as printed when the multisig operation was created."
+    )]
+    call_data: Bytes,
+    #[clap(
+        long,
+        value_parser = parse_timepoint,
+        help = "Specifies the timepoint of the multisig operation, as printed when it was created: height,index."
+    )]
+    timepoint: Timepoint,
+    #[clap(flatten)]
+    network_opts: CLINetworkOpts,
+    #[clap(flatten)]
+    signer_opts: CLISignerOpts,
+    #[clap(flatten)]
+    tx_opts: CLITxOpts,
+    #[clap(
+        short('y'),
+        long,
+        env = "AQD_SKIP_CONFIRM",
+        help = "Specifies whether to skip the confirmation prompt."
+    )]
+    skip_confirm: bool,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
+}
+
+/// Parse a hex encoded call.
+fn parse_call_data(input: &str) -> Result<Bytes> {
+    Ok(decode_hex(input)?.into())
+}
+
+impl PolkadotMultisigExecuteCommand {
+    /// Handles the execution of a pending multisig call with `Multisig::as_multi`.
+    ///
+    /// This submits the last approval of the call along with the call data, so the call is
+    /// executed if the threshold is reached. The maximum weight of the call is queried with the
+    /// `TransactionPaymentCallApi_query_call_info` runtime API.
+    pub async fn handle(&self) -> Result<()> {
+        // Make sure the command is run in the correct directory
+        // Fails if the command is run in a Solang Solana project directory
+        let target_match = check_target_match("polkadot", None)
+            .map_err(|e| anyhow!("Failed to check current directory: {}", e))?;
+        if !target_match {
+            exit(ErrorKind::Usage.exit_code());
+        }
+
+        let signer = pair_signer(&self.signer_opts.suri()?)?;
+        let client = self.network_opts.connect().await?;
+
+        let call = decode_call(&client, &self.call_data)?;
+        let max_weight = query_call_weight(&client, &self.call_data).await?;
+        let token = self.network_opts.token().await?;
+        let tx_params = self.tx_opts.tx_params(&token)?;
+
+        if !self.skip_confirm {
+            prompt_confirm_transaction(|| {
+                print_title!("Multisig Execution Summary");
+                print_key_value!("Multisig", self.multisig.account_id());
+                print_key_value!(
+                    "Call hash",
+                    format!("0x{}", hex::encode(blake2_256(&self.call_data)))
+                );
+                print_key_value!(
+                    "Timepoint",
+                    format!("{},{}", self.timepoint.height, self.timepoint.index)
+                );
+                print_key_value!(
+                    "Max weight",
+                    format!("ref_time: {}, proof_size: {}", max_weight.0, max_weight.1)
+                );
+            })?;
+        }
+
+        let events = submit_as_multi(
+            &client,
+            &signer,
+            &tx_params,
+            &self.multisig,
+            Some(self.timepoint),
+            call,
+            max_weight,
+        )
+        .await?;
+
+        print_multisig_result(
+            &client,
+            &token,
+            &events,
+            &self.multisig,
+            blake2_256(&self.call_data),
+            Some(&self.call_data[..]),
+            self.output,
+        )
+        .await
+    }
+}
+
+/// Decode an encoded call into a value that can be nested in a `Multisig::as_multi` extrinsic.
+fn decode_call(client: &OnlineClient<DefaultConfig>, call_data: &[u8]) -> Result<Value> {
+    let metadata = client.metadata();
+    let call_type = metadata
+        .pallet_by_name("Multisig")
+        .and_then(|pallet| pallet.call_variant_by_name("as_multi"))
+        .and_then(|call| {
+            call.fields
+                .iter()
+                .find(|field| field.name.as_deref() == Some("call"))
+        })
+        .map(|field| field.ty.id)
+        .ok_or_else(|| anyhow!("The Multisig pallet is not available on this chain"))?;
+    let call = scale_value::scale::decode_as_type(&mut &call_data[..], call_type, metadata.types())
+        .map_err(|e| anyhow!("Failed to decode the call data: {}", e))?;
+    Ok(call.remove_context())
+}
+
+/// Query the weight of an encoded call with the `TransactionPaymentCallApi_query_call_info`
+/// runtime API.
+async fn query_call_weight(
+    client: &OnlineClient<DefaultConfig>,
+    call_data: &[u8],
+) -> Result<(u64, u64)> {
+    // The call is passed as is, followed by its length
+    let mut call_parameters = call_data.to_vec();
+    (call_data.len() as u32).encode_to(&mut call_parameters);
+    // The result starts with the weight of the call: a `ref_time` and a `proof_size`
+    let (ref_time, proof_size): (Compact<u64>, Compact<u64>) = client
+        .runtime_api()
+        .at_latest()
+        .await?
+        .call_raw(
+            "TransactionPaymentCallApi_query_call_info",
+            Some(&call_parameters),
+        )
+        .await?;
+    Ok((ref_time.0, proof_size.0))
+}
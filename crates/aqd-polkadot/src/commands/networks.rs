@@ -0,0 +1,92 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::Result,
+    serde_json::{json, Value},
+    std::fmt::Debug,
+};
+
+use {
+    super::Network,
+    aqd_utils::{print_key_value, print_subtitle, print_title, AqdConfig, OutputFormat},
+    clap::ValueEnum,
+};
+
+#[derive(Debug, clap::Args)]
+#[clap(
+    name = "networks",
+    about = "List the Polkadot networks that can be used with --network"
+)]
+pub struct PolkadotNetworksCommand {
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
+}
+
+impl PolkadotNetworksCommand {
+    /// Handles the listing of the available networks.
+    ///
+    /// The known networks are listed first, followed by the networks defined in the aqd
+    /// configuration file. The known networks overridden by a network of the configuration file are
+    /// not listed.
+    pub async fn handle(&self) -> Result<()> {
+        let config = AqdConfig::load()?;
+        let known: Vec<&Network> = Network::value_variants()
+            .iter()
+            .filter(|network| config.network(&network.name()).is_none())
+            .collect();
+
+        let known_networks: Vec<Value> = known
+            .iter()
+            .map(|network| json!({ "name": network.name(), "url": network.url() }))
+            .collect();
+        let user_networks: Vec<Value> = config
+            .networks
+            .iter()
+            .map(|network| {
+                json!({
+                    "name": network.name,
+                    "url": network.url,
+                    "token_decimals": network.token_decimals,
+                    "token_symbol": network.token_symbol,
+                    "explorer": network.explorer,
+                })
+            })
+            .collect();
+
+        if self.output.is_structured() {
+            let json_object = json!({
+                "known_networks": known_networks,
+                "user_networks": user_networks,
+            });
+            self.output.print(&json_object)?;
+        } else {
+            print_title!("Known networks");
+            for network in &known {
+                print_key_value!(network.name(), network.url());
+            }
+            print_title!("User-defined networks");
+            if let Some(path) = AqdConfig::path() {
+                print_key_value!("Config file", path.display());
+            }
+            for network in &config.networks {
+                print_subtitle!(network.name);
+                print_key_value!("URL", network.url);
+                if let Some(symbol) = &network.token_symbol {
+                    print_key_value!("Token symbol", symbol);
+                }
+                if let Some(decimals) = network.token_decimals {
+                    print_key_value!("Token decimals", decimals);
+                }
+                if let Some(explorer) = &network.explorer {
+                    print_key_value!("Explorer", explorer);
+                }
+            }
+        }
+        Ok(())
+    }
+}
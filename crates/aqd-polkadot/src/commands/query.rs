@@ -0,0 +1,120 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    std::{fmt::Debug, path::PathBuf, process::exit},
+};
+
+use {
+    super::CLINetworkOpts,
+    aqd_polkadot_contracts::{
+        check_node_compatibility, dry_run_call, load_transcoder, parse_contract_address,
+        with_debug_output, Balance, CallDryRunResult,
+    },
+    aqd_utils::{check_target_match, print_text, ErrorKind, OutputFormat},
+    contract_extrinsics::DefaultConfig,
+    subxt::Config,
+};
+
+type AccountId = <DefaultConfig as Config>::AccountId;
+
+#[derive(Debug, clap::Args)]
+#[clap(
+    name = "query",
+    about = "Query a contract on Polkadot with a read-only message"
+)]
+pub struct PolkadotQueryCommand {
+    #[clap(
+        value_parser,
+        help = "Specifies the path to a .contract bundle or .json metadata file of the contract."
+    )]
+    file: PathBuf,
+    #[clap(
+        name = "contract",
+        long,
+        value_parser = parse_contract_address,
+        help = "Specifies the address of the contract to query, as SS58 or as a 20-byte hex address on pallet-revive chains."
+    )]
+    contract: AccountId,
+    #[clap(
+        long,
+        short,
+        help = "Specifies the name of the contract message to query."
+    )]
+    message: String,
+    #[clap(long, num_args = 0.., help = "Specifies the arguments of the contract message to query.")]
+    args: Vec<String>,
+    #[clap(flatten)]
+    network_opts: CLINetworkOpts,
+    #[clap(
+        long,
+        default_value = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY",
+        help = "Specifies the account to query the contract from. Defaults to the development account of Alice.
+    No secret key URI is needed, as nothing is signed."
+    )]
+    origin: AccountId,
+    #[clap(
+        long,
+        default_value = "0",
+        help = "Specifies the value to be transferred as part of the query.
+    Either an amount in the smallest unit of the token, or in token units, e.g. 1.5 DOT or 0.5unit."
+    )]
+    value: Balance,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
+}
+
+impl PolkadotQueryCommand {
+    /// Handles the query of a contract on the Polkadot network.
+    ///
+    /// The message is always dry-run with the `ContractsApi_call` runtime API (`ReviveApi_call` on
+    /// pallet-revive), so nothing is
+    /// submitted and no secret key URI is needed. Only the decoded return value is printed, in the
+    /// format of the `output` option.
+    pub async fn handle(&self) -> Result<()> {
+        // Make sure the command is run in the correct directory
+        // Fails if the command is run in a Solang Solana project directory
+        let target_match = check_target_match("polkadot", None)
+            .map_err(|e| anyhow!("Failed to check current directory: {}", e))?;
+        if !target_match {
+            exit(ErrorKind::Usage.exit_code());
+        }
+
+        let transcoder = load_transcoder(&self.file)?;
+        let client = self.network_opts.connect().await?;
+        let pallet = check_node_compatibility(&client)?;
+        let value = match self.value {
+            // The token is only queried when an amount in token units has to be denominated
+            Balance::Raw(value) => value,
+            _ => self.value.denominate(&self.network_opts.token().await?)?,
+        };
+        let result = dry_run_call(
+            &client,
+            pallet,
+            &self.origin,
+            &self.contract,
+            value,
+            None,
+            transcoder.encode(&self.message, &self.args)?,
+        )
+        .await?;
+        let result = CallDryRunResult::from_exec_result(&transcoder, &self.message, result)?;
+        if result.reverted {
+            return Err(with_debug_output(
+                format!("The contract reverted: {}", result.data),
+                &result.debug_output,
+            ));
+        }
+        if self.output.is_structured() {
+            self.output.print(&result.data)?;
+        } else {
+            print_text!(result.data);
+        }
+        Ok(())
+    }
+}
@@ -2,90 +2,127 @@
 
 use {
     anyhow::{anyhow, Result},
-    colored::Colorize,
-    serde_json::{from_str, json, to_string_pretty, Value},
-    std::fmt::Debug,
-    std::process::exit,
+    serde_json::json,
+    std::{fmt::Debug, path::PathBuf, process::exit},
 };
 
 use {
-    super::CLIExtrinsicOpts,
-    aqd_utils::{check_target_match, print_key_value},
-    contract_build::Verbosity,
-    contract_extrinsics::{
-        parse_code_hash, DefaultConfig, ExtrinsicOptsBuilder, RemoveCommandBuilder,
+    super::{CLINetworkOpts, CLISignerOpts, CLITxOpts},
+    aqd_polkadot_contracts::{
+        check_node_compatibility, contract_code_hash, decode_events, extrinsic_block, pair_signer,
+        submit_extrinsic, typed_events,
+    },
+    aqd_utils::{
+        check_target_match, is_quiet, print_key_value, print_text, ErrorKind, OutputFormat,
     },
-    subxt::Config,
+    contract_build::Verbosity,
+    contract_extrinsics::{parse_code_hash, DefaultConfig, DisplayEvents},
+    subxt::{dynamic::Value as DynamicValue, Config},
 };
 
+type Hash = <DefaultConfig as Config>::Hash;
+
 #[derive(Debug, clap::Args)]
 #[clap(name = "remove", about = "Remove a contract on Polkadot")]
 pub struct PolkadotRemoveCommand {
-    #[clap(long, value_parser = parse_code_hash, help = "Specifies the code hash to remove.")]
-    code_hash: Option<<DefaultConfig as Config>::Hash>,
+    #[clap(
+        long,
+        value_parser = parse_code_hash,
+        required_unless_present = "file",
+        help = "Specifies the code hash to remove."
+    )]
+    code_hash: Option<Hash>,
+    #[clap(
+        value_parser,
+        help = "Specifies the path to a contract wasm file, .contract bundle, or .json metadata file, used to compute the code hash to remove.
+    Not needed when --code-hash is given."
+    )]
+    file: Option<PathBuf>,
     #[clap(flatten)]
-    extrinsic_cli_opts: CLIExtrinsicOpts,
+    network_opts: CLINetworkOpts,
+    #[clap(flatten)]
+    signer_opts: CLISignerOpts,
+    #[clap(flatten)]
+    tx_opts: CLITxOpts,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
 }
 
 impl PolkadotRemoveCommand {
-    /// Returns whether to export the call output in JSON format.
-    pub fn output_json(&self) -> bool {
-        self.extrinsic_cli_opts.output_json
+    /// Returns the format of the output.
+    pub fn output(&self) -> OutputFormat {
+        self.output
     }
 
     /// Handles the removal of a contract from the Polkadot network.
     ///
-    /// Removes a contract with the specified code hash. If successful, it returns information about the
-    /// removal, including the events generated. The `output_json` flag controls the output format.
+    /// Removes the code with the specified code hash, or with the code hash of the contract file if
+    /// no code hash is given. If successful, it returns information about the removal, including the
+    /// events generated. The `output` option controls the output format.
     pub async fn handle(&self) -> Result<()> {
         // Make sure the command is run in the correct directory
         // Fails if the command is run in a Solang Solana project directory
         let target_match = check_target_match("polkadot", None)
             .map_err(|e| anyhow!("Failed to check current directory: {}", e))?;
         if !target_match {
-            exit(1);
+            exit(ErrorKind::Usage.exit_code());
         }
 
-        // Initialize the extrinsic options
-        let cli_options = ExtrinsicOptsBuilder::default()
-            .file(Some(self.extrinsic_cli_opts.file.clone()))
-            .url(self.extrinsic_cli_opts.url().clone())
-            .suri(self.extrinsic_cli_opts.suri.clone())
-            .storage_deposit_limit(self.extrinsic_cli_opts.storage_deposit_limit.clone())
-            .done();
-        let exec = RemoveCommandBuilder::default()
-            .code_hash(self.code_hash)
-            .extrinsic_opts(cli_options)
-            .done()
-            .await?;
+        let token = self.network_opts.token().await?;
+        let client = self.network_opts.connect().await?;
+        let pallet = check_node_compatibility(&client)?;
 
-        let remove_result = exec
-            .remove_code()
-            .await
-            .map_err(|err| anyhow!("Error removing the code: {}", err.to_string()))?;
-        let display_events = remove_result.display_events;
-        let events = if self.output_json() {
-            display_events.to_json()?
-        } else {
-            display_events.display_events(Verbosity::Default, exec.token_metadata())?
+        // The code hash is computed from the contract file if it is not specified, so that code
+        // whose artifacts no longer exist locally can still be removed
+        let code_hash = match (&self.code_hash, &self.file) {
+            (Some(code_hash), _) => *code_hash,
+            (None, Some(file)) => contract_code_hash(file, pallet)?,
+            (None, None) => {
+                return Err(anyhow!(
+                    "Missing code hash: use --code-hash or a contract file"
+                ))
+            }
         };
-        let code_removed = remove_result.code_removed.ok_or_else(|| {
-            anyhow!(
-                "Error removing the code: {}",
-                hex::encode(exec.final_code_hash())
-            )
-        })?;
-        let remove_result = code_removed.code_hash;
-        if self.output_json() {
+
+        let call = subxt::dynamic::tx(
+            pallet.name(),
+            "remove_code",
+            vec![("code_hash", DynamicValue::from_bytes(code_hash))],
+        );
+        let events = submit_extrinsic(
+            &client,
+            &call,
+            &pair_signer(&self.signer_opts.suri()?)?,
+            &self.tx_opts.tx_params(&token)?,
+        )
+        .await
+        .map_err(|err| anyhow!("Error removing the code: {}", err))?;
+        let mut block = extrinsic_block(&client, &events).await?;
+        let explorer_url = self.network_opts.explorer_url(&block);
+        block.explorer_url = Some(explorer_url.clone());
+        let remove_result = decode_events::<Hash>(&events, pallet.name(), "CodeRemoved")?
+            .pop()
+            .ok_or_else(|| anyhow!("Error removing the code: {:?}", code_hash))?;
+        if is_quiet() {
+            print_text!(format!("{:?}", remove_result));
+        } else if self.output().is_structured() {
             let json_object = json!({
-                "events": from_str::<Value>(&events)?,
+                "events": typed_events(&events, None, &client.metadata())?,
                 "removed_code_hash": remove_result,
+                "block": block,
             });
-            let json_object = to_string_pretty(&json_object)?;
-            println!("{}", json_object);
+            self.output().print(&json_object)?;
         } else {
-            println!("{events}");
+            let display_events = DisplayEvents::from_events(&events, None, &client.metadata())?;
+            print_text!(display_events.display_events(Verbosity::Default, &token.token_metadata())?);
             print_key_value!("Code hash", format!("{remove_result:?}"));
+            print_key_value!("Block", block);
+            print_key_value!("Explorer", explorer_url);
         }
         Ok(())
     }
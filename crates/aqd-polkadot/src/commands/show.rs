@@ -0,0 +1,108 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    std::{fmt::Debug, path::PathBuf, process::exit},
+};
+
+use {
+    aqd_polkadot_contracts::{ContractSpec, MessageSpec},
+    aqd_utils::{
+        check_target_match, print_key_value, print_subtitle, print_title, ErrorKind, OutputFormat,
+    },
+};
+
+#[derive(Debug, clap::Args)]
+#[clap(
+    name = "show",
+    about = "Show the constructors and messages of a Polkadot contract given its metadata"
+)]
+pub struct PolkadotShowCommand {
+    #[clap(
+        value_parser,
+        help = "Specifies the path to a contract .contract bundle or .json metadata file."
+    )]
+    file: PathBuf,
+    #[clap(
+        long,
+        help = "Specifies the name of the constructor or message to show information about.
+    If not specified, information about all constructors and messages is shown."
+    )]
+    message: Option<String>,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
+}
+
+impl PolkadotShowCommand {
+    /// Handles the listing of the constructors and messages of a contract.
+    ///
+    /// The selector, arguments, mutability, payability, return type and documentation of each
+    /// constructor and message are read from the metadata of the contract, without connecting to
+    /// a node.
+    pub async fn handle(&self) -> Result<()> {
+        // Make sure the command is run in the correct directory
+        // Fails if the command is run in a Solang Solana project directory
+        let target_match = check_target_match("polkadot", None)
+            .map_err(|e| anyhow!("Failed to check current directory: {}", e))?;
+        if !target_match {
+            exit(ErrorKind::Usage.exit_code());
+        }
+
+        let spec = ContractSpec::from_file(&self.file)?;
+
+        if let Some(label) = &self.message {
+            let message = spec.find(label).ok_or_else(|| {
+                anyhow!(
+                    "No constructor or message {} in {}",
+                    label,
+                    self.file.display()
+                )
+            })?;
+            if self.output.is_structured() {
+                self.output.print(message)?;
+            } else {
+                print_message(message);
+            }
+            return Ok(());
+        }
+
+        if self.output.is_structured() {
+            self.output.print(&spec)?;
+        } else {
+            print_title!("Contract");
+            print_key_value!("Name", spec.name);
+            print_key_value!("Version", spec.version);
+            print_key_value!("Metadata", spec.metadata_version);
+            print_title!("Constructors");
+            spec.constructors.iter().for_each(print_message);
+            print_title!("Messages");
+            spec.messages.iter().for_each(print_message);
+        }
+        Ok(())
+    }
+}
+
+/// Print a constructor or a message, with its arguments and documentation.
+fn print_message(message: &MessageSpec) {
+    print_subtitle!(message.label);
+    print_key_value!("Selector", message.selector);
+    let args: Vec<String> = message
+        .args
+        .iter()
+        .map(|arg| format!("{}: {}", arg.name, arg.ty))
+        .collect();
+    print_key_value!("Args", args.join(", "));
+    if let Some(return_type) = &message.return_type {
+        print_key_value!("Returns", return_type);
+    }
+    print_key_value!("Mutates", message.mutates);
+    print_key_value!("Payable", message.payable);
+    if !message.docs.is_empty() {
+        print_key_value!("Docs", message.docs.replace('\n', " "));
+    }
+}
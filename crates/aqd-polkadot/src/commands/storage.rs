@@ -0,0 +1,436 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Context, Result},
+    colored::Colorize,
+    serde_json::{json, Value},
+    std::{collections::HashMap, fmt::Debug, path::PathBuf, process::exit},
+};
+
+use {
+    super::CLINetworkOpts,
+    aqd_polkadot_contracts::{load_transcoder, read_metadata, StorageLayout},
+    aqd_utils::{
+        check_target_match, print_key_value, print_subtitle, print_text, print_title, ErrorKind,
+        OutputFormat,
+    },
+    contract_build::util::decode_hex,
+    contract_extrinsics::DefaultConfig,
+    contract_transcode::{ContractMessageTranscoder, Value as TranscodeValue},
+    sp_core::Bytes,
+    subxt::{ext::codec::Encode, Config, OnlineClient},
+};
+
+/// The length of the storage keys used by Solang contracts.
+const SOLANG_KEY_LENGTH: usize = 32;
+
+#[derive(Debug, clap::Args)]
+#[clap(name = "storage", about = "Read the storage of a contract on Polkadot")]
+pub struct PolkadotStorageCommand {
+    #[clap(
+        value_parser,
+        help = "Specifies the path to a contract .contract bundle or .json metadata file, used to decode the storage."
+    )]
+    file: PathBuf,
+    #[clap(
+        name = "contract",
+        long,
+        required_unless_present = "layout",
+        help = "Specifies the address of the contract whose storage is read."
+    )]
+    contract: Option<<DefaultConfig as Config>::AccountId>,
+    #[clap(
+        long,
+        value_parser = parse_hex_key,
+        conflicts_with = "root",
+        help = "Specifies a raw storage key to read, as hex."
+    )]
+    key: Option<Bytes>,
+    #[clap(
+        long,
+        help = "Specifies whether to read and decode all the fields of the contract root storage. This is the default."
+    )]
+    root: bool,
+    #[clap(
+        long,
+        conflicts_with = "key",
+        help = "Specifies whether to print the storage layout of the contract as a tree, with the keys, types and docs of its cells.
+    With --contract, the current values of the cells of the root storage are read and decoded."
+    )]
+    layout: bool,
+    #[clap(flatten)]
+    network_opts: CLINetworkOpts,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
+}
+
+/// Parse a hex encoded storage key.
+fn parse_hex_key(input: &str) -> Result<Bytes> {
+    Ok(decode_hex(input)?.into())
+}
+
+/// A field of the contract storage, as described by the storage layout of the metadata.
+#[derive(Debug)]
+struct StorageField {
+    /// The path of the field in the storage layout (e.g. `balances.total`)
+    path: String,
+    /// The key of the storage cell containing the field
+    key: Vec<u8>,
+    /// The type ID of the field in the metadata type registry
+    type_id: u32,
+}
+
+impl PolkadotStorageCommand {
+    /// Handles the reading of the storage of a contract on the Polkadot network.
+    ///
+    /// The storage is read with the `ContractsApi_get_storage` runtime API. By default, the fields of
+    /// the contract root storage are read and decoded using the storage layout of the metadata.
+    /// If a raw key is provided, the value stored at that key is printed, and decoded if the key
+    /// belongs to the storage layout.
+    pub async fn handle(&self) -> Result<()> {
+        // Make sure the command is run in the correct directory
+        // Fails if the command is run in a Solang Solana project directory
+        let target_match = check_target_match("polkadot", None)
+            .map_err(|e| anyhow!("Failed to check current directory: {}", e))?;
+        if !target_match {
+            exit(ErrorKind::Usage.exit_code());
+        }
+
+        let transcoder = load_transcoder(&self.file)?;
+        let (_, metadata) = read_metadata(&self.file)?;
+        let mut fields = vec![];
+        if let Some(layout) = metadata.get("storage") {
+            collect_storage_fields(layout, "", None, &mut fields)?;
+        }
+
+        if self.layout {
+            return self.print_layout(&transcoder, &fields).await;
+        }
+        let contract = self
+            .contract
+            .as_ref()
+            .ok_or_else(|| anyhow!("Missing contract address: use --contract"))?;
+
+        let client = self.network_opts.connect().await?;
+
+        // Read the requested key, or all the keys of the root storage
+        let keys = match &self.key {
+            Some(key) if !self.root => vec![key.to_vec()],
+            _ => root_keys(&fields),
+        };
+        let cells = read_storage_fields(&client, contract, &transcoder, &fields, keys).await?;
+
+        if self.output.is_structured() {
+            let cells: Vec<Value> = cells
+                .iter()
+                .map(|(key, value, decoded_fields)| {
+                    let decoded_fields: Vec<Value> = decoded_fields
+                        .iter()
+                        .map(|(path, decoded)| json!({ "path": path, "value": decoded }))
+                        .collect();
+                    json!({
+                        "key": format!("0x{}", hex::encode(key)),
+                        "value": value.as_ref().map(|value| format!("0x{}", hex::encode(value))),
+                        "fields": decoded_fields,
+                    })
+                })
+                .collect();
+            self.output.print(&cells)?;
+        } else {
+            print_title!("Contract storage");
+            for (key, value, decoded_fields) in &cells {
+                print_subtitle!(format!("Key 0x{}", hex::encode(key)));
+                match value {
+                    Some(value) => {
+                        print_key_value!("Raw value", format!("0x{}", hex::encode(value)))
+                    }
+                    None => print_key_value!("Raw value", "None"),
+                }
+                for (path, decoded) in decoded_fields {
+                    print_key_value!(path, decoded);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Prints the storage layout of the contract as a tree, with the current values of the cells of
+    /// the root storage if a contract address is specified.
+    async fn print_layout(
+        &self,
+        transcoder: &ContractMessageTranscoder,
+        fields: &[StorageField],
+    ) -> Result<()> {
+        let mut layout = StorageLayout::from_file(&self.file)?.ok_or_else(|| {
+            anyhow!(
+                "The metadata {} does not describe the storage",
+                self.file.display()
+            )
+        })?;
+
+        let mut values = HashMap::new();
+        if let Some(contract) = &self.contract {
+            let client = self.network_opts.connect().await?;
+            let cells =
+                read_storage_fields(&client, contract, transcoder, fields, root_keys(fields))
+                    .await?;
+            values = cells
+                .into_iter()
+                .flat_map(|(_, _, decoded_fields)| decoded_fields)
+                .collect();
+            for leaf in layout.leaves_mut() {
+                if let Some(value) = leaf.path.as_ref().and_then(|path| values.get(path)) {
+                    leaf.value = Some(serde_json::to_value(value)?);
+                }
+            }
+        }
+
+        if self.output.is_structured() {
+            self.output.print(&layout)?;
+        } else {
+            print_title!("Storage layout");
+            print_layout_node(&layout, 0, &values);
+        }
+        Ok(())
+    }
+}
+
+/// The cells of the contract storage read: their key, their raw value, and the decoded values of
+/// the fields they contain with their path.
+type StorageCells = Vec<(Vec<u8>, Option<Vec<u8>>, Vec<(String, TranscodeValue)>)>;
+
+/// Returns the keys of the cells of the root storage, in the order of the fields.
+fn root_keys(fields: &[StorageField]) -> Vec<Vec<u8>> {
+    let mut keys: Vec<Vec<u8>> = vec![];
+    for field in fields {
+        if !keys.contains(&field.key) {
+            keys.push(field.key.clone());
+        }
+    }
+    keys
+}
+
+/// Read cells of the contract storage, and decode the fields of the storage layout they contain.
+async fn read_storage_fields(
+    client: &OnlineClient<DefaultConfig>,
+    contract: &<DefaultConfig as Config>::AccountId,
+    transcoder: &ContractMessageTranscoder,
+    fields: &[StorageField],
+    keys: Vec<Vec<u8>>,
+) -> Result<StorageCells> {
+    let mut cells = vec![];
+    for key in keys {
+        let value = read_storage_cell(client, contract, &key).await?;
+
+        // The fields stored in the same cell are encoded one after the other
+        let mut decoded_fields = vec![];
+        if let Some(value) = &value {
+            let mut input = &value[..];
+            for field in fields.iter().filter(|field| field.key == key) {
+                let decoded = transcoder
+                    .decode(field.type_id, &mut input)
+                    .context(format!("Failed to decode storage field {}", field.path))?;
+                decoded_fields.push((field.path.clone(), decoded));
+            }
+        }
+        cells.push((key, value, decoded_fields));
+    }
+    Ok(cells)
+}
+
+/// Print a node of the storage layout and its children, indented by their depth.
+fn print_layout_node(node: &StorageLayout, depth: usize, values: &HashMap<String, TranscodeValue>) {
+    let indent = "  ".repeat(depth);
+    let mut line = format!("{}{} ({})", indent, node.name.bold(), node.kind);
+    if let Some(key) = &node.key {
+        line.push_str(&format!(" key {}", key));
+    }
+    if let Some(ty) = &node.ty {
+        line.push_str(&format!(": {}", ty));
+    }
+    if let Some(value) = node.path.as_ref().and_then(|path| values.get(path)) {
+        line.push_str(&format!(" = {}", value));
+    }
+    print_text!(line);
+    for doc in node.docs.lines() {
+        print_text!(format!("{}  /// {}", indent, doc));
+    }
+    for child in &node.children {
+        print_layout_node(child, depth + 1, values);
+    }
+}
+
+/// Collect the fields of a storage layout, in the order they are encoded.
+///
+/// Only the fields stored eagerly are collected: lazy mappings (`hash` layouts) are stored under
+/// keys derived from the mapping keys, and must be read with a raw key.
+fn collect_storage_fields(
+    layout: &Value,
+    path: &str,
+    root_key: Option<&[u8]>,
+    fields: &mut Vec<StorageField>,
+) -> Result<()> {
+    if let Some(root) = layout.get("root") {
+        let root_key = parse_layout_key(root.get("root_key"))?;
+        if let Some(inner) = root.get("layout") {
+            collect_storage_fields(inner, path, Some(root_key.as_slice()), fields)?;
+        }
+    } else if let Some(leaf) = layout.get("leaf") {
+        let key = match root_key {
+            Some(root_key) => root_key.to_vec(),
+            None => parse_layout_key(leaf.get("key"))?,
+        };
+        let type_id = leaf
+            .get("ty")
+            .and_then(|ty| ty.as_u64())
+            .ok_or_else(|| anyhow!("Missing type of storage field {}", path))?;
+        fields.push(StorageField {
+            path: if path.is_empty() {
+                "value".to_string()
+            } else {
+                path.to_string()
+            },
+            key,
+            type_id: type_id as u32,
+        });
+    } else if let Some(structure) = layout.get("struct") {
+        for field in structure
+            .get("fields")
+            .and_then(|fields| fields.as_array())
+            .into_iter()
+            .flatten()
+        {
+            let name = field
+                .get("name")
+                .and_then(|name| name.as_str())
+                .unwrap_or("");
+            let field_path = match (path.is_empty(), name.is_empty()) {
+                (true, _) => name.to_string(),
+                (false, true) => path.to_string(),
+                (false, false) => format!("{}.{}", path, name),
+            };
+            if let Some(inner) = field.get("layout") {
+                collect_storage_fields(inner, &field_path, root_key, fields)?;
+            }
+        }
+    } else if let Some(array) = layout.get("array") {
+        let len = array.get("len").and_then(|len| len.as_u64()).unwrap_or(0);
+        if let Some(inner) = array.get("layout") {
+            for i in 0..len {
+                collect_storage_fields(inner, &format!("{}[{}]", path, i), root_key, fields)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Parse a hex encoded key of the storage layout.
+fn parse_layout_key(key: Option<&Value>) -> Result<Vec<u8>> {
+    let key = key
+        .and_then(|key| key.as_str())
+        .ok_or_else(|| anyhow!("Missing storage key in the storage layout"))?;
+    Ok(decode_hex(key)?)
+}
+
+/// Read a cell of the contract storage.
+///
+/// ink! contracts use the key of the storage layout as is, while Solang contracts use 32 bytes
+/// keys. If nothing is stored under a shorter key, the key padded with zeros to 32 bytes is read.
+async fn read_storage_cell(
+    client: &OnlineClient<DefaultConfig>,
+    contract: &<DefaultConfig as Config>::AccountId,
+    key: &[u8],
+) -> Result<Option<Vec<u8>>> {
+    let value = get_contract_storage(client, contract, key).await?;
+    if value.is_some() || key.len() >= SOLANG_KEY_LENGTH {
+        return Ok(value);
+    }
+    let mut padded_key = key.to_vec();
+    padded_key.resize(SOLANG_KEY_LENGTH, 0);
+    get_contract_storage(client, contract, &padded_key).await
+}
+
+/// Query a storage key of a contract with the `ContractsApi_get_storage` runtime API.
+async fn get_contract_storage(
+    client: &OnlineClient<DefaultConfig>,
+    contract: &<DefaultConfig as Config>::AccountId,
+    key: &[u8],
+) -> Result<Option<Vec<u8>>> {
+    let call_parameters = (contract, key.to_vec()).encode();
+    // The error is a `ContractAccessError`, an enum without fields encoded as its variant index
+    let result: std::result::Result<Option<Vec<u8>>, u8> = client
+        .runtime_api()
+        .at_latest()
+        .await?
+        .call_raw("ContractsApi_get_storage", Some(&call_parameters))
+        .await?;
+    result.map_err(|error| match error {
+        0 => anyhow!("Contract {} does not exist", contract),
+        1 => anyhow!(
+            "The storage key 0x{} could not be decoded",
+            hex::encode(key)
+        ),
+        2 => anyhow!("A storage migration is in progress, please try again later"),
+        _ => anyhow!("Failed to read the contract storage (error {})", error),
+    })
+}
+
+/// A test for the parsing of the arguments of the `storage` command
+#[test]
+fn test_storage_command_args() {
+    use clap::{Args, Command, FromArgMatches};
+
+    const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+    let parse = |args: &[&str]| {
+        let matches = PolkadotStorageCommand::augment_args(Command::new("storage"))
+            .try_get_matches_from(args)?;
+        PolkadotStorageCommand::from_arg_matches(&matches)
+    };
+
+    let command = parse(&["storage", "flipper.contract", "--contract", ALICE]).unwrap();
+    assert_eq!(command.key, None);
+    assert!(!command.root && !command.layout);
+
+    // The key is parsed as a single hex argument
+    let command = parse(&[
+        "storage",
+        "flipper.contract",
+        "--contract",
+        ALICE,
+        "--key",
+        "0x0102",
+    ])
+    .unwrap();
+    assert_eq!(command.key, Some(Bytes(vec![1, 2])));
+    assert!(parse(&[
+        "storage",
+        "flipper.contract",
+        "--contract",
+        ALICE,
+        "--key",
+        "0xzz"
+    ])
+    .is_err());
+
+    // The key cannot be combined with the root storage or the layout
+    assert!(parse(&[
+        "storage",
+        "flipper.contract",
+        "--contract",
+        ALICE,
+        "--key",
+        "0x01",
+        "--root",
+    ])
+    .is_err());
+    assert!(parse(&["storage", "flipper.contract", "--layout", "--key", "0x01"]).is_err());
+
+    // The contract is only optional to print the layout
+    assert!(parse(&["storage", "flipper.contract"]).is_err());
+    assert!(parse(&["storage", "flipper.contract", "--layout"]).is_ok());
+}
@@ -0,0 +1,146 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    serde_json::json,
+    std::{fmt::Debug, path::PathBuf, process::exit},
+};
+
+use {
+    super::CLINetworkOpts,
+    aqd_polkadot_contracts::{
+        assemble_signed_extrinsic, extrinsic_block, load_transcoder, submit_signed_extrinsic,
+        typed_events, WaitFor,
+    },
+    aqd_utils::{check_target_match, print_key_value, print_text, ErrorKind, OutputFormat},
+    contract_build::{util::decode_hex, Verbosity},
+    contract_extrinsics::{DefaultConfig, DisplayEvents},
+    sp_core::Bytes,
+    subxt::Config,
+};
+
+type AccountId = <DefaultConfig as Config>::AccountId;
+
+#[derive(Debug, clap::Args)]
+#[clap(name = "submit", about = "Submit a signed extrinsic on Polkadot")]
+pub struct PolkadotSubmitCommand {
+    #[clap(
+        long,
+        value_parser = parse_extrinsic,
+        required_unless_present = "signature",
+        help = "Specifies the signed extrinsic to submit, as hex."
+    )]
+    extrinsic: Option<Bytes>,
+    #[clap(
+        long,
+        value_parser = parse_extrinsic,
+        conflicts_with = "extrinsic",
+        requires_all = ["signer", "extra", "call_data"],
+        help = "Specifies the signature of the signer payload of a call exported with --export-call-data, as hex: an sr25519 signature or a SCALE-encoded MultiSignature.
+    The extrinsic is assembled from the signer, the signature, the extra parameters and the call data of the exported call."
+    )]
+    signature: Option<Bytes>,
+    #[clap(
+        long,
+        requires = "signature",
+        help = "Specifies the account that signed the payload of the exported call."
+    )]
+    signer: Option<AccountId>,
+    #[clap(
+        long,
+        value_parser = parse_extrinsic,
+        requires = "signature",
+        help = "Specifies the signed extra parameters of the exported call, as hex."
+    )]
+    extra: Option<Bytes>,
+    #[clap(
+        long,
+        value_parser = parse_extrinsic,
+        requires = "signature",
+        help = "Specifies the call data of the exported call, as hex."
+    )]
+    call_data: Option<Bytes>,
+    #[clap(
+        value_parser,
+        help = "Specifies the path to a contract .contract bundle or .json metadata file, used to decode the contract events."
+    )]
+    file: Option<PathBuf>,
+    #[clap(flatten)]
+    network_opts: CLINetworkOpts,
+    #[clap(
+        long,
+        default_value = "finalized",
+        help = "Specifies whether to wait for the extrinsic to be included in a block (`in-block`), or for the block to be finalized (`finalized`)."
+    )]
+    wait_for: WaitFor,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
+}
+
+/// Parse a hex encoded extrinsic.
+fn parse_extrinsic(input: &str) -> Result<Bytes> {
+    Ok(decode_hex(input)?.into())
+}
+
+impl PolkadotSubmitCommand {
+    /// Handles the submission of a signed extrinsic to the Polkadot network.
+    ///
+    /// The extrinsic is broadcast as is, or assembled from the signature of a call exported with
+    /// `--export-call-data` first, and its events are decoded once it is included in a block
+    /// or finalized. The events emitted by the contract are decoded with the metadata of the
+    /// contract file, if given.
+    pub async fn handle(&self) -> Result<()> {
+        // Make sure the command is run in the correct directory
+        // Fails if the command is run in a Solang Solana project directory
+        let target_match = check_target_match("polkadot", None)
+            .map_err(|e| anyhow!("Failed to check current directory: {}", e))?;
+        if !target_match {
+            exit(ErrorKind::Usage.exit_code());
+        }
+
+        let transcoder = match &self.file {
+            Some(file) => Some(load_transcoder(file)?),
+            None => None,
+        };
+        let extrinsic = match (
+            &self.extrinsic,
+            &self.signer,
+            &self.signature,
+            &self.extra,
+            &self.call_data,
+        ) {
+            (Some(extrinsic), ..) => extrinsic.to_vec(),
+            (None, Some(signer), Some(signature), Some(extra), Some(call_data)) => {
+                assemble_signed_extrinsic(signer, signature, extra, call_data)?
+            }
+            _ => return Err(anyhow!("Missing the extrinsic or the signature to submit")),
+        };
+        let client = self.network_opts.connect().await?;
+        let events = submit_signed_extrinsic(&client, extrinsic, self.wait_for)
+            .await
+            .map_err(|err| anyhow!("Error submitting the extrinsic: {}", err))?;
+        let mut block = extrinsic_block(&client, &events).await?;
+        let explorer_url = self.network_opts.explorer_url(&block);
+        block.explorer_url = Some(explorer_url.clone());
+        if self.output.is_structured() {
+            let json_object = json!({
+                "events": typed_events(&events, transcoder.as_ref(), &client.metadata())?,
+                "block": block,
+            });
+            self.output.print(&json_object)?;
+        } else {
+            let display_events =
+                DisplayEvents::from_events(&events, transcoder.as_ref(), &client.metadata())?;
+            let token = self.network_opts.token().await?;
+            print_text!(display_events.display_events(Verbosity::Default, &token.token_metadata())?);
+            print_key_value!("Block", block);
+            print_key_value!("Explorer", explorer_url);
+        }
+        Ok(())
+    }
+}
@@ -2,17 +2,19 @@
 
 use {
     anyhow::{anyhow, Result},
-    colored::Colorize,
-    serde_json::{from_str, json, to_string_pretty, Value},
+    serde_json::json,
     std::fmt::Debug,
     std::process::exit,
 };
 
 use {
-    super::CLIExtrinsicOpts,
-    aqd_utils::{check_target_match, print_key_value, print_title, print_warning},
+    super::{print_cost_estimate, CLIExtrinsicOpts},
+    aqd_polkadot_contracts::PolkadotUpload,
+    aqd_utils::{
+        check_target_match, is_quiet, print_key_value, print_text, print_title, print_warning,
+        record_history, solang_compile, ErrorKind, HistoryEntry, OutputFormat,
+    },
     contract_build::Verbosity,
-    contract_extrinsics::{ExtrinsicOptsBuilder, UploadCommandBuilder},
 };
 
 #[derive(Debug, clap::Args)]
@@ -20,93 +22,134 @@ use {
 pub struct PolkadotUploadCommand {
     #[clap(flatten)]
     extrinsic_cli_opts: CLIExtrinsicOpts,
+    #[clap(
+        long,
+        conflicts_with = "execute",
+        help = "Specifies whether to only estimate the fee, weight and storage deposit of the extrinsic with a dry run, without submitting it."
+    )]
+    estimate_only: bool,
+    #[clap(
+        long,
+        help = "Specifies whether to succeed without uploading if the code is already stored on chain, reporting its code hash."
+    )]
+    skip_if_uploaded: bool,
+    #[clap(
+        long,
+        help = "Specifies whether to compile the Solang project with `solang compile` before uploading, so that the latest artifact is uploaded."
+    )]
+    build: bool,
 }
 
 impl PolkadotUploadCommand {
-    /// Returns whether to export the call output in JSON format.
-    pub fn output_json(&self) -> bool {
-        self.extrinsic_cli_opts.output_json
+    /// Returns the format of the output.
+    pub fn output(&self) -> OutputFormat {
+        self.extrinsic_cli_opts.output
     }
 
     /// Handles the Polkadot upload command, allowing users to upload contracts to the Polkadot network.
     ///
     /// This function performs the following steps:
     ///
-    /// 1. Verifies that the command is being run in the correct directory, failing if it's in a Solang Solana project directory.
+    /// 1. Verifies that the command is being run in the correct directory, failing if it's in a Solang Solana project directory,
+    ///    and compiles the project if `--build` is set.
     /// 2. Builds command-line options for executing the upload.
     /// 3. Creates and executes the upload command.
     /// 4. Processes the result based on whether execution is required or not.
-    /// 5. Prints the outcome, including any generated events or output JSON if requested.
+    /// 5. Prints the outcome, including any generated events, in the requested output format.
     pub async fn handle(&self) -> Result<()> {
         // Make sure the command is run in the correct directory
         // Fails if the command is run in a Solang Solana project directory
         let target_match = check_target_match("polkadot", None)
             .map_err(|e| anyhow!("Failed to check current directory: {}", e))?;
         if !target_match {
-            exit(1);
+            exit(ErrorKind::Usage.exit_code());
+        }
+
+        // Compile the project first, if requested
+        if self.build {
+            solang_compile()?;
         }
 
         // Initialize the extrinsic options
-        let cli_options = ExtrinsicOptsBuilder::default()
-            .file(Some(self.extrinsic_cli_opts.file.clone()))
-            .url(self.extrinsic_cli_opts.url().clone())
-            .suri(self.extrinsic_cli_opts.suri.clone())
-            .storage_deposit_limit(self.extrinsic_cli_opts.storage_deposit_limit.clone())
-            .done();
-        let exec = UploadCommandBuilder::default()
-            .extrinsic_opts(cli_options)
-            .done()
-            .await?;
+        let token = self.extrinsic_cli_opts.token().await?;
+        let upload = PolkadotUpload::new(&self.extrinsic_cli_opts.options(&token).await?).await?;
 
         // Obtain the code hash
         // This is used to check if the contract has already been uploaded
-        let code_hash = exec.code().code_hash();
+        let code_hash = upload.code_hash();
 
-        if !self.extrinsic_cli_opts.execute {
-            let result = exec
-                .upload_code_rpc()
-                .await?
-                .map_err(|err| anyhow!("Error uploading the code: {:?}", err))?;
-            if self.output_json() {
+        // Uploading code that is already stored fails, so it is detected up front for idempotent
+        // uploads
+        if self.skip_if_uploaded && upload.is_uploaded().await? {
+            let code_hash = format!("0x{}", hex::encode(code_hash));
+            if is_quiet() {
+                print_text!(code_hash);
+            } else if self.output().is_structured() {
+                let json_object = json!({
+                    "result": "AlreadyUploaded",
+                    "code_hash": code_hash,
+                });
+                self.output().print(&json_object)?;
+            } else {
+                print_title!("Upload Result");
+                print_key_value!("Status", "Already uploaded");
+                print_key_value!("Code hash", code_hash);
+            }
+            return Ok(());
+        }
+
+        if self.estimate_only {
+            let estimate = upload.estimate().await?;
+            print_cost_estimate(&token, &estimate, self.output())?;
+        } else if !self.extrinsic_cli_opts.execute {
+            let result = upload.dry_run().await?;
+            if self.output().is_structured() {
                 let json_object = json!({
                     "result": "Success",
                     "code_hash": result.code_hash,
                     "deposit": result.deposit
                 });
-                println!("{}", to_string_pretty(&json_object)?);
+                self.output().print(&json_object)?;
             } else {
                 print_title!("Upload Dry Run Result");
                 print_key_value!("Status", "Success");
                 print_key_value!("Code hash", format!("{:?}", result.code_hash));
-                print_key_value!("Deposit", format!("{:?}", result.deposit));
+                print_key_value!("Deposit", token.format(result.deposit));
                 print_warning!("Execution of your upload call has NOT been completed. To submit the transaction and execute the call on chain, please include -x/--execute flag.");
             }
         } else {
-            let result = exec
-                .upload_code()
-                .await
-                .map_err(|err| anyhow!("Error uploading the code: {}", err.to_string()))?;
-            let events = result.display_events;
-            let events = if self.output_json() {
-                events.to_json()?
-            } else {
-                events.display_events(Verbosity::Default, exec.token_metadata())?
-            };
-            let code_stored = result.code_stored.ok_or_else(|| {
-                anyhow!(
+            let result = upload.submit().await;
+            let entry = HistoryEntry::new("polkadot", "upload");
+            record_history(match &result {
+                Ok(result) => {
+                    let entry = entry.hash(format!("{:?}", result.block.extrinsic_hash));
+                    match &result.code_hash {
+                        Some(code_hash) => entry.contract(format!("{:?}", code_hash)),
+                        None => entry,
+                    }
+                }
+                Err(e) => entry.failed(e),
+            });
+            let mut result = result?;
+            let explorer_url = self.extrinsic_cli_opts.explorer_url(&result.block);
+            result.block.explorer_url = Some(explorer_url.clone());
+            let Some(code_stored) = result.code_hash else {
+                return Err(anyhow!(
                     "This contract has already been uploaded. Code hash: 0x{}",
                     hex::encode(code_hash)
-                )
-            })?;
-            if self.output_json() {
-                let json_object = json!({
-                    "events": from_str::<Value>(&events)?,
-                    "code_hash": code_stored.code_hash,
-                });
-                println!("{}", to_string_pretty(&json_object)?);
+                ));
+            };
+            if is_quiet() {
+                print_text!(format!("{:?}", code_stored));
+            } else if self.output().is_structured() {
+                self.output().print(&result)?;
             } else {
-                println!("{}", events);
-                print_key_value!("Code hash", format!("{:?}", code_stored.code_hash));
+                print_text!(result
+                    .display_events
+                    .display_events(Verbosity::Default, &upload.token_metadata())?);
+                print_key_value!("Code hash", format!("{:?}", code_stored));
+                print_key_value!("Block", result.block);
+                print_key_value!("Explorer", explorer_url);
             }
         }
         Ok(())
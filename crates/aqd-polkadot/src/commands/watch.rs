@@ -0,0 +1,153 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    serde_json::json,
+    std::{fmt::Debug, path::PathBuf, process::exit},
+};
+
+use {
+    super::CLINetworkOpts,
+    aqd_polkadot_contracts::load_transcoder,
+    aqd_utils::{
+        check_target_match, print_key_value, print_text, print_title, ErrorKind, OutputFormat,
+        RecordStream,
+    },
+    contract_extrinsics::DefaultConfig,
+    contract_transcode::ContractMessageTranscoder,
+    subxt::{events::Events, ext::codec::Decode, Config},
+};
+
+#[derive(Debug, clap::Args)]
+#[clap(
+    name = "watch",
+    about = "Stream the events emitted by a contract on Polkadot"
+)]
+pub struct PolkadotWatchCommand {
+    #[clap(
+        value_parser,
+        help = "Specifies the path to a contract .contract bundle or .json metadata file, used to decode the events."
+    )]
+    file: PathBuf,
+    #[clap(
+        name = "contract",
+        long,
+        help = "Specifies the address of the contract whose events are streamed."
+    )]
+    contract: <DefaultConfig as Config>::AccountId,
+    #[clap(flatten)]
+    network_opts: CLINetworkOpts,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
+}
+
+impl PolkadotWatchCommand {
+    /// Handles the streaming of the events of a contract on the Polkadot network.
+    ///
+    /// The finalized blocks are subscribed to, and the `Contracts::ContractEmitted` events of the
+    /// contract are decoded with the contract metadata and printed as they are finalized, until the
+    /// command is interrupted.
+    pub async fn handle(&self) -> Result<()> {
+        // Make sure the command is run in the correct directory
+        // Fails if the command is run in a Solang Solana project directory
+        let target_match = check_target_match("polkadot", None)
+            .map_err(|e| anyhow!("Failed to check current directory: {}", e))?;
+        if !target_match {
+            exit(ErrorKind::Usage.exit_code());
+        }
+
+        let transcoder = load_transcoder(&self.file)?;
+
+        let client = self.network_opts.connect().await?;
+
+        if !self.output.is_structured() {
+            print_text!(format!(
+                "Watching the events of contract {} (press Ctrl+C to stop)",
+                self.contract
+            ));
+        }
+
+        let mut stream = self.output.stream();
+        let mut blocks = client.blocks().subscribe_finalized().await?;
+        while let Some(block) = blocks.next().await {
+            let block = block?;
+            let block_number = block.number();
+            let block_hash = block.hash();
+            let events = block.events().await?;
+            print_contract_events(
+                block_number.into(),
+                block_hash,
+                &events,
+                &self.contract,
+                &transcoder,
+                &mut stream,
+            )?;
+        }
+        Err(anyhow!("The block subscription was closed by the node"))
+    }
+}
+
+/// Print the `Contracts::ContractEmitted` events of a contract in the events of a block, decoded
+/// with the contract metadata.
+///
+/// With a structured output format, each event is printed as a record of the stream, e.g. a line
+/// of JSON, or a row of a table.
+pub(crate) fn print_contract_events(
+    block_number: u64,
+    block_hash: <DefaultConfig as Config>::Hash,
+    events: &Events<DefaultConfig>,
+    contract: &<DefaultConfig as Config>::AccountId,
+    transcoder: &ContractMessageTranscoder,
+    stream: &mut RecordStream,
+) -> Result<()> {
+    for event in events.iter() {
+        let event = event?;
+        if event.pallet_name() != "Contracts" || event.variant_name() != "ContractEmitted" {
+            continue;
+        }
+
+        // ContractEmitted { contract: AccountId, data: Vec<u8> }
+        let (emitter, data) =
+            <(<DefaultConfig as Config>::AccountId, Vec<u8>)>::decode(&mut event.field_bytes())
+                .map_err(|e| anyhow!("Failed to decode ContractEmitted event: {}", e))?;
+        if &emitter != contract {
+            continue;
+        }
+
+        // The events of ink! are identified by their first topic
+        let decoded = event
+            .topics()
+            .first()
+            .ok_or_else(|| anyhow!("The event has no signature topic"))
+            .and_then(|signature_topic| {
+                transcoder.decode_contract_event(signature_topic, &mut &data[..])
+            });
+        if stream.output().is_structured() {
+            let event = json!({
+                "block_number": block_number,
+                "block_hash": format!("{block_hash:?}"),
+                "contract": emitter.to_string(),
+                "data": format!("0x{}", hex::encode(&data)),
+                "decoded": decoded.as_ref().ok(),
+                "decode_error": decoded.as_ref().err().map(|e| e.to_string()),
+            });
+            stream.print_record(&event)?;
+        } else {
+            print_title!(format!("Event in block {}", block_number));
+            print_key_value!("Block hash", format!("{block_hash:?}"));
+            match &decoded {
+                Ok(decoded) => print_key_value!("Event", decoded),
+                Err(e) => {
+                    print_key_value!("Data", format!("0x{}", hex::encode(&data)));
+                    print_key_value!("Decode error", e);
+                }
+            }
+        }
+    }
+    Ok(())
+}
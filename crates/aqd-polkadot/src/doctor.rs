@@ -0,0 +1,125 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::commands::{CLINetworkOpts, CLISignerOpts},
+    anyhow::Result,
+    aqd_polkadot_contracts::{check_node_compatibility, connect, pair_signer},
+    aqd_utils::{AqdConfig, CheckStatus, Diagnostic},
+    clap::Parser,
+    std::{env, path::Path, time::Instant},
+    subxt::utils::AccountId32,
+    url::Url,
+};
+
+/// The section of the Polkadot checks.
+const SECTION: &str = "polkadot";
+
+/// The options of the Polkadot commands, read from their environment variables only.
+#[derive(Parser)]
+struct DoctorOpts {
+    #[clap(flatten)]
+    network_opts: CLINetworkOpts,
+    #[clap(flatten)]
+    signer_opts: CLISignerOpts,
+}
+
+/// The checks of the Polkadot environment: the node of the commands and the nodes of the networks
+/// of the aqd configuration file can be reached and run contracts, and the secret key URI of the
+/// environment, if any, is valid.
+pub async fn diagnostics() -> Vec<Diagnostic> {
+    let opts = match DoctorOpts::try_parse_from(["aqd"]) {
+        Ok(opts) => opts,
+        Err(e) => {
+            return vec![Diagnostic::new(
+                SECTION,
+                "environment",
+                CheckStatus::Error,
+                e.to_string().trim(),
+            )]
+        }
+    };
+
+    let mut diagnostics = vec![];
+    // The default local node is only expected to run while developing
+    let configured =
+        env::var_os("AQD_POLKADOT_URL").is_some() || env::var_os("AQD_POLKADOT_NETWORK").is_some();
+    let mut node = match opts.network_opts.urls() {
+        Ok(urls) => node_diagnostic("node", &urls).await,
+        Err(e) => Diagnostic::new(SECTION, "node", CheckStatus::Error, format!("{:#}", e)),
+    };
+    if !configured && node.status == CheckStatus::Error {
+        node.status = CheckStatus::Warning;
+    }
+    diagnostics.push(node);
+
+    // An invalid configuration file is reported by the general checks
+    if let Ok(config) = AqdConfig::load() {
+        for network in &config.networks {
+            let check = format!("network {}", network.name);
+            diagnostics.push(match Url::parse(&network.url) {
+                Ok(url) => node_diagnostic(&check, &[url]).await,
+                Err(e) => Diagnostic::new(
+                    SECTION,
+                    &check,
+                    CheckStatus::Error,
+                    format!("Invalid URL {}: {}", network.url, e),
+                ),
+            });
+        }
+    }
+
+    let keystore = env::var("AQD_POLKADOT_KEYSTORE").ok();
+    let suri = env::var_os("AQD_POLKADOT_SURI").is_some()
+        || env::var_os("AQD_POLKADOT_SURI_FILE").is_some();
+    diagnostics.push(match (suri, keystore) {
+        (true, _) => Diagnostic::from_result(
+            SECTION,
+            "signer",
+            opts.signer_opts
+                .suri()
+                .and_then(|suri| pair_signer(&suri))
+                .map(|signer| AccountId32::from(signer.public_key().0).to_string()),
+        ),
+        (false, Some(keystore)) if Path::new(&keystore).is_file() => Diagnostic::new(
+            SECTION,
+            "signer",
+            CheckStatus::Info,
+            format!(
+                "Keystore {}: its password is prompted by the commands",
+                keystore
+            ),
+        ),
+        (false, Some(keystore)) => Diagnostic::new(
+            SECTION,
+            "signer",
+            CheckStatus::Error,
+            format!("Keystore {} not found", keystore),
+        ),
+        (false, None) => Diagnostic::new(
+            SECTION,
+            "signer",
+            CheckStatus::Info,
+            "No secret key URI in the environment: use --suri, --suri-env, --suri-file or --keystore",
+        ),
+    });
+    diagnostics
+}
+
+/// Connect to the first node of `urls` that can be reached, and check that it runs contracts.
+async fn node_diagnostic(check: &str, urls: &[Url]) -> Diagnostic {
+    let start = Instant::now();
+    let result: Result<String> = async {
+        let (url, client) = connect(urls).await?;
+        let elapsed = start.elapsed();
+        let pallet = check_node_compatibility(&client)?;
+        Ok(format!(
+            "{} (runtime spec version {}, {}, {} ms)",
+            url,
+            client.runtime_version().spec_version,
+            pallet,
+            elapsed.as_millis()
+        ))
+    }
+    .await;
+    Diagnostic::from_result(SECTION, check, result)
+}
@@ -1,10 +1,19 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod commands;
+mod doctor;
 mod polkadot_action;
+mod wallet;
 
 pub use commands::{
-    PolkadotCallCommand, PolkadotInstantiateCommand, PolkadotRemoveCommand, PolkadotUploadCommand,
+    PolkadotBalanceCommand, PolkadotBatchCommand, PolkadotBenchCommand, PolkadotCallCommand,
+    PolkadotEventsCommand, PolkadotInfoCommand, PolkadotInstantiateCommand,
+    PolkadotKeystoreCommand, PolkadotMultisigApproveCommand, PolkadotMultisigExecuteCommand,
+    PolkadotNetworksCommand, PolkadotPublicKeyCommand, PolkadotQueryCommand, PolkadotRemoveCommand,
+    PolkadotShowCommand, PolkadotSs58Command, PolkadotStorageCommand, PolkadotSubmitCommand,
+    PolkadotUploadCommand, PolkadotWatchCommand,
 };
 
+pub use doctor::diagnostics;
 pub use polkadot_action::PolkadotAction;
+pub use wallet::{default_wallet, parse_address, wallet_address, wallet_balance};
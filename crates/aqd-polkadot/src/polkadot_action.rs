@@ -2,8 +2,11 @@
 
 use {
     crate::{
-        PolkadotCallCommand, PolkadotInstantiateCommand, PolkadotRemoveCommand,
-        PolkadotUploadCommand,
+        PolkadotBalanceCommand, PolkadotBatchCommand, PolkadotBenchCommand, PolkadotCallCommand,
+        PolkadotEventsCommand, PolkadotInfoCommand, PolkadotInstantiateCommand,
+        PolkadotKeystoreCommand, PolkadotMultisigApproveCommand, PolkadotMultisigExecuteCommand,
+        PolkadotNetworksCommand, PolkadotQueryCommand, PolkadotRemoveCommand, PolkadotShowCommand,
+        PolkadotStorageCommand, PolkadotSubmitCommand, PolkadotUploadCommand, PolkadotWatchCommand,
     },
     clap::Subcommand,
 };
@@ -14,5 +17,19 @@ pub enum PolkadotAction {
     Upload(PolkadotUploadCommand),
     Instantiate(PolkadotInstantiateCommand),
     Call(PolkadotCallCommand),
+    Bench(PolkadotBenchCommand),
+    Query(PolkadotQueryCommand),
+    Batch(PolkadotBatchCommand),
+    Submit(PolkadotSubmitCommand),
+    MultisigApprove(PolkadotMultisigApproveCommand),
+    MultisigExecute(PolkadotMultisigExecuteCommand),
     Remove(PolkadotRemoveCommand),
+    Storage(PolkadotStorageCommand),
+    Info(PolkadotInfoCommand),
+    Balance(PolkadotBalanceCommand),
+    Show(PolkadotShowCommand),
+    Watch(PolkadotWatchCommand),
+    Events(PolkadotEventsCommand),
+    Networks(PolkadotNetworksCommand),
+    Keystore(PolkadotKeystoreCommand),
 }
@@ -0,0 +1,105 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::commands::{read_keystore, CLINetworkOpts, CLISignerOpts},
+    anyhow::{anyhow, Result},
+    aqd_polkadot_contracts::{
+        free_balance, pair_signer, parse_public_key, Ss58Address, SUBSTRATE_SS58_PREFIX,
+    },
+    aqd_utils::{is_keystore, WalletKey},
+    clap::Parser,
+    serde_json::Value,
+    std::{env, fs, path::Path},
+    subxt::utils::AccountId32,
+};
+
+/// The chain of the Polkadot wallets.
+const CHAIN: &str = "polkadot";
+
+/// The options of the Polkadot commands, read from their environment variables only.
+#[derive(Parser)]
+struct WalletOpts {
+    #[clap(flatten)]
+    network_opts: CLINetworkOpts,
+    #[clap(flatten)]
+    signer_opts: CLISignerOpts,
+}
+
+impl WalletOpts {
+    fn from_env() -> Result<Self> {
+        WalletOpts::try_parse_from(["aqd"]).map_err(|e| anyhow!("{}", e.to_string().trim()))
+    }
+}
+
+/// The key the Polkadot commands sign with by default, if any: the secret key URI of the
+/// `AQD_POLKADOT_SURI` environment variable, of the `AQD_POLKADOT_SURI_FILE` file, or of the
+/// `AQD_POLKADOT_KEYSTORE` keystore.
+///
+/// A keystore is not unlocked, so that listing the wallets never prompts for a password: its
+/// address is only known once it is labeled with `aqd wallet label`.
+pub fn default_wallet() -> Option<WalletKey> {
+    let source = if env::var_os("AQD_POLKADOT_SURI").is_some() {
+        "AQD_POLKADOT_SURI".to_string()
+    } else if let Ok(path) = env::var("AQD_POLKADOT_SURI_FILE") {
+        path
+    } else if let Ok(path) = env::var("AQD_POLKADOT_KEYSTORE") {
+        return Some(WalletKey {
+            chain: CHAIN.to_string(),
+            source: Some(path),
+            default: true,
+            ..Default::default()
+        });
+    } else {
+        return None;
+    };
+    let address = WalletOpts::from_env()
+        .and_then(|opts| opts.signer_opts.suri())
+        .and_then(|suri| pair_signer(&suri))
+        .map(|signer| AccountId32::from(signer.public_key().0).to_string());
+    let (address, error) = match address {
+        Ok(address) => (Some(address), None),
+        Err(e) => (None, Some(format!("{:#}", e))),
+    };
+    Some(WalletKey {
+        chain: CHAIN.to_string(),
+        address,
+        source: Some(source),
+        default: true,
+        error,
+        ..Default::default()
+    })
+}
+
+/// The address of the secret key URI of a file, or of a keystore, whose password is prompted.
+pub fn wallet_address(path: &str) -> Result<String> {
+    let content =
+        fs::read_to_string(path).map_err(|e| anyhow!("Failed to read {}: {}", path, e))?;
+    let encrypted = serde_json::from_str::<Value>(&content)
+        .map(|value| is_keystore(&value))
+        .unwrap_or(false);
+    let suri = match encrypted {
+        true => read_keystore(Path::new(path))?,
+        false => content.trim().to_string(),
+    };
+    let signer = pair_signer(&suri)?;
+    Ok(AccountId32::from(signer.public_key().0).to_string())
+}
+
+/// Validate a Polkadot address, an SS58 address of any network or 32 hex encoded bytes, and return
+/// it as an SS58 address: a public key is encoded with the generic Substrate prefix.
+pub fn parse_address(address: &str) -> Result<String> {
+    Ok(match parse_public_key(address)? {
+        (_, Some(_)) => address.trim().to_string(),
+        (public_key, None) => Ss58Address::new(public_key, SUBSTRATE_SS58_PREFIX).address,
+    })
+}
+
+/// The free balance of an address on the node of the Polkadot commands: the `AQD_POLKADOT_URL`
+/// or `AQD_POLKADOT_NETWORK` environment variables, or the default local node.
+pub async fn wallet_balance(address: &str) -> Result<String> {
+    let (public_key, _) = parse_public_key(address)?;
+    let network_opts = WalletOpts::from_env()?.network_opts;
+    let client = network_opts.connect().await?;
+    let free = free_balance(&client, public_key).await?;
+    Ok(network_opts.token().await?.format(free))
+}
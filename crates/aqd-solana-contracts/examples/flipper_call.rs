@@ -3,6 +3,7 @@
 use {
     anyhow::Result,
     aqd_solana_contracts::{deploy_program, print_transaction_information, SolanaTransaction},
+    aqd_utils::OutputFormat,
     solana_clap_v3_utils::input_validators::normalize_to_url_if_moniker,
     solana_cli_config::{Config, CONFIG_FILE},
 };
@@ -31,9 +32,9 @@ fn main() -> Result<()> {
     let rpc_url = normalize_to_url_if_moniker(&cli_config.json_rpc_url);
     let keypair = cli_config.keypair_path.to_string();
 
-    // Define the path to the IDL JSON file, the program ID, and whether to output JSON.
+    // Define the path to the IDL JSON file, the program ID, and the output format.
     let idl_json = "crates/aqd-solana-contracts/examples/contracts/flipper.json";
-    let output_json = false;
+    let output = OutputFormat::Human;
 
     // The compiled program is assumed to be in the same directory as the IDL JSON file.
     let program_location = "crates/aqd-solana-contracts/examples/contracts/flipper.so".to_string();
@@ -100,7 +101,8 @@ fn main() -> Result<()> {
         call_cmd.instruction(),
         call_cmd.idl().types.as_slice(),
         call_cmd.new_accounts(),
-        output_json,
+        &[],
+        output,
     ) {
         Ok(_) => (),
         Err(err) => eprintln!("{}", err),
@@ -159,7 +161,8 @@ fn main() -> Result<()> {
         call_cmd.instruction(),
         call_cmd.idl().types.as_slice(),
         call_cmd.new_accounts(),
-        output_json,
+        &[],
+        output,
     ) {
         Ok(_) => (),
         Err(err) => eprintln!("{}", err),
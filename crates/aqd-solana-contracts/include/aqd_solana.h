@@ -0,0 +1,48 @@
+/* SPDX-License-Identifier: Apache-2.0 */
+
+/*
+ * C bindings of aqd-solana-contracts, built with
+ * `cargo rustc -p aqd-solana-contracts --release --features cdylib --crate-type cdylib`.
+ *
+ * Each function takes a request as a JSON string, and returns a JSON string:
+ * {"ok": <RESULT>} on success, or {"error": "<MESSAGE>"}. The returned strings must be freed with
+ * aqd_free_string.
+ */
+
+#ifndef AQD_SOLANA_H
+#define AQD_SOLANA_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/*
+ * Encode the data of an instruction.
+ * Request: {"idl": <IDL>, "instruction": "<NAME>", "args": ["<ARG>", ...]}
+ * Result: {"data": "<HEX>"}
+ */
+char *aqd_encode_instruction_data(const char *request);
+
+/*
+ * Decode the return data of an instruction.
+ * Request: {"idl": <IDL>, "instruction": "<NAME>", "data": "<HEX>"}
+ * Result: the decoded value
+ */
+char *aqd_decode_return_data(const char *request);
+
+/*
+ * Submit a program call, and wait for its confirmation.
+ * Request: {"rpc_url": "<URL>", "idl": <IDL>, "program_id": "<ID>", "instruction": "<NAME>",
+ *           "args": ["<ARG>", ...], "accounts": ["<ACCOUNT>", ...], "payer": "<KEYPAIR>"}
+ * Result: {"signature": "<SIGNATURE>", "new_accounts": [{"pubkey": "<PUBKEY>", "file_name": "<PATH>"}]}
+ */
+char *aqd_submit_transaction(const char *request);
+
+/* Free a string returned by the other functions. */
+void aqd_free_string(char *response);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* AQD_SOLANA_H */
@@ -0,0 +1,64 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    solana_client::rpc_client::RpcClient,
+    solana_sdk::{
+        bpf_loader_upgradeable::UpgradeableLoaderState, native_token::lamports_to_sol,
+        pubkey::Pubkey,
+    },
+};
+
+/// The number of program bytes written per transaction when deploying a program.
+const DEPLOY_WRITE_CHUNK_SIZE: usize = 1000;
+
+/// The fee of a transaction with a single signature, used to estimate the fees of a deployment.
+const LAMPORTS_PER_SIGNATURE: u64 = 5000;
+
+/// Check that the payer can afford an operation, and fail early with a clear error otherwise.
+///
+/// # Errors
+///
+/// This function returns an error if the balance of the payer cannot be fetched, or if it is
+/// lower than `required_lamports`.
+pub fn check_payer_balance(
+    rpc_client: &RpcClient,
+    payer: &Pubkey,
+    required_lamports: u64,
+) -> Result<()> {
+    let balance = rpc_client
+        .get_balance(payer)
+        .map_err(|e| anyhow!("Error fetching the balance of payer {}: {}", payer, e))?;
+    if balance < required_lamports {
+        return Err(anyhow!(
+            "Payer {} has {} SOL but needs ~{} SOL",
+            payer,
+            lamports_to_sol(balance),
+            lamports_to_sol(required_lamports)
+        ));
+    }
+    Ok(())
+}
+
+/// Estimate the lamports needed to deploy an upgradeable program of `program_len` bytes.
+///
+/// This is the rent of the buffer, program data (sized for twice the program length, like the
+/// Solana CLI) and program accounts, plus the fees of the transactions writing the program. The
+/// buffer rent is refunded once the program is deployed, but must be available during the
+/// deployment.
+pub fn estimate_deploy_cost(rpc_client: &RpcClient, program_len: usize) -> Result<u64> {
+    let rent = |data_len: usize| {
+        rpc_client
+            .get_minimum_balance_for_rent_exemption(data_len)
+            .map_err(|e| anyhow!("Error fetching the rent exemption minimum: {}", e))
+    };
+    let buffer_rent = rent(UpgradeableLoaderState::size_of_buffer(program_len))?;
+    let programdata_rent = rent(UpgradeableLoaderState::size_of_programdata(program_len * 2))?;
+    let program_rent = rent(UpgradeableLoaderState::size_of_program())?;
+
+    // One transaction per chunk, plus the buffer creation and the final deploy transactions
+    let transactions = program_len.div_ceil(DEPLOY_WRITE_CHUNK_SIZE) as u64 + 2;
+    let fees = transactions * LAMPORTS_PER_SIGNATURE * 2;
+
+    Ok(buffer_rent + programdata_rent + program_rent + fees)
+}
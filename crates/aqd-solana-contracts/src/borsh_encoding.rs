@@ -1,27 +1,45 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use anchor_syn::idl::{IdlType, IdlTypeDefinition, IdlTypeDefinitionTy};
+use anyhow::{anyhow, bail, Result};
 use base58::ToBase58;
 use byte_slice_cast::AsByteSlice;
 use convert_case::{Boundary, Case, Casing};
 use num_bigint::{BigInt, Sign};
 use num_traits::ToPrimitive;
-use serde::Deserialize;
+use serde::{ser::SerializeMap, Deserialize, Serialize, Serializer};
 use sha2::{Digest, Sha256};
 use std::cmp::Ordering;
 
+/// The namespace of the discriminators of the instructions.
+pub const GLOBAL_NAMESPACE: &str = "global";
+
+/// The namespace of the discriminators of the account types.
+pub const ACCOUNT_NAMESPACE: &str = "account";
+
+/// The namespace of the discriminators of the events.
+pub const EVENT_NAMESPACE: &str = "event";
+
 /// Generate discriminator based on the name of the function. This is the 8 byte
 /// value anchor uses to dispatch function calls on. This should match
 /// anchor's behaviour - we need to match the discriminator exactly
-pub fn discriminator(namespace: &'static str, name: &str) -> Vec<u8> {
+///
+/// The discriminator is the first 8 bytes of `sha256("<namespace>:<name>")`. As in anchor, the
+/// names of the instructions of the `global` namespace are converted to snake case, while the
+/// names of the other namespaces, e.g. `account` and `event`, are used as is.
+pub fn discriminator(namespace: &str, name: &str) -> Vec<u8> {
     let mut hasher = Sha256::new();
-    // must match snake-case npm library, see
-    // https://github.com/coral-xyz/anchor/blob/master/ts/packages/anchor/src/coder/borsh/instruction.ts#L389
-    let normalized = name
-        .from_case(Case::Camel)
-        .without_boundaries(&[Boundary::LowerDigit])
-        .to_case(Case::Snake);
-    hasher.update(format!("{namespace}:{normalized}"));
+    if namespace == GLOBAL_NAMESPACE {
+        // must match snake-case npm library, see
+        // https://github.com/coral-xyz/anchor/blob/master/ts/packages/anchor/src/coder/borsh/instruction.ts#L389
+        let normalized = name
+            .from_case(Case::Camel)
+            .without_boundaries(&[Boundary::LowerDigit])
+            .to_case(Case::Snake);
+        hasher.update(format!("{namespace}:{normalized}"));
+    } else {
+        hasher.update(format!("{namespace}:{name}"));
+    }
     hasher.finalize()[..8].to_vec()
 }
 
@@ -312,17 +330,32 @@ pub fn encode_arguments(args: &[BorshToken]) -> Vec<u8> {
 }
 
 /// Decode a parameter at a given offset
+///
+/// Panics if the data is too short for the type, or if the data is not valid for the type. See
+/// [`try_decode_at_offset`] to get an error instead, e.g. for the data of accounts.
+#[deprecated(note = "panics on malformed data, use `try_decode_at_offset` instead")]
 pub fn decode_at_offset(
     data: &[u8],
     offset: &mut usize,
     ty: &IdlType,
     custom_types: &[IdlTypeDefinition],
 ) -> BorshToken {
-    match ty {
+    try_decode_at_offset(data, offset, ty, custom_types).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Decode a parameter at a given offset
+///
+/// Returns an error if the data is too short for the type, or if the data is not valid for the type.
+pub fn try_decode_at_offset(
+    data: &[u8],
+    offset: &mut usize,
+    ty: &IdlType,
+    custom_types: &[IdlTypeDefinition],
+) -> Result<BorshToken> {
+    let token = match ty {
         IdlType::PublicKey => {
-            let read = &data[*offset..(*offset + 32)];
-            (*offset) += 32;
-            BorshToken::Address(<[u8; 32]>::try_from(read).unwrap())
+            let read = read_bytes(data, offset, 32)?;
+            BorshToken::Address(<[u8; 32]>::try_from(read)?)
         }
 
         IdlType::U8
@@ -333,8 +366,7 @@ pub fn decode_at_offset(
         | IdlType::U256 => {
             let decoding_width = integer_byte_width(ty);
             let bigint =
-                BigInt::from_bytes_le(Sign::Plus, &data[*offset..(*offset + decoding_width)]);
-            (*offset) += decoding_width;
+                BigInt::from_bytes_le(Sign::Plus, read_bytes(data, offset, decoding_width)?);
             BorshToken::Uint {
                 width: (decoding_width * 8) as u16,
                 value: bigint,
@@ -348,8 +380,7 @@ pub fn decode_at_offset(
         | IdlType::I128
         | IdlType::I256 => {
             let decoding_width = integer_byte_width(ty);
-            let bigint = BigInt::from_signed_bytes_le(&data[*offset..(*offset + decoding_width)]);
-            (*offset) += decoding_width;
+            let bigint = BigInt::from_signed_bytes_le(read_bytes(data, offset, decoding_width)?);
             BorshToken::Int {
                 width: (decoding_width * 8) as u16,
                 value: bigint,
@@ -357,34 +388,27 @@ pub fn decode_at_offset(
         }
 
         IdlType::Bool => {
-            let val = data[*offset] == 1;
-            (*offset) += 1;
+            let val = read_bytes(data, offset, 1)?[0] == 1;
             BorshToken::Bool(val)
         }
         IdlType::String => {
-            let mut int_data: [u8; 4] = Default::default();
-            int_data.copy_from_slice(&data[*offset..(*offset + 4)]);
-            let len = u32::from_le_bytes(int_data) as usize;
-            (*offset) += 4;
-            let read_string = std::str::from_utf8(&data[*offset..(*offset + len)]).unwrap();
-            (*offset) += len;
+            let len = read_length(data, offset)?;
+            let read_string = std::str::from_utf8(read_bytes(data, offset, len)?)
+                .map_err(|e| anyhow!("Invalid UTF-8 string: {}", e))?;
             BorshToken::String(read_string.to_string())
         }
         IdlType::Array(ty, len) => {
             let mut read_items: Vec<BorshToken> = Vec::with_capacity(*len);
             for _ in 0..*len {
-                read_items.push(decode_at_offset(data, offset, ty, custom_types));
+                read_items.push(try_decode_at_offset(data, offset, ty, custom_types)?);
             }
             BorshToken::FixedArray(read_items)
         }
         IdlType::Vec(ty) => {
-            let mut int_data: [u8; 4] = Default::default();
-            int_data.copy_from_slice(&data[*offset..(*offset + 4)]);
-            let len = u32::from_le_bytes(int_data);
-            (*offset) += 4;
-            let mut read_items: Vec<BorshToken> = Vec::with_capacity(len as usize);
+            let len = read_length(data, offset)?;
+            let mut read_items: Vec<BorshToken> = Vec::new();
             for _ in 0..len {
-                read_items.push(decode_at_offset(data, offset, ty, custom_types));
+                read_items.push(try_decode_at_offset(data, offset, ty, custom_types)?);
             }
             BorshToken::Array(read_items)
         }
@@ -392,12 +416,11 @@ pub fn decode_at_offset(
             let current_ty = custom_types
                 .iter()
                 .find(|item| &item.name == value)
-                .unwrap();
+                .ok_or_else(|| anyhow!("Type definition with name {} not found", value))?;
 
             match &current_ty.ty {
                 IdlTypeDefinitionTy::Enum { .. } => {
-                    let value = data[*offset];
-                    (*offset) += 1;
+                    let value = read_bytes(data, offset, 1)?[0];
                     BorshToken::Uint {
                         width: 8,
                         value: BigInt::from(value),
@@ -406,7 +429,12 @@ pub fn decode_at_offset(
                 IdlTypeDefinitionTy::Struct { fields } => {
                     let mut read_items: Vec<BorshToken> = Vec::with_capacity(fields.len());
                     for item in fields {
-                        read_items.push(decode_at_offset(data, offset, &item.ty, custom_types));
+                        read_items.push(try_decode_at_offset(
+                            data,
+                            offset,
+                            &item.ty,
+                            custom_types,
+                        )?);
                     }
 
                     BorshToken::Tuple(read_items)
@@ -414,22 +442,230 @@ pub fn decode_at_offset(
             }
         }
         IdlType::Bytes => {
-            let mut int_data: [u8; 4] = Default::default();
-            int_data.copy_from_slice(&data[*offset..(*offset + 4)]);
-            let len = u32::from_le_bytes(int_data) as usize;
-            (*offset) += 4;
-            let read_data = &data[*offset..(*offset + len)];
-            (*offset) += len;
+            let len = read_length(data, offset)?;
+            let read_data = read_bytes(data, offset, len)?;
             BorshToken::Bytes(read_data.to_vec())
         }
 
         IdlType::Option(_) | IdlType::F32 | IdlType::F64 => {
-            unreachable!("Type not available in Solidity")
+            bail!("Type not available in Solidity")
+        }
+    };
+
+    Ok(token)
+}
+
+/// Decode the return data of an instruction given its return type, e.g. the data of a transaction
+/// fetched by the caller, without an RPC client.
+///
+/// Returns an error if the data is too short for the type, if the data is not valid for the type, or
+/// if bytes are left over once the value is decoded.
+pub fn decode_return_data(
+    data: &[u8],
+    ty: &IdlType,
+    custom_types: &[IdlTypeDefinition],
+) -> Result<BorshValue> {
+    let mut offset = 0;
+    let token = try_decode_at_offset(data, &mut offset, ty, custom_types)?;
+    if offset != data.len() {
+        bail!(
+            "Unexpected return data: {} bytes left over after decoding the value",
+            data.len() - offset
+        );
+    }
+    Ok(BorshValue::from_token(token, ty, custom_types))
+}
+
+/// Read `len` bytes at the given offset, and advance the offset
+fn read_bytes<'a>(data: &'a [u8], offset: &mut usize, len: usize) -> Result<&'a [u8]> {
+    let end = offset
+        .checked_add(len)
+        .filter(|end| *end <= data.len())
+        .ok_or_else(|| {
+            anyhow!(
+                "Unexpected end of data: need {} bytes at offset {}, but only {} bytes are available",
+                len,
+                offset,
+                data.len()
+            )
+        })?;
+    let read = &data[*offset..end];
+    *offset = end;
+    Ok(read)
+}
+
+/// Read the u32 length prefix of a dynamic type at the given offset, and advance the offset
+fn read_length(data: &[u8], offset: &mut usize) -> Result<usize> {
+    let mut int_data: [u8; 4] = Default::default();
+    int_data.copy_from_slice(read_bytes(data, offset, 4)?);
+    Ok(u32::from_le_bytes(int_data) as usize)
+}
+
+/// A value decoded from Borsh data, typed with its IDL type.
+///
+/// Unlike a [`BorshToken`], the fields of the structs and the variants of the enums are named. Values
+/// are serialized for the structured output: integers wider than 64 bits as strings, since they
+/// don't fit in a JSON number, addresses in base58, bytes in hex, structs as maps of their fields,
+/// and enums as maps tagged with their variant.
+#[derive(Debug, PartialEq, Clone)]
+pub enum BorshValue {
+    Address([u8; 32]),
+    Int {
+        width: u16,
+        value: BigInt,
+    },
+    Uint {
+        width: u16,
+        value: BigInt,
+    },
+    Bytes(Vec<u8>),
+    Bool(bool),
+    String(String),
+    Array(Vec<BorshValue>),
+    Tuple(Vec<BorshValue>),
+    /// A struct, as its fields in the order of the IDL
+    Struct(Vec<(String, BorshValue)>),
+    /// An enum, as the index of its variant, and its name if the IDL defines it
+    Enum {
+        index: u8,
+        variant: Option<String>,
+    },
+}
+
+impl BorshValue {
+    /// Type a decoded token with its IDL type, naming the fields of the structs and the variants of
+    /// the enums.
+    pub fn from_token(
+        token: BorshToken,
+        ty: &IdlType,
+        custom_types: &[IdlTypeDefinition],
+    ) -> BorshValue {
+        let definition = match ty {
+            IdlType::Defined(name) => custom_types.iter().find(|t| &t.name == name).map(|t| &t.ty),
+            _ => None,
+        };
+        match (token, ty, definition) {
+            (BorshToken::Uint { value, .. }, _, Some(IdlTypeDefinitionTy::Enum { variants })) => {
+                // Enums are decoded as the index of the variant
+                let index = value.to_u8().unwrap_or_default();
+                BorshValue::Enum {
+                    index,
+                    variant: variants.get(index as usize).map(|v| v.name.clone()),
+                }
+            }
+            (BorshToken::Tuple(items), _, Some(IdlTypeDefinitionTy::Struct { fields })) => {
+                BorshValue::Struct(
+                    fields
+                        .iter()
+                        .zip(items)
+                        .map(|(field, item)| {
+                            (
+                                field.name.clone(),
+                                BorshValue::from_token(item, &field.ty, custom_types),
+                            )
+                        })
+                        .collect(),
+                )
+            }
+            (BorshToken::FixedArray(items), IdlType::Array(elem_ty, _), _)
+            | (BorshToken::Array(items), IdlType::Vec(elem_ty), _) => BorshValue::Array(
+                items
+                    .into_iter()
+                    .map(|item| BorshValue::from_token(item, elem_ty, custom_types))
+                    .collect(),
+            ),
+            (token, _, _) => BorshValue::from(token),
+        }
+    }
+}
+
+impl From<BorshToken> for BorshValue {
+    /// Convert a token without its IDL type: structs are tuples, and enums are integers.
+    fn from(token: BorshToken) -> Self {
+        match token {
+            BorshToken::Address(data) => BorshValue::Address(data),
+            BorshToken::Int { width, value } => BorshValue::Int { width, value },
+            BorshToken::Uint { width, value } => BorshValue::Uint { width, value },
+            BorshToken::FixedBytes(data) | BorshToken::Bytes(data) => BorshValue::Bytes(data),
+            BorshToken::Bool(value) => BorshValue::Bool(value),
+            BorshToken::String(value) => BorshValue::String(value),
+            BorshToken::FixedArray(items) | BorshToken::Array(items) => {
+                BorshValue::Array(items.into_iter().map(BorshValue::from).collect())
+            }
+            BorshToken::Tuple(items) => {
+                BorshValue::Tuple(items.into_iter().map(BorshValue::from).collect())
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for BorshValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BorshValue::Address(data) => write!(f, "{}", data.to_base58()),
+            BorshValue::Int { value, .. } | BorshValue::Uint { value, .. } => {
+                write!(f, "{}", value)
+            }
+            BorshValue::Bytes(data) => write!(f, "{}", hex::encode(data)),
+            BorshValue::Bool(value) => write!(f, "{}", value),
+            BorshValue::String(data) => write!(f, "{}", data),
+            BorshValue::Array(items) | BorshValue::Tuple(items) => {
+                let response = items
+                    .iter()
+                    .map(|item| item.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                write!(f, "{}", response)
+            }
+            BorshValue::Struct(fields) => {
+                let response = fields
+                    .iter()
+                    .map(|(_, item)| item.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                write!(f, "{}", response)
+            }
+            BorshValue::Enum { index, variant } => match variant {
+                Some(variant) => write!(f, "{}", variant),
+                None => write!(f, "{}", index),
+            },
+        }
+    }
+}
+
+impl Serialize for BorshValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self {
+            BorshValue::Address(data) => serializer.serialize_str(&data.to_base58()),
+            BorshValue::Int { width, value } if *width <= 64 => match value.to_i64() {
+                Some(value) => serializer.serialize_i64(value),
+                None => serializer.collect_str(value),
+            },
+            BorshValue::Uint { width, value } if *width <= 64 => match value.to_u64() {
+                Some(value) => serializer.serialize_u64(value),
+                None => serializer.collect_str(value),
+            },
+            BorshValue::Int { value, .. } | BorshValue::Uint { value, .. } => {
+                serializer.collect_str(value)
+            }
+            BorshValue::Bytes(data) => serializer.serialize_str(&hex::encode(data)),
+            BorshValue::Bool(value) => serializer.serialize_bool(*value),
+            BorshValue::String(value) => serializer.serialize_str(value),
+            BorshValue::Array(items) | BorshValue::Tuple(items) => serializer.collect_seq(items),
+            BorshValue::Struct(fields) => {
+                serializer.collect_map(fields.iter().map(|(name, item)| (name, item)))
+            }
+            BorshValue::Enum { index, variant } => {
+                let mut map = serializer.serialize_map(Some(2))?;
+                map.serialize_entry("variant", variant)?;
+                map.serialize_entry("index", index)?;
+                map.end()
+            }
         }
     }
 }
 
-fn integer_byte_width(ty: &IdlType) -> usize {
+pub(crate) fn integer_byte_width(ty: &IdlType) -> usize {
     match ty {
         IdlType::U8 | IdlType::I8 => 1,
         IdlType::U16 | IdlType::I16 => 2,
@@ -0,0 +1,192 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::solana_deploy::deploy_program_with_overrides,
+    anyhow::{anyhow, Result},
+    serde_json::{json, Value},
+    std::{
+        fs,
+        path::{Path, PathBuf},
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Mutex,
+        },
+        thread,
+    },
+};
+
+/// A program to deploy, as listed in a deploy manifest.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ManifestProgram {
+    /// The name of the program, used in the deployment summary
+    pub name: String,
+    /// The path of the program binary (.so)
+    pub path: PathBuf,
+}
+
+/// A list of programs to deploy together.
+///
+/// The manifest is a TOML file with one `[[programs]]` entry per program:
+///
+/// ```toml
+/// [[programs]]
+/// name = "flipper"
+/// path = "build/flipper.so"
+/// ```
+///
+/// Relative paths are resolved from the directory of the manifest file. If `name` is omitted, the
+/// file name of the program binary (without extension) is used.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeployManifest {
+    pub programs: Vec<ManifestProgram>,
+}
+
+/// The result of deploying a program of a manifest.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ManifestDeployment {
+    /// The name of the program
+    pub name: String,
+    /// The path of the program binary (.so)
+    pub path: PathBuf,
+    /// The program ID, if the deployment succeeded
+    pub program_id: Option<String>,
+    /// The reason the deployment failed, if any
+    pub error: Option<String>,
+}
+
+impl ManifestDeployment {
+    /// Returns a JSON representation of the deployment.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "name": self.name,
+            "path": self.path.to_string_lossy(),
+            "program_id": self.program_id,
+            "error": self.error,
+        })
+    }
+}
+
+impl DeployManifest {
+    /// Load a deploy manifest from a TOML file.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the file cannot be read or is not a valid manifest.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read manifest {}: {}", path.display(), e))?;
+        let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+        Self::from_toml(base_dir, &content)
+            .map_err(|e| anyhow!("Failed to parse manifest {}: {}", path.display(), e))
+    }
+
+    /// Parse a deploy manifest from its TOML content, resolving relative paths from `base_dir`.
+    pub fn from_toml(base_dir: &Path, content: &str) -> Result<Self> {
+        let parsed_toml: toml::Value = toml::from_str(content)?;
+        let entries = parsed_toml
+            .get("programs")
+            .and_then(|programs| programs.as_array())
+            .ok_or_else(|| anyhow!("Missing [[programs]] entries"))?;
+
+        let programs = entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                let path = entry
+                    .get("path")
+                    .and_then(|path| path.as_str())
+                    .ok_or_else(|| anyhow!("Missing path for program {}", i + 1))?;
+                let path = base_dir.join(path);
+                let name = match entry.get("name").and_then(|name| name.as_str()) {
+                    Some(name) => name.to_string(),
+                    None => path
+                        .file_stem()
+                        .map(|stem| stem.to_string_lossy().to_string())
+                        .ok_or_else(|| anyhow!("Missing name for program {}", i + 1))?,
+                };
+                Ok(ManifestProgram { name, path })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        if programs.is_empty() {
+            return Err(anyhow!("The manifest does not list any programs"));
+        }
+
+        Ok(DeployManifest { programs })
+    }
+
+    /// Deploy all the programs of the manifest.
+    ///
+    /// A failed deployment does not stop the other deployments: the error is recorded in the
+    /// returned [`ManifestDeployment`] instead. The deployments are returned in the manifest order.
+    ///
+    /// # Arguments
+    ///
+    /// * `jobs`: The maximum number of programs deployed concurrently, `1` to deploy them one
+    ///   after the other. Each deployment signs its transactions with its own recent blockhash, so
+    ///   the same payer can deploy several programs at once.
+    /// * `json_rpc_url`: The RPC URL to deploy to. Defaults to the one of the configuration file.
+    /// * `keypair_path`: The path of the payer keypair. Defaults to the one of the configuration file.
+    pub fn deploy(
+        &self,
+        jobs: usize,
+        json_rpc_url: Option<String>,
+        keypair_path: Option<String>,
+    ) -> Vec<ManifestDeployment> {
+        let deploy_one = |program: &ManifestProgram| {
+            let result = deploy_program_with_overrides(
+                program.path.to_string_lossy().to_string(),
+                json_rpc_url.clone(),
+                keypair_path.clone(),
+            );
+            let (program_id, error) = match result {
+                Ok(program_id) => (Some(program_id), None),
+                Err(e) => (None, Some(e.to_string())),
+            };
+            ManifestDeployment {
+                name: program.name.clone(),
+                path: program.path.clone(),
+                program_id,
+                error,
+            }
+        };
+
+        let jobs = jobs.clamp(1, self.programs.len());
+        if jobs == 1 {
+            return self.programs.iter().map(deploy_one).collect();
+        }
+
+        // Each worker deploys the next program not yet taken, until there are none left
+        let next = AtomicUsize::new(0);
+        let deployments: Mutex<Vec<Option<ManifestDeployment>>> =
+            Mutex::new(vec![None; self.programs.len()]);
+        thread::scope(|scope| {
+            for _ in 0..jobs {
+                scope.spawn(|| loop {
+                    let i = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(program) = self.programs.get(i) else {
+                        break;
+                    };
+                    let deployment = deploy_one(program);
+                    deployments.lock().unwrap()[i] = Some(deployment);
+                });
+            }
+        });
+
+        deployments
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .zip(&self.programs)
+            .map(|(deployment, program)| {
+                deployment.unwrap_or_else(|| ManifestDeployment {
+                    name: program.name.clone(),
+                    path: program.path.clone(),
+                    program_id: None,
+                    error: Some("The deployment thread panicked".to_string()),
+                })
+            })
+            .collect()
+    }
+}
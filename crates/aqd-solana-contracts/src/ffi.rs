@@ -0,0 +1,197 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::{
+        borsh_encoding::decode_return_data, idl_discriminators::IdlDiscriminators,
+        instruction_data::construct_instruction_data_with_discriminator,
+        solana_transaction::SolanaTransaction,
+    },
+    anchor_syn::idl::{Idl, IdlInstruction},
+    anyhow::{anyhow, Result},
+    aqd_utils::set_quiet,
+    serde::{de::DeserializeOwned, Deserialize},
+    serde_json::{json, Value},
+    std::{
+        ffi::{c_char, CStr, CString},
+        panic::{catch_unwind, UnwindSafe},
+    },
+};
+
+/// The request of [`aqd_encode_instruction_data`].
+#[derive(Deserialize)]
+struct EncodeRequest {
+    /// The IDL JSON, kept as is to read the discriminators embedded in it
+    idl: Value,
+    instruction: String,
+    #[serde(default)]
+    args: Vec<String>,
+}
+
+/// The request of [`aqd_decode_return_data`].
+#[derive(Deserialize)]
+struct DecodeRequest {
+    idl: Idl,
+    instruction: String,
+    /// The hex encoded return data
+    data: String,
+}
+
+/// The request of [`aqd_submit_transaction`].
+#[derive(Deserialize)]
+struct SubmitRequest {
+    rpc_url: String,
+    /// The IDL JSON, kept as is to read the discriminators embedded in it
+    idl: Value,
+    program_id: String,
+    instruction: String,
+    #[serde(default)]
+    args: Vec<String>,
+    #[serde(default)]
+    accounts: Vec<String>,
+    payer: String,
+}
+
+/// Encode the data of an instruction.
+///
+/// The request is `{"idl": <IDL>, "instruction": "<NAME>", "args": ["<ARG>", ...]}`, with the
+/// arguments as accepted by `aqd solana call --data`. The result is `{"data": "<HEX>"}`, starting
+/// with the discriminator of the instruction embedded in the IDL, if any.
+///
+/// # Safety
+///
+/// `request` must be null or a valid pointer to a NUL terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn aqd_encode_instruction_data(request: *const c_char) -> *mut c_char {
+    respond(request, |request: EncodeRequest| {
+        let idl: Idl = serde_json::from_value(request.idl.clone())
+            .map_err(|e| anyhow!("Invalid IDL: {}", e))?;
+        let discriminators = IdlDiscriminators::from_value(&request.idl)?;
+        let instruction = find_instruction(&idl, &request.instruction)?;
+        let data = construct_instruction_data_with_discriminator(
+            instruction,
+            &request.args,
+            &idl.types,
+            &discriminators.instruction(&instruction.name),
+        )?;
+        Ok(json!({ "data": hex::encode(data) }))
+    })
+}
+
+/// Decode the return data of an instruction.
+///
+/// The request is `{"idl": <IDL>, "instruction": "<NAME>", "data": "<HEX>"}`. The result is the
+/// decoded value, serialized as in the JSON output of aqd.
+///
+/// # Safety
+///
+/// `request` must be null or a valid pointer to a NUL terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn aqd_decode_return_data(request: *const c_char) -> *mut c_char {
+    respond(request, |request: DecodeRequest| {
+        let instruction = find_instruction(&request.idl, &request.instruction)?;
+        let ty = instruction
+            .returns
+            .as_ref()
+            .ok_or_else(|| anyhow!("Instruction {} has no return value", instruction.name))?;
+        let data = hex::decode(request.data.trim_start_matches("0x"))
+            .map_err(|e| anyhow!("Invalid return data: {}", e))?;
+        Ok(json!(decode_return_data(&data, ty, &request.idl.types)?))
+    })
+}
+
+/// Submit a program call, and wait for its confirmation.
+///
+/// The request is `{"rpc_url": "<URL>", "idl": <IDL>, "program_id": "<ID>", "instruction":
+/// "<NAME>", "args": ["<ARG>", ...], "accounts": ["<ACCOUNT>", ...], "payer": "<KEYPAIR>"}`, with
+/// the arguments, accounts and payer as accepted by `aqd solana call`. The result is
+/// `{"signature": "<SIGNATURE>", "new_accounts": [{"pubkey": "<PUBKEY>", "file_name": "<PATH>"}]}`.
+///
+/// Nothing is printed: the progress of the transaction is not reported.
+///
+/// # Safety
+///
+/// `request` must be null or a valid pointer to a NUL terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn aqd_submit_transaction(request: *const c_char) -> *mut c_char {
+    respond(request, |request: SubmitRequest| {
+        set_quiet(true);
+        let transaction = SolanaTransaction::new()
+            .rpc_url(request.rpc_url)
+            .idl_json_str(request.idl.to_string())
+            .program_id(request.program_id)
+            .instruction(request.instruction)
+            .call_data(request.args)
+            .accounts(request.accounts)
+            .payer(request.payer)
+            .done()?;
+        let signature = transaction.submit_transaction()?;
+        let new_accounts: Vec<Value> = transaction
+            .new_accounts()
+            .iter()
+            .map(|(pubkey, file_name)| {
+                json!({
+                    "pubkey": pubkey.to_string(),
+                    "file_name": file_name,
+                })
+            })
+            .collect();
+        Ok(json!({
+            "signature": signature.to_string(),
+            "new_accounts": new_accounts,
+        }))
+    })
+}
+
+/// Free a string returned by the other functions.
+///
+/// # Safety
+///
+/// `response` must be null, or a string returned by the other functions and not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn aqd_free_string(response: *mut c_char) {
+    if !response.is_null() {
+        drop(CString::from_raw(response));
+    }
+}
+
+/// Find an instruction of an IDL by name.
+fn find_instruction<'a>(idl: &'a Idl, name: &str) -> Result<&'a IdlInstruction> {
+    idl.instructions
+        .iter()
+        .find(|instruction| instruction.name == name)
+        .ok_or_else(|| anyhow!("Instruction {} not found", name))
+}
+
+/// Parse a JSON request, handle it, and return the JSON response as a C string: `{"ok": <RESULT>}`
+/// on success, or `{"error": "<MESSAGE>"}`. A panic is reported as an error, since it must not
+/// unwind across the C ABI.
+///
+/// # Safety
+///
+/// `request` must be null or a valid pointer to a NUL terminated string.
+unsafe fn respond<T, F>(request: *const c_char, handle: F) -> *mut c_char
+where
+    T: DeserializeOwned,
+    F: FnOnce(T) -> Result<Value> + UnwindSafe,
+{
+    let request = match request.is_null() {
+        true => Err(anyhow!("The request is null")),
+        false => CStr::from_ptr(request)
+            .to_str()
+            .map_err(|e| anyhow!("The request is not valid UTF-8: {}", e)),
+    };
+    let response = catch_unwind(|| {
+        let request =
+            serde_json::from_str(request?).map_err(|e| anyhow!("Invalid request: {}", e))?;
+        handle(request)
+    });
+    let response = match response {
+        Ok(Ok(result)) => json!({ "ok": result }),
+        Ok(Err(e)) => json!({ "error": format!("{:#}", e) }),
+        Err(_) => json!({ "error": "aqd panicked while handling the request" }),
+    };
+    // A JSON string never contains a NUL byte, which is escaped
+    CString::new(response.to_string())
+        .expect("JSON without NUL bytes")
+        .into_raw()
+}
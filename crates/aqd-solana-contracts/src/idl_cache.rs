@@ -0,0 +1,155 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anchor_syn::idl::Idl,
+    anyhow::{anyhow, Result},
+    aqd_utils::{is_replaying, proxy_for, rpc_timeout},
+    reqwest::{
+        blocking::Client,
+        header::{ETAG, IF_NONE_MATCH},
+        Proxy, StatusCode,
+    },
+    sha2::{Digest, Sha256},
+    std::{env, fs, path::PathBuf},
+    tracing::{debug, warn},
+    url::Url,
+};
+
+/// The environment variable that overrides the directory of the IDLs downloaded from a URL.
+pub const AQD_IDL_CACHE_ENV: &str = "AQD_IDL_CACHE";
+
+/// Check whether an IDL argument is the URL of an IDL JSON file to download, rather than a path.
+pub fn is_idl_url(idl: &str) -> bool {
+    idl.starts_with("https://") || idl.starts_with("http://")
+}
+
+/// The response to the download of an IDL.
+enum Download {
+    /// The IDL cached with the ETag sent has not changed
+    NotModified,
+    /// The content of the IDL, with its ETag, if any
+    Modified {
+        content: String,
+        etag: Option<String>,
+    },
+}
+
+/// Download an IDL JSON file, e.g. published as a release artifact, and parse it.
+///
+/// The IDLs are cached in the directory of the `AQD_IDL_CACHE` environment variable, or in
+/// `~/.cache/aqd/idl` by default, by URL, with the ETag of their response. A cached IDL is
+/// downloaded again only if the server reports a new ETag for its URL, and is used as is when the
+/// server cannot be reached, or when the RPC responses are replayed from a cassette.
+///
+/// # Errors
+///
+/// This function returns an error if the IDL can neither be downloaded nor read from the cache,
+/// or if the downloaded file is not a valid IDL. An invalid IDL is not cached.
+///
+/// # Panics
+///
+/// As the download is blocking, this function panics if called from an asynchronous context.
+pub fn fetch_idl(url: &str) -> Result<Idl> {
+    fetch_idl_with_json(url).map(|(idl, _)| idl)
+}
+
+/// Download an IDL JSON file like [`fetch_idl`], and return the IDL with its JSON, e.g. to read the
+/// discriminators embedded in it.
+pub(crate) fn fetch_idl_with_json(url: &str) -> Result<(Idl, String)> {
+    let dir = cache_dir()?;
+    let key = hex::encode(Sha256::digest(url.as_bytes()));
+    let idl_path = dir.join(format!("{}.json", key));
+    let etag_path = dir.join(format!("{}.etag", key));
+    let cached = fs::read_to_string(&idl_path).ok();
+
+    if is_replaying() {
+        let content = cached.ok_or_else(|| {
+            anyhow!(
+                "IDL {} not cached: it cannot be downloaded while replaying a cassette",
+                url
+            )
+        })?;
+        let idl = parse_idl(url, &content)?;
+        return Ok((idl, content));
+    }
+
+    let etag = match &cached {
+        Some(_) => fs::read_to_string(&etag_path).ok(),
+        None => None,
+    };
+    match (download(url, etag.as_deref()), cached) {
+        (Ok(Download::NotModified), Some(content)) => {
+            debug!(url, "Using the cached IDL, not modified");
+            Ok((parse_idl(url, &content)?, content))
+        }
+        (Ok(Download::NotModified), None) => Err(anyhow!(
+            "Failed to download the IDL {}: not modified, but not cached",
+            url
+        )),
+        (Ok(Download::Modified { content, etag }), _) => {
+            let idl = parse_idl(url, &content)?;
+            fs::create_dir_all(&dir)
+                .map_err(|e| anyhow!("Failed to create {}: {}", dir.display(), e))?;
+            fs::write(&idl_path, &content)
+                .map_err(|e| anyhow!("Failed to write {}: {}", idl_path.display(), e))?;
+            // An IDL without an ETag is downloaded again by the next command
+            let _ = match etag {
+                Some(etag) => fs::write(&etag_path, etag),
+                None => fs::remove_file(&etag_path),
+            };
+            Ok((idl, content))
+        }
+        (Err(e), Some(content)) => {
+            warn!("{:#}. Using the cached copy of the IDL", e);
+            Ok((parse_idl(url, &content)?, content))
+        }
+        (Err(e), None) => Err(e),
+    }
+}
+
+/// The directory of the downloaded IDLs.
+fn cache_dir() -> Result<PathBuf> {
+    if let Ok(dir) = env::var(AQD_IDL_CACHE_ENV) {
+        return Ok(PathBuf::from(dir));
+    }
+    let home =
+        env::var("HOME").map_err(|_| anyhow!("Failed to locate the IDL cache: HOME is not set"))?;
+    Ok(PathBuf::from(home).join(".cache").join("aqd").join("idl"))
+}
+
+/// Download an IDL, unless its ETag is still `etag`, through the proxy of `--proxy` or of the
+/// environment, if any.
+fn download(url: &str, etag: Option<&str>) -> Result<Download> {
+    let parsed_url = Url::parse(url).map_err(|e| anyhow!("Invalid IDL URL {}: {}", url, e))?;
+    let mut client = Client::builder().timeout(rpc_timeout());
+    if let Some(proxy) = proxy_for(&parsed_url) {
+        client = client.proxy(Proxy::all(proxy.as_str())?);
+    }
+    let mut request = client.build()?.get(parsed_url);
+    if let Some(etag) = etag {
+        request = request.header(IF_NONE_MATCH, etag);
+    }
+    let response = request
+        .send()
+        .map_err(|e| anyhow!("Failed to download the IDL {}: {}", url, e))?;
+    if response.status() == StatusCode::NOT_MODIFIED {
+        return Ok(Download::NotModified);
+    }
+    let response = response
+        .error_for_status()
+        .map_err(|e| anyhow!("Failed to download the IDL {}: {}", url, e))?;
+    let etag = response
+        .headers()
+        .get(ETAG)
+        .and_then(|etag| etag.to_str().ok())
+        .map(str::to_string);
+    let content = response
+        .text()
+        .map_err(|e| anyhow!("Failed to download the IDL {}: {}", url, e))?;
+    Ok(Download::Modified { content, etag })
+}
+
+/// Parse the JSON of a downloaded IDL.
+fn parse_idl(url: &str, content: &str) -> Result<Idl> {
+    serde_json::from_str(content).map_err(|e| anyhow!("Invalid IDL {}: {}", url, e))
+}
@@ -0,0 +1,87 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::borsh_encoding::{discriminator, ACCOUNT_NAMESPACE, GLOBAL_NAMESPACE},
+    anyhow::{anyhow, Result},
+    serde_json::Value,
+    std::collections::HashMap,
+};
+
+/// The discriminators embedded in an IDL, by instruction and account name.
+///
+/// Newer Anchor IDLs list the `discriminator` bytes of each instruction and account, which
+/// [`anchor_syn::idl::Idl`] does not keep: they are read from the IDL JSON instead. The
+/// discriminators missing from the IDL are computed as the anchor discriminators of their names.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IdlDiscriminators {
+    /// The discriminators of the instructions
+    pub instructions: HashMap<String, Vec<u8>>,
+    /// The discriminators of the accounts
+    pub accounts: HashMap<String, Vec<u8>>,
+}
+
+impl IdlDiscriminators {
+    /// Read the discriminators embedded in an IDL JSON.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the JSON is invalid, or if a discriminator is not an array
+    /// of bytes.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let value: Value =
+            serde_json::from_str(json).map_err(|e| anyhow!("Invalid IDL JSON: {}", e))?;
+        Self::from_value(&value)
+    }
+
+    /// Read the discriminators embedded in an IDL JSON value.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if a discriminator is not an array of bytes.
+    pub fn from_value(idl: &Value) -> Result<Self> {
+        Ok(IdlDiscriminators {
+            instructions: section_discriminators(idl, "instructions")?,
+            accounts: section_discriminators(idl, "accounts")?,
+        })
+    }
+
+    /// The discriminator of an instruction: the one embedded in the IDL, or the anchor
+    /// discriminator of its name.
+    pub fn instruction(&self, name: &str) -> Vec<u8> {
+        self.instructions
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| discriminator(GLOBAL_NAMESPACE, name))
+    }
+
+    /// The discriminator of an account: the one embedded in the IDL, or the anchor discriminator
+    /// of its name.
+    pub fn account(&self, name: &str) -> Vec<u8> {
+        self.accounts
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| discriminator(ACCOUNT_NAMESPACE, name))
+    }
+}
+
+/// Read the discriminators of the items of a section of an IDL, e.g. `instructions`.
+fn section_discriminators(idl: &Value, section: &str) -> Result<HashMap<String, Vec<u8>>> {
+    let mut discriminators = HashMap::new();
+    let items = match idl.get(section).and_then(Value::as_array) {
+        Some(items) => items,
+        None => return Ok(discriminators),
+    };
+    for item in items {
+        let (name, bytes) = match (
+            item.get("name").and_then(Value::as_str),
+            item.get("discriminator"),
+        ) {
+            (Some(name), Some(bytes)) => (name, bytes),
+            _ => continue,
+        };
+        let bytes: Vec<u8> = serde_json::from_value(bytes.clone())
+            .map_err(|e| anyhow!("Invalid discriminator of {} {}: {}", section, name, e))?;
+        discriminators.insert(name.to_string(), bytes);
+    }
+    Ok(discriminators)
+}
@@ -0,0 +1,650 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::borsh_encoding::{
+        discriminator, encode_arguments, integer_byte_width, BorshToken, GLOBAL_NAMESPACE,
+    },
+    anchor_syn::idl::{
+        IdlInstruction, IdlType, IdlTypeDefinition, IdlTypeDefinitionTy::Enum,
+        IdlTypeDefinitionTy::Struct,
+    },
+    anyhow::{anyhow, Result},
+    base58::FromBase58,
+    base64::{engine::general_purpose::STANDARD, Engine},
+    num_bigint::BigInt,
+    num_traits::{One, Zero},
+    serde_json::Value as JsonValue,
+    std::str::FromStr,
+    tracing::{debug, trace},
+};
+
+/// Constructs binary data for an instruction based on the provided IDL instruction and raw arguments.
+///
+/// Given an [`IdlInstruction`], a vector of raw arguments, and a list of IDL type definitions, this
+/// function encodes the arguments into binary data following the instruction's layout and type
+/// definitions. The resulting binary data can be used as an instruction to invoke a smart contract
+/// on a blockchain.
+///
+/// # Arguments
+///
+/// * `instr` - The IDL instruction of type [`IdlInstruction`] for which to construct binary data.
+///
+/// * `raw_args` - A vector of raw argument values represented as strings. A value of the form
+///   `@<PATH>` is replaced by the content of the file at `PATH`, used verbatim. Use `@@` to pass a
+///   value starting with a literal `@`.
+///
+/// * `custom_types` - A vector of IDL type definitions used for encoding arguments.
+///
+/// # Returns
+///
+/// Returns a [`Result`] containing the encoded binary data as a [`Vec<u8>`].
+///
+/// # Errors
+///
+/// This function can return an error in the following cases:
+///
+/// - If any argument is missing or if the provided raw arguments do not match the expected
+///   arguments defined by the IDL instruction.
+///
+/// - If the IDL-defined types cannot be found or if there is an issue with encoding the arguments
+///   based on these types.
+pub fn construct_instruction_data(
+    instr: &IdlInstruction,
+    raw_args: &[String],
+    custom_types: &Vec<IdlTypeDefinition>,
+) -> Result<Vec<u8>> {
+    // Construct the discriminator (the first 8 bytes of the instruction data)
+    let discriminator = discriminator(GLOBAL_NAMESPACE, &instr.name);
+    construct_instruction_data_with_discriminator(instr, raw_args, custom_types, &discriminator)
+}
+
+/// Constructs binary data for an instruction like [`construct_instruction_data`], prefixed with the
+/// given discriminator instead of the anchor discriminator of the instruction.
+///
+/// This is needed for programs that dispatch their instructions on non-standard discriminators,
+/// e.g. computed in another namespace with [`discriminator`], or of another length.
+///
+/// # Errors
+///
+/// This function returns the errors of [`construct_instruction_data`].
+pub fn construct_instruction_data_with_discriminator(
+    instr: &IdlInstruction,
+    raw_args: &[String],
+    custom_types: &Vec<IdlTypeDefinition>,
+    discriminator: &[u8],
+) -> Result<Vec<u8>> {
+    let mut data = discriminator.to_vec();
+    let mut args: Vec<BorshToken> = vec![];
+
+    // Loop through the arguments and encode them given the IDL instruction
+    for (i, arg) in instr.args.iter().enumerate() {
+        let arg_name = arg.name.clone();
+        let arg_type = arg.ty.clone();
+        let arg_val = raw_args
+            .get(i)
+            .ok_or_else(|| anyhow!("Missing argument {}", arg_name))?;
+
+        // Encode the argument based on the IDL type
+        let arg_val = resolve_file_argument(arg_val)
+            .map_err(|e| anyhow!("Invalid argument {}: {}", arg_name, e))?;
+        let mut borsh_args = get_borsh_token_vector(&arg_name, arg_val, &arg_type, custom_types)?;
+        trace!(argument = %arg_name, ty = ?arg_type, tokens = ?borsh_args, "Encoded argument");
+        args.append(&mut borsh_args);
+    }
+
+    let mut encoded_data = encode_arguments(&args);
+    data.append(&mut encoded_data);
+    debug!(
+        instruction = %instr.name,
+        data = %hex::encode(&data),
+        "Encoded the instruction data"
+    );
+
+    Ok(data)
+}
+
+/// Resolves a raw argument value referencing a file.
+///
+/// A value of the form `@<PATH>` is replaced by the content of the file at `PATH`, verbatim, and
+/// `@@<VALUE>` is unescaped to `@<VALUE>`. Any other value is returned as is.
+///
+/// # Errors
+///
+/// This function returns an error if the referenced file cannot be read, or is not valid UTF-8.
+pub fn resolve_file_argument(arg_value: &str) -> Result<String> {
+    if let Some(escaped) = arg_value.strip_prefix("@@") {
+        return Ok(format!("@{escaped}"));
+    }
+    match arg_value.strip_prefix('@') {
+        Some(path) => {
+            let content = std::fs::read(path)
+                .map_err(|e| anyhow!("Failed to read argument file {}: {}", path, e))?;
+            String::from_utf8(content).map_err(|e| {
+                anyhow!(
+                    "Argument file {} is not valid UTF-8: {}",
+                    path,
+                    e.utf8_error()
+                )
+            })
+        }
+        None => Ok(arg_value.to_string()),
+    }
+}
+
+/// Converts a raw argument value into a vector of Borsh tokens based on the provided IDL type.
+///
+/// This function takes a raw argument value as a string, an IDL type definition, and a list of
+/// IDL type definitions for reference. It then converts the raw argument value into a vector of
+/// Borsh tokens, considering the specified IDL type and nested types if applicable.
+///
+/// # Arguments
+///
+/// * `arg_name` - The name of the argument, reported in the errors.
+///
+/// * `arg_value` - The raw argument value to be converted. The expected input format depends on
+///   the IDL type:
+///
+///   - For structs, provide a JSON object representing the data structure.
+///   - For arrays and vectors, provide a JSON array of values (e.g. `[[1,2],[3]]` or
+///     `[{"a": 1}]`), or a comma-separated string of values for arrays and vectors of scalars
+///     (e.g. `1,2,3`).
+///
+/// * `arg_type` - The IDL type definition specifying the expected type of the argument.
+///
+/// * `custom_types` - A vector of IDL type definitions used for resolving nested types.
+///
+/// # Returns
+///
+/// Returns a [`Result`] containing the vector of Borsh tokens ([`Vec<BorshToken>`]) representing
+/// the converted argument value.
+///
+/// # Errors
+///
+/// This function can return an error if there is an issue with converting the raw argument value
+/// to the specified Borsh tokens based on the given IDL type, e.g. an integer out of the range of
+/// its type. The error names the argument and its type.
+pub(crate) fn get_borsh_token_vector(
+    arg_name: &str,
+    arg_value: String,
+    arg_type: &IdlType,
+    custom_types: &Vec<IdlTypeDefinition>,
+) -> Result<Vec<BorshToken>> {
+    encode_json_value(&JsonValue::String(arg_value), arg_type, custom_types).map_err(|e| {
+        anyhow!(
+            "Invalid value for the argument {} of type {}: {}",
+            arg_name,
+            type_name(arg_type),
+            e
+        )
+    })
+}
+
+/// Converts a JSON value into a vector of Borsh tokens based on the provided IDL type.
+///
+/// This is the recursive parser of the arguments: the elements of the arrays and vectors and the
+/// fields of the structs are encoded with it in turn. A JSON string given for a container type is
+/// parsed like a raw argument of [`get_borsh_token_vector`], and the scalars can be given as JSON
+/// strings, numbers or booleans.
+fn encode_json_value(
+    value: &JsonValue,
+    arg_type: &IdlType,
+    custom_types: &Vec<IdlTypeDefinition>,
+) -> Result<Vec<BorshToken>> {
+    match arg_type {
+        IdlType::Defined(ty) => {
+            let defined_type = custom_types
+                .iter()
+                .find(|t| t.name == *ty)
+                .ok_or_else(|| anyhow!("Type definition with name {} not found", ty))?;
+            encode_id_defined_type(value, defined_type, custom_types)
+        }
+        IdlType::Vec(elem_type) => {
+            let mut borsh_args: Vec<BorshToken> = vec![];
+            for elem in container_elements(value, elem_type, custom_types)? {
+                let borsh_arg = encode_json_value(&elem, elem_type, custom_types)?;
+                borsh_args.push(element_token(borsh_arg));
+            }
+            Ok(vec![BorshToken::Array(borsh_args)])
+        }
+        IdlType::Array(elem_type, size) => {
+            let elems = container_elements(value, elem_type, custom_types)?;
+            // Make sure the number of elements matches the size of the array
+            if elems.len() != *size {
+                return Err(anyhow!(
+                    "The number of elements in the array does not match the size of the array. \nProvided argument: {}\n",
+                    raw_value(value)
+                ));
+            }
+            let mut borsh_args: Vec<BorshToken> = vec![];
+            for elem in elems {
+                let borsh_arg = encode_json_value(&elem, elem_type, custom_types)?;
+                borsh_args.push(element_token(borsh_arg));
+            }
+            Ok(vec![BorshToken::FixedArray(borsh_args)])
+        }
+        _ => {
+            let arg_value = match value {
+                JsonValue::String(s) => s.clone(),
+                JsonValue::Number(n) => n.to_string(),
+                JsonValue::Bool(b) => b.to_string(),
+                value => {
+                    return Err(anyhow!(
+                    "The provided argument for {} is not a valid value. \nProvided argument: {}\n",
+                    type_name(arg_type),
+                    value
+                ))
+                }
+            };
+            encode_scalar(arg_value, arg_type)
+        }
+    }
+}
+
+/// Converts a raw scalar value, e.g. an integer or a public key, into a vector of Borsh tokens
+/// based on the provided IDL type.
+fn encode_scalar(arg_value: String, arg_type: &IdlType) -> Result<Vec<BorshToken>> {
+    let mut args: Vec<BorshToken> = vec![];
+    match arg_type {
+        IdlType::Bool => {
+            let val = arg_value.parse::<bool>().map_err(|_e| {
+                anyhow!("The provided argument for bool is not a valid boolean. \nProvided argument: {}\n", arg_value)
+            })?;
+            args.push(BorshToken::Bool(val));
+        }
+        IdlType::U8
+        | IdlType::U16
+        | IdlType::U32
+        | IdlType::U64
+        | IdlType::U128
+        | IdlType::U256 => {
+            args.push(BorshToken::Uint {
+                width: (integer_byte_width(arg_type) * 8) as u16,
+                value: parse_integer(&arg_value, arg_type)?,
+            });
+        }
+        IdlType::I8
+        | IdlType::I16
+        | IdlType::I32
+        | IdlType::I64
+        | IdlType::I128
+        | IdlType::I256 => {
+            args.push(BorshToken::Int {
+                width: (integer_byte_width(arg_type) * 8) as u16,
+                value: parse_integer(&arg_value, arg_type)?,
+            });
+        }
+        IdlType::F32 | IdlType::F64 => {
+            return Err(anyhow!("Float is not supported"));
+        }
+        IdlType::Bytes => {
+            let val = match hex::decode(&arg_value) {
+                Ok(val) => val,
+                Err(_) => {
+                    return Err(anyhow!("The provided argument for Bytes is not a valid hex string. \nProvided argument: {}\n", arg_value))
+                }
+            };
+            args.push(BorshToken::Bytes(val));
+        }
+        IdlType::String => {
+            args.push(BorshToken::String(arg_value.to_string()));
+        }
+        IdlType::PublicKey => {
+            let val = arg_value.from_base58().map_err(|_e| {
+                anyhow!("The provided argument for PublicKey is not a valid base58 string. \nProvided argument: {}\n", arg_value)
+            })?;
+            let val = <[u8; 32]>::try_from(val).map_err(|_e| {
+                anyhow!("The provided argument for PublicKey is not a valid base58 string. \nProvided argument: {}\n", arg_value)
+            })?;
+            args.push(BorshToken::Address(val));
+        }
+        IdlType::Option(_) => {
+            return Err(anyhow!("Option is not supported"));
+        }
+        ty => {
+            return Err(anyhow!("{} is not a scalar type", type_name(ty)));
+        }
+    }
+    Ok(args)
+}
+
+/// Parses an integer argument, and checks that it fits in the width and sign of its type, e.g.
+/// `0..=255` for a `u8`, so that it is not truncated when encoded.
+///
+/// # Errors
+///
+/// This function returns an error if the value is not a decimal integer, or is out of the range
+/// of the type.
+fn parse_integer(arg_value: &str, arg_type: &IdlType) -> Result<BigInt> {
+    let bits = integer_byte_width(arg_type) * 8;
+    let signed = matches!(
+        arg_type,
+        IdlType::I8 | IdlType::I16 | IdlType::I32 | IdlType::I64 | IdlType::I128 | IdlType::I256
+    );
+    let value = BigInt::from_str(arg_value.trim()).map_err(|_e| {
+        anyhow!(
+            "The provided argument for {} is not a valid {} integer. \nProvided argument: {}\n",
+            type_name(arg_type),
+            if signed { "signed" } else { "unsigned" },
+            arg_value
+        )
+    })?;
+    let (min, max) = if signed {
+        (
+            -(BigInt::one() << (bits - 1)),
+            (BigInt::one() << (bits - 1)) - 1,
+        )
+    } else {
+        (BigInt::zero(), (BigInt::one() << bits) - 1)
+    };
+    if value < min || value > max {
+        return Err(anyhow!(
+            "The provided argument for {} is out of range: {} is not between {} and {}. \nProvided argument: {}\n",
+            type_name(arg_type),
+            value,
+            min,
+            max,
+            arg_value
+        ));
+    }
+    Ok(value)
+}
+
+/// The elements of an array or vector argument.
+///
+/// The argument is either a JSON array, or a string: a JSON array too if it starts with `[`, or a
+/// comma-separated list of values otherwise (see [`split_list`]). The comma-separated shorthand is
+/// only accepted for elements of scalar types (e.g. `1,2,3`), since it cannot delimit nested
+/// containers.
+fn container_elements(
+    value: &JsonValue,
+    elem_type: &IdlType,
+    custom_types: &[IdlTypeDefinition],
+) -> Result<Vec<JsonValue>> {
+    match value {
+        JsonValue::Array(elems) => Ok(elems.clone()),
+        JsonValue::String(s) if s.trim_start().starts_with('[') => serde_json::from_str(s)
+            .map_err(|e| {
+                anyhow!(
+                    "The provided argument for {} is not a valid JSON array: {} \nProvided argument: {}\n",
+                    type_name(&IdlType::Vec(Box::new(elem_type.clone()))),
+                    e,
+                    s
+                )
+            }),
+        JsonValue::String(s) if !is_container(elem_type, custom_types) => {
+            Ok(split_list(s)?.into_iter().map(JsonValue::String).collect())
+        }
+        value => Err(anyhow!(
+            "The elements of type {} must be passed as a JSON array, e.g. [[1,2],[3]] or [{{\"a\": 1}}]. \nProvided argument: {}\n",
+            type_name(elem_type),
+            raw_value(value)
+        )),
+    }
+}
+
+/// Splits a comma-separated list of values, e.g. `"a,b",c` or `a\,b,c` into `a,b` and `c`.
+///
+/// An element starting with a double quote is quoted up to the next unescaped double quote, and
+/// may contain commas; `\"` and `\\` are unescaped inside it. Outside of the quotes, `\,` is an
+/// escaped comma, and `\"` and `\\` are unescaped too. Other backslashes are kept as is.
+///
+/// # Errors
+///
+/// This function returns an error if a quoted element is not terminated, or is followed by other
+/// characters than a comma.
+fn split_list(list: &str) -> Result<Vec<String>> {
+    let mut elements = vec![];
+    let mut chars = list.chars().peekable();
+    loop {
+        let mut element = String::new();
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            loop {
+                match chars.next() {
+                    Some('\\') if matches!(chars.peek(), Some('"') | Some('\\')) => {
+                        element.extend(chars.next());
+                    }
+                    Some('"') => break,
+                    Some(c) => element.push(c),
+                    None => {
+                        return Err(anyhow!(
+                            "The quoted element {:?} is not terminated. \nProvided argument: {}\n",
+                            element,
+                            list
+                        ))
+                    }
+                }
+            }
+            match chars.next() {
+                Some(',') => {}
+                None => {
+                    elements.push(element);
+                    return Ok(elements);
+                }
+                Some(c) => {
+                    return Err(anyhow!(
+                        "Unexpected character {:?} after the quoted element {:?}, expected a comma. \nProvided argument: {}\n",
+                        c,
+                        element,
+                        list
+                    ))
+                }
+            }
+        } else {
+            loop {
+                match chars.next() {
+                    Some('\\') if matches!(chars.peek(), Some(',') | Some('"') | Some('\\')) => {
+                        element.extend(chars.next());
+                    }
+                    Some(',') => break,
+                    Some(c) => element.push(c),
+                    None => {
+                        elements.push(element);
+                        return Ok(elements);
+                    }
+                }
+            }
+        }
+        elements.push(element);
+    }
+}
+
+/// The token of an element of an array or vector: the tokens of a struct are grouped in a tuple,
+/// so that the length of the vector is the number of its elements.
+fn element_token(mut tokens: Vec<BorshToken>) -> BorshToken {
+    if tokens.len() == 1 {
+        tokens.remove(0)
+    } else {
+        BorshToken::Tuple(tokens)
+    }
+}
+
+/// Whether a type is a container, whose values are JSON arrays or objects: an array, a vector or
+/// a struct.
+fn is_container(ty: &IdlType, custom_types: &[IdlTypeDefinition]) -> bool {
+    match ty {
+        IdlType::Vec(_) | IdlType::Array(_, _) => true,
+        IdlType::Defined(name) => custom_types
+            .iter()
+            .any(|t| t.name == *name && matches!(t.ty, Struct { .. })),
+        _ => false,
+    }
+}
+
+/// The name of an IDL type in the error messages, e.g. `u8` or `{"vec":"u8"}`.
+fn type_name(ty: &IdlType) -> String {
+    match serde_json::to_value(ty) {
+        Ok(JsonValue::String(name)) => name,
+        Ok(value) => value.to_string(),
+        Err(_) => format!("{:?}", ty),
+    }
+}
+
+/// A JSON value as provided by the user: strings without their quotes.
+fn raw_value(value: &JsonValue) -> String {
+    match value {
+        JsonValue::String(s) => s.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// Converts a JSON value into a vector of Borsh tokens for a custom IDL-defined type.
+///
+/// This function takes a JSON value, a custom IDL type definition, and a list of IDL type
+/// definitions for reference. It then converts the value into a vector of Borsh tokens based on
+/// the specified custom IDL type, including handling structs and enums as per the IDL definition.
+///
+/// # Arguments
+///
+/// * `value` - The value to be converted: a JSON object, or a string of a JSON object, for a
+///   struct, and the name of the variant for an enum.
+///
+/// * `defined_type` - The custom IDL type definition specifying the expected type of the argument.
+///
+/// * `custom_types` - A vector of IDL type definitions used for resolving nested types.
+///
+/// # Returns
+///
+/// Returns a [`Result`] containing the vector of Borsh tokens ([`Vec<BorshToken>`]) representing
+/// the converted argument value.
+fn encode_id_defined_type(
+    value: &JsonValue,
+    defined_type: &IdlTypeDefinition,
+    custom_types: &Vec<IdlTypeDefinition>,
+) -> Result<Vec<BorshToken>> {
+    let mut response: Vec<BorshToken> = vec![];
+    let ty = &defined_type.ty;
+    match ty {
+        Struct { fields } => {
+            // The user should pass a json object
+            // for example: {"a": 1, "b": 2}
+            // we need to parse the json object and then encode it
+            let json_object = match value {
+                JsonValue::String(arg_value) => {
+                    // The user could either pass a JSON object or a path to a JSON file
+                    // If the user passes a path to a JSON file, we need to read the file first
+                    // Note: `@<PATH>` is the preferred way to pass a file, this is kept for compatibility
+                    let arg_value = match std::fs::read_to_string(arg_value) {
+                        Ok(s) => s,
+                        Err(_) => arg_value.clone(),
+                    };
+                    serde_json::from_str(&arg_value).map_err(
+                        |_e| anyhow!("The provided argument for Struct is not a valid JSON object. \nProvided argument: {}\n", arg_value),
+                    )?
+                }
+                value => value.clone(),
+            };
+            let json_object = match json_object {
+                JsonValue::Object(object) => object,
+                value => {
+                    return Err(anyhow!(
+                        "The provided argument for Struct is not a valid JSON object. \nProvided argument: {}\n",
+                        value
+                    ))
+                }
+            };
+            for field in fields {
+                let field_name = &field.name;
+                let field_ty = &field.ty;
+                let field_value = json_object
+                    .get(field_name)
+                    .ok_or_else(|| anyhow!("Field {} not found", field_name))?;
+                let mut borsh_args = match field_ty {
+                    // The byte fields are passed as hex or base64 strings, or arrays of numbers
+                    IdlType::Bytes => {
+                        vec![BorshToken::Bytes(field_bytes(
+                            field_value,
+                            field_name,
+                            field_ty,
+                        )?)]
+                    }
+                    IdlType::Vec(elem_type) if matches!(**elem_type, IdlType::U8) => {
+                        vec![BorshToken::Bytes(field_bytes(
+                            field_value,
+                            field_name,
+                            field_ty,
+                        )?)]
+                    }
+                    IdlType::Array(elem_type, size) if matches!(**elem_type, IdlType::U8) => {
+                        let bytes = field_bytes(field_value, field_name, field_ty)?;
+                        if bytes.len() != *size {
+                            return Err(anyhow!(
+                                "The field {} of type {} has {} bytes instead of {}. \nProvided argument: {}\n",
+                                field_name,
+                                type_name(field_ty),
+                                bytes.len(),
+                                size,
+                                raw_value(field_value)
+                            ));
+                        }
+                        vec![BorshToken::FixedBytes(bytes)]
+                    }
+                    _ => encode_json_value(field_value, field_ty, custom_types)?,
+                };
+                response.append(&mut borsh_args);
+            }
+        }
+        Enum { variants } => {
+            // If the variant is a quoted string, we need to remove the quotes
+            let arg_value = raw_value(value).replace('\"', "");
+            // The user passes a String of the variant name
+            // for example: "A"
+            // we need to find index of the variant and then encode it
+            let variant_index = variants
+                .iter()
+                .position(|v| v.name == arg_value)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Variant {} not found. \nAvailable variants of {}: {:?}",
+                        arg_value,
+                        defined_type.name,
+                        variants
+                            .iter()
+                            .map(|v| v.clone().name)
+                            .collect::<Vec<String>>()
+                    )
+                })?;
+
+            let mut borsh_args = encode_scalar(variant_index.to_string(), &IdlType::U8)?;
+            response.append(&mut borsh_args);
+        }
+    }
+
+    Ok(response)
+}
+
+/// The bytes of a `bytes`, `Vec<u8>` or `[u8; N]` field of a struct, given as a JSON array of
+/// numbers, or as a string: hex, with an optional `0x` prefix, or base64 otherwise.
+///
+/// A string that is both valid hex and valid base64, e.g. `cafe`, is decoded as hex.
+fn field_bytes(value: &JsonValue, field_name: &str, field_ty: &IdlType) -> Result<Vec<u8>> {
+    match value {
+        JsonValue::Array(_) => serde_json::from_value(value.clone()).map_err(|_e| {
+            anyhow!(
+                "The field {} of type {} is not a valid array of bytes. \nProvided argument: {}\n",
+                field_name,
+                type_name(field_ty),
+                value
+            )
+        }),
+        JsonValue::String(s) => {
+            let s = s.trim();
+            hex::decode(s.strip_prefix("0x").unwrap_or(s))
+                .or_else(|_e| STANDARD.decode(s))
+                .map_err(|_e| {
+                    anyhow!(
+                        "The field {} of type {} is not a valid hex or base64 string. \nProvided argument: {}\n",
+                        field_name,
+                        type_name(field_ty),
+                        s
+                    )
+                })
+        }
+        value => Err(anyhow!(
+            "The field {} of type {} must be a hex or base64 string, or an array of bytes. \nProvided argument: {}\n",
+            field_name,
+            type_name(field_ty),
+            value
+        )),
+    }
+}
@@ -0,0 +1,141 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::keystore::{is_keystore, read_keystore_file},
+    anyhow::{anyhow, Result},
+    aqd_utils::register_secret,
+    solana_sdk::{
+        derivation_path::DerivationPath,
+        pubkey::Pubkey,
+        signature::Keypair,
+        signer::keypair::{
+            generate_seed_from_seed_phrase_and_passphrase, keypair_from_seed_and_derivation_path,
+            read_keypair_file,
+        },
+    },
+    std::{fs, path::Path},
+};
+
+/// The derivation path used by default for mnemonic phrases, as in Phantom and Solflare.
+pub const DEFAULT_DERIVATION_PATH: &str = "m/44'/501'/0'/0'";
+
+/// Parse a keypair from any of the supported formats.
+///
+/// The following formats are accepted:
+///
+/// - The path of a keypair file. The file can contain a JSON byte array (as written by
+///   `solana-keygen`), or any of the inline formats below.
+///
+/// - The path of a keystore file encrypted with a password, as written by `aqd solana keygen
+///   --encrypt`. The password is prompted interactively.
+///
+/// - An inline JSON array of the 64 secret key bytes, e.g. `[12,34,...]`.
+///
+/// - A base58 encoded 64 bytes secret key, as exported by wallets like Phantom.
+///
+/// - A BIP39 mnemonic phrase, optionally followed by a derivation path (e.g.
+///   `"word1 ... word12 m/44'/501'/1'/0'"`). The derivation path defaults to
+///   [`DEFAULT_DERIVATION_PATH`]. No passphrase is used.
+///
+/// # Errors
+///
+/// This function returns an error if the source does not match any of the formats, or if the
+/// secret key is invalid.
+///
+/// The secret key, in all its formats, is registered as a secret to be redacted from the output.
+pub fn parse_keypair(source: &str) -> Result<Keypair> {
+    let keypair = if Path::new(source).is_file() {
+        // Keypair files written by solana-keygen
+        match read_keypair_file(source) {
+            Ok(keypair) => keypair,
+            Err(_) => {
+                let content = fs::read_to_string(source)
+                    .map_err(|e| anyhow!("Failed to read keypair file {}: {}", source, e))?;
+                if is_keystore(&content) {
+                    read_keystore_file(Path::new(source), &content)?
+                } else {
+                    parse_inline_keypair(content.trim())
+                        .map_err(|e| anyhow!("Invalid keypair file {}: {}", source, e))?
+                }
+            }
+        }
+    } else {
+        parse_inline_keypair(source.trim())?
+    };
+    register_secret(&keypair.to_base58_string());
+    register_secret(&format!("{:?}", keypair.to_bytes()).replace(' ', ""));
+    Ok(keypair)
+}
+
+/// Parse a public key: base58 encoded, as printed by the Solana tools, or 32 hex encoded bytes
+/// with a `0x` prefix.
+///
+/// # Errors
+///
+/// This function returns an error explaining why the public key is invalid: an invalid encoding,
+/// or a length other than 32 bytes.
+pub fn parse_pubkey(input: &str) -> Result<Pubkey> {
+    let input = input.trim();
+    let bytes = match input.strip_prefix("0x") {
+        Some(hex) => {
+            hex::decode(hex).map_err(|e| anyhow!("Invalid public key {}: {}", input, e))?
+        }
+        None => base58::FromBase58::from_base58(input)
+            .map_err(|e| anyhow!("Invalid public key {}: not base58 encoded ({:?})", input, e))?,
+    };
+    let bytes: [u8; 32] = bytes.try_into().map_err(|bytes: Vec<u8>| {
+        anyhow!(
+            "Invalid public key {}: expected 32 bytes, got {}",
+            input,
+            bytes.len()
+        )
+    })?;
+    Ok(Pubkey::from(bytes))
+}
+
+/// Parse a keypair given inline, as a JSON byte array, a base58 string or a mnemonic phrase.
+fn parse_inline_keypair(source: &str) -> Result<Keypair> {
+    let invalid = || {
+        anyhow!("Not a valid keypair file, JSON byte array, base58 secret key or mnemonic phrase")
+    };
+
+    let bytes = if source.starts_with('[') {
+        serde_json::from_str::<Vec<u8>>(source)
+            .map_err(|e| anyhow!("Invalid JSON keypair byte array: {}", e))?
+    } else if source.split_whitespace().count() > 1 {
+        // Registered before parsing, as the words of a mnemonic phrase are in its errors
+        register_secret(source);
+        return keypair_from_mnemonic(source);
+    } else {
+        base58::FromBase58::from_base58(source).map_err(|_| invalid())?
+    };
+
+    if bytes.len() != 64 {
+        return Err(anyhow!(
+            "Invalid secret key length: expected 64 bytes, got {}",
+            bytes.len()
+        ));
+    }
+    Keypair::from_bytes(&bytes).map_err(|e| anyhow!("Invalid secret key: {}", e))
+}
+
+/// Derive a keypair from a mnemonic phrase, optionally followed by a derivation path.
+fn keypair_from_mnemonic(source: &str) -> Result<Keypair> {
+    let words: Vec<&str> = source.split_whitespace().collect();
+    let (phrase, derivation_path) = match words.split_last() {
+        Some((last, words)) if last.starts_with("m/") => (words.join(" "), *last),
+        _ => (words.join(" "), DEFAULT_DERIVATION_PATH),
+    };
+
+    if ![12, 15, 18, 21, 24].contains(&phrase.split_whitespace().count()) {
+        return Err(anyhow!(
+            "Invalid mnemonic phrase: expected 12, 15, 18, 21 or 24 words"
+        ));
+    }
+
+    let derivation_path = DerivationPath::from_absolute_path_str(derivation_path)
+        .map_err(|e| anyhow!("Invalid derivation path {}: {}", derivation_path, e))?;
+    let seed = generate_seed_from_seed_phrase_and_passphrase(&phrase, "");
+    keypair_from_seed_and_derivation_path(&seed, Some(derivation_path))
+        .map_err(|e| anyhow!("Failed to derive keypair from mnemonic phrase: {}", e))
+}
@@ -0,0 +1,107 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    aqd_utils::{
+        decrypt_secret, encrypt_secret, is_keystore as is_json_keystore, prompt_keystore_password,
+    },
+    serde_json::{json, Value},
+    solana_sdk::{
+        pubkey::Pubkey,
+        signature::{Keypair, Signer},
+    },
+    std::{
+        collections::HashMap,
+        fs,
+        io::Write,
+        path::{Path, PathBuf},
+        sync::Mutex,
+    },
+};
+
+/// The secret keys of the keystores unlocked by the command, by path, so that the password of a
+/// keystore used several times is only prompted once.
+static UNLOCKED_KEYSTORES: Mutex<Option<HashMap<PathBuf, Vec<u8>>>> = Mutex::new(None);
+
+/// Encrypt a keypair with a password, into a keystore.
+///
+/// The 64 secret key bytes are encrypted in the keystore format of aqd, shared with the Polkadot
+/// keystores: PBKDF2-SHA256 and ChaCha20-Poly1305 (see [`encrypt_secret`]), rather than age or
+/// scrypt with AES. The public key is kept in clear, to identify the keypair without unlocking it.
+pub fn encrypt_keypair(keypair: &Keypair, password: &str) -> Result<Value> {
+    let mut keystore = encrypt_secret(keypair.to_bytes().as_slice(), password)?;
+    keystore["pubkey"] = json!(keypair.pubkey().to_string());
+    Ok(keystore)
+}
+
+/// Decrypt the keypair of a keystore with a password.
+///
+/// # Errors
+///
+/// This function returns an error if the keystore is invalid, or if the password is wrong.
+pub fn decrypt_keypair(keystore: &Value, password: &str) -> Result<Keypair> {
+    let bytes = decrypt_secret(keystore, password)?;
+    Keypair::from_bytes(&bytes).map_err(|_| anyhow!("The keystore does not hold a valid keypair"))
+}
+
+/// Check whether the content of a keypair file is a keystore, rather than a plaintext keypair.
+pub fn is_keystore(content: &str) -> bool {
+    serde_json::from_str::<Value>(content)
+        .map(|value| is_json_keystore(&value))
+        .unwrap_or(false)
+}
+
+/// Read the public key of a keystore, kept in clear, without unlocking it. Returns `None` if the
+/// content of the keypair file is not a keystore.
+pub fn keystore_pubkey(content: &str) -> Option<Pubkey> {
+    serde_json::from_str::<Value>(content)
+        .ok()
+        .filter(is_json_keystore)
+        .and_then(|keystore| keystore["pubkey"].as_str().map(str::to_string))
+        .and_then(|pubkey| pubkey.parse().ok())
+}
+
+/// Write a keypair to a keystore file encrypted with a password.
+///
+/// As with `solana-keygen`, the file is only readable by its owner on Unix.
+pub fn write_keystore_file<P: AsRef<Path>>(
+    keypair: &Keypair,
+    path: P,
+    password: &str,
+) -> Result<()> {
+    let path = path.as_ref();
+    let keystore = serde_json::to_string_pretty(&encrypt_keypair(keypair, password)?)?;
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options
+        .open(path)
+        .and_then(|mut file| file.write_all(keystore.as_bytes()))
+        .map_err(|e| anyhow!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Read the keypair of a keystore file, prompting the user for its password.
+///
+/// The password is only prompted the first time a keystore is read by the command.
+pub(crate) fn read_keystore_file(path: &Path, content: &str) -> Result<Keypair> {
+    let key = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if let Some(bytes) = UNLOCKED_KEYSTORES
+        .lock()
+        .unwrap()
+        .as_ref()
+        .and_then(|unlocked| unlocked.get(&key))
+    {
+        return Keypair::from_bytes(bytes).map_err(|e| anyhow!("Invalid secret key: {}", e));
+    }
+
+    let keystore: Value = serde_json::from_str(content)
+        .map_err(|e| anyhow!("Failed to parse keystore {}: {}", path.display(), e))?;
+    let keypair = decrypt_keypair(&keystore, &prompt_keystore_password(path)?)?;
+    UNLOCKED_KEYSTORES
+        .lock()
+        .unwrap()
+        .get_or_insert_with(HashMap::new)
+        .insert(key, keypair.to_bytes().to_vec());
+    Ok(keypair)
+}
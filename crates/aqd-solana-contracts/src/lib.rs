@@ -1,16 +1,91 @@
 // SPDX-License-Identifier: Apache-2.0
 
+#[cfg(feature = "rpc")]
+mod balance_check;
 pub mod borsh_encoding;
+#[cfg(feature = "rpc")]
+mod deploy_manifest;
+#[cfg(feature = "cdylib")]
+pub mod ffi;
+#[cfg(feature = "rpc")]
+mod idl_cache;
+mod idl_discriminators;
+mod instruction_data;
+#[cfg(feature = "rpc")]
+mod keypair;
+#[cfg(feature = "rpc")]
+mod keystore;
+#[cfg(feature = "rpc")]
+mod message_signing;
+#[cfg(feature = "rpc")]
 mod printing_utils;
+#[cfg(feature = "rpc")]
+mod rpc_client;
+#[cfg(feature = "rpc")]
+mod solana_accounts;
+#[cfg(feature = "rpc")]
 mod solana_deploy;
+#[cfg(feature = "rpc")]
 mod solana_transaction;
+#[cfg(feature = "rpc")]
+mod solana_workspace;
+#[cfg(feature = "rpc")]
+mod squads_multisig;
+#[cfg(feature = "test-harness")]
+pub mod test_harness;
+#[cfg(feature = "rpc")]
+mod token_accounts;
+#[cfg(feature = "rpc")]
 mod utils;
 
 pub use {
+    borsh_encoding::{
+        decode_return_data, discriminator, BorshValue, ACCOUNT_NAMESPACE, EVENT_NAMESPACE,
+        GLOBAL_NAMESPACE,
+    },
+    idl_discriminators::IdlDiscriminators,
+    instruction_data::{
+        construct_instruction_data, construct_instruction_data_with_discriminator,
+        resolve_file_argument,
+    },
+};
+
+#[cfg(feature = "rpc")]
+pub use {
+    balance_check::{check_payer_balance, estimate_deploy_cost},
+    deploy_manifest::{DeployManifest, ManifestDeployment, ManifestProgram},
+    idl_cache::{fetch_idl, is_idl_url, AQD_IDL_CACHE_ENV},
+    keypair::{parse_keypair, parse_pubkey, DEFAULT_DERIVATION_PATH},
+    keystore::{
+        decrypt_keypair, encrypt_keypair, is_keystore, keystore_pubkey, write_keystore_file,
+    },
+    message_signing::{message_bytes, sign_message, verify_message, MessageEncoding},
     printing_utils::{
-        decode_instruction_return_data, print_idl_instruction_info, print_transaction_information,
+        compute_units_from_logs, decode_instruction_return_data, print_idl_instruction_info,
+        print_program_account, print_program_accounts, print_simulation_result,
+        print_transaction_information, transaction_explorer_url,
+    },
+    rpc_client::rpc_client,
+    solana_accounts::{
+        account_discriminator, decode_program_account, diff_account, diff_accounts,
+        fetch_program_accounts, snapshot_accounts, stream_program_accounts, AccountDiff,
+        FieldChange, MemcmpFilter, ProgramAccount,
+    },
+    solana_deploy::{deploy_program, deploy_program_with_overrides},
+    solana_transaction::{
+        AccountOverride, InstructionAccount, SimulationOptions, SolanaTransaction,
+    },
+    solana_workspace::{
+        resolve_rpc_url, resolve_wallet, solana_cli_config, SolanaWorkspace, WorkspaceKind,
+        WorkspaceProgram,
+    },
+    squads_multisig::{SquadsMultisig, SquadsProposal, SQUADS_PROGRAM_ID},
+    token_accounts::{
+        associated_token_address, fetch_token_accounts, is_token_program, mint_token_program,
+        TokenAccount, ASSOCIATED_TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID,
+    },
+    utils::{
+        construct_instruction_accounts, construct_instruction_accounts_with_password, find_pda,
+        idl_from_json, idl_with_discriminators_from_json, STDIN_PATH,
     },
-    solana_deploy::deploy_program,
-    solana_transaction::SolanaTransaction,
-    utils::{construct_instruction_accounts, construct_instruction_data, idl_from_json},
 };
@@ -0,0 +1,68 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    solana_sdk::{
+        pubkey::Pubkey,
+        signature::{Keypair, Signature, Signer},
+    },
+    std::{fs, str::FromStr},
+};
+
+/// The encoding of a message to sign or verify.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageEncoding {
+    /// The message is a UTF-8 string, signed as is
+    Utf8,
+    /// The message is a hex string (with an optional `0x` prefix), signed as raw bytes
+    Hex,
+    /// The message is the path of a file, whose raw content is signed
+    File,
+}
+
+impl FromStr for MessageEncoding {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "utf8" | "utf-8" => Ok(MessageEncoding::Utf8),
+            "hex" => Ok(MessageEncoding::Hex),
+            "file" => Ok(MessageEncoding::File),
+            _ => Err(anyhow!(
+                "Invalid message encoding: {}. Supported encodings: utf8, hex, file",
+                s
+            )),
+        }
+    }
+}
+
+/// Get the bytes of a message given its encoding.
+///
+/// Returns an error if the message is not valid hex, or if the file cannot be read.
+pub fn message_bytes(message: &str, encoding: MessageEncoding) -> Result<Vec<u8>> {
+    match encoding {
+        MessageEncoding::Utf8 => Ok(message.as_bytes().to_vec()),
+        MessageEncoding::Hex => hex::decode(message.trim_start_matches("0x"))
+            .map_err(|e| anyhow!("Invalid hex message: {}", e)),
+        MessageEncoding::File => {
+            fs::read(message).map_err(|e| anyhow!("Failed to read message file {}: {}", message, e))
+        }
+    }
+}
+
+/// Sign a message with a keypair, using ed25519 over the raw message bytes.
+pub fn sign_message(keypair: &Keypair, message: &[u8]) -> Signature {
+    keypair.sign_message(message)
+}
+
+/// Verify the ed25519 signature of a message by a public key.
+///
+/// The signature is given as a base58 string, as printed by `sign-message`.
+///
+/// Returns an error if the signature is not a valid base58 signature. Returns `Ok(false)` if the
+/// signature is well-formed but does not match the message and public key.
+pub fn verify_message(pubkey: &Pubkey, signature: &str, message: &[u8]) -> Result<bool> {
+    let signature = Signature::from_str(signature)
+        .map_err(|e| anyhow!("Invalid signature {}: {}", signature, e))?;
+    Ok(signature.verify(pubkey.as_ref(), message))
+}
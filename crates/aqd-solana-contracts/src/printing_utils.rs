@@ -4,27 +4,43 @@
 #![allow(deprecated)]
 
 use {
-    crate::borsh_encoding::decode_at_offset,
-    anchor_syn::idl::{Idl, IdlAccountItem, IdlInstruction, IdlTypeDefinition},
+    crate::{
+        borsh_encoding::{decode_return_data, BorshValue},
+        solana_accounts::{AccountDiff, ProgramAccount},
+        token_accounts::{fetch_token_accounts, TokenAccount},
+    },
+    anchor_syn::idl::{Idl, IdlAccountItem, IdlInstruction, IdlType, IdlTypeDefinition},
     anyhow::{anyhow, Result},
-    aqd_utils::{print_key_value, print_subtitle, print_title, print_value},
-    colored::Colorize,
+    aqd_utils::{
+        configured_explorer, explorer_url, print_key_value, print_subtitle, print_text,
+        print_title, print_value, print_warning, render_table, OutputFormat,
+    },
     serde_json::{json, Map, Value},
-    solana_client::{rpc_client::RpcClient, rpc_config::RpcTransactionConfig},
+    solana_account_decoder::parse_token::TokenAccountType,
+    solana_client::{
+        rpc_client::RpcClient, rpc_config::RpcTransactionConfig,
+        rpc_response::RpcSimulateTransactionResult,
+    },
     solana_sdk::{
         commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature,
         transaction::TransactionVersion::Legacy, transaction::TransactionVersion::Number,
     },
-    solana_transaction_status::{option_serializer::OptionSerializer, UiTransactionEncoding},
+    solana_transaction_status::{
+        option_serializer::OptionSerializer, EncodedTransaction, UiMessage, UiTransactionEncoding,
+    },
+    std::str::FromStr,
+    tracing::warn,
 };
 
 /// Prints information about instructions in an Instruction Description Language (IDL) definition.
 ///
-/// This function takes an [`Idl`] structure, an optional instruction name, and a flag for output format.
+/// This function takes an [`Idl`] structure, an optional instruction name, and an output format.
 /// It provides information about instructions defined in the [`Idl`]. If an instruction name is provided,
 /// it will print details about that specific instruction. Otherwise, it can print information about all
 /// instructions in the [`Idl`]. The information includes the instruction name, documentation,
-/// associated accounts, and arguments. The output format can be either JSON or human-readable.
+/// associated accounts, and arguments. The output format can be human-readable, JSON, YAML or a table.
+/// The table has a row per instruction, with its accounts, arguments and return type, so that the
+/// instructions of a large IDL can be scanned at a glance.
 ///
 /// The function will print information about the instruction, its associated accounts, and arguments based on the
 /// specified output format.
@@ -33,8 +49,27 @@ use {
 ///
 /// * `idl`: A reference to an [`Idl`] structure that defines the instructions.
 /// * `instruction_name`: An optional reference to a specific instruction name to print details for.
-/// * `output_json`: A boolean flag indicating whether to output the information in JSON format.
-pub fn print_idl_instruction_info(idl: &Idl, instruction_name: Option<String>, output_json: bool) {
+/// * `output`: The [`OutputFormat`] of the information.
+pub fn print_idl_instruction_info(
+    idl: &Idl,
+    instruction_name: Option<String>,
+    output: OutputFormat,
+) {
+    if output == OutputFormat::Table {
+        let instructions: Vec<&IdlInstruction> = idl
+            .instructions
+            .iter()
+            .filter(|i| instruction_name.as_ref().is_none_or(|name| i.name == *name))
+            .collect();
+        match (instructions.is_empty(), instruction_name) {
+            (true, Some(instruction_name)) => {
+                print_warning!(format!("Instruction {} not found", instruction_name))
+            }
+            _ => print_text!(instruction_table(&instructions)),
+        }
+        return;
+    }
+
     // If the instruction name is provided, print only that instruction
     if let Some(instruction_name) = instruction_name {
         // Find the instruction with the specified name
@@ -43,43 +78,115 @@ pub fn print_idl_instruction_info(idl: &Idl, instruction_name: Option<String>, o
             .iter()
             .find(|i| i.name == *instruction_name)
         {
-            print_single_instruction_info(instruction, output_json);
+            print_single_instruction_info(instruction, output);
         } else {
-            eprintln!("Instruction {} not found", instruction_name);
+            print_warning!(format!("Instruction {} not found", instruction_name));
         }
     } else {
         // Print all instructions' information
-        if output_json {
-            // This is to ensure that we print only 1 JSON
-            let val = match serde_json::to_string_pretty(&idl.instructions) {
-                Ok(val) => val,
-                Err(e) => {
-                    eprintln!("Error: {}", e);
-                    return;
-                }
-            };
-            println!("{}", val);
+        if output.is_structured() {
+            // This is to ensure that we print only 1 document
+            if let Err(e) = output.print(&idl.instructions) {
+                print_warning!(format!("Error: {}", e));
+            }
         } else {
             for instruction in idl.instructions.iter() {
-                print_single_instruction_info(instruction, output_json);
+                print_single_instruction_info(instruction, output);
             }
         }
     }
 }
 
+/// A table of instructions, with a row per instruction: its name, its accounts with their
+/// constraints, its arguments with their types, and its return type.
+fn instruction_table(instructions: &[&IdlInstruction]) -> String {
+    let header: Vec<String> = ["name", "accounts", "args", "returns"]
+        .iter()
+        .map(|column| column.to_string())
+        .collect();
+    let rows: Vec<Vec<String>> = instructions
+        .iter()
+        .map(|instruction| {
+            let accounts: Vec<String> = instruction
+                .accounts
+                .iter()
+                .map(|account| match account {
+                    IdlAccountItem::IdlAccount(account) => {
+                        let constraints: Vec<&str> = [
+                            (account.is_mut, "mut"),
+                            (account.is_signer, "signer"),
+                            (account.is_optional == Some(true), "optional"),
+                        ]
+                        .iter()
+                        .filter(|(set, _)| *set)
+                        .map(|(_, constraint)| *constraint)
+                        .collect();
+                        match constraints.is_empty() {
+                            true => account.name.clone(),
+                            false => format!("{} ({})", account.name, constraints.join(", ")),
+                        }
+                    }
+                    IdlAccountItem::IdlAccounts(accounts) => format!("{} (nested)", accounts.name),
+                })
+                .collect();
+            let args: Vec<String> = instruction
+                .args
+                .iter()
+                .map(|arg| format!("{}: {}", arg.name, idl_type_name(&arg.ty)))
+                .collect();
+            vec![
+                instruction.name.clone(),
+                accounts.join(", "),
+                args.join(", "),
+                instruction
+                    .returns
+                    .as_ref()
+                    .map(idl_type_name)
+                    .unwrap_or_default(),
+            ]
+        })
+        .collect();
+    render_table(&header, &rows)
+}
+
+/// The name of an IDL type, as written in the IDL, e.g. `vec<u8>` or `[u64; 4]`.
+fn idl_type_name(ty: &IdlType) -> String {
+    fn json_type_name(ty: &Value) -> String {
+        match ty {
+            Value::String(name) => name.clone(),
+            Value::Object(object) if object.len() == 1 => {
+                let (kind, inner) = object.iter().next().unwrap();
+                match (kind.as_str(), inner) {
+                    ("defined", Value::String(name)) => name.clone(),
+                    ("array", Value::Array(array)) if array.len() == 2 => {
+                        format!(
+                            "[{}; {}]",
+                            json_type_name(&array[0]),
+                            json_type_name(&array[1])
+                        )
+                    }
+                    (kind, inner) => format!("{}<{}>", kind, json_type_name(inner)),
+                }
+            }
+            ty => ty.to_string(),
+        }
+    }
+    match serde_json::to_value(ty) {
+        Ok(ty) => json_type_name(&ty),
+        Err(_) => format!("{:?}", ty),
+    }
+}
+
 /// Print detailed information about an instruction.
 ///
-/// This function takes an instruction and an output format flag. It prints comprehensive details
+/// This function takes an instruction and an output format. It prints comprehensive details
 /// about the given instruction, including its name, documentation, associated accounts, and arguments.
-/// The output format can be either JSON or human-readable.
-fn print_single_instruction_info(instruction: &IdlInstruction, output_json: bool) {
-    if output_json {
-        match serde_json::to_string_pretty(&instruction) {
-            Ok(val) => println!("{}", val),
-            Err(e) => {
-                eprintln!("Error: {}", e);
-            }
-        };
+/// The output format can be human-readable, JSON, YAML or a table.
+fn print_single_instruction_info(instruction: &IdlInstruction, output: OutputFormat) {
+    if output.is_structured() {
+        if let Err(e) = output.print(&instruction) {
+            print_warning!(format!("Error: {}", e));
+        }
     } else {
         // Print the instruction name
         print_title!("Instruction name");
@@ -114,7 +221,7 @@ fn print_single_instruction_info(instruction: &IdlInstruction, output_json: bool
                 }
                 IdlAccountItem::IdlAccounts(accounts) => {
                     // Print a warning that this is a nested account
-                    println!("{}", "Nested accounts are not supported".red());
+                    print_warning!("Nested accounts are not supported");
                     let accounts_str = format!("{:?}", accounts);
                     print_key_value!(key, accounts_str);
                 }
@@ -150,7 +257,8 @@ fn print_single_instruction_info(instruction: &IdlInstruction, output_json: bool
 /// * `instruction`: A reference to the [`IdlInstruction`] representing the instruction in the transaction.
 /// * `custom_types`: An array of custom [`IdlTypeDefinition`]s used in the IDL definition.
 /// * `new_accounts`: A reference to a list of new accounts as tuples containing the [`Pubkey`] and keypair file path.
-/// * `output_json`: A boolean flag indicating whether to output the information in JSON format.
+/// * `account_diffs`: The field-level changes to the accounts of the transaction (empty if not tracked).
+/// * `output`: The [`OutputFormat`] of the information.
 ///
 /// The function will print information about the transaction, the associated instruction, its accounts, and arguments
 /// based on the specified output format.
@@ -159,16 +267,16 @@ pub fn print_transaction_information(
     signature: &Signature,
     instruction: &IdlInstruction,
     custom_types: &[IdlTypeDefinition],
-    new_accounts: &Vec<(Pubkey, String)>,
-    output_json: bool,
+    new_accounts: &[(Pubkey, String)],
+    account_diffs: &[AccountDiff],
+    output: OutputFormat,
 ) -> Result<()> {
     // If the instruction has a return value, we need to decode it using the IDL definition
     let decoded_return_data =
-        decode_instruction_return_data(rpc_client, signature, instruction, custom_types)?
-            .unwrap_or("None".to_string());
+        decode_instruction_return_data(rpc_client, signature, instruction, custom_types)?;
 
-    if output_json {
-        // For the JSON output, we need to fetch the transaction details using the RpcTransactionConfig
+    if output.is_structured() {
+        // For the structured output, we need to fetch the transaction details using the RpcTransactionConfig
         // with the encoding set to JSON or JSONParsed
         let config = RpcTransactionConfig {
             encoding: Some(UiTransactionEncoding::Json),
@@ -203,12 +311,55 @@ pub fn print_transaction_information(
 
         transaction_json.insert(
             "decoded_return_data".to_string(),
-            Value::String(decoded_return_data),
+            json!(decoded_return_data),
         );
 
-        // Serialize the modified transaction back to a string
-        let modified_pretty_trans = serde_json::to_string_pretty(&Value::Object(transaction_json))?;
-        println!("{}", modified_pretty_trans);
+        // Report the compute units consumed at the top level, falling back to the logs if the
+        // RPC server does not return them in the transaction meta
+        let compute_units = transaction_info.meta.as_ref().and_then(|meta| {
+            match (&meta.compute_units_consumed, &meta.log_messages) {
+                (OptionSerializer::Some(units), _) => Some(*units),
+                (_, OptionSerializer::Some(logs)) => compute_units_from_logs(logs),
+                _ => None,
+            }
+        });
+        transaction_json.insert("compute_units_consumed".to_string(), json!(compute_units));
+
+        // Same for the token accounts involved in the transaction
+        let account_keys = match &transaction_info.transaction {
+            EncodedTransaction::Json(transaction) => match &transaction.message {
+                UiMessage::Raw(message) => message
+                    .account_keys
+                    .iter()
+                    .filter_map(|key| Pubkey::from_str(key).ok())
+                    .collect(),
+                UiMessage::Parsed(_) => vec![],
+            },
+            _ => vec![],
+        };
+        let token_accounts = involved_token_accounts(rpc_client, &account_keys);
+        if !token_accounts.is_empty() {
+            transaction_json.insert(
+                "token_accounts".to_string(),
+                Value::Array(token_accounts.iter().map(|a| a.to_json()).collect()),
+            );
+        }
+
+        // Same for the account diffs
+        if !account_diffs.is_empty() {
+            transaction_json.insert(
+                "account_diffs".to_string(),
+                Value::Array(account_diffs.iter().map(|d| d.to_json()).collect()),
+            );
+        }
+
+        transaction_json.insert(
+            "explorer_url".to_string(),
+            Value::String(transaction_explorer_url(&rpc_client.url(), signature)),
+        );
+
+        // Serialize the modified transaction in the output format
+        output.print(&Value::Object(transaction_json))?;
     } else {
         let config = RpcTransactionConfig {
             encoding: Some(UiTransactionEncoding::Base64),
@@ -291,6 +442,15 @@ pub fn print_transaction_information(
                     print_key_value!("File name", name);
                 }
             }
+
+            // Print the token accounts involved in the transaction (if any)
+            let token_accounts = involved_token_accounts(rpc_client, account_keys);
+            if !token_accounts.is_empty() {
+                print_title!("Token accounts");
+                for token_account in &token_accounts {
+                    print_token_account(token_account);
+                }
+            }
         } else {
             return Err(anyhow!("Error decoding transaction"));
         }
@@ -309,9 +469,26 @@ pub fn print_transaction_information(
                 print_key_value!("Error", err);
             }
 
+            // Print the compute units consumed, falling back to the logs if the RPC server does
+            // not return them in the transaction meta
+            let compute_units = match (
+                &transaction_status.compute_units_consumed,
+                &transaction_status.log_messages,
+            ) {
+                (OptionSerializer::Some(units), _) => Some(*units),
+                (_, OptionSerializer::Some(logs)) => compute_units_from_logs(logs),
+                _ => None,
+            };
+            if let Some(compute_units) = compute_units {
+                print_key_value!("Compute units", compute_units);
+            }
+
             // Print the transaction return data
             print_title!("Transaction return data");
-            print_value!(decoded_return_data);
+            match &decoded_return_data {
+                Some(value) => print_value!(value),
+                None => print_value!("None"),
+            }
 
             // Print the transaction logs
             let logs = transaction_status.log_messages;
@@ -325,10 +502,300 @@ pub fn print_transaction_information(
                 OptionSerializer::None | OptionSerializer::Skip => {}
             }
         }
+
+        // Print the account diffs (if any)
+        if !account_diffs.is_empty() {
+            print_title!("Account changes");
+            for diff in account_diffs {
+                print_subtitle!(format!(
+                    "Account {} ({})",
+                    diff.pubkey,
+                    diff.account_type.as_deref().unwrap_or("Unknown")
+                ));
+                for change in &diff.changes {
+                    print_key_value!(
+                        change.field,
+                        format!("{} -> {}", change.before, change.after)
+                    );
+                }
+            }
+        }
+
+        print_title!("Explorer");
+        print_value!(transaction_explorer_url(&rpc_client.url(), signature));
     }
     Ok(())
 }
 
+/// The URL of the block explorer page of a transaction sent to the cluster at `rpc_url`.
+///
+/// The explorer is the one of the network of the aqd configuration file whose URL is `rpc_url`, if
+/// any, or else the Solana Explorer with the cluster of `rpc_url`: a custom cluster for the
+/// clusters other than mainnet-beta, devnet and testnet, such as a local validator.
+pub fn transaction_explorer_url(rpc_url: &str, signature: &Signature) -> String {
+    let template = configured_explorer(None, rpc_url).unwrap_or_else(|| {
+        let cluster = match rpc_url.trim_end_matches('/') {
+            "https://api.mainnet-beta.solana.com" => "",
+            "https://api.devnet.solana.com" => "?cluster=devnet",
+            "https://api.testnet.solana.com" => "?cluster=testnet",
+            _ => "?cluster=custom&customUrl={rpc}",
+        };
+        format!("https://explorer.solana.com/tx/{{tx}}{}", cluster)
+    });
+    explorer_url(&template, &signature.to_string(), None, rpc_url)
+}
+
+/// Fetch the token accounts involved in a submitted transaction.
+///
+/// The transaction has already been submitted, so a failure to fetch the token accounts is only
+/// reported as a warning, and their summary is skipped.
+fn involved_token_accounts(rpc_client: &RpcClient, account_keys: &[Pubkey]) -> Vec<TokenAccount> {
+    fetch_token_accounts(rpc_client, account_keys).unwrap_or_else(|e| {
+        warn!(
+            "Failed to fetch the token accounts of the transaction: {:#}",
+            e
+        );
+        vec![]
+    })
+}
+
+/// Print the main fields of a token account, mint or multisig owned by the SPL Token or the SPL
+/// Token-2022 program, along with the names of its extensions.
+fn print_token_account(token_account: &TokenAccount) {
+    let title = match &token_account.parsed {
+        TokenAccountType::Account(_) => "Token account",
+        TokenAccountType::Mint(_) => "Mint",
+        TokenAccountType::Multisig(_) => "Multisig",
+    };
+    print_subtitle!(format!(
+        "{} {} ({})",
+        title,
+        token_account.pubkey,
+        token_account.program_name()
+    ));
+    match &token_account.parsed {
+        TokenAccountType::Account(account) => {
+            print_key_value!("Mint", account.mint);
+            print_key_value!("Owner", account.owner);
+            print_key_value!("Amount", account.token_amount.real_number_string_trimmed());
+        }
+        TokenAccountType::Mint(mint) => {
+            print_key_value!("Supply", mint.supply);
+            print_key_value!("Decimals", mint.decimals);
+            if let Some(mint_authority) = &mint.mint_authority {
+                print_key_value!("Mint authority", mint_authority);
+            }
+        }
+        TokenAccountType::Multisig(multisig) => {
+            print_key_value!(
+                "Signers",
+                format!(
+                    "{} of {}",
+                    multisig.num_required_signers, multisig.num_valid_signers
+                )
+            );
+        }
+    }
+    let extensions = token_account.extension_names();
+    if !extensions.is_empty() {
+        print_key_value!("Extensions", extensions.join(", "));
+    }
+}
+
+/// Print a program account decoded with the program's IDL.
+///
+/// In JSONL mode, the account is printed as a single line of JSON, so that a list of accounts can be
+/// streamed and processed line by line. Otherwise, the account is printed in a human-readable format.
+pub fn print_program_account(account: &ProgramAccount, output_jsonl: bool) -> Result<()> {
+    if output_jsonl {
+        return OutputFormat::Json.print_record(&account.to_json());
+    }
+
+    print_title!(format!("Account {}", account.pubkey));
+    print_key_value!("Lamports", account.account.lamports);
+    print_key_value!("Data length", account.account.data.len());
+    print_key_value!(
+        "Account type",
+        account.account_type.as_deref().unwrap_or("Unknown")
+    );
+    if let Some(decoded_data) = &account.decoded_data {
+        print_subtitle!("Decoded data");
+        for line in serde_json::to_string_pretty(decoded_data)?.lines() {
+            print_value!(line);
+        }
+    }
+    if let Some(decode_error) = &account.decode_error {
+        print_key_value!("Decode error", decode_error);
+    }
+
+    Ok(())
+}
+
+/// Print a list of program accounts in an output format.
+///
+/// The table has a row per account, with its decoded data as compact JSON, so that the accounts
+/// can be scanned at a glance. The JSON and YAML documents list the JSON representations of the
+/// accounts, and the human output prints each account with [`print_program_account`].
+pub fn print_program_accounts(accounts: &[ProgramAccount], output: OutputFormat) -> Result<()> {
+    match output {
+        OutputFormat::Human => {
+            for account in accounts {
+                print_program_account(account, false)?;
+            }
+        }
+        OutputFormat::Table => {
+            let header: Vec<String> = ["pubkey", "lamports", "data_len", "account_type", "data"]
+                .iter()
+                .map(|column| column.to_string())
+                .collect();
+            let rows: Vec<Vec<String>> = accounts
+                .iter()
+                .map(|account| {
+                    let data = match (&account.decoded_data, &account.decode_error) {
+                        (Some(decoded_data), _) => decoded_data.to_string(),
+                        (None, Some(decode_error)) => format!("Decode error: {}", decode_error),
+                        (None, None) => String::new(),
+                    };
+                    vec![
+                        account.pubkey.to_string(),
+                        account.account.lamports.to_string(),
+                        account.account.data.len().to_string(),
+                        account.account_type.clone().unwrap_or_default(),
+                        data,
+                    ]
+                })
+                .collect();
+            print_text!(render_table(&header, &rows));
+        }
+        OutputFormat::Json | OutputFormat::Yaml => {
+            let records: Vec<Value> = accounts.iter().map(ProgramAccount::to_json).collect();
+            output.print(&records)?;
+        }
+    }
+    Ok(())
+}
+
+/// Print the result of a transaction simulation.
+///
+/// The function prints the simulation status, the error (if any), the compute units consumed, the
+/// decoded return data, and the logs. The output format can be human-readable, JSON, YAML or a
+/// table.
+///
+/// # Arguments
+///
+/// * `result`: A reference to the [`RpcSimulateTransactionResult`] returned by the RPC server.
+/// * `instruction`: A reference to the [`IdlInstruction`] that was simulated.
+/// * `custom_types`: An array of custom [`IdlTypeDefinition`]s used in the IDL definition.
+/// * `output`: The [`OutputFormat`] of the information.
+pub fn print_simulation_result(
+    result: &RpcSimulateTransactionResult,
+    instruction: &IdlInstruction,
+    custom_types: &[IdlTypeDefinition],
+    output: OutputFormat,
+) -> Result<()> {
+    // Decode the return data if the instruction has a return value
+    let decoded_return_data = match (&instruction.returns, &result.return_data) {
+        (Some(ty), Some(return_data)) => Some(decode_base64_return_data(
+            &return_data.data.0,
+            ty,
+            custom_types,
+        )?),
+        _ => None,
+    };
+
+    // Get the compute units consumed, falling back to the logs for older RPC servers
+    let compute_units = result
+        .units_consumed
+        .or_else(|| result.logs.as_deref().and_then(compute_units_from_logs));
+
+    if output.is_structured() {
+        let mut simulation_json: Map<String, Value> =
+            serde_json::from_str(&serde_json::to_string(result)?)?;
+        simulation_json.insert(
+            "decoded_return_data".to_string(),
+            json!(decoded_return_data),
+        );
+        simulation_json.insert("compute_units_consumed".to_string(), json!(compute_units));
+        output.print(&Value::Object(simulation_json))?;
+    } else {
+        print_title!("Simulation status");
+        match &result.err {
+            None => {
+                print_key_value!("Status", "Ok");
+            }
+            Some(err) => {
+                print_key_value!("Status", "Error");
+                print_key_value!("Error", err);
+            }
+        }
+        if let Some(compute_units) = compute_units {
+            print_key_value!("Compute units", compute_units);
+        }
+
+        print_title!("Simulation return data");
+        match &decoded_return_data {
+            Some(value) => print_value!(value),
+            None => print_value!("None"),
+        }
+
+        if let Some(logs) = &result.logs {
+            print_subtitle!("Logs");
+            for log in logs {
+                print_value!(log);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Parse the total compute units consumed by a transaction from its logs.
+///
+/// The runtime logs `Program <ID> consumed <N> of <M> compute units` when each program invocation
+/// completes. Only the top level invocations are counted, since the units consumed by a cross-program
+/// invocation are included in those of its caller.
+///
+/// Returns `None` if the logs do not contain any compute units.
+pub fn compute_units_from_logs(logs: &[String]) -> Option<u64> {
+    let mut depth: usize = 0;
+    let mut total: Option<u64> = None;
+    for log in logs {
+        // Skip the program logs (e.g. `Program log: ...`), which are not emitted by the runtime
+        let mut words = log.split_whitespace();
+        if words.next() != Some("Program")
+            || words
+                .next()
+                .and_then(|id| Pubkey::from_str(id).ok())
+                .is_none()
+        {
+            continue;
+        }
+        match words.next() {
+            Some("invoke") => depth += 1,
+            Some("success") | Some("failed:") => depth = depth.saturating_sub(1),
+            Some("consumed") if depth == 1 => {
+                if let Some(units) = words.next().and_then(|units| units.parse::<u64>().ok()) {
+                    total = Some(total.unwrap_or(0) + units);
+                }
+            }
+            _ => {}
+        }
+    }
+    total
+}
+
+/// Decode base64 encoded return data given the return type of the instruction.
+fn decode_base64_return_data(
+    data: &str,
+    ty: &IdlType,
+    custom_types: &[IdlTypeDefinition],
+) -> Result<BorshValue> {
+    // Deserialize the data from base64
+    let data = base64::decode(data)
+        .map_err(|e| anyhow!("Error decoding transaction return data: {}", e))?;
+    decode_return_data(&data, ty, custom_types)
+}
+
 /// Decode the return data from a Solana instruction.
 ///
 /// Given the `rpc_client`, `signature` of the transaction containing the instruction, the
@@ -340,8 +807,8 @@ pub fn print_transaction_information(
 /// return." The encoded data is Base64 encoded, and this function decodes it and attempts to
 /// deserialize it according to the provided `instruction` and `custom_types`.
 ///
-/// If successful, it returns the decoded data as a string wrapped in an `Ok` variant. If any error
-/// occurs during the decoding process, it returns an `Err` variant containing an error message.
+/// If successful, it returns the decoded data as a [`BorshValue`] wrapped in an `Ok` variant. If any
+/// error occurs during the decoding process, it returns an `Err` variant containing an error message.
 ///
 /// # Parameters
 ///
@@ -352,15 +819,15 @@ pub fn print_transaction_information(
 ///
 /// # Returns
 ///
-/// - `Ok(Some(result))`: The decoded return data as a string if successful.
-/// - `Ok(None)`: If the instruction has no return value.
+/// - `Ok(Some(result))`: The decoded return data if successful.
+/// - `Ok(None)`: If the instruction has no return value, or did not return any data.
 /// - `Err(error)`: If an error occurs during the decoding process.
 pub fn decode_instruction_return_data(
     rpc_client: &RpcClient,
     signature: &Signature,
     instruction: &IdlInstruction,
     custom_types: &[IdlTypeDefinition],
-) -> Result<Option<String>> {
+) -> Result<Option<BorshValue>> {
     // If the instruction has no return value, return None
     let ty = instruction.returns.as_ref();
     if ty.is_none() {
@@ -390,29 +857,25 @@ pub fn decode_instruction_return_data(
 
     let logs = transaction_meta.log_messages;
 
-    let mut response = String::new();
-    match logs {
-        OptionSerializer::Some(val) => {
-            for log in val {
-                if log.contains("Program return") {
-                    // A sample log message containing return data:
-                    // "Program return: FiyfwwVZjuC2GE15X68fpKdA9SukqB7bk472FageXVGv AQ=="
-                    // We need to extract the base64 encoded data
-                    let data = log.split_whitespace().last().ok_or_else(|| {
-                        anyhow!("Error extracting transaction return data from log")
-                    })?;
-                    // Deserialize the data from base64
-                    let data = base64::decode(data)
-                        .map_err(|e| anyhow!("Error decoding transaction return data: {}", e))?;
-                    let data = data.as_slice();
-                    let mut offset = 0;
-                    let data = decode_at_offset(data, &mut offset, ty, custom_types).to_string();
-                    response.push_str(&data);
-                }
-            }
+    // The return data of the instruction is the last one set, by the program or by one it invoked
+    let data = match logs {
+        OptionSerializer::Some(val) => val
+            .into_iter()
+            .rev()
+            .find(|log| log.contains("Program return")),
+        OptionSerializer::None | OptionSerializer::Skip => None,
+    };
+    match data {
+        Some(log) => {
+            // A sample log message containing return data:
+            // "Program return: FiyfwwVZjuC2GE15X68fpKdA9SukqB7bk472FageXVGv AQ=="
+            // We need to extract the base64 encoded data
+            let data = log
+                .split_whitespace()
+                .last()
+                .ok_or_else(|| anyhow!("Error extracting transaction return data from log"))?;
+            Ok(Some(decode_base64_return_data(data, ty, custom_types)?))
         }
-        OptionSerializer::None | OptionSerializer::Skip => {}
+        None => Ok(None),
     }
-
-    Ok(Some(response))
 }
@@ -0,0 +1,256 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    aqd_utils::{
+        cassette_mode, rate_limit_delay, record_interaction, register_secret_url,
+        replay_interaction, rpc_headers, rpc_timeout, CassetteMode, Interaction,
+    },
+    async_trait::async_trait,
+    base58::FromBase58,
+    base64::{engine::general_purpose::STANDARD, Engine},
+    reqwest::{
+        header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE, RETRY_AFTER},
+        StatusCode,
+    },
+    serde_json::{json, Value},
+    solana_client::{
+        client_error::{ClientErrorKind, Result as ClientResult},
+        rpc_client::{RpcClient, RpcClientConfig},
+        rpc_request::RpcRequest,
+        rpc_sender::{RpcSender, RpcTransportStats},
+    },
+    solana_rpc_client::http_sender::HttpSender,
+    solana_rpc_client_api::{
+        custom_error::{
+            NodeUnhealthyErrorData, JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY,
+            JSON_RPC_SERVER_ERROR_SEND_TRANSACTION_PREFLIGHT_FAILURE,
+        },
+        request::{RpcError, RpcResponseErrorData},
+        response::RpcSimulateTransactionResult,
+    },
+    solana_sdk::{commitment_config::CommitmentConfig, signature::Signature},
+    std::{
+        sync::atomic::{AtomicU64, Ordering},
+        time::Duration,
+    },
+    tracing::debug,
+};
+
+/// The chain of the interactions of the Solana RPC requests in a cassette.
+const CHAIN: &str = "solana";
+
+/// Create a client of the Solana RPC server at `url`.
+///
+/// With `--record`, the requests and their responses are recorded to the cassette, and with
+/// `--replay`, the responses are replayed from the cassette without connecting to the server.
+/// Each request times out after `--rpc-timeout`, and is sent with the headers of `--rpc-header`,
+/// once allowed by the rate limit of `--rate-limit`.
+pub fn rpc_client(url: String, commitment: CommitmentConfig) -> RpcClient {
+    rpc_client_with_config(url, RpcClientConfig::with_commitment(commitment))
+}
+
+/// Create a client of the Solana RPC server at `url`, with a configuration.
+///
+/// See [`rpc_client`].
+pub(crate) fn rpc_client_with_config(url: String, config: RpcClientConfig) -> RpcClient {
+    register_secret_url(&url);
+    let headers = rpc_headers();
+    let sender = CassetteSender {
+        http: match cassette_mode() {
+            Some(CassetteMode::Replay(_)) => None,
+            _ if headers.is_empty() => Some(Box::new(HttpSender::new_with_timeout(
+                url.clone(),
+                rpc_timeout(),
+            ))),
+            _ => Some(Box::new(HeadersSender::new(url.clone(), &headers))),
+        },
+        url,
+    };
+    RpcClient::new_sender(sender, config)
+}
+
+/// A sender of the RPC requests recording them to the cassette, if any, or replaying them from it
+/// without an HTTP sender.
+struct CassetteSender {
+    http: Option<Box<dyn RpcSender + Send + Sync>>,
+    url: String,
+}
+
+#[async_trait]
+impl RpcSender for CassetteSender {
+    async fn send(&self, request: RpcRequest, params: Value) -> ClientResult<Value> {
+        let method = request.to_string();
+        let Some(http) = &self.http else {
+            let result = replay_interaction(CHAIN, &method, &params)
+                .and_then(|interaction| interaction.response())
+                .map_err(|e| ClientErrorKind::Custom(e.to_string()))?;
+            // The client checks that the signature returned is the one of the transaction sent,
+            // which differs from the recorded one if the transaction has a new keypair
+            return match (request, sent_signature(&params)) {
+                (RpcRequest::SendTransaction, Some(signature)) => Ok(json!(signature.to_string())),
+                _ => Ok(result),
+            };
+        };
+
+        let delay = rate_limit_delay();
+        if !delay.is_zero() {
+            debug!(?delay, "Waiting for the rate limit of the RPC requests");
+            tokio::time::sleep(delay).await;
+        }
+        let result = http.send(request, params.clone()).await;
+        if cassette_mode().is_some() {
+            let response = match &result {
+                Ok(result) => Ok(result.clone()),
+                Err(e) => Err(Value::String(e.to_string())),
+            };
+            record_interaction(Interaction::new(CHAIN, &method, params, response));
+        }
+        result
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.http
+            .as_ref()
+            .map(|http| http.get_transport_stats())
+            .unwrap_or_default()
+    }
+
+    fn url(&self) -> String {
+        self.url.clone()
+    }
+}
+
+/// A sender of the RPC requests over HTTP with custom headers, which the sender of the Solana
+/// client does not support. It handles the responses as the sender of the Solana client does,
+/// retrying the rate limited requests, and decoding the data of the errors.
+struct HeadersSender {
+    client: reqwest::Client,
+    url: String,
+    request_id: AtomicU64,
+}
+
+impl HeadersSender {
+    /// The number of times a rate limited request is retried.
+    const TOO_MANY_REQUESTS_RETRIES: usize = 5;
+
+    /// Create a sender of the RPC requests to `url` with `headers`.
+    ///
+    /// The invalid headers are already rejected when parsing `--rpc-header`.
+    fn new(url: String, headers: &[aqd_utils::RpcHeader]) -> Self {
+        let mut default_headers = HeaderMap::new();
+        for header in headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(header.name.as_bytes()),
+                HeaderValue::from_str(&header.value),
+            ) {
+                default_headers.append(name, value);
+            }
+        }
+        let client = reqwest::Client::builder()
+            .default_headers(default_headers)
+            .timeout(rpc_timeout())
+            .pool_idle_timeout(rpc_timeout())
+            .build()
+            .expect("build rpc client");
+        HeadersSender {
+            client,
+            url,
+            request_id: AtomicU64::new(0),
+        }
+    }
+}
+
+#[async_trait]
+impl RpcSender for HeadersSender {
+    async fn send(&self, request: RpcRequest, params: Value) -> ClientResult<Value> {
+        let request_id = self.request_id.fetch_add(1, Ordering::Relaxed);
+        let request_json = request.build_request_json(request_id, params).to_string();
+        let mut retries = Self::TOO_MANY_REQUESTS_RETRIES;
+        loop {
+            let response = self
+                .client
+                .post(&self.url)
+                .header(CONTENT_TYPE, "application/json")
+                .body(request_json.clone())
+                .send()
+                .await
+                .map_err(ClientErrorKind::Reqwest)?;
+            if response.status() == StatusCode::TOO_MANY_REQUESTS && retries > 0 {
+                let duration = response
+                    .headers()
+                    .get(RETRY_AFTER)
+                    .and_then(|retry_after| retry_after.to_str().ok())
+                    .and_then(|retry_after| retry_after.parse::<u64>().ok())
+                    .map_or(Duration::from_millis(500), Duration::from_secs);
+                debug!(?duration, "Rate limited by the RPC server, retrying");
+                retries -= 1;
+                tokio::time::sleep(duration).await;
+                continue;
+            }
+            let response = response
+                .error_for_status()
+                .map_err(ClientErrorKind::Reqwest)?;
+            let mut json = response
+                .json::<Value>()
+                .await
+                .map_err(ClientErrorKind::Reqwest)?;
+            if !json["error"].is_object() {
+                return Ok(json["result"].take());
+            }
+
+            let error = &json["error"];
+            let (Some(code), Some(message)) = (error["code"].as_i64(), error["message"].as_str())
+            else {
+                return Err(ClientErrorKind::RpcError(RpcError::RpcRequestError(format!(
+                    "Failed to deserialize RPC error response: {}",
+                    error
+                )))
+                .into());
+            };
+            let data = match code {
+                JSON_RPC_SERVER_ERROR_SEND_TRANSACTION_PREFLIGHT_FAILURE => {
+                    serde_json::from_value::<RpcSimulateTransactionResult>(error["data"].clone())
+                        .map_or(
+                            RpcResponseErrorData::Empty,
+                            RpcResponseErrorData::SendTransactionPreflightFailure,
+                        )
+                }
+                JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY => {
+                    serde_json::from_value::<NodeUnhealthyErrorData>(error["data"].clone()).map_or(
+                        RpcResponseErrorData::Empty,
+                        |data| RpcResponseErrorData::NodeUnhealthy {
+                            num_slots_behind: data.num_slots_behind,
+                        },
+                    )
+                }
+                _ => RpcResponseErrorData::Empty,
+            };
+            return Err(ClientErrorKind::RpcError(RpcError::RpcResponseError {
+                code,
+                message: message.to_string(),
+                data,
+            })
+            .into());
+        }
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        RpcTransportStats::default()
+    }
+
+    fn url(&self) -> String {
+        self.url.clone()
+    }
+}
+
+/// The signature of the transaction of a `sendTransaction` request, encoded in base64 or in
+/// base58 as set in its configuration.
+fn sent_signature(params: &Value) -> Option<Signature> {
+    let encoded = params[0].as_str()?;
+    let bytes = match params[1]["encoding"].as_str() {
+        Some("base64") => STANDARD.decode(encoded).ok()?,
+        _ => encoded.from_base58().ok()?,
+    };
+    // The signatures follow their number, encoded on a single byte as there are less than 128
+    Signature::try_from(bytes.get(1..65)?).ok()
+}
@@ -0,0 +1,477 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::{
+        borsh_encoding::{discriminator, try_decode_at_offset, BorshValue, ACCOUNT_NAMESPACE},
+        idl_discriminators::IdlDiscriminators,
+    },
+    anchor_syn::idl::{Idl, IdlType},
+    anyhow::{anyhow, Result},
+    base64::{engine::general_purpose::STANDARD, Engine},
+    serde_json::{json, Value},
+    solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig},
+    solana_client::{
+        rpc_client::RpcClient,
+        rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+        rpc_filter::{Memcmp, RpcFilterType},
+        rpc_request::MAX_MULTIPLE_ACCOUNTS,
+    },
+    solana_sdk::{account::Account, pubkey::Pubkey},
+    std::str::FromStr,
+};
+
+/// A `memcmp` filter for program accounts, matching `bytes` at `offset` in the account data.
+///
+/// Parsed from a string of the form `<OFFSET>:<BASE58_BYTES>`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MemcmpFilter {
+    pub offset: usize,
+    pub bytes: Vec<u8>,
+}
+
+impl FromStr for MemcmpFilter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (offset, bytes) = s.split_once(':').ok_or_else(|| {
+            anyhow!(
+                "Invalid memcmp filter: {}. Expected format: <OFFSET>:<BASE58_BYTES>",
+                s
+            )
+        })?;
+        let offset = offset
+            .parse::<usize>()
+            .map_err(|e| anyhow!("Invalid memcmp filter offset {}: {}", offset, e))?;
+        let bytes = base58::FromBase58::from_base58(bytes)
+            .map_err(|_e| anyhow!("Invalid memcmp filter bytes {}: not a base58 string", bytes))?;
+        Ok(MemcmpFilter { offset, bytes })
+    }
+}
+
+/// An account owned by a program, decoded using the accounts section of the program's IDL.
+#[derive(Clone, Debug)]
+pub struct ProgramAccount {
+    /// The address of the account
+    pub pubkey: Pubkey,
+    /// The account as returned by the RPC server
+    pub account: Account,
+    /// The name of the IDL account type matching the account data, if any
+    pub account_type: Option<String>,
+    /// The decoded account data, if the account type is known and the data could be decoded
+    pub decoded_data: Option<Value>,
+    /// The reason the account data could not be decoded, if any
+    pub decode_error: Option<String>,
+}
+
+impl ProgramAccount {
+    /// Returns a JSON representation of the account.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "pubkey": self.pubkey.to_string(),
+            "lamports": self.account.lamports,
+            "data_len": self.account.data.len(),
+            "account_type": self.account_type,
+            "decoded_data": self.decoded_data,
+            "decode_error": self.decode_error,
+        })
+    }
+}
+
+/// Compute the discriminator of an IDL account type.
+///
+/// This is the first 8 bytes of `sha256("account:<AccountName>")`, which is stored at the start of
+/// the data of accounts of that type.
+pub fn account_discriminator(account_name: &str) -> Vec<u8> {
+    discriminator(ACCOUNT_NAMESPACE, account_name)
+}
+
+/// Fetch all the accounts owned by a program, and decode them using the program's IDL.
+///
+/// The accounts can be filtered by data size and `memcmp` filters, which are evaluated by the RPC
+/// server. If `account_type` is provided, only accounts starting with the discriminator of that IDL
+/// account type are returned: the one embedded in the IDL JSON, if any, or the computed one (see
+/// [`IdlDiscriminators`]).
+///
+/// # Errors
+///
+/// This function returns an error if `account_type` is not defined in the IDL, or if the RPC server
+/// fails to return the program accounts. Accounts that cannot be decoded are still returned, with
+/// the reason stored in [`ProgramAccount::decode_error`].
+pub fn fetch_program_accounts(
+    rpc_client: &RpcClient,
+    program_id: &Pubkey,
+    idl: &Idl,
+    discriminators: &IdlDiscriminators,
+    data_size: Option<u64>,
+    memcmp: &[MemcmpFilter],
+    account_type: Option<&str>,
+) -> Result<Vec<ProgramAccount>> {
+    let config = program_accounts_config(
+        rpc_client,
+        idl,
+        discriminators,
+        data_size,
+        memcmp,
+        account_type,
+    )?;
+    let accounts = rpc_client
+        .get_program_accounts_with_config(program_id, config)
+        .map_err(|e| anyhow!("Error fetching program accounts: {}", e))?;
+
+    Ok(accounts
+        .into_iter()
+        .map(|(pubkey, account)| decode_program_account(idl, discriminators, pubkey, account))
+        .collect())
+}
+
+/// Fetch the accounts owned by a program like [`fetch_program_accounts`], passing each decoded
+/// account to `on_account` as soon as its page is fetched, rather than once all are.
+///
+/// The addresses of the accounts are fetched first, without their data, and the accounts are then
+/// fetched by pages of [`MAX_MULTIPLE_ACCOUNTS`] with `getMultipleAccounts`. Accounts closed in
+/// between are skipped.
+///
+/// # Errors
+///
+/// In addition to the errors of [`fetch_program_accounts`], this function returns an error if a
+/// page cannot be fetched, or the first error returned by `on_account`.
+#[allow(clippy::too_many_arguments)]
+pub fn stream_program_accounts(
+    rpc_client: &RpcClient,
+    program_id: &Pubkey,
+    idl: &Idl,
+    discriminators: &IdlDiscriminators,
+    data_size: Option<u64>,
+    memcmp: &[MemcmpFilter],
+    account_type: Option<&str>,
+    mut on_account: impl FnMut(ProgramAccount) -> Result<()>,
+) -> Result<()> {
+    let mut config = program_accounts_config(
+        rpc_client,
+        idl,
+        discriminators,
+        data_size,
+        memcmp,
+        account_type,
+    )?;
+    // The filters still apply to the whole data, but none of it is returned
+    config.account_config.data_slice = Some(UiDataSliceConfig {
+        offset: 0,
+        length: 0,
+    });
+    let pubkeys: Vec<Pubkey> = rpc_client
+        .get_program_accounts_with_config(program_id, config)
+        .map_err(|e| anyhow!("Error fetching program accounts: {}", e))?
+        .into_iter()
+        .map(|(pubkey, _)| pubkey)
+        .collect();
+
+    for page in pubkeys.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let accounts = rpc_client
+            .get_multiple_accounts_with_commitment(page, rpc_client.commitment())
+            .map_err(|e| anyhow!("Error fetching program accounts: {}", e))?
+            .value;
+        for (pubkey, account) in page.iter().zip(accounts) {
+            if let Some(account) = account {
+                on_account(decode_program_account(
+                    idl,
+                    discriminators,
+                    *pubkey,
+                    account,
+                ))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Build the configuration of `getProgramAccounts` for the filters of [`fetch_program_accounts`].
+fn program_accounts_config(
+    rpc_client: &RpcClient,
+    idl: &Idl,
+    discriminators: &IdlDiscriminators,
+    data_size: Option<u64>,
+    memcmp: &[MemcmpFilter],
+    account_type: Option<&str>,
+) -> Result<RpcProgramAccountsConfig> {
+    let mut filters: Vec<RpcFilterType> = vec![];
+    if let Some(data_size) = data_size {
+        filters.push(RpcFilterType::DataSize(data_size));
+    }
+    for filter in memcmp {
+        filters.push(RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            filter.offset,
+            &filter.bytes,
+        )));
+    }
+    if let Some(account_type) = account_type {
+        if !idl.accounts.iter().any(|a| a.name == account_type) {
+            return Err(anyhow!(
+                "Account type {} not found in the IDL. Available account types: {:?}",
+                account_type,
+                idl.accounts.iter().map(|a| &a.name).collect::<Vec<_>>()
+            ));
+        }
+        filters.push(RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            0,
+            &discriminators.account(account_type),
+        )));
+    }
+
+    Ok(RpcProgramAccountsConfig {
+        filters: if filters.is_empty() {
+            None
+        } else {
+            Some(filters)
+        },
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(rpc_client.commitment()),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    })
+}
+
+/// Decode the data of a program account using the accounts section of the IDL.
+///
+/// The account type is identified by the discriminator at the start of the data: the one embedded
+/// in the IDL JSON, if any, or the 8 byte anchor discriminator of the account type.
+pub fn decode_program_account(
+    idl: &Idl,
+    discriminators: &IdlDiscriminators,
+    pubkey: Pubkey,
+    account: Account,
+) -> ProgramAccount {
+    let account_type = idl.accounts.iter().find_map(|account_type| {
+        let discriminator = discriminators.account(&account_type.name);
+        account
+            .data
+            .starts_with(&discriminator)
+            .then_some((account_type, discriminator.len()))
+    });
+
+    let (decoded_data, decode_error) = match account_type {
+        Some((account_type, discriminator_len)) => {
+            // The account type definitions are looked up in both the accounts and the types sections
+            let mut custom_types = idl.types.clone();
+            custom_types.extend(idl.accounts.iter().cloned());
+            let ty = IdlType::Defined(account_type.name.clone());
+            let mut offset = discriminator_len;
+            match try_decode_at_offset(&account.data, &mut offset, &ty, &custom_types) {
+                Ok(token) => {
+                    let value = BorshValue::from_token(token, &ty, &custom_types);
+                    (Some(json!(value)), None)
+                }
+                Err(e) => (None, Some(e.to_string())),
+            }
+        }
+        None => (
+            None,
+            Some("The account data does not match any account type of the IDL".to_string()),
+        ),
+    };
+
+    ProgramAccount {
+        pubkey,
+        account,
+        account_type: account_type.map(|(account_type, _)| account_type.name.clone()),
+        decoded_data,
+        decode_error,
+    }
+}
+
+/// A change to a single field of an account, as reported by [`diff_account`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldChange {
+    /// The path of the field, e.g. `lamports` or `data.authority`
+    pub field: String,
+    /// The value before the change (`null` if the field did not exist)
+    pub before: Value,
+    /// The value after the change (`null` if the field no longer exists)
+    pub after: Value,
+}
+
+/// The field-level changes to an account between two snapshots.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccountDiff {
+    /// The address of the account
+    pub pubkey: Pubkey,
+    /// The name of the IDL account type of the account, if known
+    pub account_type: Option<String>,
+    /// The fields that changed, in the order they appear in the account
+    pub changes: Vec<FieldChange>,
+}
+
+impl AccountDiff {
+    /// Returns a JSON representation of the account diff.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "pubkey": self.pubkey.to_string(),
+            "account_type": self.account_type,
+            "changes": self
+                .changes
+                .iter()
+                .map(|change| {
+                    json!({
+                        "field": change.field,
+                        "before": change.before,
+                        "after": change.after,
+                    })
+                })
+                .collect::<Vec<Value>>(),
+        })
+    }
+}
+
+/// Fetch a set of accounts and decode them using the program's IDL.
+///
+/// The accounts are fetched by pages of [`MAX_MULTIPLE_ACCOUNTS`] with `getMultipleAccounts`.
+/// Accounts that do not exist are returned as `None`. Accounts that are not owned by the program, or
+/// whose data cannot be decoded, are returned with the reason stored in
+/// [`ProgramAccount::decode_error`].
+///
+/// # Errors
+///
+/// This function returns an error if the RPC server fails to return the accounts.
+pub fn snapshot_accounts(
+    rpc_client: &RpcClient,
+    idl: &Idl,
+    discriminators: &IdlDiscriminators,
+    pubkeys: &[Pubkey],
+) -> Result<Vec<(Pubkey, Option<ProgramAccount>)>> {
+    let mut snapshot = Vec::with_capacity(pubkeys.len());
+    for page in pubkeys.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let accounts = rpc_client
+            .get_multiple_accounts(page)
+            .map_err(|e| anyhow!("Error fetching accounts: {}", e))?;
+        snapshot.extend(page.iter().zip(accounts).map(|(pubkey, account)| {
+            (
+                *pubkey,
+                account
+                    .map(|account| decode_program_account(idl, discriminators, *pubkey, account)),
+            )
+        }));
+    }
+    Ok(snapshot)
+}
+
+/// Compute the field-level diff of an account between two snapshots.
+///
+/// The lamports, owner and data length are always compared. If the account data was decoded in
+/// both snapshots, the decoded fields are compared one by one, otherwise the raw data is compared.
+/// A missing account is treated as having all its fields set to `null`.
+///
+/// Returns `None` if nothing changed.
+pub fn diff_account(
+    pubkey: Pubkey,
+    before: Option<&ProgramAccount>,
+    after: Option<&ProgramAccount>,
+) -> Option<AccountDiff> {
+    // Compare the raw data unless both sides could be decoded
+    let decoded = before.is_none_or(|a| a.decoded_data.is_some())
+        && after.is_none_or(|a| a.decoded_data.is_some());
+    let before_fields = account_fields(before, decoded);
+    let after_fields = account_fields(after, decoded);
+
+    // Collect the field names in the order they first appear
+    let mut fields: Vec<&String> = vec![];
+    for (field, _) in before_fields.iter().chain(after_fields.iter()) {
+        if !fields.contains(&field) {
+            fields.push(field);
+        }
+    }
+
+    let lookup = |fields: &[(String, Value)], name: &str| {
+        fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value.clone())
+            .unwrap_or(Value::Null)
+    };
+    let changes: Vec<FieldChange> = fields
+        .into_iter()
+        .filter_map(|field| {
+            let before = lookup(&before_fields, field);
+            let after = lookup(&after_fields, field);
+            (before != after).then(|| FieldChange {
+                field: field.clone(),
+                before,
+                after,
+            })
+        })
+        .collect();
+
+    if changes.is_empty() {
+        return None;
+    }
+
+    Some(AccountDiff {
+        pubkey,
+        account_type: after
+            .and_then(|a| a.account_type.clone())
+            .or_else(|| before.and_then(|a| a.account_type.clone())),
+        changes,
+    })
+}
+
+/// Compute the field-level diffs between two snapshots taken with [`snapshot_accounts`].
+///
+/// Only the accounts that changed are returned.
+pub fn diff_accounts(
+    before: &[(Pubkey, Option<ProgramAccount>)],
+    after: &[(Pubkey, Option<ProgramAccount>)],
+) -> Vec<AccountDiff> {
+    before
+        .iter()
+        .filter_map(|(pubkey, before)| {
+            let after = after
+                .iter()
+                .find(|(after_pubkey, _)| after_pubkey == pubkey)
+                .and_then(|(_, after)| after.as_ref());
+            diff_account(*pubkey, before.as_ref(), after)
+        })
+        .collect()
+}
+
+/// List the comparable fields of an account, flattening the decoded data into dotted paths.
+fn account_fields(account: Option<&ProgramAccount>, decoded: bool) -> Vec<(String, Value)> {
+    let account = match account {
+        Some(account) => account,
+        None => return vec![],
+    };
+
+    let mut fields = vec![
+        ("lamports".to_string(), json!(account.account.lamports)),
+        (
+            "owner".to_string(),
+            json!(account.account.owner.to_string()),
+        ),
+        ("data_len".to_string(), json!(account.account.data.len())),
+    ];
+    match (&account.decoded_data, decoded) {
+        (Some(decoded_data), true) => flatten_fields("data", decoded_data, &mut fields),
+        _ => fields.push((
+            "data".to_string(),
+            json!(STANDARD.encode(&account.account.data)),
+        )),
+    }
+    fields
+}
+
+/// Flatten a JSON value into a list of fields with dotted paths (and indices for arrays).
+fn flatten_fields(path: &str, value: &Value, fields: &mut Vec<(String, Value)>) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                flatten_fields(&format!("{path}.{key}"), value, fields);
+            }
+        }
+        Value::Array(array) => {
+            for (i, value) in array.iter().enumerate() {
+                flatten_fields(&format!("{path}[{i}]"), value, fields);
+            }
+        }
+        _ => fields.push((path.to_string(), value.clone())),
+    }
+}
@@ -1,19 +1,30 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use {
+    crate::{
+        balance_check::{check_payer_balance, estimate_deploy_cost},
+        keypair::parse_keypair,
+        rpc_client::rpc_client_with_config,
+    },
     anyhow::Result,
+    aqd_utils::{
+        cassette_mode, emit_progress, is_quiet, proxy_for, rpc_headers, rpc_timeout, ErrorKind,
+        ProgressEvent, ProgressStage,
+    },
     solana_cli::{
         cli::{
             process_command, CliCommand, CliCommandInfo, CliConfig,
-            DEFAULT_CONFIRM_TX_TIMEOUT_SECONDS, DEFAULT_RPC_TIMEOUT_SECONDS,
+            DEFAULT_CONFIRM_TX_TIMEOUT_SECONDS,
         },
         program::ProgramCliCommand,
     },
     solana_cli_config::{Config, CONFIG_FILE},
     solana_cli_output::OutputFormat,
+    solana_client::rpc_client::RpcClientConfig,
     solana_rpc_client_api::config::RpcSendTransactionConfig,
-    solana_sdk::{commitment_config::CommitmentConfig, signer::keypair::read_keypair_file},
-    std::{str::FromStr, time::Duration},
+    solana_sdk::commitment_config::CommitmentConfig,
+    std::{fs, str::FromStr, sync::Arc, time::Duration},
+    tracing::warn,
 };
 
 /// Deploy a Solana program to the blockchain.
@@ -29,19 +40,72 @@ use {
 ///
 /// A `Result` containing a string that represents the result of the deployment operation.
 pub fn deploy_program<S>(program_location: S) -> Result<String>
+where
+    S: Into<String>,
+{
+    deploy_program_with_overrides(program_location, None, None)
+}
+
+/// Deploy a Solana program to the blockchain, overriding the RPC URL and keypair of the default
+/// Solana configuration file.
+///
+/// This is used to deploy to the cluster and with the wallet configured in a workspace.
+///
+/// Before deploying, the balance of the payer is checked against the estimated cost of the
+/// deployment (see [`estimate_deploy_cost`]), to fail early with a clear error.
+///
+/// # Arguments
+///
+/// * `program_location`: A string representing the location of the program to be deployed.
+/// * `json_rpc_url`: The RPC URL to deploy to. Defaults to the one of the configuration file.
+/// * `keypair_path`: The path of the payer and upgrade authority keypair. Defaults to the one of
+///   the configuration file.
+///
+/// # Returns
+///
+/// A `Result` containing a string that represents the result of the deployment operation.
+pub fn deploy_program_with_overrides<S>(
+    program_location: S,
+    json_rpc_url: Option<String>,
+    keypair_path: Option<String>,
+) -> Result<String>
 where
     S: Into<String>,
 {
     // Convert the program location to a string
     let program_location: String = program_location.into();
 
+    // The deployment sends its transactions to the validators directly, not to the RPC server
+    if cassette_mode().is_some() {
+        return Err(ErrorKind::Usage.wrap(anyhow::anyhow!(
+            "Solana program deployments cannot be recorded or replayed"
+        )));
+    }
+
     // Get the path to the configuration file (default location)
     let config_file = CONFIG_FILE
         .as_ref()
         .ok_or_else(|| anyhow::anyhow!("Failed to get configuration file path"))?;
 
     // Load configuration settings from a file or use defaults if the file is not found
-    let config = Config::load(config_file).unwrap_or_default();
+    let mut config = Config::load(config_file).unwrap_or_default();
+    if let Some(json_rpc_url) = json_rpc_url {
+        config.websocket_url = Config::compute_websocket_url(&json_rpc_url);
+        config.json_rpc_url = json_rpc_url;
+    }
+    if let Some(keypair_path) = keypair_path {
+        config.keypair_path = keypair_path;
+    }
+    if url::Url::parse(&config.json_rpc_url)
+        .ok()
+        .and_then(|url| proxy_for(&url))
+        .is_some()
+    {
+        warn!("The RPC requests of the deployment go through the proxy, but its transactions are sent to the validators directly, and their confirmation is subscribed to on the websocket endpoint without the proxy");
+    }
+    if !rpc_headers().is_empty() {
+        warn!("The RPC headers are sent with the RPC requests of the deployment, but not with its websocket subscriptions");
+    }
 
     // Create a CLI command for program deployment and define signers
     let CliCommandInfo { command, signers } = CliCommandInfo {
@@ -58,7 +122,7 @@ where
             skip_fee_check: false,
         }),
         // Load signer keypair from the file specified in the configuration
-        signers: vec![read_keypair_file(&config.keypair_path)
+        signers: vec![parse_keypair(&config.keypair_path)
             .map_err(|e| {
                 anyhow::anyhow!(
                     "Failed to read keypair file '{}': {}",
@@ -76,19 +140,32 @@ where
             anyhow::anyhow!("Failed to parse commitment level from configuration file")
         })?;
 
-    // Determine the output format (JSON or Display)
-    let output_format = OutputFormat::Display;
-
-    let rpc_timeout = Duration::from_secs(
-        DEFAULT_RPC_TIMEOUT_SECONDS
-            .parse::<u64>()
-            .map_err(|e| anyhow::anyhow!("Failed to parse RPC timeout: {}", e))?,
-    );
+    // Make sure the payer can afford the deployment before starting it
+    let program_len = fs::metadata(&program_location)
+        .map_err(|e| anyhow::anyhow!("Failed to read program file '{}': {}", program_location, e))?
+        .len() as usize;
     let confirm_transaction_initial_timeout = Duration::from_secs(
         DEFAULT_CONFIRM_TX_TIMEOUT_SECONDS
             .parse::<u64>()
             .map_err(|e| anyhow::anyhow!("Failed to parse confirm transaction timeout: {}", e))?,
     );
+    // The client of the deployment too, so that its requests are sent with the RPC headers
+    let rpc_client = Arc::new(rpc_client_with_config(
+        config.json_rpc_url.clone(),
+        RpcClientConfig {
+            commitment_config: commitment,
+            confirm_transaction_initial_timeout: Some(confirm_transaction_initial_timeout),
+        },
+    ));
+    let deploy_cost = estimate_deploy_cost(&rpc_client, program_len)?;
+    check_payer_balance(&rpc_client, &signers[0].pubkey(), deploy_cost)?;
+
+    // Determine the output format, quiet in quiet mode
+    let output_format = if is_quiet() {
+        OutputFormat::DisplayQuiet
+    } else {
+        OutputFormat::Display
+    };
 
     // Create a new configuration with modified settings
     let cmd_config = CliConfig {
@@ -97,8 +174,8 @@ where
         websocket_url: config.websocket_url,
         signers: signers.iter().map(|s| s.as_ref()).collect(),
         keypair_path: config.keypair_path,
-        rpc_client: None,
-        rpc_timeout,
+        rpc_client: Some(rpc_client),
+        rpc_timeout: rpc_timeout(),
         verbose: false,
         output_format,
         commitment,
@@ -111,9 +188,12 @@ where
         use_quic: true,
     };
 
-    // Process the deployment command with the updated configuration
+    // Process the deployment command with the updated configuration. Its many transactions are
+    // reported as a single one in the progress events.
+    emit_progress(ProgressEvent::new("solana", ProgressStage::Started));
     let result = process_command(&cmd_config)
         .map_err(|e| anyhow::anyhow!("Failed to process deployment command: {}", e))?;
+    emit_progress(ProgressEvent::new("solana", ProgressStage::Confirmed));
 
     // Extract the program ID from the result
     // Sample result = "Program Id: 71gxeC5D6bGAUznocUWyXdhWQozhDc72qKL7oZ8zn4kR"
@@ -1,22 +1,58 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use {
-    crate::utils::{construct_instruction_accounts, construct_instruction_data, idl_from_json},
-    anchor_syn::idl::{Idl, IdlInstruction},
+    crate::{
+        balance_check::check_payer_balance,
+        idl_discriminators::IdlDiscriminators,
+        instruction_data::construct_instruction_data_with_discriminator,
+        keypair::parse_keypair,
+        rpc_client::rpc_client,
+        token_accounts::{associated_token_address, mint_token_program},
+        utils::{
+            construct_instruction_accounts_with_password, find_pda,
+            idl_with_discriminators_from_json,
+        },
+    },
+    anchor_syn::idl::{Idl, IdlAccountItem, IdlInstruction},
     anyhow::{format_err, Result},
-    solana_client::rpc_client::RpcClient,
+    aqd_utils::{
+        cassette_mode, connect_timeout, emit_progress, is_quiet, progress_format, proxy_for,
+        ProgressEvent, ProgressFormat, ProgressStage,
+    },
+    base64::{engine::general_purpose::STANDARD, Engine},
+    serde_json::json,
+    solana_client::{
+        pubsub_client::PubsubClient,
+        rpc_client::RpcClient,
+        rpc_config::{RpcSignatureSubscribeConfig, RpcSimulateTransactionConfig},
+        rpc_request::RpcRequest,
+        rpc_response::{
+            ProcessedSignatureResult, Response, RpcSignatureResult, RpcSimulateTransactionResult,
+        },
+    },
     solana_sdk::{
         commitment_config::CommitmentConfig,
+        compute_budget::ComputeBudgetInstruction,
+        hash::Hash,
         instruction::{AccountMeta, Instruction},
         message::Message,
         pubkey::Pubkey,
         signature::Keypair,
         signature::Signature,
         signature::Signer,
-        signer::keypair::read_keypair_file,
         transaction::Transaction,
     },
-    std::{ffi::OsStr, marker::PhantomData, str::FromStr},
+    solana_transaction_status::UiTransactionEncoding,
+    std::{
+        ffi::OsStr,
+        marker::PhantomData,
+        str::FromStr,
+        sync::{mpsc, Arc},
+        thread,
+        time::{Duration, Instant},
+    },
+    tracing::{debug, info},
+    url::Url,
 };
 
 /// Represents a Solana program call configuration and execution context.
@@ -26,15 +62,104 @@ use {
 /// Definition Language) for the program, the program's ID, the specific instruction to call, call data,
 /// accounts involved, signers, new accounts to be created, and the payer's keypair.
 pub struct SolanaTransaction {
-    rpc_client: RpcClient,
+    rpc_client: Arc<RpcClient>,
     idl: Idl,
+    discriminators: IdlDiscriminators,
     program_id: Pubkey,
     instruction: IdlInstruction,
     call_data: Vec<u8>,
     accounts: Vec<AccountMeta>,
-    signers: Vec<Keypair>,
+    signers: Vec<Box<dyn Signer + Send + Sync>>,
     new_accounts: Vec<(Pubkey, String)>,
+    pdas: Vec<(String, Pubkey, u8)>,
     payer: Keypair,
+    ws_url: Option<String>,
+}
+
+/// An account of a Solana program call, given as an object rather than as a string.
+///
+/// This allows applications embedding the builder to pass public keys and signers directly, without
+/// writing their secret keys to disk.
+pub enum InstructionAccount {
+    /// A keyword, keypair or public key, as accepted by [`SolanaTransactionBuilder::accounts`]
+    Arg(String),
+    /// The public key of the account. If the account must sign the transaction, it is signed
+    /// externally, e.g. by a multisig vault.
+    Pubkey(Pubkey),
+    /// A signer of the account, e.g. a keypair held in memory or a hardware wallet. It must be
+    /// `Send` and `Sync`, so that a transaction can be submitted from several threads
+    Signer(Box<dyn Signer + Send + Sync>),
+}
+
+impl From<String> for InstructionAccount {
+    fn from(arg: String) -> Self {
+        InstructionAccount::Arg(arg)
+    }
+}
+
+impl From<&str> for InstructionAccount {
+    fn from(arg: &str) -> Self {
+        InstructionAccount::Arg(arg.to_string())
+    }
+}
+
+impl From<Pubkey> for InstructionAccount {
+    fn from(pubkey: Pubkey) -> Self {
+        InstructionAccount::Pubkey(pubkey)
+    }
+}
+
+impl From<Keypair> for InstructionAccount {
+    fn from(keypair: Keypair) -> Self {
+        InstructionAccount::Signer(Box::new(keypair))
+    }
+}
+
+impl From<Box<dyn Signer + Send + Sync>> for InstructionAccount {
+    fn from(signer: Box<dyn Signer + Send + Sync>) -> Self {
+        InstructionAccount::Signer(signer)
+    }
+}
+
+/// An override of the data of an account, used when simulating a transaction.
+///
+/// Parsed from a string of the form `<ADDRESS>:<BASE64_DATA>`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccountOverride {
+    pub address: Pubkey,
+    pub data: Vec<u8>,
+}
+
+impl FromStr for AccountOverride {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (address, data) = s.split_once(':').ok_or_else(|| {
+            format_err!(
+                "Invalid account override: {}. Expected format: <ADDRESS>:<BASE64_DATA>",
+                s
+            )
+        })?;
+        let address = Pubkey::from_str(address)
+            .map_err(|e| format_err!("Invalid account override address {}: {}", address, e))?;
+        let data = STANDARD
+            .decode(data)
+            .map_err(|e| format_err!("Invalid account override data for {}: {}", address, e))?;
+        Ok(AccountOverride { address, data })
+    }
+}
+
+/// Options of a transaction simulation, to make its result reproducible against a known chain state.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SimulationOptions {
+    /// Whether the RPC server replaces the recent blockhash of the transaction with the latest one.
+    /// The signatures are not verified in that case.
+    pub replace_recent_blockhash: bool,
+    /// The minimum slot the RPC server must have reached to simulate the transaction
+    pub min_context_slot: Option<u64>,
+    /// The accounts whose data is overridden during the simulation, sent in the `accountOverrides`
+    /// field of the request. The RPC server must support it, the cluster itself is not modified.
+    pub account_overrides: Vec<AccountOverride>,
 }
 
 /// Type state for the call command to tell that some mandatory state has not yet
@@ -67,13 +192,89 @@ pub mod state {
 /// configuration options for setting up a Solana program call. The options are stored as strings,
 /// making it convenient for users to specify their desired parameters.
 struct SolanaTransactionOpts {
-    rpc_url: String,
-    idl: String,
+    rpc: RpcSource,
+    idl: IdlSource,
     program_id: String,
     instruction: String,
     call_data: Vec<String>,
-    accounts: Vec<String>,
-    payer: String,
+    accounts: Vec<InstructionAccount>,
+    payer: PayerSource,
+    ws_url: Option<String>,
+    keystore_password: Option<String>,
+    discriminator: Option<Vec<u8>>,
+}
+
+/// The source of the RPC client of a Solana program call.
+enum RpcSource {
+    /// The URL of a Solana RPC endpoint
+    Url(String),
+    /// A client already configured, possibly shared with other calls
+    Client(Arc<RpcClient>),
+}
+
+impl RpcSource {
+    /// Connect to the RPC endpoint, if needed.
+    fn connect(self) -> Arc<RpcClient> {
+        match self {
+            RpcSource::Url(rpc_url) => {
+                debug!(rpc_url = %rpc_url, "Using RPC server");
+                Arc::new(rpc_client(rpc_url, CommitmentConfig::confirmed()))
+            }
+            RpcSource::Client(client) => {
+                debug!(rpc_url = %client.url(), "Using the given RPC client");
+                client
+            }
+        }
+    }
+}
+
+/// The source of the payer of a Solana program call.
+enum PayerSource {
+    /// A keypair file or keystore, or any other source of [`parse_keypair`]
+    Arg(String),
+    /// A keypair held in memory
+    Keypair(Keypair),
+}
+
+impl PayerSource {
+    /// Read the keypair, if needed.
+    fn load(self) -> Result<Keypair> {
+        match self {
+            PayerSource::Arg(payer) => {
+                parse_keypair(&payer).map_err(|e| format_err!("Error getting payer: {}", e))
+            }
+            PayerSource::Keypair(keypair) => Ok(keypair),
+        }
+    }
+}
+
+/// The source of the Idl of a Solana program call.
+enum IdlSource {
+    /// The path to an Idl JSON file
+    Path(String),
+    /// The content of an Idl JSON file
+    Json(String),
+    /// An Idl already parsed
+    Value(Box<Idl>),
+}
+
+impl IdlSource {
+    /// Read or parse the Idl, if needed, with the discriminators embedded in its JSON. An Idl
+    /// already parsed has no embedded discriminators.
+    fn load(self) -> Result<(Idl, IdlDiscriminators)> {
+        match self {
+            IdlSource::Path(path) => idl_with_discriminators_from_json(OsStr::new(&path))
+                .map_err(|e| format_err!("Error getting Idl from JSON file: {}", e)),
+            IdlSource::Json(json) => {
+                let idl = serde_json::from_str(&json)
+                    .map_err(|e| format_err!("Error parsing Idl JSON: {}", e))?;
+                let discriminators = IdlDiscriminators::from_json(&json)
+                    .map_err(|e| format_err!("Error parsing Idl JSON: {}", e))?;
+                Ok((idl, discriminators))
+            }
+            IdlSource::Value(idl) => Ok((*idl, IdlDiscriminators::default())),
+        }
+    }
 }
 
 /// A builder for configuring and constructing Solana program calls.
@@ -138,13 +339,88 @@ impl
     pub fn new() -> Self {
         Self {
             opts: SolanaTransactionOpts {
-                rpc_url: "".to_string(),
-                idl: "".to_string(),
+                rpc: RpcSource::Url("".to_string()),
+                idl: IdlSource::Path("".to_string()),
                 program_id: "".to_string(),
                 instruction: "".to_string(),
                 call_data: vec![],
                 accounts: vec![],
-                payer: "".to_string(),
+                payer: PayerSource::Arg("".to_string()),
+                ws_url: None,
+                keystore_password: None,
+                discriminator: None,
+            },
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<Rp, Id, Pi, In, C, A, Py> SolanaTransactionBuilder<Rp, Id, Pi, In, C, A, Py> {
+    /// Sets the websocket URL used to confirm the submitted transaction.
+    ///
+    /// This is optional. If it is set, the transaction is confirmed with a signature subscription
+    /// on the websocket endpoint, instead of polling the RPC endpoint. This is needed for RPC
+    /// providers that use distinct HTTP and websocket endpoints.
+    ///
+    /// # Parameters
+    ///
+    /// - `ws_url`: A `String` representing the URL of the Solana websocket endpoint.
+    ///
+    /// # Returns
+    ///
+    /// Returns the [`SolanaTransactionBuilder`] instance with the websocket URL option set.
+    pub fn ws_url<T: Into<String>>(self, ws_url: T) -> Self {
+        SolanaTransactionBuilder {
+            opts: SolanaTransactionOpts {
+                ws_url: Some(ws_url.into()),
+                ..self.opts
+            },
+            marker: PhantomData,
+        }
+    }
+
+    /// Sets the password of the keystores of the `new` accounts.
+    ///
+    /// This is optional. If it is set, the keypairs of the `new` accounts are written to keystores
+    /// encrypted with the password, instead of plaintext keypair files.
+    ///
+    /// # Parameters
+    ///
+    /// - `password`: A `String` representing the password of the keystores.
+    ///
+    /// # Returns
+    ///
+    /// Returns the [`SolanaTransactionBuilder`] instance with the keystore password option set.
+    pub fn keystore_password<T: Into<String>>(self, password: T) -> Self {
+        SolanaTransactionBuilder {
+            opts: SolanaTransactionOpts {
+                keystore_password: Some(password.into()),
+                ..self.opts
+            },
+            marker: PhantomData,
+        }
+    }
+
+    /// Sets the discriminator the instruction data starts with.
+    ///
+    /// This is optional. By default, the instruction data starts with the discriminator of the
+    /// instruction embedded in the Idl JSON, if any, or with its anchor discriminator, the first 8
+    /// bytes of `sha256("global:<instruction_name>")`. Setting it is
+    /// needed for programs that dispatch their instructions on non-standard discriminators, e.g.
+    /// computed in another namespace with [`discriminator`](crate::discriminator).
+    ///
+    /// # Parameters
+    ///
+    /// - `discriminator`: The bytes the instruction data starts with, before the arguments.
+    ///
+    /// # Returns
+    ///
+    /// Returns the [`SolanaTransactionBuilder`] instance with the discriminator option set.
+    pub fn discriminator<T: Into<Vec<u8>>>(self, discriminator: T) -> Self {
+        SolanaTransactionBuilder {
+            opts: SolanaTransactionOpts {
+                discriminator: Some(discriminator.into()),
+                ..self.opts
             },
             marker: PhantomData,
         }
@@ -170,12 +446,38 @@ impl<Id, Pi, In, C, A, Py>
     pub fn rpc_url<T: Into<String>>(
         self,
         rpc_url: T,
+    ) -> SolanaTransactionBuilder<state::RpcClient, Id, Pi, In, C, A, Py> {
+        self.rpc_source(RpcSource::Url(rpc_url.into()))
+    }
+
+    /// Sets the RPC client used to connect to a Solana network, instead of its URL.
+    ///
+    /// This allows an application to share one configured client across many program calls, and
+    /// tests to use a client whose requests are answered without a network, e.g. created with
+    /// [`RpcClient::new_mock`] or with [`RpcClient::new_sender`] and a custom `RpcSender`. The
+    /// commitment of the client is used to query the network and to confirm the transaction.
+    ///
+    /// # Parameters
+    ///
+    /// - `rpc_client`: An [`RpcClient`], or an `Arc<RpcClient>` shared with other program calls.
+    ///
+    /// # Returns
+    ///
+    /// Returns a new [`SolanaTransactionBuilder`] instance with the RPC client option set.
+    pub fn rpc_client<T: Into<Arc<RpcClient>>>(
+        self,
+        rpc_client: T,
+    ) -> SolanaTransactionBuilder<state::RpcClient, Id, Pi, In, C, A, Py> {
+        self.rpc_source(RpcSource::Client(rpc_client.into()))
+    }
+
+    /// Sets the source of the RPC client.
+    fn rpc_source(
+        self,
+        rpc: RpcSource,
     ) -> SolanaTransactionBuilder<state::RpcClient, Id, Pi, In, C, A, Py> {
         SolanaTransactionBuilder {
-            opts: SolanaTransactionOpts {
-                rpc_url: rpc_url.into(),
-                ..self.opts
-            },
+            opts: SolanaTransactionOpts { rpc, ..self.opts },
             marker: PhantomData,
         }
     }
@@ -191,7 +493,8 @@ impl<Rp, Pi, In, C, A, Py> SolanaTransactionBuilder<Rp, Missing<state::Idl>, Pi,
     ///
     /// # Parameters
     ///
-    /// - `idl`: A `String` representing the path to the Idl JSON file.
+    /// - `idl`: A `String` representing the path to the Idl JSON file, or `-` to read it from the
+    ///   standard input.
     ///
     /// # Returns
     ///
@@ -199,12 +502,49 @@ impl<Rp, Pi, In, C, A, Py> SolanaTransactionBuilder<Rp, Missing<state::Idl>, Pi,
     pub fn idl<T: Into<String>>(
         self,
         idl: T,
+    ) -> SolanaTransactionBuilder<Rp, state::Idl, Pi, In, C, A, Py> {
+        self.idl_source(IdlSource::Path(idl.into()))
+    }
+
+    /// Sets the Interface Definition Language (Idl) from an Idl already parsed, e.g. fetched from
+    /// the chain or embedded in the binary, without writing it to a file.
+    ///
+    /// # Parameters
+    ///
+    /// - `idl`: The [`Idl`] of the program.
+    ///
+    /// # Returns
+    ///
+    /// Returns a new [`SolanaTransactionBuilder`] instance with the Idl option set.
+    pub fn idl_value(self, idl: Idl) -> SolanaTransactionBuilder<Rp, state::Idl, Pi, In, C, A, Py> {
+        self.idl_source(IdlSource::Value(Box::new(idl)))
+    }
+
+    /// Sets the Interface Definition Language (Idl) from the content of an Idl JSON file, without
+    /// writing it to a file. The JSON is parsed when the call is built by
+    /// [`SolanaTransactionBuilder::done`].
+    ///
+    /// # Parameters
+    ///
+    /// - `idl_json`: A `String` holding the Idl JSON.
+    ///
+    /// # Returns
+    ///
+    /// Returns a new [`SolanaTransactionBuilder`] instance with the Idl option set.
+    pub fn idl_json_str<T: Into<String>>(
+        self,
+        idl_json: T,
+    ) -> SolanaTransactionBuilder<Rp, state::Idl, Pi, In, C, A, Py> {
+        self.idl_source(IdlSource::Json(idl_json.into()))
+    }
+
+    /// Sets the source of the Idl.
+    fn idl_source(
+        self,
+        idl: IdlSource,
     ) -> SolanaTransactionBuilder<Rp, state::Idl, Pi, In, C, A, Py> {
         SolanaTransactionBuilder {
-            opts: SolanaTransactionOpts {
-                idl: idl.into(),
-                ..self.opts
-            },
+            opts: SolanaTransactionOpts { idl, ..self.opts },
             marker: PhantomData,
         }
     }
@@ -278,7 +618,9 @@ impl<Rp, Id, Pi, In, A, Py>
     ///
     /// - For primitive types, such as integers or strings, a single string should be provided.
     /// - For structs, the string should be a JSON representation of the struct.
-    /// - For arrays and vectors, the string should contain a comma-separated list of values.
+    /// - For arrays and vectors, the string should contain a JSON array, whose elements may be
+    ///   nested arrays or struct objects, or a comma-separated list of scalar values, whose
+    ///   elements can be double-quoted or have their commas escaped (e.g. `"a,b",c` or `a\,b,c`).
     ///
     /// These data values will be compared to the Idl instruction's argument types and encoded accordingly
     /// when invoking the Solana program.
@@ -316,13 +658,23 @@ impl<Rp, Id, Pi, In, C, Py>
     /// You can use special keywords for some accounts:
     ///
     /// - `"new"`: Creates a new account and writes the keypair to a file. Information about the new account
-    /// will be stored in the `new_accounts` field and can be accessed later.
+    ///   will be stored in the `new_accounts` field and can be accessed later.
     ///
     /// - `"self"`: Reads the default keypair from the local configuration file. This is useful for accessing
-    /// the current user's account without specifying a keypair.
+    ///   the current user's account without specifying a keypair.
     ///
     /// - `"system"`: Represents the system program account.
     ///
+    /// - `"pda:<SEED1>,<SEED2>,..."`: Derives a program derived address from the seeds and the program ID.
+    ///   Seeds can be strings, `hex:<HEX>`, `pubkey:<PUBKEY>` or `arg:<NAME>` to use an instruction argument.
+    ///   The derived addresses and bumps are stored in the `pdas` field and can be accessed later.
+    ///
+    /// - `"token"`, `"token-2022"` and `"associated-token"`: Represent the SPL Token, SPL Token-2022 and
+    ///   Associated Token Account programs.
+    ///
+    /// - `"ata:<MINT>[:<OWNER>]"`: Derives the associated token account of the owner (the payer by default)
+    ///   for the mint. The token program (SPL Token or SPL Token-2022) is read from the owner of the mint.
+    ///
     /// Whether an account is signable and mutable will be determined based on the account's definition in the
     /// Idl (Interface Definition Language). Accounts marked as signable in the Idl will be treated as signers,
     /// and mutable accounts will be set as mutable.
@@ -337,10 +689,36 @@ impl<Rp, Id, Pi, In, C, Py>
     pub fn accounts<S: Into<String>>(
         self,
         accounts: Vec<S>,
+    ) -> SolanaTransactionBuilder<Rp, Id, Pi, In, C, state::Accounts, Py> {
+        self.instruction_accounts(
+            accounts
+                .into_iter()
+                .map(|s| InstructionAccount::Arg(s.into()))
+                .collect(),
+        )
+    }
+
+    /// Sets the accounts of the Solana program instruction, as public keys, signers or strings.
+    ///
+    /// A [`Pubkey`] is used as is, and a signer, e.g. a [`Keypair`] or a
+    /// `Box<dyn Signer + Send + Sync>`, signs the transaction if the account is signable in the
+    /// Idl, so that secret keys held in memory never need to be written to a file. Strings are interpreted as by
+    /// [`SolanaTransactionBuilder::accounts`].
+    ///
+    /// # Parameters
+    ///
+    /// - `accounts`: A `Vec` of [`InstructionAccount`], or of any type convertible into one.
+    ///
+    /// # Returns
+    ///
+    /// Returns a new [`SolanaTransactionBuilder`] instance with the specified accounts set.
+    pub fn instruction_accounts<T: Into<InstructionAccount>>(
+        self,
+        accounts: Vec<T>,
     ) -> SolanaTransactionBuilder<Rp, Id, Pi, In, C, state::Accounts, Py> {
         SolanaTransactionBuilder {
             opts: SolanaTransactionOpts {
-                accounts: accounts.into_iter().map(|s| s.into()).collect(),
+                accounts: accounts.into_iter().map(|account| account.into()).collect(),
                 ..self.opts
             },
             marker: PhantomData,
@@ -364,12 +742,35 @@ impl<Rp, Id, Pi, In, C, A> SolanaTransactionBuilder<Rp, Id, Pi, In, C, A, Missin
     pub fn payer<T: Into<String>>(
         self,
         payer: T,
+    ) -> SolanaTransactionBuilder<Rp, Id, Pi, In, C, A, state::Payer> {
+        self.payer_source(PayerSource::Arg(payer.into()))
+    }
+
+    /// Sets the payer for the Solana program instruction, as a keypair held in memory.
+    ///
+    /// This avoids writing the secret key of the payer to a file when embedding the builder.
+    ///
+    /// # Parameters
+    ///
+    /// - `keypair`: The [`Keypair`] of the payer account.
+    ///
+    /// # Returns
+    ///
+    /// Returns a new [`SolanaTransactionBuilder`] instance with the specified payer set.
+    pub fn payer_keypair(
+        self,
+        keypair: Keypair,
+    ) -> SolanaTransactionBuilder<Rp, Id, Pi, In, C, A, state::Payer> {
+        self.payer_source(PayerSource::Keypair(keypair))
+    }
+
+    /// Sets the source of the payer.
+    fn payer_source(
+        self,
+        payer: PayerSource,
     ) -> SolanaTransactionBuilder<Rp, Id, Pi, In, C, A, state::Payer> {
         SolanaTransactionBuilder {
-            opts: SolanaTransactionOpts {
-                payer: payer.into(),
-                ..self.opts
-            },
+            opts: SolanaTransactionOpts { payer, ..self.opts },
             marker: PhantomData,
         }
     }
@@ -397,7 +798,7 @@ impl
     ///
     /// This method returns an error if any of the following conditions are met:
     ///
-    /// - The Idl cannot be obtained from the specified JSON file.
+    /// - The Idl cannot be read from the specified JSON file, or parsed from the JSON string.
     /// - The program ID cannot be parsed from the provided string.
     /// - The specified instruction is not found in the Idl.
     /// - There is an error constructing the call data.
@@ -409,14 +810,10 @@ impl
     /// Returns a `Result` containing the configured [`SolanaTransaction`] instance if the configuration
     pub fn done(self) -> Result<SolanaTransaction> {
         // Get the RPC client
-        let rpc_client = RpcClient::new_with_commitment(
-            self.opts.rpc_url.clone(),
-            CommitmentConfig::confirmed(),
-        );
+        let rpc_client = self.opts.rpc.connect();
 
         // Get the Idl
-        let idl = idl_from_json(OsStr::new(&self.opts.idl))
-            .map_err(|e| format_err!("Error getting Idl from JSON file: {}", e))?;
+        let (idl, discriminators) = self.opts.idl.load()?;
 
         // Get the program ID
         let program_id = Pubkey::from_str(&self.opts.program_id)
@@ -432,29 +829,97 @@ impl
 
         // Prepare the call data
         let idl_defined_types = idl.types.clone();
-        let call_data =
-            construct_instruction_data(&instruction, &self.opts.call_data, &idl_defined_types)
-                .map_err(|e| format_err!("Error constructing call data: {}", e))?;
+        // The discriminator set on the builder wins over the one embedded in the Idl, if any
+        let discriminator = self
+            .opts
+            .discriminator
+            .clone()
+            .unwrap_or_else(|| discriminators.instruction(&instruction.name));
+        let call_data = construct_instruction_data_with_discriminator(
+            &instruction,
+            &self.opts.call_data,
+            &idl_defined_types,
+            &discriminator,
+        )
+        .map_err(|e| format_err!("Error constructing call data: {}", e))?;
+
+        // Get the payer
+        let payer = self.opts.payer.load()?;
+
+        // Replace the accounts given as objects with their address, keeping their signers
+        let mut account_signers: Vec<Box<dyn Signer + Send + Sync>> = vec![];
+        let mut raw_accounts: Vec<String> = vec![];
+        for account in self.opts.accounts {
+            raw_accounts.push(match account {
+                InstructionAccount::Arg(arg) => arg,
+                InstructionAccount::Pubkey(pubkey) => pubkey.to_string(),
+                InstructionAccount::Signer(signer) => {
+                    let pubkey = signer.try_pubkey().map_err(|e| {
+                        format_err!("Error constructing accounts: invalid signer: {}", e)
+                    })?;
+                    account_signers.push(signer);
+                    pubkey.to_string()
+                }
+            });
+        }
+
+        // Derive the PDA and associated token accounts, and replace them with their address
+        let mut pdas: Vec<(String, Pubkey, u8)> = vec![];
+        for (i, raw_account) in raw_accounts.iter_mut().enumerate() {
+            if raw_account.starts_with("pda:") {
+                let (pda, bump) = find_pda(
+                    raw_account,
+                    &program_id,
+                    &instruction,
+                    &self.opts.call_data,
+                    &idl_defined_types,
+                )
+                .map_err(|e| format_err!("Error deriving PDA: {}", e))?;
+                let account_name = match instruction.accounts.get(i) {
+                    Some(IdlAccountItem::IdlAccount(account)) => account.name.clone(),
+                    _ => format!("Account {}", i + 1),
+                };
+                pdas.push((account_name, pda, bump));
+                *raw_account = pda.to_string();
+            } else if let Some(ata) = raw_account.strip_prefix("ata:") {
+                let ata = resolve_associated_token_account(&rpc_client, ata, &payer.pubkey())
+                    .map_err(|e| format_err!("Error deriving associated token account: {}", e))?;
+                *raw_account = ata.to_string();
+            }
+        }
 
         // Prepare the accounts
-        let (accounts, signers, new_accounts) =
-            construct_instruction_accounts(&instruction, &self.opts.accounts)
-                .map_err(|e| format_err!("Error constructing accounts: {}", e))?;
+        let (accounts, signers, new_accounts) = construct_instruction_accounts_with_password(
+            &instruction,
+            &raw_accounts,
+            self.opts.keystore_password.as_deref(),
+        )
+        .map_err(|e| format_err!("Error constructing accounts: {}", e))?;
 
-        // Get the payer
-        let payer = read_keypair_file(&self.opts.payer)
-            .map_err(|e| format_err!("Error getting payer: {}", e))?;
+        // The signers given as objects sign for the signable accounts only
+        let mut signers: Vec<Box<dyn Signer + Send + Sync>> = signers
+            .into_iter()
+            .map(|keypair| Box::new(keypair) as Box<dyn Signer + Send + Sync>)
+            .collect();
+        signers.extend(account_signers.into_iter().filter(|signer| {
+            accounts
+                .iter()
+                .any(|account| account.is_signer && account.pubkey == signer.pubkey())
+        }));
 
         Ok(SolanaTransaction {
             rpc_client,
             idl,
+            discriminators,
             program_id,
             instruction,
             call_data,
             accounts,
             signers,
             new_accounts,
+            pdas,
             payer,
+            ws_url: self.opts.ws_url,
         })
     }
 }
@@ -485,6 +950,11 @@ impl SolanaTransaction {
         &self.idl
     }
 
+    /// Get the discriminators embedded in the Idl JSON
+    pub fn discriminators(&self) -> &IdlDiscriminators {
+        &self.discriminators
+    }
+
     /// Get the program ID
     pub fn program_id(&self) -> &Pubkey {
         &self.program_id
@@ -506,7 +976,7 @@ impl SolanaTransaction {
     }
 
     /// Get the signers
-    pub fn signers(&self) -> &Vec<Keypair> {
+    pub fn signers(&self) -> &[Box<dyn Signer + Send + Sync>] {
         &self.signers
     }
 
@@ -515,11 +985,38 @@ impl SolanaTransaction {
         &self.new_accounts
     }
 
+    /// Get the derived PDA accounts, as (account name, address, bump)
+    pub fn pdas(&self) -> &Vec<(String, Pubkey, u8)> {
+        &self.pdas
+    }
+
     /// Get the payer
     pub fn payer(&self) -> &Keypair {
         &self.payer
     }
 
+    /// Build the instruction of the program call, without submitting it, e.g. to compose it with
+    /// other instructions into a transaction with its own signers.
+    ///
+    /// The keypairs of the `new` accounts and of the other signer accounts are available from
+    /// [`SolanaTransaction::signers`].
+    pub fn to_instruction(&self) -> Instruction {
+        Instruction {
+            program_id: self.program_id,
+            accounts: self.accounts.clone(),
+            data: self.call_data.clone(),
+        }
+    }
+
+    /// Build the message of a transaction with the instruction of the program call only, paid by
+    /// the payer, without submitting it.
+    ///
+    /// The recent blockhash of the message is not set: set it, or use a durable nonce, before
+    /// signing a transaction with the message.
+    pub fn to_message(&self) -> Message {
+        Message::new(&[self.to_instruction()], Some(&self.payer.pubkey()))
+    }
+
     /// Submits a transaction to the Solana network using the configured parameters.
     ///
     /// This method prepares and submits a transaction to the Solana network based on the
@@ -532,6 +1029,7 @@ impl SolanaTransaction {
     /// This method returns an error if any of the following conditions are met:
     ///
     /// - The RPC client encounters an error when fetching the latest blockhash.
+    /// - The payer balance is lower than the transaction fee.
     /// - Signing the transaction with the payer or other signers fails.
     /// - Sending and confirming the transaction on the Solana network fails.
     ///
@@ -539,23 +1037,217 @@ impl SolanaTransaction {
     ///
     /// Returns a `Result` containing the transaction's [`Signature`] if the submission process succeeds.
     pub fn submit_transaction(&self) -> Result<Signature> {
+        let (recent_blockhash, _) = self.latest_blockhash()?;
+        self.submit_transaction_with_blockhash(recent_blockhash)
+    }
+
+    /// Fetches the latest blockhash from the RPC server, along with the last block height at which
+    /// a transaction using it can be processed.
+    ///
+    /// # Errors
+    ///
+    /// This method returns an error if the RPC client fails to fetch the latest blockhash.
+    pub fn latest_blockhash(&self) -> Result<(Hash, u64)> {
+        self.rpc_client
+            .get_latest_blockhash_with_commitment(self.rpc_client.commitment())
+            .map_err(|err| format_err!("error: unable to get latest blockhash: {}", err))
+    }
+
+    /// Submits the transaction like [`SolanaTransaction::submit_transaction`], but with a given
+    /// recent blockhash instead of fetching the latest one at submission time.
+    ///
+    /// # Errors
+    ///
+    /// In addition to the errors of [`SolanaTransaction::submit_transaction`], this method returns an
+    /// error if the blockhash is expired or unknown to the RPC server.
+    pub fn submit_transaction_with_blockhash(&self, recent_blockhash: Hash) -> Result<Signature> {
+        self.submit_signed_transaction(recent_blockhash, None)
+    }
+
+    /// Submits the transaction like [`SolanaTransaction::submit_transaction_with_blockhash`], made
+    /// unique by a compute unit price of `index` micro-lamports, so that the same instruction can
+    /// be submitted many times with the same blockhash, e.g. to benchmark it. The cluster would
+    /// otherwise drop the identical transactions as duplicates.
+    ///
+    /// The priority fee paid is `index` micro-lamports per requested compute unit, i.e. 0.2
+    /// lamports per index with the default limit of 200,000 compute units.
+    ///
+    /// # Errors
+    ///
+    /// This method returns the errors of [`SolanaTransaction::submit_transaction_with_blockhash`].
+    pub fn submit_unique_transaction(
+        &self,
+        recent_blockhash: Hash,
+        index: u64,
+    ) -> Result<Signature> {
+        self.submit_signed_transaction(recent_blockhash, Some(index))
+    }
+
+    /// Signs and submits the transaction, with a compute unit price if any, and waits for its
+    /// confirmation, reporting its stages as progress events.
+    fn submit_signed_transaction(
+        &self,
+        recent_blockhash: Hash,
+        compute_unit_price: Option<u64>,
+    ) -> Result<Signature> {
+        emit_progress(ProgressEvent::new("solana", ProgressStage::Started));
+        let rpc_client = &self.rpc_client;
+        let blockhash_valid = rpc_client
+            .is_blockhash_valid(&recent_blockhash, rpc_client.commitment())
+            .map_err(|err| format_err!("error: unable to check the blockhash: {}", err))?;
+        if !blockhash_valid {
+            return Err(format_err!(
+                "Blockhash {} is expired or unknown to the RPC server",
+                recent_blockhash
+            ));
+        }
+        emit_progress(
+            ProgressEvent::new("solana", ProgressStage::BlockhashFetched).block(recent_blockhash),
+        );
+
+        let transaction = self.signed_transaction(recent_blockhash, compute_unit_price)?;
+        emit_progress(
+            ProgressEvent::new("solana", ProgressStage::Signed).hash(transaction.signatures[0]),
+        );
+
+        // Make sure the payer can afford the transaction fee
+        let fee = rpc_client
+            .get_fee_for_message(&transaction.message)
+            .map_err(|err| format_err!("error: unable to get the transaction fee: {}", err))?;
+        debug!(fee, payer = %self.payer.pubkey(), "Fetched the transaction fee");
+        check_payer_balance(rpc_client, &self.payer.pubkey(), fee)?;
+
+        info!(
+            instruction = %self.instruction.name,
+            blockhash = %recent_blockhash,
+            websocket = self.ws_url.is_some(),
+            "Sending transaction"
+        );
+        let start = Instant::now();
+
+        // The websocket notifications are not recorded in the cassettes, and the websocket client
+        // does not support proxies
+        let ws_url = self.ws_url.as_ref().filter(|ws_url| {
+            cassette_mode().is_none()
+                && Url::parse(ws_url).map_or(true, |url| proxy_for(&url).is_none())
+        });
+        let signature = match ws_url {
+            Some(ws_url) => {
+                let signature = rpc_client
+                    .send_transaction(&transaction)
+                    .map_err(|err| format_err!("Error: {}", err,))?;
+                emit_submitted(&signature);
+                confirm_with_subscription(ws_url, &signature, rpc_client.commitment())?;
+                signature
+            }
+            // The submission is reported before waiting for the confirmation
+            None if progress_format() == ProgressFormat::Ndjson => {
+                let signature = rpc_client
+                    .send_transaction(&transaction)
+                    .map_err(|err| format_err!("Error: {}", err,))?;
+                emit_submitted(&signature);
+                confirm_with_polling(rpc_client, &signature)?;
+                signature
+            }
+            // The spinner is disabled in quiet mode
+            None if is_quiet() => rpc_client
+                .send_and_confirm_transaction(&transaction)
+                .map_err(|err| format_err!("Error: {}", err,))?,
+            None => rpc_client
+                .send_and_confirm_transaction_with_spinner(&transaction)
+                .map_err(|err| format_err!("Error: {}", err,))?,
+        };
+        info!(%signature, elapsed = ?start.elapsed(), "Transaction confirmed");
+        emit_confirmed(rpc_client, &signature);
+
+        Ok(signature)
+    }
+
+    /// Simulates the transaction on the Solana network without submitting it.
+    ///
+    /// The transaction is built and signed exactly like in [`SolanaTransaction::submit_transaction`],
+    /// and then passed to the `simulateTransaction` RPC method. No state is changed on chain.
+    ///
+    /// # Errors
+    ///
+    /// This method returns an error if the latest blockhash cannot be fetched, if signing the
+    /// transaction fails, or if the RPC server fails to simulate the transaction.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the [`RpcSimulateTransactionResult`] reported by the RPC server.
+    pub fn simulate_transaction(&self) -> Result<RpcSimulateTransactionResult> {
+        self.simulate_transaction_with_options(&SimulationOptions::default())
+    }
+
+    /// Simulates the transaction like [`SolanaTransaction::simulate_transaction`], with the given
+    /// [`SimulationOptions`].
+    ///
+    /// # Errors
+    ///
+    /// In addition to the errors of [`SolanaTransaction::simulate_transaction`], this method returns
+    /// an error if an overridden account is not used by the instruction, and the RPC server returns
+    /// an error if it has not reached the minimum context slot.
+    pub fn simulate_transaction_with_options(
+        &self,
+        options: &SimulationOptions,
+    ) -> Result<RpcSimulateTransactionResult> {
+        for account_override in &options.account_overrides {
+            if !self
+                .accounts
+                .iter()
+                .any(|account| account.pubkey == account_override.address)
+            {
+                return Err(format_err!(
+                    "Account {} is not used by instruction {}",
+                    account_override.address,
+                    self.instruction.name
+                ));
+            }
+        }
+        simulate_with_client(&self.rpc_client, self, options)
+    }
+
+    /// Builds the transaction and signs it with the payer and the other signers.
+    ///
+    /// With a compute unit price, a `ComputeBudget` instruction setting it comes first.
+    fn signed_transaction(
+        &self,
+        recent_blockhash: Hash,
+        compute_unit_price: Option<u64>,
+    ) -> Result<Transaction> {
+        // Every signer account must have a keypair, unless it is the payer
+        let payer_pubkey = self.payer.pubkey();
+        if let Some(account) = self.accounts.iter().find(|account| {
+            account.is_signer
+                && account.pubkey != payer_pubkey
+                && !self
+                    .signers
+                    .iter()
+                    .any(|signer| signer.pubkey() == account.pubkey)
+        }) {
+            return Err(format_err!(
+                "Account {} must sign the transaction, but no keypair was provided for it",
+                account.pubkey
+            ));
+        }
+
         // Create the instruction
-        let instruction = Instruction {
-            program_id: self.program_id,
-            accounts: self.accounts.clone(),
-            data: self.call_data.clone(),
+        let instruction = self.to_instruction();
+
+        let instructions = match compute_unit_price {
+            Some(price) => vec![
+                ComputeBudgetInstruction::set_compute_unit_price(price),
+                instruction,
+            ],
+            None => vec![instruction],
         };
 
         // Create the message
         let payer_keypair = &self.payer;
-        let message = Message::new(&[instruction], Some(&payer_keypair.pubkey()));
+        let message = Message::new(&instructions, Some(&payer_keypair.pubkey()));
         let mut transaction = Transaction::new_unsigned(message);
 
-        let rpc_client = &self.rpc_client;
-        let recent_blockhash = rpc_client
-            .get_latest_blockhash()
-            .map_err(|err| format_err!("error: unable to get latest blockhash: {}", err))?;
-
         // The payer needs to sign the transaction.
         // This method does not require all keypairs to be provided.
         // Note: It is permitted to sign a transaction with the same keypair multiple times.
@@ -564,7 +1256,7 @@ impl SolanaTransaction {
         let signers = self
             .signers
             .iter()
-            .map(|keypair| keypair as &dyn Signer)
+            .map(|signer| signer.as_ref() as &dyn Signer)
             .collect::<Vec<&dyn Signer>>();
 
         // Sign the transaction
@@ -572,10 +1264,193 @@ impl SolanaTransaction {
             .try_sign(&signers, recent_blockhash)
             .map_err(|err| format_err!("error: failed to sign transaction: {}", err))?;
 
-        let signature = rpc_client
-            .send_and_confirm_transaction_with_spinner(&transaction)
-            .map_err(|err| format_err!("Error: {}", err,))?;
+        Ok(transaction)
+    }
+}
 
-        Ok(signature)
+/// How long to wait for the confirmation of a transaction on the websocket endpoint, or by polling
+/// its status.
+const CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// The interval between two polls of the status of a transaction.
+const CONFIRMATION_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Report the submission of a transaction, as a progress event.
+pub(crate) fn emit_submitted(signature: &Signature) {
+    emit_progress(ProgressEvent::new("solana", ProgressStage::Submitted).hash(signature));
+}
+
+/// Report the confirmation of a transaction, as a progress event. The transaction is only
+/// reported finalized if it was waited for with the finalized commitment.
+pub(crate) fn emit_confirmed(rpc_client: &RpcClient, signature: &Signature) {
+    let stage = if rpc_client.commitment().is_finalized() {
+        ProgressStage::Finalized
+    } else {
+        ProgressStage::Confirmed
+    };
+    emit_progress(ProgressEvent::new("solana", stage).hash(signature));
+}
+
+/// Waits for the confirmation of a transaction by polling its status, and returns an error if the
+/// transaction failed or was not confirmed in time.
+pub(crate) fn confirm_with_polling(rpc_client: &RpcClient, signature: &Signature) -> Result<()> {
+    let start = Instant::now();
+    while start.elapsed() < CONFIRMATION_TIMEOUT {
+        let status = rpc_client
+            .get_signature_status_with_commitment(signature, rpc_client.commitment())
+            .map_err(|err| format_err!("error: unable to get the transaction status: {}", err))?;
+        match status {
+            Some(Ok(())) => return Ok(()),
+            Some(Err(err)) => return Err(format_err!("Transaction {} failed: {}", signature, err)),
+            None => std::thread::sleep(CONFIRMATION_POLL_INTERVAL),
+        }
     }
+    Err(format_err!(
+        "Transaction {} was not confirmed after {} seconds",
+        signature,
+        CONFIRMATION_TIMEOUT.as_secs()
+    ))
+}
+
+/// Waits for the confirmation of a transaction with a signature subscription on the websocket
+/// endpoint, and returns an error if the connection timed out, or if the transaction failed or
+/// was not confirmed in time.
+fn confirm_with_subscription(
+    ws_url: &str,
+    signature: &Signature,
+    commitment: CommitmentConfig,
+) -> Result<()> {
+    let config = RpcSignatureSubscribeConfig {
+        commitment: Some(commitment),
+        enable_received_notification: Some(false),
+    };
+    // The websocket client has no connection timeout, so it connects in its own thread
+    let (sender, connected) = mpsc::channel();
+    thread::spawn({
+        let (url, signature) = (ws_url.to_string(), *signature);
+        move || {
+            let _ = sender.send(PubsubClient::signature_subscribe(
+                &url,
+                &signature,
+                Some(config),
+            ));
+        }
+    });
+    let (mut subscription, receiver) = connected
+        .recv_timeout(connect_timeout())
+        .map_err(|_| {
+            format_err!(
+                "Timed out connecting to {} after {} ms, see --connect-timeout",
+                ws_url,
+                connect_timeout().as_millis()
+            )
+        })?
+        .map_err(|err| format_err!("Error subscribing to {}: {}", ws_url, err))?;
+    let result = receiver.recv_timeout(CONFIRMATION_TIMEOUT);
+    // The subscription is not needed anymore, whatever the result
+    let _ = subscription.shutdown();
+
+    match result {
+        Ok(response) => match response.value {
+            RpcSignatureResult::ProcessedSignature(ProcessedSignatureResult { err: None }) => {
+                Ok(())
+            }
+            RpcSignatureResult::ProcessedSignature(ProcessedSignatureResult { err: Some(err) }) => {
+                Err(format_err!("Transaction {} failed: {}", signature, err))
+            }
+            RpcSignatureResult::ReceivedSignature(_) => Err(format_err!(
+                "Unexpected received notification for transaction {}",
+                signature
+            )),
+        },
+        Err(_) => Err(format_err!(
+            "Transaction {} was not confirmed after {} seconds",
+            signature,
+            CONFIRMATION_TIMEOUT.as_secs()
+        )),
+    }
+}
+
+/// Simulates `transaction` using the given RPC client and simulation options.
+fn simulate_with_client(
+    rpc_client: &RpcClient,
+    transaction: &SolanaTransaction,
+    options: &SimulationOptions,
+) -> Result<RpcSimulateTransactionResult> {
+    let recent_blockhash = rpc_client
+        .get_latest_blockhash()
+        .map_err(|err| format_err!("error: unable to get latest blockhash: {}", err))?;
+
+    let signed_transaction = transaction.signed_transaction(recent_blockhash, None)?;
+
+    // The RPC server rejects signature verification combined with a blockhash replacement
+    let config = RpcSimulateTransactionConfig {
+        sig_verify: !options.replace_recent_blockhash,
+        replace_recent_blockhash: options.replace_recent_blockhash,
+        commitment: Some(rpc_client.commitment()),
+        min_context_slot: options.min_context_slot,
+        ..RpcSimulateTransactionConfig::default()
+    };
+    debug!(
+        ?config,
+        overrides = options.account_overrides.len(),
+        "Simulating transaction"
+    );
+    let start = Instant::now();
+    let result = if options.account_overrides.is_empty() {
+        rpc_client.simulate_transaction_with_config(&signed_transaction, config)
+    } else {
+        // The overrides are not part of the config of the RPC client, so the request is sent as is
+        let serialized = bincode::serialize(&signed_transaction)
+            .map_err(|err| format_err!("Error serializing transaction: {}", err))?;
+        let mut config = serde_json::to_value(RpcSimulateTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            ..config
+        })?;
+        config["accountOverrides"] = options
+            .account_overrides
+            .iter()
+            .map(|account_override| {
+                (
+                    account_override.address.to_string(),
+                    json!({ "data": [STANDARD.encode(&account_override.data), "base64"] }),
+                )
+            })
+            .collect::<serde_json::Map<_, _>>()
+            .into();
+        rpc_client.send::<Response<RpcSimulateTransactionResult>>(
+            RpcRequest::SimulateTransaction,
+            json!([STANDARD.encode(serialized), config]),
+        )
+    }
+    .map_err(|err| format_err!("Error simulating transaction: {}", err))?;
+    debug!(
+        elapsed = ?start.elapsed(),
+        units_consumed = ?result.value.units_consumed,
+        "Simulation completed"
+    );
+
+    Ok(result.value)
+}
+
+/// Resolves an `ata:<MINT>[:<OWNER>]` account argument (without its prefix) to the address of the
+/// associated token account, using `default_owner` if no owner is given.
+fn resolve_associated_token_account(
+    rpc_client: &RpcClient,
+    ata: &str,
+    default_owner: &Pubkey,
+) -> Result<Pubkey> {
+    let (mint, owner) = match ata.split_once(':') {
+        Some((mint, owner)) => (mint, Some(owner)),
+        None => (ata, None),
+    };
+    let mint = Pubkey::from_str(mint).map_err(|e| format_err!("Invalid mint {}: {}", mint, e))?;
+    let owner = match owner {
+        Some(owner) => {
+            Pubkey::from_str(owner).map_err(|e| format_err!("Invalid owner {}: {}", owner, e))?
+        }
+        None => *default_owner,
+    };
+    let token_program = mint_token_program(rpc_client, &mint)?;
+    Ok(associated_token_address(&owner, &mint, &token_program))
 }
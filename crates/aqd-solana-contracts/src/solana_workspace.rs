@@ -0,0 +1,319 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anchor_syn::idl::Idl,
+    anyhow::{anyhow, Result},
+    aqd_utils::SolangConfig,
+    solana_cli_config::{Config, CONFIG_FILE},
+    std::{
+        env, fs,
+        path::{Path, PathBuf},
+    },
+    tracing::info,
+};
+
+/// The kind of workspace a [`SolanaWorkspace`] was loaded from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WorkspaceKind {
+    /// An Anchor workspace, configured by `Anchor.toml`
+    Anchor,
+    /// A Solang project, configured by `solang.toml`
+    Solang,
+}
+
+/// A program of a workspace, with the paths of its build artifacts.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WorkspaceProgram {
+    /// The name of the program
+    pub name: String,
+    /// The program ID, if it is defined in the workspace configuration
+    pub program_id: Option<String>,
+    /// The path of the IDL JSON file of the program
+    pub idl_path: PathBuf,
+    /// The path of the program binary (.so)
+    pub program_path: PathBuf,
+}
+
+impl WorkspaceProgram {
+    /// Get the program ID, either from the workspace configuration or from the `metadata.address`
+    /// field of the IDL.
+    pub fn resolve_program_id(&self, idl: &Idl) -> Option<String> {
+        self.program_id.clone().or_else(|| {
+            idl.metadata
+                .as_ref()
+                .and_then(|metadata| metadata.get("address"))
+                .and_then(|address| address.as_str())
+                .map(|address| address.to_string())
+        })
+    }
+}
+
+/// An Anchor or Solang workspace, used to resolve the IDL, program ID and cluster of a command
+/// when they are not provided explicitly.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SolanaWorkspace {
+    /// The kind of workspace
+    pub kind: WorkspaceKind,
+    /// The directory containing the workspace configuration file
+    pub root: PathBuf,
+    /// The cluster configured for the workspace (a moniker or a URL), if any
+    pub cluster: Option<String>,
+    /// The path of the wallet keypair configured for the workspace, if any
+    pub wallet: Option<String>,
+    /// The programs of the workspace
+    pub programs: Vec<WorkspaceProgram>,
+}
+
+impl SolanaWorkspace {
+    /// Discover the workspace of the current directory.
+    ///
+    /// See [`SolanaWorkspace::discover_from`].
+    pub fn discover() -> Result<Option<Self>> {
+        let current_dir = env::current_dir()
+            .map_err(|e| anyhow!("Failed to get the current directory: {}", e))?;
+        Self::discover_from(&current_dir)
+    }
+
+    /// Discover the workspace of a directory.
+    ///
+    /// Only the directory itself is searched for an `Anchor.toml` or a `solang.toml` file, as the
+    /// project directory of the commands: the configuration file of a parent directory may belong
+    /// to an unrelated project. Returns `None` if no workspace configuration file is found.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if a configuration file is found but cannot be read or parsed.
+    pub fn discover_from(dir: &Path) -> Result<Option<Self>> {
+        for (file_name, kind) in [
+            ("Anchor.toml", WorkspaceKind::Anchor),
+            ("solang.toml", WorkspaceKind::Solang),
+        ] {
+            let path = dir.join(file_name);
+            if !path.exists() {
+                continue;
+            }
+            let content = fs::read_to_string(&path)
+                .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+            let workspace = match kind {
+                WorkspaceKind::Anchor => Self::from_anchor_toml(dir, &content),
+                WorkspaceKind::Solang => Self::from_solang_toml(dir, &content),
+            }
+            .map_err(|e| anyhow!("Failed to parse {}: {}", path.display(), e))?;
+            info!("Using the workspace configured by {}", path.display());
+            return Ok(Some(workspace));
+        }
+        Ok(None)
+    }
+
+    /// The path of the configuration file of the workspace: `Anchor.toml` or `solang.toml`.
+    pub fn config_path(&self) -> PathBuf {
+        self.root.join(match self.kind {
+            WorkspaceKind::Anchor => "Anchor.toml",
+            WorkspaceKind::Solang => "solang.toml",
+        })
+    }
+
+    /// Load an Anchor workspace from the content of its `Anchor.toml` file.
+    ///
+    /// The cluster and wallet are read from the `[provider]` section, and the programs from the
+    /// `[programs.<cluster>]` section matching the cluster (falling back to `[programs.localnet]`).
+    /// The IDL and program binary are expected in `target/idl` and `target/deploy`.
+    pub fn from_anchor_toml(root: &Path, content: &str) -> Result<Self> {
+        let parsed_toml: toml::Value = toml::from_str(content)?;
+
+        let provider = parsed_toml.get("provider");
+        let cluster = provider
+            .and_then(|provider| provider.get("cluster"))
+            .and_then(|cluster| cluster.as_str())
+            .map(|cluster| cluster.to_string());
+        let wallet = provider
+            .and_then(|provider| provider.get("wallet"))
+            .and_then(|wallet| wallet.as_str())
+            .map(expand_home);
+
+        let programs_table = parsed_toml.get("programs").and_then(|p| p.as_table());
+        let programs = programs_table
+            .and_then(|table| {
+                cluster
+                    .as_deref()
+                    .and_then(|cluster| table.get(cluster))
+                    .or_else(|| table.get("localnet"))
+                    .or_else(|| table.values().next())
+            })
+            .and_then(|programs| programs.as_table())
+            .map(|programs| {
+                programs
+                    .iter()
+                    .map(|(name, program_id)| WorkspaceProgram {
+                        name: name.clone(),
+                        program_id: program_id.as_str().map(|id| id.to_string()),
+                        idl_path: root.join("target").join("idl").join(format!("{name}.json")),
+                        program_path: root
+                            .join("target")
+                            .join("deploy")
+                            .join(format!("{name}.so")),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(SolanaWorkspace {
+            kind: WorkspaceKind::Anchor,
+            root: root.to_path_buf(),
+            cluster,
+            wallet,
+            programs,
+        })
+    }
+
+    /// Load a Solang project from the content of its `solang.toml` file.
+    ///
+    /// The programs are the contracts listed in the `[package]` section, with their program binary
+    /// in the output directory of the `[compiler-output]` section (the project root by default),
+    /// and their IDL in its metadata output directory (the output directory by default). The
+    /// cluster and wallet are read from the optional `[provider]` section (see [`SolangConfig`]).
+    /// The program ID is not part of `solang.toml`, and is read from the IDL instead.
+    pub fn from_solang_toml(root: &Path, content: &str) -> Result<Self> {
+        let config = SolangConfig::from_toml(content)?;
+        let artifacts_dir = config.artifacts_dir(root);
+        let metadata_dir = config.metadata_dir(root);
+
+        let programs = config
+            .contracts
+            .into_iter()
+            .map(|name| WorkspaceProgram {
+                idl_path: metadata_dir.join(format!("{name}.json")),
+                program_path: artifacts_dir.join(format!("{name}.so")),
+                program_id: None,
+                name,
+            })
+            .collect();
+
+        Ok(SolanaWorkspace {
+            kind: WorkspaceKind::Solang,
+            root: root.to_path_buf(),
+            cluster: config.cluster,
+            wallet: config.wallet.as_deref().map(expand_home),
+            programs,
+        })
+    }
+
+    /// Get the RPC URL of the workspace cluster, if a cluster is configured.
+    ///
+    /// Anchor cluster monikers (`localnet`, `devnet`, `testnet` and `mainnet`) are converted to their
+    /// URL. Any other value is returned as is.
+    pub fn rpc_url(&self) -> Option<String> {
+        self.cluster.as_deref().map(|cluster| {
+            match cluster {
+                "localnet" | "localhost" => "http://127.0.0.1:8899",
+                "devnet" => "https://api.devnet.solana.com",
+                "testnet" => "https://api.testnet.solana.com",
+                "mainnet" | "mainnet-beta" => "https://api.mainnet-beta.solana.com",
+                url => url,
+            }
+            .to_string()
+        })
+    }
+
+    /// Get a program of the workspace by name.
+    ///
+    /// If no name is provided, the workspace must contain exactly one program.
+    pub fn program(&self, name: Option<&str>) -> Result<&WorkspaceProgram> {
+        let names = || {
+            self.programs
+                .iter()
+                .map(|p| p.name.as_str())
+                .collect::<Vec<_>>()
+        };
+        match name {
+            Some(name) => self.programs.iter().find(|p| p.name == name).ok_or_else(|| {
+                anyhow!(
+                    "Program {} not found in the workspace. Available programs: {:?}",
+                    name,
+                    names()
+                )
+            }),
+            None => match self.programs.as_slice() {
+                [program] => Ok(program),
+                [] => Err(anyhow!("No programs found in the workspace")),
+                _ => Err(anyhow!(
+                    "The workspace contains several programs, please specify one with --program-name. Available programs: {:?}",
+                    names()
+                )),
+            },
+        }
+    }
+}
+
+/// Load the configuration file of the Solana CLI, if it exists.
+///
+/// Unlike the default configuration used without a file, the settings of an existing file are
+/// explicit, and take precedence over the workspace (see [`resolve_rpc_url`]).
+pub fn solana_cli_config() -> Option<Config> {
+    CONFIG_FILE
+        .as_ref()
+        .filter(|path| Path::new(path).is_file())
+        .and_then(|path| Config::load(path).ok())
+}
+
+/// Resolve the RPC URL of a command: the explicit `url` takes precedence, then the RPC URL of the
+/// configuration file of the Solana CLI (`cli_config`, see [`solana_cli_config`]), then the
+/// cluster of the workspace. Returns `None` if none of them is set.
+///
+/// The file supplying the RPC URL is logged.
+pub fn resolve_rpc_url(
+    url: Option<String>,
+    cli_config: Option<&Config>,
+    workspace: Option<&SolanaWorkspace>,
+) -> Option<String> {
+    if url.is_some() {
+        return url;
+    }
+    if let Some(cli_config) = cli_config {
+        info!("Using the RPC URL of the Solana CLI configuration file");
+        return Some(cli_config.json_rpc_url.clone());
+    }
+    let workspace = workspace?;
+    let rpc_url = workspace.rpc_url()?;
+    info!(
+        "Using the cluster of {}: {}",
+        workspace.config_path().display(),
+        rpc_url
+    );
+    Some(rpc_url)
+}
+
+/// Resolve the wallet of a command, the path of its payer keypair: the explicit `keypair` takes
+/// precedence, then the keypair of the configuration file of the Solana CLI (`cli_config`, see
+/// [`solana_cli_config`]), then the wallet of the workspace. Returns `None` if none of them is set.
+///
+/// The file supplying the wallet is logged.
+pub fn resolve_wallet(
+    keypair: Option<String>,
+    cli_config: Option<&Config>,
+    workspace: Option<&SolanaWorkspace>,
+) -> Option<String> {
+    if keypair.is_some() {
+        return keypair;
+    }
+    if let Some(cli_config) = cli_config {
+        info!("Using the keypair of the Solana CLI configuration file");
+        return Some(cli_config.keypair_path.clone());
+    }
+    let workspace = workspace?;
+    let wallet = workspace.wallet.clone()?;
+    info!(
+        "Using the wallet of {}: {}",
+        workspace.config_path().display(),
+        wallet
+    );
+    Some(wallet)
+}
+
+/// Expand a leading `~` to the home directory.
+fn expand_home(path: &str) -> String {
+    match (path.strip_prefix('~'), env::var("HOME")) {
+        (Some(rest), Ok(home)) => format!("{home}{rest}"),
+        _ => path.to_string(),
+    }
+}
@@ -0,0 +1,391 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::{
+        borsh_encoding::{discriminator, GLOBAL_NAMESPACE},
+        solana_transaction::{confirm_with_polling, emit_confirmed, emit_submitted},
+    },
+    anyhow::{anyhow, bail, Result},
+    aqd_utils::{
+        emit_progress, is_quiet, progress_format, ProgressEvent, ProgressFormat, ProgressStage,
+    },
+    solana_client::rpc_client::RpcClient,
+    solana_sdk::{
+        instruction::{AccountMeta, Instruction},
+        message::Message,
+        pubkey,
+        pubkey::Pubkey,
+        signature::{Keypair, Signature, Signer},
+        system_program,
+        transaction::Transaction,
+    },
+};
+
+/// The program ID of the Squads Protocol v4 multisig program.
+pub const SQUADS_PROGRAM_ID: Pubkey = pubkey!("SQDS4ep65T869zMMBKyuUq6aD6EgTu8psMjkvj52pCf");
+
+/// Offset of the `transaction_index` field in the multisig account data:
+/// discriminator (8) + create_key (32) + config_authority (32) + threshold (2) + time_lock (4).
+const MULTISIG_TRANSACTION_INDEX_OFFSET: usize = 78;
+
+/// A Squads Protocol v4 multisig, used to propose, approve and execute transactions signed by one
+/// of its vaults instead of submitting them directly.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SquadsMultisig {
+    /// The address of the multisig account
+    pub multisig: Pubkey,
+    /// The index of the vault that signs the proposed transactions
+    pub vault_index: u8,
+    /// The ID of the Squads program
+    pub program_id: Pubkey,
+}
+
+/// A transaction proposal created in a Squads multisig.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SquadsProposal {
+    /// The index of the transaction in the multisig
+    pub transaction_index: u64,
+    /// The address of the vault transaction account
+    pub transaction: Pubkey,
+    /// The address of the proposal account
+    pub proposal: Pubkey,
+    /// The signature of the transaction that created the proposal
+    pub signature: Signature,
+}
+
+impl SquadsMultisig {
+    /// Create a multisig handle for the default Squads program.
+    pub fn new(multisig: Pubkey, vault_index: u8) -> Self {
+        SquadsMultisig {
+            multisig,
+            vault_index,
+            program_id: SQUADS_PROGRAM_ID,
+        }
+    }
+
+    /// Get the address of the vault that signs the proposed transactions.
+    pub fn vault_address(&self) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                b"multisig",
+                self.multisig.as_ref(),
+                b"vault",
+                &[self.vault_index],
+            ],
+            &self.program_id,
+        )
+        .0
+    }
+
+    /// Get the address of the vault transaction account with the given index.
+    pub fn transaction_address(&self, transaction_index: u64) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                b"multisig",
+                self.multisig.as_ref(),
+                b"transaction",
+                &transaction_index.to_le_bytes(),
+            ],
+            &self.program_id,
+        )
+        .0
+    }
+
+    /// Get the address of the proposal account of the transaction with the given index.
+    pub fn proposal_address(&self, transaction_index: u64) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                b"multisig",
+                self.multisig.as_ref(),
+                b"transaction",
+                &transaction_index.to_le_bytes(),
+                b"proposal",
+            ],
+            &self.program_id,
+        )
+        .0
+    }
+
+    /// Fetch the index of the last transaction created in the multisig.
+    pub fn fetch_transaction_index(&self, rpc_client: &RpcClient) -> Result<u64> {
+        let data = rpc_client
+            .get_account_data(&self.multisig)
+            .map_err(|e| anyhow!("Error fetching multisig {}: {}", self.multisig, e))?;
+        let bytes = data
+            .get(MULTISIG_TRANSACTION_INDEX_OFFSET..MULTISIG_TRANSACTION_INDEX_OFFSET + 8)
+            .ok_or_else(|| anyhow!("Account {} is not a Squads multisig", self.multisig))?;
+        Ok(u64::from_le_bytes(bytes.try_into()?))
+    }
+
+    /// Create a vault transaction executing `instructions`, and a proposal to approve it.
+    ///
+    /// The instructions must only require the signature of the vault. The creator must be a member
+    /// of the multisig with the initiate permission, and pays the rent of the new accounts.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if an instruction requires another signer than the vault, if
+    /// the multisig cannot be fetched, or if the transaction fails.
+    pub fn create_proposal(
+        &self,
+        rpc_client: &RpcClient,
+        creator: &Keypair,
+        instructions: &[Instruction],
+        memo: Option<String>,
+    ) -> Result<SquadsProposal> {
+        let vault = self.vault_address();
+        for instruction in instructions {
+            if let Some(account) = instruction
+                .accounts
+                .iter()
+                .find(|account| account.is_signer && account.pubkey != vault)
+            {
+                bail!(
+                    "Account {} must sign the instruction, but only the multisig vault {} can sign proposed transactions",
+                    account.pubkey,
+                    vault
+                );
+            }
+        }
+
+        let transaction_index = self.fetch_transaction_index(rpc_client)? + 1;
+        let transaction = self.transaction_address(transaction_index);
+        let proposal = self.proposal_address(transaction_index);
+
+        // vault_transaction_create(VaultTransactionCreateArgs)
+        let mut data = discriminator(GLOBAL_NAMESPACE, "vault_transaction_create");
+        data.push(self.vault_index);
+        // No ephemeral signers
+        data.push(0);
+        encode_bytes(&transaction_message(&vault, instructions)?, &mut data);
+        encode_option_string(&memo, &mut data);
+        let create_transaction = Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new(self.multisig, false),
+                AccountMeta::new(transaction, false),
+                AccountMeta::new_readonly(creator.pubkey(), true),
+                AccountMeta::new(creator.pubkey(), true),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+            data,
+        };
+
+        // proposal_create(ProposalCreateArgs)
+        let mut data = discriminator(GLOBAL_NAMESPACE, "proposal_create");
+        data.extend_from_slice(&transaction_index.to_le_bytes());
+        // Not a draft
+        data.push(0);
+        let create_proposal = Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(self.multisig, false),
+                AccountMeta::new(proposal, false),
+                AccountMeta::new_readonly(creator.pubkey(), true),
+                AccountMeta::new(creator.pubkey(), true),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+            data,
+        };
+
+        let signature =
+            send_instructions(rpc_client, creator, &[create_transaction, create_proposal])?;
+
+        Ok(SquadsProposal {
+            transaction_index,
+            transaction,
+            proposal,
+            signature,
+        })
+    }
+
+    /// Approve the proposal of the transaction with the given index as a member of the multisig.
+    pub fn approve_proposal(
+        &self,
+        rpc_client: &RpcClient,
+        member: &Keypair,
+        transaction_index: u64,
+        memo: Option<String>,
+    ) -> Result<Signature> {
+        // proposal_approve(ProposalVoteArgs)
+        let mut data = discriminator(GLOBAL_NAMESPACE, "proposal_approve");
+        encode_option_string(&memo, &mut data);
+        let approve = Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(self.multisig, false),
+                AccountMeta::new(member.pubkey(), true),
+                AccountMeta::new(self.proposal_address(transaction_index), false),
+            ],
+            data,
+        };
+
+        send_instructions(rpc_client, member, &[approve])
+    }
+
+    /// Execute the approved vault transaction with the given index as a member of the multisig.
+    ///
+    /// The accounts of the vault transaction are read from the transaction account, and passed to
+    /// the Squads program along with their writability.
+    pub fn execute_proposal(
+        &self,
+        rpc_client: &RpcClient,
+        member: &Keypair,
+        transaction_index: u64,
+    ) -> Result<Signature> {
+        let transaction = self.transaction_address(transaction_index);
+        let data = rpc_client
+            .get_account_data(&transaction)
+            .map_err(|e| anyhow!("Error fetching vault transaction {}: {}", transaction, e))?;
+
+        // vault_transaction_execute()
+        let mut accounts = vec![
+            AccountMeta::new_readonly(self.multisig, false),
+            AccountMeta::new(self.proposal_address(transaction_index), false),
+            AccountMeta::new_readonly(transaction, false),
+            AccountMeta::new_readonly(member.pubkey(), true),
+        ];
+        accounts.extend(vault_transaction_accounts(&data)?);
+        let execute = Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: discriminator(GLOBAL_NAMESPACE, "vault_transaction_execute"),
+        };
+
+        send_instructions(rpc_client, member, &[execute])
+    }
+}
+
+/// Serialize instructions into the Squads `TransactionMessage` format, with the vault as payer.
+///
+/// The accounts are ordered like in a legacy Solana message. Lengths are encoded as `u8`, except
+/// for the instruction data which uses a `u16`.
+fn transaction_message(vault: &Pubkey, instructions: &[Instruction]) -> Result<Vec<u8>> {
+    let message = Message::new(instructions, Some(vault));
+    let header = message.header;
+    let num_keys = message.account_keys.len();
+    let too_long = |what: &str| anyhow!("Too many {} in the proposed transaction", what);
+
+    let mut data = vec![
+        header.num_required_signatures,
+        header.num_required_signatures - header.num_readonly_signed_accounts,
+        (num_keys
+            - header.num_required_signatures as usize
+            - header.num_readonly_unsigned_accounts as usize) as u8,
+    ];
+    data.push(u8::try_from(num_keys).map_err(|_| too_long("accounts"))?);
+    for key in &message.account_keys {
+        data.extend_from_slice(key.as_ref());
+    }
+    data.push(u8::try_from(message.instructions.len()).map_err(|_| too_long("instructions"))?);
+    for instruction in &message.instructions {
+        data.push(instruction.program_id_index);
+        data.push(u8::try_from(instruction.accounts.len()).map_err(|_| too_long("accounts"))?);
+        data.extend_from_slice(&instruction.accounts);
+        let data_len =
+            u16::try_from(instruction.data.len()).map_err(|_| too_long("instruction data"))?;
+        data.extend_from_slice(&data_len.to_le_bytes());
+        data.extend_from_slice(&instruction.data);
+    }
+    // No address table lookups
+    data.push(0);
+    Ok(data)
+}
+
+/// Read the accounts of the message stored in a vault transaction account.
+///
+/// The vault is passed as a non-signer, since the Squads program signs for it.
+fn vault_transaction_accounts(data: &[u8]) -> Result<Vec<AccountMeta>> {
+    let invalid = || anyhow!("Invalid vault transaction account data");
+    let read = |offset: usize, len: usize| data.get(offset..offset + len).ok_or_else(invalid);
+
+    // discriminator (8) + multisig (32) + creator (32) + index (8) + bump (1) + vault_index (1)
+    // + vault_bump (1), followed by the ephemeral signer bumps (Vec<u8>)
+    let mut offset = 83;
+    let ephemeral_signers = u32::from_le_bytes(read(offset, 4)?.try_into()?) as usize;
+    offset += 4 + ephemeral_signers;
+
+    // VaultTransactionMessage header and account keys (Vec<Pubkey>)
+    let header = read(offset, 3)?;
+    let (num_signers, num_writable_signers, num_writable_non_signers) =
+        (header[0] as usize, header[1] as usize, header[2] as usize);
+    offset += 3;
+    let num_keys = u32::from_le_bytes(read(offset, 4)?.try_into()?) as usize;
+    offset += 4;
+
+    (0..num_keys)
+        .map(|i| {
+            let pubkey = Pubkey::try_from(read(offset + i * 32, 32)?).map_err(|_| invalid())?;
+            let is_writable = if i < num_signers {
+                i < num_writable_signers
+            } else {
+                i - num_signers < num_writable_non_signers
+            };
+            Ok(if is_writable {
+                AccountMeta::new(pubkey, false)
+            } else {
+                AccountMeta::new_readonly(pubkey, false)
+            })
+        })
+        .collect()
+}
+
+/// Borsh encode a byte vector.
+fn encode_bytes(bytes: &[u8], data: &mut Vec<u8>) {
+    data.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    data.extend_from_slice(bytes);
+}
+
+/// Borsh encode an optional string.
+fn encode_option_string(value: &Option<String>, data: &mut Vec<u8>) {
+    match value {
+        Some(value) => {
+            data.push(1);
+            encode_bytes(value.as_bytes(), data);
+        }
+        None => data.push(0),
+    }
+}
+
+/// Sign and send instructions with a single signer, which also pays the fees.
+fn send_instructions(
+    rpc_client: &RpcClient,
+    signer: &Keypair,
+    instructions: &[Instruction],
+) -> Result<Signature> {
+    emit_progress(ProgressEvent::new("solana", ProgressStage::Started));
+    let recent_blockhash = rpc_client
+        .get_latest_blockhash()
+        .map_err(|err| anyhow!("error: unable to get latest blockhash: {}", err))?;
+    emit_progress(
+        ProgressEvent::new("solana", ProgressStage::BlockhashFetched).block(recent_blockhash),
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&signer.pubkey()),
+        &[signer],
+        recent_blockhash,
+    );
+    emit_progress(
+        ProgressEvent::new("solana", ProgressStage::Signed).hash(transaction.signatures[0]),
+    );
+    let signature = if progress_format() == ProgressFormat::Ndjson {
+        // The submission is reported before waiting for the confirmation
+        let signature = rpc_client
+            .send_transaction(&transaction)
+            .map_err(|err| anyhow!("Error: {}", err))?;
+        emit_submitted(&signature);
+        confirm_with_polling(rpc_client, &signature)?;
+        signature
+    } else {
+        // The spinner is disabled in quiet mode
+        let result = if is_quiet() {
+            rpc_client.send_and_confirm_transaction(&transaction)
+        } else {
+            rpc_client.send_and_confirm_transaction_with_spinner(&transaction)
+        };
+        result.map_err(|err| anyhow!("Error: {}", err))?
+    };
+    emit_confirmed(rpc_client, &signature);
+    Ok(signature)
+}
@@ -0,0 +1,302 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    solana_client::rpc_client::RpcClient,
+    solana_sdk::{
+        commitment_config::CommitmentConfig,
+        pubkey::Pubkey,
+        signature::{write_keypair_file, Keypair, Signer},
+    },
+    std::{
+        fs,
+        net::TcpListener,
+        path::{Path, PathBuf},
+        process::{Child, Command, Stdio},
+        thread::sleep,
+        time::{Duration, Instant},
+    },
+};
+
+/// The default time to wait for the validator to become healthy.
+const DEFAULT_STARTUP_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A builder for configuring and starting a local [`TestValidator`].
+///
+/// The builder collects the programs to preload into the genesis of the validator and the ports
+/// to bind to. Unless they are set, free ports are picked when the validator starts, so several
+/// validators can run concurrently. Programs are loaded using the `--bpf-program` option of
+/// `solana-test-validator`, which means they are available as soon as the validator is healthy,
+/// without a separate deploy step.
+pub struct TestValidatorBuilder {
+    programs: Vec<(Pubkey, PathBuf)>,
+    rpc_port: Option<u16>,
+    faucet_port: Option<u16>,
+    startup_timeout: Duration,
+    validator_binary: String,
+}
+
+impl Default for TestValidatorBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TestValidatorBuilder {
+    /// Create a new builder with the default configuration.
+    pub fn new() -> Self {
+        Self {
+            programs: vec![],
+            rpc_port: None,
+            faucet_port: None,
+            startup_timeout: DEFAULT_STARTUP_TIMEOUT,
+            validator_binary: "solana-test-validator".to_string(),
+        }
+    }
+
+    /// Adds a program (`.so` file) to be deployed when the validator starts.
+    ///
+    /// A new program ID is generated for the program. The program ID can be retrieved from the
+    /// started [`TestValidator`] using [`TestValidator::program_id`], in the order the programs were added.
+    pub fn program<P: AsRef<Path>>(mut self, program_location: P) -> Self {
+        let program_id = Keypair::new().pubkey();
+        self.programs
+            .push((program_id, program_location.as_ref().to_path_buf()));
+        self
+    }
+
+    /// Adds a program (`.so` file) to be deployed at a specific program ID when the validator starts.
+    pub fn program_with_id<P: AsRef<Path>>(
+        mut self,
+        program_id: Pubkey,
+        program_location: P,
+    ) -> Self {
+        self.programs
+            .push((program_id, program_location.as_ref().to_path_buf()));
+        self
+    }
+
+    /// Sets the RPC port the validator listens on. The websocket of the validator listens on the
+    /// next port.
+    pub fn rpc_port(mut self, rpc_port: u16) -> Self {
+        self.rpc_port = Some(rpc_port);
+        self
+    }
+
+    /// Sets the faucet port the validator listens on.
+    pub fn faucet_port(mut self, faucet_port: u16) -> Self {
+        self.faucet_port = Some(faucet_port);
+        self
+    }
+
+    /// Sets the maximum time to wait for the validator to become healthy.
+    pub fn startup_timeout(mut self, startup_timeout: Duration) -> Self {
+        self.startup_timeout = startup_timeout;
+        self
+    }
+
+    /// Sets the path of the `solana-test-validator` binary.
+    ///
+    /// By default, the binary is looked up in the `PATH`.
+    pub fn validator_binary<T: Into<String>>(mut self, validator_binary: T) -> Self {
+        self.validator_binary = validator_binary.into();
+        self
+    }
+
+    /// Starts the validator and waits until it is healthy.
+    ///
+    /// A fresh ledger is created in the system temporary directory, and a new payer keypair is
+    /// generated and used as the mint of the validator, so it is funded from genesis.
+    ///
+    /// # Errors
+    ///
+    /// This method returns an error if no free port is found, if the validator binary cannot be
+    /// spawned, if the payer keypair cannot be written to disk, or if the validator does not become
+    /// healthy before the timeout.
+    pub fn start(self) -> Result<TestValidator> {
+        // The reserved ports are released right before the validator is spawned
+        let mut reserved = vec![];
+        let rpc_port = match self.rpc_port {
+            Some(rpc_port) => rpc_port,
+            None => {
+                let (rpc_port, listeners) = reserve_port(true)?;
+                reserved.extend(listeners);
+                rpc_port
+            }
+        };
+        let faucet_port = match self.faucet_port {
+            Some(faucet_port) => faucet_port,
+            None => {
+                let (faucet_port, listeners) = reserve_port(false)?;
+                reserved.extend(listeners);
+                faucet_port
+            }
+        };
+
+        let work_dir = std::env::temp_dir().join(format!(
+            "aqd-test-validator-{}-{}",
+            std::process::id(),
+            rpc_port
+        ));
+        if work_dir.exists() {
+            fs::remove_dir_all(&work_dir)?;
+        }
+        fs::create_dir_all(&work_dir)?;
+
+        // Generate the payer and write it to disk, so it can be used by APIs that take keypair paths
+        let payer = Keypair::new();
+        let payer_path = work_dir.join("payer.json");
+        write_keypair_file(&payer, &payer_path)
+            .map_err(|e| anyhow!("Failed to write payer keypair file: {}", e))?;
+
+        let ledger = work_dir.join("ledger");
+        let mut command = Command::new(&self.validator_binary);
+        command
+            .arg("--quiet")
+            .arg("--reset")
+            .arg("--ledger")
+            .arg(&ledger)
+            .arg("--rpc-port")
+            .arg(rpc_port.to_string())
+            .arg("--faucet-port")
+            .arg(faucet_port.to_string())
+            .arg("--mint")
+            .arg(payer.pubkey().to_string());
+        for (program_id, program_location) in &self.programs {
+            command
+                .arg("--bpf-program")
+                .arg(program_id.to_string())
+                .arg(program_location);
+        }
+
+        drop(reserved);
+        let process = command
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| anyhow!("Failed to start {}: {}", self.validator_binary, e))?;
+
+        let rpc_url = format!("http://127.0.0.1:{}", rpc_port);
+        let validator = TestValidator {
+            process,
+            work_dir,
+            rpc_client: RpcClient::new_with_commitment(
+                rpc_url.clone(),
+                CommitmentConfig::confirmed(),
+            ),
+            rpc_url,
+            payer,
+            payer_path,
+            programs: self.programs,
+        };
+
+        validator.wait_for_health(self.startup_timeout)?;
+
+        Ok(validator)
+    }
+}
+
+/// A local `solana-test-validator` process for integration tests.
+///
+/// The validator is started with a funded payer and the programs configured in the
+/// [`TestValidatorBuilder`]. The process is killed and its ledger removed when the
+/// [`TestValidator`] is dropped.
+pub struct TestValidator {
+    process: Child,
+    work_dir: PathBuf,
+    rpc_client: RpcClient,
+    rpc_url: String,
+    payer: Keypair,
+    payer_path: PathBuf,
+    programs: Vec<(Pubkey, PathBuf)>,
+}
+
+impl TestValidator {
+    /// Returns a clean builder for [`TestValidator`]
+    pub fn builder() -> TestValidatorBuilder {
+        TestValidatorBuilder::new()
+    }
+
+    /// Get the RPC URL of the validator
+    pub fn rpc_url(&self) -> &str {
+        &self.rpc_url
+    }
+
+    /// Get the Rpc client
+    pub fn rpc_client(&self) -> &RpcClient {
+        &self.rpc_client
+    }
+
+    /// Get the payer
+    pub fn payer(&self) -> &Keypair {
+        &self.payer
+    }
+
+    /// Get the path of the payer keypair file
+    pub fn payer_path(&self) -> String {
+        self.payer_path.to_string_lossy().to_string()
+    }
+
+    /// Get the program ID of the program at `index`, in the order the programs were added to the builder
+    pub fn program_id(&self, index: usize) -> Option<Pubkey> {
+        self.programs.get(index).map(|(program_id, _)| *program_id)
+    }
+
+    /// Request an airdrop of `lamports` to `pubkey` and wait for it to be confirmed.
+    pub fn airdrop(&self, pubkey: &Pubkey, lamports: u64) -> Result<()> {
+        let signature = self
+            .rpc_client
+            .request_airdrop(pubkey, lamports)
+            .map_err(|e| anyhow!("Failed to request airdrop: {}", e))?;
+        self.rpc_client
+            .poll_for_signature(&signature)
+            .map_err(|e| anyhow!("Failed to confirm airdrop: {}", e))?;
+        Ok(())
+    }
+
+    /// Wait until the validator reports itself as healthy and has processed its first blockhash.
+    fn wait_for_health(&self, timeout: Duration) -> Result<()> {
+        let start = Instant::now();
+        while start.elapsed() < timeout {
+            if self.rpc_client.get_health().is_ok()
+                && self.rpc_client.get_latest_blockhash().is_ok()
+            {
+                return Ok(());
+            }
+            sleep(Duration::from_millis(500));
+        }
+        Err(anyhow!(
+            "The test validator did not become healthy within {} seconds",
+            timeout.as_secs()
+        ))
+    }
+}
+
+impl Drop for TestValidator {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+        let _ = fs::remove_dir_all(&self.work_dir);
+    }
+}
+
+/// Reserve a free local port, asking the system for one. With `with_next`, the next port is
+/// reserved too, as the validator serves its websocket on the port after the RPC port.
+///
+/// The port stays reserved until the returned listeners are dropped, right before the validator
+/// binds it.
+fn reserve_port(with_next: bool) -> Result<(u16, Vec<TcpListener>)> {
+    for _ in 0..100 {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let port = listener.local_addr()?.port();
+        if !with_next {
+            return Ok((port, vec![listener]));
+        }
+        if let Some(next) = port.checked_add(1) {
+            if let Ok(next_listener) = TcpListener::bind(("127.0.0.1", next)) {
+                return Ok((port, vec![listener, next_listener]));
+            }
+        }
+    }
+    Err(anyhow!("Failed to find a free port for the test validator"))
+}
@@ -0,0 +1,170 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    serde_json::{json, Value},
+    solana_account_decoder::{
+        parse_token::{get_token_account_mint, parse_token, TokenAccountType},
+        parse_token_extension::UiExtension,
+    },
+    solana_client::rpc_client::RpcClient,
+    solana_sdk::{pubkey, pubkey::Pubkey},
+    std::collections::HashMap,
+};
+
+/// The ID of the SPL Token program.
+pub const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+/// The ID of the SPL Token-2022 program.
+pub const TOKEN_2022_PROGRAM_ID: Pubkey = pubkey!("TokenzQdBNbLqP5VEhdkAS6EHFLe1vWKumXtEoiBVQJ");
+
+/// The ID of the SPL Associated Token Account program.
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+/// Returns true if the program is the SPL Token or the SPL Token-2022 program.
+pub fn is_token_program(program_id: &Pubkey) -> bool {
+    *program_id == TOKEN_PROGRAM_ID || *program_id == TOKEN_2022_PROGRAM_ID
+}
+
+/// Derive the associated token account of an owner for a mint.
+///
+/// The token program is part of the seeds, so the address differs between mints of the SPL Token
+/// and the SPL Token-2022 programs.
+pub fn associated_token_address(owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[owner.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Get the token program owning a mint, which is either the SPL Token or the SPL Token-2022
+/// program.
+///
+/// # Errors
+///
+/// This function returns an error if the mint cannot be fetched or is not owned by a token program.
+pub fn mint_token_program(rpc_client: &RpcClient, mint: &Pubkey) -> Result<Pubkey> {
+    let account = rpc_client
+        .get_account(mint)
+        .map_err(|e| anyhow!("Error fetching mint {}: {}", mint, e))?;
+    if !is_token_program(&account.owner) {
+        return Err(anyhow!(
+            "Account {} is not a token mint: it is owned by {}",
+            mint,
+            account.owner
+        ));
+    }
+    Ok(account.owner)
+}
+
+/// An account owned by the SPL Token or the SPL Token-2022 program, decoded with its extensions.
+#[derive(Debug, PartialEq)]
+pub struct TokenAccount {
+    /// The address of the account
+    pub pubkey: Pubkey,
+    /// The token program owning the account
+    pub token_program: Pubkey,
+    /// The decoded account: a token account, a mint or a multisig
+    pub parsed: TokenAccountType,
+}
+
+impl TokenAccount {
+    /// Get the name of the token program owning the account.
+    pub fn program_name(&self) -> &'static str {
+        if self.token_program == TOKEN_2022_PROGRAM_ID {
+            "Token-2022"
+        } else {
+            "Token"
+        }
+    }
+
+    /// Get the names of the extensions of the account (always empty for SPL Token accounts).
+    pub fn extension_names(&self) -> Vec<String> {
+        let extensions: &[UiExtension] = match &self.parsed {
+            TokenAccountType::Account(account) => &account.extensions,
+            TokenAccountType::Mint(mint) => &mint.extensions,
+            TokenAccountType::Multisig(_) => &[],
+        };
+        extensions
+            .iter()
+            .map(|extension| {
+                // The extension name is the tag of its JSON representation
+                serde_json::to_value(extension)
+                    .ok()
+                    .and_then(|value| value.get("extension").cloned())
+                    .and_then(|name| name.as_str().map(|name| name.to_string()))
+                    .unwrap_or_else(|| "unknown".to_string())
+            })
+            .collect()
+    }
+
+    /// Returns a JSON representation of the account.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "pubkey": self.pubkey.to_string(),
+            "program": self.program_name(),
+            "parsed": serde_json::to_value(&self.parsed).unwrap_or(Value::Null),
+        })
+    }
+}
+
+/// Fetch and decode the token accounts among a list of accounts.
+///
+/// Accounts that do not exist or are not owned by a token program are skipped. The mints of token
+/// accounts are fetched as well, to get the number of decimals of the amounts.
+///
+/// # Errors
+///
+/// This function returns an error if the accounts cannot be fetched.
+pub fn fetch_token_accounts(
+    rpc_client: &RpcClient,
+    pubkeys: &[Pubkey],
+) -> Result<Vec<TokenAccount>> {
+    let accounts = rpc_client
+        .get_multiple_accounts(pubkeys)
+        .map_err(|e| anyhow!("Error fetching accounts: {}", e))?;
+    let token_accounts: Vec<_> = pubkeys
+        .iter()
+        .zip(accounts)
+        .filter_map(|(pubkey, account)| account.map(|account| (*pubkey, account)))
+        .filter(|(_, account)| is_token_program(&account.owner))
+        .collect();
+
+    // Fetch the decimals of the mints of the token accounts
+    let mut mints: Vec<Pubkey> = token_accounts
+        .iter()
+        .filter_map(|(_, account)| get_token_account_mint(&account.data))
+        .collect();
+    mints.sort();
+    mints.dedup();
+    let mut decimals: HashMap<Pubkey, u8> = HashMap::new();
+    if !mints.is_empty() {
+        let mint_accounts = rpc_client
+            .get_multiple_accounts(&mints)
+            .map_err(|e| anyhow!("Error fetching mints: {}", e))?;
+        for (mint, account) in mints.iter().zip(mint_accounts) {
+            if let Some(Ok(TokenAccountType::Mint(parsed))) =
+                account.map(|account| parse_token(&account.data, None))
+            {
+                decimals.insert(*mint, parsed.decimals);
+            }
+        }
+    }
+
+    Ok(token_accounts
+        .into_iter()
+        .filter_map(|(pubkey, account)| {
+            let mint_decimals =
+                get_token_account_mint(&account.data).and_then(|mint| decimals.get(&mint).copied());
+            parse_token(&account.data, mint_decimals)
+                .ok()
+                .map(|parsed| TokenAccount {
+                    pubkey,
+                    token_program: account.owner,
+                    parsed,
+                })
+        })
+        .collect())
+}
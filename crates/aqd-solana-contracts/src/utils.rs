@@ -1,25 +1,42 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use {
-    crate::borsh_encoding::{discriminator, encode_arguments, BorshToken},
-    anchor_syn::idl::{
-        Idl, IdlAccountItem, IdlInstruction, IdlType, IdlTypeDefinition, IdlTypeDefinitionTy::Enum,
-        IdlTypeDefinitionTy::Struct,
+    crate::{
+        borsh_encoding::encode_arguments,
+        idl_cache::{fetch_idl_with_json, is_idl_url},
+        idl_discriminators::IdlDiscriminators,
+        instruction_data::{get_borsh_token_vector, resolve_file_argument},
+        keypair::parse_keypair,
+        keystore::write_keystore_file,
+        token_accounts::{ASSOCIATED_TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID},
     },
+    anchor_syn::idl::{Idl, IdlAccountItem, IdlInstruction, IdlType, IdlTypeDefinition},
     anyhow::{anyhow, bail, Result},
-    base58::FromBase58,
-    num_bigint::BigInt,
+    aqd_utils::register_generated_file,
     solana_cli_config::{Config, CONFIG_FILE},
     solana_sdk::{
         instruction::AccountMeta,
-        pubkey::Pubkey,
+        pubkey::{Pubkey, MAX_SEEDS, MAX_SEED_LEN},
         signature::{write_keypair_file, Keypair, Signer},
-        signer::keypair::read_keypair_file,
         system_program,
     },
-    std::{ffi::OsStr, fs::File, str::FromStr},
+    std::{
+        ffi::OsStr,
+        fs,
+        io::{self, IsTerminal, Read},
+        str::FromStr,
+        sync::Mutex,
+    },
 };
 
+/// The path of the IDL JSON file standing for the standard input, e.g. with
+/// `solang compile ... | aqd solana show --idl -`.
+pub const STDIN_PATH: &str = "-";
+
+/// The IDL JSON read from the standard input, kept as the standard input can only be read once
+/// while a command may parse the IDL several times.
+static STDIN_IDL: Mutex<Option<String>> = Mutex::new(None);
+
 /// Parses an IDL (Interface Description Language) definition from a JSON file.
 ///
 /// Given a file path provided as an [`OsStr`], this function attempts to open the file and
@@ -28,27 +45,70 @@ use {
 ///
 /// # Arguments
 ///
-/// * `file` - The path to the JSON file containing the IDL instruction definition.
+/// * `file` - The path to the JSON file containing the IDL instruction definition,
+///   [`STDIN_PATH`] (`-`) to read the JSON from the standard input, or an HTTP(S) URL to download
+///   it from (see [`fetch_idl`](crate::fetch_idl)).
 ///
 /// # Errors
 ///
 /// This function can return errors if the provided file path is invalid, the file cannot be
 /// opened, or there are issues with parsing the JSON content.
 pub fn idl_from_json(file: &OsStr) -> Result<Idl> {
-    let f = match File::open(file) {
-        Ok(s) => s,
-        Err(e) => {
-            bail!("{}: error: {}", file.to_string_lossy(), e);
+    idl_with_discriminators_from_json(file).map(|(idl, _)| idl)
+}
+
+/// Parses an IDL definition from a JSON file like [`idl_from_json`], along with the discriminators
+/// embedded in it, if any.
+///
+/// # Errors
+///
+/// This function returns the errors of [`idl_from_json`], or an error if an embedded
+/// discriminator is not an array of bytes.
+pub fn idl_with_discriminators_from_json(file: &OsStr) -> Result<(Idl, IdlDiscriminators)> {
+    let (idl, json) = if let Some(url) = file.to_str().filter(|file| is_idl_url(file)) {
+        fetch_idl_with_json(url)?
+    } else if file == STDIN_PATH {
+        let json = read_stdin_idl()?;
+        let idl =
+            serde_json::from_str(&json).map_err(|e| anyhow!("standard input: error: {}", e))?;
+        (idl, json)
+    } else {
+        let json = match fs::read_to_string(file) {
+            Ok(json) => json,
+            Err(e) => {
+                bail!("{}: error: {}", file.to_string_lossy(), e);
+            }
+        };
+
+        // Parse the JSON into an IDL
+        match serde_json::from_str(&json) {
+            Ok(idl) => (idl, json),
+            Err(e) => {
+                bail!("{}: error: {}", file.to_string_lossy(), e);
+            }
         }
     };
+    let discriminators = IdlDiscriminators::from_json(&json)
+        .map_err(|e| anyhow!("{}: error: {}", file.to_string_lossy(), e))?;
+    Ok((idl, discriminators))
+}
 
-    // Parse the JSON into an IDL
-    match serde_json::from_reader(f) {
-        Ok(idl) => Ok(idl),
-        Err(e) => {
-            bail!("{}: error: {}", file.to_string_lossy(), e);
-        }
+/// Read the IDL JSON from the standard input, once.
+fn read_stdin_idl() -> Result<String> {
+    let mut stdin_idl = STDIN_IDL.lock().unwrap();
+    if let Some(idl) = stdin_idl.as_ref() {
+        return Ok(idl.clone());
+    }
+    let mut stdin = io::stdin();
+    if stdin.is_terminal() {
+        bail!("No IDL piped to the standard input: pipe the IDL JSON to aqd, e.g. cat flipper.json | aqd solana show --idl -");
     }
+    let mut idl = String::new();
+    stdin
+        .read_to_string(&mut idl)
+        .map_err(|e| anyhow!("Failed to read the IDL from the standard input: {}", e))?;
+    *stdin_idl = Some(idl.clone());
+    Ok(idl)
 }
 
 /// Constructs accounts, keypairs, and new accounts information for an IDL instruction.
@@ -59,17 +119,22 @@ pub fn idl_from_json(file: &OsStr) -> Result<Idl> {
 /// special actions are taken:
 ///
 /// - `new`: Create a new account and generate a keypair for it. The account's public key and
-///   keypair path are recorded for reference.
+///   keypair path are recorded for reference. The keypair is written to a plaintext keypair file,
+///   or to an encrypted keystore with [`construct_instruction_accounts_with_password`].
 ///
 /// - `self`: Use the keypair specified in the local solana configuration file.
 ///
 /// - `system`: Use the system program ID for the account. This is equivalent to passing in the
-///  system program ID as a public key.
+///   system program ID as a public key.
+///
+/// - `token`, `token-2022` and `associated-token`: Use the ID of the SPL Token, SPL Token-2022 or
+///   Associated Token Account program.
 ///
 /// For other raw account arguments, the function checks if it's a valid keypair path or a valid
 /// public key. If it's a valid keypair path, the keypair is loaded and used for the account. If
 /// it's a valid public key, the public key is used for the account. Otherwise, an error is
-/// returned.
+/// returned. Signer accounts passed as a public key are not added to the signers, and must be
+/// signed externally (e.g. by a multisig vault).
 ///
 /// # Arguments
 ///
@@ -101,6 +166,20 @@ pub fn idl_from_json(file: &OsStr) -> Result<Idl> {
 pub fn construct_instruction_accounts(
     instr: &IdlInstruction,
     raw_args: &[String],
+) -> Result<(Vec<AccountMeta>, Vec<Keypair>, Vec<(Pubkey, String)>)> {
+    construct_instruction_accounts_with_password(instr, raw_args, None)
+}
+
+/// Constructs accounts, keypairs, and new accounts information for an IDL instruction, writing
+/// the keypairs of the `new` accounts to keystores encrypted with `keystore_password`, if any,
+/// instead of plaintext keypair files.
+///
+/// See [`construct_instruction_accounts`].
+#[allow(clippy::type_complexity)]
+pub fn construct_instruction_accounts_with_password(
+    instr: &IdlInstruction,
+    raw_args: &[String],
+    keystore_password: Option<&str>,
 ) -> Result<(Vec<AccountMeta>, Vec<Keypair>, Vec<(Pubkey, String)>)> {
     // Initialize the return values
     let mut accounts: Vec<AccountMeta> = vec![];
@@ -124,10 +203,16 @@ pub fn construct_instruction_accounts(
                 // "new" is a special keyword that creates a new account
                 let keypair = Keypair::new();
                 let pubkey = keypair.pubkey();
-                // Write the keypair to a file
+                // Write the keypair to a file, encrypted if a keystore password is set
                 let keypair_path = format!("{}-{}.json", account_name, pubkey);
-                write_keypair_file(&keypair, &keypair_path)
-                    .map_err(|_| anyhow!("Couldn't write keypair file to disk"))?;
+                match keystore_password {
+                    Some(password) => write_keystore_file(&keypair, &keypair_path, password)?,
+                    None => write_keypair_file(&keypair, &keypair_path)
+                        .map(|_| ())
+                        .map_err(|_| anyhow!("Couldn't write keypair file to disk"))?,
+                }
+                // Removed by aqd if the transaction is not submitted
+                register_generated_file(&keypair_path);
                 new_accounts.push((pubkey, keypair_path.clone()));
 
                 (Some(keypair), pubkey)
@@ -136,7 +221,7 @@ pub fn construct_instruction_accounts(
                 // "self" is a special keyword that uses the keypair from the config file
                 let config_file = CONFIG_FILE.as_ref().unwrap();
                 let cli_config = Config::load(config_file).unwrap_or_default();
-                let keypair = read_keypair_file(&cli_config.keypair_path).unwrap();
+                let keypair = parse_keypair(&cli_config.keypair_path)?;
                 let pubkey = keypair.pubkey();
                 (Some(keypair), pubkey)
             }
@@ -145,12 +230,27 @@ pub fn construct_instruction_accounts(
                 None,
                 system_program::id(),
             ),
+            "token" => (
+                // "token" is a special keyword that uses the SPL Token program ID
+                None,
+                TOKEN_PROGRAM_ID,
+            ),
+            "token-2022" => (
+                // "token-2022" is a special keyword that uses the SPL Token-2022 program ID
+                None,
+                TOKEN_2022_PROGRAM_ID,
+            ),
+            "associated-token" => (
+                // "associated-token" is a special keyword that uses the Associated Token Account program ID
+                None,
+                ASSOCIATED_TOKEN_PROGRAM_ID,
+            ),
             // There are 2 cases here:
-            // 1. The user passes in a keypair path
+            // 1. The user passes in a keypair (path, JSON byte array, base58 secret key or mnemonic)
             // 2. The user passes in a public key
             _ => {
                 // First, check if the user passed in a keypair path
-                let keypair = read_keypair_file(raw_pubkey);
+                let keypair = parse_keypair(raw_pubkey);
                 match keypair {
                     Ok(keypair) => {
                         let pubkey = keypair.pubkey();
@@ -167,12 +267,12 @@ pub fn construct_instruction_accounts(
             }
         };
 
-        // Add the keypair to the list of signers if the account is a signer
+        // Add the keypair to the list of signers if the account is a signer.
+        // Signer accounts passed as a public key are signed externally (e.g. by a multisig vault).
         if is_signer {
-            let key_pair = key_pair.ok_or_else(
-                || anyhow!("The provided argument for account: {} is not a valid keyword, keypair path or public key. \nProvided argument: {}", account_name , raw_pubkey),
-            )?; // This should never fail
-            signers.push(key_pair);
+            if let Some(key_pair) = key_pair {
+                signers.push(key_pair);
+            }
         }
         accounts.push(AccountMeta {
             pubkey,
@@ -184,359 +284,117 @@ pub fn construct_instruction_accounts(
     Ok((accounts, signers, new_accounts))
 }
 
-/// Constructs binary data for an instruction based on the provided IDL instruction and raw arguments.
-///
-/// Given an [`IdlInstruction`], a vector of raw arguments, and a list of IDL type definitions, this
-/// function encodes the arguments into binary data following the instruction's layout and type
-/// definitions. The resulting binary data can be used as an instruction to invoke a smart contract
-/// on a blockchain.
-///
-/// # Arguments
-///
-/// * `instr` - The IDL instruction of type [`IdlInstruction`] for which to construct binary data.
-///
-/// * `raw_args` - A vector of raw argument values represented as strings.
+/// Finds the program derived address (PDA) and bump for a list of seeds.
 ///
-/// * `custom_types` - A vector of IDL type definitions used for encoding arguments.
-///
-/// # Returns
-///
-/// Returns a [`Result`] containing the encoded binary data as a [`Vec<u8>`].
+/// The seeds are given as a comma-separated list, with an optional `pda:` prefix (e.g.
+/// `pda:counter,arg:id`). Each seed can be one of the following:
 ///
-/// # Errors
-///
-/// This function can return an error in the following cases:
+/// - `str:<TEXT>` or `<TEXT>`: The UTF-8 bytes of the string.
 ///
-/// - If any argument is missing or if the provided raw arguments do not match the expected
-///   arguments defined by the IDL instruction.
+/// - `hex:<HEX>`: The bytes of the hex string.
 ///
-/// - If the IDL-defined types cannot be found or if there is an issue with encoding the arguments
-///   based on these types.
-pub fn construct_instruction_data(
-    instr: &IdlInstruction,
-    raw_args: &[String],
-    custom_types: &Vec<IdlTypeDefinition>,
-) -> Result<Vec<u8>> {
-    // Construct the discriminator (the first 8 bytes of the instruction data)
-    // The namespace is always "global"
-    let mut data = discriminator("global", &instr.name);
-    let mut args: Vec<BorshToken> = vec![];
-
-    // Loop through the arguments and encode them given the IDL instruction
-    for (i, arg) in instr.args.iter().enumerate() {
-        let arg_name = arg.name.clone();
-        let arg_type = arg.ty.clone();
-        let arg_val = raw_args
-            .get(i)
-            .ok_or_else(|| anyhow!("Missing argument {}", arg_name))?;
-
-        // Encode the argument based on the IDL type
-        let mut borsh_args = get_borsh_token_vector(arg_val.to_string(), &arg_type, custom_types)?;
-        args.append(&mut borsh_args);
-    }
-
-    let mut encoded_data = encode_arguments(&args);
-    data.append(&mut encoded_data);
-
-    Ok(data)
-}
-
-/// Converts a raw argument value into a vector of Borsh tokens based on the provided IDL type.
+/// - `pubkey:<PUBKEY>`: The 32 bytes of the public key.
 ///
-/// This function takes a raw argument value as a string, an IDL type definition, and a list of
-/// IDL type definitions for reference. It then converts the raw argument value into a vector of
-/// Borsh tokens, considering the specified IDL type and nested types if applicable.
+/// - `arg:<NAME>`: The Borsh encoding of the instruction argument `NAME`, taken from the raw data
+///   arguments. As in Anchor, strings and byte vectors are used without their length prefix.
 ///
 /// # Arguments
 ///
-/// * `arg_value` - The raw argument value to be converted. The expected input format depends on
-/// the IDL type:
+/// * `seeds` - The comma-separated list of seeds.
 ///
-///   - For structs, provide a JSON string representing the data structure.
-///   - For arrays and vectors, provide a comma-separated string of values.
+/// * `program_id` - The ID of the program the address is derived for.
 ///
-/// * `arg_type` - The IDL type definition specifying the expected type of the argument.
+/// * `instr` - The IDL instruction of type [`IdlInstruction`] whose arguments can be used as seeds.
 ///
-/// * `custom_types` - A vector of IDL type definitions used for resolving nested types.
+/// * `data_args` - The raw data arguments of the instruction.
 ///
-/// # Returns
-///
-/// Returns a [`Result`] containing the vector of Borsh tokens ([`Vec<BorshToken>`]) representing
-/// the converted argument value.
+/// * `custom_types` - A vector of IDL type definitions used for encoding arguments.
 ///
 /// # Errors
 ///
-/// This function can return an error if there is an issue with converting the raw argument value
-/// to the specified Borsh tokens based on the given IDL type.
-fn get_borsh_token_vector(
-    arg_value: String,
-    arg_type: &IdlType,
+/// This function returns an error if a seed is invalid or longer than 32 bytes, if there are more
+/// than 16 seeds, or if no valid bump is found.
+pub fn find_pda(
+    seeds: &str,
+    program_id: &Pubkey,
+    instr: &IdlInstruction,
+    data_args: &[String],
     custom_types: &Vec<IdlTypeDefinition>,
-) -> Result<Vec<BorshToken>> {
-    let mut args: Vec<BorshToken> = vec![];
-    match arg_type {
-        IdlType::Bool => {
-            let val = arg_value.parse::<bool>().map_err(|_e| {
-                anyhow!("The provided argument for bool is not a valid boolean. \nProvided argument: {}\n", arg_value)
-            })?;
-            args.push(BorshToken::Bool(val));
-        }
-        IdlType::U8 => {
-            let val = arg_value.parse::<u8>().map_err(|_e| {
-                anyhow!("The provided argument for u8 is not a valid unsigned integer. \nProvided argument: {}\n", arg_value)
-            })?;
-            args.push(BorshToken::Uint {
-                width: 8,
-                value: BigInt::from(val),
-            });
-        }
-        IdlType::I8 => {
-            let val = arg_value.parse::<i8>().map_err(|_e| {
-                anyhow!("The provided argument for i8 is not a valid signed integer. \nProvided argument: {}\n", arg_value)
-            })?;
-            args.push(BorshToken::Int {
-                width: 8,
-                value: BigInt::from(val),
-            });
-        }
-        IdlType::U16 => {
-            let val = arg_value.parse::<u16>().map_err(|_e| {
-                anyhow!("The provided argument for u16 is not a valid unsigned integer. \nProvided argument: {}\n", arg_value)
-            })?;
-            args.push(BorshToken::Uint {
-                width: 16,
-                value: BigInt::from(val),
-            });
-        }
-        IdlType::I16 => {
-            let val = arg_value.parse::<i16>().map_err(|_e| {
-                anyhow!("The provided argument for i16 is not a valid signed integer. \nProvided argument: {}\n", arg_value)
-            })?;
-            args.push(BorshToken::Int {
-                width: 16,
-                value: BigInt::from(val),
-            });
-        }
-        IdlType::U32 => {
-            let val = arg_value.parse::<u32>().map_err(
-                |_e| anyhow!("The provided argument for u32 is not a valid unsigned integer. \nProvided argument: {}\n", arg_value),
-            )?;
-            args.push(BorshToken::Uint {
-                width: 32,
-                value: BigInt::from(val),
-            });
-        }
-        IdlType::I32 => {
-            let val = arg_value.parse::<i32>().map_err(
-                |_e| anyhow!("The provided argument for i32 is not a valid signed integer. \nProvided argument: {}\n", arg_value),
-            )?;
-            args.push(BorshToken::Int {
-                width: 32,
-                value: BigInt::from(val),
-            });
-        }
-        IdlType::F32 => {
-            return Err(anyhow!("Float is not supported"));
-        }
-        IdlType::U64 => {
-            let val = arg_value.parse::<u64>().map_err(
-                |_e| anyhow!("The provided argument for u64 is not a valid unsigned integer. \nProvided argument: {}\n", arg_value),
-            )?;
-            args.push(BorshToken::Uint {
-                width: 64,
-                value: BigInt::from(val),
-            });
-        }
-        IdlType::I64 => {
-            let val = arg_value.parse::<i64>().map_err(
-                |_e| anyhow!("The provided argument for i64 is not a valid signed integer. \nProvided argument: {}\n", arg_value),
-            )?;
-            args.push(BorshToken::Int {
-                width: 64,
-                value: BigInt::from(val),
-            });
-        }
-        IdlType::F64 => {
-            return Err(anyhow!("Float is not supported"));
-        }
-        IdlType::U128 => {
-            let val = arg_value.parse::<u128>().map_err(
-                |_e| anyhow!("The provided argument for u128 is not a valid unsigned integer. \nProvided argument: {}\n", arg_value),
-            )?;
-            args.push(BorshToken::Uint {
-                width: 128,
-                value: BigInt::from(val),
-            });
-        }
-        IdlType::I128 => {
-            let val = arg_value.parse::<i128>().map_err(
-                |_e| anyhow!("The provided argument for i128 is not a valid signed integer. \nProvided argument: {}\n", arg_value),
-            )?;
-            args.push(BorshToken::Int {
-                width: 128,
-                value: BigInt::from(val),
-            });
-        }
-        IdlType::U256 => {
-            let val = BigInt::parse_bytes(arg_value.as_bytes(), 10).ok_or_else(
-                || anyhow!("The provided argument for u256 is not a valid unsigned integer. \nProvided argument: {}\n", arg_value),
-            )?;
-            args.push(BorshToken::Uint {
-                width: 256,
-                value: val,
-            });
-        }
-        IdlType::I256 => {
-            let val = BigInt::parse_bytes(arg_value.as_bytes(), 10).ok_or_else(
-                || anyhow!("The provided argument for i256 is not a valid unsigned integer. \nProvided argument: {}\n", arg_value),
-            )?;
-            args.push(BorshToken::Int {
-                width: 256,
-                value: val,
-            });
-        }
-        IdlType::Bytes => {
-            let val = match hex::decode(&arg_value) {
-                Ok(val) => val,
-                Err(_) => {
-                    return Err(anyhow!("The provided argument for Bytes is not a valid hex string. \nProvided argument: {}\n", arg_value))
-                }
-            };
-            args.push(BorshToken::Bytes(val));
-        }
-        IdlType::String => {
-            args.push(BorshToken::String(arg_value.to_string()));
-        }
-        IdlType::PublicKey => {
-            let val = arg_value.from_base58().map_err(|_e| {
-                anyhow!("The provided argument for PublicKey is not a valid base58 string. \nProvided argument: {}\n", arg_value)
-            })?;
-            let val = <[u8; 32]>::try_from(val).map_err(|_e| {
-                anyhow!("The provided argument for PublicKey is not a valid base58 string. \nProvided argument: {}\n", arg_value)
-            })?;
-            args.push(BorshToken::Address(val));
-        }
-        IdlType::Defined(ty) => {
-            let defined_type = custom_types
-                .iter()
-                .find(|t| t.name == *ty)
-                .ok_or_else(|| anyhow!("Type definition with name {} not found", ty))?;
-            let mut borsh_args_for_defined_type =
-                encode_id_defined_type(arg_value.to_string(), defined_type, custom_types)?;
-            args.append(&mut borsh_args_for_defined_type);
-        }
-        IdlType::Option(_) => {
-            return Err(anyhow!("Option is not supported"));
-        }
-        IdlType::Vec(elem_type) => {
-            // Split the string into a vector of strings
-            let val: Vec<String> = arg_value.split(',').map(|s| s.to_string()).collect();
-            let mut borsh_args: Vec<BorshToken> = vec![];
-            for arg in val {
-                let mut borsh_arg = get_borsh_token_vector(arg, elem_type, custom_types)?;
-                borsh_args.append(&mut borsh_arg);
-            }
-            args.push(BorshToken::Array(borsh_args));
-        }
-        IdlType::Array(elem_type, size) => {
-            // Split the string into a vector of strings
-            let val: Vec<String> = arg_value.split(',').map(|s| s.to_string()).collect();
-            // Make sure the number of elements matches the size of the array
-            if val.len() != *size {
-                return Err(anyhow!(
-                    "The number of elements in the array does not match the size of the array. \nProvided argument: {}\n",
-                    arg_value
-                ));
-            }
-            let mut borsh_args: Vec<BorshToken> = vec![];
-            for arg in val {
-                let mut borsh_arg = get_borsh_token_vector(arg, elem_type, custom_types)?;
-                borsh_args.append(&mut borsh_arg);
-            }
-            args.push(BorshToken::FixedArray(borsh_args));
-        }
+) -> Result<(Pubkey, u8)> {
+    let seeds = seeds.strip_prefix("pda:").unwrap_or(seeds);
+    let seeds: Vec<Vec<u8>> = seeds
+        .split(',')
+        .map(|seed| pda_seed_bytes(seed, instr, data_args, custom_types))
+        .collect::<Result<_>>()?;
+
+    // A bump seed is appended to the seeds, so only 15 seeds can be provided
+    if seeds.len() >= MAX_SEEDS {
+        bail!(
+            "Too many PDA seeds: {}. At most {} seeds are allowed",
+            seeds.len(),
+            MAX_SEEDS - 1
+        );
     }
-    Ok(args)
+    if let Some(seed) = seeds.iter().find(|seed| seed.len() > MAX_SEED_LEN) {
+        bail!(
+            "PDA seed {:?} is {} bytes long. Seeds can be at most {} bytes long",
+            seed,
+            seed.len(),
+            MAX_SEED_LEN
+        );
+    }
+
+    let seeds: Vec<&[u8]> = seeds.iter().map(|seed| seed.as_slice()).collect();
+    Pubkey::try_find_program_address(&seeds, program_id)
+        .ok_or_else(|| anyhow!("Unable to find a valid bump for the PDA seeds"))
 }
 
-/// Converts a raw argument value into a vector of Borsh tokens for a custom IDL-defined type.
-///
-/// This function takes a raw argument value as a string, a custom IDL type definition, and a list
-/// of IDL type definitions for reference. It then converts the raw argument value into a vector of
-/// Borsh tokens based on the specified custom IDL type, including handling structs and enums as per
-/// the IDL definition.
-///
-/// # Arguments
-///
-/// * `arg_value` - The raw argument value as a string to be converted.
-///
-/// * `defined_type` - The custom IDL type definition specifying the expected type of the argument.
-///
-/// * `custom_types` - A vector of IDL type definitions used for resolving nested types.
-///
-/// # Returns
-///
-/// Returns a [`Result`] containing the vector of Borsh tokens ([`Vec<BorshToken>`]) representing
-/// the converted argument value.
-fn encode_id_defined_type(
-    arg_value: String,
-    defined_type: &IdlTypeDefinition,
+/// Converts a single PDA seed into bytes. See [`find_pda`] for the supported formats.
+fn pda_seed_bytes(
+    seed: &str,
+    instr: &IdlInstruction,
+    data_args: &[String],
     custom_types: &Vec<IdlTypeDefinition>,
-) -> Result<Vec<BorshToken>> {
-    let mut response: Vec<BorshToken> = vec![];
-    let ty = &defined_type.ty;
-    match ty {
-        Struct { fields } => {
-            // The user could either pass a JSON object or a path to a JSON file
-            // If the user passes a path to a JSON file, we need to read the file first
-            let arg_value = match std::fs::read_to_string(&arg_value) {
-                Ok(s) => s,
-                Err(_) => arg_value,
-            };
-
-            // The user should pass a json object
-            // for example: {"a": 1, "b": 2}
-            // we need to parse the json object and then encode it
-            let json_object: serde_json::Value = serde_json::from_str(&arg_value).map_err(
-                |_e| anyhow!("The provided argument for Struct is not a valid JSON object. \nProvided argument: {}\n", arg_value),
-            )?;
-            for field in fields {
-                let field_name = &field.name;
-                let field_ty = &field.ty;
-                let field_value = json_object
-                    .get(field_name)
-                    .ok_or_else(|| anyhow!("Field {} not found", field_name))?;
-                let mut borsh_args =
-                    get_borsh_token_vector(field_value.to_string(), field_ty, custom_types)?;
-                response.append(&mut borsh_args);
-            }
-        }
-        Enum { variants } => {
-            // If the variant is a string, we need to remove the quotes
-            let arg_value = arg_value.replace('\"', "");
-            // The user passes a String of the variant name
-            // for example: "A"
-            // we need to find index of the variant and then encode it
-            let variant_index = variants
-                .iter()
-                .position(|v| v.name == arg_value)
-                .ok_or_else(|| {
-                    anyhow!(
-                        "Variant {} not found. \nAvailable variants of {}: {:?}",
-                        arg_value,
-                        defined_type.name,
-                        variants
-                            .iter()
-                            .map(|v| v.clone().name)
-                            .collect::<Vec<String>>()
-                    )
-                })?;
+) -> Result<Vec<u8>> {
+    if let Some(hex_seed) = seed.strip_prefix("hex:") {
+        hex::decode(hex_seed.trim_start_matches("0x"))
+            .map_err(|e| anyhow!("Invalid hex PDA seed {}: {}", hex_seed, e))
+    } else if let Some(pubkey) = seed.strip_prefix("pubkey:") {
+        let pubkey = Pubkey::from_str(pubkey)
+            .map_err(|e| anyhow!("Invalid public key PDA seed {}: {}", pubkey, e))?;
+        Ok(pubkey.to_bytes().to_vec())
+    } else if let Some(arg_name) = seed.strip_prefix("arg:") {
+        let index = instr
+            .args
+            .iter()
+            .position(|arg| arg.name == arg_name)
+            .ok_or_else(|| {
+                anyhow!(
+                    "PDA seed argument {} not found in instruction {}",
+                    arg_name,
+                    instr.name
+                )
+            })?;
+        let arg_type = &instr.args[index].ty;
+        let arg_val = data_args
+            .get(index)
+            .ok_or_else(|| anyhow!("Missing argument {}", arg_name))?;
+        let tokens = get_borsh_token_vector(
+            arg_name,
+            resolve_file_argument(arg_val)
+                .map_err(|e| anyhow!("Invalid argument {}: {}", arg_name, e))?,
+            arg_type,
+            custom_types,
+        )?;
+        let encoded = encode_arguments(&tokens);
 
-            let mut borsh_args =
-                get_borsh_token_vector(variant_index.to_string(), &IdlType::U8, custom_types)?;
-            response.append(&mut borsh_args);
+        // Strings and byte vectors are used as seeds without their length prefix
+        match arg_type {
+            IdlType::String | IdlType::Bytes => Ok(encoded[4..].to_vec()),
+            _ => Ok(encoded),
         }
+    } else {
+        let seed = seed.strip_prefix("str:").unwrap_or(seed);
+        Ok(seed.as_bytes().to_vec())
     }
-
-    Ok(response)
 }
@@ -0,0 +1,47 @@
+contract NestedTypes {
+    // Struct definition
+    struct Person {
+        string name;
+        uint8 age;
+    }
+
+    struct Blob {
+        string label;
+        bytes data;
+        uint8[] payload;
+        bytes4 hash;
+    }
+
+    // State variables
+    Person[] public people;
+    uint8[][] public matrix;
+    string[] public names;
+    Blob public blob;
+
+    @payer(payer)
+    @space(1024)
+	constructor() {}
+
+    function setPeople(Person[] newPeople) public {
+        delete people;
+        for (uint32 i = 0; i < newPeople.length; i++) {
+            people.push(newPeople[i]);
+        }
+    }
+
+    function setMatrix(uint8[][] newMatrix) public {
+        matrix = newMatrix;
+    }
+
+    function setGrid(uint8[2][2] grid) public pure returns (uint8) {
+        return grid[0][0] + grid[1][1];
+    }
+
+    function setNames(string[] newNames) public {
+        names = newNames;
+    }
+
+    function setBlob(Blob newBlob) public {
+        blob = newBlob;
+    }
+}
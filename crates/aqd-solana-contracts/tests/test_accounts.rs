@@ -0,0 +1,301 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anchor_syn::idl::Idl,
+    anyhow::Result,
+    aqd_solana_contracts::{
+        account_discriminator, decode_program_account, diff_account, stream_program_accounts,
+        FieldChange, IdlDiscriminators, MemcmpFilter,
+    },
+    serde_json::json,
+    solana_account_decoder::{UiAccount, UiAccountEncoding},
+    solana_client::{
+        rpc_client::RpcClient,
+        rpc_request::RpcRequest,
+        rpc_response::{Response, RpcKeyedAccount, RpcResponseContext},
+    },
+    solana_sdk::{account::Account, pubkey::Pubkey},
+    std::{collections::HashMap, str::FromStr},
+};
+
+/// An IDL with a single account type, used to test account decoding.
+fn counter_idl() -> Result<Idl> {
+    let idl = json!({
+        "version": "0.0.1",
+        "name": "counter",
+        "instructions": [],
+        "accounts": [
+            {
+                "name": "Counter",
+                "type": {
+                    "kind": "struct",
+                    "fields": [
+                        { "name": "count", "type": "u64" },
+                        { "name": "authority", "type": "publicKey" }
+                    ]
+                }
+            }
+        ]
+    });
+    Ok(serde_json::from_value(idl)?)
+}
+
+/// Purpose: This test checks that a program account is decoded using the accounts section of the IDL.
+#[test]
+pub fn test_decode_program_account() -> Result<()> {
+    let idl = counter_idl()?;
+    let authority = Pubkey::new_unique();
+
+    // The account data is the discriminator, followed by the Borsh encoded fields.
+    let mut data = account_discriminator("Counter");
+    data.extend_from_slice(&5u64.to_le_bytes());
+    data.extend_from_slice(authority.as_ref());
+    let account = Account {
+        lamports: 1_000,
+        data,
+        owner: Pubkey::new_unique(),
+        executable: false,
+        rent_epoch: 0,
+    };
+
+    let decoded = decode_program_account(
+        &idl,
+        &IdlDiscriminators::default(),
+        Pubkey::new_unique(),
+        account,
+    );
+
+    assert_eq!(decoded.account_type, Some("Counter".to_string()));
+    assert_eq!(decoded.decode_error, None);
+    assert_eq!(
+        decoded.decoded_data,
+        Some(json!({ "count": 5, "authority": authority.to_string() }))
+    );
+
+    Ok(())
+}
+
+/// Purpose: This test checks that a program account is identified by the discriminator embedded in the IDL JSON, of any length, rather than by the computed one.
+#[test]
+pub fn test_decode_program_account_embedded_discriminator() -> Result<()> {
+    let mut idl_json = serde_json::to_value(counter_idl()?)?;
+    idl_json["accounts"][0]["discriminator"] = json!([1, 2, 3, 4]);
+    let idl: Idl = serde_json::from_value(idl_json.clone())?;
+    let discriminators = IdlDiscriminators::from_value(&idl_json)?;
+    assert_eq!(discriminators.account("Counter"), vec![1, 2, 3, 4]);
+    let authority = Pubkey::new_unique();
+
+    let account = |mut data: Vec<u8>| {
+        data.extend_from_slice(&5u64.to_le_bytes());
+        data.extend_from_slice(authority.as_ref());
+        Account {
+            lamports: 1_000,
+            data,
+            owner: Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 0,
+        }
+    };
+
+    let decoded = decode_program_account(
+        &idl,
+        &discriminators,
+        Pubkey::new_unique(),
+        account(vec![1, 2, 3, 4]),
+    );
+    assert_eq!(decoded.account_type, Some("Counter".to_string()));
+    assert_eq!(
+        decoded.decoded_data,
+        Some(json!({ "count": 5, "authority": authority.to_string() }))
+    );
+
+    // The computed discriminator no longer matches
+    let decoded = decode_program_account(
+        &idl,
+        &discriminators,
+        Pubkey::new_unique(),
+        account(account_discriminator("Counter")),
+    );
+    assert_eq!(decoded.account_type, None);
+
+    Ok(())
+}
+
+/// Purpose: This test checks that accounts with unknown or truncated data are reported, not decoded.
+#[test]
+pub fn test_decode_invalid_program_account() -> Result<()> {
+    let idl = counter_idl()?;
+
+    // Unknown discriminator
+    let account = Account {
+        lamports: 1_000,
+        data: vec![0; 48],
+        owner: Pubkey::new_unique(),
+        executable: false,
+        rent_epoch: 0,
+    };
+    let decoded = decode_program_account(
+        &idl,
+        &IdlDiscriminators::default(),
+        Pubkey::new_unique(),
+        account,
+    );
+    assert_eq!(decoded.account_type, None);
+    assert!(decoded.decode_error.is_some());
+
+    // Truncated data
+    let mut data = account_discriminator("Counter");
+    data.extend_from_slice(&5u64.to_le_bytes());
+    let account = Account {
+        lamports: 1_000,
+        data,
+        owner: Pubkey::new_unique(),
+        executable: false,
+        rent_epoch: 0,
+    };
+    let decoded = decode_program_account(
+        &idl,
+        &IdlDiscriminators::default(),
+        Pubkey::new_unique(),
+        account,
+    );
+    assert_eq!(decoded.account_type, Some("Counter".to_string()));
+    assert_eq!(decoded.decoded_data, None);
+    assert!(decoded.decode_error.is_some());
+
+    Ok(())
+}
+
+/// Purpose: This test checks that the streamed program accounts are fetched by their addresses, and
+/// that the accounts closed in between are skipped.
+#[test]
+pub fn test_stream_program_accounts() -> Result<()> {
+    let idl = counter_idl()?;
+    let owner = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let (counter, closed) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+    let mut data = account_discriminator("Counter");
+    data.extend_from_slice(&5u64.to_le_bytes());
+    data.extend_from_slice(authority.as_ref());
+    let account = Account {
+        lamports: 1_000,
+        data,
+        owner,
+        executable: false,
+        rent_epoch: 0,
+    };
+    let encode = |pubkey: &Pubkey, account: &Account| {
+        UiAccount::encode(pubkey, account, UiAccountEncoding::Base64, None, None)
+    };
+
+    // The addresses are fetched without the data, and the second account is closed before its page
+    let mocks = HashMap::from([
+        (
+            RpcRequest::GetProgramAccounts,
+            json!([counter, closed]
+                .iter()
+                .map(|pubkey| RpcKeyedAccount {
+                    pubkey: pubkey.to_string(),
+                    account: encode(pubkey, &Account::default()),
+                })
+                .collect::<Vec<_>>()),
+        ),
+        (
+            RpcRequest::GetMultipleAccounts,
+            serde_json::to_value(Response {
+                context: RpcResponseContext {
+                    slot: 1,
+                    api_version: None,
+                },
+                value: vec![Some(encode(&counter, &account)), None],
+            })?,
+        ),
+    ]);
+    let rpc_client = RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks);
+
+    let mut accounts = vec![];
+    stream_program_accounts(
+        &rpc_client,
+        &owner,
+        &idl,
+        &IdlDiscriminators::default(),
+        None,
+        &[],
+        Some("Counter"),
+        |account| {
+            accounts.push(account);
+            Ok(())
+        },
+    )?;
+    assert_eq!(accounts.len(), 1);
+    assert_eq!(accounts[0].pubkey, counter);
+    assert_eq!(
+        accounts[0].decoded_data,
+        Some(json!({ "count": 5, "authority": authority.to_string() }))
+    );
+
+    Ok(())
+}
+
+/// Purpose: This test checks that a memcmp filter is parsed from `<OFFSET>:<BASE58_BYTES>`.
+#[test]
+pub fn test_parse_memcmp_filter() -> Result<()> {
+    let filter = MemcmpFilter::from_str("8:2VfUX")?;
+    assert_eq!(filter.offset, 8);
+    assert_eq!(filter.bytes, vec![1, 2, 3, 4]);
+
+    assert!(MemcmpFilter::from_str("8").is_err());
+    assert!(MemcmpFilter::from_str("eight:2VfUX").is_err());
+
+    Ok(())
+}
+
+/// Purpose: This test checks that the diff of an account reports the changed decoded fields only.
+#[test]
+pub fn test_diff_account() -> Result<()> {
+    let idl = counter_idl()?;
+    let pubkey = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+
+    let counter = |count: u64| {
+        let mut data = account_discriminator("Counter");
+        data.extend_from_slice(&count.to_le_bytes());
+        data.extend_from_slice(authority.as_ref());
+        let account = Account {
+            lamports: 1_000,
+            data,
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        };
+        decode_program_account(&idl, &IdlDiscriminators::default(), pubkey, account)
+    };
+    let before = counter(5);
+    let after = counter(6);
+
+    // Unchanged account
+    assert_eq!(diff_account(pubkey, Some(&before), Some(&before)), None);
+
+    // Changed field
+    let diff = diff_account(pubkey, Some(&before), Some(&after)).unwrap();
+    assert_eq!(diff.account_type, Some("Counter".to_string()));
+    assert_eq!(
+        diff.changes,
+        vec![FieldChange {
+            field: "data.count".to_string(),
+            before: json!(5),
+            after: json!(6),
+        }]
+    );
+
+    // Created account
+    let diff = diff_account(pubkey, None, Some(&after)).unwrap();
+    assert!(diff
+        .changes
+        .iter()
+        .any(|change| change.field == "lamports" && change.before.is_null()));
+
+    Ok(())
+}
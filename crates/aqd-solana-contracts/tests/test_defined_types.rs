@@ -44,7 +44,7 @@ pub async fn test_defined_types_new_data() -> Result<()> {
     // Verify the instruction data is correct.
     assert_eq!(
         data,
-        vec![135, 44, 205, 198, 25, 1, 72, 188, 7, 0, 0, 0, 34, 65, 108, 105, 99, 101, 34, 30, 0]
+        vec![135, 44, 205, 198, 25, 1, 72, 188, 5, 0, 0, 0, 65, 108, 105, 99, 101, 30, 0]
     );
 
     Ok(())
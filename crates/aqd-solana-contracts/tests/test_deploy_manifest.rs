@@ -0,0 +1,44 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::Result,
+    aqd_solana_contracts::{DeployManifest, ManifestProgram},
+    std::path::Path,
+};
+
+/// Purpose: This test checks that the programs of a deploy manifest are parsed, with paths relative to the manifest.
+#[test]
+pub fn test_parse_deploy_manifest() -> Result<()> {
+    let content = r#"
+        [[programs]]
+        name = "flip"
+        path = "build/flipper.so"
+
+        [[programs]]
+        path = "/programs/counter.so"
+    "#;
+    let manifest = DeployManifest::from_toml(Path::new("/project"), content)?;
+
+    assert_eq!(
+        manifest.programs,
+        vec![
+            ManifestProgram {
+                name: "flip".to_string(),
+                path: Path::new("/project/build/flipper.so").to_path_buf(),
+            },
+            // The name defaults to the file name of the program
+            ManifestProgram {
+                name: "counter".to_string(),
+                path: Path::new("/programs/counter.so").to_path_buf(),
+            },
+        ]
+    );
+
+    // A manifest without programs, or with a program without a path, is rejected
+    assert!(DeployManifest::from_toml(Path::new("/project"), "").is_err());
+    assert!(
+        DeployManifest::from_toml(Path::new("/project"), "[[programs]]\nname = \"flip\"").is_err()
+    );
+
+    Ok(())
+}
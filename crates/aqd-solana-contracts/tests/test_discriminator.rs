@@ -0,0 +1,81 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::Result,
+    aqd_solana_contracts::{
+        account_discriminator, discriminator, IdlDiscriminators, ACCOUNT_NAMESPACE,
+        EVENT_NAMESPACE, GLOBAL_NAMESPACE,
+    },
+    serde_json::json,
+    sha2::{Digest, Sha256},
+};
+
+fn sha256_prefix(preimage: &str) -> Vec<u8> {
+    Sha256::digest(preimage.as_bytes())[..8].to_vec()
+}
+
+/// Purpose: This test checks that the discriminators of the instructions are computed from their snake case name, and those of the accounts and events from their name as is.
+#[test]
+pub fn test_discriminator_namespaces() {
+    assert_eq!(
+        discriminator(GLOBAL_NAMESPACE, "setColor"),
+        sha256_prefix("global:set_color")
+    );
+    assert_eq!(
+        discriminator(ACCOUNT_NAMESPACE, "CounterState"),
+        sha256_prefix("account:CounterState")
+    );
+    assert_eq!(
+        discriminator(ACCOUNT_NAMESPACE, "CounterState"),
+        account_discriminator("CounterState")
+    );
+    assert_eq!(
+        discriminator(EVENT_NAMESPACE, "ColorChanged"),
+        sha256_prefix("event:ColorChanged")
+    );
+    assert_eq!(
+        discriminator("custom", "setColor"),
+        sha256_prefix("custom:setColor")
+    );
+}
+
+/// Purpose: This test checks that the discriminators embedded in an IDL JSON are used, and that the missing ones are computed.
+#[test]
+pub fn test_idl_discriminators() -> Result<()> {
+    let idl = json!({
+        "version": "0.1.0",
+        "name": "counter",
+        "instructions": [
+            { "name": "initialize", "discriminator": [175, 175, 109, 31, 13, 152, 155, 237] },
+            { "name": "increment" }
+        ],
+        "accounts": [
+            { "name": "Counter", "discriminator": [255, 176, 4, 245, 188, 253, 124, 25] },
+            { "name": "Config" }
+        ]
+    });
+    let discriminators = IdlDiscriminators::from_json(&idl.to_string())?;
+
+    assert_eq!(
+        discriminators.instruction("initialize"),
+        vec![175, 175, 109, 31, 13, 152, 155, 237]
+    );
+    assert_eq!(
+        discriminators.instruction("increment"),
+        discriminator(GLOBAL_NAMESPACE, "increment")
+    );
+    assert_eq!(
+        discriminators.account("Counter"),
+        vec![255, 176, 4, 245, 188, 253, 124, 25]
+    );
+    assert_eq!(
+        discriminators.account("Config"),
+        account_discriminator("Config")
+    );
+
+    // A discriminator must be an array of bytes
+    let idl = json!({ "instructions": [{ "name": "initialize", "discriminator": [256] }] });
+    assert!(IdlDiscriminators::from_value(&idl).is_err());
+
+    Ok(())
+}
@@ -0,0 +1,51 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    aqd_solana_contracts::transaction_explorer_url,
+    aqd_utils::AQD_CONFIG_ENV,
+    solana_sdk::signature::Signature,
+    std::{env, fs},
+};
+
+/// Purpose: This test checks that the explorer links of the transactions use the Solana Explorer with the cluster of the RPC URL, unless the aqd configuration file defines an explorer for that URL.
+#[test]
+pub fn test_transaction_explorer_url() {
+    let signature = Signature::new_unique();
+    let config = env::temp_dir().join(format!("aqd-test-explorer-{}.toml", signature));
+    env::set_var(AQD_CONFIG_ENV, &config);
+
+    assert_eq!(
+        transaction_explorer_url("https://api.mainnet-beta.solana.com", &signature),
+        format!("https://explorer.solana.com/tx/{}", signature)
+    );
+    assert_eq!(
+        transaction_explorer_url("https://api.devnet.solana.com/", &signature),
+        format!(
+            "https://explorer.solana.com/tx/{}?cluster=devnet",
+            signature
+        )
+    );
+    assert_eq!(
+        transaction_explorer_url("http://localhost:8899", &signature),
+        format!(
+            "https://explorer.solana.com/tx/{}?cluster=custom&customUrl=http%3A%2F%2Flocalhost%3A8899",
+            signature
+        )
+    );
+
+    fs::write(
+        &config,
+        r#"
+        [[networks]]
+        name = "devnet"
+        url = "https://api.devnet.solana.com"
+        explorer = "https://solscan.io/tx/{tx}?cluster=devnet"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(
+        transaction_explorer_url("https://api.devnet.solana.com", &signature),
+        format!("https://solscan.io/tx/{}?cluster=devnet", signature)
+    );
+    fs::remove_file(config).unwrap();
+}
@@ -0,0 +1,72 @@
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(feature = "cdylib")]
+
+use {
+    anyhow::Result,
+    aqd_solana_contracts::{
+        construct_instruction_data,
+        ffi::{aqd_decode_return_data, aqd_encode_instruction_data, aqd_free_string},
+        idl_from_json,
+    },
+    serde_json::{json, Value},
+    std::ffi::{c_char, CStr, CString, OsStr},
+};
+
+/// Call a function of the C bindings with a JSON request, and parse its JSON response.
+fn call(function: unsafe extern "C" fn(*const c_char) -> *mut c_char, request: &Value) -> Value {
+    let request = CString::new(request.to_string()).unwrap();
+    unsafe {
+        let response = function(request.as_ptr());
+        let value = serde_json::from_str(CStr::from_ptr(response).to_str().unwrap()).unwrap();
+        aqd_free_string(response);
+        value
+    }
+}
+
+/// Purpose: This test checks that the C bindings encode the instruction data and decode the return data of the flipper program from JSON requests, and report errors as JSON.
+#[test]
+pub fn test_ffi_encode_decode() -> Result<()> {
+    let idl_json = "tests/contracts/flipper.json";
+    let idl = idl_from_json(OsStr::new(idl_json))?;
+    let idl_value: Value = serde_json::from_str(&std::fs::read_to_string(idl_json)?)?;
+
+    let response = call(
+        aqd_encode_instruction_data,
+        &json!({ "idl": idl_value, "instruction": "new", "args": ["true"] }),
+    );
+    let new = idl.instructions.iter().find(|i| i.name == "new").unwrap();
+    let expected = construct_instruction_data(new, &["true".to_string()], &idl.types)?;
+    assert_eq!(response, json!({ "ok": { "data": hex::encode(expected) } }));
+
+    let response = call(
+        aqd_decode_return_data,
+        &json!({ "idl": idl_value, "instruction": "get", "data": "01" }),
+    );
+    assert_eq!(response, json!({ "ok": true }));
+
+    // The errors are reported in the response
+    let response = call(
+        aqd_decode_return_data,
+        &json!({ "idl": idl_value, "instruction": "flip", "data": "01" }),
+    );
+    assert_eq!(
+        response,
+        json!({ "error": "Instruction flip has no return value" })
+    );
+    let response = call(aqd_encode_instruction_data, &json!({ "idl": idl_value }));
+    assert!(response["error"]
+        .as_str()
+        .unwrap()
+        .starts_with("Invalid request"));
+
+    // A null request is an error too
+    unsafe {
+        let response = aqd_encode_instruction_data(std::ptr::null());
+        let value: Value = serde_json::from_str(CStr::from_ptr(response).to_str()?)?;
+        assert_eq!(value, json!({ "error": "The request is null" }));
+        aqd_free_string(response);
+    }
+
+    Ok(())
+}
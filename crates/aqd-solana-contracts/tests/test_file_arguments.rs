@@ -0,0 +1,36 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {anyhow::Result, aqd_solana_contracts::resolve_file_argument, std::fs};
+
+/// Purpose: This test checks that arguments referencing a file are replaced by its content, verbatim,
+/// and that files that are not valid UTF-8 are reported.
+#[test]
+pub fn test_resolve_file_argument() -> Result<()> {
+    let path = std::env::temp_dir().join(format!("aqd-test-argument-{}", std::process::id()));
+    let argument = format!("@{}", path.display());
+
+    fs::write(&path, "hello")?;
+    assert_eq!(resolve_file_argument(&argument)?, "hello");
+
+    // The newlines are part of the content
+    fs::write(&path, "hello\n")?;
+    assert_eq!(resolve_file_argument(&argument)?, "hello\n");
+    fs::write(&path, "line 1\r\nline 2\r\n")?;
+    assert_eq!(resolve_file_argument(&argument)?, "line 1\r\nline 2\r\n");
+
+    fs::write(&path, [0x68, 0xff, 0xfe])?;
+    let error = resolve_file_argument(&argument).unwrap_err().to_string();
+    assert!(error.starts_with(&format!(
+        "Argument file {} is not valid UTF-8",
+        path.display()
+    )));
+
+    fs::remove_file(&path)?;
+    assert!(resolve_file_argument(&argument).is_err());
+
+    // Other values are returned as is, and @@ is unescaped
+    assert_eq!(resolve_file_argument("hello\n")?, "hello\n");
+    assert_eq!(resolve_file_argument("@@hello")?, "@hello");
+
+    Ok(())
+}
@@ -154,3 +154,30 @@ pub async fn test_flipper_flip_accounts() -> Result<()> {
 
     Ok(())
 }
+
+/// Purpose: This test checks that a data argument can be read from a file with `@<PATH>`.
+#[tokio::test]
+pub async fn test_flipper_new_data_from_file() -> Result<()> {
+    let path = std::env::temp_dir().join(format!("aqd-test-arg-{}", Pubkey::new_unique()));
+    std::fs::write(&path, "true")?;
+
+    let idl = idl_from_json(OsStr::new("tests/contracts/flipper.json"))?;
+    let idl_instruction = idl
+        .instructions
+        .iter()
+        .find(|i| i.name == "new")
+        .ok_or_else(|| anyhow::anyhow!("Instruction not found: new"))?
+        .clone();
+
+    // The file content is used as the argument value
+    let data = vec![format!("@{}", path.display())];
+    let data = construct_instruction_data(&idl_instruction, &data, &idl.types)?;
+    assert_eq!(data, vec![135, 44, 205, 198, 25, 1, 72, 188, 1]);
+
+    // A missing file is reported
+    let data = vec!["@/does/not/exist".to_string()];
+    assert!(construct_instruction_data(&idl_instruction, &data, &idl.types).is_err());
+
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
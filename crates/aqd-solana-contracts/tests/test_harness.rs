@@ -0,0 +1,78 @@
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(feature = "test-harness")]
+
+use {
+    anyhow::Result,
+    aqd_solana_contracts::{
+        decode_instruction_return_data, test_harness::TestValidator, BorshValue, SolanaTransaction,
+    },
+};
+
+/// Purpose: This test submits the "new", "flip" and "get" instructions of the flipper program to a local
+/// test validator, and checks the decoded return data of "get".
+///
+/// Requires `solana-test-validator` to be available in the `PATH`.
+#[test]
+#[ignore]
+pub fn test_flipper_submit_transaction() -> Result<()> {
+    // Start a validator with the flipper program deployed.
+    let validator = TestValidator::builder()
+        .program("examples/contracts/flipper.so")
+        .start()?;
+    let program_id = validator.program_id(0).unwrap().to_string();
+    let idl_json = "tests/contracts/flipper.json";
+
+    // Call the "new" instruction, creating a new data account.
+    let flipper_new = SolanaTransaction::new()
+        .rpc_url(validator.rpc_url())
+        .idl(idl_json)
+        .program_id(program_id.clone())
+        .instruction("new")
+        .call_data(vec!["true"])
+        .accounts(vec![
+            "new".to_string(),
+            validator.payer_path(),
+            "system".to_string(),
+        ])
+        .payer(validator.payer_path())
+        .done()?;
+    flipper_new.submit_transaction()?;
+    let (data_account, data_account_path) = flipper_new.new_accounts()[0].clone();
+    std::fs::remove_file(data_account_path)?;
+
+    // Call the "flip" instruction.
+    let flipper_flip = SolanaTransaction::new()
+        .rpc_url(validator.rpc_url())
+        .idl(idl_json)
+        .program_id(program_id.clone())
+        .instruction("flip")
+        .call_data(Vec::<String>::new())
+        .accounts(vec![data_account.to_string()])
+        .payer(validator.payer_path())
+        .done()?;
+    flipper_flip.submit_transaction()?;
+
+    // Call the "get" instruction and decode the return data.
+    let flipper_get = SolanaTransaction::new()
+        .rpc_url(validator.rpc_url())
+        .idl(idl_json)
+        .program_id(program_id)
+        .instruction("get")
+        .call_data(Vec::<String>::new())
+        .accounts(vec![data_account.to_string()])
+        .payer(validator.payer_path())
+        .done()?;
+    let signature = flipper_get.submit_transaction()?;
+    let return_data = decode_instruction_return_data(
+        flipper_get.rpc_client(),
+        &signature,
+        flipper_get.instruction(),
+        &flipper_get.idl().types,
+    )?;
+
+    // The value was initialized to true and flipped once.
+    assert_eq!(return_data, Some(BorshValue::Bool(false)));
+
+    Ok(())
+}
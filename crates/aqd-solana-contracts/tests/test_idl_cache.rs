@@ -0,0 +1,111 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::Result,
+    aqd_solana_contracts::{fetch_idl, idl_from_json, AQD_IDL_CACHE_ENV},
+    sha2::{Digest, Sha256},
+    std::{
+        env, fs,
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        thread::{self, JoinHandle},
+    },
+};
+
+/// Use a temporary directory for the IDL cache of the tests.
+fn set_idl_cache() {
+    env::set_var(
+        AQD_IDL_CACHE_ENV,
+        env::temp_dir().join("aqd-test-idl-cache"),
+    );
+    env::set_var("NO_PROXY", "*");
+}
+
+/// Serve `content` with the ETag `"v1"` to `requests` requests, answering `304 Not Modified` to
+/// the requests with this ETag. Returns the URL of the IDL, and the server thread, returning the
+/// `If-None-Match` headers received.
+fn serve_idl(
+    content: String,
+    requests: usize,
+) -> Result<(String, JoinHandle<Vec<Option<String>>>)> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let url = format!("http://{}/flipper.json", listener.local_addr()?);
+    let server = thread::spawn(move || {
+        let mut received = vec![];
+        for stream in listener.incoming().take(requests) {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut if_none_match = None;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("if-none-match") {
+                        if_none_match = Some(value.trim().to_string());
+                    }
+                }
+            }
+            let response = match if_none_match.as_deref() {
+                Some("\"v1\"") => {
+                    "HTTP/1.1 304 Not Modified\r\nContent-Length: 0\r\n\r\n".to_string()
+                }
+                _ => format!(
+                    "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: {}\r\n\r\n{}",
+                    content.len(),
+                    content
+                ),
+            };
+            stream.write_all(response.as_bytes()).unwrap();
+            received.push(if_none_match);
+        }
+        received
+    });
+    Ok((url, server))
+}
+
+/// Purpose: This test checks that an IDL downloaded from a URL is cached with its ETag, revalidated by the next download, and read from the cache when the server cannot be reached.
+#[test]
+pub fn test_fetch_idl_cache() -> Result<()> {
+    set_idl_cache();
+    let content = fs::read_to_string("tests/contracts/flipper.json")?;
+    let (url, server) = serve_idl(content, 2)?;
+
+    // The first download is cached, the second one is not modified
+    let downloaded = fetch_idl(&url)?;
+    assert_eq!(downloaded.name, "flipper");
+    let revalidated = idl_from_json(url.as_ref())?;
+    assert_eq!(
+        revalidated.instructions.len(),
+        downloaded.instructions.len()
+    );
+    assert_eq!(
+        server.join().unwrap(),
+        vec![None, Some("\"v1\"".to_string())]
+    );
+
+    // The server is stopped: the cached IDL is used
+    let cached = fetch_idl(&url)?;
+    assert_eq!(cached.name, "flipper");
+
+    Ok(())
+}
+
+/// Purpose: This test checks that a downloaded file which is not a valid IDL is rejected, and not cached.
+#[test]
+pub fn test_fetch_invalid_idl() -> Result<()> {
+    set_idl_cache();
+    let (url, _) = serve_idl("<html>Not found</html>".to_string(), 1)?;
+
+    let error = fetch_idl(&url).unwrap_err();
+    assert!(error.to_string().contains("Invalid IDL"));
+    let key = hex::encode(Sha256::digest(url.as_bytes()));
+    let cached = env::temp_dir()
+        .join("aqd-test-idl-cache")
+        .join(format!("{}.json", key));
+    assert!(!cached.exists());
+
+    Ok(())
+}
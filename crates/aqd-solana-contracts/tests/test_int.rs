@@ -115,3 +115,65 @@ pub async fn test_signed_int_data() -> Result<()> {
 
     Ok(())
 }
+
+/// Construct the instruction data of the "new" function of a program with integer arguments, e.g.
+/// the "unsigned_int" program, with one of its arguments replaced.
+fn int_data(idl_json: &str, index: usize, value: &str) -> Result<Vec<u8>> {
+    let idl = idl_from_json(OsStr::new(idl_json))?;
+    let idl_instruction = idl
+        .instructions
+        .iter()
+        .find(|i| i.name == "new")
+        .ok_or_else(|| anyhow::anyhow!("Instruction not found: new"))?;
+    let mut data = vec!["1".to_string(); 6];
+    data[index] = value.to_string();
+    construct_instruction_data(idl_instruction, &data, &idl.types)
+}
+
+/// Purpose: This test checks that the integers at the bounds of their width and sign are encoded,
+/// and that those out of range are rejected with an error naming the argument, its type and the
+/// value, rather than truncated.
+#[test]
+pub fn test_int_ranges() -> Result<()> {
+    let unsigned = "tests/contracts/unsigned_int.json";
+    let signed = "tests/contracts/signed_int.json";
+    let u256_max = "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+    let i256_max = "57896044618658097711785492504343953926634992332820282019728792003956564819967";
+    let i256_min = "-57896044618658097711785492504343953926634992332820282019728792003956564819968";
+
+    // The u256 is the last argument, after 1 + 2 + 4 + 8 + 16 bytes
+    let data = int_data(unsigned, 5, u256_max)?;
+    assert_eq!(data[8 + 31..], [255; 32]);
+    let data = int_data(signed, 5, i256_min)?;
+    assert_eq!(data[8 + 31], 0);
+    assert_eq!(data[8 + 31 + 31], 128);
+    assert_eq!(int_data(signed, 0, "-128")?[8], 128);
+    assert!(int_data(signed, 5, i256_max).is_ok());
+    assert!(int_data(unsigned, 0, "255").is_ok());
+
+    let error = int_data(
+        unsigned,
+        5,
+        &format!("{}6", &u256_max[..u256_max.len() - 1]),
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(error.contains("inituint256value"), "{}", error);
+    assert!(error.contains("u256"), "{}", error);
+    assert!(
+        error.contains(&format!("{}6", &u256_max[..u256_max.len() - 1])),
+        "{}",
+        error
+    );
+
+    let error = int_data(unsigned, 0, "-1").unwrap_err().to_string();
+    assert!(error.contains("inituint8value"), "{}", error);
+    assert!(error.contains("-1"), "{}", error);
+
+    assert!(int_data(unsigned, 0, "256").is_err());
+    assert!(int_data(unsigned, 5, "-1").is_err());
+    assert!(int_data(signed, 0, "-129").is_err());
+    assert!(int_data(signed, 5, &format!("{}8", &i256_max[..i256_max.len() - 1])).is_err());
+    assert!(int_data(signed, 5, &format!("{}9", &i256_min[..i256_min.len() - 1])).is_err());
+    Ok(())
+}
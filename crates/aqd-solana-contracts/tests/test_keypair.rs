@@ -0,0 +1,101 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::Result,
+    aqd_solana_contracts::{parse_keypair, parse_pubkey},
+    solana_sdk::{
+        derivation_path::DerivationPath,
+        signature::{Keypair, Signer},
+        signer::keypair::{
+            generate_seed_from_seed_phrase_and_passphrase, keypair_from_seed_and_derivation_path,
+        },
+    },
+    std::fs,
+};
+
+const MNEMONIC: &str =
+    "pill tomorrow foster begin walnut borrow virtual kick shift mutual shoe scatter";
+
+/// Purpose: This test checks that keypairs are parsed from inline JSON byte arrays and base58 secret keys.
+#[test]
+pub fn test_parse_inline_keypair() -> Result<()> {
+    let keypair = Keypair::new();
+
+    let json = serde_json::to_string(&keypair.to_bytes().to_vec())?;
+    assert_eq!(parse_keypair(&json)?.pubkey(), keypair.pubkey());
+    assert_eq!(
+        parse_keypair(&keypair.to_base58_string())?.pubkey(),
+        keypair.pubkey()
+    );
+
+    // A public key is not a secret key
+    assert!(parse_keypair(&keypair.pubkey().to_string()).is_err());
+    assert!(parse_keypair("[1,2,3]").is_err());
+
+    Ok(())
+}
+
+/// Purpose: This test checks that keypair files can contain a JSON byte array or a base58 secret key.
+#[test]
+pub fn test_parse_keypair_file() -> Result<()> {
+    let keypair = Keypair::new();
+    let path = std::env::temp_dir().join(format!("aqd-test-keypair-{}", keypair.pubkey()));
+
+    fs::write(&path, serde_json::to_string(&keypair.to_bytes().to_vec())?)?;
+    assert_eq!(
+        parse_keypair(&path.to_string_lossy())?.pubkey(),
+        keypair.pubkey()
+    );
+
+    fs::write(&path, format!("{}\n", keypair.to_base58_string()))?;
+    assert_eq!(
+        parse_keypair(&path.to_string_lossy())?.pubkey(),
+        keypair.pubkey()
+    );
+
+    fs::remove_file(&path)?;
+    Ok(())
+}
+
+/// Purpose: This test checks that keypairs are derived from mnemonic phrases with the default or a custom derivation path.
+#[test]
+pub fn test_parse_mnemonic_keypair() -> Result<()> {
+    let seed = generate_seed_from_seed_phrase_and_passphrase(MNEMONIC, "");
+    let derive = |account: u32| {
+        keypair_from_seed_and_derivation_path(
+            &seed,
+            Some(DerivationPath::new_bip44(Some(account), Some(0))),
+        )
+        .unwrap()
+    };
+
+    // The default derivation path is m/44'/501'/0'/0'
+    assert_eq!(parse_keypair(MNEMONIC)?.pubkey(), derive(0).pubkey());
+    assert_eq!(
+        parse_keypair(&format!("{} m/44'/501'/1'/0'", MNEMONIC))?.pubkey(),
+        derive(1).pubkey()
+    );
+
+    // Invalid number of words or derivation path
+    assert!(parse_keypair("pill tomorrow foster").is_err());
+    assert!(parse_keypair(&format!("{} m/not/a/path", MNEMONIC)).is_err());
+
+    Ok(())
+}
+
+/// Purpose: This test checks that public keys are parsed in base58 or in hex, and that invalid public keys are rejected.
+#[test]
+pub fn test_parse_pubkey() -> Result<()> {
+    let pubkey = Keypair::new().pubkey();
+    assert_eq!(parse_pubkey(&pubkey.to_string())?, pubkey);
+    assert_eq!(
+        parse_pubkey(&format!("0x{}", hex::encode(pubkey.to_bytes())))?,
+        pubkey
+    );
+
+    // Not base58 (0 is not in the alphabet), too short, or a secret key
+    assert!(parse_pubkey("0OIl").is_err());
+    assert!(parse_pubkey("11111111").is_err());
+    assert!(parse_pubkey(&Keypair::new().to_base58_string()).is_err());
+    Ok(())
+}
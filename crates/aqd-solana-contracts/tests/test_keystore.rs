@@ -0,0 +1,56 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::Result,
+    aqd_solana_contracts::{
+        decrypt_keypair, encrypt_keypair, is_keystore, keystore_pubkey, write_keystore_file,
+    },
+    solana_sdk::signature::{Keypair, Signer},
+    std::{env, fs},
+};
+
+/// Purpose: This test checks that a keypair encrypted into a keystore is decrypted with the same password only.
+#[test]
+pub fn test_keystore_round_trip() -> Result<()> {
+    let keypair = Keypair::new();
+    let keystore = encrypt_keypair(&keypair, "correct horse")?;
+
+    // The secret key is not in clear, the public key is
+    let content = keystore.to_string();
+    assert!(!content.contains(&keypair.to_base58_string()));
+    assert_eq!(keystore["pubkey"], keypair.pubkey().to_string());
+    assert!(is_keystore(&content));
+    assert_eq!(keystore_pubkey(&content), Some(keypair.pubkey()));
+
+    let decrypted = decrypt_keypair(&keystore, "correct horse")?;
+    assert_eq!(decrypted.to_bytes(), keypair.to_bytes());
+
+    let error = decrypt_keypair(&keystore, "wrong horse").unwrap_err();
+    assert!(error.to_string().contains("wrong password"));
+
+    Ok(())
+}
+
+/// Purpose: This test checks that keystore files are only readable by their owner, and that plaintext keypair files are not keystores.
+#[test]
+pub fn test_write_keystore_file() -> Result<()> {
+    let keypair = Keypair::new();
+    let path = env::temp_dir().join(format!("aqd-test-keystore-{}.json", keypair.pubkey()));
+    write_keystore_file(&keypair, &path, "correct horse")?;
+
+    let content = fs::read_to_string(&path)?;
+    assert!(is_keystore(&content));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(fs::metadata(&path)?.permissions().mode() & 0o777, 0o600);
+    }
+    fs::remove_file(&path)?;
+
+    let plaintext = serde_json::to_string(&keypair.to_bytes().to_vec())?;
+    assert!(!is_keystore(&plaintext));
+    assert_eq!(keystore_pubkey(&plaintext), None);
+    assert!(!is_keystore("not json"));
+
+    Ok(())
+}
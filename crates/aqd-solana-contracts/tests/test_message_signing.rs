@@ -0,0 +1,50 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::Result,
+    aqd_solana_contracts::{message_bytes, sign_message, verify_message, MessageEncoding},
+    solana_sdk::signature::{Keypair, Signer},
+};
+
+/// Purpose: This test checks that a signed message is verified with the signer's public key only.
+#[test]
+pub fn test_sign_and_verify_message() -> Result<()> {
+    let keypair = Keypair::new();
+    let message = message_bytes("I own this key", MessageEncoding::Utf8)?;
+
+    let signature = sign_message(&keypair, &message).to_string();
+
+    assert!(verify_message(&keypair.pubkey(), &signature, &message)?);
+    // Another message or another public key does not match
+    assert!(!verify_message(
+        &keypair.pubkey(),
+        &signature,
+        b"Another message"
+    )?);
+    assert!(!verify_message(
+        &Keypair::new().pubkey(),
+        &signature,
+        &message
+    )?);
+    // An invalid signature is rejected
+    assert!(verify_message(&keypair.pubkey(), "not a signature", &message).is_err());
+
+    Ok(())
+}
+
+/// Purpose: This test checks that messages are decoded according to their encoding.
+#[test]
+pub fn test_message_bytes() -> Result<()> {
+    assert_eq!(
+        message_bytes("0x0102ff", MessageEncoding::Hex)?,
+        vec![1, 2, 255]
+    );
+    assert_eq!(
+        message_bytes("abc", MessageEncoding::Utf8)?,
+        b"abc".to_vec()
+    );
+    assert!(message_bytes("zz", MessageEncoding::Hex).is_err());
+    assert!(message_bytes("/does/not/exist", MessageEncoding::File).is_err());
+
+    Ok(())
+}
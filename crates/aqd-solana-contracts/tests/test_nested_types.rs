@@ -0,0 +1,180 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    aqd_solana_contracts::{construct_instruction_data, idl_from_json},
+    std::ffi::OsStr,
+};
+
+/// Construct the instruction data of an instruction of the NestedTypes program, without its
+/// 8-byte discriminator.
+///
+/// Note: The "NestedTypes" program is a custom program that was created for these tests.
+/// The program's IDL is defined in tests/contracts/NestedTypes.json.
+fn encoded_arguments(instruction_name: &str, data: &str) -> Result<Vec<u8>> {
+    let idl = idl_from_json(OsStr::new("tests/contracts/NestedTypes.json"))?;
+    let idl_instruction = idl
+        .instructions
+        .iter()
+        .find(|i| i.name == instruction_name)
+        .ok_or_else(|| anyhow!("Instruction not found: {}", instruction_name))?;
+    let data = construct_instruction_data(idl_instruction, &[data.to_string()], &idl.types)?;
+    Ok(data[8..].to_vec())
+}
+
+/// Purpose: This test checks that a vector of structs is passed as a JSON array of objects, whose
+/// strings may contain commas.
+#[test]
+pub fn test_vector_of_structs() -> Result<()> {
+    let data = encoded_arguments(
+        "setPeople",
+        r#"[{"name": "Alice", "age": 30}, {"name": "Bob, Jr.", "age": 5}]"#,
+    )?;
+
+    let mut expected = vec![2, 0, 0, 0];
+    expected.extend_from_slice(&[5, 0, 0, 0]);
+    expected.extend_from_slice(b"Alice");
+    expected.push(30);
+    expected.extend_from_slice(&[8, 0, 0, 0]);
+    expected.extend_from_slice(b"Bob, Jr.");
+    expected.push(5);
+    assert_eq!(data, expected);
+
+    // A struct cannot be passed as a comma-separated list
+    assert!(encoded_arguments("setPeople", "Alice,30").is_err());
+    Ok(())
+}
+
+/// Purpose: This test checks that nested vectors are passed as JSON arrays, with the elements as
+/// numbers or strings, and that the comma-separated shorthand is rejected for them.
+#[test]
+pub fn test_nested_vectors() -> Result<()> {
+    let expected = vec![2, 0, 0, 0, 2, 0, 0, 0, 1, 2, 1, 0, 0, 0, 3];
+    assert_eq!(encoded_arguments("setMatrix", "[[1, 2], [3]]")?, expected);
+    assert_eq!(
+        encoded_arguments("setMatrix", r#"[["1", "2"], "3"]"#)?,
+        expected
+    );
+    assert_eq!(encoded_arguments("setMatrix", "[]")?, vec![0, 0, 0, 0]);
+
+    assert!(encoded_arguments("setMatrix", "1,2,3").is_err());
+    assert!(encoded_arguments("setMatrix", "[[1, 2], [256]]").is_err());
+    assert!(encoded_arguments("setMatrix", "[[1, 2]").is_err());
+    Ok(())
+}
+
+/// Purpose: This test checks that the size of the nested fixed-size arrays passed as JSON arrays
+/// is verified.
+#[test]
+pub fn test_nested_fixed_arrays() -> Result<()> {
+    assert_eq!(
+        encoded_arguments("setGrid", "[[1, 2], [3, 4]]")?,
+        vec![1, 2, 3, 4]
+    );
+    assert!(encoded_arguments("setGrid", "[[1, 2], [3]]").is_err());
+    assert!(encoded_arguments("setGrid", "[[1, 2]]").is_err());
+    Ok(())
+}
+
+/// Purpose: This test checks that a vector of strings passed as a JSON array keeps the commas of
+/// its elements, while the comma-separated shorthand splits them.
+#[test]
+pub fn test_vector_of_strings() -> Result<()> {
+    let mut expected = vec![2, 0, 0, 0];
+    expected.extend_from_slice(&[3, 0, 0, 0]);
+    expected.extend_from_slice(b"a,b");
+    expected.extend_from_slice(&[1, 0, 0, 0]);
+    expected.extend_from_slice(b"c");
+    assert_eq!(encoded_arguments("setNames", r#"["a,b", "c"]"#)?, expected);
+
+    let mut expected = vec![3, 0, 0, 0];
+    for name in ["a", "b", "c"] {
+        expected.extend_from_slice(&[1, 0, 0, 0]);
+        expected.extend_from_slice(name.as_bytes());
+    }
+    assert_eq!(encoded_arguments("setNames", "a,b,c")?, expected);
+    Ok(())
+}
+
+/// Purpose: This test checks that the elements of the comma-separated shorthand of a vector of
+/// strings can be quoted, or have their commas escaped, to contain commas.
+#[test]
+pub fn test_vector_of_quoted_strings() -> Result<()> {
+    let encoded = |names: &[&str]| {
+        let mut expected = (names.len() as u32).to_le_bytes().to_vec();
+        for name in names {
+            expected.extend_from_slice(&(name.len() as u32).to_le_bytes());
+            expected.extend_from_slice(name.as_bytes());
+        }
+        expected
+    };
+
+    assert_eq!(
+        encoded_arguments("setNames", r#""a,b",c"#)?,
+        encoded(&["a,b", "c"])
+    );
+    assert_eq!(
+        encoded_arguments("setNames", r"a\,b,c")?,
+        encoded(&["a,b", "c"])
+    );
+    assert_eq!(
+        encoded_arguments("setNames", r#"c,"say \"hi\", \\o/","""#)?,
+        encoded(&["c", r#"say "hi", \o/"#, ""])
+    );
+    assert_eq!(
+        encoded_arguments("setNames", r"C:\temp,d")?,
+        encoded(&[r"C:\temp", "d"])
+    );
+
+    assert!(encoded_arguments("setNames", r#""a,b,c"#).is_err());
+    assert!(encoded_arguments("setNames", r#""a"b,c"#).is_err());
+    Ok(())
+}
+
+/// Purpose: This test checks that the `bytes`, `Vec<u8>` and `[u8; N]` fields of a struct are
+/// passed as hex strings, with or without a `0x` prefix, base64 strings, or arrays of numbers.
+#[test]
+pub fn test_struct_byte_fields() -> Result<()> {
+    let mut expected = vec![1, 0, 0, 0];
+    expected.extend_from_slice(b"x");
+    expected.extend_from_slice(&[4, 0, 0, 0, 0xde, 0xad, 0xbe, 0xef]);
+    expected.extend_from_slice(&[3, 0, 0, 0, 1, 2, 3]);
+    expected.extend_from_slice(&[1, 2, 3, 4]);
+
+    assert_eq!(
+        encoded_arguments(
+            "setBlob",
+            r#"{"label": "x", "data": "0xdeadbeef", "payload": "AQID", "hash": [1, 2, 3, 4]}"#
+        )?,
+        expected
+    );
+    assert_eq!(
+        encoded_arguments(
+            "setBlob",
+            r#"{"label": "x", "data": [222, 173, 190, 239], "payload": "010203", "hash": "AQIDBA=="}"#
+        )?,
+        expected
+    );
+
+    assert!(encoded_arguments(
+        "setBlob",
+        r#"{"label": "x", "data": "not hex!", "payload": [], "hash": [1, 2, 3, 4]}"#
+    )
+    .is_err());
+    assert!(encoded_arguments(
+        "setBlob",
+        r#"{"label": "x", "data": "", "payload": [256], "hash": [1, 2, 3, 4]}"#
+    )
+    .is_err());
+    assert!(encoded_arguments(
+        "setBlob",
+        r#"{"label": "x", "data": "", "payload": [], "hash": "0x010203"}"#
+    )
+    .is_err());
+    assert!(encoded_arguments(
+        "setBlob",
+        r#"{"label": "x", "data": true, "payload": [], "hash": [1, 2, 3, 4]}"#
+    )
+    .is_err());
+    Ok(())
+}
@@ -0,0 +1,71 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anchor_syn::idl::IdlInstruction, anyhow::Result, aqd_solana_contracts::find_pda,
+    serde_json::json, solana_sdk::pubkey::Pubkey,
+};
+
+/// An instruction with a string and an integer argument, used as PDA seeds.
+fn instruction() -> Result<IdlInstruction> {
+    let instruction = json!({
+        "name": "initialize",
+        "accounts": [],
+        "args": [
+            { "name": "name", "type": "string" },
+            { "name": "id", "type": "u64" }
+        ]
+    });
+    Ok(serde_json::from_value(instruction)?)
+}
+
+/// Purpose: This test checks that the PDA is derived from string, hex, public key and argument seeds.
+#[test]
+pub fn test_find_pda() -> Result<()> {
+    let instruction = instruction()?;
+    let program_id = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let data_args = vec!["alice".to_string(), "7".to_string()];
+
+    let (pda, bump) = find_pda(
+        &format!("pda:counter,hex:0102,pubkey:{},arg:name,arg:id", authority),
+        &program_id,
+        &instruction,
+        &data_args,
+        &vec![],
+    )?;
+
+    // Strings are used without their length prefix, integers as little endian bytes
+    let expected = Pubkey::find_program_address(
+        &[
+            b"counter",
+            &[1, 2],
+            authority.as_ref(),
+            b"alice",
+            &7u64.to_le_bytes(),
+        ],
+        &program_id,
+    );
+    assert_eq!((pda, bump), expected);
+
+    Ok(())
+}
+
+/// Purpose: This test checks that invalid PDA seeds are rejected.
+#[test]
+pub fn test_find_pda_invalid_seeds() -> Result<()> {
+    let instruction = instruction()?;
+    let program_id = Pubkey::new_unique();
+    let data_args = vec!["alice".to_string(), "7".to_string()];
+    let find = |seeds: &str| find_pda(seeds, &program_id, &instruction, &data_args, &vec![]);
+
+    // Unknown argument
+    assert!(find("pda:arg:unknown").is_err());
+    // Invalid hex
+    assert!(find("pda:hex:zz").is_err());
+    // Invalid public key
+    assert!(find("pda:pubkey:not_a_pubkey").is_err());
+    // Seed longer than 32 bytes
+    assert!(find(&format!("pda:{}", "a".repeat(33))).is_err());
+
+    Ok(())
+}
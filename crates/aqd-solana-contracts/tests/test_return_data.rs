@@ -0,0 +1,110 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anchor_syn::idl::IdlType,
+    anyhow::Result,
+    aqd_solana_contracts::{decode_return_data, idl_from_json, BorshValue},
+    num_bigint::BigInt,
+    serde_json::json,
+    std::ffi::OsStr,
+};
+
+/// Purpose: This test checks that the return data of an instruction is decoded from its bytes and return type, without an RPC client.
+#[test]
+pub fn test_decode_return_data() -> Result<()> {
+    let idl = idl_from_json(OsStr::new("tests/contracts/DefinedTypes.json"))?;
+    let returns = |name: &str| {
+        idl.instructions
+            .iter()
+            .find(|i| i.name == name)
+            .and_then(|i| i.returns.clone())
+            .unwrap()
+    };
+
+    // getPerson returns a Person struct: the string "Bob", the u8 42 and the Color Blue
+    let mut data = vec![3, 0, 0, 0];
+    data.extend_from_slice(b"Bob");
+    data.extend_from_slice(&[42, 2]);
+    let person = decode_return_data(&data, &returns("getPerson"), &idl.types)?;
+    assert_eq!(
+        person,
+        BorshValue::Struct(vec![
+            ("name".to_string(), BorshValue::String("Bob".to_string())),
+            (
+                "age".to_string(),
+                BorshValue::Uint {
+                    width: 8,
+                    value: BigInt::from(42)
+                }
+            ),
+            (
+                "favoriteColor".to_string(),
+                BorshValue::Enum {
+                    index: 2,
+                    variant: Some("Blue".to_string())
+                }
+            ),
+        ])
+    );
+    assert_eq!(person.to_string(), "Bob, 42, Blue");
+
+    // Primitive types are decoded without custom types
+    let value = decode_return_data(&(-5i64).to_le_bytes(), &IdlType::I64, &[])?;
+    assert_eq!(value.to_string(), "-5");
+
+    // An enum variant unknown to the IDL keeps its index
+    let color = decode_return_data(&[7], &returns("getColor"), &idl.types)?;
+    assert_eq!(
+        color,
+        BorshValue::Enum {
+            index: 7,
+            variant: None
+        }
+    );
+
+    // Data too short, or too long, for the type is rejected
+    let error = decode_return_data(&[1, 0], &IdlType::U32, &[]).unwrap_err();
+    assert!(error.to_string().contains("Unexpected end of data"));
+    let error = decode_return_data(&[1, 0], &IdlType::U8, &[]).unwrap_err();
+    assert!(error.to_string().contains("1 bytes left over"));
+
+    Ok(())
+}
+
+/// Purpose: This test checks that decoded values are serialized as structured data: structs as maps, enums as tagged values, and integers wider than 64 bits as strings.
+#[test]
+pub fn test_borsh_value_serialization() -> Result<()> {
+    let idl = idl_from_json(OsStr::new("tests/contracts/DefinedTypes.json"))?;
+    let person_ty = IdlType::Defined("Person".to_string());
+    let mut data = vec![5, 0, 0, 0];
+    data.extend_from_slice(b"Alice");
+    data.extend_from_slice(&[30, 0]);
+    let person = decode_return_data(&data, &person_ty, &idl.types)?;
+    assert_eq!(
+        serde_json::to_value(&person)?,
+        json!({
+            "name": "Alice",
+            "age": 30,
+            "favoriteColor": { "variant": "Red", "index": 0 }
+        })
+    );
+
+    let big = decode_return_data(&u128::MAX.to_le_bytes(), &IdlType::U128, &[])?;
+    assert_eq!(serde_json::to_value(&big)?, json!(u128::MAX.to_string()));
+    let small = decode_return_data(&u64::MAX.to_le_bytes(), &IdlType::U64, &[])?;
+    assert_eq!(serde_json::to_value(&small)?, json!(u64::MAX));
+
+    let address = decode_return_data(&[1; 32], &IdlType::PublicKey, &[])?;
+    let bytes = decode_return_data(&[2, 0, 0, 0, 0xca, 0xfe], &IdlType::Bytes, &[])?;
+    let list = decode_return_data(
+        &[2, 0, 0, 0, 0xff, 0x7f],
+        &IdlType::Vec(Box::new(IdlType::I8)),
+        &[],
+    )?;
+    assert_eq!(
+        serde_json::to_value([&address, &bytes, &list])?,
+        json!([address.to_string(), "cafe", [-1, 127]])
+    );
+
+    Ok(())
+}
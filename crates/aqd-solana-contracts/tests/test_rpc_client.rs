@@ -0,0 +1,47 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    aqd_solana_contracts::rpc_client,
+    aqd_utils::{set_cassette, Cassette, CassetteMode, Interaction},
+    serde_json::json,
+    solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey},
+    std::{env, fs},
+};
+
+/// Purpose: This test checks that the RPC responses are replayed from a cassette, in order, without connecting to the RPC server.
+#[test]
+pub fn test_rpc_client_replay() {
+    let account = Pubkey::new_unique();
+    let mut cassette = Cassette::default();
+    // The client queries the version of the node before its first request with a commitment
+    cassette.interactions.push(Interaction::new(
+        "solana",
+        "getVersion",
+        json!(null),
+        Ok(json!({ "solana-core": "1.17.3", "feature-set": 0 })),
+    ));
+    for lamports in [42, 43] {
+        cassette.interactions.push(Interaction::new(
+            "solana",
+            "getBalance",
+            json!([account.to_string(), { "commitment": "confirmed" }]),
+            Ok(json!({ "context": { "slot": 1 }, "value": lamports })),
+        ));
+    }
+    let path = env::temp_dir().join(format!("aqd-test-cassette-{}.json", account));
+    cassette.save(&path).unwrap();
+    set_cassette(Some(CassetteMode::Replay(path.clone()))).unwrap();
+
+    // Nothing listens on this port
+    let rpc_client = rpc_client(
+        "http://127.0.0.1:1".to_string(),
+        CommitmentConfig::confirmed(),
+    );
+    assert_eq!(rpc_client.get_balance(&account).unwrap(), 42);
+    assert_eq!(rpc_client.get_balance(&account).unwrap(), 43);
+    let error = rpc_client.get_balance(&account).unwrap_err();
+    assert!(error.to_string().contains("No recorded response left"));
+
+    set_cassette(None).unwrap();
+    fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,104 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::Result,
+    aqd_solana_contracts::{
+        compute_units_from_logs, AccountOverride, SimulationOptions, SolanaTransaction,
+    },
+    solana_client::rpc_client::RpcClient,
+    solana_sdk::{pubkey::Pubkey, signature::Keypair},
+    std::{str::FromStr, sync::Arc},
+};
+
+/// Purpose: This test checks that an account override is parsed from `<ADDRESS>:<BASE64_DATA>`.
+#[test]
+pub fn test_parse_account_override() -> Result<()> {
+    let address = Pubkey::new_unique();
+
+    let account_override = AccountOverride::from_str(&format!("{}:AQID", address))?;
+
+    assert_eq!(account_override.address, address);
+    assert_eq!(account_override.data, vec![1, 2, 3]);
+
+    Ok(())
+}
+
+/// Purpose: This test checks that invalid account overrides are rejected.
+#[test]
+pub fn test_parse_invalid_account_override() {
+    let address = Pubkey::new_unique();
+
+    // Missing data
+    assert!(AccountOverride::from_str(&address.to_string()).is_err());
+    // Invalid address
+    assert!(AccountOverride::from_str("not_an_address:AQID").is_err());
+    // Invalid base64 data
+    assert!(AccountOverride::from_str(&format!("{}:not base64!", address)).is_err());
+}
+
+/// Purpose: This test checks that a transaction is simulated with the overrides of its accounts, and that overrides of accounts it does not use are rejected.
+#[test]
+pub fn test_simulate_with_account_overrides() -> Result<()> {
+    let data_account = Pubkey::new_unique();
+    let transaction = SolanaTransaction::new()
+        .rpc_client(Arc::new(RpcClient::new_mock("succeeds".to_string())))
+        .idl("tests/contracts/flipper.json")
+        .program_id(Pubkey::new_unique().to_string())
+        .instruction("flip")
+        .call_data(Vec::<String>::new())
+        .accounts(vec![data_account.to_string()])
+        .payer(Keypair::new().to_base58_string())
+        .done()?;
+
+    let result = transaction.simulate_transaction_with_options(&SimulationOptions {
+        account_overrides: vec![AccountOverride {
+            address: data_account,
+            data: vec![1, 2, 3],
+        }],
+        ..SimulationOptions::default()
+    })?;
+    assert!(result.err.is_none());
+
+    let unused_account = Pubkey::new_unique();
+    let error = transaction
+        .simulate_transaction_with_options(&SimulationOptions {
+            account_overrides: vec![AccountOverride {
+                address: unused_account,
+                data: vec![1, 2, 3],
+            }],
+            ..SimulationOptions::default()
+        })
+        .err()
+        .unwrap();
+    assert_eq!(
+        error.to_string(),
+        format!("Account {} is not used by instruction flip", unused_account)
+    );
+
+    Ok(())
+}
+
+/// Purpose: This test checks that the compute units are parsed from the top level invocations in the logs.
+#[test]
+pub fn test_compute_units_from_logs() {
+    let program = Pubkey::new_unique();
+    let inner_program = Pubkey::new_unique();
+    let logs: Vec<String> = vec![
+        format!("Program {} invoke [1]", program),
+        "Program log: Program consumed 1 of 2 compute units".to_string(),
+        format!("Program {} invoke [2]", inner_program),
+        format!(
+            "Program {} consumed 300 of 199000 compute units",
+            inner_program
+        ),
+        format!("Program {} success", inner_program),
+        format!("Program {} consumed 1000 of 200000 compute units", program),
+        format!("Program {} success", program),
+        format!("Program {} invoke [1]", program),
+        format!("Program {} consumed 500 of 199000 compute units", program),
+        format!("Program {} success", program),
+    ];
+
+    assert_eq!(compute_units_from_logs(&logs), Some(1500));
+    assert_eq!(compute_units_from_logs(&[]), None);
+}
@@ -0,0 +1,56 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    aqd_solana_contracts::{SquadsMultisig, SQUADS_PROGRAM_ID},
+    solana_client::rpc_client::RpcClient,
+    solana_sdk::{
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+        signature::Keypair,
+    },
+};
+
+/// Purpose: This test checks that the Squads vault, transaction and proposal addresses are derived from the multisig.
+#[test]
+pub fn test_squads_addresses() {
+    let multisig_address = Pubkey::new_unique();
+    let multisig = SquadsMultisig::new(multisig_address, 1);
+
+    let (vault, _) = Pubkey::find_program_address(
+        &[b"multisig", multisig_address.as_ref(), b"vault", &[1]],
+        &SQUADS_PROGRAM_ID,
+    );
+    assert_eq!(multisig.vault_address(), vault);
+
+    let (proposal, _) = Pubkey::find_program_address(
+        &[
+            b"multisig",
+            multisig_address.as_ref(),
+            b"transaction",
+            &7u64.to_le_bytes(),
+            b"proposal",
+        ],
+        &SQUADS_PROGRAM_ID,
+    );
+    assert_eq!(multisig.proposal_address(7), proposal);
+    assert_ne!(multisig.transaction_address(7), proposal);
+}
+
+/// Purpose: This test checks that instructions requiring another signer than the vault cannot be proposed.
+#[test]
+pub fn test_squads_proposal_requires_vault_signer() {
+    let multisig = SquadsMultisig::new(Pubkey::new_unique(), 0);
+    let instruction = Instruction {
+        program_id: Pubkey::new_unique(),
+        accounts: vec![AccountMeta::new(Pubkey::new_unique(), true)],
+        data: vec![],
+    };
+
+    // The instruction is rejected before reaching the RPC server
+    let rpc_client = RpcClient::new("http://127.0.0.1:1".to_string());
+    let result = multisig.create_proposal(&rpc_client, &Keypair::new(), &[instruction], None);
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("only the multisig vault"));
+}
@@ -0,0 +1,67 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anchor_syn::idl::IdlInstruction,
+    anyhow::Result,
+    aqd_solana_contracts::{
+        associated_token_address, construct_instruction_accounts, is_token_program,
+        ASSOCIATED_TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID,
+    },
+    serde_json::json,
+    solana_sdk::pubkey::Pubkey,
+};
+
+/// Purpose: This test checks that the associated token account depends on the token program.
+#[test]
+pub fn test_associated_token_address() {
+    let owner = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+
+    let ata = associated_token_address(&owner, &mint, &TOKEN_PROGRAM_ID);
+    let expected = Pubkey::find_program_address(
+        &[owner.as_ref(), TOKEN_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0;
+    assert_eq!(ata, expected);
+
+    let ata_2022 = associated_token_address(&owner, &mint, &TOKEN_2022_PROGRAM_ID);
+    assert_ne!(ata, ata_2022);
+
+    assert!(is_token_program(&TOKEN_PROGRAM_ID));
+    assert!(is_token_program(&TOKEN_2022_PROGRAM_ID));
+    assert!(!is_token_program(&ASSOCIATED_TOKEN_PROGRAM_ID));
+}
+
+/// Purpose: This test checks that the token program keywords are resolved to the program IDs.
+#[test]
+pub fn test_token_program_keywords() -> Result<()> {
+    let instruction: IdlInstruction = serde_json::from_value(json!({
+        "name": "transfer",
+        "accounts": [
+            { "name": "tokenProgram", "isMut": false, "isSigner": false },
+            { "name": "token2022Program", "isMut": false, "isSigner": false },
+            { "name": "associatedTokenProgram", "isMut": false, "isSigner": false }
+        ],
+        "args": []
+    }))?;
+    let raw_args = vec![
+        "token".to_string(),
+        "token-2022".to_string(),
+        "associated-token".to_string(),
+    ];
+
+    let (accounts, signers, _) = construct_instruction_accounts(&instruction, &raw_args)?;
+    let pubkeys: Vec<Pubkey> = accounts.iter().map(|a| a.pubkey).collect();
+    assert_eq!(
+        pubkeys,
+        vec![
+            TOKEN_PROGRAM_ID,
+            TOKEN_2022_PROGRAM_ID,
+            ASSOCIATED_TOKEN_PROGRAM_ID
+        ]
+    );
+    assert!(signers.is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,247 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::Result,
+    aqd_solana_contracts::{idl_from_json, InstructionAccount, SolanaTransaction},
+    solana_client::rpc_client::RpcClient,
+    solana_sdk::{
+        pubkey::Pubkey,
+        signature::{Keypair, Signer},
+        system_program,
+    },
+    std::{ffi::OsStr, fs, sync::Arc},
+};
+
+/// Purpose: This test checks that the Idl of a program call can be given as a file path, as a JSON string or as an Idl already parsed, with the same result.
+#[test]
+pub fn test_builder_idl_sources() -> Result<()> {
+    let idl_json = "tests/contracts/flipper.json";
+    let program_id = Pubkey::new_unique().to_string();
+    let data_account = Pubkey::new_unique().to_string();
+    let payer = Keypair::new().to_base58_string();
+
+    let from_path = SolanaTransaction::new()
+        .rpc_url("http://localhost:8899")
+        .idl(idl_json)
+        .program_id(program_id.clone())
+        .instruction("flip")
+        .call_data(Vec::<String>::new())
+        .accounts(vec![data_account.clone()])
+        .payer(payer.clone())
+        .done()?;
+    let from_json = SolanaTransaction::new()
+        .rpc_url("http://localhost:8899")
+        .idl_json_str(fs::read_to_string(idl_json)?)
+        .program_id(program_id.clone())
+        .instruction("flip")
+        .call_data(Vec::<String>::new())
+        .accounts(vec![data_account.clone()])
+        .payer(payer.clone())
+        .done()?;
+    let from_value = SolanaTransaction::new()
+        .rpc_url("http://localhost:8899")
+        .idl_value(idl_from_json(OsStr::new(idl_json))?)
+        .program_id(program_id.clone())
+        .instruction("flip")
+        .call_data(Vec::<String>::new())
+        .accounts(vec![data_account.clone()])
+        .payer(payer.clone())
+        .done()?;
+
+    for transaction in [&from_json, &from_value] {
+        assert_eq!(transaction.idl().name, from_path.idl().name);
+        assert_eq!(transaction.call_data(), from_path.call_data());
+        assert_eq!(transaction.accounts(), from_path.accounts());
+    }
+
+    // An invalid JSON string is reported when the call is built
+    let error = SolanaTransaction::new()
+        .rpc_url("http://localhost:8899")
+        .idl_json_str("{ not an idl")
+        .program_id(program_id)
+        .instruction("flip")
+        .call_data(Vec::<String>::new())
+        .accounts(vec![data_account])
+        .payer(payer)
+        .done()
+        .err()
+        .unwrap();
+    assert!(error.to_string().contains("Error parsing Idl JSON"));
+
+    Ok(())
+}
+
+/// Purpose: This test checks that the instruction and the message of a program call are built without submitting it.
+#[test]
+pub fn test_to_instruction_and_message() -> Result<()> {
+    let program_id = Pubkey::new_unique();
+    let data_account = Pubkey::new_unique();
+    let payer = Keypair::new();
+
+    let transaction = SolanaTransaction::new()
+        .rpc_url("http://localhost:8899")
+        .idl("tests/contracts/flipper.json")
+        .program_id(program_id.to_string())
+        .instruction("flip")
+        .call_data(Vec::<String>::new())
+        .accounts(vec![data_account.to_string()])
+        .payer(payer.to_base58_string())
+        .done()?;
+
+    let instruction = transaction.to_instruction();
+    assert_eq!(instruction.program_id, program_id);
+    assert_eq!(instruction.accounts.len(), 1);
+    assert_eq!(instruction.accounts[0].pubkey, data_account);
+    assert!(instruction.accounts[0].is_writable);
+    assert_eq!(&instruction.data, transaction.call_data());
+
+    // The payer is the fee payer, and the first signer of the message
+    let message = transaction.to_message();
+    assert_eq!(message.account_keys[0], payer.pubkey());
+    assert_eq!(message.header.num_required_signatures, 1);
+    assert_eq!(message.instructions.len(), 1);
+    assert_eq!(message.instructions[0].data, instruction.data);
+
+    Ok(())
+}
+
+/// Purpose: This test checks that a program call can be built with an RPC client shared with other calls, instead of the RPC URL.
+#[test]
+pub fn test_builder_shared_rpc_client() -> Result<()> {
+    let rpc_client = Arc::new(RpcClient::new_mock("succeeds".to_string()));
+    let program_id = Pubkey::new_unique().to_string();
+    let payer = Keypair::new().to_base58_string();
+
+    let transactions = (0..2)
+        .map(|_| {
+            SolanaTransaction::new()
+                .rpc_client(rpc_client.clone())
+                .idl("tests/contracts/flipper.json")
+                .program_id(program_id.clone())
+                .instruction("flip")
+                .call_data(Vec::<String>::new())
+                .accounts(vec![Pubkey::new_unique().to_string()])
+                .payer(payer.clone())
+                .done()
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    for transaction in &transactions {
+        assert!(std::ptr::eq(transaction.rpc_client(), rpc_client.as_ref()));
+    }
+
+    // The mock client answers the requests without a network
+    assert!(transactions[0].rpc_client().get_latest_blockhash().is_ok());
+
+    Ok(())
+}
+
+/// Purpose: This test checks that the payer and the accounts of a program call can be given as keypairs, signers and public keys held in memory, and that only the signable accounts sign.
+#[test]
+pub fn test_builder_keypair_objects() -> Result<()> {
+    let idl = idl_from_json(OsStr::new("tests/contracts/flipper.json"))?;
+    let program_id = Pubkey::new_unique().to_string();
+    let payer = Keypair::new();
+    let payer_pubkey = payer.pubkey();
+    let data_account = Keypair::new();
+    let data_pubkey = data_account.pubkey();
+
+    // The data account of flip is not signable: its keypair does not sign
+    let transaction = SolanaTransaction::new()
+        .rpc_url("http://localhost:8899")
+        .idl_value(idl.clone())
+        .program_id(program_id.clone())
+        .instruction("flip")
+        .call_data(Vec::<String>::new())
+        .instruction_accounts(vec![Box::new(data_account) as Box<dyn Signer + Send + Sync>])
+        .payer_keypair(payer)
+        .done()?;
+    assert_eq!(transaction.payer().pubkey(), payer_pubkey);
+    assert_eq!(transaction.accounts()[0].pubkey, data_pubkey);
+    assert!(transaction.signers().is_empty());
+
+    // The data account of new is signable: its keypair signs
+    let data_account = Keypair::new();
+    let data_pubkey = data_account.pubkey();
+    let transaction = SolanaTransaction::new()
+        .rpc_url("http://localhost:8899")
+        .idl_value(idl)
+        .program_id(program_id)
+        .instruction("new")
+        .call_data(vec!["true"])
+        .instruction_accounts(vec![
+            InstructionAccount::from(data_account),
+            InstructionAccount::from(payer_pubkey),
+            InstructionAccount::from("system"),
+        ])
+        .payer_keypair(Keypair::new())
+        .done()?;
+    let accounts = transaction.accounts();
+    assert_eq!(accounts[0].pubkey, data_pubkey);
+    assert_eq!(accounts[1].pubkey, payer_pubkey);
+    assert_eq!(accounts[2].pubkey, system_program::id());
+    assert_eq!(transaction.signers().len(), 1);
+    assert_eq!(transaction.signers()[0].pubkey(), data_pubkey);
+
+    Ok(())
+}
+
+/// Purpose: This test checks that the instruction data of a program call starts with the discriminator set on the builder, instead of the anchor discriminator of the instruction.
+#[test]
+pub fn test_builder_discriminator() -> Result<()> {
+    let transaction = SolanaTransaction::new()
+        .rpc_url("http://localhost:8899")
+        .idl("tests/contracts/flipper.json")
+        .program_id(Pubkey::new_unique().to_string())
+        .instruction("new")
+        .call_data(vec!["true"])
+        .accounts(vec![
+            Pubkey::new_unique().to_string(),
+            Pubkey::new_unique().to_string(),
+            "system".to_string(),
+        ])
+        .payer(Keypair::new().to_base58_string())
+        .discriminator(vec![7])
+        .done()?;
+    assert_eq!(transaction.call_data(), &vec![7, 1]);
+
+    Ok(())
+}
+
+/// Purpose: This test checks that the instruction data starts with the discriminator embedded in the Idl JSON, if any, unless another one is set on the builder.
+#[test]
+pub fn test_builder_embedded_discriminator() -> Result<()> {
+    let mut idl: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string("tests/contracts/flipper.json")?)?;
+    for instruction in idl["instructions"].as_array_mut().unwrap() {
+        if instruction["name"] == "new" {
+            instruction["discriminator"] = serde_json::json!([1, 2, 3, 4]);
+        }
+    }
+    let builder = || {
+        SolanaTransaction::new()
+            .rpc_url("http://localhost:8899")
+            .idl_json_str(idl.to_string())
+            .program_id(Pubkey::new_unique().to_string())
+            .instruction("new")
+            .call_data(vec!["true"])
+            .accounts(vec![
+                Pubkey::new_unique().to_string(),
+                Pubkey::new_unique().to_string(),
+                "system".to_string(),
+            ])
+            .payer(Keypair::new().to_base58_string())
+    };
+
+    let transaction = builder().done()?;
+    assert_eq!(transaction.call_data(), &vec![1, 2, 3, 4, 1]);
+    assert_eq!(
+        transaction.discriminators().instruction("new"),
+        vec![1, 2, 3, 4]
+    );
+
+    let transaction = builder().discriminator(vec![7]).done()?;
+    assert_eq!(transaction.call_data(), &vec![7, 1]);
+
+    Ok(())
+}
@@ -0,0 +1,185 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::Result,
+    aqd_solana_contracts::{resolve_rpc_url, resolve_wallet, SolanaWorkspace, WorkspaceKind},
+    solana_cli_config::Config,
+    std::{fs, path::Path},
+};
+
+/// Purpose: This test checks that the programs, cluster and wallet are read from an Anchor.toml file.
+#[test]
+pub fn test_anchor_workspace() -> Result<()> {
+    let content = r#"
+        [provider]
+        cluster = "devnet"
+        wallet = "/keys/id.json"
+
+        [programs.localnet]
+        counter = "Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS"
+
+        [programs.devnet]
+        counter = "2VfUXjYzvnuf9gTq2VQjn5dWTsRE9ntJRAyzjfhBqTvV"
+        flipper = "F1ipperKF9EfD821ZbbYjS319LXYiBmjhzkkf5a26rC"
+    "#;
+    let root = Path::new("/workspace");
+    let workspace = SolanaWorkspace::from_anchor_toml(root, content)?;
+
+    assert_eq!(workspace.kind, WorkspaceKind::Anchor);
+    assert_eq!(workspace.wallet, Some("/keys/id.json".to_string()));
+    assert_eq!(
+        workspace.rpc_url(),
+        Some("https://api.devnet.solana.com".to_string())
+    );
+
+    // The programs are read from the section of the provider cluster
+    let counter = workspace.program(Some("counter"))?;
+    assert_eq!(
+        counter.program_id,
+        Some("2VfUXjYzvnuf9gTq2VQjn5dWTsRE9ntJRAyzjfhBqTvV".to_string())
+    );
+    assert_eq!(counter.idl_path, root.join("target/idl/counter.json"));
+    assert_eq!(counter.program_path, root.join("target/deploy/counter.so"));
+
+    // A program name is required when the workspace contains several programs
+    assert!(workspace.program(None).is_err());
+    assert!(workspace.program(Some("unknown")).is_err());
+
+    Ok(())
+}
+
+/// Purpose: This test checks that the programs are read from a solang.toml file.
+#[test]
+pub fn test_solang_workspace() -> Result<()> {
+    let content = r#"
+        [package]
+        version = "0.1.0"
+        input_files = ["flipper.sol"]
+        contracts = ["flipper"]
+
+        [target]
+        name = "solana"
+
+        [compiler-output]
+        output_directory = "build"
+    "#;
+    let root = Path::new("/workspace");
+    let workspace = SolanaWorkspace::from_solang_toml(root, content)?;
+
+    assert_eq!(workspace.kind, WorkspaceKind::Solang);
+    assert_eq!(workspace.rpc_url(), None);
+
+    // With a single program, the program name is optional
+    let flipper = workspace.program(None)?;
+    assert_eq!(flipper.name, "flipper");
+    assert_eq!(flipper.program_id, None);
+    assert_eq!(flipper.idl_path, root.join("build/flipper.json"));
+    assert_eq!(flipper.program_path, root.join("build/flipper.so"));
+
+    Ok(())
+}
+
+/// Purpose: This test checks that the IDL location and the cluster settings are read from a solang.toml file.
+#[test]
+pub fn test_solang_workspace_settings() -> Result<()> {
+    let content = r#"
+        [package]
+        version = "0.1.0"
+        input_files = ["flipper.sol"]
+        contracts = ["flipper"]
+
+        [target]
+        name = "solana"
+
+        [compiler-output]
+        output_directory = "build"
+        output_meta = "idl"
+
+        [provider]
+        cluster = "devnet"
+        wallet = "/keys/id.json"
+    "#;
+    let root = Path::new("/workspace");
+    let workspace = SolanaWorkspace::from_solang_toml(root, content)?;
+
+    assert_eq!(
+        workspace.rpc_url(),
+        Some("https://api.devnet.solana.com".to_string())
+    );
+    assert_eq!(workspace.wallet, Some("/keys/id.json".to_string()));
+
+    let flipper = workspace.program(None)?;
+    assert_eq!(flipper.idl_path, root.join("idl/flipper.json"));
+    assert_eq!(flipper.program_path, root.join("build/flipper.so"));
+
+    Ok(())
+}
+
+/// Purpose: This test checks that a workspace is only discovered in the project directory, and not
+/// in its parent directories.
+#[test]
+pub fn test_discover_workspace() -> Result<()> {
+    let root = std::env::temp_dir().join(format!("aqd-test-workspace-{}", std::process::id()));
+    let project = root.join("project");
+    fs::create_dir_all(&project)?;
+    fs::write(
+        root.join("Anchor.toml"),
+        "[provider]\ncluster = \"devnet\"\nwallet = \"/keys/id.json\"\n",
+    )?;
+
+    assert!(SolanaWorkspace::discover_from(&project)?.is_none());
+    let workspace = SolanaWorkspace::discover_from(&root)?.unwrap();
+    assert_eq!(workspace.config_path(), root.join("Anchor.toml"));
+
+    fs::remove_dir_all(&root)?;
+    Ok(())
+}
+
+/// Purpose: This test checks that the explicit values and the configuration file of the Solana CLI
+/// take precedence over the cluster and the wallet of the workspace.
+#[test]
+pub fn test_resolve_workspace_settings() -> Result<()> {
+    let content = r#"
+        [provider]
+        cluster = "devnet"
+        wallet = "/keys/id.json"
+    "#;
+    let workspace = SolanaWorkspace::from_anchor_toml(Path::new("/workspace"), content)?;
+    let cli_config = Config {
+        json_rpc_url: "https://api.testnet.solana.com".to_string(),
+        keypair_path: "/config/id.json".to_string(),
+        ..Config::default()
+    };
+
+    let url = Some("http://localhost:8899".to_string());
+    assert_eq!(
+        resolve_rpc_url(url.clone(), Some(&cli_config), Some(&workspace)),
+        url
+    );
+    assert_eq!(
+        resolve_rpc_url(None, Some(&cli_config), Some(&workspace)),
+        Some("https://api.testnet.solana.com".to_string())
+    );
+    assert_eq!(
+        resolve_rpc_url(None, None, Some(&workspace)),
+        Some("https://api.devnet.solana.com".to_string())
+    );
+    assert_eq!(resolve_rpc_url(None, None, None), None);
+
+    let keypair = Some("/payer.json".to_string());
+    assert_eq!(
+        resolve_wallet(keypair.clone(), Some(&cli_config), Some(&workspace)),
+        keypair
+    );
+    assert_eq!(
+        resolve_wallet(None, Some(&cli_config), Some(&workspace)),
+        Some("/config/id.json".to_string())
+    );
+    assert_eq!(
+        resolve_wallet(None, None, Some(&workspace)),
+        Some("/keys/id.json".to_string())
+    );
+    assert_eq!(resolve_wallet(None, None, None), None);
+
+    Ok(())
+}
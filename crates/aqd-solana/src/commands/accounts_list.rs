@@ -0,0 +1,150 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::Result,
+    solana_clap_v3_utils::input_validators::normalize_to_url_if_moniker,
+    solana_cli_config::{Config, CONFIG_FILE},
+    solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey},
+    std::{ffi::OsStr, process::exit, str::FromStr},
+};
+use {
+    aqd_solana_contracts::{
+        fetch_program_accounts, idl_with_discriminators_from_json, print_program_account,
+        print_program_accounts, rpc_client, stream_program_accounts, MemcmpFilter,
+    },
+    aqd_utils::{
+        check_target_match, export_records, print_text, ErrorKind, ExportFormat, OutputFormat,
+    },
+};
+
+#[derive(Clone, Debug, clap::Args)]
+#[clap(
+    name = "accounts-list",
+    about = "List and decode all the accounts owned by a Solana program"
+)]
+pub struct SolanaAccountsList {
+    #[clap(
+        long,
+        help = "Specifies the path or the HTTP(S) URL of the IDL JSON file, or - to read it from the standard input"
+    )]
+    idl: String,
+    #[clap(long, help = "Specifies the program ID of the deployed program")]
+    program: String,
+    #[clap(
+        long,
+        help = "Specifies the name of the IDL account type to list. Only accounts starting with the discriminator of this type are returned"
+    )]
+    account_type: Option<String>,
+    #[clap(
+        long,
+        help = "Specifies the exact size of the account data to filter on"
+    )]
+    data_size: Option<u64>,
+    #[clap(
+        long,
+        help = "Specifies a memcmp filter on the account data, as <OFFSET>:<BASE58_BYTES>",
+        // The number of memcmp filters is variable (Can be 0 or more)
+        num_args = 0..,
+    )]
+    memcmp: Vec<MemcmpFilter>,
+    #[clap(
+        long,
+        help = "Specifies whether to print each account as a line of JSON (JSONL)"
+    )]
+    output_jsonl: bool,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table, with a row per account."
+    )]
+    output: OutputFormat,
+    #[clap(
+        long,
+        env = "AQD_SOLANA_URL",
+        help = "Specifies the URL or moniker (mainnet-beta, devnet, testnet or localhost) of the Solana cluster. Defaults to the cluster of the configuration file"
+    )]
+    url: Option<String>,
+    #[clap(
+        long,
+        requires = "out",
+        help = "Specifies the format to export the accounts in (jsonl or csv)"
+    )]
+    export: Option<ExportFormat>,
+    #[clap(
+        long,
+        requires = "export",
+        help = "Specifies the path of the file to export the accounts to"
+    )]
+    out: Option<String>,
+}
+
+impl SolanaAccountsList {
+    /// Handle the Solana accounts-list command.
+    ///
+    /// This function fetches all the accounts owned by the program using `getProgramAccounts`,
+    /// applying the data size and memcmp filters, decodes each account using the accounts section
+    /// of the IDL, and prints the accounts one by one, either in a human-readable format or as JSONL,
+    /// or as a document of the output format, e.g. a table with a row per account. With JSONL, the
+    /// accounts are fetched by pages and printed as the pages arrive.
+    /// If an export format is provided, the accounts are written to the output file instead.
+    pub fn handle(&self) -> Result<()> {
+        // Make sure the command is run in the correct directory
+        // Fails if the command is run in a Solang Polkadot project directory
+        let target_match = check_target_match("solana", None)
+            .map_err(|e| anyhow::anyhow!("Failed to check current directory: {}", e))?;
+        if !target_match {
+            exit(ErrorKind::Usage.exit_code());
+        }
+
+        // Get the RPC URL from the config file
+        let config_file = CONFIG_FILE
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Error loading config file"))?;
+        let cli_config = Config::load(config_file).unwrap_or_default();
+        let rpc_url =
+            normalize_to_url_if_moniker(self.url.as_deref().unwrap_or(&cli_config.json_rpc_url));
+        let rpc_client = rpc_client(rpc_url, CommitmentConfig::confirmed());
+
+        // Get the IDL from the JSON file and the program ID
+        let (idl, discriminators) = idl_with_discriminators_from_json(OsStr::new(&self.idl))?;
+        let program_id = Pubkey::from_str(&self.program)
+            .map_err(|e| anyhow::anyhow!("Error getting program ID: {}", e))?;
+
+        // Print each account as soon as its page is fetched
+        if self.output_jsonl && self.export.is_none() {
+            return stream_program_accounts(
+                &rpc_client,
+                &program_id,
+                &idl,
+                &discriminators,
+                self.data_size,
+                &self.memcmp,
+                self.account_type.as_deref(),
+                |account| print_program_account(&account, true),
+            );
+        }
+
+        // Fetch and decode the program accounts
+        let accounts = fetch_program_accounts(
+            &rpc_client,
+            &program_id,
+            &idl,
+            &discriminators,
+            self.data_size,
+            &self.memcmp,
+            self.account_type.as_deref(),
+        )?;
+
+        // Export the accounts to a file if requested, otherwise print them
+        if let (Some(format), Some(out)) = (self.export, &self.out) {
+            let records: Vec<_> = accounts.iter().map(|account| account.to_json()).collect();
+            export_records(&records, format, out)?;
+            print_text!(format!("Exported {} accounts to {}", records.len(), out));
+        } else {
+            print_program_accounts(&accounts, self.output)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,128 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::commands::call::SolanaTransactionOpts,
+    anyhow::{anyhow, Result},
+    aqd_utils::{
+        check_target_match, cleanup_generated_files, is_quiet, keep_generated_files,
+        mark_generated_files_sent, parse_concurrency, parse_iterations, print_key_value,
+        print_text, print_title, prompt_confirm_transaction, run_bench, ErrorKind, OutputFormat,
+    },
+    std::process::exit,
+};
+
+#[derive(Clone, Debug, clap::Args)]
+#[clap(
+    name = "bench",
+    about = "Submit an instruction repeatedly and report the latency percentiles, the failures and the throughput"
+)]
+pub struct SolanaBench {
+    #[clap(flatten)]
+    transaction_opts: SolanaTransactionOpts,
+    #[clap(
+        long,
+        default_value = "10",
+        value_parser = parse_iterations,
+        help = "Specifies the number of transactions to submit"
+    )]
+    iterations: usize,
+    #[clap(
+        long,
+        default_value = "1",
+        value_parser = parse_concurrency,
+        help = "Specifies the number of transactions submitted concurrently"
+    )]
+    concurrency: usize,
+    #[clap(
+        long,
+        help = "Specifies whether to simulate the transaction repeatedly instead of submitting it"
+    )]
+    simulate: bool,
+    #[clap(
+        short('y'),
+        long,
+        env = "AQD_SKIP_CONFIRM",
+        help = "Specifies whether to skip the confirmation prompt."
+    )]
+    skip_confirm: bool,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
+}
+
+impl SolanaBench {
+    /// Handle the benchmark of an instruction.
+    ///
+    /// The transaction is built once, like for `aqd solana call`. Each iteration then fetches the
+    /// latest blockhash and submits the transaction, made unique by a compute unit price equal to
+    /// its index, and waits for its confirmation. With `--simulate`, each iteration simulates the
+    /// transaction instead. The latency of an iteration is measured from the blockhash fetch to
+    /// the confirmation. The benchmarked transactions are not recorded in the history.
+    pub fn handle(&self) -> Result<()> {
+        // Make sure the command is run in the correct directory
+        // Fails if the command is run in a Solang Polkadot project directory
+        let target_match = check_target_match("solana", None)
+            .map_err(|e| anyhow!("Failed to check current directory: {}", e))?;
+        if !target_match {
+            exit(ErrorKind::Usage.exit_code());
+        }
+
+        let transaction = self.transaction_opts.transaction()?;
+        if !transaction.new_accounts().is_empty() && !self.simulate && self.iterations > 1 {
+            return Err(ErrorKind::Usage.wrap(anyhow!(
+                "New accounts can only be created once: use existing accounts to benchmark an instruction"
+            )));
+        }
+
+        if !self.simulate && !self.skip_confirm {
+            prompt_confirm_transaction(|| {
+                print_title!("Benchmark Summary");
+                print_key_value!("Program", transaction.program_id());
+                print_key_value!("Instruction", transaction.instruction().name);
+                print_key_value!("Transactions", self.iterations);
+                print_key_value!("Concurrency", self.concurrency);
+            })?;
+        }
+        if !self.output.is_structured() && !is_quiet() {
+            print_text!(format!(
+                "{} {} {} times, {} at a time",
+                if self.simulate {
+                    "Simulating"
+                } else {
+                    "Submitting"
+                },
+                transaction.instruction().name,
+                self.iterations,
+                self.concurrency
+            ));
+        }
+
+        if !self.simulate {
+            // The outcome of a failed submission is unknown, so the generated keypairs are kept
+            mark_generated_files_sent();
+        }
+        let report = run_bench(self.iterations, self.concurrency, |index| {
+            if self.simulate {
+                let result = transaction.simulate_transaction()?;
+                return match result.err {
+                    Some(err) => Err(anyhow!("Transaction simulation failed: {}", err)),
+                    None => Ok(()),
+                };
+            }
+            let (blockhash, _) = transaction.latest_blockhash()?;
+            transaction.submit_unique_transaction(blockhash, index as u64)?;
+            Ok(())
+        });
+        if report.successes > 0 && !self.simulate {
+            // The new account of a single submitted transaction exists on chain
+            keep_generated_files();
+        } else {
+            cleanup_generated_files();
+        }
+        report.print(self.output)
+    }
+}
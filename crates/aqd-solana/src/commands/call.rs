@@ -1,30 +1,56 @@
 // SPDX-License-Identifier: Apache-2.0
 
+#[cfg(feature = "simulate-overrides")]
+use aqd_solana_contracts::AccountOverride;
 use {
     anyhow::Result,
+    serde_json::json,
     solana_clap_v3_utils::input_validators::normalize_to_url_if_moniker,
     solana_cli_config::{Config, CONFIG_FILE},
-    std::process::exit,
+    solana_sdk::{hash::Hash, pubkey::Pubkey},
+    std::{ffi::OsStr, process::exit, str::FromStr},
 };
 use {
-    aqd_solana_contracts::{print_transaction_information, SolanaTransaction},
-    aqd_utils::check_target_match,
+    aqd_solana_contracts::{
+        diff_accounts, idl_from_json, print_simulation_result, print_transaction_information,
+        resolve_rpc_url, resolve_wallet, snapshot_accounts, solana_cli_config, SimulationOptions,
+        SolanaTransaction, SolanaWorkspace, SquadsMultisig,
+    },
+    aqd_utils::{
+        check_target_match, cleanup_generated_files, is_quiet, keep_generated_files,
+        mark_generated_files_sent, print_key_value, print_text, prompt_new_password,
+        record_history, ErrorKind, HistoryEntry, OutputFormat,
+    },
 };
 
+/// The options of the program, the instruction, the payer and the cluster of a transaction, shared
+/// by the `call` and `bench` subcommands.
 #[derive(Clone, Debug, clap::Args)]
-#[clap(name = "call", about = "Send a custom transaction to a Solana program")]
-pub struct SolanaCall {
-    #[clap(long, help = "Specifies the path of the IDL JSON file")]
-    idl: String,
-    #[clap(long, help = "Specifies the program ID of the deployed program")]
-    program: String,
+pub struct SolanaTransactionOpts {
+    #[clap(
+        long,
+        help = "Specifies the path or the HTTP(S) URL of the IDL JSON file, or - to read it from the standard input. Defaults to the IDL of the workspace program"
+    )]
+    idl: Option<String>,
+    #[clap(
+        long,
+        help = "Specifies the program ID of the deployed program. Defaults to the program ID of the workspace program"
+    )]
+    program: Option<String>,
+    #[clap(
+        long,
+        help = "Specifies the name of the workspace program to call, when the workspace (Anchor.toml or solang.toml) contains several programs"
+    )]
+    program_name: Option<String>,
     #[clap(long, help = "Specifies the name of the instruction to call")]
     instruction: String,
     #[clap(
         long,
         help = "Specifies the data arguments to pass to the instruction.
-                For arrays and vectors, pass a comma-separated list of values. (e.g. 1,2,3,4)
-                For structs, pass a JSON string of the struct. (can be a path to a JSON file)",
+                For arrays and vectors, pass a JSON array (e.g. [[1,2],[3]] or [{\"a\": 1}]), or a comma-separated list of scalar values (e.g. 1,2,3,4),
+                whose elements can be double-quoted or have their commas escaped to contain commas (e.g. \"a,b\",c or a\\,b,c)
+                For structs, pass a JSON string of the struct, whose byte fields can be hex or base64 strings.
+                Any value can reference a file with @<PATH>, whose content is used verbatim (use @@ for a literal @)",
         // The number of data arguments is variable (Can be 0 or more)
         num_args = 0..,
     )]
@@ -33,43 +59,131 @@ pub struct SolanaCall {
         long,
         help = "Specifies the accounts arguments to pass to the instruction\
         Keywords:
-        - new: create a new account
+        - new: create a new account, whose keypair is written to a file (an encrypted keystore with --encrypt-new)
         - self: reads the default keypair from the local configuration file.
-        - system: use the system program ID as the account",
+        - system: use the system program ID as the account
+        - pda:<SEED1>,<SEED2>,...: derive a PDA from seeds (string, hex:<HEX>, pubkey:<PUBKEY> or arg:<NAME>)
+        - token, token-2022, associated-token: use the SPL Token, SPL Token-2022 or Associated Token Account program ID
+        - ata:<MINT>[:<OWNER>]: derive the associated token account of the owner (the payer by default) for the mint",
         // The number of accounts arguments is variable (Can be 0 or more)
         num_args = 0..,
     )]
     accounts: Vec<String>,
-    #[clap(long, help = "Specifies the payer keypair to use for the transaction")]
+    #[clap(
+        long,
+        env = "AQD_SOLANA_KEYPAIR",
+        hide_env_values = true,
+        help = "Specifies the payer keypair to use for the transaction.
+                Can be a keypair file, an inline JSON byte array, a base58 secret key or a mnemonic phrase (optionally followed by a derivation path).
+                Defaults to the keypair of the configuration file, if it exists, then to the wallet of the workspace"
+    )]
     payer: Option<String>,
-    #[clap(long, help = "Specifies whether to export the output in JSON format")]
-    output_json: bool,
+    #[clap(
+        long,
+        help = "Specifies the websocket URL used to confirm the transaction with a signature subscription, for RPC providers with distinct HTTP and websocket endpoints.
+                By default, the transaction is confirmed by polling the RPC URL"
+    )]
+    ws_url: Option<String>,
+    #[clap(
+        long,
+        env = "AQD_SOLANA_URL",
+        help = "Specifies the URL or moniker (mainnet-beta, devnet, testnet or localhost) of the Solana cluster. Defaults to the cluster of the configuration file, if it exists, then to the cluster of the workspace"
+    )]
+    url: Option<String>,
+    #[clap(
+        long,
+        env = "AQD_SOLANA_ENCRYPT_NEW",
+        help = "Specifies whether to write the keypairs of the new accounts to keystores encrypted with a password, prompted interactively, instead of plaintext keypair files"
+    )]
+    encrypt_new: bool,
 }
 
-impl SolanaCall {
-    /// Handle the Solana transaction command.
-    ///
-    /// This function handles the processing of a Solana transaction command. It checks if the command
-    /// is being run in the correct directory, parses the command-line arguments, retrieves the RPC URL
-    /// and payer keypair from the configuration file, creates a [`SolanaTransaction`] object, submits
-    /// the transaction, and prints transaction information.
-    pub fn handle(&self) -> Result<()> {
-        // Make sure the command is run in the correct directory
-        // Fails if the command is run in a Solang Polkadot project directory
-        let target_match = check_target_match("solana", None)
-            .map_err(|e| anyhow::anyhow!("Failed to check current directory: {}", e))?;
-        if !target_match {
-            exit(1);
-        }
+#[derive(Clone, Debug, clap::Args)]
+#[clap(name = "call", about = "Send a custom transaction to a Solana program")]
+pub struct SolanaCall {
+    #[clap(flatten)]
+    transaction_opts: SolanaTransactionOpts,
+    #[clap(
+        long,
+        help = "Specifies whether to simulate the transaction instead of submitting it"
+    )]
+    simulate: bool,
+    #[cfg(feature = "simulate-overrides")]
+    #[clap(
+        long,
+        requires = "simulate",
+        help = "Specifies an account whose data is overridden during the simulation, as <ADDRESS>:<BASE64_DATA>.
+                The overrides are sent with the simulation request, and the RPC server must support them",
+        // The number of overrides is variable (Can be 0 or more)
+        num_args = 0..,
+    )]
+    override_account: Vec<AccountOverride>,
+    #[clap(
+        long,
+        requires = "simulate",
+        help = "Specifies the minimum slot the RPC server must have reached to simulate the transaction"
+    )]
+    min_context_slot: Option<u64>,
+    #[clap(
+        long,
+        requires = "simulate",
+        help = "Specifies whether the RPC server replaces the blockhash of the simulated transaction with its latest one.
+                Signatures are not verified in that case"
+    )]
+    replace_blockhash: bool,
+    #[clap(
+        long,
+        conflicts_with = "simulate",
+        help = "Specifies whether to print a field-level diff of the writable accounts before and after the transaction"
+    )]
+    diff_accounts: bool,
+    #[clap(
+        long,
+        conflicts_with_all = ["simulate", "diff_accounts"],
+        help = "Specifies the address of a Squads multisig. Instead of submitting the transaction, a proposal signed by the multisig vault is created.
+                Signer accounts of the instruction must be the vault address, and the payer must be a member of the multisig"
+    )]
+    squads_multisig: Option<String>,
+    #[clap(
+        long,
+        requires = "squads_multisig",
+        default_value = "0",
+        help = "Specifies the index of the Squads vault that signs the proposed transaction"
+    )]
+    squads_vault_index: u8,
+    #[clap(
+        long,
+        requires = "squads_multisig",
+        help = "Specifies a memo to attach to the Squads proposal"
+    )]
+    squads_memo: Option<String>,
+    #[clap(
+        long,
+        conflicts_with_all = ["simulate", "squads_multisig"],
+        help = "Specifies the recent blockhash to use for the transaction, instead of fetching the latest one at submission time"
+    )]
+    blockhash: Option<Hash>,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
+}
 
+impl SolanaTransactionOpts {
+    /// Build the transaction.
+    ///
+    /// The RPC URL and the payer keypair are taken from the workspace (Anchor.toml or
+    /// solang.toml) or the configuration file, and the IDL and the program ID from the workspace,
+    /// unless they are provided.
+    pub fn transaction(&self) -> Result<SolanaTransaction> {
         // Parse command-line arguments
-        let idl_json = self.idl.clone();
-        let program_id = self.program.clone();
         let instruction = self.instruction.clone();
         let data_args = self.data.clone();
         let accounts_args = self.accounts.clone();
         let payer = self.payer.clone();
-        let output_json = self.output_json;
 
         // Get the RPC URL from the config file
         // Parse the config file to get the RPC URL and payer keypair.
@@ -77,10 +191,51 @@ impl SolanaCall {
             .as_ref()
             .ok_or_else(|| anyhow::anyhow!("Error loading config file"))?;
         let cli_config = Config::load(config_file).unwrap_or_default();
-        let rpc_url = normalize_to_url_if_moniker(&cli_config.json_rpc_url);
-        let keypair = cli_config.keypair_path.to_string();
 
-        let payer = payer.unwrap_or(keypair);
+        // Resolve the IDL, program ID, cluster and wallet from the workspace (if any), the
+        // command-line arguments and the existing configuration file taking precedence.
+        let workspace = SolanaWorkspace::discover()?;
+        let workspace_program = match (&workspace, &self.idl, &self.program) {
+            (Some(workspace), None, _) | (Some(workspace), _, None) => {
+                Some(workspace.program(self.program_name.as_deref())?)
+            }
+            _ => None,
+        };
+        let idl_json = match (&self.idl, workspace_program) {
+            (Some(idl), _) => idl.clone(),
+            (None, Some(program)) => program.idl_path.to_string_lossy().to_string(),
+            (None, None) => {
+                return Err(anyhow::anyhow!(
+                    "No IDL provided. Use --idl or run the command inside a workspace"
+                ))
+            }
+        };
+        let program_id = match (&self.program, workspace_program) {
+            (Some(program), _) => program.clone(),
+            (None, Some(program)) => program
+                .resolve_program_id(&idl_from_json(OsStr::new(&idl_json))?)
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Program ID of {} not found in the workspace or the IDL. Use --program",
+                        program.name
+                    )
+                })?,
+            (None, None) => {
+                return Err(anyhow::anyhow!(
+                    "No program ID provided. Use --program or run the command inside a workspace"
+                ))
+            }
+        };
+        let existing_cli_config = solana_cli_config();
+        let rpc_url = resolve_rpc_url(
+            self.url.clone(),
+            existing_cli_config.as_ref(),
+            workspace.as_ref(),
+        )
+        .map(|url| normalize_to_url_if_moniker(&url))
+        .unwrap_or_else(|| normalize_to_url_if_moniker(&cli_config.json_rpc_url));
+        let payer = resolve_wallet(payer, existing_cli_config.as_ref(), workspace.as_ref())
+            .unwrap_or_else(|| cli_config.keypair_path.to_string());
 
         // Create a `SolanaTransaction` object with the necessary parameters.
         let transaction = SolanaTransaction::new()
@@ -90,20 +245,191 @@ impl SolanaCall {
             .instruction(instruction.to_string())
             .call_data(data_args)
             .accounts(accounts_args)
-            .payer(payer.clone())
-            .done()?;
+            .payer(payer.clone());
+        let transaction = match &self.ws_url {
+            Some(ws_url) => transaction.ws_url(ws_url.clone()),
+            None => transaction,
+        };
+        // Only prompted if a new account is created
+        let transaction = if self.encrypt_new && self.accounts.iter().any(|a| a == "new") {
+            transaction.keystore_password(prompt_new_password()?)
+        } else {
+            transaction
+        };
+        transaction.done()
+    }
+}
 
-        // Submit the transaction.
-        let signature = transaction.submit_transaction()?;
+impl SolanaCall {
+    /// Handle the Solana transaction command.
+    ///
+    /// This function handles the processing of a Solana transaction command. It checks if the command
+    /// is being run in the correct directory, parses the command-line arguments, retrieves the RPC URL
+    /// and payer keypair from the workspace (Anchor.toml or solang.toml) or the configuration file,
+    /// resolves the IDL and program ID from the workspace if they are not provided, creates a
+    /// [`SolanaTransaction`] object, submits the transaction, and prints transaction information. If `--diff-accounts` is set, the writable
+    /// accounts are fetched before and after the transaction, and the changes to their decoded
+    /// fields are printed along with the transaction information. If `--squads-multisig` is set, a
+    /// proposal signed by the multisig vault is created instead of submitting the transaction. If
+    /// `--blockhash` is set, the transaction uses it instead of the latest blockhash.
+    pub fn handle(&self) -> Result<()> {
+        // Make sure the command is run in the correct directory
+        // Fails if the command is run in a Solang Polkadot project directory
+        let target_match = check_target_match("solana", None)
+            .map_err(|e| anyhow::anyhow!("Failed to check current directory: {}", e))?;
+        if !target_match {
+            exit(ErrorKind::Usage.exit_code());
+        }
 
-        // Print the transaction information.
+        let output = self.output;
+        let transaction = self.transaction_opts.transaction()?;
+
+        // Simulate the transaction instead of submitting it, if requested.
+        if self.simulate {
+            let result = transaction.simulate_transaction_with_options(&SimulationOptions {
+                replace_recent_blockhash: self.replace_blockhash,
+                min_context_slot: self.min_context_slot,
+                #[cfg(feature = "simulate-overrides")]
+                account_overrides: self.override_account.clone(),
+                #[cfg(not(feature = "simulate-overrides"))]
+                account_overrides: vec![],
+            })?;
+            print_simulation_result(
+                &result,
+                transaction.instruction(),
+                transaction.idl().types.as_slice(),
+                output,
+            )?;
+            // Nothing was sent, so the keypairs generated for the new accounts are not needed
+            cleanup_generated_files();
+            // The simulation result is printed in full, but a failed simulation is still an error
+            return match &result.err {
+                Some(err) => Err(ErrorKind::SimulationRevert
+                    .wrap(anyhow::anyhow!("Transaction simulation failed: {}", err))),
+                None => Ok(()),
+            };
+        }
+
+        // Create a Squads proposal instead of submitting the transaction, if requested.
+        if let Some(multisig) = &self.squads_multisig {
+            let multisig = Pubkey::from_str(multisig)
+                .map_err(|e| anyhow::anyhow!("Invalid Squads multisig address: {}", e))?;
+            let multisig = SquadsMultisig::new(multisig, self.squads_vault_index);
+            mark_generated_files_sent();
+            let proposal = multisig.create_proposal(
+                transaction.rpc_client(),
+                transaction.payer(),
+                &[transaction.to_instruction()],
+                self.squads_memo.clone(),
+            )?;
+            keep_generated_files();
+            if is_quiet() {
+                print_text!(proposal.proposal);
+            } else if output.is_structured() {
+                let proposal = json!({
+                    "multisig": multisig.multisig.to_string(),
+                    "vault": multisig.vault_address().to_string(),
+                    "transaction_index": proposal.transaction_index,
+                    "transaction": proposal.transaction.to_string(),
+                    "proposal": proposal.proposal.to_string(),
+                    "signature": proposal.signature.to_string(),
+                });
+                output.print(&proposal)?;
+            } else {
+                print_text!(format!(
+                    "Proposal created in multisig {}",
+                    multisig.multisig
+                ));
+                print_key_value!("Transaction index", proposal.transaction_index);
+                print_key_value!("Proposal", proposal.proposal);
+                print_key_value!("Signature", proposal.signature);
+            }
+            return Ok(());
+        }
+
+        // Snapshot the writable accounts before submitting the transaction, if requested.
+        let mut writable_accounts: Vec<Pubkey> = vec![];
+        for account in transaction.accounts() {
+            if account.is_writable && !writable_accounts.contains(&account.pubkey) {
+                writable_accounts.push(account.pubkey);
+            }
+        }
+        let snapshot_before = if self.diff_accounts {
+            Some(snapshot_accounts(
+                transaction.rpc_client(),
+                transaction.idl(),
+                transaction.discriminators(),
+                &writable_accounts,
+            )?)
+        } else {
+            None
+        };
+
+        // Print the derived PDAs, so that their bumps are known.
+        if !output.is_structured() && !is_quiet() {
+            for (account_name, pda, bump) in transaction.pdas() {
+                print_text!(format!(
+                    "Derived PDA for {}: {} (bump: {})",
+                    account_name, pda, bump
+                ));
+            }
+        }
+
+        // Submit the transaction, with the provided blockhash or the latest one.
+        let blockhash = match self.blockhash {
+            Some(blockhash) => blockhash,
+            None => {
+                let (blockhash, last_valid_block_height) = transaction.latest_blockhash()?;
+                if !output.is_structured() && !is_quiet() {
+                    print_text!(format!(
+                        "Using blockhash {} (last valid block height: {})",
+                        blockhash, last_valid_block_height
+                    ));
+                }
+                blockhash
+            }
+        };
+        // From here on, the outcome of a failure is unknown and the generated keypairs are kept
+        mark_generated_files_sent();
+        let signature = transaction.submit_transaction_with_blockhash(blockhash);
+        let entry = HistoryEntry::new("solana", "call")
+            .contract(transaction.program_id())
+            .function(&transaction.instruction().name);
+        record_history(match &signature {
+            Ok(signature) => entry.hash(signature),
+            Err(e) => entry.failed(e),
+        });
+        let signature = signature?;
+        // The new accounts are created by the submitted transaction, so their keypairs are kept
+        keep_generated_files();
+
+        // Re-fetch the writable accounts and compute the diff.
+        let account_diffs = match snapshot_before {
+            Some(snapshot_before) => {
+                let snapshot_after = snapshot_accounts(
+                    transaction.rpc_client(),
+                    transaction.idl(),
+                    transaction.discriminators(),
+                    &writable_accounts,
+                )?;
+                diff_accounts(&snapshot_before, &snapshot_after)
+            }
+            None => vec![],
+        };
+
+        // Print the signature alone in quiet mode, or the transaction information.
+        if is_quiet() {
+            print_text!(signature);
+            return Ok(());
+        }
         print_transaction_information(
             transaction.rpc_client(),
             &signature,
             transaction.instruction(),
             transaction.idl().types.as_slice(),
             transaction.new_accounts(),
-            output_json,
+            &account_diffs,
+            output,
         )
     }
 }
@@ -0,0 +1,98 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::Result,
+    serde_json::json,
+    solana_sdk::{pubkey::Pubkey, signature::Signer},
+};
+use {
+    aqd_solana_contracts::{parse_keypair, parse_pubkey},
+    aqd_utils::{is_quiet, print_key_value, print_text, OutputFormat},
+};
+
+#[derive(Clone, Debug, clap::Args)]
+#[clap(
+    name = "pubkey",
+    about = "Validate a Solana public key, and convert it between base58 and hex"
+)]
+pub struct SolanaPubkey {
+    #[clap(
+        value_parser,
+        help = "Specifies the public key to validate, base58 encoded or as 32 hex encoded bytes with a 0x prefix"
+    )]
+    pubkey: String,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
+}
+
+impl SolanaPubkey {
+    /// Handle the validation of a Solana public key.
+    ///
+    /// The public key is printed in base58 and in hex, along with whether it is on the ed25519
+    /// curve: the addresses of keypairs are, while the program derived addresses are not.
+    pub fn handle(&self) -> Result<()> {
+        let pubkey = parse_pubkey(&self.pubkey)?;
+        match is_quiet() {
+            true => print_text!(pubkey),
+            false => print_pubkey(&pubkey, self.output)?,
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, clap::Args)]
+#[clap(name = "keypair", about = "Print the public key of a Solana keypair")]
+pub struct SolanaKeypairPubkey {
+    #[clap(
+        value_parser,
+        help = "Specifies the keypair: a keypair file or keystore, a JSON byte array, a base58 secret key or a mnemonic phrase"
+    )]
+    keypair: String,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
+}
+
+impl SolanaKeypairPubkey {
+    /// Handle the extraction of the public key of a keypair.
+    ///
+    /// The password of an encrypted keystore is prompted interactively. The secret key is never
+    /// printed.
+    pub fn handle(&self) -> Result<()> {
+        let pubkey = parse_keypair(&self.keypair)?.pubkey();
+        match is_quiet() {
+            true => print_text!(pubkey),
+            false => print_pubkey(&pubkey, self.output)?,
+        }
+        Ok(())
+    }
+}
+
+/// Print a public key in base58 and in hex, and whether it is on the ed25519 curve.
+fn print_pubkey(pubkey: &Pubkey, output: OutputFormat) -> Result<()> {
+    let hex = format!("0x{}", hex::encode(pubkey.to_bytes()));
+    if output.is_structured() {
+        output.print(&json!({
+            "pubkey": pubkey.to_string(),
+            "hex": hex,
+            "on_curve": pubkey.is_on_curve(),
+        }))?;
+    } else {
+        print_key_value!("Public key", pubkey);
+        print_key_value!("Hex", hex);
+        match pubkey.is_on_curve() {
+            true => print_key_value!("On curve", "yes (the address of a keypair)"),
+            false => print_key_value!("On curve", "no (a program derived address)"),
+        }
+    }
+    Ok(())
+}
@@ -1,23 +1,96 @@
 // SPDX-License-Identifier: Apache-2.0
 
-use {anyhow::Result, serde_json::json, std::process::exit};
-use {aqd_solana_contracts::deploy_program, aqd_utils::check_target_match};
+use {
+    anyhow::Result,
+    serde_json::json,
+    solana_clap_v3_utils::input_validators::normalize_to_url_if_moniker,
+    std::{fs, process::exit},
+};
+use {
+    aqd_solana_contracts::{
+        deploy_program_with_overrides, resolve_rpc_url, resolve_wallet, solana_cli_config,
+        DeployManifest, SolanaWorkspace,
+    },
+    aqd_utils::{
+        check_target_match, is_quiet, parse_jobs, print_key_value, print_text, record_history,
+        solang_compile, ErrorKind, HistoryEntry, OutputFormat,
+    },
+};
 
 #[derive(Clone, Debug, clap::Args)]
 #[clap(name = "deploy", about = "Deploy a program to Solana")]
 pub struct SolanaDeploy {
-    #[clap(help = "Specifies the path to the program file to deploy (.so)")]
-    program_location: String,
-    #[clap(long, help = "Specifies whether to export the output in JSON format")]
-    output_json: bool,
+    #[clap(
+        help = "Specifies the path to the program file to deploy (.so). Defaults to the program of the workspace"
+    )]
+    program_location: Option<String>,
+    #[clap(
+        long,
+        help = "Specifies the name of the workspace program to deploy, when the workspace (Anchor.toml or solang.toml) contains several programs"
+    )]
+    program_name: Option<String>,
+    #[clap(
+        long,
+        conflicts_with_all = ["program_location", "program_name"],
+        help = "Specifies the path of a TOML manifest listing the programs to deploy ([[programs]] entries with a name and a path)"
+    )]
+    manifest: Option<String>,
+    #[clap(
+        long,
+        requires = "manifest",
+        conflicts_with = "jobs",
+        help = "Specifies whether to deploy all the programs of the manifest in parallel"
+    )]
+    parallel: bool,
+    #[clap(
+        short,
+        long,
+        requires = "manifest",
+        default_value = "1",
+        value_parser = parse_jobs,
+        help = "Specifies the maximum number of programs of the manifest to deploy concurrently"
+    )]
+    jobs: usize,
+    #[clap(
+        long,
+        requires = "manifest",
+        help = "Specifies the path of the file to write the JSON summary of the manifest deployment to"
+    )]
+    summary: Option<String>,
+    #[clap(
+        long,
+        env = "AQD_SOLANA_URL",
+        help = "Specifies the URL or moniker (mainnet-beta, devnet, testnet or localhost) of the Solana cluster. Defaults to the cluster of the configuration file, if it exists, then to the cluster of the workspace"
+    )]
+    url: Option<String>,
+    #[clap(
+        long,
+        env = "AQD_SOLANA_KEYPAIR",
+        hide_env_values = true,
+        help = "Specifies the path of the keypair file paying for the deployment. Defaults to the keypair of the configuration file, if it exists, then to the wallet of the workspace"
+    )]
+    keypair: Option<String>,
+    #[clap(
+        long,
+        help = "Specifies whether to compile the Solang project with `solang compile` before deploying, so that the latest artifact is deployed."
+    )]
+    build: bool,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
 }
 
 impl SolanaDeploy {
     /// Handle the deployment of a Solana program
     ///
     /// This function is responsible for managing the deployment process,
-    /// including checking the current directory, parsing command-line arguments,
-    /// configuring settings, and executing the deployment command. It also handles
+    /// including checking the current directory, compiling the Solang project if `--build` is set, parsing command-line arguments,
+    /// resolving the program, cluster and wallet from the workspace (Anchor.toml or
+    /// solang.toml), configuring settings, and executing the deployment command. It also handles
     /// loading the necessary configuration and signers, defining output formats,
     /// and processing the deployment command using the provided configuration.
     pub fn handle(&self) -> Result<()> {
@@ -26,23 +99,130 @@ impl SolanaDeploy {
         let target_match = check_target_match("solana", None)
             .map_err(|e| anyhow::anyhow!("Failed to check current directory: {}", e))?;
         if !target_match {
-            exit(1);
+            exit(ErrorKind::Usage.exit_code());
+        }
+
+        // Compile the project first, if requested
+        if self.build {
+            solang_compile()?;
         }
 
         // Parse command-line arguments
-        let program_location = self.program_location.clone();
-        let output_json = self.output_json;
+        let output = self.output;
+
+        // Resolve the program location, cluster and wallet from the workspace (if any), the
+        // command-line arguments and the existing configuration file taking precedence.
+        let workspace = SolanaWorkspace::discover()?;
+        let cli_config = solana_cli_config();
+        let json_rpc_url =
+            resolve_rpc_url(self.url.clone(), cli_config.as_ref(), workspace.as_ref())
+                .map(|url| normalize_to_url_if_moniker(&url));
+        let keypair_path = resolve_wallet(
+            self.keypair.clone(),
+            cli_config.as_ref(),
+            workspace.as_ref(),
+        );
+
+        // Deploy all the programs of the manifest, if provided
+        if let Some(manifest) = &self.manifest {
+            return self.deploy_manifest(manifest, json_rpc_url, keypair_path);
+        }
+
+        let program_location = match (&self.program_location, &workspace) {
+            (Some(program_location), _) => program_location.clone(),
+            (None, Some(workspace)) => workspace
+                .program(self.program_name.as_deref())?
+                .program_path
+                .to_string_lossy()
+                .to_string(),
+            (None, None) => {
+                return Err(anyhow::anyhow!(
+                    "No program file provided. Specify one or run the command inside a workspace"
+                ))
+            }
+        };
 
         // Deploy the program
-        let program_id = deploy_program(program_location)?;
+        let program_id =
+            deploy_program_with_overrides(program_location, json_rpc_url, keypair_path);
+        let entry = HistoryEntry::new("solana", "deploy");
+        record_history(match &program_id {
+            Ok(program_id) => entry.contract(program_id),
+            Err(e) => entry.failed(e),
+        });
+        let program_id = program_id?;
 
-        // If the output is JSON, print the program ID in JSON format
+        // If the output is quiet, print the program ID alone
+        // If the output is structured, print the program ID in the output format
         // Else, print the program ID as a string
-        if output_json {
-            let program_id = json!({ "program_id": program_id });
-            println!("{}", program_id);
+        if is_quiet() {
+            print_text!(program_id);
+        } else if output.is_structured() {
+            output.print(&json!({ "program_id": program_id }))?;
         } else {
-            println!("Program ID: {}", program_id);
+            print_key_value!("Program ID", program_id);
+        }
+
+        Ok(())
+    }
+
+    /// Deploy all the programs listed in a manifest, and print a summary of the deployments.
+    ///
+    /// The summary is printed in the `--output` format if it is structured, and written in JSON to
+    /// the `--summary` file if provided. Returns an error if any of the deployments failed.
+    fn deploy_manifest(
+        &self,
+        manifest: &str,
+        json_rpc_url: Option<String>,
+        keypair_path: Option<String>,
+    ) -> Result<()> {
+        let manifest = DeployManifest::from_file(manifest)?;
+        let jobs = match self.parallel {
+            true => manifest.programs.len(),
+            false => self.jobs,
+        };
+        let deployments = manifest.deploy(jobs, json_rpc_url, keypair_path);
+        for deployment in &deployments {
+            let entry = HistoryEntry::new("solana", "deploy");
+            record_history(match (&deployment.program_id, &deployment.error) {
+                (_, Some(error)) => entry.failed(&anyhow::anyhow!("{}", error)),
+                (Some(program_id), None) => entry.contract(program_id),
+                (None, None) => entry,
+            });
+        }
+
+        let summary = json!({
+            "programs": deployments.iter().map(|d| d.to_json()).collect::<Vec<_>>(),
+        });
+        if let Some(summary_path) = &self.summary {
+            fs::write(summary_path, serde_json::to_string_pretty(&summary)?).map_err(|e| {
+                anyhow::anyhow!("Failed to write summary file {}: {}", summary_path, e)
+            })?;
+        }
+
+        if self.output.is_structured() {
+            self.output.print(&summary)?;
+        } else {
+            for deployment in &deployments {
+                match (&deployment.program_id, &deployment.error) {
+                    (Some(program_id), _) => {
+                        print_key_value!(deployment.name, program_id)
+                    }
+                    (None, Some(error)) => {
+                        print_key_value!(deployment.name, format!("Failed: {}", error))
+                    }
+                    (None, None) => {}
+                }
+            }
+        }
+
+        let failed = deployments.iter().filter(|d| d.error.is_some()).count();
+        if failed > 0 {
+            return Err(anyhow::anyhow!(
+                "{} of {} programs failed to deploy",
+                failed,
+                deployments.len()
+            ));
         }
 
         Ok(())
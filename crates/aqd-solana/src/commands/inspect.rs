@@ -0,0 +1,132 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::Result,
+    solana_clap_v3_utils::input_validators::normalize_to_url_if_moniker,
+    solana_cli_config::{Config, CONFIG_FILE},
+    solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey},
+    std::{ffi::OsStr, process::exit, str::FromStr},
+};
+use {
+    aqd_solana_contracts::{
+        idl_with_discriminators_from_json, print_program_account, print_program_accounts,
+        rpc_client, snapshot_accounts,
+    },
+    aqd_utils::{
+        check_target_match, export_records, print_text, print_warning, ErrorKind, ExportFormat,
+        OutputFormat,
+    },
+};
+
+#[derive(Clone, Debug, clap::Args)]
+#[clap(
+    name = "inspect",
+    about = "Fetch and decode a set of Solana accounts given an IDL JSON file"
+)]
+pub struct SolanaInspect {
+    #[clap(
+        long,
+        help = "Specifies the path or the HTTP(S) URL of the IDL JSON file, or - to read it from the standard input"
+    )]
+    idl: String,
+    #[clap(
+        long,
+        required = true,
+        help = "Specifies the addresses of the accounts to decode",
+        // The number of accounts is variable (Can be 1 or more)
+        num_args = 1..,
+    )]
+    accounts: Vec<String>,
+    #[clap(
+        long,
+        help = "Specifies whether to print each account as a line of JSON (JSONL)"
+    )]
+    output_jsonl: bool,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table, with a row per account."
+    )]
+    output: OutputFormat,
+    #[clap(
+        long,
+        env = "AQD_SOLANA_URL",
+        help = "Specifies the URL or moniker (mainnet-beta, devnet, testnet or localhost) of the Solana cluster. Defaults to the cluster of the configuration file"
+    )]
+    url: Option<String>,
+    #[clap(
+        long,
+        requires = "out",
+        help = "Specifies the format to export the accounts in (jsonl or csv)"
+    )]
+    export: Option<ExportFormat>,
+    #[clap(
+        long,
+        requires = "export",
+        help = "Specifies the path of the file to export the accounts to"
+    )]
+    out: Option<String>,
+}
+
+impl SolanaInspect {
+    /// Handle the Solana inspect command.
+    ///
+    /// This function fetches the given accounts using `getMultipleAccounts`, decodes each account
+    /// using the accounts section of the IDL, and prints the accounts like the accounts-list
+    /// command. The accounts that do not exist are reported with a warning.
+    /// If an export format is provided, the accounts are written to the output file instead.
+    pub fn handle(&self) -> Result<()> {
+        // Make sure the command is run in the correct directory
+        // Fails if the command is run in a Solang Polkadot project directory
+        let target_match = check_target_match("solana", None)
+            .map_err(|e| anyhow::anyhow!("Failed to check current directory: {}", e))?;
+        if !target_match {
+            exit(ErrorKind::Usage.exit_code());
+        }
+
+        // Get the RPC URL from the config file
+        let config_file = CONFIG_FILE
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Error loading config file"))?;
+        let cli_config = Config::load(config_file).unwrap_or_default();
+        let rpc_url =
+            normalize_to_url_if_moniker(self.url.as_deref().unwrap_or(&cli_config.json_rpc_url));
+        let rpc_client = rpc_client(rpc_url, CommitmentConfig::confirmed());
+
+        // Get the IDL from the JSON file and the addresses of the accounts
+        let (idl, discriminators) = idl_with_discriminators_from_json(OsStr::new(&self.idl))?;
+        let pubkeys = self
+            .accounts
+            .iter()
+            .map(|account| {
+                Pubkey::from_str(account)
+                    .map_err(|e| anyhow::anyhow!("Invalid account address {}: {}", account, e))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // Fetch and decode the accounts
+        let mut accounts = vec![];
+        for (pubkey, account) in snapshot_accounts(&rpc_client, &idl, &discriminators, &pubkeys)? {
+            match account {
+                Some(account) => accounts.push(account),
+                None => print_warning!(format!("Account {} not found", pubkey)),
+            }
+        }
+
+        // Export the accounts to a file if requested, otherwise print them
+        if let (Some(format), Some(out)) = (self.export, &self.out) {
+            let records: Vec<_> = accounts.iter().map(|account| account.to_json()).collect();
+            export_records(&records, format, out)?;
+            print_text!(format!("Exported {} accounts to {}", records.len(), out));
+        } else if self.output_jsonl {
+            for account in &accounts {
+                print_program_account(account, true)?;
+            }
+        } else {
+            print_program_accounts(&accounts, self.output)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,104 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::Result,
+    serde_json::json,
+    solana_sdk::signature::{write_keypair_file, Keypair, Signer},
+    std::{path::PathBuf, process::exit},
+};
+use {
+    aqd_solana_contracts::{parse_keypair, write_keystore_file},
+    aqd_utils::{
+        check_target_match, is_quiet, print_key_value, print_text, prompt_new_password, ErrorKind,
+        OutputFormat,
+    },
+};
+
+#[derive(Clone, Debug, clap::Args)]
+#[clap(
+    name = "keygen",
+    about = "Generate a keypair, or encrypt an existing one, into a keypair file or an encrypted keystore"
+)]
+pub struct SolanaKeygen {
+    #[clap(
+        value_parser,
+        help = "Specifies the path of the keypair file to create"
+    )]
+    path: PathBuf,
+    #[clap(
+        long,
+        help = "Specifies whether to encrypt the keypair with a password, prompted interactively. The keystore can be used wherever a keypair file is accepted, and its password is prompted when it is used"
+    )]
+    encrypt: bool,
+    #[clap(
+        long,
+        help = "Specifies an existing keypair (file, JSON byte array, base58 secret key or mnemonic) to write instead of a new one, e.g. to encrypt a plaintext keypair file"
+    )]
+    from: Option<String>,
+    #[clap(long, help = "Specifies whether to overwrite an existing file")]
+    force: bool,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
+}
+
+impl SolanaKeygen {
+    /// Handle the Solana keygen command.
+    ///
+    /// This function generates a new keypair, or reads the keypair of `--from`, and writes it to a
+    /// keypair file as `solana-keygen` does, or to a keystore encrypted with a password if
+    /// `--encrypt` is set. The public key of the keypair is printed.
+    pub fn handle(&self) -> Result<()> {
+        // Make sure the command is run in the correct directory
+        // Fails if the command is run in a Solang Polkadot project directory
+        let target_match = check_target_match("solana", None)
+            .map_err(|e| anyhow::anyhow!("Failed to check current directory: {}", e))?;
+        if !target_match {
+            exit(ErrorKind::Usage.exit_code());
+        }
+
+        if self.path.exists() && !self.force {
+            return Err(ErrorKind::Usage.wrap(anyhow::anyhow!(
+                "{} already exists, use --force to overwrite it",
+                self.path.display()
+            )));
+        }
+        let keypair = match &self.from {
+            Some(from) => parse_keypair(from)
+                .map_err(|e| anyhow::anyhow!("Error reading keypair {}: {}", from, e))?,
+            None => Keypair::new(),
+        };
+        if self.encrypt {
+            write_keystore_file(&keypair, &self.path, &prompt_new_password()?)?;
+        } else {
+            write_keypair_file(&keypair, &self.path)
+                .map_err(|e| anyhow::anyhow!("Failed to write {}: {}", self.path.display(), e))?;
+        }
+
+        if is_quiet() {
+            print_text!(keypair.pubkey());
+        } else if self.output.is_structured() {
+            let output = json!({
+                "pubkey": keypair.pubkey().to_string(),
+                "path": self.path.display().to_string(),
+                "encrypted": self.encrypt,
+            });
+            self.output.print(&output)?;
+        } else {
+            print_key_value!("Public key", keypair.pubkey());
+            print_key_value!("Path", self.path.display());
+            if self.encrypt {
+                print_key_value!(
+                    "Encrypted",
+                    "yes, the password is prompted when the keystore is used"
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
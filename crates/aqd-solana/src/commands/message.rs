@@ -0,0 +1,168 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::Result,
+    serde_json::json,
+    solana_cli_config::{Config, CONFIG_FILE},
+    solana_sdk::{pubkey::Pubkey, signature::Signer},
+    std::{process::exit, str::FromStr},
+};
+use {
+    aqd_solana_contracts::{
+        message_bytes, parse_keypair, sign_message, verify_message, MessageEncoding,
+    },
+    aqd_utils::{
+        check_target_match, is_quiet, print_key_value, print_text, ErrorKind, OutputFormat,
+    },
+};
+
+#[derive(Clone, Debug, clap::Args)]
+#[clap(
+    name = "sign-message",
+    about = "Sign an arbitrary message with a keypair (ed25519)"
+)]
+pub struct SolanaSignMessage {
+    #[clap(help = "Specifies the message to sign")]
+    message: String,
+    #[clap(
+        long,
+        default_value = "utf8",
+        help = "Specifies the encoding of the message (utf8, hex or file)"
+    )]
+    encoding: MessageEncoding,
+    #[clap(
+        long,
+        env = "AQD_SOLANA_KEYPAIR",
+        hide_env_values = true,
+        help = "Specifies the keypair (file, JSON byte array, base58 secret key or mnemonic) to sign the message with. Defaults to the keypair of the configuration file"
+    )]
+    keypair: Option<String>,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
+}
+
+#[derive(Clone, Debug, clap::Args)]
+#[clap(
+    name = "verify-message",
+    about = "Verify the ed25519 signature of an arbitrary message"
+)]
+pub struct SolanaVerifyMessage {
+    #[clap(help = "Specifies the message that was signed")]
+    message: String,
+    #[clap(
+        long,
+        default_value = "utf8",
+        help = "Specifies the encoding of the message (utf8, hex or file)"
+    )]
+    encoding: MessageEncoding,
+    #[clap(long, help = "Specifies the base58 signature to verify")]
+    signature: String,
+    #[clap(
+        long,
+        help = "Specifies the public key (or keypair file) of the signer"
+    )]
+    pubkey: String,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
+}
+
+impl SolanaSignMessage {
+    /// Handle the Solana sign-message command.
+    ///
+    /// This function signs the message bytes with the keypair, and prints the base58 signature
+    /// along with the public key of the signer.
+    pub fn handle(&self) -> Result<()> {
+        // Make sure the command is run in the correct directory
+        // Fails if the command is run in a Solang Polkadot project directory
+        let target_match = check_target_match("solana", None)
+            .map_err(|e| anyhow::anyhow!("Failed to check current directory: {}", e))?;
+        if !target_match {
+            exit(ErrorKind::Usage.exit_code());
+        }
+
+        // Get the keypair, defaulting to the one of the config file
+        let keypair_path = match &self.keypair {
+            Some(keypair) => keypair.clone(),
+            None => {
+                let config_file = CONFIG_FILE
+                    .as_ref()
+                    .ok_or_else(|| anyhow::anyhow!("Error loading config file"))?;
+                Config::load(config_file).unwrap_or_default().keypair_path
+            }
+        };
+        let keypair = parse_keypair(&keypair_path)
+            .map_err(|e| anyhow::anyhow!("Error reading keypair {}: {}", keypair_path, e))?;
+
+        let message = message_bytes(&self.message, self.encoding)?;
+        let signature = sign_message(&keypair, &message);
+
+        if is_quiet() {
+            print_text!(signature);
+        } else if self.output.is_structured() {
+            let output = json!({
+                "pubkey": keypair.pubkey().to_string(),
+                "signature": signature.to_string(),
+            });
+            self.output.print(&output)?;
+        } else {
+            print_key_value!("Public key", keypair.pubkey());
+            print_key_value!("Signature", signature);
+        }
+
+        Ok(())
+    }
+}
+
+impl SolanaVerifyMessage {
+    /// Handle the Solana verify-message command.
+    ///
+    /// This function verifies the signature of the message bytes by the public key, and returns
+    /// an error if the signature does not match.
+    pub fn handle(&self) -> Result<()> {
+        // Make sure the command is run in the correct directory
+        // Fails if the command is run in a Solang Polkadot project directory
+        let target_match = check_target_match("solana", None)
+            .map_err(|e| anyhow::anyhow!("Failed to check current directory: {}", e))?;
+        if !target_match {
+            exit(ErrorKind::Usage.exit_code());
+        }
+
+        // The signer can be given as a public key or a keypair file
+        let pubkey = match Pubkey::from_str(&self.pubkey) {
+            Ok(pubkey) => pubkey,
+            Err(_) => parse_keypair(&self.pubkey)
+                .map_err(|_e| {
+                    anyhow::anyhow!("{} is not a valid public key or keypair path", self.pubkey)
+                })?
+                .pubkey(),
+        };
+
+        let message = message_bytes(&self.message, self.encoding)?;
+        let valid = verify_message(&pubkey, &self.signature, &message)?;
+
+        if self.output.is_structured() {
+            self.output
+                .print(&json!({ "pubkey": pubkey.to_string(), "valid": valid }))?;
+        } else if valid && !is_quiet() {
+            print_text!(format!("Signature is valid for {}", pubkey));
+        }
+        if !valid {
+            return Err(anyhow::anyhow!(
+                "Signature verification failed for {}",
+                pubkey
+            ));
+        }
+
+        Ok(())
+    }
+}
@@ -1,5 +1,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod accounts_list;
+pub mod bench;
 pub mod call;
+pub mod convert;
 pub mod deploy;
+pub mod inspect;
+pub mod keygen;
+pub mod message;
 pub mod show;
+pub mod squads;
@@ -3,7 +3,7 @@
 use {anyhow::Result, std::ffi::OsStr, std::process::exit};
 use {
     aqd_solana_contracts::{idl_from_json, print_idl_instruction_info},
-    aqd_utils::check_target_match,
+    aqd_utils::{check_target_match, ErrorKind, OutputFormat},
 };
 
 #[derive(Clone, Debug, clap::Args)]
@@ -12,7 +12,10 @@ use {
     about = "Show information about a Solana program's instructions given an IDL JSON file"
 )]
 pub struct SolanaShow {
-    #[clap(long, help = "Specifies the path of the IDL JSON file")]
+    #[clap(
+        long,
+        help = "Specifies the path or the HTTP(S) URL of the IDL JSON file, or - to read it from the standard input"
+    )]
     idl: String,
     #[clap(
         long,
@@ -20,8 +23,13 @@ pub struct SolanaShow {
                 If not specified, information about all instructions is shown"
     )]
     instruction: Option<String>,
-    #[clap(long, help = "Specifies whether to export the output in JSON format")]
-    output_json: bool,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
 }
 
 impl SolanaShow {
@@ -36,19 +44,19 @@ impl SolanaShow {
         let target_match = check_target_match("solana", None)
             .map_err(|e| anyhow::anyhow!("Failed to check current directory: {}", e))?;
         if !target_match {
-            exit(1);
+            exit(ErrorKind::Usage.exit_code());
         }
 
         // Parse command-line arguments
         let idl_json = self.idl.clone();
         let instruction = self.instruction.clone();
-        let output_json = self.output_json;
+        let output = self.output;
 
         // Get the IDL from the JSON file
         let idl = idl_from_json(OsStr::new(&idl_json))?;
 
         // Print information about the instruction
-        print_idl_instruction_info(&idl, instruction, output_json);
+        print_idl_instruction_info(&idl, instruction, output);
 
         Ok(())
     }
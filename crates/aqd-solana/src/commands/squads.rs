@@ -0,0 +1,160 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::Result,
+    serde_json::json,
+    solana_clap_v3_utils::input_validators::normalize_to_url_if_moniker,
+    solana_cli_config::{Config, CONFIG_FILE},
+    solana_client::rpc_client::RpcClient,
+    solana_sdk::{
+        commitment_config::CommitmentConfig,
+        pubkey::Pubkey,
+        signature::{Keypair, Signature},
+    },
+    std::{process::exit, str::FromStr},
+};
+use {
+    aqd_solana_contracts::{parse_keypair, rpc_client, SquadsMultisig},
+    aqd_utils::{check_target_match, is_quiet, print_text, ErrorKind, OutputFormat},
+};
+
+#[derive(Clone, Debug, clap::Args)]
+#[clap(
+    name = "squads-approve",
+    about = "Approve a transaction proposal of a Squads multisig"
+)]
+pub struct SolanaSquadsApprove {
+    #[clap(long, help = "Specifies the address of the Squads multisig")]
+    multisig: String,
+    #[clap(
+        long,
+        help = "Specifies the index of the transaction whose proposal to approve"
+    )]
+    transaction_index: u64,
+    #[clap(long, help = "Specifies a memo to attach to the approval")]
+    memo: Option<String>,
+    #[clap(
+        long,
+        env = "AQD_SOLANA_KEYPAIR",
+        hide_env_values = true,
+        help = "Specifies the keypair of the multisig member approving the proposal"
+    )]
+    member: Option<String>,
+    #[clap(
+        long,
+        env = "AQD_SOLANA_URL",
+        help = "Specifies the URL or moniker (mainnet-beta, devnet, testnet or localhost) of the Solana cluster. Defaults to the cluster of the configuration file"
+    )]
+    url: Option<String>,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
+}
+
+#[derive(Clone, Debug, clap::Args)]
+#[clap(
+    name = "squads-execute",
+    about = "Execute an approved transaction proposal of a Squads multisig"
+)]
+pub struct SolanaSquadsExecute {
+    #[clap(long, help = "Specifies the address of the Squads multisig")]
+    multisig: String,
+    #[clap(long, help = "Specifies the index of the transaction to execute")]
+    transaction_index: u64,
+    #[clap(
+        long,
+        env = "AQD_SOLANA_KEYPAIR",
+        hide_env_values = true,
+        help = "Specifies the keypair of the multisig member executing the transaction"
+    )]
+    member: Option<String>,
+    #[clap(
+        long,
+        env = "AQD_SOLANA_URL",
+        help = "Specifies the URL or moniker (mainnet-beta, devnet, testnet or localhost) of the Solana cluster. Defaults to the cluster of the configuration file"
+    )]
+    url: Option<String>,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
+}
+
+impl SolanaSquadsApprove {
+    /// Handle the Solana squads-approve command.
+    ///
+    /// This function approves the proposal of a vault transaction as a member of the multisig. The
+    /// member keypair defaults to the keypair of the configuration file.
+    pub fn handle(&self) -> Result<()> {
+        let (rpc_client, multisig, member) = setup(&self.multisig, &self.member, &self.url)?;
+        let signature = multisig.approve_proposal(
+            &rpc_client,
+            &member,
+            self.transaction_index,
+            self.memo.clone(),
+        )?;
+        print_signature("Proposal approved", &signature, self.output)
+    }
+}
+
+impl SolanaSquadsExecute {
+    /// Handle the Solana squads-execute command.
+    ///
+    /// This function executes an approved vault transaction as a member of the multisig. The
+    /// member keypair defaults to the keypair of the configuration file.
+    pub fn handle(&self) -> Result<()> {
+        let (rpc_client, multisig, member) = setup(&self.multisig, &self.member, &self.url)?;
+        let signature = multisig.execute_proposal(&rpc_client, &member, self.transaction_index)?;
+        print_signature("Transaction executed", &signature, self.output)
+    }
+}
+
+/// Check the current directory, and load the RPC client, the multisig and the member keypair.
+fn setup(
+    multisig: &str,
+    member: &Option<String>,
+    url: &Option<String>,
+) -> Result<(RpcClient, SquadsMultisig, Keypair)> {
+    // Make sure the command is run in the correct directory
+    // Fails if the command is run in a Solang Polkadot project directory
+    let target_match = check_target_match("solana", None)
+        .map_err(|e| anyhow::anyhow!("Failed to check current directory: {}", e))?;
+    if !target_match {
+        exit(ErrorKind::Usage.exit_code());
+    }
+
+    // Get the RPC URL and the default keypair from the config file, unless given
+    let config_file = CONFIG_FILE
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("Error loading config file"))?;
+    let cli_config = Config::load(config_file).unwrap_or_default();
+    let rpc_url = normalize_to_url_if_moniker(url.as_deref().unwrap_or(&cli_config.json_rpc_url));
+    let rpc_client = rpc_client(rpc_url, CommitmentConfig::confirmed());
+
+    let multisig = Pubkey::from_str(multisig)
+        .map_err(|e| anyhow::anyhow!("Invalid Squads multisig address: {}", e))?;
+    let member_path = member.clone().unwrap_or(cli_config.keypair_path);
+    let member = parse_keypair(&member_path)
+        .map_err(|e| anyhow::anyhow!("Error reading member keypair {}: {}", member_path, e))?;
+
+    Ok((rpc_client, SquadsMultisig::new(multisig, 0), member))
+}
+
+/// Print the signature of a Squads transaction.
+fn print_signature(message: &str, signature: &Signature, output: OutputFormat) -> Result<()> {
+    if is_quiet() {
+        print_text!(signature);
+    } else if output.is_structured() {
+        output.print(&json!({ "signature": signature.to_string() }))?;
+    } else {
+        print_text!(format!("{}. Signature: {}", message, signature));
+    }
+    Ok(())
+}
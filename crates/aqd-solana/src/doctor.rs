@@ -0,0 +1,276 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::anyhow,
+    aqd_solana_contracts::{
+        keystore_pubkey, parse_keypair, rpc_client, solana_cli_config, SolanaWorkspace,
+    },
+    aqd_utils::{CheckStatus, Diagnostic},
+    solana_clap_v3_utils::input_validators::normalize_to_url_if_moniker,
+    solana_cli_config::{Config, CONFIG_FILE},
+    solana_client::pubsub_client::PubsubClient,
+    solana_sdk::{
+        commitment_config::CommitmentConfig, native_token::lamports_to_sol, pubkey::Pubkey,
+        signer::Signer,
+    },
+    std::{env, fs, path::Path, time::Instant},
+};
+
+/// The section of the Solana checks.
+const SECTION: &str = "solana";
+
+/// The checks of the Solana environment, resolved as the Solana commands do: the configuration
+/// file of the Solana CLI, the workspace of the current directory, the payer keypair, and the RPC
+/// and websocket endpoints of the cluster.
+pub fn diagnostics() -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+
+    let cli_config = match CONFIG_FILE.as_ref() {
+        Some(path) if Path::new(path).is_file() => match Config::load(path) {
+            Ok(config) => {
+                diagnostics.push(Diagnostic::new(
+                    SECTION,
+                    "config file",
+                    CheckStatus::Ok,
+                    path,
+                ));
+                config
+            }
+            Err(e) => {
+                diagnostics.push(Diagnostic::new(
+                    SECTION,
+                    "config file",
+                    CheckStatus::Error,
+                    format!("Invalid {}: {}", path, e),
+                ));
+                Config::default()
+            }
+        },
+        Some(path) => {
+            diagnostics.push(Diagnostic::new(
+                SECTION,
+                "config file",
+                CheckStatus::Warning,
+                format!(
+                    "{} not found: the default cluster and keypair are used, see solana config set",
+                    path
+                ),
+            ));
+            Config::default()
+        }
+        None => {
+            diagnostics.push(Diagnostic::new(
+                SECTION,
+                "config file",
+                CheckStatus::Error,
+                "The path of the configuration file cannot be determined",
+            ));
+            Config::default()
+        }
+    };
+
+    let workspace = match SolanaWorkspace::discover() {
+        Ok(Some(workspace)) => {
+            diagnostics.push(Diagnostic::new(
+                SECTION,
+                "workspace",
+                CheckStatus::Ok,
+                format!(
+                    "{:?} workspace at {} ({} programs)",
+                    workspace.kind,
+                    workspace.root.display(),
+                    workspace.programs.len()
+                ),
+            ));
+            for program in &workspace.programs {
+                diagnostics.push(program_diagnostic(
+                    &program.name,
+                    &program.idl_path,
+                    &program.program_path,
+                ));
+            }
+            Some(workspace)
+        }
+        Ok(None) => {
+            diagnostics.push(Diagnostic::new(
+                SECTION,
+                "workspace",
+                CheckStatus::Info,
+                "No Anchor.toml or solang.toml for solana in the current directory",
+            ));
+            None
+        }
+        Err(e) => {
+            diagnostics.push(Diagnostic::new(
+                SECTION,
+                "workspace",
+                CheckStatus::Error,
+                format!("{:#}", e),
+            ));
+            None
+        }
+    };
+
+    let config_file_exists = solana_cli_config().is_some();
+    let (keypair_source, keypair) = match env::var("AQD_SOLANA_KEYPAIR") {
+        Ok(keypair) => ("AQD_SOLANA_KEYPAIR", keypair),
+        Err(_) => match workspace.as_ref().and_then(|w| w.wallet.clone()) {
+            Some(wallet) if !config_file_exists => ("the workspace wallet", wallet),
+            _ => ("the config file", cli_config.keypair_path.clone()),
+        },
+    };
+    let payer = keypair_diagnostic(keypair_source, &keypair, &mut diagnostics);
+
+    let rpc_url = env::var("AQD_SOLANA_URL")
+        .ok()
+        .map(|url| normalize_to_url_if_moniker(&url))
+        .or_else(|| {
+            workspace
+                .as_ref()
+                .filter(|_| !config_file_exists)
+                .and_then(|w| w.rpc_url())
+        })
+        .unwrap_or_else(|| normalize_to_url_if_moniker(&cli_config.json_rpc_url));
+    let rpc_client = rpc_client(rpc_url.clone(), CommitmentConfig::confirmed());
+    let start = Instant::now();
+    let rpc_reachable = match rpc_client.get_version() {
+        Ok(version) => {
+            diagnostics.push(Diagnostic::new(
+                SECTION,
+                "rpc",
+                CheckStatus::Ok,
+                format!(
+                    "{} (solana-core {}, {} ms)",
+                    rpc_url,
+                    version.solana_core,
+                    start.elapsed().as_millis()
+                ),
+            ));
+            true
+        }
+        Err(e) => {
+            diagnostics.push(Diagnostic::new(
+                SECTION,
+                "rpc",
+                CheckStatus::Error,
+                format!("Failed to reach {}: {}", rpc_url, e),
+            ));
+            false
+        }
+    };
+    if let (true, Some(payer)) = (rpc_reachable, payer) {
+        diagnostics.push(Diagnostic::from_result(
+            SECTION,
+            "payer balance",
+            rpc_client
+                .get_balance(&payer)
+                .map(|lamports| format!("{} SOL", lamports_to_sol(lamports)))
+                .map_err(|e| anyhow!("Failed to get the balance of {}: {}", payer, e)),
+        ));
+    }
+
+    let ws_url = match cli_config.websocket_url.as_str() {
+        "" => Config::compute_websocket_url(&rpc_url),
+        ws_url => ws_url.to_string(),
+    };
+    diagnostics.push(match PubsubClient::slot_subscribe(&ws_url) {
+        Ok((mut subscription, _)) => {
+            let _ = subscription.shutdown();
+            Diagnostic::new(SECTION, "websocket", CheckStatus::Ok, ws_url)
+        }
+        Err(e) => Diagnostic::new(
+            SECTION,
+            "websocket",
+            CheckStatus::Error,
+            format!(
+                "Failed to subscribe to {}: {}. aqd solana deploy confirms its transactions on it",
+                ws_url, e
+            ),
+        ),
+    });
+    diagnostics
+}
+
+/// Check that the payer keypair can be read, and that its file, if any, is not readable by the
+/// other users. Returns the public key of the payer, if valid.
+fn keypair_diagnostic(
+    source: &str,
+    keypair: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Option<Pubkey> {
+    // A keystore is not unlocked, so that the checks never prompt for a password
+    if let Some(pubkey) = fs::read_to_string(keypair)
+        .ok()
+        .and_then(|content| keystore_pubkey(&content))
+    {
+        diagnostics.push(Diagnostic::new(
+            SECTION,
+            "keypair",
+            CheckStatus::Ok,
+            format!(
+                "{} ({}, encrypted keystore, from {})",
+                pubkey, keypair, source
+            ),
+        ));
+        return Some(pubkey);
+    }
+    let payer = match parse_keypair(keypair) {
+        Ok(payer) => payer.pubkey(),
+        Err(e) => {
+            diagnostics.push(Diagnostic::new(
+                SECTION,
+                "keypair",
+                CheckStatus::Error,
+                format!("Invalid keypair from {}: {:#}", source, e),
+            ));
+            return None;
+        }
+    };
+    let path = Path::new(keypair);
+    let detail = match path.is_file() {
+        true => format!("{} ({}, from {})", payer, keypair, source),
+        false => format!("{} (from {})", payer, source),
+    };
+    diagnostics.push(Diagnostic::new(SECTION, "keypair", CheckStatus::Ok, detail));
+
+    #[cfg(unix)]
+    if let Ok(metadata) = path.metadata() {
+        use std::os::unix::fs::PermissionsExt;
+        if metadata.permissions().mode() & 0o077 != 0 {
+            diagnostics.push(Diagnostic::new(
+                SECTION,
+                "keypair permissions",
+                CheckStatus::Warning,
+                format!(
+                    "{} is readable by other users: restrict it with chmod 600",
+                    keypair
+                ),
+            ));
+        }
+    }
+    Some(payer)
+}
+
+/// Check that the IDL and the binary of a workspace program are built.
+fn program_diagnostic(name: &str, idl_path: &Path, program_path: &Path) -> Diagnostic {
+    let missing: Vec<String> = [idl_path, program_path]
+        .iter()
+        .filter(|path| !path.is_file())
+        .map(|path| path.display().to_string())
+        .collect();
+    let check = format!("program {}", name);
+    match missing.is_empty() {
+        true => Diagnostic::new(
+            SECTION,
+            &check,
+            CheckStatus::Ok,
+            idl_path.display().to_string(),
+        ),
+        false => Diagnostic::new(
+            SECTION,
+            &check,
+            CheckStatus::Warning,
+            format!("Not built yet, missing {}", missing.join(" and ")),
+        ),
+    }
+}
@@ -1,7 +1,22 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod commands;
+mod doctor;
 mod solana_action;
+mod wallet;
 
-pub use commands::{call::SolanaCall, deploy::SolanaDeploy, show::SolanaShow};
+pub use commands::{
+    accounts_list::SolanaAccountsList,
+    bench::SolanaBench,
+    call::SolanaCall,
+    convert::{SolanaKeypairPubkey, SolanaPubkey},
+    deploy::SolanaDeploy,
+    inspect::SolanaInspect,
+    keygen::SolanaKeygen,
+    message::{SolanaSignMessage, SolanaVerifyMessage},
+    show::SolanaShow,
+    squads::{SolanaSquadsApprove, SolanaSquadsExecute},
+};
+pub use doctor::diagnostics;
 pub use solana_action::SolanaAction;
+pub use wallet::{default_wallet, parse_address, wallet_address, wallet_balance};
@@ -1,7 +1,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use {
-    crate::{SolanaCall, SolanaDeploy, SolanaShow},
+    crate::{
+        SolanaAccountsList, SolanaBench, SolanaCall, SolanaDeploy, SolanaInspect, SolanaKeygen,
+        SolanaShow, SolanaSignMessage, SolanaSquadsApprove, SolanaSquadsExecute,
+        SolanaVerifyMessage,
+    },
     clap::Subcommand,
 };
 
@@ -10,5 +14,13 @@ use {
 pub enum SolanaAction {
     Deploy(SolanaDeploy),
     Call(SolanaCall),
+    Bench(SolanaBench),
     Show(SolanaShow),
+    AccountsList(SolanaAccountsList),
+    Inspect(SolanaInspect),
+    SquadsApprove(SolanaSquadsApprove),
+    SquadsExecute(SolanaSquadsExecute),
+    SignMessage(SolanaSignMessage),
+    VerifyMessage(SolanaVerifyMessage),
+    Keygen(SolanaKeygen),
 }
@@ -0,0 +1,105 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    aqd_solana_contracts::{
+        keystore_pubkey, parse_keypair, parse_pubkey, resolve_rpc_url, resolve_wallet, rpc_client,
+        solana_cli_config, SolanaWorkspace,
+    },
+    aqd_utils::WalletKey,
+    solana_clap_v3_utils::input_validators::normalize_to_url_if_moniker,
+    solana_cli_config::{Config, CONFIG_FILE},
+    solana_sdk::{
+        commitment_config::CommitmentConfig, native_token::lamports_to_sol, signer::Signer,
+    },
+    std::{env, fs, path::Path},
+};
+
+/// The chain of the Solana wallets.
+const CHAIN: &str = "solana";
+
+/// The configuration file of the Solana CLI, or the default configuration if there is none.
+fn cli_config() -> Config {
+    CONFIG_FILE
+        .as_ref()
+        .and_then(|path| Config::load(path).ok())
+        .unwrap_or_default()
+}
+
+/// The key the Solana commands sign with by default, resolved as they do: the
+/// `AQD_SOLANA_KEYPAIR` environment variable, then the keypair of the configuration file of the
+/// Solana CLI if it exists, then the wallet of the workspace.
+///
+/// A keystore is not unlocked: its public key is stored in clear.
+pub fn default_wallet() -> WalletKey {
+    let workspace = SolanaWorkspace::discover().ok().flatten();
+    let keypair = resolve_wallet(
+        env::var("AQD_SOLANA_KEYPAIR").ok(),
+        solana_cli_config().as_ref(),
+        workspace.as_ref(),
+    )
+    .unwrap_or_else(|| cli_config().keypair_path);
+    // A keypair given inline in the environment is a secret, and is not printed
+    let source = match Path::new(&keypair).is_file() {
+        true => keypair.clone(),
+        false => "AQD_SOLANA_KEYPAIR".to_string(),
+    };
+    let (address, error) = match wallet_address(&keypair) {
+        Ok(address) => (Some(address), None),
+        Err(e) => (None, Some(format!("{:#}", e))),
+    };
+    WalletKey {
+        chain: CHAIN.to_string(),
+        address,
+        source: Some(source),
+        default: true,
+        error,
+        ..Default::default()
+    }
+}
+
+/// The address of a keypair: a keypair file or keystore, or any other source of
+/// [`parse_keypair`].
+///
+/// The public key of a keystore is read without unlocking it.
+pub fn wallet_address(keypair: &str) -> Result<String> {
+    if let Some(pubkey) = fs::read_to_string(keypair)
+        .ok()
+        .and_then(|content| keystore_pubkey(&content))
+    {
+        return Ok(pubkey.to_string());
+    }
+    Ok(parse_keypair(keypair)?.pubkey().to_string())
+}
+
+/// Validate a Solana address, base58 encoded or as 32 hex encoded bytes with a `0x` prefix, and
+/// return it base58 encoded.
+pub fn parse_address(address: &str) -> Result<String> {
+    Ok(parse_pubkey(address)?.to_string())
+}
+
+/// The balance of an address, in SOL, on the cluster of the Solana commands: the `AQD_SOLANA_URL`
+/// environment variable, then the cluster of the configuration file of the Solana CLI if it exists,
+/// then the cluster of the workspace.
+pub fn wallet_balance(address: &str) -> Result<String> {
+    let pubkey = parse_pubkey(address)?;
+    let workspace = SolanaWorkspace::discover().ok().flatten();
+    let rpc_url = resolve_rpc_url(
+        env::var("AQD_SOLANA_URL").ok(),
+        solana_cli_config().as_ref(),
+        workspace.as_ref(),
+    )
+    .map(|url| normalize_to_url_if_moniker(&url))
+    .unwrap_or_else(|| normalize_to_url_if_moniker(&cli_config().json_rpc_url));
+    let lamports = rpc_client(rpc_url.clone(), CommitmentConfig::confirmed())
+        .get_balance(&pubkey)
+        .map_err(|e| {
+            anyhow!(
+                "Failed to get the balance of {} from {}: {}",
+                pubkey,
+                rpc_url,
+                e
+            )
+        })?;
+    Ok(format!("{} SOL", lamports_to_sol(lamports)))
+}
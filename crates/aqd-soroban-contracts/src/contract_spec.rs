@@ -0,0 +1,299 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    serde_json::{json, Map, Value},
+    std::{fs, path::Path},
+    stellar_strkey::Strkey,
+    stellar_xdr::curr::{
+        AccountId, Hash, Int128Parts, PublicKey, ScAddress, ScBytes, ScSpecEntry, ScSpecFunctionV0,
+        ScSpecTypeDef, ScString, ScSymbol, ScVal, ScVec, UInt128Parts, Uint256,
+    },
+    tracing::{debug, trace},
+};
+
+/// The specification of a Soroban contract, read from the `contractspecv0` custom section of its
+/// WASM file.
+///
+/// The specification describes the functions of the contract, with the types of their inputs
+/// and outputs. It plays the role of the IDL of Solana programs.
+pub struct ContractSpec {
+    entries: Vec<ScSpecEntry>,
+}
+
+impl ContractSpec {
+    /// Read the specification of a contract from the bytes of its WASM file.
+    pub fn from_wasm(wasm: &[u8]) -> Result<Self> {
+        let entries = soroban_spec::read::from_wasm(wasm)
+            .map_err(|e| anyhow!("Failed to read the contract spec from the WASM file: {}", e))?;
+        Ok(ContractSpec { entries })
+    }
+
+    /// Read the specification of a contract from its WASM file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let wasm = fs::read(path)
+            .map_err(|e| anyhow!("Failed to read WASM file {}: {}", path.display(), e))?;
+        Self::from_wasm(&wasm)
+    }
+
+    /// Get the functions of the contract.
+    pub fn functions(&self) -> Vec<&ScSpecFunctionV0> {
+        self.entries
+            .iter()
+            .filter_map(|entry| match entry {
+                ScSpecEntry::FunctionV0(function) => Some(function),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Get the function of the contract with the given name.
+    pub fn function(&self, name: &str) -> Result<&ScSpecFunctionV0> {
+        self.functions()
+            .into_iter()
+            .find(|function| function.name.0.to_utf8_string_lossy() == name)
+            .ok_or_else(|| anyhow!("Function {} not found in the contract spec", name))
+    }
+
+    /// Encode the arguments of a function call, given as strings, into Soroban values.
+    ///
+    /// Each argument is parsed according to the type of the corresponding input of the function
+    /// (see [`parse_sc_val`]).
+    pub fn encode_args(&self, function: &ScSpecFunctionV0, args: &[String]) -> Result<Vec<ScVal>> {
+        let name = function.name.0.to_utf8_string_lossy();
+        if function.inputs.len() != args.len() {
+            return Err(anyhow!(
+                "Function {} expects {} arguments, got {}",
+                name,
+                function.inputs.len(),
+                args.len()
+            ));
+        }
+
+        let values = function
+            .inputs
+            .iter()
+            .zip(args)
+            .map(|(input, arg)| {
+                trace!(
+                    input = %input.name.to_utf8_string_lossy(),
+                    type_ = %type_name(&input.type_),
+                    arg,
+                    "Encoding argument"
+                );
+                parse_sc_val(arg, &input.type_).map_err(|e| {
+                    anyhow!(
+                        "Invalid argument {}: {}",
+                        input.name.to_utf8_string_lossy(),
+                        e
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        debug!(function = %name, ?values, "Encoded the arguments");
+
+        Ok(values)
+    }
+}
+
+/// Get the name of a type of the contract spec, as written in the Rust SDK (e.g. `vec<u32>`).
+pub fn type_name(type_: &ScSpecTypeDef) -> String {
+    match type_ {
+        ScSpecTypeDef::Val => "val".to_string(),
+        ScSpecTypeDef::Bool => "bool".to_string(),
+        ScSpecTypeDef::Void => "void".to_string(),
+        ScSpecTypeDef::Error => "error".to_string(),
+        ScSpecTypeDef::U32 => "u32".to_string(),
+        ScSpecTypeDef::I32 => "i32".to_string(),
+        ScSpecTypeDef::U64 => "u64".to_string(),
+        ScSpecTypeDef::I64 => "i64".to_string(),
+        ScSpecTypeDef::Timepoint => "timepoint".to_string(),
+        ScSpecTypeDef::Duration => "duration".to_string(),
+        ScSpecTypeDef::U128 => "u128".to_string(),
+        ScSpecTypeDef::I128 => "i128".to_string(),
+        ScSpecTypeDef::U256 => "u256".to_string(),
+        ScSpecTypeDef::I256 => "i256".to_string(),
+        ScSpecTypeDef::Bytes => "bytes".to_string(),
+        ScSpecTypeDef::String => "string".to_string(),
+        ScSpecTypeDef::Symbol => "symbol".to_string(),
+        ScSpecTypeDef::Address => "address".to_string(),
+        ScSpecTypeDef::Option(option) => format!("option<{}>", type_name(&option.value_type)),
+        ScSpecTypeDef::Result(result) => format!(
+            "result<{}, {}>",
+            type_name(&result.ok_type),
+            type_name(&result.error_type)
+        ),
+        ScSpecTypeDef::Vec(vec) => format!("vec<{}>", type_name(&vec.element_type)),
+        ScSpecTypeDef::Map(map) => format!(
+            "map<{}, {}>",
+            type_name(&map.key_type),
+            type_name(&map.value_type)
+        ),
+        ScSpecTypeDef::Tuple(tuple) => format!(
+            "tuple<{}>",
+            tuple
+                .value_types
+                .iter()
+                .map(type_name)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        ScSpecTypeDef::BytesN(bytes) => format!("bytesn<{}>", bytes.n),
+        ScSpecTypeDef::Udt(udt) => udt.name.to_utf8_string_lossy(),
+    }
+}
+
+/// Parse a value given as a string into a Soroban value of the given type.
+///
+/// - Booleans are `true` or `false`, and integers are decimal numbers.
+/// - Strings and symbols are used verbatim.
+/// - Addresses are account (G...) or contract (C...) strkeys.
+/// - Bytes are hex strings, with or without a `0x` prefix.
+/// - Vectors are comma-separated lists of values (e.g. `1,2,3,4`).
+/// - Options are `none` or a value of the inner type.
+///
+/// # Errors
+///
+/// This function returns an error if the value does not match the type, or if the type is not
+/// supported (maps, tuples and user-defined types).
+pub fn parse_sc_val(value: &str, type_: &ScSpecTypeDef) -> Result<ScVal> {
+    let invalid =
+        |e: &dyn std::fmt::Display| anyhow!("Invalid {} {}: {}", type_name(type_), value, e);
+
+    let sc_val = match type_ {
+        ScSpecTypeDef::Void => ScVal::Void,
+        ScSpecTypeDef::Bool => ScVal::Bool(value.parse().map_err(|e| invalid(&e))?),
+        ScSpecTypeDef::U32 => ScVal::U32(value.parse().map_err(|e| invalid(&e))?),
+        ScSpecTypeDef::I32 => ScVal::I32(value.parse().map_err(|e| invalid(&e))?),
+        ScSpecTypeDef::U64 => ScVal::U64(value.parse().map_err(|e| invalid(&e))?),
+        ScSpecTypeDef::I64 => ScVal::I64(value.parse().map_err(|e| invalid(&e))?),
+        ScSpecTypeDef::U128 => {
+            let value: u128 = value.parse().map_err(|e| invalid(&e))?;
+            ScVal::U128(UInt128Parts {
+                hi: (value >> 64) as u64,
+                lo: value as u64,
+            })
+        }
+        ScSpecTypeDef::I128 => {
+            let value: i128 = value.parse().map_err(|e| invalid(&e))?;
+            ScVal::I128(Int128Parts {
+                hi: (value >> 64) as i64,
+                lo: value as u64,
+            })
+        }
+        ScSpecTypeDef::String => ScVal::String(ScString(
+            value.to_string().try_into().map_err(|e| invalid(&e))?,
+        )),
+        ScSpecTypeDef::Symbol => ScVal::Symbol(ScSymbol(
+            value.to_string().try_into().map_err(|e| invalid(&e))?,
+        )),
+        ScSpecTypeDef::Address => ScVal::Address(parse_address(value)?),
+        ScSpecTypeDef::Bytes => {
+            let bytes = hex::decode(value.trim_start_matches("0x")).map_err(|e| invalid(&e))?;
+            ScVal::Bytes(ScBytes(bytes.try_into().map_err(|e| invalid(&e))?))
+        }
+        ScSpecTypeDef::BytesN(bytes_n) => {
+            let bytes = hex::decode(value.trim_start_matches("0x")).map_err(|e| invalid(&e))?;
+            if bytes.len() != bytes_n.n as usize {
+                return Err(anyhow!(
+                    "Invalid bytesn<{}> {}: expected {} bytes, got {}",
+                    bytes_n.n,
+                    value,
+                    bytes_n.n,
+                    bytes.len()
+                ));
+            }
+            ScVal::Bytes(ScBytes(bytes.try_into().map_err(|e| invalid(&e))?))
+        }
+        ScSpecTypeDef::Vec(vec) => {
+            let elements = if value.is_empty() {
+                vec![]
+            } else {
+                value
+                    .split(',')
+                    .map(|element| parse_sc_val(element.trim(), &vec.element_type))
+                    .collect::<Result<Vec<_>>>()?
+            };
+            ScVal::Vec(Some(ScVec(elements.try_into().map_err(|e| invalid(&e))?)))
+        }
+        ScSpecTypeDef::Option(option) => match value {
+            "none" => ScVal::Void,
+            _ => parse_sc_val(value, &option.value_type)?,
+        },
+        _ => {
+            return Err(anyhow!(
+                "Arguments of type {} are not supported",
+                type_name(type_)
+            ))
+        }
+    };
+
+    Ok(sc_val)
+}
+
+/// Parse an account (G...) or contract (C...) address.
+pub fn parse_address(address: &str) -> Result<ScAddress> {
+    match Strkey::from_string(address) {
+        Ok(Strkey::PublicKeyEd25519(public_key)) => Ok(ScAddress::Account(AccountId(
+            PublicKey::PublicKeyTypeEd25519(Uint256(public_key.0)),
+        ))),
+        Ok(Strkey::Contract(contract)) => Ok(ScAddress::Contract(Hash(contract.0))),
+        _ => Err(anyhow!(
+            "Invalid address {}: expected an account (G...) or a contract (C...) address",
+            address
+        )),
+    }
+}
+
+/// Encode an address as a strkey: G... for accounts and C... for contracts.
+pub fn address_to_string(address: &ScAddress) -> String {
+    match address {
+        ScAddress::Account(AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(public_key)))) => {
+            stellar_strkey::ed25519::PublicKey(*public_key).to_string()
+        }
+        ScAddress::Contract(Hash(contract)) => stellar_strkey::Contract(*contract).to_string(),
+    }
+}
+
+/// Convert a Soroban value into JSON, to print the results of the contract calls.
+///
+/// 128-bit integers are converted into strings, as they do not fit in JSON numbers. Bytes are
+/// converted into hex strings and addresses into strkeys.
+pub fn sc_val_to_json(value: &ScVal) -> Value {
+    match value {
+        ScVal::Void => Value::Null,
+        ScVal::Bool(value) => json!(value),
+        ScVal::U32(value) => json!(value),
+        ScVal::I32(value) => json!(value),
+        ScVal::U64(value) => json!(value),
+        ScVal::I64(value) => json!(value),
+        ScVal::Timepoint(value) => json!(value.0),
+        ScVal::Duration(value) => json!(value.0),
+        ScVal::U128(UInt128Parts { hi, lo }) => {
+            json!((((*hi as u128) << 64) | *lo as u128).to_string())
+        }
+        ScVal::I128(Int128Parts { hi, lo }) => {
+            json!((((*hi as i128) << 64) | *lo as i128).to_string())
+        }
+        ScVal::Bytes(bytes) => json!(hex::encode(bytes.0.as_slice())),
+        ScVal::String(string) => json!(string.0.to_utf8_string_lossy()),
+        ScVal::Symbol(symbol) => json!(symbol.0.to_utf8_string_lossy()),
+        ScVal::Address(address) => json!(address_to_string(address)),
+        ScVal::Vec(Some(vec)) => Value::Array(vec.0.iter().map(sc_val_to_json).collect()),
+        ScVal::Vec(None) => Value::Array(vec![]),
+        ScVal::Map(Some(map)) => {
+            let mut object = Map::new();
+            for entry in map.0.iter() {
+                let key = match sc_val_to_json(&entry.key) {
+                    Value::String(key) => key,
+                    key => key.to_string(),
+                };
+                object.insert(key, sc_val_to_json(&entry.val));
+            }
+            Value::Object(object)
+        }
+        ScVal::Map(None) => Value::Object(Map::new()),
+        value => json!(format!("{:?}", value)),
+    }
+}
@@ -0,0 +1,46 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    aqd_utils::register_secret,
+    ed25519_dalek::SigningKey,
+    std::{fs, path::Path},
+};
+
+/// Parse the secret key of a Stellar account.
+///
+/// The following formats are accepted:
+///
+/// - A Stellar secret key, encoded as a strkey starting with `S` (e.g. `SC36...`), as exported
+///   by the `soroban` CLI and the Stellar wallets.
+///
+/// - The path of a file containing such a secret key.
+///
+/// # Errors
+///
+/// This function returns an error if the source is neither a secret key nor a file containing
+/// one.
+///
+/// The secret key is registered as a secret to be redacted from the output.
+pub fn parse_secret_key(source: &str) -> Result<SigningKey> {
+    if Path::new(source).is_file() {
+        let content = fs::read_to_string(source)
+            .map_err(|e| anyhow!("Failed to read secret key file {}: {}", source, e))?;
+        return parse_inline_secret_key(content.trim())
+            .map_err(|e| anyhow!("Invalid secret key file {}: {}", source, e));
+    }
+    parse_inline_secret_key(source.trim())
+}
+
+/// Parse a secret key given inline, as a strkey.
+fn parse_inline_secret_key(source: &str) -> Result<SigningKey> {
+    register_secret(source);
+    let secret_key = stellar_strkey::ed25519::PrivateKey::from_string(source)
+        .map_err(|_| anyhow!("Not a valid Stellar secret key (S...) or secret key file"))?;
+    Ok(SigningKey::from_bytes(&secret_key.0))
+}
+
+/// Encode the public key of a signing key as a Stellar account address (G...).
+pub fn account_address(key: &SigningKey) -> String {
+    stellar_strkey::ed25519::PublicKey(key.verifying_key().to_bytes()).to_string()
+}
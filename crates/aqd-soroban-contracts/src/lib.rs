@@ -0,0 +1,19 @@
+// SPDX-License-Identifier: Apache-2.0
+
+mod contract_spec;
+mod keypair;
+mod printing_utils;
+mod soroban_deploy;
+mod soroban_rpc;
+mod soroban_transaction;
+
+pub use {
+    contract_spec::{
+        address_to_string, parse_address, parse_sc_val, sc_val_to_json, type_name, ContractSpec,
+    },
+    keypair::{account_address, parse_secret_key},
+    printing_utils::{print_contract_spec, print_invocation_result},
+    soroban_deploy::{deploy_contract, SorobanDeployment},
+    soroban_rpc::{normalize_to_url_if_moniker, SimulationResult, SorobanRpc},
+    soroban_transaction::{invoke_contract_function, SorobanTransaction, SorobanTransactionResult},
+};
@@ -0,0 +1,113 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::contract_spec::{sc_val_to_json, type_name, ContractSpec},
+    anyhow::Result,
+    aqd_utils::{print_key_value, print_subtitle, print_title, print_value, OutputFormat},
+    serde_json::{json, Value},
+    stellar_xdr::curr::{ScSpecFunctionV0, ScVal},
+};
+
+/// Prints information about the functions of a Soroban contract spec.
+///
+/// If a function name is provided, only that function is printed. Otherwise, all the functions
+/// of the contract are printed. The information includes the function name, documentation,
+/// inputs and outputs. The output format can be human-readable, JSON, YAML or a table.
+///
+/// # Arguments
+///
+/// * `spec`: The [`ContractSpec`] of the contract.
+/// * `function_name`: An optional name of the function to print details for.
+/// * `output`: The [`OutputFormat`] of the information.
+pub fn print_contract_spec(
+    spec: &ContractSpec,
+    function_name: Option<String>,
+    output: OutputFormat,
+) -> Result<()> {
+    let functions = match function_name {
+        Some(function_name) => vec![spec.function(&function_name)?],
+        None => spec.functions(),
+    };
+
+    if output.is_structured() {
+        // Print a single document with all the functions
+        let functions: Vec<Value> = functions.into_iter().map(function_to_json).collect();
+        return output.print(&functions);
+    }
+
+    for function in functions {
+        print_title!("Function name");
+        print_value!(function.name.0.to_utf8_string_lossy());
+
+        print_title!("Function docs");
+        let docs = function.doc.to_utf8_string_lossy();
+        if docs.is_empty() {
+            print_value!("No documentation");
+        } else {
+            print_value!(docs);
+        }
+
+        print_title!("Inputs");
+        if function.inputs.is_empty() {
+            print_value!("No inputs");
+        }
+        for (i, input) in function.inputs.iter().enumerate() {
+            print_subtitle!(format!("Input {}", i + 1));
+            print_key_value!("Name: ", input.name.to_utf8_string_lossy());
+            print_key_value!("Type: ", type_name(&input.type_));
+        }
+
+        print_title!("Outputs");
+        if function.outputs.is_empty() {
+            print_value!("No outputs");
+        }
+        for output in function.outputs.iter() {
+            print_value!(type_name(output));
+        }
+    }
+
+    Ok(())
+}
+
+/// Convert a function of the contract spec into JSON.
+fn function_to_json(function: &ScSpecFunctionV0) -> Value {
+    json!({
+        "name": function.name.0.to_utf8_string_lossy(),
+        "docs": function.doc.to_utf8_string_lossy(),
+        "inputs": function.inputs.iter().map(|input| json!({
+            "name": input.name.to_utf8_string_lossy(),
+            "type": type_name(&input.type_),
+        })).collect::<Vec<_>>(),
+        "outputs": function.outputs.iter().map(type_name).collect::<Vec<_>>(),
+    })
+}
+
+/// Prints the result of a contract function call.
+///
+/// # Arguments
+///
+/// * `hash`: The hash of the transaction, or `None` for a simulated call.
+/// * `return_value`: The return value of the function, if any.
+/// * `output`: The [`OutputFormat`] of the result.
+pub fn print_invocation_result(
+    hash: Option<&str>,
+    return_value: Option<&ScVal>,
+    output: OutputFormat,
+) -> Result<()> {
+    let return_value = return_value.map(sc_val_to_json).unwrap_or(Value::Null);
+
+    if output.is_structured() {
+        return output.print(&json!({
+            "transaction": hash,
+            "return_value": return_value,
+        }));
+    }
+
+    print_title!("Result");
+    if let Some(hash) = hash {
+        print_key_value!("Transaction: ", hash);
+    }
+    print_key_value!("Return value: ", return_value);
+
+    Ok(())
+}
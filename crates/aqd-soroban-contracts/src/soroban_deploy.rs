@@ -0,0 +1,74 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::{contract_spec::address_to_string, soroban_transaction::SorobanTransaction},
+    anyhow::{anyhow, Result},
+    sha2::{Digest, Sha256},
+    stellar_xdr::curr::{
+        ContractExecutable, ContractIdPreimage, ContractIdPreimageFromAddress, CreateContractArgs,
+        Hash, HostFunction, ScVal, Uint256,
+    },
+    tracing::info,
+};
+
+/// The result of the deployment of a Soroban contract.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SorobanDeployment {
+    /// The hex encoded SHA-256 hash of the uploaded WASM.
+    pub wasm_hash: String,
+    /// The address of the created contract (C...).
+    pub contract_id: String,
+}
+
+/// Deploy a Soroban contract to the blockchain.
+///
+/// The deployment is made of two transactions signed by the source account: the upload of the
+/// WASM, then the creation of a contract instance of the uploaded WASM. Uploading a WASM that is
+/// already stored on chain is allowed.
+///
+/// # Arguments
+///
+/// * `transaction`: The context of the transactions.
+/// * `wasm`: The bytes of the WASM file of the contract.
+/// * `salt`: The salt from which the contract address is derived, along with the source account.
+///   A random salt is used by default.
+///
+/// # Returns
+///
+/// A `Result` containing the hash of the WASM and the address of the contract.
+pub fn deploy_contract(
+    transaction: &SorobanTransaction,
+    wasm: &[u8],
+    salt: Option<[u8; 32]>,
+) -> Result<SorobanDeployment> {
+    // Upload the WASM
+    let wasm_hash: [u8; 32] = Sha256::digest(wasm).into();
+    info!(wasm_hash = %hex::encode(wasm_hash), "Uploading the contract WASM");
+    transaction.submit(HostFunction::UploadContractWasm(wasm.to_vec().try_into()?))?;
+
+    // Create the contract
+    let salt = salt.unwrap_or_else(rand::random);
+    info!(salt = %hex::encode(salt), "Creating the contract");
+    let result = transaction.submit(HostFunction::CreateContract(CreateContractArgs {
+        contract_id_preimage: ContractIdPreimage::Address(ContractIdPreimageFromAddress {
+            address: transaction.source_sc_address(),
+            salt: Uint256(salt),
+        }),
+        executable: ContractExecutable::Wasm(Hash(wasm_hash)),
+    }))?;
+
+    let contract_id = match result.return_value {
+        Some(ScVal::Address(address)) => address_to_string(&address),
+        _ => {
+            return Err(anyhow!(
+                "The contract creation transaction {} did not return the contract address",
+                result.hash
+            ))
+        }
+    };
+
+    Ok(SorobanDeployment {
+        wasm_hash: hex::encode(wasm_hash),
+        contract_id,
+    })
+}
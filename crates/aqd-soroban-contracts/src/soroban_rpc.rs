@@ -0,0 +1,282 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    aqd_utils::{
+        cassette_mode, emit_progress, is_replaying, record_interaction, replay_interaction,
+        ErrorKind, Interaction, ProgressEvent, ProgressStage,
+    },
+    serde_json::{json, Value},
+    std::{thread::sleep, time::Duration},
+    stellar_xdr::curr::{
+        AccountId, LedgerEntryData, LedgerKey, LedgerKeyAccount, Limits, PublicKey, ReadXdr, ScVal,
+        SorobanAuthorizationEntry, SorobanTransactionData, TransactionEnvelope, TransactionMeta,
+        Uint256, WriteXdr,
+    },
+    tracing::{debug, info},
+};
+
+/// The number of times the status of a submitted transaction is polled, once per second, before
+/// giving up.
+const TRANSACTION_POLL_ATTEMPTS: u32 = 30;
+
+/// Resolve the Soroban RPC URL from a URL or a moniker (`testnet`, `futurenet` or `localhost`).
+///
+/// `localhost` is the RPC endpoint of the `stellar/quickstart` docker image.
+pub fn normalize_to_url_if_moniker(url_or_moniker: &str) -> String {
+    match url_or_moniker {
+        "testnet" => "https://soroban-testnet.stellar.org",
+        "futurenet" => "https://rpc-futurenet.stellar.org",
+        "localhost" | "local" => "http://localhost:8000/soroban/rpc",
+        url => url,
+    }
+    .to_string()
+}
+
+/// The result of the simulation of a transaction by the Soroban RPC server.
+///
+/// Its resources, fee and authorizations are used to assemble the transaction that is submitted.
+#[derive(Debug)]
+pub struct SimulationResult {
+    pub transaction_data: SorobanTransactionData,
+    pub min_resource_fee: u32,
+    pub auth: Vec<SorobanAuthorizationEntry>,
+    pub return_value: Option<ScVal>,
+}
+
+/// A client of the JSON-RPC API of a Soroban RPC server.
+pub struct SorobanRpc {
+    url: String,
+    client: reqwest::blocking::Client,
+}
+
+impl SorobanRpc {
+    /// Create a client of the Soroban RPC server at the given URL.
+    pub fn new<T: Into<String>>(url: T) -> Self {
+        SorobanRpc {
+            url: url.into(),
+            client: reqwest::blocking::Client::new(),
+        }
+    }
+
+    /// The URL of the Soroban RPC server.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Send a JSON-RPC request and return its result.
+    ///
+    /// With `--record`, the request and its result are recorded to the cassette, and with
+    /// `--replay`, the result is replayed from the cassette without sending the request.
+    fn request(&self, method: &str, params: Value) -> Result<Value> {
+        if is_replaying() {
+            return replay_interaction("soroban", method, &params)?.response();
+        }
+        let result = self.send(method, &params);
+        if cassette_mode().is_some() {
+            let response = match &result {
+                Ok(result) => Ok(result.clone()),
+                Err(e) => Err(Value::String(e.to_string())),
+            };
+            record_interaction(Interaction::new("soroban", method, params, response));
+        }
+        result
+    }
+
+    /// Send a JSON-RPC request to the server and return its result.
+    fn send(&self, method: &str, params: &Value) -> Result<Value> {
+        debug!(url = %self.url, method, "Sending Soroban RPC request");
+        let response: Value = self
+            .client
+            .post(&self.url)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": method,
+                "params": params,
+            }))
+            .send()
+            .and_then(|response| response.json())
+            .map_err(|e| {
+                ErrorKind::Rpc.wrap(anyhow!(
+                    "Failed to send {} request to {}: {}",
+                    method,
+                    self.url,
+                    e
+                ))
+            })?;
+
+        if let Some(error) = response.get("error") {
+            return Err(anyhow!(
+                "{} request failed: {}",
+                method,
+                error["message"].as_str().unwrap_or(&error.to_string())
+            ));
+        }
+        response
+            .get("result")
+            .cloned()
+            .ok_or_else(|| anyhow!("Invalid response to {} request: no result", method))
+    }
+
+    /// Get the passphrase of the network of the server, which is part of the signed payload of
+    /// the transactions.
+    pub fn network_passphrase(&self) -> Result<String> {
+        let result = self.request("getNetwork", json!({}))?;
+        result["passphrase"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("Invalid response to getNetwork request: no passphrase"))
+    }
+
+    /// Get the current sequence number of an account.
+    pub fn account_sequence(&self, account: &[u8; 32]) -> Result<i64> {
+        let key = LedgerKey::Account(LedgerKeyAccount {
+            account_id: AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(*account))),
+        });
+        let result = self.request(
+            "getLedgerEntries",
+            json!({ "keys": [key.to_xdr_base64(Limits::none())?] }),
+        )?;
+
+        let entry = result["entries"]
+            .as_array()
+            .and_then(|entries| entries.first())
+            .and_then(|entry| entry["xdr"].as_str())
+            .ok_or_else(|| {
+                anyhow!(
+                    "Account {} not found. Fund it before submitting transactions",
+                    stellar_strkey::ed25519::PublicKey(*account)
+                )
+            })?;
+        match LedgerEntryData::from_xdr_base64(entry, Limits::none())? {
+            LedgerEntryData::Account(account) => Ok(account.seq_num.0),
+            _ => Err(anyhow!("Invalid response to getLedgerEntries request")),
+        }
+    }
+
+    /// Simulate a transaction, to compute its resources, fee and authorizations, and its return
+    /// value.
+    pub fn simulate_transaction(&self, envelope: &TransactionEnvelope) -> Result<SimulationResult> {
+        let result = self.request(
+            "simulateTransaction",
+            json!({ "transaction": envelope.to_xdr_base64(Limits::none())? }),
+        )?;
+        if let Some(error) = result["error"].as_str() {
+            return Err(ErrorKind::SimulationRevert
+                .wrap(anyhow!("Transaction simulation failed: {}", error)));
+        }
+
+        let transaction_data = SorobanTransactionData::from_xdr_base64(
+            result["transactionData"]
+                .as_str()
+                .ok_or_else(|| anyhow!("Invalid simulation result: no transaction data"))?,
+            Limits::none(),
+        )?;
+        let min_resource_fee = result["minResourceFee"]
+            .as_str()
+            .and_then(|fee| fee.parse().ok())
+            .ok_or_else(|| anyhow!("Invalid simulation result: no resource fee"))?;
+        let (auth, return_value) = match result["results"].as_array().and_then(|r| r.first()) {
+            Some(result) => {
+                let auth = result["auth"]
+                    .as_array()
+                    .map(Vec::as_slice)
+                    .unwrap_or_default()
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(|auth| SorobanAuthorizationEntry::from_xdr_base64(auth, Limits::none()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let return_value = match result["xdr"].as_str() {
+                    Some(xdr) => Some(ScVal::from_xdr_base64(xdr, Limits::none())?),
+                    None => None,
+                };
+                (auth, return_value)
+            }
+            None => (vec![], None),
+        };
+        debug!(
+            min_resource_fee,
+            auth = auth.len(),
+            "Simulated the transaction"
+        );
+
+        Ok(SimulationResult {
+            transaction_data,
+            min_resource_fee,
+            auth,
+            return_value,
+        })
+    }
+
+    /// Submit a signed transaction and wait for it to be applied.
+    ///
+    /// Returns the hash of the transaction and the return value of the invoked host function.
+    pub fn send_and_confirm_transaction(
+        &self,
+        envelope: &TransactionEnvelope,
+    ) -> Result<(String, Option<ScVal>)> {
+        let result = self.request(
+            "sendTransaction",
+            json!({ "transaction": envelope.to_xdr_base64(Limits::none())? }),
+        )?;
+        let hash = result["hash"]
+            .as_str()
+            .ok_or_else(|| anyhow!("Invalid response to sendTransaction request: no hash"))?
+            .to_string();
+        match result["status"].as_str() {
+            Some("PENDING") | Some("DUPLICATE") => {}
+            status => {
+                return Err(anyhow!(
+                    "Transaction {} was rejected with status {}: {}",
+                    hash,
+                    status.unwrap_or("unknown"),
+                    result["errorResultXdr"]
+                        .as_str()
+                        .unwrap_or("no error result")
+                ))
+            }
+        }
+        info!(hash, "Sent the transaction, waiting for its confirmation");
+        emit_progress(ProgressEvent::new("soroban", ProgressStage::Submitted).hash(&hash));
+
+        for _ in 0..TRANSACTION_POLL_ATTEMPTS {
+            let result = self.request("getTransaction", json!({ "hash": hash }))?;
+            match result["status"].as_str() {
+                Some("SUCCESS") => {
+                    let return_value = match result["resultMetaXdr"].as_str() {
+                        Some(meta) => match TransactionMeta::from_xdr_base64(meta, Limits::none())?
+                        {
+                            TransactionMeta::V3(meta) => meta
+                                .soroban_meta
+                                .map(|soroban_meta| soroban_meta.return_value),
+                            _ => None,
+                        },
+                        None => None,
+                    };
+                    info!(hash, ledger = %result["ledger"], "Transaction confirmed");
+                    emit_progress(
+                        ProgressEvent::new("soroban", ProgressStage::Confirmed)
+                            .hash(&hash)
+                            .block(&result["ledger"]),
+                    );
+                    return Ok((hash, return_value));
+                }
+                Some("FAILED") => {
+                    return Err(anyhow!(
+                        "Transaction {} failed: {}",
+                        hash,
+                        result["resultXdr"].as_str().unwrap_or("no result")
+                    ))
+                }
+                _ => sleep(Duration::from_secs(1)),
+            }
+        }
+
+        Err(anyhow!(
+            "Transaction {} was not confirmed after {} seconds",
+            hash,
+            TRANSACTION_POLL_ATTEMPTS
+        ))
+    }
+}
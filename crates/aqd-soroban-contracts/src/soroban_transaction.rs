@@ -0,0 +1,229 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::{
+        contract_spec::parse_address,
+        keypair::account_address,
+        soroban_rpc::{SimulationResult, SorobanRpc},
+    },
+    anyhow::{anyhow, Result},
+    aqd_utils::{emit_progress, ProgressEvent, ProgressStage},
+    ed25519_dalek::{Signer, SigningKey},
+    sha2::{Digest, Sha256},
+    stellar_xdr::curr::{
+        AccountId, DecoratedSignature, Hash, HostFunction, InvokeContractArgs,
+        InvokeHostFunctionOp, Limits, Memo, MuxedAccount, Operation, OperationBody, Preconditions,
+        PublicKey, ScAddress, ScSymbol, ScVal, SequenceNumber, Signature, SignatureHint,
+        SorobanAuthorizationEntry, Transaction, TransactionEnvelope, TransactionExt,
+        TransactionSignaturePayload, TransactionSignaturePayloadTaggedTransaction,
+        TransactionV1Envelope, Uint256, WriteXdr,
+    },
+    tracing::{debug, info},
+};
+
+/// The base fee of the transactions, in stroops, on top of which the resource fee computed by
+/// the simulation is paid.
+const BASE_FEE: u32 = 100;
+
+/// The result of a transaction applied on chain.
+#[derive(Debug)]
+pub struct SorobanTransactionResult {
+    /// The hex encoded hash of the transaction.
+    pub hash: String,
+    /// The return value of the invoked host function, if any.
+    pub return_value: Option<ScVal>,
+}
+
+/// Represents the execution context of Soroban transactions: the RPC server, the network and the
+/// source account that signs and pays for the transactions.
+///
+/// Each transaction invokes a single host function (upload of a WASM, creation of a contract, or
+/// call of a contract function). It is simulated first, to compute its resources, fee and
+/// authorizations, then signed and submitted. Only the authorizations of the source account are
+/// supported.
+pub struct SorobanTransaction {
+    rpc: SorobanRpc,
+    network_passphrase: String,
+    source: SigningKey,
+}
+
+impl SorobanTransaction {
+    /// Create the context of the transactions signed by `source`.
+    ///
+    /// The network passphrase is fetched from the RPC server if not provided.
+    pub fn new(
+        rpc: SorobanRpc,
+        source: SigningKey,
+        network_passphrase: Option<String>,
+    ) -> Result<Self> {
+        let network_passphrase = match network_passphrase {
+            Some(network_passphrase) => network_passphrase,
+            None => rpc.network_passphrase()?,
+        };
+        debug!(
+            rpc_url = rpc.url(),
+            network_passphrase, "Using Soroban network"
+        );
+        Ok(SorobanTransaction {
+            rpc,
+            network_passphrase,
+            source,
+        })
+    }
+
+    /// The Soroban RPC client.
+    pub fn rpc(&self) -> &SorobanRpc {
+        &self.rpc
+    }
+
+    /// The address of the source account (G...).
+    pub fn source_address(&self) -> String {
+        account_address(&self.source)
+    }
+
+    /// The source account, as an address of the host functions.
+    pub fn source_sc_address(&self) -> ScAddress {
+        ScAddress::Account(AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(
+            self.source.verifying_key().to_bytes(),
+        ))))
+    }
+
+    /// Simulate the invocation of a host function, without submitting it.
+    pub fn simulate(&self, host_function: HostFunction) -> Result<SimulationResult> {
+        let sequence = self.next_sequence()?;
+        self.simulate_with_sequence(host_function, sequence)
+    }
+
+    /// Simulate the invocation of a host function by a transaction with the given sequence number.
+    fn simulate_with_sequence(
+        &self,
+        host_function: HostFunction,
+        sequence: i64,
+    ) -> Result<SimulationResult> {
+        let transaction = self.build(
+            host_function,
+            vec![],
+            sequence,
+            BASE_FEE,
+            TransactionExt::V0,
+        )?;
+        self.rpc
+            .simulate_transaction(&TransactionEnvelope::Tx(TransactionV1Envelope {
+                tx: transaction,
+                signatures: Default::default(),
+            }))
+    }
+
+    /// Invoke a host function on chain.
+    ///
+    /// The transaction is simulated, assembled with the resources, fee and authorizations of the
+    /// simulation, signed by the source account, then submitted. This function waits for the
+    /// transaction to be applied, reporting its stages as progress events.
+    pub fn submit(&self, host_function: HostFunction) -> Result<SorobanTransactionResult> {
+        emit_progress(ProgressEvent::new("soroban", ProgressStage::Started));
+        let sequence = self.next_sequence()?;
+        let simulation = self.simulate_with_sequence(host_function.clone(), sequence)?;
+
+        let fee = BASE_FEE + simulation.min_resource_fee;
+        info!(source = %self.source_address(), sequence, fee, "Submitting Soroban transaction");
+        let transaction = self.build(
+            host_function,
+            simulation.auth,
+            sequence,
+            fee,
+            TransactionExt::V1(simulation.transaction_data),
+        )?;
+        let envelope = self.sign(transaction)?;
+        emit_progress(ProgressEvent::new("soroban", ProgressStage::Signed));
+
+        let (hash, return_value) = self.rpc.send_and_confirm_transaction(&envelope)?;
+        Ok(SorobanTransactionResult { hash, return_value })
+    }
+
+    /// The sequence number of the next transaction of the source account.
+    fn next_sequence(&self) -> Result<i64> {
+        Ok(self
+            .rpc
+            .account_sequence(&self.source.verifying_key().to_bytes())?
+            + 1)
+    }
+
+    /// Build a transaction of the source account invoking a single host function.
+    fn build(
+        &self,
+        host_function: HostFunction,
+        auth: Vec<SorobanAuthorizationEntry>,
+        sequence: i64,
+        fee: u32,
+        ext: TransactionExt,
+    ) -> Result<Transaction> {
+        let operation = Operation {
+            source_account: None,
+            body: OperationBody::InvokeHostFunction(InvokeHostFunctionOp {
+                host_function,
+                auth: auth.try_into()?,
+            }),
+        };
+        Ok(Transaction {
+            source_account: MuxedAccount::Ed25519(Uint256(self.source.verifying_key().to_bytes())),
+            fee,
+            seq_num: SequenceNumber(sequence),
+            cond: Preconditions::None,
+            memo: Memo::None,
+            operations: vec![operation].try_into()?,
+            ext,
+        })
+    }
+
+    /// Sign a transaction with the source account, for the network of the RPC server.
+    fn sign(&self, transaction: Transaction) -> Result<TransactionEnvelope> {
+        let network_id = Hash(Sha256::digest(self.network_passphrase.as_bytes()).into());
+        let payload = TransactionSignaturePayload {
+            network_id,
+            tagged_transaction: TransactionSignaturePayloadTaggedTransaction::Tx(
+                transaction.clone(),
+            ),
+        };
+        let hash = Sha256::digest(payload.to_xdr(Limits::none())?);
+        let signature = self.source.sign(&hash);
+
+        let public_key = self.source.verifying_key().to_bytes();
+        let hint = SignatureHint(public_key[28..].try_into()?);
+        let signature = DecoratedSignature {
+            hint,
+            signature: Signature(signature.to_bytes().to_vec().try_into()?),
+        };
+        Ok(TransactionEnvelope::Tx(TransactionV1Envelope {
+            tx: transaction,
+            signatures: vec![signature].try_into()?,
+        }))
+    }
+}
+
+/// Build the host function calling a function of a contract.
+///
+/// # Arguments
+///
+/// * `contract_id`: The address of the contract (C...).
+/// * `function`: The name of the function to call.
+/// * `args`: The arguments of the call, encoded with [`crate::ContractSpec::encode_args`].
+pub fn invoke_contract_function(
+    contract_id: &str,
+    function: &str,
+    args: Vec<ScVal>,
+) -> Result<HostFunction> {
+    let contract_address = match parse_address(contract_id)? {
+        contract_address @ ScAddress::Contract(_) => contract_address,
+        ScAddress::Account(_) => {
+            return Err(anyhow!(
+                "Invalid contract address {}: expected a contract (C...) address",
+                contract_id
+            ))
+        }
+    };
+    Ok(HostFunction::InvokeContract(InvokeContractArgs {
+        contract_address,
+        function_name: ScSymbol(function.to_string().try_into()?),
+        args: args.try_into()?,
+    }))
+}
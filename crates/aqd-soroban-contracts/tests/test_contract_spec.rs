@@ -0,0 +1,123 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::Result,
+    aqd_soroban_contracts::{
+        address_to_string, parse_address, parse_sc_val, sc_val_to_json, type_name,
+    },
+    serde_json::json,
+    stellar_xdr::curr::{
+        Int128Parts, ScAddress, ScSpecTypeBytesN, ScSpecTypeDef, ScSpecTypeOption, ScSpecTypeVec,
+        ScVal, UInt128Parts,
+    },
+};
+
+const ACCOUNT: &str = "GCNPEEJSTMX4QLS672IGAYWHGAECQGNSH7UDSS6EGXQLDPYELDVVJB2X";
+const CONTRACT: &str = "CDGIGIOWG5OESTIEH7OQEYHSDPAOYUO2ZSPWVO37SCONZUYEDN4L7FVM";
+
+fn vec_of(element_type: ScSpecTypeDef) -> ScSpecTypeDef {
+    ScSpecTypeDef::Vec(Box::new(ScSpecTypeVec {
+        element_type: Box::new(element_type),
+    }))
+}
+
+/// Purpose: This test checks that the arguments are parsed according to the types of the contract spec.
+#[test]
+pub fn test_parse_sc_val() -> Result<()> {
+    assert_eq!(
+        parse_sc_val("true", &ScSpecTypeDef::Bool)?,
+        ScVal::Bool(true)
+    );
+    assert_eq!(parse_sc_val("42", &ScSpecTypeDef::U32)?, ScVal::U32(42));
+    assert_eq!(parse_sc_val("-42", &ScSpecTypeDef::I64)?, ScVal::I64(-42));
+    assert_eq!(
+        parse_sc_val("18446744073709551616", &ScSpecTypeDef::U128)?,
+        ScVal::U128(UInt128Parts { hi: 1, lo: 0 })
+    );
+    assert_eq!(
+        parse_sc_val("-1", &ScSpecTypeDef::I128)?,
+        ScVal::I128(Int128Parts {
+            hi: -1,
+            lo: u64::MAX
+        })
+    );
+    assert_eq!(
+        parse_sc_val("1,2,3", &vec_of(ScSpecTypeDef::U32))?,
+        ScVal::Vec(Some(
+            vec![ScVal::U32(1), ScVal::U32(2), ScVal::U32(3)].try_into()?
+        ))
+    );
+
+    let option = ScSpecTypeDef::Option(Box::new(ScSpecTypeOption {
+        value_type: Box::new(ScSpecTypeDef::U32),
+    }));
+    assert_eq!(parse_sc_val("none", &option)?, ScVal::Void);
+    assert_eq!(parse_sc_val("7", &option)?, ScVal::U32(7));
+
+    let bytes_n = ScSpecTypeDef::BytesN(ScSpecTypeBytesN { n: 2 });
+    assert!(parse_sc_val("0x0102", &bytes_n).is_ok());
+    assert!(parse_sc_val("0x010203", &bytes_n).is_err());
+
+    // Values that do not match the type
+    assert!(parse_sc_val("yes", &ScSpecTypeDef::Bool).is_err());
+    assert!(parse_sc_val("-1", &ScSpecTypeDef::U32).is_err());
+    assert!(parse_sc_val("1,x", &vec_of(ScSpecTypeDef::U32)).is_err());
+
+    Ok(())
+}
+
+/// Purpose: This test checks that account and contract addresses are parsed from and encoded back into strkeys.
+#[test]
+pub fn test_parse_address() -> Result<()> {
+    let account = parse_address(ACCOUNT)?;
+    assert!(matches!(account, ScAddress::Account(_)));
+    assert_eq!(address_to_string(&account), ACCOUNT);
+
+    let contract = parse_address(CONTRACT)?;
+    assert!(matches!(contract, ScAddress::Contract(_)));
+    assert_eq!(address_to_string(&contract), CONTRACT);
+
+    assert!(parse_address("not an address").is_err());
+
+    Ok(())
+}
+
+/// Purpose: This test checks that the return values of the contract calls are converted into JSON.
+#[test]
+pub fn test_sc_val_to_json() -> Result<()> {
+    assert_eq!(sc_val_to_json(&ScVal::Void), json!(null));
+    assert_eq!(sc_val_to_json(&ScVal::Bool(true)), json!(true));
+    assert_eq!(sc_val_to_json(&ScVal::U32(42)), json!(42));
+    assert_eq!(
+        sc_val_to_json(&ScVal::I128(Int128Parts {
+            hi: -1,
+            lo: u64::MAX
+        })),
+        json!("-1")
+    );
+    assert_eq!(
+        sc_val_to_json(&parse_sc_val("1,2", &vec_of(ScSpecTypeDef::U32))?),
+        json!([1, 2])
+    );
+    assert_eq!(
+        sc_val_to_json(&parse_sc_val(CONTRACT, &ScSpecTypeDef::Address)?),
+        json!(CONTRACT)
+    );
+    assert_eq!(
+        sc_val_to_json(&parse_sc_val("0xcafe", &ScSpecTypeDef::Bytes)?),
+        json!("cafe")
+    );
+
+    Ok(())
+}
+
+/// Purpose: This test checks that the types of the contract spec are named as in the Rust SDK.
+#[test]
+pub fn test_type_name() {
+    assert_eq!(type_name(&ScSpecTypeDef::U32), "u32");
+    assert_eq!(type_name(&vec_of(ScSpecTypeDef::Address)), "vec<address>");
+    assert_eq!(
+        type_name(&ScSpecTypeDef::BytesN(ScSpecTypeBytesN { n: 32 })),
+        "bytesn<32>"
+    );
+}
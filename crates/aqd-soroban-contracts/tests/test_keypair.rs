@@ -0,0 +1,29 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::Result,
+    aqd_soroban_contracts::{account_address, parse_secret_key},
+    std::fs,
+};
+
+/// Purpose: This test checks that secret keys are parsed from strkeys and from files containing them.
+#[test]
+pub fn test_parse_secret_key() -> Result<()> {
+    let secret_key = stellar_strkey::ed25519::PrivateKey([7; 32]).to_string();
+    let key = parse_secret_key(&secret_key)?;
+    assert_eq!(key.to_bytes(), [7; 32]);
+    assert!(account_address(&key).starts_with('G'));
+
+    let path = std::env::temp_dir().join(format!("aqd-test-secret-key-{}", account_address(&key)));
+    fs::write(&path, format!("{}\n", secret_key))?;
+    assert_eq!(
+        parse_secret_key(&path.to_string_lossy())?.to_bytes(),
+        key.to_bytes()
+    );
+    fs::remove_file(&path)?;
+
+    // A public key is not a secret key
+    assert!(parse_secret_key(&account_address(&key)).is_err());
+
+    Ok(())
+}
@@ -0,0 +1,101 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {crate::commands::CLISorobanOpts, anyhow::Result, std::process::exit};
+use {
+    aqd_soroban_contracts::{invoke_contract_function, print_invocation_result, ContractSpec},
+    aqd_utils::{
+        check_target_match, is_quiet, print_text, record_history, ErrorKind, HistoryEntry,
+        OutputFormat,
+    },
+};
+
+#[derive(Clone, Debug, clap::Args)]
+#[clap(name = "call", about = "Call a function of a Soroban contract")]
+pub struct SorobanCall {
+    #[clap(
+        long,
+        help = "Specifies the path of the contract WASM file, whose spec describes the function arguments"
+    )]
+    wasm: String,
+    #[clap(long, help = "Specifies the address of the deployed contract (C...)")]
+    contract: String,
+    #[clap(long, help = "Specifies the name of the function to call")]
+    function: String,
+    #[clap(
+        long,
+        help = "Specifies the arguments to pass to the function.
+                For vectors, pass a comma-separated list of values. (e.g. 1,2,3,4)
+                Addresses are account (G...) or contract (C...) addresses, and bytes are hex strings",
+        // The number of arguments is variable (Can be 0 or more)
+        num_args = 0..,
+    )]
+    args: Vec<String>,
+    #[clap(
+        long,
+        help = "Specifies whether to simulate the call instead of submitting it, e.g. to read the state of the contract"
+    )]
+    simulate: bool,
+    #[clap(flatten)]
+    soroban_opts: CLISorobanOpts,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
+}
+
+impl SorobanCall {
+    /// Handle the Soroban call command.
+    ///
+    /// This function checks that the command is run in the correct directory, reads the contract
+    /// spec from the WASM file, encodes the arguments according to the inputs of the function, and
+    /// submits the call, or simulates it if `--simulate` is set. It then prints the transaction
+    /// hash and the return value of the function.
+    pub fn handle(&self) -> Result<()> {
+        // Make sure the command is run in the correct directory
+        // Fails if the command is run in a Solang project directory of another target
+        let target_match = check_target_match("soroban", None)
+            .map_err(|e| anyhow::anyhow!("Failed to check current directory: {}", e))?;
+        if !target_match {
+            exit(ErrorKind::Usage.exit_code());
+        }
+
+        // Encode the arguments according to the contract spec
+        let spec = ContractSpec::from_file(&self.wasm)?;
+        let function = spec.function(&self.function)?;
+        let args = spec.encode_args(function, &self.args)?;
+        let host_function = invoke_contract_function(&self.contract, &self.function, args)?;
+
+        let transaction = self.soroban_opts.transaction()?;
+
+        // Simulate the call instead of submitting it, if requested.
+        if self.simulate {
+            let simulation = transaction.simulate(host_function)?;
+            return print_invocation_result(None, simulation.return_value.as_ref(), self.output);
+        }
+
+        // Submit the call and record it in the history.
+        let result = transaction.submit(host_function);
+        let entry = HistoryEntry::new("soroban", "call")
+            .contract(&self.contract)
+            .function(&self.function);
+        record_history(match &result {
+            Ok(result) => entry.hash(&result.hash),
+            Err(e) => entry.failed(e),
+        });
+        let result = result?;
+
+        // Print the transaction hash alone in quiet mode, or the result of the call.
+        if is_quiet() {
+            print_text!(result.hash);
+            return Ok(());
+        }
+        print_invocation_result(
+            Some(&result.hash),
+            result.return_value.as_ref(),
+            self.output,
+        )
+    }
+}
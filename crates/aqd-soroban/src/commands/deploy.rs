@@ -0,0 +1,96 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::commands::CLISorobanOpts,
+    anyhow::Result,
+    serde_json::json,
+    std::{fs, process::exit},
+};
+use {
+    aqd_soroban_contracts::deploy_contract,
+    aqd_utils::{
+        check_target_match, is_quiet, print_key_value, print_text, record_history, ErrorKind,
+        HistoryEntry, OutputFormat,
+    },
+};
+
+#[derive(Clone, Debug, clap::Args)]
+#[clap(name = "deploy", about = "Deploy a contract to Soroban")]
+pub struct SorobanDeploy {
+    #[clap(help = "Specifies the path to the contract file to deploy (.wasm)")]
+    wasm: String,
+    #[clap(
+        long,
+        help = "Specifies the hex encoded 32 bytes salt from which the contract address is derived. Defaults to a random salt"
+    )]
+    salt: Option<String>,
+    #[clap(flatten)]
+    soroban_opts: CLISorobanOpts,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
+}
+
+impl SorobanDeploy {
+    /// Handle the deployment of a Soroban contract
+    ///
+    /// This function checks that the command is run in the correct directory, reads the WASM file
+    /// of the contract, uploads it and creates a contract instance of it, then prints the address
+    /// of the contract.
+    pub fn handle(&self) -> Result<()> {
+        // Make sure the command is run in the correct directory
+        // Fails if the command is run in a Solang project directory of another target
+        let target_match = check_target_match("soroban", None)
+            .map_err(|e| anyhow::anyhow!("Failed to check current directory: {}", e))?;
+        if !target_match {
+            exit(ErrorKind::Usage.exit_code());
+        }
+
+        // Parse command-line arguments
+        let wasm = fs::read(&self.wasm)
+            .map_err(|e| anyhow::anyhow!("Failed to read WASM file {}: {}", self.wasm, e))?;
+        let salt = match &self.salt {
+            Some(salt) => Some(
+                hex::decode(salt.trim_start_matches("0x"))
+                    .ok()
+                    .and_then(|salt| salt.try_into().ok())
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("Invalid salt {}: expected 32 hex encoded bytes", salt)
+                    })?,
+            ),
+            None => None,
+        };
+        let output = self.output;
+
+        // Deploy the contract
+        let transaction = self.soroban_opts.transaction()?;
+        let deployment = deploy_contract(&transaction, &wasm, salt);
+        let entry = HistoryEntry::new("soroban", "deploy");
+        record_history(match &deployment {
+            Ok(deployment) => entry.contract(&deployment.contract_id),
+            Err(e) => entry.failed(e),
+        });
+        let deployment = deployment?;
+
+        // If the output is quiet, print the contract ID alone
+        // If the output is structured, print the deployment in the output format
+        // Else, print the contract ID and the WASM hash as strings
+        if is_quiet() {
+            print_text!(deployment.contract_id);
+        } else if output.is_structured() {
+            output.print(&json!({
+                "contract_id": deployment.contract_id,
+                "wasm_hash": deployment.wasm_hash,
+            }))?;
+        } else {
+            print_key_value!("WASM hash", deployment.wasm_hash);
+            print_key_value!("Contract ID", deployment.contract_id);
+        }
+
+        Ok(())
+    }
+}
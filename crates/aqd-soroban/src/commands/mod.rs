@@ -0,0 +1,45 @@
+// SPDX-License-Identifier: Apache-2.0
+
+pub mod call;
+pub mod deploy;
+pub mod show;
+
+use {
+    anyhow::Result,
+    aqd_soroban_contracts::{
+        normalize_to_url_if_moniker, parse_secret_key, SorobanRpc, SorobanTransaction,
+    },
+};
+
+/// The options of the commands submitting transactions to a Soroban network.
+#[derive(Clone, Debug, clap::Args)]
+pub struct CLISorobanOpts {
+    #[clap(
+        long,
+        env = "AQD_SOROBAN_URL",
+        help = "Specifies the URL or moniker (testnet, futurenet or localhost) of the Soroban RPC server"
+    )]
+    url: String,
+    #[clap(
+        long,
+        env = "AQD_SOROBAN_NETWORK_PASSPHRASE",
+        help = "Specifies the passphrase of the network. Defaults to the passphrase of the network of the RPC server"
+    )]
+    network_passphrase: Option<String>,
+    #[clap(
+        long,
+        env = "AQD_SOROBAN_SOURCE",
+        hide_env_values = true,
+        help = "Specifies the secret key (S...), or the path of a file containing it, of the account that signs and pays for the transactions"
+    )]
+    source: String,
+}
+
+impl CLISorobanOpts {
+    /// Create the context of the transactions from the options.
+    pub fn transaction(&self) -> Result<SorobanTransaction> {
+        let rpc = SorobanRpc::new(normalize_to_url_if_moniker(&self.url));
+        let source = parse_secret_key(&self.source)?;
+        SorobanTransaction::new(rpc, source, self.network_passphrase.clone())
+    }
+}
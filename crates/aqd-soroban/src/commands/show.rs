@@ -0,0 +1,55 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {anyhow::Result, std::process::exit};
+use {
+    aqd_soroban_contracts::{print_contract_spec, ContractSpec},
+    aqd_utils::{check_target_match, ErrorKind, OutputFormat},
+};
+
+#[derive(Clone, Debug, clap::Args)]
+#[clap(
+    name = "show",
+    about = "Show information about a Soroban contract's functions given its WASM file"
+)]
+pub struct SorobanShow {
+    #[clap(
+        long,
+        help = "Specifies the path of the contract WASM file, whose spec describes the functions"
+    )]
+    wasm: String,
+    #[clap(
+        long,
+        help = "Specifies the name of the function to show information about\n
+                If not specified, information about all functions is shown"
+    )]
+    function: Option<String>,
+    #[clap(
+        long,
+        default_value = "human",
+        env = "AQD_OUTPUT",
+        help = "Specifies the format of the output: human, json, yaml or table."
+    )]
+    output: OutputFormat,
+}
+
+impl SorobanShow {
+    /// Handle the Soroban show command.
+    ///
+    /// This function checks that the command is run in the correct directory, reads the contract
+    /// spec from the WASM file, and prints information about its functions.
+    pub fn handle(&self) -> Result<()> {
+        // Make sure the command is run in the correct directory
+        // Fails if the command is run in a Solang project directory of another target
+        let target_match = check_target_match("soroban", None)
+            .map_err(|e| anyhow::anyhow!("Failed to check current directory: {}", e))?;
+        if !target_match {
+            exit(ErrorKind::Usage.exit_code());
+        }
+
+        // Get the contract spec from the WASM file
+        let spec = ContractSpec::from_file(&self.wasm)?;
+
+        // Print information about the functions
+        print_contract_spec(&spec, self.function.clone(), self.output)
+    }
+}
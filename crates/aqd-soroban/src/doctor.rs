@@ -0,0 +1,57 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    aqd_soroban_contracts::{
+        account_address, normalize_to_url_if_moniker, parse_secret_key, SorobanRpc,
+    },
+    aqd_utils::{CheckStatus, Diagnostic},
+    std::{env, time::Instant},
+};
+
+/// The section of the Soroban checks.
+const SECTION: &str = "soroban";
+
+/// The checks of the Soroban environment: the RPC server of `AQD_SOROBAN_URL` can be reached, and
+/// the secret key of `AQD_SOROBAN_SOURCE` is valid.
+///
+/// The Soroban commands have no default server, so nothing is checked without these variables.
+pub fn diagnostics() -> Vec<Diagnostic> {
+    let rpc = match env::var("AQD_SOROBAN_URL") {
+        Ok(url) => {
+            let rpc = SorobanRpc::new(normalize_to_url_if_moniker(&url));
+            let start = Instant::now();
+            Diagnostic::from_result(
+                SECTION,
+                "rpc",
+                rpc.network_passphrase().map(|passphrase| {
+                    format!(
+                        "{} ({}, {} ms)",
+                        rpc.url(),
+                        passphrase,
+                        start.elapsed().as_millis()
+                    )
+                }),
+            )
+        }
+        Err(_) => Diagnostic::new(
+            SECTION,
+            "rpc",
+            CheckStatus::Info,
+            "AQD_SOROBAN_URL is not set: use --url",
+        ),
+    };
+    let source = match env::var("AQD_SOROBAN_SOURCE") {
+        Ok(source) => Diagnostic::from_result(
+            SECTION,
+            "source",
+            parse_secret_key(&source).map(|key| account_address(&key)),
+        ),
+        Err(_) => Diagnostic::new(
+            SECTION,
+            "source",
+            CheckStatus::Info,
+            "AQD_SOROBAN_SOURCE is not set: use --source",
+        ),
+    };
+    vec![rpc, source]
+}
@@ -0,0 +1,9 @@
+// SPDX-License-Identifier: Apache-2.0
+
+mod commands;
+mod doctor;
+mod soroban_action;
+
+pub use commands::{call::SorobanCall, deploy::SorobanDeploy, show::SorobanShow};
+pub use doctor::diagnostics;
+pub use soroban_action::SorobanAction;
@@ -0,0 +1,14 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::{SorobanCall, SorobanDeploy, SorobanShow},
+    clap::Subcommand,
+};
+
+/// Available subcommands for the `soroban` subcommand.
+#[derive(Debug, Subcommand)]
+pub enum SorobanAction {
+    Deploy(SorobanDeploy),
+    Call(SorobanCall),
+    Show(SorobanShow),
+}
@@ -0,0 +1,241 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::{is_quiet, output::OutputFormat, print_key_value, print_text, print_title},
+    anyhow::{anyhow, Result},
+    serde::Serialize,
+    std::{
+        collections::BTreeMap,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Mutex,
+        },
+        thread,
+        time::{Duration, Instant},
+    },
+};
+
+/// The outcome of an iteration of a benchmark: its latency, and its error if it failed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BenchSample {
+    pub latency: Duration,
+    pub error: Option<String>,
+}
+
+impl BenchSample {
+    /// Create the sample of an iteration that took `latency`, from its result.
+    pub fn new<T>(latency: Duration, result: Result<T>) -> Self {
+        BenchSample {
+            latency,
+            error: result.err().map(|e| e.to_string()),
+        }
+    }
+
+    /// Time an iteration of a benchmark.
+    pub fn measure<F: FnOnce() -> Result<()>>(iteration: F) -> Self {
+        let start = Instant::now();
+        let result = iteration();
+        BenchSample::new(start.elapsed(), result)
+    }
+}
+
+/// The latency percentiles of the successful iterations of a benchmark, in milliseconds.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct LatencyStats {
+    pub min: f64,
+    pub mean: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+impl LatencyStats {
+    /// Compute the statistics of latencies, or `None` if there are none.
+    pub fn from_latencies(latencies: &[Duration]) -> Option<Self> {
+        let mut latencies: Vec<f64> = latencies
+            .iter()
+            .map(|latency| latency.as_nanos() as f64 / 1_000_000.0)
+            .collect();
+        if latencies.is_empty() {
+            return None;
+        }
+        latencies.sort_by(f64::total_cmp);
+        Some(LatencyStats {
+            min: latencies[0],
+            mean: latencies.iter().sum::<f64>() / latencies.len() as f64,
+            p50: percentile(&latencies, 50.0),
+            p90: percentile(&latencies, 90.0),
+            p99: percentile(&latencies, 99.0),
+            max: latencies[latencies.len() - 1],
+        })
+    }
+}
+
+/// The report of a benchmark: the latencies of the successful iterations, the failures, and the
+/// throughput.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct BenchReport {
+    pub iterations: usize,
+    pub concurrency: usize,
+    pub successes: usize,
+    pub failures: usize,
+    /// The duration of the whole benchmark, in seconds
+    pub elapsed: f64,
+    /// The successful iterations per second
+    pub throughput: f64,
+    /// The latencies of the successful iterations, if any
+    pub latency: Option<LatencyStats>,
+    /// The number of failures of each error
+    pub errors: BTreeMap<String, usize>,
+}
+
+impl BenchReport {
+    /// Build the report of the iterations of a benchmark, run with the given concurrency in the
+    /// given duration.
+    pub fn new(samples: &[BenchSample], concurrency: usize, elapsed: Duration) -> Self {
+        let mut latencies = vec![];
+        let mut errors = BTreeMap::new();
+        for sample in samples {
+            match &sample.error {
+                None => latencies.push(sample.latency),
+                Some(error) => *errors.entry(error.clone()).or_insert(0) += 1,
+            }
+        }
+        let elapsed = elapsed.as_secs_f64();
+        BenchReport {
+            iterations: samples.len(),
+            concurrency,
+            successes: latencies.len(),
+            failures: samples.len() - latencies.len(),
+            elapsed,
+            throughput: if elapsed > 0.0 {
+                latencies.len() as f64 / elapsed
+            } else {
+                0.0
+            },
+            latency: LatencyStats::from_latencies(&latencies),
+            errors,
+        }
+    }
+
+    /// Print the report in the output format, or as a summary for humans.
+    pub fn print(&self, output: OutputFormat) -> Result<()> {
+        if output.is_structured() {
+            return output.print(self);
+        }
+        if is_quiet() {
+            print_text!(format!("{:.2}", self.throughput));
+            return Ok(());
+        }
+        print_title!("Benchmark Result");
+        print_key_value!("Iterations", self.iterations);
+        print_key_value!("Concurrency", self.concurrency);
+        print_key_value!("Successes", self.successes);
+        print_key_value!("Failures", self.failures);
+        print_key_value!("Elapsed", format!("{:.2} s", self.elapsed));
+        print_key_value!("Throughput", format!("{:.2} tx/s", self.throughput));
+        if let Some(latency) = &self.latency {
+            print_title!("Latency (ms)");
+            print_key_value!("min", format!("{:.1}", latency.min));
+            print_key_value!("mean", format!("{:.1}", latency.mean));
+            print_key_value!("p50", format!("{:.1}", latency.p50));
+            print_key_value!("p90", format!("{:.1}", latency.p90));
+            print_key_value!("p99", format!("{:.1}", latency.p99));
+            print_key_value!("max", format!("{:.1}", latency.max));
+        }
+        if !self.errors.is_empty() {
+            print_title!("Errors");
+            for (error, count) in &self.errors {
+                print_key_value!(format!("{}x", count), error);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Run the iterations of a benchmark on `concurrency` threads, and report their latencies.
+///
+/// `iteration` is called with the index of each iteration, from 0 to `iterations - 1`.
+pub fn run_bench<F>(iterations: usize, concurrency: usize, iteration: F) -> BenchReport
+where
+    F: Fn(usize) -> Result<()> + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let samples = Mutex::new(Vec::with_capacity(iterations));
+    let start = Instant::now();
+    thread::scope(|scope| {
+        for _ in 0..concurrency.min(iterations) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                if index >= iterations {
+                    break;
+                }
+                let sample = BenchSample::measure(|| iteration(index));
+                samples.lock().unwrap().push(sample);
+            });
+        }
+    });
+    BenchReport::new(&samples.into_inner().unwrap(), concurrency, start.elapsed())
+}
+
+/// Parse the number of iterations of a benchmark, at least 1.
+pub fn parse_iterations(iterations: &str) -> Result<usize> {
+    match iterations.parse::<usize>() {
+        Ok(iterations) if iterations > 0 => Ok(iterations),
+        _ => Err(anyhow!(
+            "Invalid number of iterations: {}. Expected a positive integer",
+            iterations
+        )),
+    }
+}
+
+/// Parse the number of concurrent iterations of a benchmark, at least 1.
+pub fn parse_concurrency(concurrency: &str) -> Result<usize> {
+    match concurrency.parse::<usize>() {
+        Ok(concurrency) if concurrency > 0 => Ok(concurrency),
+        _ => Err(anyhow!(
+            "Invalid concurrency: {}. Expected a positive integer",
+            concurrency
+        )),
+    }
+}
+
+/// The percentile of sorted values, with the nearest-rank method.
+fn percentile(sorted: &[f64], percentile: f64) -> f64 {
+    let rank = (percentile / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// A test for the `BenchReport::new` function
+#[test]
+fn test_bench_report() {
+    let mut samples: Vec<BenchSample> = (1..=100)
+        .map(|ms| BenchSample {
+            latency: Duration::from_millis(ms),
+            error: None,
+        })
+        .collect();
+    samples.push(BenchSample {
+        latency: Duration::from_millis(5000),
+        error: Some("Blockhash not found".to_string()),
+    });
+
+    let report = BenchReport::new(&samples, 4, Duration::from_secs(10));
+    assert_eq!(report.iterations, 101);
+    assert_eq!((report.successes, report.failures), (100, 1));
+    assert_eq!(report.throughput, 10.0);
+    assert_eq!(report.errors.get("Blockhash not found"), Some(&1));
+    let latency = report.latency.unwrap();
+    assert_eq!((latency.min, latency.max), (1.0, 100.0));
+    assert_eq!((latency.p50, latency.p90, latency.p99), (50.0, 90.0, 99.0));
+    assert_eq!(latency.mean, 50.5);
+
+    // All the iterations are run once, whatever the concurrency
+    let report = run_bench(10, 3, |index| match index % 2 {
+        0 => Ok(()),
+        _ => Err(anyhow!("odd")),
+    });
+    assert_eq!((report.successes, report.failures), (5, 5));
+    assert!(parse_iterations("0").is_err());
+}
@@ -0,0 +1,403 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    serde::{Deserialize, Serialize},
+    serde_json::Value,
+    std::{
+        env, fs,
+        path::{Path, PathBuf},
+        sync::Mutex,
+    },
+};
+
+/// The environment variable of the cassette to record the RPC requests to.
+pub const AQD_RECORD_ENV: &str = "AQD_RECORD";
+
+/// The environment variable of the cassette to replay the RPC responses from.
+pub const AQD_REPLAY_ENV: &str = "AQD_REPLAY";
+
+/// The strings of the requests shorter than this are not substituted in the replayed responses,
+/// so that a differing number or short name does not alter unrelated responses.
+const MIN_SUBSTITUTION_LENGTH: usize = 16;
+
+/// The cassette of the command, if its RPC requests are recorded or replayed.
+static CASSETTE: Mutex<Option<CassetteState>> = Mutex::new(None);
+
+/// The recording or replaying of the RPC requests of a command, selected with `--record` or
+/// `--replay`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Send the requests to the network, and record them with their responses to the cassette
+    Record(PathBuf),
+    /// Replay the responses of the cassette, without any network access
+    Replay(PathBuf),
+}
+
+impl CassetteMode {
+    /// The path of the cassette.
+    pub fn path(&self) -> &Path {
+        match self {
+            CassetteMode::Record(path) | CassetteMode::Replay(path) => path,
+        }
+    }
+
+    /// The environment variable selecting the mode, to pass it to another aqd command.
+    pub fn env_var(&self) -> &'static str {
+        match self {
+            CassetteMode::Record(_) => AQD_RECORD_ENV,
+            CassetteMode::Replay(_) => AQD_REPLAY_ENV,
+        }
+    }
+
+    /// The mode of a step of a playbook, with its own cassette named after the cassette of the
+    /// playbook and the step, e.g. `flipper.deploy.json` for the `deploy` step of `flipper.json`.
+    ///
+    /// The path is absolute, as the steps are run in the directory of the playbook.
+    pub fn for_step(&self, step: &str) -> Self {
+        let path = self.path();
+        let stem = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let name = match path.extension() {
+            Some(extension) => format!("{}.{}.{}", stem, step, extension.to_string_lossy()),
+            None => format!("{}.{}", stem, step),
+        };
+        let path = path.with_file_name(name);
+        let path = match env::current_dir() {
+            Ok(dir) if path.is_relative() => dir.join(path),
+            _ => path,
+        };
+        match self {
+            CassetteMode::Record(_) => CassetteMode::Record(path),
+            CassetteMode::Replay(_) => CassetteMode::Replay(path),
+        }
+    }
+}
+
+/// The RPC requests of a command and their responses, saved as JSON.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Cassette {
+    pub interactions: Vec<Interaction>,
+    /// The strings of the recorded requests replaced in the replayed responses by the strings of
+    /// the requests of the replay, such as the extrinsics signed again with a random nonce
+    #[serde(skip)]
+    substitutions: Vec<(String, String)>,
+}
+
+/// An RPC request and its response, or the notifications of a subscription.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    /// The chain of the RPC server: `solana`, `polkadot`, `soroban` or `evm`
+    pub chain: String,
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    /// The JSON-RPC error object, or the message of the error, of a failed request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<Value>,
+    /// The method of the notifications of a subscription
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notification: Option<String>,
+    /// The results of the notifications of a subscription, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notifications: Vec<Value>,
+    #[serde(skip)]
+    replayed: bool,
+}
+
+impl Interaction {
+    /// Create the interaction of a request and its result, or its error.
+    pub fn new(chain: &str, method: &str, params: Value, response: Result<Value, Value>) -> Self {
+        let (result, error) = match response {
+            Ok(result) => (Some(result), None),
+            Err(error) => (None, Some(error)),
+        };
+        Interaction {
+            chain: chain.to_string(),
+            method: method.to_string(),
+            params,
+            result,
+            error,
+            notification: None,
+            notifications: vec![],
+            replayed: false,
+        }
+    }
+
+    /// The recorded result of the request, or its error.
+    pub fn response(&self) -> Result<Value> {
+        match &self.error {
+            Some(error) => Err(anyhow!(
+                "{}",
+                error["message"]
+                    .as_str()
+                    .or(error.as_str())
+                    .map_or_else(|| error.to_string(), str::to_string)
+            )),
+            None => Ok(self.result.clone().unwrap_or(Value::Null)),
+        }
+    }
+}
+
+impl Cassette {
+    /// Read a cassette from a JSON file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read cassette {}: {}", path.display(), e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| anyhow!("Invalid cassette {}: {}", path.display(), e))
+    }
+
+    /// Write the cassette to a JSON file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(path, content + "\n")
+            .map_err(|e| anyhow!("Failed to write cassette {}: {}", path.display(), e))
+    }
+
+    /// Take the recorded interaction answering a request.
+    ///
+    /// The interactions are replayed once each, in the order they were recorded. The first one
+    /// with the same method and parameters is preferred, and otherwise the first one with the same
+    /// method, as some parameters change from a run to another, such as the randomly generated
+    /// keypairs and the signatures. The strings of the parameters of such a request then replace
+    /// those of the recorded request in the next responses.
+    pub fn replay(&mut self, chain: &str, method: &str, params: &Value) -> Option<Interaction> {
+        let candidates = || {
+            self.interactions
+                .iter()
+                .enumerate()
+                .filter(move |(_, interaction)| {
+                    !interaction.replayed
+                        && interaction.chain == chain
+                        && interaction.method == method
+                })
+        };
+        let index = candidates()
+            .find(|(_, interaction)| interaction.params == *params)
+            .or_else(|| candidates().next())
+            .map(|(index, _)| index)?;
+
+        self.interactions[index].replayed = true;
+        let mut substitutions = vec![];
+        diff_strings(&self.interactions[index].params, params, &mut substitutions);
+        self.substitutions.extend(substitutions);
+
+        let mut interaction = self.interactions[index].clone();
+        for value in interaction
+            .result
+            .iter_mut()
+            .chain(interaction.notifications.iter_mut())
+        {
+            substitute(value, &self.substitutions);
+        }
+        Some(interaction)
+    }
+}
+
+/// The cassette of the command, and whether it is recorded or replayed.
+struct CassetteState {
+    mode: CassetteMode,
+    cassette: Cassette,
+}
+
+/// Select the recording or replaying of the RPC requests, reading the cassette to replay.
+pub fn set_cassette(mode: Option<CassetteMode>) -> Result<()> {
+    let state = match mode {
+        None => None,
+        Some(CassetteMode::Replay(path)) => Some(CassetteState {
+            cassette: Cassette::from_file(&path)?,
+            mode: CassetteMode::Replay(path),
+        }),
+        Some(mode) => Some(CassetteState {
+            mode,
+            cassette: Cassette::default(),
+        }),
+    };
+    *CASSETTE.lock().unwrap() = state;
+    Ok(())
+}
+
+/// Whether the RPC requests are recorded or replayed, and the path of the cassette.
+pub fn cassette_mode() -> Option<CassetteMode> {
+    CASSETTE
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|state| state.mode.clone())
+}
+
+/// Whether the RPC responses are replayed from a cassette, without any network access.
+pub fn is_replaying() -> bool {
+    matches!(cassette_mode(), Some(CassetteMode::Replay(_)))
+}
+
+/// Record an interaction to the cassette, if recording, and return its index.
+pub fn record_interaction(interaction: Interaction) -> Option<usize> {
+    let mut cassette = CASSETTE.lock().unwrap();
+    match cassette.as_mut() {
+        Some(CassetteState {
+            mode: CassetteMode::Record(_),
+            cassette,
+        }) => {
+            cassette.interactions.push(interaction);
+            Some(cassette.interactions.len() - 1)
+        }
+        _ => None,
+    }
+}
+
+/// Record a notification of the subscription recorded at `index`.
+pub fn record_notification(index: usize, notification: &str, result: Value) {
+    let mut cassette = CASSETTE.lock().unwrap();
+    if let Some(interaction) = cassette
+        .as_mut()
+        .and_then(|state| state.cassette.interactions.get_mut(index))
+    {
+        interaction.notification = Some(notification.to_string());
+        interaction.notifications.push(result);
+    }
+}
+
+/// Take the recorded interaction answering a request, when replaying.
+///
+/// See [`Cassette::replay`].
+///
+/// # Errors
+///
+/// This function returns an error if no recorded interaction is left for the method.
+pub fn replay_interaction(chain: &str, method: &str, params: &Value) -> Result<Interaction> {
+    let mut cassette = CASSETTE.lock().unwrap();
+    let state = cassette
+        .as_mut()
+        .ok_or_else(|| anyhow!("No cassette to replay"))?;
+    state.cassette.replay(chain, method, params).ok_or_else(|| {
+        anyhow!(
+            "No recorded response left for the {} request {} in the cassette {}",
+            chain,
+            method,
+            state.mode.path().display()
+        )
+    })
+}
+
+/// Write the recorded cassette, if recording.
+pub fn save_cassette() -> Result<()> {
+    match CASSETTE.lock().unwrap().as_ref() {
+        Some(CassetteState {
+            mode: CassetteMode::Record(path),
+            cassette,
+        }) => cassette.save(path),
+        _ => Ok(()),
+    }
+}
+
+/// Collect the pairs of different strings at the same place of two values.
+fn diff_strings(recorded: &Value, actual: &Value, substitutions: &mut Vec<(String, String)>) {
+    match (recorded, actual) {
+        (Value::String(recorded), Value::String(actual))
+            if recorded != actual && recorded.len() >= MIN_SUBSTITUTION_LENGTH =>
+        {
+            substitutions.push((recorded.clone(), actual.clone()));
+        }
+        (Value::Array(recorded), Value::Array(actual)) => {
+            for (recorded, actual) in recorded.iter().zip(actual) {
+                diff_strings(recorded, actual, substitutions);
+            }
+        }
+        (Value::Object(recorded), Value::Object(actual)) => {
+            for (key, recorded) in recorded {
+                if let Some(actual) = actual.get(key) {
+                    diff_strings(recorded, actual, substitutions);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Replace the recorded strings of a value with the strings of the replay.
+fn substitute(value: &mut Value, substitutions: &[(String, String)]) {
+    match value {
+        Value::String(string) => {
+            for (recorded, actual) in substitutions {
+                if string.contains(recorded.as_str()) {
+                    *string = string.replace(recorded.as_str(), actual);
+                }
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                substitute(value, substitutions);
+            }
+        }
+        Value::Object(map) => {
+            for value in map.values_mut() {
+                substitute(value, substitutions);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// A test for the `Cassette::replay` function
+#[test]
+fn test_cassette_replay() {
+    use serde_json::json;
+
+    let extrinsic = "0x2d02840088dc3417d5058ec4b4503e0c12ea1a0a89be200fe98922423d4334014fa6b0ee";
+    let mut cassette = Cassette::default();
+    for (method, params, result) in [
+        ("chain_getHeader", json!([]), json!({ "number": "0x1" })),
+        ("chain_getHeader", json!([]), json!({ "number": "0x2" })),
+        ("author_submitExtrinsic", json!([extrinsic]), json!("0x01")),
+        (
+            "chain_getBlock",
+            json!(["0x02"]),
+            json!({ "extrinsics": [extrinsic] }),
+        ),
+    ] {
+        cassette
+            .interactions
+            .push(Interaction::new("polkadot", method, params, Ok(result)));
+    }
+
+    // The requests of a method are answered in order, once each
+    let header = cassette.replay("polkadot", "chain_getHeader", &json!([]));
+    assert_eq!(header.unwrap().result, Some(json!({ "number": "0x1" })));
+    let header = cassette.replay("polkadot", "chain_getHeader", &json!([]));
+    assert_eq!(header.unwrap().result, Some(json!({ "number": "0x2" })));
+    assert!(cassette
+        .replay("polkadot", "chain_getHeader", &json!([]))
+        .is_none());
+    assert!(cassette
+        .replay("solana", "author_submitExtrinsic", &json!([]))
+        .is_none());
+
+    // An extrinsic signed again replaces the recorded one in the next responses
+    let signed_again = "0x2d02840088dc3417d5058ec4b4503e0c12ea1a0a89be200fe98922423d4334014fa6b0ff";
+    let submitted = cassette.replay("polkadot", "author_submitExtrinsic", &json!([signed_again]));
+    assert_eq!(submitted.unwrap().response().unwrap(), json!("0x01"));
+    let block = cassette.replay("polkadot", "chain_getBlock", &json!(["0x02"]));
+    assert_eq!(
+        block.unwrap().result,
+        Some(json!({ "extrinsics": [signed_again] }))
+    );
+
+    let failed = Interaction::new(
+        "evm",
+        "eth_call",
+        json!([]),
+        Err(json!({ "code": 3, "message": "execution reverted" })),
+    );
+    assert_eq!(
+        failed.response().unwrap_err().to_string(),
+        "execution reverted"
+    );
+}
@@ -0,0 +1,345 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    std::{env, fs, path::PathBuf},
+    toml_edit::{value, ArrayOfTables, Document, Item, Table},
+};
+
+/// The environment variable that overrides the path of the aqd configuration file.
+pub const AQD_CONFIG_ENV: &str = "AQD_CONFIG";
+
+/// A network defined by the user in the aqd configuration file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NetworkConfig {
+    /// The name used to reference the network (e.g. with `--network`)
+    pub name: String,
+    /// The websockets URL of the network node
+    pub url: String,
+    /// The number of decimals of the network token, if known
+    pub token_decimals: Option<u8>,
+    /// The symbol of the network token, if known
+    pub token_symbol: Option<String>,
+    /// The URL template of the block explorer page of a transaction, if any (see
+    /// [`crate::explorer_url`])
+    pub explorer: Option<String>,
+}
+
+/// A signing key labeled by the user in the aqd configuration file.
+///
+/// Only the address of the key and the path of the file its secret is read from are stored, never
+/// the secret itself.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WalletConfig {
+    /// The label of the wallet
+    pub name: String,
+    /// The chain of the wallet: `solana` or `polkadot`
+    pub chain: String,
+    /// The address of the wallet: a base58 public key on Solana, an SS58 address on Polkadot
+    pub address: String,
+    /// The file the secret key is read from, if any: a keypair file or keystore on Solana, a file
+    /// of the secret key URI or a keystore on Polkadot
+    pub path: Option<String>,
+}
+
+/// The aqd configuration file.
+///
+/// The configuration file is a TOML file, read from the path in the `AQD_CONFIG` environment
+/// variable, or from `~/.config/aqd/config.toml` by default. Networks are defined with one
+/// `[[networks]]` entry per network:
+///
+/// ```toml
+/// [[networks]]
+/// name = "mychain"
+/// url = "wss://rpc.mychain.io"
+/// token_decimals = 12
+/// token_symbol = "MYC"
+/// explorer = "https://mychain.subscan.io/extrinsic/{tx}"
+/// ```
+///
+/// The `explorer` of a network whose `url` is the URL of a Solana cluster is used for the
+/// transactions sent to that cluster.
+///
+/// The signing keys are labeled with one `[[wallets]]` entry per key, usually written by
+/// `aqd wallet label`:
+///
+/// ```toml
+/// [[wallets]]
+/// name = "deployer"
+/// chain = "solana"
+/// address = "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU"
+/// path = "/home/user/.config/solana/deployer.json"
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AqdConfig {
+    pub networks: Vec<NetworkConfig>,
+    pub wallets: Vec<WalletConfig>,
+}
+
+impl AqdConfig {
+    /// Get the path of the configuration file, if it can be determined.
+    pub fn path() -> Option<PathBuf> {
+        if let Ok(path) = env::var(AQD_CONFIG_ENV) {
+            return Some(PathBuf::from(path));
+        }
+        env::var("HOME").ok().map(|home| {
+            PathBuf::from(home)
+                .join(".config")
+                .join("aqd")
+                .join("config.toml")
+        })
+    }
+
+    /// Load the configuration file.
+    ///
+    /// Returns an empty configuration if the file does not exist.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the file exists but cannot be read or parsed.
+    pub fn load() -> Result<Self> {
+        let path = match Self::path() {
+            Some(path) if path.exists() => path,
+            _ => return Ok(AqdConfig::default()),
+        };
+        let content = fs::read_to_string(&path)
+            .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+        Self::from_toml(&content).map_err(|e| anyhow!("Failed to parse {}: {}", path.display(), e))
+    }
+
+    /// Parse the configuration from its TOML content.
+    pub fn from_toml(content: &str) -> Result<Self> {
+        let parsed_toml: toml::Value = toml::from_str(content)?;
+        let networks = parsed_toml
+            .get("networks")
+            .and_then(|networks| networks.as_array())
+            .map(|networks| networks.as_slice())
+            .unwrap_or_default()
+            .iter()
+            .enumerate()
+            .map(|(i, network)| {
+                let name = network
+                    .get("name")
+                    .and_then(|name| name.as_str())
+                    .ok_or_else(|| anyhow!("Missing name for network {}", i + 1))?;
+                let url = network
+                    .get("url")
+                    .and_then(|url| url.as_str())
+                    .ok_or_else(|| anyhow!("Missing url for network {}", name))?;
+                let token_decimals = match network.get("token_decimals") {
+                    Some(decimals) => Some(
+                        decimals
+                            .as_integer()
+                            .and_then(|decimals| u8::try_from(decimals).ok())
+                            .ok_or_else(|| {
+                                anyhow!("Invalid token_decimals for network {}", name)
+                            })?,
+                    ),
+                    None => None,
+                };
+                let token_symbol = network
+                    .get("token_symbol")
+                    .and_then(|symbol| symbol.as_str())
+                    .map(|symbol| symbol.to_string());
+                let explorer = network
+                    .get("explorer")
+                    .and_then(|explorer| explorer.as_str())
+                    .map(|explorer| explorer.to_string());
+                Ok(NetworkConfig {
+                    name: name.to_string(),
+                    url: url.to_string(),
+                    token_decimals,
+                    token_symbol,
+                    explorer,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let wallets = parsed_toml
+            .get("wallets")
+            .and_then(|wallets| wallets.as_array())
+            .map(|wallets| wallets.as_slice())
+            .unwrap_or_default()
+            .iter()
+            .enumerate()
+            .map(|(i, wallet)| {
+                let name = wallet
+                    .get("name")
+                    .and_then(|name| name.as_str())
+                    .ok_or_else(|| anyhow!("Missing name for wallet {}", i + 1))?;
+                let chain = wallet
+                    .get("chain")
+                    .and_then(|chain| chain.as_str())
+                    .ok_or_else(|| anyhow!("Missing chain for wallet {}", name))?;
+                let address = wallet
+                    .get("address")
+                    .and_then(|address| address.as_str())
+                    .ok_or_else(|| anyhow!("Missing address for wallet {}", name))?;
+                let path = wallet
+                    .get("path")
+                    .and_then(|path| path.as_str())
+                    .map(|path| path.to_string());
+                Ok(WalletConfig {
+                    name: name.to_string(),
+                    chain: chain.to_string(),
+                    address: address.to_string(),
+                    path,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(AqdConfig { networks, wallets })
+    }
+
+    /// Get a network by name.
+    pub fn network(&self, name: &str) -> Option<&NetworkConfig> {
+        self.networks.iter().find(|network| network.name == name)
+    }
+
+    /// Get a network by URL, ignoring a trailing slash.
+    pub fn network_by_url(&self, url: &str) -> Option<&NetworkConfig> {
+        self.networks
+            .iter()
+            .find(|network| network.url.trim_end_matches('/') == url.trim_end_matches('/'))
+    }
+
+    /// Get a wallet by name.
+    pub fn wallet(&self, name: &str) -> Option<&WalletConfig> {
+        self.wallets.iter().find(|wallet| wallet.name == name)
+    }
+
+    /// Add a wallet to the configuration file, replacing the wallet of the same name, if any, and
+    /// return the path of the file.
+    ///
+    /// The file is created if it does not exist. Its other entries and its comments are kept.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the file cannot be read, parsed or written.
+    pub fn save_wallet(wallet: &WalletConfig) -> Result<PathBuf> {
+        let path = Self::path()
+            .ok_or_else(|| anyhow!("Failed to locate the configuration file: HOME is not set"))?;
+        let content = match path.exists() {
+            true => fs::read_to_string(&path)
+                .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?,
+            false => String::new(),
+        };
+        let content = with_wallet(&content, wallet)
+            .map_err(|e| anyhow!("Failed to parse {}: {}", path.display(), e))?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| anyhow!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        fs::write(&path, content)
+            .map_err(|e| anyhow!("Failed to write {}: {}", path.display(), e))?;
+        Ok(path)
+    }
+}
+
+/// Add a `[[wallets]]` entry to the TOML content of a configuration file, replacing the entry of
+/// the same name, if any.
+fn with_wallet(content: &str, wallet: &WalletConfig) -> Result<String> {
+    let mut document: Document = content.parse()?;
+    let wallets = document
+        .entry("wallets")
+        .or_insert(Item::ArrayOfTables(ArrayOfTables::new()))
+        .as_array_of_tables_mut()
+        .ok_or_else(|| anyhow!("wallets is not an array of tables"))?;
+    wallets.retain(|table| {
+        table.get("name").and_then(|name| name.as_str()) != Some(wallet.name.as_str())
+    });
+
+    let mut table = Table::new();
+    table["name"] = value(&wallet.name);
+    table["chain"] = value(&wallet.chain);
+    table["address"] = value(&wallet.address);
+    if let Some(path) = &wallet.path {
+        table["path"] = value(path);
+    }
+    wallets.push(table);
+    Ok(document.to_string())
+}
+
+/// A test for the `AqdConfig::from_toml` function
+#[test]
+fn test_aqd_config_from_toml() {
+    let content = r#"
+        [[networks]]
+        name = "mychain"
+        url = "wss://rpc.mychain.io"
+        token_decimals = 12
+        token_symbol = "MYC"
+        explorer = "https://mychain.subscan.io/extrinsic/{tx}"
+
+        [[networks]]
+        name = "local"
+        url = "ws://127.0.0.1:9944"
+    "#;
+    let config = AqdConfig::from_toml(content).unwrap();
+    assert_eq!(config.networks.len(), 2);
+    assert_eq!(
+        config.network("mychain"),
+        Some(&NetworkConfig {
+            name: "mychain".to_string(),
+            url: "wss://rpc.mychain.io".to_string(),
+            token_decimals: Some(12),
+            token_symbol: Some("MYC".to_string()),
+            explorer: Some("https://mychain.subscan.io/extrinsic/{tx}".to_string()),
+        })
+    );
+    assert_eq!(
+        config.network_by_url("ws://127.0.0.1:9944/").unwrap().name,
+        "local"
+    );
+    assert_eq!(config.network("local").unwrap().token_decimals, None);
+    assert!(config.network("unknown").is_none());
+
+    // A network without URL is an error
+    assert!(AqdConfig::from_toml("[[networks]]\nname = \"broken\"").is_err());
+
+    // An empty file has no networks
+    assert_eq!(AqdConfig::from_toml("").unwrap(), AqdConfig::default());
+
+    let content = r#"
+        [[wallets]]
+        name = "deployer"
+        chain = "solana"
+        address = "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU"
+        path = "deployer.json"
+    "#;
+    let config = AqdConfig::from_toml(content).unwrap();
+    assert_eq!(config.wallet("deployer").unwrap().chain, "solana");
+    assert_eq!(
+        config.wallet("deployer").unwrap().path.as_deref(),
+        Some("deployer.json")
+    );
+
+    // A wallet without address is an error
+    assert!(AqdConfig::from_toml("[[wallets]]\nname = \"broken\"\nchain = \"solana\"").is_err());
+}
+
+/// A test for the `with_wallet` function
+#[test]
+fn test_with_wallet() {
+    let content = "# My networks\n[[networks]]\nname = \"local\"\nurl = \"ws://127.0.0.1:9944\"\n";
+    let alice = WalletConfig {
+        name: "alice".to_string(),
+        chain: "polkadot".to_string(),
+        address: "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY".to_string(),
+        path: None,
+    };
+    let content = with_wallet(content, &alice).unwrap();
+    assert!(content.starts_with("# My networks"));
+    let config = AqdConfig::from_toml(&content).unwrap();
+    assert_eq!(config.networks.len(), 1);
+    assert_eq!(config.wallets, vec![alice.clone()]);
+
+    // A wallet of the same name is replaced
+    let with_path = WalletConfig {
+        path: Some("alice.json".to_string()),
+        ..alice
+    };
+    let content = with_wallet(&content, &with_path).unwrap();
+    assert_eq!(
+        AqdConfig::from_toml(&content).unwrap().wallets,
+        vec![with_path]
+    );
+}
@@ -0,0 +1,299 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::{
+        find_solang_project, is_quiet, print_text, print_title, AqdConfig, OutputFormat,
+        SolangConfig,
+    },
+    anyhow::Result,
+    colored::Colorize,
+    serde::Serialize,
+    std::{env, fs, path::Path, process::Command},
+};
+
+/// The status of a check of `aqd doctor`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    /// The check passed
+    Ok,
+    /// Nothing to check, e.g. an optional setting that is not configured
+    Info,
+    /// The check passed, but something is likely to fail later
+    Warning,
+    /// The check failed
+    Error,
+}
+
+/// The result of a check of `aqd doctor`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    /// The section of the check: `general`, `solang`, or the name of a chain
+    pub section: String,
+    /// The name of the check
+    pub check: String,
+    /// The status of the check
+    pub status: CheckStatus,
+    /// What was found, or why the check failed
+    pub detail: String,
+}
+
+impl Diagnostic {
+    /// Create the result of a check.
+    pub fn new(section: &str, check: &str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Diagnostic {
+            section: section.to_string(),
+            check: check.to_string(),
+            status,
+            detail: detail.into(),
+        }
+    }
+
+    /// Create the result of a check that passed with the detail of `result`, or failed with its
+    /// error.
+    pub fn from_result(section: &str, check: &str, result: Result<String>) -> Self {
+        match result {
+            Ok(detail) => Diagnostic::new(section, check, CheckStatus::Ok, detail),
+            Err(e) => Diagnostic::new(section, check, CheckStatus::Error, format!("{:#}", e)),
+        }
+    }
+}
+
+/// Print the results of the checks, in the output format.
+///
+/// For humans, the checks are grouped by section, with their status first. In quiet mode, only
+/// the warnings and the errors are printed.
+pub fn print_diagnostics(diagnostics: &[Diagnostic], output: OutputFormat) -> Result<()> {
+    if output.is_structured() {
+        return output.print(diagnostics);
+    }
+    let mut section = None;
+    for diagnostic in diagnostics {
+        if is_quiet() && matches!(diagnostic.status, CheckStatus::Ok | CheckStatus::Info) {
+            continue;
+        }
+        if section != Some(&diagnostic.section) {
+            print_title!(diagnostic.section);
+            section = Some(&diagnostic.section);
+        }
+        let status = match diagnostic.status {
+            CheckStatus::Ok => "ok".bold().green(),
+            CheckStatus::Info => "info".bold().blue(),
+            CheckStatus::Warning => "warning".bold().yellow(),
+            CheckStatus::Error => "error".bold().red(),
+        };
+        print_text!(format!(
+            "  {:<8} {}: {}",
+            status, diagnostic.check, diagnostic.detail
+        ));
+    }
+    Ok(())
+}
+
+/// The checks of the environment common to all the chains: the versions of aqd, of the operating
+/// system and of the Solang compiler, the aqd configuration file, and the Solang project of the
+/// current directory, if any.
+pub fn general_diagnostics(aqd_version: &str) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![
+        Diagnostic::new("general", "aqd", CheckStatus::Ok, aqd_version),
+        Diagnostic::new(
+            "general",
+            "platform",
+            CheckStatus::Ok,
+            format!("{} {}", env::consts::OS, env::consts::ARCH),
+        ),
+        match Command::new("solang").arg("--version").output() {
+            Ok(output) if output.status.success() => Diagnostic::new(
+                "general",
+                "solang",
+                CheckStatus::Ok,
+                String::from_utf8_lossy(&output.stdout).trim(),
+            ),
+            _ => Diagnostic::new(
+                "general",
+                "solang",
+                CheckStatus::Warning,
+                "solang not found in PATH: --build and aqd run cannot compile the contracts",
+            ),
+        },
+    ];
+
+    diagnostics.push(match AqdConfig::path() {
+        Some(path) if path.is_file() => Diagnostic::from_result(
+            "general",
+            "aqd config",
+            AqdConfig::load()
+                .map(|config| format!("{} ({} networks)", path.display(), config.networks.len())),
+        ),
+        Some(path) => Diagnostic::new(
+            "general",
+            "aqd config",
+            CheckStatus::Info,
+            format!("{} not found: no networks are defined", path.display()),
+        ),
+        None => Diagnostic::new(
+            "general",
+            "aqd config",
+            CheckStatus::Warning,
+            "HOME is not set: no configuration file is read",
+        ),
+    });
+
+    match env::current_dir()
+        .ok()
+        .and_then(|dir| find_solang_project(&dir))
+    {
+        Some(root) => diagnostics.extend(solang_project_diagnostics(&root)),
+        None => diagnostics.push(Diagnostic::new(
+            "solang",
+            "solang.toml",
+            CheckStatus::Info,
+            "No solang.toml in the current directory or its parents",
+        )),
+    }
+    diagnostics
+}
+
+/// The checks of the consistency of the Solang project at `root`: its `solang.toml` file is
+/// valid, for a target supported by aqd, its contracts exist, and they are compiled.
+pub fn solang_project_diagnostics(root: &Path) -> Vec<Diagnostic> {
+    let path = root.join("solang.toml");
+    let config = fs::read_to_string(&path)
+        .map_err(anyhow::Error::from)
+        .and_then(|content| SolangConfig::from_toml(&content));
+    let config = match config {
+        Ok(config) => config,
+        Err(e) => {
+            return vec![Diagnostic::new(
+                "solang",
+                "solang.toml",
+                CheckStatus::Error,
+                format!("Invalid {}: {:#}", path.display(), e),
+            )]
+        }
+    };
+    let mut diagnostics = vec![Diagnostic::new(
+        "solang",
+        "solang.toml",
+        CheckStatus::Ok,
+        path.display().to_string(),
+    )];
+
+    diagnostics.push(match config.target.as_str() {
+        "solana" | "polkadot" | "soroban" | "evm" => {
+            Diagnostic::new("solang", "target", CheckStatus::Ok, &config.target)
+        }
+        target => Diagnostic::new(
+            "solang",
+            "target",
+            CheckStatus::Error,
+            format!(
+                "Unknown target {}: expected solana, polkadot, soroban or evm",
+                target
+            ),
+        ),
+    });
+
+    let missing: Vec<&str> = config
+        .contracts
+        .iter()
+        .filter(|contract| !root.join(contract).is_file())
+        .map(String::as_str)
+        .collect();
+    diagnostics.push(match (config.contracts.is_empty(), missing.is_empty()) {
+        (true, _) => Diagnostic::new(
+            "solang",
+            "contracts",
+            CheckStatus::Warning,
+            "No contracts in the [package] section",
+        ),
+        (false, true) => Diagnostic::new(
+            "solang",
+            "contracts",
+            CheckStatus::Ok,
+            config.contracts.join(", "),
+        ),
+        (false, false) => Diagnostic::new(
+            "solang",
+            "contracts",
+            CheckStatus::Error,
+            format!("Missing contract files: {}", missing.join(", ")),
+        ),
+    });
+
+    for (check, dir) in [
+        ("output directory", config.artifacts_dir(root)),
+        ("metadata directory", config.metadata_dir(root)),
+    ] {
+        diagnostics.push(match dir.is_dir() {
+            true => Diagnostic::new("solang", check, CheckStatus::Ok, dir.display().to_string()),
+            false => Diagnostic::new(
+                "solang",
+                check,
+                CheckStatus::Warning,
+                format!(
+                    "{} not found: compile the project with solang compile or --build",
+                    dir.display()
+                ),
+            ),
+        });
+    }
+
+    if config.target != "solana" && (config.cluster.is_some() || config.wallet.is_some()) {
+        diagnostics.push(Diagnostic::new(
+            "solang",
+            "provider",
+            CheckStatus::Warning,
+            format!(
+                "The [provider] section is only used by the Solana commands, not for the {} target",
+                config.target
+            ),
+        ));
+    }
+    diagnostics
+}
+
+/// A test for the `solang_project_diagnostics` function
+#[test]
+fn test_solang_project_diagnostics() {
+    let root = env::temp_dir().join(format!("aqd-test-doctor-{}", std::process::id()));
+    fs::create_dir_all(root.join("build")).unwrap();
+    fs::write(root.join("flipper.sol"), "contract flipper {}").unwrap();
+    fs::write(
+        root.join("solang.toml"),
+        r#"
+        [package]
+        contracts = ["flipper.sol", "missing.sol"]
+
+        [target]
+        name = "polkadot"
+
+        [compiler-output]
+        output_directory = "build"
+
+        [provider]
+        cluster = "devnet"
+        "#,
+    )
+    .unwrap();
+
+    let diagnostics = solang_project_diagnostics(&root);
+    let status = |check: &str| {
+        diagnostics
+            .iter()
+            .find(|diagnostic| diagnostic.check == check)
+            .map(|diagnostic| diagnostic.status)
+    };
+    assert_eq!(status("solang.toml"), Some(CheckStatus::Ok));
+    assert_eq!(status("target"), Some(CheckStatus::Ok));
+    assert_eq!(status("contracts"), Some(CheckStatus::Error));
+    assert_eq!(status("output directory"), Some(CheckStatus::Ok));
+    assert_eq!(status("provider"), Some(CheckStatus::Warning));
+
+    fs::write(root.join("solang.toml"), "[package]").unwrap();
+    let diagnostics = solang_project_diagnostics(&root);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].status, CheckStatus::Error);
+
+    fs::remove_dir_all(root).unwrap();
+}
@@ -0,0 +1,138 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::{output::OutputFormat, redact::redact},
+    serde_json::json,
+    std::{fmt, process::exit},
+};
+
+/// The kind of an error, which determines the exit code of aqd.
+///
+/// | Kind               | Exit code | Cause                                                       |
+/// |--------------------|-----------|-------------------------------------------------------------|
+/// | `general`          | 1         | Any other error                                             |
+/// | `usage`            | 2         | Invalid arguments, or a command run in the wrong project    |
+/// | `rpc`              | 3         | The node or RPC server cannot be reached                    |
+/// | `simulation_revert`| 4         | The dry run or simulation of a transaction failed           |
+/// | `user_abort`       | 5         | The user declined the confirmation prompt                   |
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    General,
+    Usage,
+    Rpc,
+    SimulationRevert,
+    UserAbort,
+}
+
+impl ErrorKind {
+    /// The exit code of aqd for the errors of this kind.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ErrorKind::General => 1,
+            ErrorKind::Usage => 2,
+            ErrorKind::Rpc => 3,
+            ErrorKind::SimulationRevert => 4,
+            ErrorKind::UserAbort => 5,
+        }
+    }
+
+    /// The name of the kind, as reported in the JSON errors.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorKind::General => "general",
+            ErrorKind::Usage => "usage",
+            ErrorKind::Rpc => "rpc",
+            ErrorKind::SimulationRevert => "simulation_revert",
+            ErrorKind::UserAbort => "user_abort",
+        }
+    }
+
+    /// Tag an error with this kind. The message of the error is unchanged.
+    pub fn wrap(self, error: anyhow::Error) -> anyhow::Error {
+        anyhow::Error::new(KindedError {
+            kind: self,
+            source: error,
+        })
+    }
+
+    /// Get the kind of an error: the kind it, or one of its causes, was tagged with, or
+    /// [`ErrorKind::General`] by default.
+    pub fn of(error: &anyhow::Error) -> ErrorKind {
+        error
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<KindedError>())
+            .map(|kinded| kinded.kind)
+            .unwrap_or(ErrorKind::General)
+    }
+}
+
+/// An error tagged with its kind.
+#[derive(Debug)]
+struct KindedError {
+    kind: ErrorKind,
+    source: anyhow::Error,
+}
+
+impl fmt::Display for KindedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+impl std::error::Error for KindedError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source.source()
+    }
+}
+
+/// Tag the error of a result with its kind, see [`ErrorKind::wrap`].
+pub trait WithErrorKind<T> {
+    fn error_kind(self, kind: ErrorKind) -> anyhow::Result<T>;
+}
+
+impl<T> WithErrorKind<T> for anyhow::Result<T> {
+    fn error_kind(self, kind: ErrorKind) -> anyhow::Result<T> {
+        self.map_err(|error| kind.wrap(error))
+    }
+}
+
+/// Report an error on stderr and exit with the code of its kind.
+///
+/// With the JSON output format, the error is reported as
+/// `{"error": {"kind": ..., "message": ...}}`, for scripts to handle it. The registered secrets
+/// are redacted from the message.
+pub fn exit_with_error(error: &anyhow::Error, output: OutputFormat) -> ! {
+    let kind = ErrorKind::of(error);
+    if output == OutputFormat::Json {
+        eprintln!(
+            "{}",
+            json!({
+                "error": {
+                    "kind": kind.as_str(),
+                    "message": redact(&error.to_string()),
+                }
+            })
+        );
+    } else {
+        eprintln!("{}", redact(&error.to_string()));
+    }
+    exit(kind.exit_code())
+}
+
+/// A test for the `ErrorKind::of` function
+#[test]
+fn test_error_kind_of() {
+    let error = anyhow::anyhow!("Connection refused");
+    assert_eq!(ErrorKind::of(&error), ErrorKind::General);
+
+    // The message is unchanged, and the kind is kept through the contexts
+    let error = ErrorKind::Rpc.wrap(error);
+    assert_eq!(error.to_string(), "Connection refused");
+    assert_eq!(ErrorKind::of(&error), ErrorKind::Rpc);
+    let error = error.context("Failed to upload the contract");
+    assert_eq!(ErrorKind::of(&error), ErrorKind::Rpc);
+
+    let result: anyhow::Result<()> = Err(anyhow::anyhow!("Transaction not submitted"));
+    let error = result.error_kind(ErrorKind::UserAbort).unwrap_err();
+    assert_eq!(ErrorKind::of(&error).exit_code(), 5);
+}
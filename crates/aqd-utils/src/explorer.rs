@@ -0,0 +1,61 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {crate::AqdConfig, url::form_urlencoded::byte_serialize};
+
+/// The page of a block of Polkadot.js Apps, connected to the node of the transaction, used for the
+/// networks without a block explorer.
+pub const POLKADOT_APPS_EXPLORER: &str =
+    "https://polkadot.js.org/apps/?rpc={rpc}#/explorer/query/{block}";
+
+/// Build the URL of the block explorer page of a transaction from a URL template, replacing:
+///
+/// - `{tx}` with the hash of the transaction: the signature of a Solana transaction, or the hash
+///   of a Polkadot extrinsic;
+/// - `{block}` with the hash of the block of the transaction, if known;
+/// - `{rpc}` with the URL-encoded URL of the RPC endpoint the transaction was sent to.
+pub fn explorer_url(template: &str, tx: &str, block: Option<&str>, rpc_url: &str) -> String {
+    let rpc_url: String = byte_serialize(rpc_url.as_bytes()).collect();
+    template
+        .replace("{tx}", tx)
+        .replace("{block}", block.unwrap_or_default())
+        .replace("{rpc}", &rpc_url)
+}
+
+/// The explorer URL template of the network of the aqd configuration file named `name`, or else
+/// whose URL is `rpc_url`, if it defines one.
+///
+/// A configuration file that cannot be loaded is ignored, as the explorer link is printed after the
+/// transaction is sent.
+pub fn configured_explorer(name: Option<&str>, rpc_url: &str) -> Option<String> {
+    let config = AqdConfig::load().unwrap_or_default();
+    name.and_then(|name| config.network(name))
+        .or_else(|| config.network_by_url(rpc_url))
+        .and_then(|network| network.explorer.clone())
+}
+
+/// A test for the `explorer_url` function
+#[test]
+fn test_explorer_url() {
+    assert_eq!(
+        explorer_url(
+            "https://explorer.solana.com/tx/{tx}?cluster=custom&customUrl={rpc}",
+            "5sig",
+            None,
+            "http://localhost:8899"
+        ),
+        "https://explorer.solana.com/tx/5sig?cluster=custom&customUrl=http%3A%2F%2Flocalhost%3A8899"
+    );
+    assert_eq!(
+        explorer_url(
+            POLKADOT_APPS_EXPLORER,
+            "0xabc",
+            Some("0xdef"),
+            "ws://127.0.0.1:9944"
+        ),
+        "https://polkadot.js.org/apps/?rpc=ws%3A%2F%2F127.0.0.1%3A9944#/explorer/query/0xdef"
+    );
+    assert_eq!(
+        explorer_url("https://astar.subscan.io/extrinsic/{tx}", "0xabc", None, ""),
+        "https://astar.subscan.io/extrinsic/0xabc"
+    );
+}
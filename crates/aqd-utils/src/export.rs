@@ -0,0 +1,166 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    serde_json::{Map, Value},
+    std::{
+        fs::File,
+        io::{BufWriter, Write},
+        path::Path,
+        str::FromStr,
+    },
+};
+
+/// The file formats supported when exporting records.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// One JSON object per line
+    Jsonl,
+    /// Comma-separated values, with nested objects flattened into dotted column names
+    Csv,
+}
+
+impl FromStr for ExportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "jsonl" => Ok(ExportFormat::Jsonl),
+            "csv" => Ok(ExportFormat::Csv),
+            _ => Err(anyhow!(
+                "Invalid export format: {}. Supported formats: jsonl, csv",
+                s
+            )),
+        }
+    }
+}
+
+/// Export a list of JSON records to a file in the given format.
+///
+/// For CSV, the columns are the union of the (flattened) keys of all the records, in the order they
+/// first appear. Missing values are left empty.
+///
+/// Returns an error if the file cannot be created or written.
+pub fn export_records<P: AsRef<Path>>(
+    records: &[Value],
+    format: ExportFormat,
+    path: P,
+) -> Result<()> {
+    let path = path.as_ref();
+    let file = File::create(path)
+        .map_err(|e| anyhow!("Failed to create export file {}: {}", path.display(), e))?;
+    let mut writer = BufWriter::new(file);
+    write_records(records, format, &mut writer)
+        .map_err(|e| anyhow!("Failed to write export file {}: {}", path.display(), e))?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Write a list of JSON records to a writer in the given format.
+pub fn write_records<W: Write>(
+    records: &[Value],
+    format: ExportFormat,
+    writer: &mut W,
+) -> Result<()> {
+    match format {
+        ExportFormat::Jsonl => {
+            for record in records {
+                writeln!(writer, "{}", serde_json::to_string(record)?)?;
+            }
+        }
+        ExportFormat::Csv => {
+            let rows: Vec<Map<String, Value>> = records
+                .iter()
+                .map(|record| {
+                    let mut row = Map::new();
+                    flatten_json("", record, &mut row);
+                    row
+                })
+                .collect();
+
+            // Collect the columns in the order they first appear
+            let mut columns: Vec<String> = vec![];
+            for row in &rows {
+                for key in row.keys() {
+                    if !columns.contains(key) {
+                        columns.push(key.clone());
+                    }
+                }
+            }
+
+            let header: Vec<String> = columns.iter().map(|c| csv_escape(c)).collect();
+            writeln!(writer, "{}", header.join(","))?;
+            for row in &rows {
+                let line: Vec<String> = columns
+                    .iter()
+                    .map(|column| match row.get(column) {
+                        None | Some(Value::Null) => String::new(),
+                        Some(Value::String(s)) => csv_escape(s),
+                        Some(value) => csv_escape(&value.to_string()),
+                    })
+                    .collect();
+                writeln!(writer, "{}", line.join(","))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Flatten nested JSON objects into a single map with dotted keys.
+///
+/// Arrays are kept as values, and serialized as JSON in the CSV cell.
+pub(crate) fn flatten_json(prefix: &str, value: &Value, output: &mut Map<String, Value>) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                let key = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                flatten_json(&key, value, output);
+            }
+        }
+        _ => {
+            let key = if prefix.is_empty() { "value" } else { prefix };
+            output.insert(key.to_string(), value.clone());
+        }
+    }
+}
+
+/// Escape a CSV field, quoting it if it contains a separator, a quote or a line break.
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// A test for the `write_records` function
+#[test]
+fn test_write_records() {
+    let records = vec![
+        serde_json::json!({"data": {"count": 1, "name": "x,y"}, "pubkey": "A"}),
+        serde_json::json!({"data": {"count": 2, "tags": [1, 2]}, "pubkey": "B"}),
+    ];
+
+    // JSONL writes one record per line
+    let mut output: Vec<u8> = vec![];
+    write_records(&records, ExportFormat::Jsonl, &mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert_eq!(output.lines().count(), 2);
+    assert_eq!(
+        serde_json::from_str::<Value>(output.lines().next().unwrap()).unwrap(),
+        records[0]
+    );
+
+    // CSV flattens nested objects, and escapes separators and quotes
+    let mut output: Vec<u8> = vec![];
+    write_records(&records, ExportFormat::Csv, &mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert_eq!(
+        output,
+        "data.count,data.name,pubkey,data.tags\n1,\"x,y\",A,\n2,,B,\"[1,2]\"\n"
+    );
+}
@@ -0,0 +1,142 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::{quiet::is_quiet, redact::redact},
+    anyhow::{anyhow, Result},
+    colored::Colorize,
+    std::{
+        fs,
+        path::{Path, PathBuf},
+        process::exit,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Mutex,
+        },
+    },
+};
+
+/// The exit code of aqd when interrupted with Ctrl-C, as for the shells: 128 + SIGINT.
+const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// The files generated for a transaction that is not submitted yet, such as the keypairs of the
+/// `new` Solana accounts.
+static GENERATED_FILES: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Whether the transaction of the generated files was sent, so that its outcome may be unknown.
+static SENT: AtomicBool = AtomicBool::new(false);
+
+/// Register a file generated for a transaction, to remove it if the transaction is not submitted.
+pub fn register_generated_file<P: AsRef<Path>>(path: P) {
+    GENERATED_FILES
+        .lock()
+        .unwrap()
+        .push(path.as_ref().to_path_buf());
+}
+
+/// Mark the transaction of the generated files as sent, right before sending it.
+///
+/// From then on, the generated files are kept even if the transaction fails or is interrupted,
+/// since the accounts may already exist on chain.
+pub fn mark_generated_files_sent() {
+    SENT.store(true, Ordering::SeqCst);
+}
+
+/// Keep the generated files, once their transaction is submitted.
+pub fn keep_generated_files() {
+    GENERATED_FILES.lock().unwrap().clear();
+    SENT.store(false, Ordering::SeqCst);
+}
+
+/// Remove the generated files, and return the files removed and those that could not be.
+pub fn remove_generated_files() -> (Vec<PathBuf>, Vec<PathBuf>) {
+    let files: Vec<PathBuf> = GENERATED_FILES.lock().unwrap().drain(..).collect();
+    files
+        .into_iter()
+        .filter(|file| file.exists())
+        .partition(|file| fs::remove_file(file).is_ok())
+}
+
+/// Remove the generated files of a transaction that failed or was interrupted, and report them on
+/// stderr.
+///
+/// If the transaction was sent, its outcome is unknown: the files are kept instead, and their
+/// paths are printed even in quiet mode.
+pub fn cleanup_generated_files() {
+    if SENT.swap(false, Ordering::SeqCst) {
+        let files: Vec<PathBuf> = GENERATED_FILES.lock().unwrap().drain(..).collect();
+        for file in files.iter().filter(|file| file.exists()) {
+            eprintln!(
+                "{} {}",
+                "Warning:".bold().yellow(),
+                redact(&format!(
+                    "Kept the generated file {}, its transaction may have been submitted",
+                    file.display()
+                ))
+                .yellow()
+            );
+        }
+        return;
+    }
+    let (removed, remaining) = remove_generated_files();
+    if is_quiet() {
+        return;
+    }
+    for file in removed {
+        eprintln!(
+            "{}",
+            redact(&format!("Removed the generated file {}", file.display()))
+        );
+    }
+    for file in remaining {
+        eprintln!(
+            "{} {}",
+            "Warning:".bold().yellow(),
+            redact(&format!(
+                "Failed to remove the generated file {}, remove it manually",
+                file.display()
+            ))
+            .yellow()
+        );
+    }
+}
+
+/// Handle Ctrl-C by removing the generated files of the transaction in progress before exiting, or
+/// by keeping them if the transaction was already sent.
+pub fn install_interrupt_handler() -> Result<()> {
+    ctrlc::set_handler(|| {
+        cleanup_generated_files();
+        exit(INTERRUPTED_EXIT_CODE);
+    })
+    .map_err(|e| anyhow!("Failed to set the Ctrl-C handler: {}", e))
+}
+
+/// A test for the `remove_generated_files` function
+#[test]
+fn test_remove_generated_files() {
+    let dir = std::env::temp_dir();
+    let kept = dir.join(format!("aqd-kept-{}.json", std::process::id()));
+    let removed = dir.join(format!("aqd-removed-{}.json", std::process::id()));
+    fs::write(&kept, "[]").unwrap();
+    fs::write(&removed, "[]").unwrap();
+
+    // The files of a submitted transaction are kept
+    register_generated_file(&kept);
+    keep_generated_files();
+    register_generated_file(&removed);
+    assert_eq!(remove_generated_files(), (vec![removed.clone()], vec![]));
+    assert!(kept.exists());
+    assert!(!removed.exists());
+
+    // The files are removed once
+    assert_eq!(remove_generated_files(), (vec![], vec![]));
+
+    // The files of a sent transaction are kept by the cleanup, as its outcome is unknown
+    register_generated_file(&removed);
+    fs::write(&removed, "[]").unwrap();
+    mark_generated_files_sent();
+    cleanup_generated_files();
+    assert!(removed.exists());
+    assert_eq!(remove_generated_files(), (vec![], vec![]));
+    fs::remove_file(kept).unwrap();
+    fs::remove_file(removed).unwrap();
+}
@@ -0,0 +1,277 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::{quiet::is_quiet, redact::redact},
+    anyhow::{anyhow, Result},
+    chrono::{DateTime, Utc},
+    colored::Colorize,
+    serde::{Deserialize, Serialize},
+    std::{
+        env,
+        fs::{self, OpenOptions},
+        io::Write,
+        path::{Path, PathBuf},
+    },
+};
+
+/// The environment variable that overrides the path of the history file.
+pub const AQD_HISTORY_ENV: &str = "AQD_HISTORY";
+
+/// The environment variable that disables the recording of the history when set.
+pub const AQD_NO_HISTORY_ENV: &str = "AQD_NO_HISTORY";
+
+/// The outcome of a recorded transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryOutcome {
+    Success,
+    Failure,
+}
+
+/// A deployment or a call submitted by aqd, recorded in the history.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// The time the transaction was submitted
+    pub timestamp: DateTime<Utc>,
+    /// The chain (`solana`, `polkadot`, `soroban` or `evm`)
+    pub chain: String,
+    /// The action (`deploy`, `upload`, `instantiate`, `call`, ...)
+    pub action: String,
+    /// The signature, extrinsic hash or transaction hash, if the transaction was submitted
+    pub hash: Option<String>,
+    /// The program ID, contract address or code hash
+    pub contract: Option<String>,
+    /// The instruction, message or function called
+    pub function: Option<String>,
+    pub outcome: HistoryOutcome,
+    /// The error, if the transaction failed
+    pub error: Option<String>,
+}
+
+impl HistoryEntry {
+    /// Create a successful entry of the given chain and action, submitted now.
+    pub fn new(chain: &str, action: &str) -> Self {
+        HistoryEntry {
+            timestamp: Utc::now(),
+            chain: chain.to_string(),
+            action: action.to_string(),
+            hash: None,
+            contract: None,
+            function: None,
+            outcome: HistoryOutcome::Success,
+            error: None,
+        }
+    }
+
+    /// Set the hash of the transaction.
+    pub fn hash<T: ToString>(mut self, hash: T) -> Self {
+        self.hash = Some(hash.to_string());
+        self
+    }
+
+    /// Set the contract of the transaction.
+    pub fn contract<T: ToString>(mut self, contract: T) -> Self {
+        self.contract = Some(contract.to_string());
+        self
+    }
+
+    /// Set the function called by the transaction.
+    pub fn function<T: ToString>(mut self, function: T) -> Self {
+        self.function = Some(function.to_string());
+        self
+    }
+
+    /// Mark the transaction as failed with the given error.
+    pub fn failed(mut self, error: &anyhow::Error) -> Self {
+        self.outcome = HistoryOutcome::Failure;
+        self.error = Some(crate::redact(&error.to_string()));
+        self
+    }
+}
+
+/// The criteria to select the entries of the history. Unset criteria match all the entries.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HistoryFilter {
+    pub chain: Option<String>,
+    pub action: Option<String>,
+    pub contract: Option<String>,
+    pub function: Option<String>,
+    pub outcome: Option<HistoryOutcome>,
+    /// Only the entries submitted at or after this time
+    pub since: Option<DateTime<Utc>>,
+}
+
+impl HistoryFilter {
+    /// Check whether an entry matches all the criteria.
+    pub fn matches(&self, entry: &HistoryEntry) -> bool {
+        let matches = |criterion: &Option<String>, value: &Option<String>| match criterion {
+            Some(criterion) => value.as_deref() == Some(criterion.as_str()),
+            None => true,
+        };
+        self.chain
+            .as_ref()
+            .is_none_or(|chain| chain == &entry.chain)
+            && self
+                .action
+                .as_ref()
+                .is_none_or(|action| action == &entry.action)
+            && matches(&self.contract, &entry.contract)
+            && matches(&self.function, &entry.function)
+            && self.outcome.is_none_or(|outcome| outcome == entry.outcome)
+            && self.since.is_none_or(|since| entry.timestamp >= since)
+    }
+}
+
+/// The local history of the transactions submitted by aqd, for auditing.
+///
+/// The history is stored as a file of JSON lines, one entry per line, at the path in the
+/// `AQD_HISTORY` environment variable, or at `~/.local/share/aqd/history.jsonl` by default.
+pub struct History {
+    path: PathBuf,
+}
+
+impl History {
+    /// Open the history at its default path.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the path cannot be determined.
+    pub fn open() -> Result<Self> {
+        if let Ok(path) = env::var(AQD_HISTORY_ENV) {
+            return Ok(History::at(path));
+        }
+        let home = env::var("HOME")
+            .map_err(|_| anyhow!("Failed to locate the history: HOME is not set"))?;
+        Ok(History::at(
+            PathBuf::from(home)
+                .join(".local")
+                .join("share")
+                .join("aqd")
+                .join("history.jsonl"),
+        ))
+    }
+
+    /// Open the history stored in the given file.
+    pub fn at<P: AsRef<Path>>(path: P) -> Self {
+        History {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// The path of the history file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append an entry to the history, creating the file if needed.
+    pub fn append(&self, entry: &HistoryEntry) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| anyhow!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| anyhow!("Failed to open {}: {}", self.path.display(), e))?;
+        writeln!(file, "{}", serde_json::to_string(entry)?)
+            .map_err(|e| anyhow!("Failed to write {}: {}", self.path.display(), e))
+    }
+
+    /// Read the entries of the history matching the filter, in the order they were recorded.
+    ///
+    /// Returns no entries if the history file does not exist.
+    pub fn entries(&self, filter: &HistoryFilter) -> Result<Vec<HistoryEntry>> {
+        if !self.path.exists() {
+            return Ok(vec![]);
+        }
+        let content = fs::read_to_string(&self.path)
+            .map_err(|e| anyhow!("Failed to read {}: {}", self.path.display(), e))?;
+        let mut entries = vec![];
+        for (i, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: HistoryEntry = serde_json::from_str(line).map_err(|e| {
+                anyhow!("Invalid entry at {}:{}: {}", self.path.display(), i + 1, e)
+            })?;
+            if filter.matches(&entry) {
+                entries.push(entry);
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Remove all the entries of the history.
+    pub fn clear(&self) -> Result<()> {
+        if self.path.exists() {
+            fs::remove_file(&self.path)
+                .map_err(|e| anyhow!("Failed to remove {}: {}", self.path.display(), e))?;
+        }
+        Ok(())
+    }
+}
+
+/// Record a submitted transaction in the history, unless `AQD_NO_HISTORY` is set.
+///
+/// Failing to record the transaction does not fail the command: a warning is printed to stderr
+/// instead, so that the output of the command is not altered.
+pub fn record_history(entry: HistoryEntry) {
+    if env::var_os(AQD_NO_HISTORY_ENV).is_some() {
+        return;
+    }
+    if let Err(e) = History::open().and_then(|history| history.append(&entry)) {
+        if !is_quiet() {
+            eprintln!(
+                "{} {}",
+                "Warning:".bold().yellow(),
+                redact(&format!(
+                    "Failed to record the transaction in the history: {}",
+                    e
+                ))
+                .yellow()
+            );
+        }
+    }
+}
+
+/// A test for the `History::append` and `History::entries` functions
+#[test]
+fn test_history_entries() {
+    let path = env::temp_dir().join(format!("aqd-history-{}.jsonl", std::process::id()));
+    let history = History::at(&path);
+    history.clear().unwrap();
+    assert!(history
+        .entries(&HistoryFilter::default())
+        .unwrap()
+        .is_empty());
+
+    let deploy = HistoryEntry::new("solana", "deploy")
+        .hash("5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnbJLgp8uirBgmQpjKhoR4tjF3ZpRzrFmBV6UjKdiSZkQUW")
+        .contract("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+    let call = HistoryEntry::new("evm", "call")
+        .contract("0x5fbdb2315678afecb367f032d93f642f64180aa3")
+        .function("flip")
+        .failed(&anyhow!("Transaction reverted"));
+    history.append(&deploy).unwrap();
+    history.append(&call).unwrap();
+
+    assert_eq!(
+        history.entries(&HistoryFilter::default()).unwrap(),
+        vec![deploy.clone(), call.clone()]
+    );
+    let filter = HistoryFilter {
+        chain: Some("evm".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(history.entries(&filter).unwrap(), vec![call]);
+    let filter = HistoryFilter {
+        outcome: Some(HistoryOutcome::Success),
+        function: Some("flip".to_string()),
+        ..Default::default()
+    };
+    assert!(history.entries(&filter).unwrap().is_empty());
+
+    history.clear().unwrap();
+    assert!(!path.exists());
+}
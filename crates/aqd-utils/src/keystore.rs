@@ -0,0 +1,238 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::redact::register_secret,
+    anyhow::{anyhow, Result},
+    chacha20poly1305::{
+        aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng},
+        ChaCha20Poly1305, Key, Nonce,
+    },
+    serde_json::{json, Value},
+    sha2::Sha256,
+    std::path::Path,
+};
+
+/// The version of the keystore format.
+const KEYSTORE_VERSION: u64 = 1;
+
+/// The number of PBKDF2 iterations used to derive the encryption key from the password.
+const PBKDF2_ITERATIONS: u32 = 600_000;
+
+/// The length of the salt used to derive the encryption key.
+const SALT_LENGTH: usize = 16;
+
+/// The length of the ChaCha20-Poly1305 nonce.
+const NONCE_LENGTH: usize = 12;
+
+/// Derive the encryption key of a keystore from its password.
+fn derive_key(password: &str, salt: &[u8], iterations: u32) -> Key {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, iterations, &mut key);
+    Key::from(key)
+}
+
+/// Encrypt a secret with a password, into the keystore format shared by the Solana keypairs and
+/// the Polkadot secret key URIs.
+///
+/// The encryption key is derived from the password with PBKDF2-SHA256 (600,000 iterations), and
+/// the secret is encrypted with ChaCha20-Poly1305, an authenticated cipher. This is used instead of
+/// age or scrypt with AES, as both primitives are already dependencies of the Solana and Polkadot
+/// SDKs. The keystore is a JSON object, to which the callers may add fields kept in clear:
+///
+/// ```json
+/// {
+///   "version": 1,
+///   "kdf": { "name": "pbkdf2-sha256", "iterations": 600000, "salt": "<hex>" },
+///   "cipher": { "name": "chacha20poly1305", "nonce": "<hex>" },
+///   "ciphertext": "<hex>"
+/// }
+/// ```
+pub fn encrypt_secret(secret: &[u8], password: &str) -> Result<Value> {
+    encrypt_with_iterations(secret, password, PBKDF2_ITERATIONS)
+}
+
+/// Encrypt a secret with a password, deriving the encryption key with the given number of PBKDF2
+/// iterations.
+fn encrypt_with_iterations(secret: &[u8], password: &str, iterations: u32) -> Result<Value> {
+    let mut salt = [0u8; SALT_LENGTH];
+    OsRng.fill_bytes(&mut salt);
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let cipher = ChaCha20Poly1305::new(&derive_key(password, &salt, iterations));
+    let ciphertext = cipher
+        .encrypt(&nonce, secret)
+        .map_err(|_| anyhow!("Failed to encrypt the keystore"))?;
+    Ok(json!({
+        "version": KEYSTORE_VERSION,
+        "kdf": {
+            "name": "pbkdf2-sha256",
+            "iterations": iterations,
+            "salt": hex::encode(salt),
+        },
+        "cipher": {
+            "name": "chacha20poly1305",
+            "nonce": hex::encode(nonce),
+        },
+        "ciphertext": hex::encode(ciphertext),
+    }))
+}
+
+/// Decrypt the secret of a keystore with a password.
+///
+/// # Errors
+///
+/// This function returns an error if the keystore is invalid or of an unsupported version or
+/// encryption, if the password is wrong, or if the ciphertext was tampered with.
+pub fn decrypt_secret(keystore: &Value, password: &str) -> Result<Vec<u8>> {
+    let field = |pointer: &str| {
+        keystore
+            .pointer(pointer)
+            .ok_or_else(|| anyhow!("Missing {} in the keystore", pointer))
+    };
+    let hex_field = |pointer: &str| -> Result<Vec<u8>> {
+        let value = field(pointer)?
+            .as_str()
+            .ok_or_else(|| anyhow!("Invalid {} in the keystore", pointer))?;
+        hex::decode(value).map_err(|e| anyhow!("Invalid {} in the keystore: {}", pointer, e))
+    };
+
+    if field("/version")?.as_u64() != Some(KEYSTORE_VERSION) {
+        return Err(anyhow!("Unsupported keystore version"));
+    }
+    if field("/kdf/name")?.as_str() != Some("pbkdf2-sha256")
+        || field("/cipher/name")?.as_str() != Some("chacha20poly1305")
+    {
+        return Err(anyhow!("Unsupported keystore encryption"));
+    }
+    let iterations = field("/kdf/iterations")?
+        .as_u64()
+        .and_then(|iterations| u32::try_from(iterations).ok())
+        .ok_or_else(|| anyhow!("Invalid /kdf/iterations in the keystore"))?;
+    let salt = hex_field("/kdf/salt")?;
+    let nonce = hex_field("/cipher/nonce")?;
+    if nonce.len() != NONCE_LENGTH {
+        return Err(anyhow!("Invalid /cipher/nonce in the keystore"));
+    }
+    let ciphertext = hex_field("/ciphertext")?;
+
+    let cipher = ChaCha20Poly1305::new(&derive_key(password, &salt, iterations));
+    cipher
+        .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+        .map_err(|_| anyhow!("Failed to decrypt the keystore: wrong password"))
+}
+
+/// Check whether a JSON value is a keystore, rather than e.g. a plaintext keypair.
+pub fn is_keystore(value: &Value) -> bool {
+    value.get("ciphertext").is_some()
+}
+
+/// Prompt the user for the password of a keystore file. The password is registered as a secret.
+pub fn prompt_keystore_password(path: &Path) -> Result<String> {
+    let password = rpassword::prompt_password(format!("Password for {}: ", path.display()))?;
+    register_secret(&password);
+    Ok(password)
+}
+
+/// Prompt the user for the password of new keystores, twice to catch typos. The password is
+/// registered as a secret.
+pub fn prompt_new_password() -> Result<String> {
+    let password = rpassword::prompt_password("Keystore password: ")?;
+    register_secret(&password);
+    if password.is_empty() {
+        return Err(anyhow!("The keystore password cannot be empty"));
+    }
+    if password != rpassword::prompt_password("Repeat the keystore password: ")? {
+        return Err(anyhow!("The passwords do not match"));
+    }
+    Ok(password)
+}
+
+/// A test for the `encrypt_secret` and `decrypt_secret` functions
+#[test]
+fn test_keystore() {
+    // The key derivation of the keystores takes seconds without optimizations
+    let encrypt_secret = |secret, password| encrypt_with_iterations(secret, password, 1_000);
+    let secret = b"//Alice";
+    let keystore = encrypt_secret(secret, "correct horse").unwrap();
+    assert!(is_keystore(&keystore));
+    assert!(!is_keystore(&json!([1, 2, 3])));
+    assert_eq!(decrypt_secret(&keystore, "correct horse").unwrap(), secret);
+    assert!(!keystore.to_string().contains(&hex::encode(secret)));
+
+    // Each keystore has its own salt and nonce
+    let other = encrypt_secret(secret, "correct horse").unwrap();
+    assert_ne!(keystore["kdf"]["salt"], other["kdf"]["salt"]);
+    assert_ne!(keystore["cipher"]["nonce"], other["cipher"]["nonce"]);
+
+    // A keystore encrypted by another implementation of PBKDF2-SHA256 and ChaCha20-Poly1305
+    let vector = json!({
+        "version": 1,
+        "kdf": {
+            "name": "pbkdf2-sha256",
+            "iterations": 1000,
+            "salt": "000102030405060708090a0b0c0d0e0f",
+        },
+        "cipher": { "name": "chacha20poly1305", "nonce": "000102030405060708090a0b" },
+        "ciphertext": "b9a9aa6222852fe674e6103ae2e70e775e03549f0de2f5",
+    });
+    assert_eq!(decrypt_secret(&vector, "correct horse").unwrap(), secret);
+
+    let error = decrypt_secret(&keystore, "wrong horse").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Failed to decrypt the keystore: wrong password"
+    );
+
+    // The authentication tag catches a truncated or tampered ciphertext
+    let ciphertext = keystore["ciphertext"].as_str().unwrap();
+    let mut truncated = keystore.clone();
+    truncated["ciphertext"] = json!(ciphertext[..ciphertext.len() - 2]);
+    assert!(decrypt_secret(&truncated, "correct horse").is_err());
+    let mut tampered = keystore.clone();
+    let flipped = if ciphertext.starts_with('0') {
+        "1"
+    } else {
+        "0"
+    };
+    tampered["ciphertext"] = json!(format!("{}{}", flipped, &ciphertext[1..]));
+    assert!(decrypt_secret(&tampered, "correct horse").is_err());
+    let mut tampered = keystore.clone();
+    tampered["ciphertext"] = json!("not hex");
+    assert!(decrypt_secret(&tampered, "correct horse")
+        .unwrap_err()
+        .to_string()
+        .starts_with("Invalid /ciphertext in the keystore"));
+
+    // The unknown versions, key derivations and ciphers are rejected
+    let mut unknown = keystore.clone();
+    unknown["version"] = json!(2);
+    assert_eq!(
+        decrypt_secret(&unknown, "correct horse")
+            .unwrap_err()
+            .to_string(),
+        "Unsupported keystore version"
+    );
+    let mut unknown = keystore.clone();
+    unknown["kdf"]["name"] = json!("scrypt");
+    assert_eq!(
+        decrypt_secret(&unknown, "correct horse")
+            .unwrap_err()
+            .to_string(),
+        "Unsupported keystore encryption"
+    );
+    let mut unknown = keystore.clone();
+    unknown["cipher"]["name"] = json!("aes-128-ctr");
+    assert_eq!(
+        decrypt_secret(&unknown, "correct horse")
+            .unwrap_err()
+            .to_string(),
+        "Unsupported keystore encryption"
+    );
+    let mut missing = keystore;
+    missing["kdf"].as_object_mut().unwrap().remove("salt");
+    assert_eq!(
+        decrypt_secret(&missing, "correct horse")
+            .unwrap_err()
+            .to_string(),
+        "Missing /kdf/salt in the keystore"
+    );
+}
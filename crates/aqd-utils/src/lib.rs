@@ -1,6 +1,80 @@
 // SPDX-License-Identifier: Apache-2.0
 
+mod bench;
+mod cassette;
+mod config;
+mod doctor;
+mod error;
+mod explorer;
+mod export;
+mod generated_files;
+mod history;
+mod keystore;
+mod output;
+mod playbook;
 pub mod printing_macros;
+mod progress;
+mod proxy;
+mod quiet;
+mod rate_limit;
+mod redact;
+mod release_manifest;
+mod report;
+mod rpc_headers;
+mod solang_build;
+mod solang_config;
+mod timeouts;
 mod utils;
+mod wallet;
 
-pub use utils::{check_target_match, prompt_confirm_transaction};
+pub use {
+    bench::{
+        parse_concurrency, parse_iterations, run_bench, BenchReport, BenchSample, LatencyStats,
+    },
+    cassette::{
+        cassette_mode, is_replaying, record_interaction, record_notification, replay_interaction,
+        save_cassette, set_cassette, Cassette, CassetteMode, Interaction, AQD_RECORD_ENV,
+        AQD_REPLAY_ENV,
+    },
+    config::{AqdConfig, NetworkConfig, WalletConfig, AQD_CONFIG_ENV},
+    doctor::{
+        general_diagnostics, print_diagnostics, solang_project_diagnostics, CheckStatus, Diagnostic,
+    },
+    error::{exit_with_error, ErrorKind, WithErrorKind},
+    explorer::{configured_explorer, explorer_url, POLKADOT_APPS_EXPLORER},
+    export::{export_records, write_records, ExportFormat},
+    generated_files::{
+        cleanup_generated_files, install_interrupt_handler, keep_generated_files,
+        mark_generated_files_sent, register_generated_file, remove_generated_files,
+    },
+    history::{
+        record_history, History, HistoryEntry, HistoryFilter, HistoryOutcome, AQD_HISTORY_ENV,
+        AQD_NO_HISTORY_ENV,
+    },
+    keystore::{
+        decrypt_secret, encrypt_secret, is_keystore, prompt_keystore_password, prompt_new_password,
+    },
+    output::{render_table, set_table_style, table_style, OutputFormat, RecordStream, TableStyle},
+    playbook::{lookup, Playbook, PlaybookCommand, PlaybookContext, PlaybookStep},
+    progress::{
+        emit_progress, progress_format, set_progress, ProgressEvent, ProgressFormat, ProgressStage,
+    },
+    proxy::{parse_proxy, proxy_for, set_proxy},
+    quiet::{is_quiet, set_quiet},
+    rate_limit::{parse_burst, parse_rate_limit, rate_limit, rate_limit_delay, set_rate_limit},
+    redact::{redact, register_secret, register_secret_uri, RedactingWriter, REDACTED},
+    release_manifest::{
+        deployed_address, ContractOverrides, ReleaseContract, ReleaseEnvironment, ReleaseManifest,
+        ReleaseStep,
+    },
+    report::{
+        report, set_reporter, CaptureReporter, HumanReporter, JsonReporter, QuietReporter, Report,
+        Reporter, Theme,
+    },
+    rpc_headers::{parse_rpc_header, register_secret_url, rpc_headers, set_rpc_headers, RpcHeader},
+    solang_build::{find_solang_project, solang_compile},
+    solang_config::SolangConfig,
+    timeouts::{connect_timeout, parse_timeout, rpc_timeout, set_timeouts},
+    utils::{check_target_match, parse_jobs, prompt_confirm_transaction},
+    wallet::WalletKey,
+};
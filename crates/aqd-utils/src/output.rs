@@ -0,0 +1,423 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::{export::flatten_json, redact::redact},
+    anyhow::{anyhow, Result},
+    serde::Serialize,
+    serde_json::{Map, Value},
+    std::{
+        fmt,
+        str::FromStr,
+        sync::atomic::{AtomicBool, Ordering},
+    },
+};
+
+/// Whether the tables are drawn with unicode borders, set once from the `--table-style` flag.
+static UNICODE_TABLES: AtomicBool = AtomicBool::new(false);
+
+/// The formats of the output of the commands, selected with `--output`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Titles and key-value pairs, for humans to read
+    #[default]
+    Human,
+    /// Pretty-printed JSON
+    Json,
+    /// YAML
+    Yaml,
+    /// An aligned table, with nested objects flattened into dotted column names
+    Table,
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "human" => Ok(OutputFormat::Human),
+            "json" => Ok(OutputFormat::Json),
+            "yaml" => Ok(OutputFormat::Yaml),
+            "table" => Ok(OutputFormat::Table),
+            _ => Err(anyhow!(
+                "Invalid output format: {}. Supported formats: human, json, yaml, table",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputFormat::Human => write!(f, "human"),
+            OutputFormat::Json => write!(f, "json"),
+            OutputFormat::Yaml => write!(f, "yaml"),
+            OutputFormat::Table => write!(f, "table"),
+        }
+    }
+}
+
+impl OutputFormat {
+    /// Check whether the output is structured data (JSON, YAML or a table) rather than text for
+    /// humans to read.
+    pub fn is_structured(&self) -> bool {
+        *self != OutputFormat::Human
+    }
+
+    /// Format a value as a document in the output format. The human format falls back to JSON,
+    /// as the commands print their human output themselves.
+    ///
+    /// The registered secrets are redacted from the document.
+    pub fn format<T: Serialize + ?Sized>(&self, value: &T) -> Result<String> {
+        let document = match self {
+            OutputFormat::Human | OutputFormat::Json => serde_json::to_string_pretty(value)?,
+            OutputFormat::Yaml => serde_yaml::to_string(value)?.trim_end().to_string(),
+            OutputFormat::Table => format_table(&serde_json::to_value(value)?),
+        };
+        Ok(redact(&document))
+    }
+
+    /// Format a value as a record of a stream, such as the events of a chain being watched: a
+    /// line of JSON, a YAML document starting with `---`, or a table. Use a [`RecordStream`] to
+    /// print the records of a stream as the rows of a single table.
+    pub fn format_record<T: Serialize + ?Sized>(&self, value: &T) -> Result<String> {
+        match self {
+            OutputFormat::Human | OutputFormat::Json => Ok(redact(&serde_json::to_string(value)?)),
+            OutputFormat::Yaml => Ok(format!("---\n{}", self.format(value)?)),
+            OutputFormat::Table => self.format(value),
+        }
+    }
+
+    /// Print a value as a document in the output format.
+    pub fn print<T: Serialize + ?Sized>(&self, value: &T) -> Result<()> {
+        println!("{}", self.format(value)?);
+        Ok(())
+    }
+
+    /// Print a value as a record of a stream, see [`OutputFormat::format_record`].
+    pub fn print_record<T: Serialize + ?Sized>(&self, value: &T) -> Result<()> {
+        println!("{}", self.format_record(value)?);
+        Ok(())
+    }
+
+    /// Start a stream of records in the output format.
+    pub fn stream(self) -> RecordStream {
+        RecordStream {
+            output: self,
+            columns: None,
+        }
+    }
+}
+
+/// The styles of the tables, selected with `--table-style`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TableStyle {
+    /// Columns aligned with spaces, without borders
+    #[default]
+    Plain,
+    /// Columns separated by unicode box-drawing borders
+    Unicode,
+}
+
+impl FromStr for TableStyle {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "plain" => Ok(TableStyle::Plain),
+            "unicode" => Ok(TableStyle::Unicode),
+            _ => Err(anyhow!(
+                "Invalid table style: {}. Supported styles: plain, unicode",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for TableStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TableStyle::Plain => write!(f, "plain"),
+            TableStyle::Unicode => write!(f, "unicode"),
+        }
+    }
+}
+
+impl TableStyle {
+    /// Render a table with a header and rows of cells, with the columns aligned on their widest
+    /// cell. The header is upper-cased, and the line breaks of the cells replaced with spaces.
+    pub fn render(&self, header: &[String], rows: &[Vec<String>]) -> String {
+        let widths = column_widths(header, rows);
+        let mut lines = self.header_lines(header, &widths);
+        lines.extend(rows.iter().map(|row| self.row_line(row, &widths)));
+        if *self == TableStyle::Unicode {
+            lines.push(border_line('└', '┴', '┘', &widths));
+        }
+        lines.join("\n")
+    }
+
+    /// The lines of the header of a table, with its top border and separator, if any.
+    fn header_lines(&self, header: &[String], widths: &[usize]) -> Vec<String> {
+        let header: Vec<String> = header.iter().map(|column| column.to_uppercase()).collect();
+        match self {
+            TableStyle::Plain => vec![self.row_line(&header, widths)],
+            TableStyle::Unicode => vec![
+                border_line('┌', '┬', '┐', widths),
+                self.row_line(&header, widths),
+                border_line('├', '┼', '┤', widths),
+            ],
+        }
+    }
+
+    /// A row of a table, with its cells padded to the widths of the columns.
+    fn row_line(&self, row: &[String], widths: &[usize]) -> String {
+        let cells = widths.iter().enumerate().map(|(i, width)| {
+            let cell = row.get(i).map(|cell| cell.replace(['\r', '\n'], " "));
+            format!("{:<width$}", cell.unwrap_or_default(), width = width)
+        });
+        match self {
+            TableStyle::Plain => cells.collect::<Vec<_>>().join("  ").trim_end().to_string(),
+            TableStyle::Unicode => format!("│ {} │", cells.collect::<Vec<_>>().join(" │ ")),
+        }
+    }
+}
+
+/// Select the style of the tables.
+pub fn set_table_style(style: TableStyle) {
+    UNICODE_TABLES.store(style == TableStyle::Unicode, Ordering::Relaxed);
+}
+
+/// The style of the tables.
+pub fn table_style() -> TableStyle {
+    if UNICODE_TABLES.load(Ordering::Relaxed) {
+        TableStyle::Unicode
+    } else {
+        TableStyle::Plain
+    }
+}
+
+/// Render a table with a header and rows of cells in the style of `--table-style`, e.g. to print
+/// a summary of a list with selected columns rather than every field of its items.
+///
+/// The registered secrets are redacted from the table.
+pub fn render_table(header: &[String], rows: &[Vec<String>]) -> String {
+    redact(&table_style().render(header, rows))
+}
+
+/// A stream of records in an output format, such as the events of a chain being watched.
+///
+/// In the table format, the records are printed as the rows of a single table: the header is
+/// printed with the first record, and the columns are those of the first record, with its widths.
+/// The other formats print each record with [`OutputFormat::format_record`].
+pub struct RecordStream {
+    output: OutputFormat,
+    /// The columns of the table and their widths, once the first record is printed
+    columns: Option<(Vec<String>, Vec<usize>)>,
+}
+
+impl RecordStream {
+    /// The output format of the stream.
+    pub fn output(&self) -> OutputFormat {
+        self.output
+    }
+
+    /// Format a record of the stream.
+    pub fn format_record<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<String> {
+        if self.output != OutputFormat::Table {
+            return self.output.format_record(value);
+        }
+        let mut record = Map::new();
+        flatten_json("", &serde_json::to_value(value)?, &mut record);
+        let style = table_style();
+        let mut lines = vec![];
+        let (columns, widths) = self.columns.get_or_insert_with(|| {
+            let columns: Vec<String> = record.keys().cloned().collect();
+            let row: Vec<String> = columns
+                .iter()
+                .map(|column| cell(record.get(column)))
+                .collect();
+            let widths = column_widths(&columns, &[row]);
+            lines = style.header_lines(&columns, &widths);
+            (columns, widths)
+        });
+        let row: Vec<String> = columns
+            .iter()
+            .map(|column| cell(record.get(column)))
+            .collect();
+        lines.push(style.row_line(&row, widths));
+        Ok(redact(&lines.join("\n")))
+    }
+
+    /// Print a record of the stream.
+    pub fn print_record<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        println!("{}", self.format_record(value)?);
+        Ok(())
+    }
+}
+
+/// The widths of the columns of a table: the number of characters of their widest cell.
+fn column_widths(header: &[String], rows: &[Vec<String>]) -> Vec<usize> {
+    (0..header.len())
+        .map(|i| {
+            rows.iter()
+                .filter_map(|row| row.get(i))
+                .map(|cell| cell.chars().count())
+                .chain([header[i].chars().count()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect()
+}
+
+/// A horizontal border of a unicode table.
+fn border_line(left: char, middle: char, right: char, widths: &[usize]) -> String {
+    let segments: Vec<String> = widths.iter().map(|width| "─".repeat(width + 2)).collect();
+    format!("{}{}{}", left, segments.join(&middle.to_string()), right)
+}
+
+/// Format a JSON value as an aligned table, in the style of `--table-style`.
+///
+/// An array of objects is printed with a row per object, and a column per (flattened) key, in the
+/// order they first appear. An object is printed with a row per (flattened) key and its value.
+/// Other values are printed with a row per element.
+fn format_table(value: &Value) -> String {
+    let (header, rows): (Vec<String>, Vec<Vec<String>>) = match value {
+        Value::Array(items) if items.iter().all(Value::is_object) && !items.is_empty() => {
+            let flattened: Vec<Map<String, Value>> = items
+                .iter()
+                .map(|item| {
+                    let mut row = Map::new();
+                    flatten_json("", item, &mut row);
+                    row
+                })
+                .collect();
+            let mut columns: Vec<String> = vec![];
+            for row in &flattened {
+                for key in row.keys() {
+                    if !columns.contains(key) {
+                        columns.push(key.clone());
+                    }
+                }
+            }
+            let rows = flattened
+                .iter()
+                .map(|row| columns.iter().map(|column| cell(row.get(column))).collect())
+                .collect();
+            (columns, rows)
+        }
+        Value::Array(items) => (
+            vec!["VALUE".to_string()],
+            items.iter().map(|item| vec![cell(Some(item))]).collect(),
+        ),
+        value => {
+            let mut flattened = Map::new();
+            flatten_json("", value, &mut flattened);
+            (
+                vec!["KEY".to_string(), "VALUE".to_string()],
+                flattened
+                    .iter()
+                    .map(|(key, value)| vec![key.clone(), cell(Some(value))])
+                    .collect(),
+            )
+        }
+    };
+
+    table_style().render(&header, &rows)
+}
+
+/// Format the value of a table cell: strings without quotes, and other values as JSON.
+fn cell(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => s.clone(),
+        Some(value) => value.to_string(),
+    }
+}
+
+/// A test for the `OutputFormat::format` function
+#[test]
+fn test_output_format() {
+    let value = serde_json::json!([
+        {"data": {"mutates": true}, "name": "flip"},
+        {"data": {"mutates": false, "returns": "bool"}, "name": "get"},
+    ]);
+
+    assert_eq!(OutputFormat::from_str("YAML").unwrap(), OutputFormat::Yaml);
+    assert!(OutputFormat::from_str("xml").is_err());
+
+    // JSON is pretty-printed, and a single line for the records of a stream
+    assert_eq!(
+        serde_json::from_str::<Value>(&OutputFormat::Json.format(&value).unwrap()).unwrap(),
+        value
+    );
+    assert_eq!(
+        OutputFormat::Json
+            .format_record(&value)
+            .unwrap()
+            .lines()
+            .count(),
+        1
+    );
+
+    // YAML can be parsed back
+    let yaml = OutputFormat::Yaml.format(&value).unwrap();
+    assert_eq!(serde_yaml::from_str::<Value>(&yaml).unwrap(), value);
+    assert!(OutputFormat::Yaml
+        .format_record(&value)
+        .unwrap()
+        .starts_with("---\n"));
+
+    // Tables have a column per flattened key, and a row per object
+    assert_eq!(
+        OutputFormat::Table.format(&value).unwrap(),
+        "DATA.MUTATES  NAME  DATA.RETURNS\ntrue          flip\nfalse         get   bool"
+    );
+    assert_eq!(
+        OutputFormat::Table
+            .format(&serde_json::json!({"contract": "5F", "code": {"hash": "0x01"}}))
+            .unwrap(),
+        "KEY        VALUE\ncode.hash  0x01\ncontract   5F"
+    );
+}
+
+/// A test for the unicode tables and the tables of the record streams
+#[test]
+fn test_table_styles() {
+    let header = vec!["name".to_string(), "args".to_string()];
+    let rows = vec![
+        vec!["flip".to_string(), String::new()],
+        vec!["set".to_string(), "value: bool".to_string()],
+    ];
+    assert_eq!(
+        TableStyle::Unicode.render(&header, &rows),
+        [
+            "┌──────┬─────────────┐",
+            "│ NAME │ ARGS        │",
+            "├──────┼─────────────┤",
+            "│ flip │             │",
+            "│ set  │ value: bool │",
+            "└──────┴─────────────┘",
+        ]
+        .join("\n")
+    );
+    assert_eq!(
+        "Unicode".parse::<TableStyle>().unwrap(),
+        TableStyle::Unicode
+    );
+    assert!("ascii".parse::<TableStyle>().is_err());
+
+    // The header of a stream is printed with its first record only
+    let mut stream = OutputFormat::Table.stream();
+    assert_eq!(
+        stream
+            .format_record(&serde_json::json!({"block": 1, "event": "Flipped"}))
+            .unwrap(),
+        "BLOCK  EVENT\n1      Flipped"
+    );
+    assert_eq!(
+        stream
+            .format_record(&serde_json::json!({"block": 2, "event": "Set"}))
+            .unwrap(),
+        "2      Set"
+    );
+}
@@ -0,0 +1,404 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    serde::Deserialize,
+    serde_json::{Map, Value},
+    std::{collections::BTreeMap, fs, path::Path},
+};
+
+/// A playbook: a list of aqd commands run in order, on any chain, whose outputs can be used by the
+/// following commands and checked against expected values.
+///
+/// ```yaml
+/// vars:
+///   suri: //Alice
+/// steps:
+///   - name: flipper
+///     run: polkadot instantiate flipper.contract --args true --suri ${vars.suri} -x
+///   - name: program
+///     run: solana deploy flipper.so
+///   - run: solana call --idl flipper.json --program ${program.program_id} --instruction get --simulate
+///     expect:
+///       decoded_return_data: "true"
+/// ```
+///
+/// The output of each step is its JSON output, referenced by the name of the step in the
+/// `${step.path}` placeholders, where the path is a dotted list of object keys and array indexes.
+/// An empty path (`${step}`, or `.` in `expect`) is the whole output.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Playbook {
+    /// The values referenced as `${vars.name}`
+    #[serde(default)]
+    pub vars: Map<String, Value>,
+    pub steps: Vec<PlaybookStep>,
+}
+
+/// A step of a playbook.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PlaybookStep {
+    /// The name of the step, to reference its output. Defaults to `step<N>`, starting from 1.
+    pub name: Option<String>,
+    /// The aqd command to run, without the `aqd` program name
+    pub run: PlaybookCommand,
+    /// The expected values of the output, by path
+    #[serde(default)]
+    pub expect: BTreeMap<String, Value>,
+    /// The steps that must be run before this one, besides those whose outputs it uses
+    #[serde(default)]
+    pub after: Vec<String>,
+}
+
+/// The command of a step: a command line, split on whitespace except in quotes, or a list of
+/// arguments.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum PlaybookCommand {
+    Line(String),
+    Args(Vec<String>),
+}
+
+impl PlaybookCommand {
+    /// The arguments of the command.
+    pub fn args(&self) -> Result<Vec<String>> {
+        match self {
+            PlaybookCommand::Line(line) => split_command_line(line),
+            PlaybookCommand::Args(args) => Ok(args.clone()),
+        }
+    }
+}
+
+impl Playbook {
+    /// Parse a playbook from its YAML content.
+    pub fn from_yaml(content: &str) -> Result<Self> {
+        let playbook: Playbook = serde_yaml::from_str(content)?;
+        if playbook.steps.is_empty() {
+            return Err(anyhow!("The playbook has no steps"));
+        }
+        Ok(playbook)
+    }
+
+    /// Read a playbook from a YAML file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+        Self::from_yaml(&content).map_err(|e| anyhow!("Invalid playbook {}: {}", path.display(), e))
+    }
+
+    /// The names of the steps, with the default `step<N>` for the unnamed ones.
+    pub fn step_names(&self) -> Vec<String> {
+        self.steps
+            .iter()
+            .enumerate()
+            .map(|(i, step)| {
+                step.name
+                    .clone()
+                    .unwrap_or_else(|| format!("step{}", i + 1))
+            })
+            .collect()
+    }
+}
+
+impl Playbook {
+    /// The dependencies of each step: the indexes of the steps whose outputs it uses in its
+    /// placeholders, and of the steps listed in its `after` list.
+    ///
+    /// The steps without dependencies between them can be run concurrently.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if a step depends on itself or on a later step, or if its
+    /// `after` list names an unknown step.
+    pub fn dependencies(&self) -> Result<Vec<Vec<usize>>> {
+        let names = self.step_names();
+        self.steps
+            .iter()
+            .enumerate()
+            .map(|(i, step)| {
+                let mut referenced = match &step.run {
+                    PlaybookCommand::Line(line) => placeholder_steps(line),
+                    PlaybookCommand::Args(args) => {
+                        args.iter().flat_map(|arg| placeholder_steps(arg)).collect()
+                    }
+                };
+                for expected in step.expect.values() {
+                    if let Value::String(expected) = expected {
+                        referenced.extend(placeholder_steps(expected));
+                    }
+                }
+
+                let mut dependencies = vec![];
+                let references = referenced
+                    .into_iter()
+                    .filter(|name| names.iter().any(|step| step == name))
+                    .chain(step.after.iter().map(|name| name.as_str()));
+                for name in references {
+                    let j = names
+                        .iter()
+                        .position(|step| step == name)
+                        .ok_or_else(|| anyhow!("Step {}: unknown step {}", names[i], name))?;
+                    if j >= i {
+                        return Err(anyhow!(
+                            "Step {} depends on step {}, which is not run before it",
+                            names[i],
+                            name
+                        ));
+                    }
+                    if !dependencies.contains(&j) {
+                        dependencies.push(j);
+                    }
+                }
+                Ok(dependencies)
+            })
+            .collect()
+    }
+}
+
+/// The values available to the placeholders of a playbook: its variables and the outputs of the
+/// steps run so far.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PlaybookContext {
+    values: Map<String, Value>,
+}
+
+impl PlaybookContext {
+    /// Create the context of a playbook, before any of its steps is run.
+    pub fn new(playbook: &Playbook) -> Self {
+        let mut values = Map::new();
+        values.insert("vars".to_string(), Value::Object(playbook.vars.clone()));
+        PlaybookContext { values }
+    }
+
+    /// Record the output of a step.
+    pub fn insert(&mut self, step: &str, output: Value) {
+        self.values.insert(step.to_string(), output);
+    }
+
+    /// The outputs of the steps run so far, and the variables.
+    pub fn values(&self) -> &Map<String, Value> {
+        &self.values
+    }
+
+    /// Replace the `${path}` placeholders of a string with their values. Strings are inserted as
+    /// they are, other values as JSON.
+    pub fn substitute(&self, input: &str) -> Result<String> {
+        let mut output = String::new();
+        let mut rest = input;
+        while let Some(start) = rest.find("${") {
+            output.push_str(&rest[..start]);
+            let end = rest[start..]
+                .find('}')
+                .ok_or_else(|| anyhow!("Unterminated placeholder in {}", input))?;
+            let path = &rest[start + 2..start + end];
+            let (step, step_path) = path.split_once('.').unwrap_or((path, ""));
+            let value = self
+                .values
+                .get(step)
+                .and_then(|output| lookup(output, step_path))
+                .ok_or_else(|| anyhow!("Unknown value ${{{}}}", path))?;
+            output.push_str(&value_to_string(value));
+            rest = &rest[start + end + 1..];
+        }
+        output.push_str(rest);
+        Ok(output)
+    }
+
+    /// Check the output of a step against its expected values.
+    ///
+    /// A value matches if it is equal to the expected one, or if both are written the same, so
+    /// that e.g. `1000` matches the `"1000"` string of a large integer. The placeholders of the
+    /// expected strings are substituted first.
+    pub fn check_expectations(
+        &self,
+        output: &Value,
+        expect: &BTreeMap<String, Value>,
+    ) -> Result<()> {
+        for (path, expected) in expect {
+            let expected = match expected {
+                Value::String(expected) => Value::String(self.substitute(expected)?),
+                expected => expected.clone(),
+            };
+            let actual = lookup(output, path).ok_or_else(|| {
+                anyhow!("Expected {} to be {}, but it is missing", path, expected)
+            })?;
+            if *actual != expected && value_to_string(actual) != value_to_string(&expected) {
+                return Err(anyhow!(
+                    "Expected {} to be {}, but it is {}",
+                    path,
+                    expected,
+                    actual
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Get the value at a dotted path of object keys and array indexes. An empty path is the value
+/// itself.
+pub fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .filter(|segment| !segment.is_empty())
+        .try_fold(value, |value, segment| match value {
+            Value::Object(object) => object.get(segment),
+            Value::Array(array) => segment.parse::<usize>().ok().and_then(|i| array.get(i)),
+            _ => None,
+        })
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(string) => string.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// The first segment of the paths of the `${path}` placeholders of a string: the step (or `vars`)
+/// they refer to.
+fn placeholder_steps(input: &str) -> Vec<&str> {
+    input
+        .split("${")
+        .skip(1)
+        .filter_map(|placeholder| placeholder.split(['.', '}']).next())
+        .collect()
+}
+
+/// Split a command line on whitespace, except in single or double quotes.
+fn split_command_line(line: &str) -> Result<Vec<String>> {
+    let mut args = vec![];
+    let mut current: Option<String> = None;
+    let mut quote: Option<char> = None;
+    for c in line.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => current.get_or_insert_with(String::new).push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                current.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => args.extend(current.take()),
+            (None, c) => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if quote.is_some() {
+        return Err(anyhow!("Unterminated quote in {}", line));
+    }
+    args.extend(current);
+    Ok(args)
+}
+
+/// A test for the `Playbook::from_yaml` function
+#[test]
+fn test_playbook_from_yaml() {
+    let playbook = Playbook::from_yaml(
+        r#"
+vars:
+  suri: //Alice
+steps:
+  - name: flipper
+    run: polkadot instantiate flipper.contract --args true --suri ${vars.suri} -x
+  - run: ["solana", "call", "--data", "hello world"]
+    expect:
+      decoded_return_data: "true"
+"#,
+    )
+    .unwrap();
+    assert_eq!(playbook.step_names(), vec!["flipper", "step2"]);
+    assert_eq!(
+        playbook.steps[1].run.args().unwrap(),
+        vec!["solana", "call", "--data", "hello world"]
+    );
+    assert!(Playbook::from_yaml("steps: []").is_err());
+    assert!(Playbook::from_yaml("steps:\n  - command: deploy").is_err());
+}
+
+/// A test for the `PlaybookContext::substitute` function
+#[test]
+fn test_playbook_context_substitute() {
+    let playbook = Playbook::from_yaml("vars:\n  suri: //Alice\nsteps:\n  - run: x").unwrap();
+    let mut context = PlaybookContext::new(&playbook);
+    context.insert(
+        "deploy",
+        serde_json::json!({ "program_id": "Fg6P", "accounts": [{ "size": 8 }] }),
+    );
+
+    assert_eq!(
+        context
+            .substitute("--program ${deploy.program_id} --suri=${vars.suri}")
+            .unwrap(),
+        "--program Fg6P --suri=//Alice"
+    );
+    assert_eq!(
+        context.substitute("${deploy.accounts.0.size}").unwrap(),
+        "8"
+    );
+    assert!(context.substitute("${deploy.missing}").is_err());
+    assert!(context.substitute("${deploy").is_err());
+
+    assert_eq!(
+        split_command_line("call --args 'a b' \"\" c").unwrap(),
+        vec!["call", "--args", "a b", "", "c"]
+    );
+}
+
+/// A test for the `PlaybookContext::check_expectations` function
+#[test]
+fn test_playbook_context_check_expectations() {
+    let context = PlaybookContext::default();
+    let output =
+        serde_json::json!({ "data": true, "balance": "1000", "events": [{ "name": "Flipped" }] });
+    let expect = |yaml: &str| serde_yaml::from_str::<BTreeMap<String, Value>>(yaml).unwrap();
+
+    assert!(context
+        .check_expectations(
+            &output,
+            &expect("data: true\nbalance: 1000\nevents.0.name: Flipped")
+        )
+        .is_ok());
+    assert!(context
+        .check_expectations(&output, &expect("data: false"))
+        .is_err());
+    assert!(context
+        .check_expectations(&output, &expect("missing: 1"))
+        .is_err());
+}
+
+/// A test for the `Playbook::dependencies` function
+#[test]
+fn test_playbook_dependencies() {
+    let playbook = Playbook::from_yaml(
+        r#"
+vars:
+  suri: //Alice
+steps:
+  - name: flipper
+    run: polkadot instantiate flipper.contract --suri ${vars.suri} -x
+  - name: program
+    run: solana deploy flipper.so
+  - run: ["solana", "call", "--program", "${program.program_id}"]
+    expect:
+      contract: ${flipper.contract}
+  - run: polkadot call --contract ${flipper.contract} --message flip -x
+    after: [step3]
+"#,
+    )
+    .unwrap();
+    assert_eq!(
+        playbook.dependencies().unwrap(),
+        vec![vec![], vec![], vec![1, 0], vec![0, 2]]
+    );
+
+    let playbook = Playbook::from_yaml(concat!(
+        "steps:\n",
+        "  - run: solana show --program ${later.program_id}\n",
+        "  - name: later\n",
+        "    run: solana deploy flipper.so\n",
+    ))
+    .unwrap();
+    assert!(playbook.dependencies().is_err());
+    let playbook = Playbook::from_yaml("steps:\n  - run: x\n    after: [missing]").unwrap();
+    assert!(playbook.dependencies().is_err());
+}
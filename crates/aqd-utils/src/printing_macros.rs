@@ -1,26 +1,37 @@
 // SPDX-License-Identifier: Apache-2.0
 
-/// macro to print a title (cyan and bold)
+// The printed text is written through the reporter set with `set_reporter`, and redacted of the
+// registered secrets, see `register_secret`.
+
+/// macro to print a title (in the title color of the theme and bold), omitted in quiet mode
 #[macro_export]
 macro_rules! print_title {
     ($title:expr) => {
-        println!("{}", format!("\n{}", $title.bold().cyan(),));
+        $crate::report($crate::Report::Title {
+            text: $title.to_string(),
+        })
     };
 }
 
-/// macro to print a subtitle (cyan and bold) indented with 2 spaces
+/// macro to print a subtitle (in the title color of the theme and bold) indented with 2 spaces,
+/// omitted in quiet mode
 #[macro_export]
 macro_rules! print_subtitle {
     ($title:expr) => {
-        println!("{}", format!("\n  {}", $title.bold().cyan(),));
+        $crate::report($crate::Report::Subtitle {
+            text: $title.to_string(),
+        })
     };
 }
 
-/// macro to print a key and value (green and bold) indented with 4 spaces
+/// macro to print a key (in the key color of the theme and bold) and value indented with 4 spaces
 #[macro_export]
 macro_rules! print_key_value {
     ($key:expr, $value:expr) => {
-        println!("    {}: {}", format!("{:<15}", $key.bold().green()), $value);
+        $crate::report($crate::Report::KeyValue {
+            key: $key.to_string(),
+            value: $value.to_string(),
+        })
     };
 }
 
@@ -28,17 +39,28 @@ macro_rules! print_key_value {
 #[macro_export]
 macro_rules! print_value {
     ($val:expr) => {
-        println!("    {}", $val);
+        $crate::report($crate::Report::Value {
+            text: $val.to_string(),
+        })
+    };
+}
+
+/// macro to print a text as is, e.g. a result alone in quiet mode, or a table
+#[macro_export]
+macro_rules! print_text {
+    ($text:expr) => {
+        $crate::report($crate::Report::Text {
+            text: $text.to_string(),
+        })
     };
 }
 
-/// macro to print a warning (yellow and bold)
+/// macro to print a warning (in the warning color of the theme and bold), omitted in quiet mode
 #[macro_export]
 macro_rules! print_warning {
     ($warning:expr) => {
-        println!(
-            "{}",
-            format!("\n{} {}", "Warning:".bold().yellow(), $warning.yellow())
-        );
+        $crate::report($crate::Report::Warning {
+            text: $warning.to_string(),
+        })
     };
 }
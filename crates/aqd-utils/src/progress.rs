@@ -0,0 +1,158 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::redact::redact,
+    anyhow::{anyhow, Result},
+    chrono::{DateTime, Utc},
+    serde::Serialize,
+    std::{
+        fmt,
+        str::FromStr,
+        sync::atomic::{AtomicBool, Ordering},
+    },
+};
+
+/// Whether the progress events are printed, set once from the `--progress` flag.
+static PROGRESS_EVENTS: AtomicBool = AtomicBool::new(false);
+
+/// The formats of the progress of the transactions, selected with `--progress`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ProgressFormat {
+    /// The spinners and messages for humans, if any
+    #[default]
+    Human,
+    /// A line of JSON per progress event, on stderr
+    Ndjson,
+}
+
+impl FromStr for ProgressFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "human" => Ok(ProgressFormat::Human),
+            "ndjson" => Ok(ProgressFormat::Ndjson),
+            _ => Err(anyhow!(
+                "Invalid progress format: {}. Supported formats: human, ndjson",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ProgressFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProgressFormat::Human => write!(f, "human"),
+            ProgressFormat::Ndjson => write!(f, "ndjson"),
+        }
+    }
+}
+
+/// Select the format of the progress of the transactions.
+///
+/// With NDJSON progress events, the spinners are disabled, as they would be mixed with the events
+/// on stderr.
+pub fn set_progress(format: ProgressFormat) {
+    PROGRESS_EVENTS.store(format == ProgressFormat::Ndjson, Ordering::Relaxed);
+}
+
+/// The format of the progress of the transactions.
+pub fn progress_format() -> ProgressFormat {
+    if PROGRESS_EVENTS.load(Ordering::Relaxed) {
+        ProgressFormat::Ndjson
+    } else {
+        ProgressFormat::Human
+    }
+}
+
+/// The stages of a transaction reported by the progress events.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProgressStage {
+    /// The transaction is being prepared
+    Started,
+    /// The recent block the transaction refers to is known: the Solana blockhash, or the block
+    /// starting the mortality period of a Polkadot extrinsic
+    BlockhashFetched,
+    /// The transaction is signed
+    Signed,
+    /// The transaction is sent to the node, and its hash known
+    Submitted,
+    /// The transaction is included in a block (or a ledger), or confirmed by the cluster
+    Confirmed,
+    /// The block of the transaction is finalized
+    Finalized,
+}
+
+/// An event of the progress of a transaction, printed on stderr as a line of JSON, e.g.
+/// `{"event":"submitted","chain":"polkadot","timestamp":"...","hash":"0x..."}`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ProgressEvent {
+    pub event: ProgressStage,
+    pub chain: String,
+    pub timestamp: DateTime<Utc>,
+    /// The signature or hash of the transaction, once signed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+    /// The blockhash of a `blockhash-fetched` event, or the block (or ledger) of the transaction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block: Option<String>,
+}
+
+impl ProgressEvent {
+    /// Create an event of a stage of a transaction on a chain, at the current time.
+    pub fn new(chain: &str, event: ProgressStage) -> Self {
+        ProgressEvent {
+            event,
+            chain: chain.to_string(),
+            timestamp: Utc::now(),
+            hash: None,
+            block: None,
+        }
+    }
+
+    /// Set the signature or hash of the transaction.
+    pub fn hash<H: fmt::Display>(mut self, hash: H) -> Self {
+        self.hash = Some(hash.to_string());
+        self
+    }
+
+    /// Set the blockhash, or the block of the transaction.
+    pub fn block<B: fmt::Display>(mut self, block: B) -> Self {
+        self.block = Some(block.to_string());
+        self
+    }
+}
+
+/// Print a progress event on stderr, if enabled with `--progress ndjson`.
+pub fn emit_progress(event: ProgressEvent) {
+    if progress_format() != ProgressFormat::Ndjson {
+        return;
+    }
+    if let Ok(line) = serde_json::to_string(&event) {
+        eprintln!("{}", redact(&line));
+    }
+}
+
+/// A test for the `ProgressEvent` serialization
+#[test]
+fn test_progress_event() {
+    let event = ProgressEvent::new("solana", ProgressStage::BlockhashFetched)
+        .block("4sGjMW1sUnHzSxGspuhpqLDx6wiyjNtZAMdL4VZHirAn");
+    let json = serde_json::to_value(&event).unwrap();
+    assert_eq!(json["event"], "blockhash-fetched");
+    assert_eq!(json["chain"], "solana");
+    assert_eq!(
+        json["block"],
+        "4sGjMW1sUnHzSxGspuhpqLDx6wiyjNtZAMdL4VZHirAn"
+    );
+    // The fields not set are omitted
+    assert!(json.get("hash").is_none());
+
+    assert_eq!(
+        "ndjson".parse::<ProgressFormat>().unwrap(),
+        ProgressFormat::Ndjson
+    );
+    assert!("xml".parse::<ProgressFormat>().is_err());
+}
@@ -0,0 +1,23 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether aqd runs in quiet mode, set once from the `--quiet` flag.
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Enable or disable the quiet mode.
+///
+/// In quiet mode, the titles and warnings are not printed, the colors and spinners are disabled,
+/// and the commands print only their essential result (a signature, a program ID, a contract
+/// address, ...), so that their output can be consumed directly by shell scripts.
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+    if quiet {
+        colored::control::set_override(false);
+    }
+}
+
+/// Check whether aqd runs in quiet mode.
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
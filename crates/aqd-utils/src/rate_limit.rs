@@ -0,0 +1,135 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    std::{
+        sync::Mutex,
+        time::{Duration, Instant},
+    },
+};
+
+/// The token bucket shared by all the RPC requests of the process, set once from the
+/// `--rate-limit` and `--rate-limit-burst` options.
+static RATE_LIMITER: Mutex<Option<TokenBucket>> = Mutex::new(None);
+
+/// A token bucket refilled at a constant rate, up to its burst.
+///
+/// A request takes a token, and waits for the bucket to refill when it is empty. The tokens can
+/// go negative, so that the concurrent requests waiting are spaced out rather than woken up
+/// together.
+#[derive(Clone, Debug)]
+struct TokenBucket {
+    /// The rate at which the bucket is refilled, in tokens per second
+    rate: f64,
+    /// The maximum number of tokens in the bucket
+    burst: f64,
+    /// The number of tokens in the bucket at `updated`
+    tokens: f64,
+    /// The time of the last request
+    updated: Instant,
+}
+
+impl TokenBucket {
+    /// A full token bucket.
+    fn new(rate: f64, burst: u32, now: Instant) -> Self {
+        Self {
+            rate,
+            burst: burst as f64,
+            tokens: burst as f64,
+            updated: now,
+        }
+    }
+
+    /// Take a token at `now`, and return how long the request has to wait for it.
+    fn reserve(&mut self, now: Instant) -> Duration {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.burst) - 1.0;
+        self.updated = now.max(self.updated);
+        match self.tokens < 0.0 {
+            true => Duration::from_secs_f64(-self.tokens / self.rate),
+            false => Duration::ZERO,
+        }
+    }
+}
+
+/// Limit the rate of the RPC requests to the Solana cluster and the Polkadot node, to
+/// `requests_per_second` on average, with bursts of up to `burst` requests (by default, the
+/// number of requests of a second). No limit is applied without `requests_per_second`.
+pub fn set_rate_limit(requests_per_second: Option<f64>, burst: Option<u32>) {
+    *RATE_LIMITER.lock().unwrap() = requests_per_second.map(|rate| {
+        let burst = burst.unwrap_or(rate.ceil() as u32).max(1);
+        TokenBucket::new(rate, burst, Instant::now())
+    });
+}
+
+/// The rate limit of the RPC requests, in requests per second, and its burst, if any.
+pub fn rate_limit() -> Option<(f64, u32)> {
+    RATE_LIMITER
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|bucket| (bucket.rate, bucket.burst as u32))
+}
+
+/// Reserve the sending of an RPC request under the rate limit, and return how long to wait
+/// before sending it: zero without a rate limit, or while the burst is not exhausted.
+///
+/// The limit is shared by all the threads and clients of the process, such as the programs
+/// deployed in parallel.
+pub fn rate_limit_delay() -> Duration {
+    match RATE_LIMITER.lock().unwrap().as_mut() {
+        Some(bucket) => bucket.reserve(Instant::now()),
+        None => Duration::ZERO,
+    }
+}
+
+/// Parse the `--rate-limit` option: a positive number of requests per second (e.g. `10`, `0.5`).
+pub fn parse_rate_limit(rate_limit: &str) -> Result<f64> {
+    match rate_limit.trim().parse::<f64>() {
+        Ok(rate) if rate.is_finite() && rate > 0.0 => Ok(rate),
+        _ => Err(anyhow!(
+            "Invalid rate limit: {}. Expected a positive number of requests per second",
+            rate_limit
+        )),
+    }
+}
+
+/// Parse the `--rate-limit-burst` option: a positive number of requests.
+pub fn parse_burst(burst: &str) -> Result<u32> {
+    match burst.parse::<u32>() {
+        Ok(burst) if burst > 0 => Ok(burst),
+        _ => Err(anyhow!(
+            "Invalid burst: {}. Expected a positive integer",
+            burst
+        )),
+    }
+}
+
+/// A test for the `TokenBucket::reserve` function
+#[test]
+fn test_token_bucket_reserve() {
+    let start = Instant::now();
+    let mut bucket = TokenBucket::new(2.0, 2, start);
+    // The burst is sent without waiting
+    assert_eq!(bucket.reserve(start), Duration::ZERO);
+    assert_eq!(bucket.reserve(start), Duration::ZERO);
+    // The next requests are spaced out at the rate
+    assert_eq!(bucket.reserve(start), Duration::from_millis(500));
+    assert_eq!(bucket.reserve(start), Duration::from_millis(1000));
+    // The bucket refills over time, up to its burst
+    let later = start + Duration::from_secs(10);
+    assert_eq!(bucket.reserve(later), Duration::ZERO);
+    assert_eq!(bucket.reserve(later), Duration::ZERO);
+    assert_eq!(bucket.reserve(later), Duration::from_millis(500));
+}
+
+/// A test for the `parse_rate_limit` function
+#[test]
+fn test_parse_rate_limit() {
+    assert_eq!(parse_rate_limit("10").unwrap(), 10.0);
+    assert_eq!(parse_rate_limit("0.5").unwrap(), 0.5);
+    assert!(parse_rate_limit("0").is_err());
+    assert!(parse_rate_limit("-1").is_err());
+    assert!(parse_rate_limit("inf").is_err());
+    assert!(parse_rate_limit("fast").is_err());
+}
@@ -0,0 +1,105 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{io, sync::Mutex};
+
+/// The text that replaces the secrets in the output.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// The secrets are only redacted from this length, so that short parts of a secret key URI, such
+/// as a soft derivation index, do not redact unrelated output.
+const MIN_SECRET_LENGTH: usize = 4;
+
+/// The secrets used by the command, such as secret key URIs, mnemonic phrases and private keys.
+static SECRETS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Register a secret, so that it is redacted from all the output of aqd: the printed results, the
+/// JSON output, the errors and the logs.
+///
+/// The crates register the secrets as soon as they read them, before parsing them, so that they
+/// are redacted from the parsing errors too.
+pub fn register_secret(secret: &str) {
+    let secret = secret.trim();
+    if secret.len() < MIN_SECRET_LENGTH {
+        return;
+    }
+    let mut secrets = SECRETS.lock().unwrap();
+    if !secrets.iter().any(|registered| registered == secret) {
+        secrets.push(secret.to_string());
+        // Redact the longest secrets first, as a secret can contain another one
+        secrets.sort_by_key(|secret| std::cmp::Reverse(secret.len()));
+    }
+}
+
+/// Register a secret key URI (`<phrase or seed>[//hard][/soft][///password]`), along with its
+/// phrase or seed, and its password. The derivation paths alone are not secret, such as the
+/// `//Alice` of the development accounts.
+pub fn register_secret_uri(suri: &str) {
+    let suri = suri.trim();
+    register_secret(suri);
+    let (path, password) = match suri.split_once("///") {
+        Some((path, password)) => (path, Some(password)),
+        None => (suri, None),
+    };
+    if let Some(password) = password {
+        register_secret(password);
+    }
+    let phrase = path.split('/').next().unwrap_or_default();
+    register_secret(phrase);
+}
+
+/// Replace the registered secrets of a text with [`REDACTED`].
+pub fn redact(text: &str) -> String {
+    let secrets = SECRETS.lock().unwrap();
+    let mut text = text.to_string();
+    for secret in secrets.iter() {
+        if text.contains(secret.as_str()) {
+            text = text.replace(secret.as_str(), REDACTED);
+        }
+    }
+    text
+}
+
+/// A writer redacting the secrets of the text written to another writer, such as the logs written
+/// to stderr.
+///
+/// The text is redacted a write at a time, so a secret split across several writes is not
+/// redacted: the log lines are written at once.
+pub struct RedactingWriter<W: io::Write>(pub W);
+
+impl<W: io::Write> io::Write for RedactingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match std::str::from_utf8(buf) {
+            Ok(text) => self.0.write_all(redact(text).as_bytes())?,
+            Err(_) => self.0.write_all(buf)?,
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+/// A test for the `redact` function
+#[test]
+fn test_redact() {
+    let mnemonic =
+        "bottom drive obey lake curtain smoke basket hold race lonely fit walk//Alice///hunter22";
+    register_secret_uri(mnemonic);
+    register_secret("S");
+
+    assert_eq!(
+        redact(&format!("Invalid secret key URI: {}", mnemonic)),
+        format!("Invalid secret key URI: {}", REDACTED)
+    );
+    assert_eq!(
+        redact("password hunter22, phrase bottom drive obey lake curtain smoke basket hold race lonely fit walk"),
+        format!("password {}, phrase {}", REDACTED, REDACTED)
+    );
+    // The derivation paths alone, and the too short secrets, are not redacted
+    assert_eq!(redact("//Alice Signer"), "//Alice Signer");
+
+    let mut output = vec![];
+    io::Write::write(&mut RedactingWriter(&mut output), b"DEBUG suri=hunter22").unwrap();
+    assert_eq!(String::from_utf8(output).unwrap(), "DEBUG suri=[REDACTED]");
+}
@@ -0,0 +1,364 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    serde::Deserialize,
+    serde_json::Value,
+    std::{collections::BTreeMap, fs, path::Path, path::PathBuf},
+};
+
+/// The chains whose contracts can be deployed by a release manifest.
+const RELEASE_CHAINS: [&str; 2] = ["solana", "polkadot"];
+
+/// A release manifest: the contracts of an application on several chains, deployed together by
+/// `aqd deploy --manifest`, with the settings of each environment.
+///
+/// ```toml
+/// [[contracts]]
+/// name = "flipper"
+/// chain = "solana"
+/// artifact = "build/flipper.so"
+/// idl = "build/flipper.json"
+///
+/// [[contracts]]
+/// name = "token"
+/// chain = "polkadot"
+/// artifact = "build/token.contract"
+/// args = ["1000000"]
+///
+/// [environments.staging.solana]
+/// url = "devnet"
+/// keypair = "keys/staging.json"
+///
+/// [environments.staging.polkadot]
+/// network = "rococo"
+/// suri-file = "keys/staging.suri"
+///
+/// [environments.staging.contracts.token]
+/// args = ["1000"]
+/// ```
+///
+/// The settings of a chain are the options of its deploy command (`aqd solana deploy` or `aqd
+/// polkadot instantiate`), given by their long name without dashes: a string or a number is passed
+/// as the value of the option, and `true` sets a flag. The constructor, arguments and options of a
+/// contract can be overridden in an environment. Relative paths are resolved from the directory of
+/// the manifest.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReleaseManifest {
+    pub contracts: Vec<ReleaseContract>,
+    /// The settings of each environment, by name
+    #[serde(default)]
+    pub environments: BTreeMap<String, ReleaseEnvironment>,
+}
+
+/// A contract of a release manifest.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReleaseContract {
+    /// The name of the contract, used as its key in the addresses file
+    pub name: String,
+    /// The chain of the contract: `solana` or `polkadot`
+    pub chain: String,
+    /// The artifact to deploy: the program binary (.so) on Solana, the .contract bundle on Polkadot
+    pub artifact: PathBuf,
+    /// The IDL of a Solana program, recorded in the addresses file
+    pub idl: Option<PathBuf>,
+    /// The constructor of a Polkadot contract, `new` by default
+    pub constructor: Option<String>,
+    /// The arguments of the constructor of a Polkadot contract
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// The other options of the deploy command of the contract
+    #[serde(default)]
+    pub options: BTreeMap<String, toml::Value>,
+}
+
+/// The settings of an environment of a release manifest.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct ReleaseEnvironment {
+    /// The overrides of the contracts, by name
+    #[serde(default)]
+    pub contracts: BTreeMap<String, ContractOverrides>,
+    /// The options of the deploy commands of each chain
+    #[serde(flatten)]
+    pub chains: BTreeMap<String, BTreeMap<String, toml::Value>>,
+}
+
+/// The settings of a contract overridden in an environment.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ContractOverrides {
+    pub constructor: Option<String>,
+    pub args: Option<Vec<String>>,
+    #[serde(default)]
+    pub options: BTreeMap<String, toml::Value>,
+}
+
+/// The deployment of a contract of a release manifest: the aqd command deploying it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReleaseStep {
+    /// The name of the contract
+    pub name: String,
+    /// The chain of the contract
+    pub chain: String,
+    /// The arguments of the aqd command, without the `aqd` program name
+    pub args: Vec<String>,
+}
+
+impl ReleaseManifest {
+    /// Parse a release manifest from its TOML content.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the content is not a valid manifest, if it has no
+    /// contracts, if two contracts have the same name, if a contract is on an unsupported chain, or
+    /// if an environment has settings for an unsupported chain or overrides an unknown contract.
+    pub fn from_toml(content: &str) -> Result<Self> {
+        let manifest: ReleaseManifest = toml::from_str(content)?;
+        if manifest.contracts.is_empty() {
+            return Err(anyhow!("The manifest has no [[contracts]]"));
+        }
+        for (i, contract) in manifest.contracts.iter().enumerate() {
+            if !RELEASE_CHAINS.contains(&contract.chain.as_str()) {
+                return Err(anyhow!(
+                    "Unsupported chain {} for contract {}: expected {}",
+                    contract.chain,
+                    contract.name,
+                    RELEASE_CHAINS.join(" or ")
+                ));
+            }
+            if manifest.contracts[..i]
+                .iter()
+                .any(|other| other.name == contract.name)
+            {
+                return Err(anyhow!("Duplicate contract name {}", contract.name));
+            }
+        }
+        for (name, environment) in &manifest.environments {
+            if let Some(chain) = environment
+                .chains
+                .keys()
+                .find(|chain| !RELEASE_CHAINS.contains(&chain.as_str()))
+            {
+                return Err(anyhow!(
+                    "Environment {} has settings for unsupported chain {}: expected {}",
+                    name,
+                    chain,
+                    RELEASE_CHAINS.join(" or ")
+                ));
+            }
+            if let Some(unknown) = environment
+                .contracts
+                .keys()
+                .find(|contract| !manifest.contracts.iter().any(|c| &&c.name == contract))
+            {
+                return Err(anyhow!(
+                    "Environment {} overrides unknown contract {}",
+                    name,
+                    unknown
+                ));
+            }
+        }
+        Ok(manifest)
+    }
+
+    /// Read a release manifest from a TOML file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+        Self::from_toml(&content).map_err(|e| anyhow!("Invalid manifest {}: {}", path.display(), e))
+    }
+
+    /// The aqd commands deploying the contracts of the manifest in an environment, in order.
+    ///
+    /// The options of a contract take precedence over the options of its chain in the environment,
+    /// and the overrides of the contract in the environment over both.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the environment is unknown, if no environment is given
+    /// while the manifest defines some, or if an option has an unsupported value.
+    pub fn steps(&self, environment: Option<&str>) -> Result<Vec<ReleaseStep>> {
+        let environment = match environment {
+            Some(name) => Some(self.environments.get(name).ok_or_else(|| {
+                anyhow!(
+                    "Unknown environment {}: expected one of {}",
+                    name,
+                    self.environment_names()
+                )
+            })?),
+            None if self.environments.is_empty() => None,
+            None => {
+                return Err(anyhow!(
+                    "Specify the environment to deploy to with --env: {}",
+                    self.environment_names()
+                ))
+            }
+        };
+
+        self.contracts
+            .iter()
+            .map(|contract| {
+                let overrides = environment.and_then(|env| env.contracts.get(&contract.name));
+                let mut options = environment
+                    .and_then(|env| env.chains.get(&contract.chain))
+                    .cloned()
+                    .unwrap_or_default();
+                options.extend(contract.options.clone());
+                if let Some(overrides) = overrides {
+                    options.extend(overrides.options.clone());
+                }
+
+                let mut args = vec![contract.chain.clone()];
+                match contract.chain.as_str() {
+                    "solana" => {
+                        args.push("deploy".to_string());
+                        args.push(contract.artifact.to_string_lossy().to_string());
+                    }
+                    _ => {
+                        args.push("instantiate".to_string());
+                        args.push(contract.artifact.to_string_lossy().to_string());
+                        args.extend(["--upload".to_string(), "--execute".to_string()]);
+                        let constructor = overrides
+                            .and_then(|overrides| overrides.constructor.as_ref())
+                            .or(contract.constructor.as_ref());
+                        if let Some(constructor) = constructor {
+                            args.extend(["--constructor".to_string(), constructor.clone()]);
+                        }
+                    }
+                }
+                for (key, value) in &options {
+                    match value {
+                        toml::Value::Boolean(true) => args.push(format!("--{}", key)),
+                        toml::Value::Boolean(false) => {}
+                        toml::Value::String(value) => {
+                            args.extend([format!("--{}", key), value.clone()])
+                        }
+                        toml::Value::Integer(value) => {
+                            args.extend([format!("--{}", key), value.to_string()])
+                        }
+                        _ => {
+                            return Err(anyhow!(
+                                "Unsupported value of option {} for contract {}: expected a string, a number or a boolean",
+                                key,
+                                contract.name
+                            ))
+                        }
+                    }
+                }
+                // The constructor arguments come last, as they take any number of values
+                let constructor_args = overrides
+                    .and_then(|overrides| overrides.args.as_ref())
+                    .unwrap_or(&contract.args);
+                if !constructor_args.is_empty() {
+                    args.push("--args".to_string());
+                    args.extend(constructor_args.iter().cloned());
+                }
+
+                Ok(ReleaseStep {
+                    name: contract.name.clone(),
+                    chain: contract.chain.clone(),
+                    args,
+                })
+            })
+            .collect()
+    }
+
+    /// The names of the environments, separated by commas.
+    fn environment_names(&self) -> String {
+        self.environments
+            .keys()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// The address of a deployed contract, from the JSON output of the command deploying it: the
+/// program ID on Solana, the contract address on Polkadot.
+pub fn deployed_address(chain: &str, output: &Value) -> Option<String> {
+    let key = match chain {
+        "solana" => "program_id",
+        _ => "contract",
+    };
+    output[key].as_str().map(str::to_string)
+}
+
+/// A test for the `ReleaseManifest::steps` function
+#[test]
+fn test_release_manifest_steps() {
+    let manifest = ReleaseManifest::from_toml(
+        r#"
+        [[contracts]]
+        name = "flipper"
+        chain = "solana"
+        artifact = "build/flipper.so"
+        idl = "build/flipper.json"
+
+        [[contracts]]
+        name = "token"
+        chain = "polkadot"
+        artifact = "build/token.contract"
+        args = ["1000000"]
+        options = { salt = "random" }
+
+        [environments.staging.solana]
+        url = "devnet"
+
+        [environments.staging.polkadot]
+        network = "rococo"
+        suri-file = "keys/staging.suri"
+
+        [environments.staging.contracts.token]
+        constructor = "with_supply"
+        args = ["1000"]
+
+        [environments.production.solana]
+        url = "mainnet-beta"
+        "#,
+    )
+    .unwrap();
+
+    let steps = manifest.steps(Some("staging")).unwrap();
+    assert_eq!(
+        steps[0].args,
+        ["solana", "deploy", "build/flipper.so", "--url", "devnet"]
+    );
+    assert_eq!(
+        steps[1].args,
+        [
+            "polkadot",
+            "instantiate",
+            "build/token.contract",
+            "--upload",
+            "--execute",
+            "--constructor",
+            "with_supply",
+            "--network",
+            "rococo",
+            "--salt",
+            "random",
+            "--suri-file",
+            "keys/staging.suri",
+            "--args",
+            "1000"
+        ]
+    );
+
+    let steps = manifest.steps(Some("production")).unwrap();
+    assert_eq!(
+        steps[1].args[5..],
+        ["--salt", "random", "--args", "1000000"]
+    );
+
+    assert!(manifest.steps(None).is_err());
+    assert!(manifest.steps(Some("dev")).is_err());
+
+    let output = serde_json::json!({ "program_id": "Flip111" });
+    assert_eq!(
+        deployed_address("solana", &output),
+        Some("Flip111".to_string())
+    );
+}
@@ -0,0 +1,285 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    crate::{quiet::is_quiet, redact::redact},
+    anyhow::{anyhow, Result},
+    colored::{Color, Colorize},
+    serde::Serialize,
+    std::{
+        str::FromStr,
+        sync::{Arc, Mutex, RwLock},
+    },
+};
+
+/// The reporter the output of the commands is written through, set once from the command-line
+/// flags. Without it, the output is written for humans, or in quiet mode.
+static REPORTER: RwLock<Option<Arc<dyn Reporter>>> = RwLock::new(None);
+
+/// A piece of the output of a command, as written by the `print_*` macros.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Report {
+    /// A title, e.g. of a section of the result
+    Title { text: String },
+    /// A subtitle, within a section
+    Subtitle { text: String },
+    /// A field of the result
+    KeyValue { key: String, value: String },
+    /// A value of the result, e.g. an item of a list
+    Value { text: String },
+    /// A warning
+    Warning { text: String },
+    /// Text written as is, e.g. a result printed alone in quiet mode, or a table
+    Text { text: String },
+}
+
+impl Report {
+    /// The same report, with the registered secrets of its text redacted.
+    fn redacted(&self) -> Report {
+        match self {
+            Report::Title { text } => Report::Title { text: redact(text) },
+            Report::Subtitle { text } => Report::Subtitle { text: redact(text) },
+            Report::KeyValue { key, value } => Report::KeyValue {
+                key: redact(key),
+                value: redact(value),
+            },
+            Report::Value { text } => Report::Value { text: redact(text) },
+            Report::Warning { text } => Report::Warning { text: redact(text) },
+            Report::Text { text } => Report::Text { text: redact(text) },
+        }
+    }
+}
+
+/// The destination of the output of the commands.
+///
+/// The reports are redacted of the registered secrets (see
+/// [`register_secret`](crate::register_secret)) before being written by a reporter.
+pub trait Reporter: Send + Sync {
+    /// Write a report.
+    fn report(&self, report: &Report);
+}
+
+/// The colors of the output for humans.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Theme {
+    /// The color of the titles and subtitles
+    pub title: Color,
+    /// The color of the keys of the fields
+    pub key: Color,
+    /// The color of the warnings
+    pub warning: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            title: Color::Cyan,
+            key: Color::Green,
+            warning: Color::Yellow,
+        }
+    }
+}
+
+impl FromStr for Theme {
+    type Err = anyhow::Error;
+
+    /// Parse a theme: `default`, or the colors to change from the default theme, e.g.
+    /// `title=blue,key=magenta`.
+    fn from_str(s: &str) -> Result<Self> {
+        let mut theme = Theme::default();
+        if s.trim().is_empty() || s.trim() == "default" {
+            return Ok(theme);
+        }
+        for entry in s.split(',') {
+            let (element, color) = entry.split_once('=').ok_or_else(|| {
+                anyhow!(
+                    "Invalid theme entry: {}. Expected format: <ELEMENT>=<COLOR>",
+                    entry
+                )
+            })?;
+            let color = Color::from_str(color.trim())
+                .map_err(|_| anyhow!("Invalid color of the theme: {}", color.trim()))?;
+            match element.trim() {
+                "title" => theme.title = color,
+                "key" => theme.key = color,
+                "warning" => theme.warning = color,
+                element => {
+                    return Err(anyhow!(
+                        "Invalid element of the theme: {}. Supported elements: title, key, warning",
+                        element
+                    ))
+                }
+            }
+        }
+        Ok(theme)
+    }
+}
+
+/// The reporter of the output for humans, in color.
+#[derive(Clone, Debug, Default)]
+pub struct HumanReporter {
+    theme: Theme,
+}
+
+impl HumanReporter {
+    /// Create a reporter with the colors of a theme.
+    pub fn new(theme: Theme) -> Self {
+        HumanReporter { theme }
+    }
+}
+
+impl Reporter for HumanReporter {
+    fn report(&self, report: &Report) {
+        match report {
+            Report::Title { text } => {
+                println!("\n{}", text.bold().color(self.theme.title));
+            }
+            Report::Subtitle { text } => {
+                println!("\n  {}", text.bold().color(self.theme.title));
+            }
+            Report::KeyValue { key, value } => {
+                println!("    {:<15}: {}", key.bold().color(self.theme.key), value);
+            }
+            Report::Value { text } => println!("    {}", text),
+            Report::Warning { text } => println!(
+                "\n{} {}",
+                "Warning:".bold().color(self.theme.warning),
+                text.color(self.theme.warning)
+            ),
+            Report::Text { text } => println!("{}", text),
+        }
+    }
+}
+
+/// The reporter of the quiet mode: the titles and warnings are omitted, and the fields, values and
+/// texts are written without colors.
+#[derive(Clone, Debug, Default)]
+pub struct QuietReporter;
+
+impl Reporter for QuietReporter {
+    fn report(&self, report: &Report) {
+        match report {
+            Report::KeyValue { key, value } => println!("    {:<15}: {}", key, value),
+            Report::Value { text } => println!("    {}", text),
+            Report::Text { text } => println!("{}", text),
+            Report::Title { .. } | Report::Subtitle { .. } | Report::Warning { .. } => {}
+        }
+    }
+}
+
+/// The reporter writing each report as a line of JSON, e.g.
+/// `{"type":"key_value","key":"Signature","value":"..."}`.
+#[derive(Clone, Debug, Default)]
+pub struct JsonReporter;
+
+impl Reporter for JsonReporter {
+    fn report(&self, report: &Report) {
+        if let Ok(line) = serde_json::to_string(report) {
+            println!("{}", line);
+        }
+    }
+}
+
+/// The reporter keeping the reports in memory rather than writing them, e.g. to check the output
+/// of a command in a test.
+#[derive(Debug, Default)]
+pub struct CaptureReporter {
+    reports: Mutex<Vec<Report>>,
+}
+
+impl CaptureReporter {
+    /// The reports captured so far.
+    pub fn reports(&self) -> Vec<Report> {
+        self.reports.lock().unwrap().clone()
+    }
+}
+
+impl Reporter for CaptureReporter {
+    fn report(&self, report: &Report) {
+        self.reports.lock().unwrap().push(report.clone());
+    }
+}
+
+/// Set the reporter the output of the commands is written through.
+pub fn set_reporter(reporter: Arc<dyn Reporter>) {
+    *REPORTER.write().unwrap() = Some(reporter);
+}
+
+/// Write a report through the reporter set with [`set_reporter`], or for humans, or in quiet
+/// mode, by default. The registered secrets are redacted first.
+pub fn report(report: Report) {
+    let report = report.redacted();
+    match REPORTER.read().unwrap().as_ref() {
+        Some(reporter) => reporter.report(&report),
+        None if is_quiet() => QuietReporter.report(&report),
+        None => HumanReporter::default().report(&report),
+    }
+}
+
+/// A test for the `Theme` parsing
+#[test]
+fn test_theme() {
+    assert_eq!("default".parse::<Theme>().unwrap(), Theme::default());
+    let theme = "title=blue, key=bright magenta".parse::<Theme>().unwrap();
+    assert_eq!(theme.title, Color::Blue);
+    assert_eq!(theme.key, Color::BrightMagenta);
+    assert_eq!(theme.warning, Color::Yellow);
+
+    assert!("title".parse::<Theme>().is_err());
+    assert!("title=nocolor".parse::<Theme>().is_err());
+    assert!("border=red".parse::<Theme>().is_err());
+}
+
+/// A test for the `CaptureReporter` and the JSON serialization of the reports
+#[test]
+fn test_capture_reporter() {
+    let reporter = CaptureReporter::default();
+    reporter.report(&Report::Title {
+        text: "Result".to_string(),
+    });
+    reporter.report(&Report::KeyValue {
+        key: "Signature".to_string(),
+        value: "abc".to_string(),
+    });
+    assert_eq!(
+        reporter.reports(),
+        vec![
+            Report::Title {
+                text: "Result".to_string()
+            },
+            Report::KeyValue {
+                key: "Signature".to_string(),
+                value: "abc".to_string()
+            },
+        ]
+    );
+    assert_eq!(
+        serde_json::to_value(&reporter.reports()[1]).unwrap(),
+        serde_json::json!({ "type": "key_value", "key": "Signature", "value": "abc" })
+    );
+}
+
+/// A test for the redaction of the registered secrets in the reports, e.g. in the events or the
+/// result of a command printed alone in quiet mode
+#[test]
+fn test_report_redacted() {
+    let secret = "0x5fb92d6e98884f76de468fa3f6278f8807c48bebc13595d45af5bdc4da702133";
+    crate::register_secret(secret);
+    let reporter = Arc::new(CaptureReporter::default());
+    set_reporter(reporter.clone());
+
+    crate::print_text!(format!("Event Transfer {{ from: {} }}", secret));
+    crate::print_key_value!("Secret key", secret);
+
+    let reports = reporter.reports();
+    assert!(reports.contains(&Report::Text {
+        text: format!("Event Transfer {{ from: {} }}", crate::REDACTED)
+    }));
+    assert!(reports.contains(&Report::KeyValue {
+        key: "Secret key".to_string(),
+        value: crate::REDACTED.to_string()
+    }));
+    assert!(!reports
+        .iter()
+        .any(|report| format!("{:?}", report).contains(secret)));
+}
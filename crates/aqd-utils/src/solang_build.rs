@@ -0,0 +1,71 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    std::{
+        env,
+        path::{Path, PathBuf},
+        process::Command,
+    },
+};
+
+/// Find the root of the Solang project containing a directory: the closest ancestor with a
+/// `solang.toml` file.
+pub fn find_solang_project(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .find(|root| root.join("solang.toml").is_file())
+        .map(Path::to_path_buf)
+}
+
+/// Compile the Solang project containing the current directory, with `solang compile` and the
+/// configuration of its `solang.toml` file.
+///
+/// The compiler output is only shown if the compilation fails, so that the output of the command
+/// is unchanged.
+///
+/// # Errors
+///
+/// This function returns an error if the current directory is not in a Solang project, if the
+/// `solang` binary cannot be run, or if the compilation fails.
+pub fn solang_compile() -> Result<()> {
+    let current_dir =
+        env::current_dir().map_err(|e| anyhow!("Failed to get the current directory: {}", e))?;
+    let root = find_solang_project(&current_dir).ok_or_else(|| {
+        anyhow!("No solang.toml found in the current directory or its parents, nothing to build")
+    })?;
+
+    let output = Command::new("solang")
+        .arg("compile")
+        .current_dir(&root)
+        .output()
+        .map_err(|e| {
+            anyhow!(
+                "Failed to run solang: {}. Make sure it is installed and in your PATH",
+                e
+            )
+        })?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Compilation of {} failed:\n{}{}",
+            root.display(),
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    Ok(())
+}
+
+/// A test for the `find_solang_project` function
+#[test]
+fn test_find_solang_project() {
+    let root = env::temp_dir().join("aqd-test-solang-project");
+    let nested = root.join("contracts").join("flipper");
+    std::fs::create_dir_all(&nested).unwrap();
+    std::fs::write(root.join("solang.toml"), "[target]\nname = \"solana\"\n").unwrap();
+
+    assert_eq!(find_solang_project(&nested), Some(root.clone()));
+    assert_eq!(find_solang_project(&root), Some(root.clone()));
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
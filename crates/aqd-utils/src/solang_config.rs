@@ -0,0 +1,129 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    std::path::{Path, PathBuf},
+};
+
+/// The settings of a Solang project used by aqd, read from its `solang.toml` file.
+///
+/// Besides the sections of the Solang compiler, an optional `[provider]` section configures the
+/// cluster and the wallet used by the commands, as in `Anchor.toml`:
+///
+/// ```toml
+/// [provider]
+/// cluster = "devnet"
+/// wallet = "~/.config/solana/id.json"
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SolangConfig {
+    /// The name of the target (`solana`, `polkadot`, ...)
+    pub target: String,
+    /// The contracts compiled by the project
+    pub contracts: Vec<String>,
+    /// The output directory of the binary artifacts, relative to the project root
+    pub output_directory: Option<PathBuf>,
+    /// The output directory of the metadata (IDL files), relative to the project root
+    pub output_meta: Option<PathBuf>,
+    /// The cluster of the `[provider]` section (a moniker or a URL), if any
+    pub cluster: Option<String>,
+    /// The path of the wallet keypair of the `[provider]` section, if any
+    pub wallet: Option<String>,
+}
+
+impl SolangConfig {
+    /// Parse the settings from the TOML content of a `solang.toml` file.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the content is not valid TOML, or if the target name is
+    /// missing.
+    pub fn from_toml(content: &str) -> Result<Self> {
+        let parsed_toml: toml::Value = toml::from_str(content)?;
+        let string = |section: &str, key: &str| {
+            parsed_toml
+                .get(section)
+                .and_then(|section| section.get(key))
+                .and_then(|value| value.as_str())
+                .map(|value| value.to_string())
+        };
+
+        let target = string("target", "name")
+            .ok_or_else(|| anyhow!("Failed to get target name from solang.toml"))?;
+        let contracts = parsed_toml
+            .get("package")
+            .and_then(|package| package.get("contracts"))
+            .and_then(|contracts| contracts.as_array())
+            .map(|contracts| {
+                contracts
+                    .iter()
+                    .filter_map(|contract| contract.as_str())
+                    .map(|contract| contract.to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(SolangConfig {
+            target,
+            contracts,
+            output_directory: string("compiler-output", "output_directory").map(PathBuf::from),
+            output_meta: string("compiler-output", "output_meta").map(PathBuf::from),
+            cluster: string("provider", "cluster"),
+            wallet: string("provider", "wallet"),
+        })
+    }
+
+    /// The directory of the binary artifacts of the project: the output directory, or the project
+    /// root by default.
+    pub fn artifacts_dir(&self, root: &Path) -> PathBuf {
+        match &self.output_directory {
+            Some(dir) => root.join(dir),
+            None => root.to_path_buf(),
+        }
+    }
+
+    /// The directory of the metadata of the project: the metadata output directory, or the
+    /// directory of the binary artifacts by default.
+    pub fn metadata_dir(&self, root: &Path) -> PathBuf {
+        match &self.output_meta {
+            Some(dir) => root.join(dir),
+            None => self.artifacts_dir(root),
+        }
+    }
+}
+
+/// A test for the `SolangConfig::from_toml` function
+#[test]
+fn test_solang_config_from_toml() {
+    let config =
+        SolangConfig::from_toml(include_str!("../solang_config_examples/solana_config.toml"))
+            .unwrap();
+    assert_eq!(config.target, "solana");
+    assert_eq!(config.contracts, vec!["flipper".to_string()]);
+    assert_eq!(config.output_directory, None);
+    assert_eq!(config.cluster, None);
+
+    let root = Path::new("/project");
+    assert_eq!(config.metadata_dir(root), root);
+
+    let content = r#"
+        [target]
+        name = "solana"
+
+        [compiler-output]
+        output_directory = "build"
+        output_meta = "idl"
+
+        [provider]
+        cluster = "devnet"
+        wallet = "keys/id.json"
+    "#;
+    let config = SolangConfig::from_toml(content).unwrap();
+    assert_eq!(config.artifacts_dir(root), root.join("build"));
+    assert_eq!(config.metadata_dir(root), root.join("idl"));
+    assert_eq!(config.cluster.as_deref(), Some("devnet"));
+    assert_eq!(config.wallet.as_deref(), Some("keys/id.json"));
+
+    // The target name is mandatory
+    assert!(SolangConfig::from_toml("[package]\ncontracts = []").is_err());
+}
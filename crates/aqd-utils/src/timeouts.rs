@@ -0,0 +1,79 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use {
+    anyhow::{anyhow, Result},
+    std::{
+        sync::atomic::{AtomicU64, Ordering},
+        time::Duration,
+    },
+};
+
+/// The default timeout of an RPC request, in milliseconds, as in the Solana clients.
+const DEFAULT_RPC_TIMEOUT_MS: u64 = 30_000;
+
+/// The default timeout of the connection to a websocket endpoint, in milliseconds, as in the
+/// Polkadot client.
+const DEFAULT_CONNECT_TIMEOUT_MS: u64 = 10_000;
+
+/// The timeout of the RPC requests, in milliseconds, set once from the `--rpc-timeout` option.
+static RPC_TIMEOUT: AtomicU64 = AtomicU64::new(DEFAULT_RPC_TIMEOUT_MS);
+
+/// The timeout of the websocket connections, in milliseconds, set once from the
+/// `--connect-timeout` option.
+static CONNECT_TIMEOUT: AtomicU64 = AtomicU64::new(DEFAULT_CONNECT_TIMEOUT_MS);
+
+/// Set the timeouts of the network clients: the timeout of an RPC request, and the timeout of the
+/// connection to a websocket endpoint.
+pub fn set_timeouts(rpc_timeout: Duration, connect_timeout: Duration) {
+    RPC_TIMEOUT.store(rpc_timeout.as_millis() as u64, Ordering::Relaxed);
+    CONNECT_TIMEOUT.store(connect_timeout.as_millis() as u64, Ordering::Relaxed);
+}
+
+/// The timeout of an RPC request: the Solana HTTP requests, including their connection, and the
+/// Polkadot websocket requests.
+pub fn rpc_timeout() -> Duration {
+    Duration::from_millis(RPC_TIMEOUT.load(Ordering::Relaxed))
+}
+
+/// The timeout of the connection to a websocket endpoint: the Polkadot nodes and the Solana
+/// websocket subscriptions.
+pub fn connect_timeout() -> Duration {
+    Duration::from_millis(CONNECT_TIMEOUT.load(Ordering::Relaxed))
+}
+
+/// Parse the `--rpc-timeout` and `--connect-timeout` options: a positive duration in seconds, or
+/// with a unit among `ms`, `s` and `m` (e.g. `500ms`, `45s`, `2m`).
+pub fn parse_timeout(timeout: &str) -> Result<Duration> {
+    let invalid = || {
+        anyhow!(
+            "Invalid timeout: {}. Expected a positive duration in seconds, or with a unit (e.g. 500ms, 45s, 2m)",
+            timeout
+        )
+    };
+    let (value, millis_per_unit) = match timeout.trim() {
+        t if t.ends_with("ms") => (&t[..t.len() - 2], 1),
+        t if t.ends_with('s') => (&t[..t.len() - 1], 1_000),
+        t if t.ends_with('m') => (&t[..t.len() - 1], 60_000),
+        t => (t, 1_000),
+    };
+    match value.trim().parse::<u64>() {
+        Ok(value) if value > 0 => value
+            .checked_mul(millis_per_unit)
+            .map(Duration::from_millis)
+            .ok_or_else(invalid),
+        _ => Err(invalid()),
+    }
+}
+
+/// A test for the `parse_timeout` function
+#[test]
+fn test_parse_timeout() {
+    assert_eq!(parse_timeout("45").unwrap(), Duration::from_secs(45));
+    assert_eq!(parse_timeout("45s").unwrap(), Duration::from_secs(45));
+    assert_eq!(parse_timeout("500ms").unwrap(), Duration::from_millis(500));
+    assert_eq!(parse_timeout("2m").unwrap(), Duration::from_secs(120));
+    assert!(parse_timeout("0").is_err());
+    assert!(parse_timeout("-1s").is_err());
+    assert!(parse_timeout("1h").is_err());
+    assert!(parse_timeout("").is_err());
+}
@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use {
+    crate::{error::ErrorKind, quiet::is_quiet, solang_config::SolangConfig},
     anyhow::{anyhow, Result},
     std::{
         fs::File,
@@ -11,16 +12,31 @@ use {
 };
 
 /// Prompt the user to confirm transaction.
+///
+/// Declining the transaction is an error of kind [`ErrorKind::UserAbort`].
+///
+/// The scripts running aqd in quiet mode cannot answer the prompt, so the transaction is rejected
+/// in quiet mode unless the prompt is skipped explicitly.
 pub fn prompt_confirm_transaction<F: FnOnce()>(summary: F) -> Result<()> {
+    if is_quiet() {
+        return Err(ErrorKind::Usage.wrap(anyhow!(
+            "Transaction not submitted: the confirmation prompt is not available in quiet mode, use -y/--skip-confirm"
+        )));
+    }
     summary();
     println!("Are you sure you want to submit this transaction? (Y/n): ");
 
     let mut choice = String::new();
     io::stdout().flush()?;
-    io::stdin().read_line(&mut choice)?;
+    // The standard input may be closed, or already read, e.g. for an IDL piped with `--idl -`
+    if io::stdin().read_line(&mut choice)? == 0 {
+        return Err(ErrorKind::Usage.wrap(anyhow!(
+            "Transaction not submitted: no answer to the confirmation prompt on the standard input, use -y/--skip-confirm"
+        )));
+    }
     match choice.trim().to_lowercase().as_str() {
         "y" | "" => Ok(()),
-        "n" => Err(anyhow!("Transaction not submitted")),
+        "n" => Err(ErrorKind::UserAbort.wrap(anyhow!("Transaction not submitted"))),
         _ => Err(anyhow!("Invalid choice")),
     }
 }
@@ -68,16 +84,14 @@ pub fn check_target_match(target_name: &str, config_file_content: Option<String>
     };
 
     // Parse the TOML content and extract the target name
-    let parsed_toml: toml::Value = toml::from_str(&content).map_err(|err| {
-        anyhow::anyhow!(
-            "Failed to parse solang.toml file in the current directory: {}",
-            err
-        )
-    })?;
-    let config_target = parsed_toml["target"]["name"]
-        .as_str()
-        .ok_or_else(|| anyhow::anyhow!("Failed to get target name from solang.toml"))?
-        .to_string();
+    let config_target = SolangConfig::from_toml(&content)
+        .map_err(|err| {
+            anyhow::anyhow!(
+                "Failed to parse solang.toml file in the current directory: {}",
+                err
+            )
+        })?
+        .target;
 
     // Compare the target name with the provided argument
     if config_target != target_name {
@@ -91,6 +105,17 @@ pub fn check_target_match(target_name: &str, config_file_content: Option<String>
     Ok(true)
 }
 
+/// Parse the `--jobs` option: the number of deployments or calls run concurrently, at least 1.
+pub fn parse_jobs(jobs: &str) -> Result<usize> {
+    match jobs.parse::<usize>() {
+        Ok(jobs) if jobs > 0 => Ok(jobs),
+        _ => Err(anyhow!(
+            "Invalid number of jobs: {}. Expected a positive integer",
+            jobs
+        )),
+    }
+}
+
 /// A test for the `check_target_match` function
 #[test]
 fn test_check_target_match() {
@@ -112,3 +137,12 @@ fn test_check_target_match() {
     assert!(result.is_ok(), "Error: {:?}", result);
     assert!(!result.unwrap());
 }
+
+/// A test for the `parse_jobs` function
+#[test]
+fn test_parse_jobs() {
+    assert_eq!(parse_jobs("4").unwrap(), 4);
+    assert!(parse_jobs("0").is_err());
+    assert!(parse_jobs("-1").is_err());
+    assert!(parse_jobs("all").is_err());
+}
@@ -0,0 +1,26 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::Serialize;
+
+/// A key aqd signs with: a wallet labeled in the aqd configuration file, or the key the commands
+/// of a chain sign with by default.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct WalletKey {
+    /// The label of the wallet in the aqd configuration file, if labeled
+    pub name: Option<String>,
+    /// The chain of the key: `solana` or `polkadot`
+    pub chain: String,
+    /// The address of the key, if it can be read without unlocking the key
+    pub address: Option<String>,
+    /// Where the key is read from: a file, or an environment variable. The secret key itself is
+    /// never included.
+    pub source: Option<String>,
+    /// Whether the commands of the chain sign with this key by default
+    pub default: bool,
+    /// The balance of the address on the network of the chain, if queried
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub balance: Option<String>,
+    /// Why the address or the balance of the key could not be read
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}